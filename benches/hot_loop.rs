@@ -0,0 +1,86 @@
+// benches/hot_loop.rs
+//
+// 热循环基准：同一段脚本分别由字节码 VM 和树遍历解释器执行，比较耗时
+// 运行：cargo bench --bench hot_loop
+
+use pawc::PawScript;
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 5;
+
+const CASES: &[(&str, &str)] = &[
+    (
+        "while",
+        r#"
+let total: Int = 0
+let i: Int = 0
+loop i < 200000 {
+  total = (total + i * 3) % 1000003
+  i = i + 1
+}
+total
+"#,
+    ),
+    (
+        "range",
+        r#"
+let evens: Int = 0
+loop i in 0..200000 {
+  if i % 2 == 0 { evens = evens + 1 }
+}
+evens
+"#,
+    ),
+    (
+        "nested",
+        r#"
+let hits: Int = 0
+loop i in 0..400 {
+  loop j in 0..400 {
+    if (i + j) % 7 == 0 { hits = hits + 1 }
+  }
+}
+hits
+"#,
+    ),
+    (
+        "array",
+        r#"
+let xs: Array<Int> = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+let sum: Int = 0
+loop k in 0..20000 {
+  loop x in xs { sum = (sum + x * k) % 65521 }
+}
+sum
+"#,
+    ),
+];
+
+/// 取多次运行中最快的一次，减少噪声
+fn time(src: &str, vm: bool) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut paw = PawScript::new();
+            paw.set_vm(vm);
+            let start = Instant::now();
+            paw.eval_str(src).expect("benchmark script failed");
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    println!("{:<8} {:>12} {:>12} {:>8}", "case", "tree-walk", "vm", "speedup");
+    for (name, src) in CASES {
+        let tree = time(src, false);
+        let vm = time(src, true);
+        println!(
+            "{:<8} {:>10.2}ms {:>10.2}ms {:>7.1}x",
+            name,
+            tree.as_secs_f64() * 1000.0,
+            vm.as_secs_f64() * 1000.0,
+            tree.as_secs_f64() / vm.as_secs_f64()
+        );
+    }
+}
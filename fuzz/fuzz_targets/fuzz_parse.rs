@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pawc::error::error::PawError;
+
+fuzz_target!(|data: &[u8]| {
+    // 非法输入必须以 Syntax 错误结束，其它错误种类同样视为 bug
+    if let Err(err) = pawc::fuzz::fuzz_parse(data) {
+        assert!(matches!(err, PawError::Syntax { .. }), "{}", err);
+    }
+});
@@ -1,6 +1,10 @@
 // src/ast/expr.rs
 
+use std::fmt;
 use crate::ast::method::Method;
+use crate::ast::param::Param;
+use crate::ast::statement::Statement;
+use crate::ast::type_node::TypeNode;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ExprKind {
@@ -9,6 +13,20 @@ pub enum ExprKind {
     LiteralFloat(f32),
     LiteralDouble(f64),
     LiteralString(String),
+    /// 匿名函数 `fun(x: Int): Int { ... }`，求值为捕获当前环境的闭包
+    Lambda {
+        params: Vec<Param>,
+        return_type: Option<TypeNode>,
+        is_async: bool,
+        body: Vec<Statement>,
+    },
+    /// 字符串插值，按顺序拼接各部分（字面量部分为 LiteralString）
+    Interpolation(Vec<Expr>),
+    /// 插值中的 `${expr:spec}`：按格式说明输出 expr 的值
+    Formatted {
+        expr: Box<Expr>,
+        spec: FormatSpec,
+    },
     LiteralChar(char),
     LiteralBool(bool),
     LiteralNopaw,
@@ -39,10 +57,28 @@ pub enum ExprKind {
 
     Cast {
         expr: Box<Expr>,
-        ty: String,
+        ty: TypeNode,
+    },
+    /// `e is T`：运行时检查值是否属于类型 T，结果为 Bool
+    TypeTest {
+        expr: Box<Expr>,
+        ty: TypeNode,
     },
 
+    /// 整数范围 `start..end`、`start..=end`，可带 `step`，如 `10..0 step -2`
+    Range {
+        start: Box<Expr>,
+        end: Box<Expr>,
+        step: Option<Box<Expr>>,
+        inclusive: bool,
+    },
     ArrayLiteral(Vec<Expr>),
+    /// Map 字面量 {k: v, ...}
+    MapLiteral(Vec<(Expr, Expr)>),
+    /// Set 字面量 Set {a, b, ...}
+    SetLiteral(Vec<Expr>),
+    /// 元组字面量 (a, b, ...)，至少两个元素
+    TupleLiteral(Vec<Expr>),
     Index {
         array: Box<Expr>,
         index: Box<Expr>,
@@ -58,6 +94,16 @@ pub enum ExprKind {
     Await {
         expr: Box<Expr>,
     },
+    /// `e?`：`e` 为 `err(...)` 时把它从当前函数返回，否则取出 `ok(...)` 中的值
+    Propagate {
+        expr: Box<Expr>,
+    },
+    /// `tail e`：立即在后台开始执行 Future `e`，求值为等待其结果的 Future
+    Tail {
+        expr: Box<Expr>,
+    },
+    /// `async { ... }`，求值为一个 Future
+    AsyncBlock(Vec<Statement>),
 }
 
 /// 带位置的表达式
@@ -83,6 +129,8 @@ pub enum BinaryOp {
     Mul,
     Div,
     Mod,
+    /// `**`，右结合，优先级高于乘除
+    Pow,
     EqEq,
     NotEq,
     Lt,
@@ -91,5 +139,202 @@ pub enum BinaryOp {
     Ge,
     And,
     Or,
-    As,
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Pow => "**",
+            BinaryOp::EqEq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// 把表达式还原成近似源码的形式，用于诊断信息
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(items: &[Expr]) -> String {
+            items.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ")
+        }
+        // 嵌套的二元运算加括号，避免还原时丢失优先级
+        fn operand(e: &Expr) -> String {
+            match &e.kind {
+                ExprKind::BinaryOp { .. }
+                | ExprKind::Cast { .. }
+                | ExprKind::TypeTest { .. }
+                | ExprKind::Range { .. } => {
+                    format!("({})", e)
+                }
+                _ => e.to_string(),
+            }
+        }
+        match &self.kind {
+            ExprKind::LiteralInt(n) => write!(f, "{}", n),
+            ExprKind::LiteralLong(n) => write!(f, "{}L", n),
+            ExprKind::LiteralFloat(n) => write!(f, "{}", n),
+            ExprKind::LiteralDouble(n) => write!(f, "{}", n),
+            ExprKind::LiteralString(s) => write!(f, "{:?}", s),
+            ExprKind::LiteralChar(c) => write!(f, "{:?}", c),
+            ExprKind::LiteralBool(b) => write!(f, "{}", b),
+            ExprKind::LiteralNopaw => write!(f, "nopaw"),
+            ExprKind::Interpolation(_) => write!(f, "\"...\""),
+            ExprKind::Formatted { expr, spec } => write!(f, "{}:{}", operand(expr), spec),
+            ExprKind::Var(name) => write!(f, "{}", name),
+            ExprKind::UnaryOp { op, expr } => write!(f, "{}{}", op, operand(expr)),
+            ExprKind::BinaryOp { op, left, right } => {
+                write!(f, "{} {} {}", operand(left), op, operand(right))
+            }
+            ExprKind::Call { name, args } => write!(f, "{}({})", name, list(args)),
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => write!(f, "{}.{}({})", operand(receiver), method, list(args)),
+            ExprKind::Cast { expr, ty } => write!(f, "{} as {}", operand(expr), ty),
+            ExprKind::TypeTest { expr, ty } => write!(f, "{} is {}", operand(expr), ty),
+            ExprKind::Range {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                let dots = if *inclusive { "..=" } else { ".." };
+                write!(f, "{}{}{}", operand(start), dots, operand(end))?;
+                match step {
+                    Some(step) => write!(f, " step {}", operand(step)),
+                    None => Ok(()),
+                }
+            }
+            ExprKind::ArrayLiteral(items) => write!(f, "[{}]", list(items)),
+            ExprKind::MapLiteral(entries) => {
+                let parts: Vec<String> =
+                    entries.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+                write!(f, "{{{}}}", parts.join(", "))
+            }
+            ExprKind::SetLiteral(items) => write!(f, "Set {{{}}}", list(items)),
+            ExprKind::TupleLiteral(items) => write!(f, "({})", list(items)),
+            ExprKind::Index { array, index } => write!(f, "{}[{}]", operand(array), index),
+            ExprKind::FieldAccess { expr, field } => write!(f, "{}.{}", operand(expr), field),
+            ExprKind::RecordInit { name, fields } => {
+                let parts: Vec<String> =
+                    fields.iter().map(|(n, v)| format!("{}: {}", n, v)).collect();
+                write!(f, "{} {{ {} }}", name, parts.join(", "))
+            }
+            ExprKind::Await { expr } => write!(f, "await {}", operand(expr)),
+            ExprKind::Propagate { expr } => write!(f, "{}?", operand(expr)),
+            ExprKind::Tail { expr } => write!(f, "tail {}", operand(expr)),
+            ExprKind::AsyncBlock(_) => write!(f, "async {{ ... }}"),
+            ExprKind::Lambda { .. } => write!(f, "fun(...) {{ ... }}"),
+        }
+    }
+}
+
+/// 格式说明中的对齐方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatAlign {
+    Left,
+    Right,
+    Center,
+}
+
+/// `${value:spec}` 与 `format("{:spec}", value)` 共用的格式说明：
+/// `[[fill]align][+][0][width][.precision]`，如 `08.3`、`>10`、`*^7`、`+.2`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FormatSpec {
+    pub fill: Option<char>,
+    pub align: Option<FormatAlign>,
+    /// `+`：非负数也带正号
+    pub sign: bool,
+    /// `0`：在符号之后用 0 补足宽度
+    pub zero: bool,
+    pub width: usize,
+    /// 浮点数的小数位数，或字符串最多保留的字符数
+    pub precision: Option<usize>,
+    /// 原文，用于诊断信息
+    pub text: String,
+}
+
+impl FormatSpec {
+    /// 宽度与精度的上限，避免一个格式说明就生成巨大的字符串
+    pub const MAX_WIDTH: usize = 1024;
+
+    pub fn parse(text: &str) -> Result<FormatSpec, String> {
+        let align_of = |c: char| match c {
+            '<' => Some(FormatAlign::Left),
+            '>' => Some(FormatAlign::Right),
+            '^' => Some(FormatAlign::Center),
+            _ => None,
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let mut spec = FormatSpec {
+            text: text.to_string(),
+            ..FormatSpec::default()
+        };
+        let mut i = 0;
+        if let Some(align) = chars.get(1).and_then(|c| align_of(*c)) {
+            spec.fill = Some(chars[0]);
+            spec.align = Some(align);
+            i = 2;
+        } else if let Some(align) = chars.first().and_then(|c| align_of(*c)) {
+            spec.align = Some(align);
+            i = 1;
+        }
+        if chars.get(i) == Some(&'+') {
+            spec.sign = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            spec.zero = true;
+            i += 1;
+        }
+        let number = |i: &mut usize| -> Result<Option<usize>, String> {
+            let start = *i;
+            while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                *i += 1;
+            }
+            if *i == start {
+                return Ok(None);
+            }
+            let digits: String = chars[start..*i].iter().collect();
+            match digits.parse::<usize>() {
+                Ok(n) if n <= FormatSpec::MAX_WIDTH => Ok(Some(n)),
+                _ => Err(format!(
+                    "{} in format spec `{}` is larger than {}",
+                    digits,
+                    text,
+                    FormatSpec::MAX_WIDTH
+                )),
+            }
+        };
+        spec.width = number(&mut i)?.unwrap_or(0);
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            spec.precision = Some(
+                number(&mut i)?.ok_or_else(|| format!("Missing precision after `.` in format spec `{}`", text))?,
+            );
+        }
+        if i < chars.len() {
+            return Err(format!("Invalid format spec `{}`", text));
+        }
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for FormatSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
 }
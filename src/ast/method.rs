@@ -9,12 +9,43 @@ pub enum Method {
     StartsWith,
     EndsWith,
     Contains,
+    Split,
+    Replace,
+    Substring,
+    ToInt,
+    ToDouble,
     // Array methods
     Push,
     Pop,
     LengthArr,   // 避免跟 String.length 冲突
+    Map,
+    Filter,
+    Reduce,
+    Join,
+    Sort,
+    Reverse,
+    Slice,
+    ToSet,
+    // Map methods
+    Insert,
+    Remove,
+    ContainsKey,
+    Keys,
+    Values,
+    // Set methods（remove / contains / length 与上面共用）
+    Add,
+    Union,
+    Intersection,
+    Difference,
+    ToArray,
+    // Result methods
+    IsOk,
+    IsErr,
+    Unwrap,
+    UnwrapOr,
+    Error,
     // …根据需要再加…
-    Other,       // 用于模块成员调用或用户自定义
+    Other(String), // 用于模块成员调用或用户自定义，保存成员名
 }
 
 impl std::fmt::Display for Method {
@@ -27,10 +58,38 @@ impl std::fmt::Display for Method {
             Method::StartsWith => write!(f, "starts_with"),
             Method::EndsWith => write!(f, "ends_with"),
             Method::Contains => write!(f, "contains"),
+            Method::Split => write!(f, "split"),
+            Method::Replace => write!(f, "replace"),
+            Method::Substring => write!(f, "substring"),
+            Method::ToInt => write!(f, "to_int"),
+            Method::ToDouble => write!(f, "to_double"),
             Method::Push => write!(f, "push"),
             Method::Pop => write!(f, "pop"),
             Method::LengthArr => write!(f, "length"),
-            _ => write!(f, "{:?}", self),
+            Method::Map => write!(f, "map"),
+            Method::Filter => write!(f, "filter"),
+            Method::Reduce => write!(f, "reduce"),
+            Method::Join => write!(f, "join"),
+            Method::Sort => write!(f, "sort"),
+            Method::Reverse => write!(f, "reverse"),
+            Method::Slice => write!(f, "slice"),
+            Method::ToSet => write!(f, "to_set"),
+            Method::Insert => write!(f, "insert"),
+            Method::Remove => write!(f, "remove"),
+            Method::ContainsKey => write!(f, "contains_key"),
+            Method::Keys => write!(f, "keys"),
+            Method::Values => write!(f, "values"),
+            Method::Add => write!(f, "add"),
+            Method::Union => write!(f, "union"),
+            Method::Intersection => write!(f, "intersection"),
+            Method::Difference => write!(f, "difference"),
+            Method::ToArray => write!(f, "to_array"),
+            Method::IsOk => write!(f, "is_ok"),
+            Method::IsErr => write!(f, "is_err"),
+            Method::Unwrap => write!(f, "unwrap"),
+            Method::UnwrapOr => write!(f, "unwrap_or"),
+            Method::Error => write!(f, "error"),
+            Method::Other(name) => write!(f, "{}", name),
         }
     }
 }
@@ -45,10 +104,38 @@ impl Method {
             Method::StartsWith   => "starts_with",
             Method::EndsWith     => "ends_with",
             Method::Contains     => "contains",
+            Method::Split        => "split",
+            Method::Replace      => "replace",
+            Method::Substring    => "substring",
+            Method::ToInt        => "to_int",
+            Method::ToDouble     => "to_double",
             Method::Push         => "push",
             Method::Pop          => "pop",
             Method::LengthArr    => "length",
-            Method::Other        => "", // or panic! if you never use Other here
+            Method::Map          => "map",
+            Method::Filter       => "filter",
+            Method::Reduce       => "reduce",
+            Method::Join         => "join",
+            Method::Sort         => "sort",
+            Method::Reverse      => "reverse",
+            Method::Slice        => "slice",
+            Method::ToSet        => "to_set",
+            Method::Insert       => "insert",
+            Method::Remove       => "remove",
+            Method::ContainsKey  => "contains_key",
+            Method::Keys         => "keys",
+            Method::Values       => "values",
+            Method::Add          => "add",
+            Method::Union        => "union",
+            Method::Intersection => "intersection",
+            Method::Difference   => "difference",
+            Method::ToArray      => "to_array",
+            Method::IsOk         => "is_ok",
+            Method::IsErr        => "is_err",
+            Method::Unwrap       => "unwrap",
+            Method::UnwrapOr     => "unwrap_or",
+            Method::Error        => "error",
+            Method::Other(name)  => name,
         }
     }
 }
\ No newline at end of file
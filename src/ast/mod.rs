@@ -1,4 +1,6 @@
 pub mod statement;
 pub mod param;
+pub mod pattern;
 pub mod expr;
-pub mod method;
\ No newline at end of file
+pub mod method;
+pub mod type_node;
\ No newline at end of file
@@ -1,16 +1,27 @@
 // src/ast/param.rs
 
+use crate::ast::expr::Expr;
+use crate::ast::type_node::TypeNode;
+
 /// 函数参数
 #[derive(Debug, Clone, PartialEq)]
 pub struct Param {
     pub name: String,
-    pub ty: String,
+    pub ty: TypeNode,
+    /// 默认值：调用时省略该参数则在被调函数的环境中求值
+    pub default: Option<Expr>,
     pub line: usize,
     pub col: usize,
 }
 
 impl Param {
-    pub fn new(name: String, ty: String, line: usize, col: usize) -> Self {
-        Param { name, ty, line, col }
+    pub fn new(name: String, ty: TypeNode, line: usize, col: usize) -> Self {
+        Param {
+            name,
+            ty,
+            default: None,
+            line,
+            col,
+        }
     }
 }
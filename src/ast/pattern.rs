@@ -0,0 +1,133 @@
+// src/ast/pattern.rs
+
+use crate::ast::expr::{Expr, ExprKind};
+use crate::ast::statement::Statement;
+use crate::ast::type_node::{TypeNode, TypeNodeKind};
+use std::collections::HashMap;
+
+/// match 分支中的模式种类
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    /// `_`：匹配任意值，不绑定
+    Wildcard,
+    /// 字面量（数字可带负号）或 `nopaw`，与值相等时匹配
+    Literal(Expr),
+    /// `name`：匹配任意值并绑定到 name
+    Bind(String),
+    /// `name: T` / `_: T`：值属于类型 T 时匹配并绑定
+    Typed { name: String, ty: TypeNode },
+    /// `Name { a, b: pattern }`：是该 record 且各字段匹配；只写字段名时绑定同名变量
+    Record {
+        name: String,
+        fields: Vec<(String, Pattern)>,
+    },
+    /// `(p1, p2, ...)`：同样长度的元组，按位置匹配
+    Tuple(Vec<Pattern>),
+    /// `Shape.Circle(p)`：是该 enum 的该变体且数据按位置匹配；省略括号时不检查数据
+    Variant {
+        enum_name: String,
+        variant: String,
+        args: Option<Vec<Pattern>>,
+    },
+}
+
+/// 带位置的模式
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Pattern {
+    pub fn new(kind: PatternKind, line: usize, col: usize) -> Self {
+        Pattern { kind, line, col }
+    }
+
+    /// 是否匹配任何值（`_`、变量名、`_: Any` 之类）
+    pub fn is_catch_all(&self) -> bool {
+        match &self.kind {
+            PatternKind::Wildcard | PatternKind::Bind(_) => true,
+            PatternKind::Typed { ty, .. } => matches!(&ty.kind, TypeNodeKind::Name(name) if name == "Any"),
+            _ => false,
+        }
+    }
+
+    /// 模式中绑定的变量名，按出现顺序
+    pub fn bindings<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match &self.kind {
+            PatternKind::Bind(name) => out.push(name),
+            PatternKind::Typed { name, .. } if name != "_" => out.push(name),
+            PatternKind::Record { fields, .. } => fields.iter().for_each(|(_, p)| p.bindings(out)),
+            PatternKind::Tuple(items) | PatternKind::Variant { args: Some(items), .. } => {
+                items.iter().for_each(|p| p.bindings(out))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `p1, p2 => { ... }`：任一模式匹配时执行 body
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub patterns: Vec<Pattern>,
+    pub body: Vec<Statement>,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// 可以直接查表的字面量；Int 与 Long 字面量按数值归为同一种键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LiteralKey {
+    Integer(i64),
+    Bool(bool),
+    Char(char),
+    String(String),
+    Nopaw,
+}
+
+impl LiteralKey {
+    /// 字面量模式对应的键；浮点数相等带误差，不能查表
+    pub fn of(expr: &Expr) -> Option<LiteralKey> {
+        Some(match &expr.kind {
+            ExprKind::LiteralInt(n) => LiteralKey::Integer(*n as i64),
+            ExprKind::LiteralLong(n) => LiteralKey::Integer(*n),
+            ExprKind::LiteralBool(b) => LiteralKey::Bool(*b),
+            ExprKind::LiteralChar(c) => LiteralKey::Char(*c),
+            ExprKind::LiteralString(s) => LiteralKey::String(s.clone()),
+            ExprKind::LiteralNopaw => LiteralKey::Nopaw,
+            _ => return None,
+        })
+    }
+}
+
+/// 解析时为 match 预先建立的跳转表：开头只含可查表字面量的分支按键直接定位，
+/// 从 `linear_from` 开始的分支才需要逐个尝试
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MatchTable {
+    /// 键 → 第一个含该字面量的分支下标
+    pub keys: HashMap<LiteralKey, usize>,
+    pub linear_from: usize,
+}
+
+impl MatchTable {
+    pub fn new(arms: &[MatchArm]) -> Self {
+        let mut table = MatchTable::default();
+        for arm in arms {
+            let keys: Option<Vec<LiteralKey>> = arm
+                .patterns
+                .iter()
+                .map(|p| match &p.kind {
+                    PatternKind::Literal(lit) => LiteralKey::of(lit),
+                    _ => None,
+                })
+                .collect();
+            let Some(keys) = keys else { break };
+            for key in keys {
+                table.keys.entry(key).or_insert(table.linear_from);
+            }
+            table.linear_from += 1;
+        }
+        table
+    }
+}
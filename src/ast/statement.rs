@@ -2,29 +2,54 @@
 
 use crate::ast::expr::Expr;
 use crate::ast::param::Param;
+use crate::ast::pattern::{MatchArm, MatchTable};
+use crate::ast::type_node::TypeNode;
 
 /// 语句种类
 #[derive(Debug, Clone, PartialEq)]
 pub enum StatementKind {
+    /// `let x: T = value`；省略类型时（`let x = value`）由检查器按值推断
     Let {
         name: String,
-        ty: String,
+        ty: Option<TypeNode>,
+        value: Expr,
+    },
+    /// `let (a, b) = value` 或 `let (a, b): (A, B) = value`，按位置解构元组；`_` 忽略该位置
+    LetTuple {
+        names: Vec<String>,
+        ty: Option<TypeNode>,
         value: Expr,
     },
     Say(Expr),
+    /// `say_debug expr`：按 `inspect(expr)` 的格式输出，带类型与缩进，供调试使用
+    SayDebug(Expr),
     Assign {
         name: String,
         value: Expr,
     },
+    /// `target[index] = value`，target 可以是变量或嵌套的索引
+    AssignIndex {
+        array: Expr,
+        index: Expr,
+        value: Expr,
+    },
+    /// `target.field = value`
+    AssignField {
+        object: Expr,
+        field: String,
+        value: Expr,
+    },
     Ask {
         name: String,
-        ty: String,
+        ty: TypeNode,
         prompt: String,
     },
     AskPrompt(String),
     Return(Option<Expr>),
-    Break,
-    Continue,
+    /// `break` 或 `break label`：跳出最近一层或带该标签的循环
+    Break(Option<String>),
+    /// `continue` 或 `continue label`
+    Continue(Option<String>),
     Expr(Expr),
 
     If {
@@ -32,60 +57,179 @@ pub enum StatementKind {
         body: Vec<Statement>,
         else_branch: Option<Box<Statement>>,
     },
-    LoopForever(Vec<Statement>),
+    /// 各种循环的 `label` 来自 `loop outer: ...`，供 `break outer` / `continue outer` 指明目标
+    LoopForever {
+        label: Option<String>,
+        body: Vec<Statement>,
+    },
     LoopWhile {
+        label: Option<String>,
         condition: Expr,
         body: Vec<Statement>,
     },
+    /// `loop { ... } while cond`：先执行一次循环体，之后每轮结束时检查条件
+    LoopDoWhile {
+        label: Option<String>,
+        body: Vec<Statement>,
+        condition: Expr,
+    },
+    /// `loop i in start..end [step s] { ... }`，`inclusive` 表示 `..=`
     LoopRange {
+        label: Option<String>,
         var: String,
         start: Expr,
         end: Expr,
+        step: Option<Expr>,
+        inclusive: bool,
         body: Vec<Statement>,
     },
+    /// `loop x in xs { ... }`：遍历 Array、Set、Range、Map（键值对）或 String（字符）
     LoopArray {
-        var: String,
+        label: Option<String>,
+        var: LoopVar,
         array: Expr,
         body: Vec<Statement>,
     },
 
+    /// `match value { pattern => { ... } ... }`，执行第一个匹配的分支；`table` 由解析器根据字面量分支建立
+    Match {
+        subject: Expr,
+        arms: Vec<MatchArm>,
+        table: MatchTable,
+    },
+
     FunDecl {
         name: String,
+        /// 类型参数，如 `fun first<T>(xs: Array<T>): T` 中的 `T`；非泛型函数为空
+        type_params: Vec<String>,
         params: Vec<Param>,
         is_async: bool,
-        return_type: Option<String>,
+        return_type: Option<TypeNode>,
+        /// 签名后写了 `barks`：调用方要么在 sniff 中调用，要么自己也声明 `barks`
+        barks: bool,
         body: Vec<Statement>,
     },
     Block(Vec<Statement>),
 
     Throw(Expr),
+    /// `sniff { } snatch (e: T) { } ... snatch (e) { } lastly { }`，按顺序匹配 snatch 子句
     TryCatchFinally {
         body: Vec<Statement>,
-        err_name: String,
-        handler: Vec<Statement>,
+        clauses: Vec<SnatchClause>,
         finally: Vec<Statement>,
     },
 
+    /// `task_group { ... }`：等待块内所有 spawn 的任务结束
+    TaskGroup(Vec<Statement>),
+    /// `spawn expr`：把 Future 交给外层 task_group
+    Spawn(Expr),
+
     Import {
         module: Vec<String>,
         alias: String,
     },
+    /// `record Name: P1, P2 { ... }`，冒号后列出要遵循的 protocol
     RecordDecl {
         name: String,
+        /// 类型参数，如 `record Box<T> { value: T }` 中的 `T`；非泛型 record 为空
+        type_params: Vec<String>,
         fields: Vec<Param>,
+        protocols: Vec<String>,
+        /// 方法，每个都是 FunDecl，第一个参数是隐含的 `self: Name`（泛型 record 为 `self: Name<T>`）
+        methods: Vec<Statement>,
     },
+    /// `enum Name { A(T1, T2), B }`：取值为其中一个变体的和类型，变体可以带按位置排列的数据
+    EnumDecl {
+        name: String,
+        variants: Vec<EnumVariant>,
+    },
+    /// `protocol Name { field: Type  fun method(a: T): R }`，
+    /// 方法要求按函数类型的字段存放，如 `area: fun(): Double`
+    ProtocolDecl {
+        name: String,
+        members: Vec<Param>,
+    },
+}
+
+/// `loop ... in` 的循环变量
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoopVar {
+    Name(String),
+    /// `loop (k, v) in m`：按位置解构元组元素；`_` 忽略该位置
+    Tuple(Vec<String>),
+}
+
+/// 一个 `snatch` 子句
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnatchClause {
+    /// 绑定错误的变量名
+    pub name: String,
+    /// `snatch (e: T)` 只捕获 bark 出的 T 类型 record；为 None 时捕获所有错误，并把错误信息绑定为 String
+    pub ty: Option<TypeNode>,
+    pub body: Vec<Statement>,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// enum 的一个变体：`Circle(Float)` 或不带数据的 `Empty`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: String,
+    pub payload: Vec<TypeNode>,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// 声明上的属性，如 `@deprecated("use bar instead")`；`@cfg` 在解析时处理，不会留在语法树中
+#[derive(Debug, Clone, PartialEq)]
+pub enum Attribute {
+    /// 已弃用，使用处给出警告；可以附带说明
+    Deprecated(Option<String>),
+    /// `@pure`：函数没有副作用，由检查器验证，解释器可以缓存它的结果
+    Pure,
 }
 
 /// 带位置的语句
 #[derive(Debug, Clone, PartialEq)]
 pub struct Statement {
     pub kind: StatementKind,
+    pub attributes: Vec<Attribute>,
     pub line: usize,
     pub col: usize,
 }
 
 impl Statement {
     pub fn new(kind: StatementKind, line: usize, col: usize) -> Self {
-        Statement { kind, line, col }
+        Statement {
+            kind,
+            attributes: Vec::new(),
+            line,
+            col,
+        }
+    }
+
+    /// `@deprecated` 的说明；没有该属性时为 None，不带说明时为 Some(None)
+    pub fn deprecation(&self) -> Option<Option<&str>> {
+        self.attributes.iter().find_map(|attr| match attr {
+            Attribute::Deprecated(note) => Some(note.as_deref()),
+            Attribute::Pure => None,
+        })
+    }
+
+    /// 是否标注了 `@pure`
+    pub fn is_pure(&self) -> bool {
+        self.attributes.contains(&Attribute::Pure)
+    }
+
+    /// 声明语句引入的名字：fun、单个名字的 let、record、enum 与 protocol
+    pub fn declared_name(&self) -> Option<&str> {
+        match &self.kind {
+            StatementKind::Let { name, .. }
+            | StatementKind::FunDecl { name, .. }
+            | StatementKind::RecordDecl { name, .. }
+            | StatementKind::EnumDecl { name, .. }
+            | StatementKind::ProtocolDecl { name, .. } => Some(name),
+            _ => None,
+        }
     }
 }
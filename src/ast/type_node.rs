@@ -0,0 +1,53 @@
+// src/ast/type_node.rs
+
+use std::fmt;
+
+/// 源码中的类型注解，保留位置，由类型检查器按作用域解析为 PawType
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeNode {
+    pub kind: TypeNodeKind,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// 类型注解的种类
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeNodeKind {
+    /// 类型名：内置类型、record 或 protocol
+    Name(String),
+    /// 带类型参数的泛型，如 `Array<T>`、`Map<K, V>`、`Future<T>`
+    Generic { name: String, args: Vec<TypeNode> },
+    /// 可选类型 `T?`
+    Optional(Box<TypeNode>),
+    /// 函数类型 `fun(A, B): R`，省略返回类型时为 Void
+    Function { params: Vec<TypeNode>, ret: Box<TypeNode> },
+    /// 元组类型 `(A, B, ...)`
+    Tuple(Vec<TypeNode>),
+}
+
+impl TypeNode {
+    pub fn new(kind: TypeNodeKind, line: usize, col: usize) -> Self {
+        TypeNode { kind, line, col }
+    }
+
+    /// 构造一个类型名节点
+    pub fn name(name: &str, line: usize, col: usize) -> Self {
+        TypeNode::new(TypeNodeKind::Name(name.to_string()), line, col)
+    }
+}
+
+/// 还原成源码写法，如 `Map<String, Int?>`、`fun(Int): Void`
+impl fmt::Display for TypeNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(items: &[TypeNode]) -> String {
+            items.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")
+        }
+        match &self.kind {
+            TypeNodeKind::Name(name) => write!(f, "{}", name),
+            TypeNodeKind::Generic { name, args } => write!(f, "{}<{}>", name, list(args)),
+            TypeNodeKind::Optional(inner) => write!(f, "{}?", inner),
+            TypeNodeKind::Function { params, ret } => write!(f, "fun({}): {}", list(params), ret),
+            TypeNodeKind::Tuple(elems) => write!(f, "({})", list(elems)),
+        }
+    }
+}
@@ -1,11 +1,31 @@
 // src/cli/cli.rs
 
-use crate::interpreter::interpreter::Engine;
+use crate::interpreter::interpreter::{Counters, Engine, SizeLimits, Stats};
+use crate::parser::cfg::Cfg;
 use crate::parser::parser::Parser as PawParser;
-use crate::{error::error::PawError, interpreter::env::Env, interpreter::interpreter::Interpreter, lexer::lexer::Lexer, semantic::type_checker::TypeChecker, STACK_SIZE};
-use clap::Parser;
-use std::path::PathBuf;
+use crate::error::panic::install_hook;
+use crate::runtime::block_on;
+use crate::session::Session;
+use crate::{error::error::PawError, interpreter::env::Env, interpreter::interpreter::Interpreter, interpreter::value::ValueInner, lexer::lexer::Lexer, semantic::type_checker::TypeChecker, STACK_SIZE};
+use crate::fuzz::{parse_corpus, CorpusOutcome};
+use crate::highlight::{highlight, Format};
+use crate::playground::{json_string, serve, PlaygroundConfig};
+use crate::runtime::memory::set_memory_limit;
+use crate::interpreter::host::Host;
+use crate::interpreter::numbers::parse_basic;
+use crate::interpreter::value::{set_float_precision, Value};
+use crate::lexer::token::TokenKind;
+use crate::semantic::graph::import_graph;
+use crate::semantic::imports::check_imports;
+use crate::semantic::lint::{lint_program, Rule, RuleSet};
+use crate::semantic::types::PawType;
+use crate::testing::{find_test_files, run_test_file, TestOptions};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// 🐾 PawScript interpreter — execute .paw scripts
 #[derive(Parser, Debug)]
@@ -15,62 +35,822 @@ use std::fs;
     author = "Kinleoapple",
     about = "🐾 PawScript interpreter — execute .paw scripts"
 )]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Path to the .paw script to run
-    #[arg(value_name = "SCRIPT", required = true)]
-    script: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// 栈大小（MiB），默认 1
+    /// Path to the .paw script to run (`-` reads it from stdin); omit to start the interactive REPL
+    #[arg(value_name = "SCRIPT")]
+    script: Option<PathBuf>,
+
+    /// 执行线程的栈大小（MiB），默认 1
     #[arg(long, default_value = "1")]
     pub stack_size: usize, // MiB
+
+    /// 关闭字节码 VM，所有循环都由树遍历解释器执行
+    #[arg(long)]
+    pub no_vm: bool,
+
+    /// 沙箱模式：禁止导入模块和读取控制台输入
+    #[arg(long)]
+    pub sandbox: bool,
+
+    /// 沙箱模式下字符串的最大字节数，超出时报 E6020
+    #[arg(long, value_name = "BYTES", requires = "sandbox")]
+    pub max_string_bytes: Option<usize>,
+
+    /// 沙箱模式下数组、Map 与 Set 的最大元素数，超出时报 E6020
+    #[arg(long, value_name = "N", requires = "sandbox")]
+    pub max_collection_len: Option<usize>,
+
+    /// 进程可分配内存的上限（MiB），超出时终止执行
+    #[arg(long, value_name = "MIB")]
+    pub max_memory: Option<usize>,
+
+    /// 确定性模式：固定时钟与随机数种子，禁止导入 `term` 与 `signal`，同样的输入每次得到同样的输出
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// 确定性模式下 `time.now_millis()` 返回的时间（自 Unix 纪元起的毫秒数），默认 0
+    #[arg(long, value_name = "MILLIS", requires = "deterministic")]
+    pub epoch: Option<i64>,
+
+    /// 确定性模式下随机数的种子，默认 0
+    #[arg(long, value_name = "N", requires = "deterministic")]
+    pub seed: Option<u64>,
+
+    /// 运行结束后在 stderr 输出各阶段耗时与加载的模块数
+    #[arg(long)]
+    pub timings: bool,
+
+    /// Float / Double 输出的小数位数，默认按最短形式输出
+    #[arg(long, value_name = "DIGITS")]
+    pub float_precision: Option<usize>,
+
+    /// 预先定义一个全局变量，可重复使用；默认为 String，写 `NAME:Int=8080` 指定基础类型
+    #[arg(long = "define", short = 'D', value_name = "NAME=VALUE", value_parser = parse_define)]
+    pub defines: Vec<Define>,
+
+    /// 打开一个 `@cfg("NAME")` 开关，可重复使用；当前平台的开关（如 `linux`、`unix`）默认打开
+    #[arg(long = "cfg", value_name = "NAME")]
+    pub cfg: Vec<String>,
+
+    /// 运行前类型检查被导入模块的层数，默认检查整个导入图；0 只检查脚本本身
+    #[arg(long, value_name = "DEPTH")]
+    pub check_imports: Option<usize>,
+}
+
+/// `--define` 注入的全局变量
+#[derive(Clone, Debug)]
+struct Define {
+    name: String,
+    ty: PawType,
+    value: Value,
+}
+
+/// 解析 `NAME=VALUE` 或 `NAME:Type=VALUE`，值按类型转换，规则与 `ask` 相同
+fn parse_define(arg: &str) -> Result<Define, String> {
+    let (target, text) = arg
+        .split_once('=')
+        .ok_or("expected NAME=VALUE or NAME:Type=VALUE")?;
+    let (name, ty) = match target.split_once(':') {
+        Some((name, ty)) => (name.trim(), ty.trim()),
+        None => (target.trim(), "String"),
+    };
+    let tokens = Lexer::new(name).tokenize();
+    if !matches!(
+        tokens.iter().map(|t| &t.kind).collect::<Vec<_>>().as_slice(),
+        [TokenKind::Identifier(_), TokenKind::Eof]
+    ) {
+        return Err(format!("'{}' is not a valid variable name", name));
+    }
+    if !matches!(ty, "Int" | "Long" | "Float" | "Double" | "Bool" | "Char" | "String") {
+        return Err(format!(
+            "unsupported type '{}'; expected Int, Long, Float, Double, Bool, Char or String",
+            ty
+        ));
+    }
+    Ok(Define {
+        name: name.to_string(),
+        ty: PawType::parse(ty),
+        value: parse_basic(text, ty)?,
+    })
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Type-check a script and the modules it imports without running it
+    Check {
+        /// Path to the .paw script to check
+        #[arg(value_name = "SCRIPT")]
+        script: PathBuf,
+
+        /// 检查被导入模块的层数，默认检查整个导入图；0 只检查脚本本身
+        #[arg(long, value_name = "DEPTH")]
+        check_imports: Option<usize>,
+
+        /// 打开一个 `@cfg("NAME")` 开关，可重复使用
+        #[arg(long = "cfg", value_name = "NAME")]
+        cfg: Vec<String>,
+    },
+    /// Run the static analyses on scripts without running them
+    Lint {
+        /// Paths to the .paw scripts to lint
+        #[arg(value_name = "SCRIPT", required = true)]
+        scripts: Vec<PathBuf>,
+
+        /// 启用的规则或规则集，逗号分隔；规则集有 all、default（除 shadowing 外的全部规则）、checker、flow
+        #[arg(long, value_name = "RULES", default_value = "default", value_parser = RuleSet::parse)]
+        rules: RuleSet,
+
+        /// 关闭规则或规则集，可重复使用
+        #[arg(long, value_name = "RULES", value_parser = RuleSet::parse)]
+        allow: Vec<RuleSet>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: LintFormat,
+
+        /// 打开一个 `@cfg("NAME")` 开关，可重复使用
+        #[arg(long = "cfg", value_name = "NAME")]
+        cfg: Vec<String>,
+    },
+    /// Print the module import graph of a script as DOT or JSON
+    Graph {
+        /// Path to the entry .paw script
+        #[arg(value_name = "SCRIPT")]
+        script: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+
+        /// 打开一个 `@cfg("NAME")` 开关，可重复使用
+        #[arg(long = "cfg", value_name = "NAME")]
+        cfg: Vec<String>,
+    },
+    /// Run the `test_*` functions of test scripts
+    Test {
+        /// Test scripts, or directories searched recursively for `*_test.paw`
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// 用新值覆盖与之不符的快照，而不是判为失败
+        #[arg(long)]
+        update_snapshots: bool,
+
+        /// 固定 `random` 与 `forall` 的随机数种子，让每次运行得到相同的结果
+        #[arg(long, value_name = "N")]
+        seed: Option<u64>,
+
+        /// 打开一个 `@cfg("NAME")` 开关，可重复使用
+        #[arg(long = "cfg", value_name = "NAME")]
+        cfg: Vec<String>,
+    },
+    /// Print a script with syntax highlighting
+    Highlight {
+        /// Path to the .paw script to highlight
+        #[arg(value_name = "SCRIPT")]
+        script: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "ansi")]
+        format: HighlightFormat,
+    },
+    /// Parse every file under a directory (e.g. a fuzz corpus) and report crashes
+    ParseCorpus {
+        /// Directory to scan recursively
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+
+        /// 同时列出每个被拒绝文件的语法错误
+        #[arg(long)]
+        show_errors: bool,
+    },
+    /// Serve a JSON API that runs submitted scripts in sandbox mode
+    ServePlayground {
+        /// 监听的地址
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// 监听的端口，0 表示由系统分配空闲端口
+        #[arg(long, default_value = "8080")]
+        port: u16,
+
+        /// 单次执行的时间上限（毫秒）
+        #[arg(long, default_value = "5000")]
+        timeout_ms: u64,
+
+        /// 单次执行的内存上限（MiB）
+        #[arg(long, default_value = "128")]
+        max_memory: usize,
+
+        /// stdout、stderr 各自保留的最大长度（KiB）
+        #[arg(long, default_value = "64")]
+        max_output: usize,
+
+        /// 源码的最大长度（KiB）
+        #[arg(long, default_value = "64")]
+        max_source: usize,
+
+        /// 同时处理的请求数上限，更多的请求排队等待
+        #[arg(long, default_value = "4")]
+        workers: usize,
+
+        /// 接收一个请求的时间上限（毫秒），超时的连接以 408 关闭
+        #[arg(long, default_value = "10000")]
+        request_timeout_ms: u64,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum HighlightFormat {
+    Ansi,
+    Html,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum LintFormat {
+    Text,
+    Json,
 }
 
-pub(crate) async fn run() {
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+/// 解析命令行参数，在按 `--stack-size` 设定栈大小的线程上运行脚本或 REPL
+pub fn run() {
     let args = Args::parse();
+    install_hook();
+    match &args.command {
+        Some(Command::Check {
+            script,
+            check_imports,
+            cfg,
+        }) => {
+            if !check_report(script, *check_imports, &cfg_flags(cfg)) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Lint {
+            scripts,
+            rules,
+            allow,
+            format,
+            cfg,
+        }) => {
+            let rules = allow.iter().fold(rules.clone(), |rules, allowed| rules.without(allowed));
+            if !lint_report(scripts, &rules, *format, &cfg_flags(cfg)) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Graph { script, format, cfg }) => {
+            if !graph_report(script, *format, &cfg_flags(cfg)) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Test {
+            paths,
+            update_snapshots,
+            seed,
+            cfg,
+        }) => {
+            let options = TestOptions {
+                cfg: cfg_flags(cfg),
+                update_snapshots: *update_snapshots,
+                seed: *seed,
+            };
+            if !test_report(paths, &options) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Highlight { script, format }) => {
+            let format = match format {
+                HighlightFormat::Ansi => Format::Ansi,
+                HighlightFormat::Html => Format::Html,
+            };
+            match read_script(script) {
+                Ok(src) => print!("{}", highlight(&src, format)),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::ParseCorpus { dir, show_errors }) => {
+            if !parse_corpus_report(dir, *show_errors) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::ServePlayground {
+            host,
+            port,
+            timeout_ms,
+            max_memory,
+            max_output,
+            max_source,
+            workers,
+            request_timeout_ms,
+        }) => {
+            let config = PlaygroundConfig {
+                addr: format!("{}:{}", host, port),
+                exe: std::env::current_exe().unwrap_or_else(|_| PathBuf::from("pawc")),
+                timeout: Duration::from_millis(*timeout_ms),
+                max_memory: *max_memory,
+                max_output: max_output * 1024,
+                max_source: max_source * 1024,
+                workers: *workers,
+                request_timeout: Duration::from_millis(*request_timeout_ms),
+            };
+            if let Err(e) = serve(config) {
+                eprintln!("Failed to start the playground server: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+    if let Some(mib) = args.max_memory {
+        set_memory_limit(mib * 1024 * 1024);
+    }
+    set_float_precision(args.float_precision);
     STACK_SIZE.set(args.stack_size).ok();
-    
-    if let Err(err) = run_script(&args.script).await {
+    let stack_size_bytes = args.stack_size * 1024 * 1024;
+
+    let result = block_on(|| execute(args), stack_size_bytes);
+    if let Err(err) = result {
         eprintln!("{}", err);
         std::process::exit(1);
     }
 }
 
+async fn execute(args: Args) {
+    let Some(script) = &args.script else {
+        repl(!args.no_vm, &args.defines, &cfg_flags(&args.cfg)).await;
+        return;
+    };
+    let mut timings = Timings::default();
+    let result = run_script(script, &args, &mut timings).await;
+    if args.timings {
+        eprint!("{}", timings);
+    }
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+/// `--timings` 记录的各阶段耗时；未执行到的阶段为 None
+#[derive(Default)]
+struct Timings {
+    lex: Option<Duration>,
+    parse: Option<Duration>,
+    check: Option<Duration>,
+    execute: Option<Duration>,
+    /// 执行期间从文件加载的模块数，模块的分析与执行耗时计入 execute
+    modules: usize,
+    /// 执行阶段的计数，未执行到时为 None
+    stats: Option<Stats>,
+}
+
+impl std::fmt::Display for Timings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "🐾 Timings 🐾")?;
+        let phases = [
+            ("lex", self.lex),
+            ("parse", self.parse),
+            ("check", self.check),
+            ("execute", self.execute),
+        ];
+        let mut total = Duration::ZERO;
+        for (name, elapsed) in phases {
+            if let Some(elapsed) = elapsed {
+                writeln!(f, "   {:<9}{:>10.3} ms", name, elapsed.as_secs_f64() * 1000.0)?;
+                total += elapsed;
+            }
+        }
+        writeln!(f, "   {:<9}{:>10.3} ms", "total", total.as_secs_f64() * 1000.0)?;
+        writeln!(f, "   {:<9}{:>7} loaded (timed as part of execute)", "modules", self.modules)?;
+        if let Some(stats) = self.stats {
+            writeln!(f, "   {:<9}{:>10}", "stmts", stats.statements)?;
+            writeln!(f, "   {:<9}{:>10}", "vm ops", stats.instructions)?;
+            writeln!(f, "   {:<9}{:>10}", "calls", stats.calls)?;
+            writeln!(f, "   {:<9}{:>10}", "allocs", stats.allocations)?;
+        }
+        Ok(())
+    }
+}
+
+/// 计时执行一个阶段
+fn timed<T>(slot: &mut Option<Duration>, phase: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let out = phase();
+    *slot = Some(start.elapsed());
+    out
+}
+
+/// 解析语料库并输出汇总；有文件导致 panic 或目录无法读取时返回 false
+fn parse_corpus_report(dir: &Path, show_errors: bool) -> bool {
+    let results = match parse_corpus(dir) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Failed to read corpus '{}': {}", dir.display(), e);
+            return false;
+        }
+    };
+    let (mut parsed, mut rejected, mut panicked) = (0, 0, 0);
+    for (path, outcome) in &results {
+        match outcome {
+            CorpusOutcome::Parsed => parsed += 1,
+            CorpusOutcome::Rejected(err) => {
+                rejected += 1;
+                if show_errors {
+                    println!("rejected  {}: [{}] {}", path.display(), err.code(), err.message());
+                }
+            }
+            CorpusOutcome::Panicked(reason) => {
+                panicked += 1;
+                println!("PANICKED  {}: {}", path.display(), reason);
+            }
+        }
+    }
+    println!(
+        "🐾 {} file(s): {} parsed, {} rejected with syntax errors, {} panicked",
+        results.len(),
+        parsed,
+        rejected,
+        panicked
+    );
+    panicked == 0
+}
+
+/// 当前平台的开关加上 `--cfg` 打开的开关
+fn cfg_flags(flags: &[String]) -> Cfg {
+    let mut cfg = Cfg::new();
+    for flag in flags {
+        cfg.enable(flag);
+    }
+    cfg
+}
+
+/// `pawc check`：检查脚本及其导入图，逐条输出诊断与汇总；有错误时返回 false
+fn check_report(script: &Path, depth: Option<usize>, cfg: &Cfg) -> bool {
+    let file = script.to_string_lossy();
+    let checked = read_script(script).and_then(|src| {
+        let tokens = Lexer::new(&src).tokenize();
+        let ast = PawParser::new(tokens, &src, &file).with_cfg(cfg).parse_program()?;
+        let mut tc = TypeChecker::new(&file);
+        let result = tc.check_program(&ast);
+        Ok((ast, result, tc.warnings()))
+    });
+    let (mut errors, mut warnings, mut files) = (Vec::new(), Vec::new(), 1);
+    match checked {
+        Ok((ast, result, entry_warnings)) => {
+            errors.extend(result.err().map(|e| *e));
+            warnings.extend(entry_warnings);
+            let imports = check_imports(&file, &ast, depth, cfg);
+            files += imports.modules;
+            errors.extend(imports.errors);
+            warnings.extend(imports.warnings);
+        }
+        Err(err) => errors.push(*err),
+    }
+    for diagnostic in warnings.iter().chain(&errors) {
+        eprintln!("{}", diagnostic);
+    }
+    println!(
+        "🐾 Checked {} file(s): {} error(s), {} warning(s)",
+        files,
+        errors.len(),
+        warnings.len()
+    );
+    errors.is_empty()
+}
+
+/// `pawc lint`：逐个文件解析、类型检查并运行选中的分析，不执行脚本也不检查导入的模块；
+/// 有任何诊断时返回 false
+fn lint_report(scripts: &[PathBuf], rules: &RuleSet, format: LintFormat, cfg: &Cfg) -> bool {
+    let mut diagnostics = Vec::new();
+    for script in scripts {
+        let file = script.to_string_lossy();
+        let parsed = read_script(script).and_then(|src| {
+            let tokens = Lexer::new(&src).tokenize();
+            PawParser::new(tokens, &src, &file).with_cfg(cfg).parse_program()
+        });
+        let ast = match parsed {
+            Ok(ast) => ast,
+            Err(err) => {
+                diagnostics.push(*err);
+                continue;
+            }
+        };
+        let mut tc = TypeChecker::new(&file);
+        let checked = tc.check_program(&ast);
+        let mut found: Vec<PawError> = tc.warnings().into_iter().filter(|w| rules.reports(w)).collect();
+        found.extend(lint_program(&file, &ast, rules));
+        found.extend(checked.err().map(|e| *e));
+        found.sort_by_key(|d| (d.location().1, d.location().2));
+        diagnostics.extend(found);
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| !matches!(d, PawError::Warning { .. }))
+        .count();
+    match format {
+        LintFormat::Text => {
+            for diagnostic in &diagnostics {
+                eprintln!("{}", diagnostic);
+            }
+            println!(
+                "🐾 Linted {} file(s): {} error(s), {} warning(s)",
+                scripts.len(),
+                errors,
+                diagnostics.len() - errors
+            );
+        }
+        LintFormat::Json => {
+            let items: Vec<String> = diagnostics.iter().map(diagnostic_json).collect();
+            println!("[{}]", items.join(",\n "));
+        }
+    }
+    diagnostics.is_empty()
+}
+
+/// `pawc graph`：输出入口脚本的模块依赖图；读不到或解析不了的模块报告在标准错误中，
+/// 图仍然输出。有这样的错误时返回 false
+fn graph_report(script: &Path, format: GraphFormat, cfg: &Cfg) -> bool {
+    let file = script.to_string_lossy();
+    let parsed = read_script(script).and_then(|src| {
+        let tokens = Lexer::new(&src).tokenize();
+        PawParser::new(tokens, &src, &file).with_cfg(cfg).parse_program()
+    });
+    let ast = match parsed {
+        Ok(ast) => ast,
+        Err(err) => {
+            eprintln!("{}", err);
+            return false;
+        }
+    };
+    let graph = import_graph(&file, &ast, cfg);
+    for err in &graph.errors {
+        eprintln!("{}", err);
+    }
+    match format {
+        GraphFormat::Dot => print!("{}", graph.to_dot()),
+        GraphFormat::Json => print!("{}", graph.to_json()),
+    }
+    graph.errors.is_empty()
+}
+
+/// `pawc test`：逐个文件运行测试并输出每个测试的结果与汇总；有失败时返回 false
+fn test_report(paths: &[PathBuf], options: &TestOptions) -> bool {
+    let files = match find_test_files(paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Failed to find test scripts: {}", e);
+            return false;
+        }
+    };
+    let (mut passed, mut failed) = (0, 0);
+    for file in &files {
+        let results = match run_test_file(file, options) {
+            Ok(results) => results,
+            Err(err) => {
+                failed += 1;
+                println!("FAIL  {}", file.display());
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+        for result in results {
+            match result.outcome {
+                Ok(()) => {
+                    passed += 1;
+                    println!("ok    {}::{}", file.display(), result.name);
+                }
+                Err(err) => {
+                    failed += 1;
+                    println!("FAIL  {}::{}", file.display(), result.name);
+                    eprintln!("{}", err);
+                }
+            }
+        }
+    }
+    println!(
+        "🐾 Tested {} file(s): {} passed, {} failed",
+        files.len(),
+        passed,
+        failed
+    );
+    failed == 0
+}
+
+/// 一条诊断的 JSON 对象；错误没有所属的规则，rule 为 null
+fn diagnostic_json(diagnostic: &PawError) -> String {
+    let (file, line, column) = diagnostic.location();
+    let warning = matches!(diagnostic, PawError::Warning { .. });
+    let optional = |s: Option<&str>| s.map_or("null".to_string(), json_string);
+    format!(
+        "{{\"file\":{},\"line\":{},\"column\":{},\"severity\":{},\"code\":{},\"rule\":{},\"message\":{},\"hint\":{}}}",
+        json_string(file),
+        line,
+        column,
+        json_string(if warning { "warning" } else { "error" }),
+        json_string(diagnostic.code()),
+        optional(Rule::of_code(diagnostic.code()).map(Rule::name)),
+        json_string(&diagnostic.message()),
+        optional(diagnostic.hint())
+    )
+}
+
+/// 交互式 REPL：每次输入在同一个 Session 中检查并执行，括号未闭合时继续读取下一行
+async fn repl(vm: bool, defines: &[Define], cfg: &Cfg) {
+    let mut session = Session::new("<repl>");
+    session.set_vm(vm);
+    session.set_cfg(cfg.clone());
+    for define in defines {
+        session.define(&define.name, define.value.clone());
+    }
+    let stdin = io::stdin();
+    let mut buffer = String::new();
+    loop {
+        print!("{}", if buffer.is_empty() { "paw> " } else { "...> " });
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        buffer.push_str(&line);
+        if open_brackets(&buffer) > 0 {
+            continue;
+        }
+        let src = std::mem::take(&mut buffer);
+        if src.trim().is_empty() {
+            continue;
+        }
+        // 导入过的模块文件被修改时，先换上新版本
+        match session.reload_modules_async().await {
+            Ok(paths) => {
+                for path in paths {
+                    eprintln!("🐾 Reloaded {}", path.display());
+                }
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+        match session.eval_async(&src).await {
+            Ok(value) => {
+                for warning in session.warnings() {
+                    eprintln!("{}", warning);
+                }
+                if !matches!(&*value.0, ValueInner::Null) {
+                    println!("{}", value);
+                }
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+    println!();
+}
+
+/// 统计尚未闭合的括号数量，忽略字符串、字符字面量与 `#` 注释中的括号
+fn open_brackets(src: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut chars = src.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            (None, '{' | '(' | '[') => depth += 1,
+            (None, '}' | ')' | ']') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
 /// Load, parse, type‐check and run a PawScript file.
-async fn run_script(script: &PathBuf) -> Result<(), PawError> {
-    // 1. Read file
-    let src = fs::read_to_string(script).map_err(|e| PawError::Internal {
-        file: script.to_str().unwrap_or_default().into(),
-        code: "E1000".into(),
-        message: format!("Failed to read script '{}': {}", script.display(), e),
-        line: 0,
-        column: 0,
-        snippet: None,
-        hint: Some("Ensure the file exists and is readable.".into()),
-    })?;
+async fn run_script(script: &Path, args: &Args, timings: &mut Timings) -> Result<(), Box<PawError>> {
+    let (vm, sandbox, defines) = (!args.no_vm, args.sandbox, &args.defines);
+    let defaults = SizeLimits::default();
+    let limits = SizeLimits {
+        max_string_bytes: args.max_string_bytes.unwrap_or(defaults.max_string_bytes),
+        max_collection_len: args.max_collection_len.unwrap_or(defaults.max_collection_len),
+    };
+    let cfg = cfg_flags(&args.cfg);
 
-    // 2. Lex & parse
-    let tokens = Lexer::new(&src).tokenize();
+    // 1. Read file（`-` 表示从标准输入读取）
+    let (src, script) = if script.as_os_str() == "-" {
+        (read_stdin()?, PathBuf::from("<stdin>"))
+    } else {
+        (read_script(script)?, script.to_path_buf())
+    };
+    let file = script.to_string_lossy();
 
-    let mut parser = PawParser::new(tokens, &src, &*script.to_string_lossy());
-    let ast = parser.parse_program().map_err(|err| {
-        // If you want, you could fill in err.line/column/snippet here
-        err
+    // 2. Lex & parse
+    let tokens = timed(&mut timings.lex, || Lexer::new(&src).tokenize());
+    let ast = timed(&mut timings.parse, || {
+        PawParser::new(tokens, &src, &file).with_cfg(&cfg).parse_program()
     })?;
 
     // 3. Static type check
-    let mut tc = TypeChecker::new(&*script.to_string_lossy());
-    tc.check_program(&ast).map_err(|err| {
-        // err already has code/message/etc.
-        err
-    })?;
+    let mut tc = TypeChecker::new(&file);
+    for define in defines {
+        tc.scope.set(&define.name, define.ty.clone());
+    }
+    // 沙箱模式禁止导入文件模块，不必检查导入图
+    let imports = timed(&mut timings.check, || {
+        tc.check_program(&ast)?;
+        Ok::<_, Box<PawError>>((!sandbox).then(|| check_imports(&file, &ast, args.check_imports, &cfg)))
+    })?
+    .unwrap_or_default();
+    // 模块的警告在执行时导入模块时输出，这里只输出脚本本身的
+    for warning in tc.warnings() {
+        eprintln!("{}", warning);
+    }
+    // 模块的错误各自归属于模块文件；先输出其余的，最后一个作为结果返回
+    let mut errors = imports.errors;
+    if let Some(last) = errors.pop() {
+        for err in errors {
+            eprintln!("{}", err);
+        }
+        return Err(Box::new(last));
+    }
 
     // 4. Interpret
     let env = Env::new();
-    let engine = Engine::new(env, &*script.to_string_lossy());
-    vuot::run(Interpreter {
+    let mut engine = Engine::new(env, &file);
+    engine.set_vm(vm);
+    engine.set_sandbox(sandbox);
+    engine.set_size_limits(limits);
+    engine.set_cfg(cfg);
+    if args.deterministic {
+        let host = Arc::new(Host::new());
+        host.set_deterministic(args.epoch.unwrap_or(0), args.seed.unwrap_or(0));
+        engine.set_host(host);
+    }
+    for define in defines {
+        engine.set_global(&define.name, define.value.clone());
+    }
+    let modules = engine.module_cache();
+    let counters = Arc::new(Counters::default());
+    engine.set_counters(counters.clone());
+    let start = Instant::now();
+    let result = vuot::run(Interpreter {
         engine,
         statements: &ast,
-    }).await?;
+    })
+    .await;
+    timings.execute = Some(start.elapsed());
+    timings.modules = modules.lock().len();
+    timings.stats = Some(counters.snapshot());
+    result?;
 
     Ok(())
 }
+
+/// 读取脚本源码，失败时报告 E1000
+fn read_script(script: &Path) -> Result<String, Box<PawError>> {
+    fs::read_to_string(script).map_err(|e| Box::new(PawError::Internal {
+        file: script.to_str().unwrap_or_default().into(),
+        code: "E1000",
+        message: format!("Failed to read script '{}': {}", script.display(), e),
+        line: 0,
+        column: 0,
+        snippet: None,
+        hint: Some("Ensure the file exists and is readable.".into()),
+    }))
+}
+
+/// 从标准输入读取脚本源码，失败时报告 E1000
+fn read_stdin() -> Result<String, Box<PawError>> {
+    let mut src = String::new();
+    io::stdin().read_to_string(&mut src).map_err(|e| PawError::Internal {
+        file: "<stdin>".into(),
+        code: "E1000",
+        message: format!("Failed to read script from stdin: {}", e),
+        line: 0,
+        column: 0,
+        snippet: None,
+        hint: None,
+    })?;
+    Ok(src)
+}
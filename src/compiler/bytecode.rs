@@ -0,0 +1,81 @@
+// src/compiler/bytecode.rs
+
+use crate::ast::expr::{BinaryOp, FormatSpec};
+use crate::interpreter::value::Value;
+
+/// 字节码指令；操作数都是下标（常量池、槽位或跳转目标），保持指令紧凑
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// 压入常量池中的值
+    Const(u32),
+    /// 压入具名槽位中的变量，未定义时报 E4001
+    Load(u32),
+    /// 进入代码块或开始一次迭代时清空其中局部变量的槽位
+    Clear(u32),
+    /// 弹出栈顶，写入已存在的变量（赋值语句）
+    Store(u32),
+    /// 弹出栈顶，定义或覆盖变量（let 语句、循环变量）
+    Define(u32),
+    /// 一元 `-`
+    Neg,
+    /// 一元 `!`
+    Not,
+    /// 弹出右、左操作数，压入运算结果
+    Binary(BinaryOp),
+    /// 弹出下标、容器，压入取到的元素
+    Index,
+    /// 弹出 n 个值，按顺序拼接成字符串（字符串插值）
+    Concat(u32),
+    /// 弹出栈顶，按格式说明表中的说明输出为字符串（`${x:spec}`）
+    Format(u32),
+    /// 丢弃栈顶（表达式语句）
+    Pop,
+    /// 弹出栈顶并打印
+    Say,
+    /// 无条件跳转；向回跳时检查任务是否已被取消
+    Jump(u32),
+    /// 弹出条件，不是 `true` 时跳转（if 语义）
+    JumpUnlessTrue(u32),
+    /// 栈顶为 Bool(decided) 时保留它并跳转（`&&` 左侧为 false、`||` 左侧为 true），
+    /// 否则继续求右侧，由随后的 Binary 计算结果
+    ShortCircuit { decided: bool, target: u32 },
+    /// 弹出循环条件：`true` 继续，`false` 跳转，其它类型报 E3007
+    LoopCond(u32),
+    /// 依次弹出步长（`step` 为 true 时）、上界、下界，构造范围存入隐藏槽位；
+    /// 不是 Int / Long 时报 E3008，步长为 0 时报 E6022
+    RangeInit { range: u32, step: bool, inclusive: bool },
+    /// 范围还有剩余元素时把下一个元素定义为循环变量，否则跳转
+    RangeNext { range: u32, var: u32, exit: u32 },
+    /// 弹出被遍历的值，按 `Value::loop_items` 的遍历协议存入隐藏槽位，不能遍历时报 E3018
+    IterInit(u32),
+    /// 还有剩余元素时把下一个元素定义为循环变量，否则跳转
+    IterNext { iter: u32, var: u32, exit: u32 },
+    /// 弹出栈顶，作为 return 的值离开字节码
+    Return,
+}
+
+/// 具名槽位的种类
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    /// 循环所在环境中的变量：进入时从环境读取，退出时写回环境
+    Global,
+    /// `{ }` 代码块或循环体中定义的变量（含循环变量）；在其中定义之前，读写落到外层同名槽位 `outer`
+    Local { outer: u32 },
+}
+
+/// 一段编译好的字节码
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    pub ops: Vec<Op>,
+    /// 每条指令对应的源码位置 (line, column)，用于报错
+    pub positions: Vec<(usize, usize)>,
+    pub constants: Vec<Value>,
+    /// 插值中用到的格式说明
+    pub specs: Vec<FormatSpec>,
+    /// 具名槽位对应的变量名
+    pub names: Vec<String>,
+    /// 与 `names` 一一对应的槽位种类
+    pub slots: Vec<Slot>,
+    /// 编译器为循环状态分配的隐藏槽位数量
+    pub hidden: usize,
+}
@@ -0,0 +1,418 @@
+// src/compiler/mod.rs
+//
+// 把循环语句降级为字节码，交给解释器中的 VM 执行；
+// 遇到暂不支持的语法（函数调用、await、sniff、方法调用等）时放弃编译，
+// 由树遍历解释器照常执行。
+
+pub mod bytecode;
+
+use crate::ast::expr::{BinaryOp, Expr, ExprKind};
+use crate::ast::statement::{LoopVar, Statement, StatementKind};
+use crate::compiler::bytecode::{Chunk, Op, Slot};
+use crate::interpreter::value::Value;
+use ahash::AHashMap;
+
+/// 正在编译的循环，记录标签、continue 的目标与待回填的 break
+struct LoopCtx {
+    label: Option<String>,
+    /// continue 跳回的地址；`loop { } while cond` 的条件在循环体之后，此时为 None，
+    /// continue 记入 `continues` 待回填
+    continue_to: Option<u32>,
+    continues: Vec<usize>,
+    breaks: Vec<usize>,
+}
+
+/// 循环字节码编译器
+pub struct Compiler {
+    chunk: Chunk,
+    slots: AHashMap<String, u32>,
+    /// 外层到内层的代码块作用域，记录块内定义的变量所在槽位
+    scopes: Vec<AHashMap<String, u32>>,
+    loops: Vec<LoopCtx>,
+}
+
+impl Compiler {
+    /// 编译一条循环语句；包含不支持的语法时返回 None
+    pub fn compile_loop(stmt: &Statement) -> Option<Chunk> {
+        if !matches!(
+            stmt.kind,
+            StatementKind::LoopForever { .. }
+                | StatementKind::LoopWhile { .. }
+                | StatementKind::LoopDoWhile { .. }
+                | StatementKind::LoopRange { .. }
+                | StatementKind::LoopArray { .. }
+        ) {
+            return None;
+        }
+        let mut compiler = Compiler {
+            chunk: Chunk::default(),
+            slots: AHashMap::new(),
+            scopes: Vec::new(),
+            loops: Vec::new(),
+        };
+        compiler.statement(stmt)?;
+        Some(compiler.chunk)
+    }
+
+    /// 追加一条指令，返回它的地址
+    fn emit(&mut self, op: Op, line: usize, col: usize) -> usize {
+        self.chunk.ops.push(op);
+        self.chunk.positions.push((line, col));
+        self.chunk.ops.len() - 1
+    }
+
+    /// 下一条指令的地址
+    fn here(&self) -> u32 {
+        self.chunk.ops.len() as u32
+    }
+
+    /// 把 `at` 处跳转指令的目标改为当前位置
+    fn patch(&mut self, at: usize) {
+        let target = self.here();
+        match &mut self.chunk.ops[at] {
+            Op::Jump(t)
+            | Op::JumpUnlessTrue(t)
+            | Op::LoopCond(t)
+            | Op::ShortCircuit { target: t, .. } => *t = target,
+            Op::RangeNext { exit, .. } | Op::IterNext { exit, .. } => *exit = target,
+            _ => unreachable!("patching a non-jump instruction"),
+        }
+    }
+
+    fn constant(&mut self, v: Value, line: usize, col: usize) {
+        let idx = self.chunk.constants.len() as u32;
+        self.chunk.constants.push(v);
+        self.emit(Op::Const(idx), line, col);
+    }
+
+    /// 变量名当前对应的槽位：先找代码块作用域，再找循环所在环境的变量（首次出现时分配）
+    fn slot(&mut self, name: &str) -> u32 {
+        if let Some(idx) = self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            return *idx;
+        }
+        if let Some(idx) = self.slots.get(name) {
+            return *idx;
+        }
+        let idx = self.new_slot(name, Slot::Global);
+        self.slots.insert(name.to_string(), idx);
+        idx
+    }
+
+    fn new_slot(&mut self, name: &str, kind: Slot) -> u32 {
+        self.chunk.names.push(name.to_string());
+        self.chunk.slots.push(kind);
+        (self.chunk.names.len() - 1) as u32
+    }
+
+    fn hidden(&mut self) -> u32 {
+        self.chunk.hidden += 1;
+        (self.chunk.hidden - 1) as u32
+    }
+
+    fn statements(&mut self, stmts: &[Statement]) -> Option<()> {
+        for stmt in stmts {
+            self.statement(stmt)?;
+        }
+        Some(())
+    }
+
+
+    /// 循环变量的槽位：与循环体内定义的变量一样属于每次迭代自己的作用域，不写回环境
+    fn loop_var(&mut self, name: &str) -> u32 {
+        let outer = self.slot(name);
+        self.new_slot(name, Slot::Local { outer })
+    }
+
+    /// 编译有自己作用域的一段语句（`{ }` 代码块或一次迭代的循环体）：其中定义的变量分配局部槽位，
+    /// 每次进入时清空，与树遍历解释器每次新建一层作用域一致；`var` 为已分配好的循环变量槽位
+    fn scoped(&mut self, stmts: &[Statement], var: Option<(&str, u32)>, line: usize, col: usize) -> Option<()> {
+        let mut names = Vec::new();
+        declared_names(stmts, &mut names);
+        let mut scope: AHashMap<String, u32> = var.map(|(name, slot)| (name.to_string(), slot)).into_iter().collect();
+        for name in names {
+            if scope.contains_key(&name) {
+                continue;
+            }
+            let outer = self.slot(&name);
+            let local = self.new_slot(&name, Slot::Local { outer });
+            self.emit(Op::Clear(local), line, col);
+            scope.insert(name, local);
+        }
+        self.scopes.push(scope);
+        let compiled = self.statements(stmts);
+        self.scopes.pop();
+        compiled
+    }
+
+    /// 编译循环体：body 之后跳回 `top`，并把 break 回填到循环出口
+    fn loop_body(
+        &mut self,
+        label: &Option<String>,
+        body: &[Statement],
+        var: Option<(&str, u32)>,
+        top: u32,
+        line: usize,
+        col: usize,
+    ) -> Option<Vec<usize>> {
+        self.loops.push(LoopCtx {
+            label: label.clone(),
+            continue_to: Some(top),
+            continues: Vec::new(),
+            breaks: Vec::new(),
+        });
+        let compiled = self.scoped(body, var, line, col);
+        let ctx = self.loops.pop()?;
+        compiled?;
+        self.emit(Op::Jump(top), line, col);
+        Some(ctx.breaks)
+    }
+
+    /// break / continue 作用的循环：不带标签时是最近一层，带标签的目标不在本次编译的循环中时放弃编译
+    fn target_loop(&mut self, label: &Option<String>) -> Option<&mut LoopCtx> {
+        match label {
+            None => self.loops.last_mut(),
+            Some(_) => self.loops.iter_mut().rev().find(|ctx| ctx.label == *label),
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> Option<()> {
+        let (line, col) = (stmt.line, stmt.col);
+        match &stmt.kind {
+            StatementKind::Let { name, value, .. } => {
+                self.expr(value)?;
+                let slot = self.slot(name);
+                self.emit(Op::Define(slot), line, col);
+            }
+            StatementKind::Assign { name, value } => {
+                self.expr(value)?;
+                let slot = self.slot(name);
+                self.emit(Op::Store(slot), line, col);
+            }
+            StatementKind::Say(e) => {
+                self.expr(e)?;
+                self.emit(Op::Say, line, col);
+            }
+            StatementKind::Expr(e) => {
+                self.expr(e)?;
+                self.emit(Op::Pop, line, col);
+            }
+            StatementKind::Return(opt) => {
+                match opt {
+                    Some(e) => self.expr(e)?,
+                    None => self.constant(Value::Null(), line, col),
+                }
+                self.emit(Op::Return, line, col);
+            }
+            StatementKind::Break(label) => {
+                let at = self.emit(Op::Jump(u32::MAX), line, col);
+                self.target_loop(label)?.breaks.push(at);
+            }
+            StatementKind::Continue(label) => {
+                let target = self.target_loop(label)?.continue_to;
+                let at = self.emit(Op::Jump(target.unwrap_or(u32::MAX)), line, col);
+                if target.is_none() {
+                    self.target_loop(label)?.continues.push(at);
+                }
+            }
+            StatementKind::If {
+                condition,
+                body,
+                else_branch,
+            } => {
+                self.expr(condition)?;
+                let skip = self.emit(Op::JumpUnlessTrue(u32::MAX), line, col);
+                self.statements(body)?;
+                match else_branch {
+                    Some(else_stmt) => {
+                        let end = self.emit(Op::Jump(u32::MAX), line, col);
+                        self.patch(skip);
+                        self.statement(else_stmt)?;
+                        self.patch(end);
+                    }
+                    None => self.patch(skip),
+                }
+            }
+            StatementKind::Block(stmts) => self.scoped(stmts, None, line, col)?,
+            StatementKind::LoopForever { label, body } => {
+                let top = self.here();
+                let breaks = self.loop_body(label, body, None, top, line, col)?;
+                breaks.into_iter().for_each(|at| self.patch(at));
+            }
+            StatementKind::LoopWhile { label, condition, body } => {
+                let top = self.here();
+                self.expr(condition)?;
+                let exit = self.emit(Op::LoopCond(u32::MAX), condition.line, condition.col);
+                let breaks = self.loop_body(label, body, None, top, line, col)?;
+                self.patch(exit);
+                breaks.into_iter().for_each(|at| self.patch(at));
+            }
+            StatementKind::LoopDoWhile {
+                label,
+                body,
+                condition,
+            } => {
+                let top = self.here();
+                self.loops.push(LoopCtx {
+                    label: label.clone(),
+                    continue_to: None,
+                    continues: Vec::new(),
+                    breaks: Vec::new(),
+                });
+                let compiled = self.scoped(body, None, line, col);
+                let ctx = self.loops.pop()?;
+                compiled?;
+                ctx.continues.into_iter().for_each(|at| self.patch(at));
+                self.expr(condition)?;
+                let exit = self.emit(Op::LoopCond(u32::MAX), condition.line, condition.col);
+                self.emit(Op::Jump(top), line, col);
+                self.patch(exit);
+                ctx.breaks.into_iter().for_each(|at| self.patch(at));
+            }
+            StatementKind::LoopRange {
+                label,
+                var,
+                start,
+                end,
+                step,
+                inclusive,
+                body,
+            } => {
+                self.expr(start)?;
+                self.expr(end)?;
+                if let Some(step) = step {
+                    self.expr(step)?;
+                }
+                let range = self.hidden();
+                let init = Op::RangeInit {
+                    range,
+                    step: step.is_some(),
+                    inclusive: *inclusive,
+                };
+                self.emit(init, line, col);
+                let (name, var) = (var.as_str(), self.loop_var(var));
+                let top = self.here();
+                let next = self.emit(
+                    Op::RangeNext {
+                        range,
+                        var,
+                        exit: u32::MAX,
+                    },
+                    line,
+                    col,
+                );
+                let breaks = self.loop_body(label, body, Some((name, var)), top, line, col)?;
+                self.patch(next);
+                breaks.into_iter().for_each(|at| self.patch(at));
+            }
+            StatementKind::LoopArray { label, var, array, body } => {
+                // 解构元组的循环交给树遍历解释器
+                let LoopVar::Name(var) = var else {
+                    return None;
+                };
+                self.expr(array)?;
+                let iter = self.hidden();
+                self.emit(Op::IterInit(iter), array.line, array.col);
+                let (name, var) = (var.as_str(), self.loop_var(var));
+                let top = self.here();
+                let next = self.emit(
+                    Op::IterNext {
+                        iter,
+                        var,
+                        exit: u32::MAX,
+                    },
+                    line,
+                    col,
+                );
+                let breaks = self.loop_body(label, body, Some((name, var)), top, line, col)?;
+                self.patch(next);
+                breaks.into_iter().for_each(|at| self.patch(at));
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Option<()> {
+        let (line, col) = (expr.line, expr.col);
+        match &expr.kind {
+            ExprKind::LiteralInt(n) => self.constant(Value::Int(*n), line, col),
+            ExprKind::LiteralLong(n) => self.constant(Value::Long(*n), line, col),
+            ExprKind::LiteralFloat(f) => self.constant(Value::Float(*f), line, col),
+            ExprKind::LiteralDouble(f) => self.constant(Value::Double(*f), line, col),
+            ExprKind::LiteralString(s) => self.constant(Value::String(s.clone()), line, col),
+            ExprKind::LiteralBool(b) => self.constant(Value::Bool(*b), line, col),
+            ExprKind::LiteralChar(c) => self.constant(Value::Char(*c), line, col),
+            ExprKind::LiteralNopaw => self.constant(Value::Null(), line, col),
+            ExprKind::Interpolation(parts) => {
+                for part in parts {
+                    self.expr(part)?;
+                }
+                self.emit(Op::Concat(parts.len() as u32), line, col);
+            }
+            ExprKind::Formatted { expr: inner, spec } => {
+                self.expr(inner)?;
+                let idx = self.chunk.specs.len() as u32;
+                self.chunk.specs.push(spec.clone());
+                self.emit(Op::Format(idx), line, col);
+            }
+            ExprKind::Var(name) => {
+                let slot = self.slot(name);
+                self.emit(Op::Load(slot), line, col);
+            }
+            ExprKind::UnaryOp { op, expr: inner } => {
+                let op = match op.as_str() {
+                    "-" => Op::Neg,
+                    "!" => Op::Not,
+                    _ => return None,
+                };
+                self.expr(inner)?;
+                self.emit(op, line, col);
+            }
+            ExprKind::BinaryOp { op, left, right } => {
+                self.expr(left)?;
+                let decided = match op {
+                    BinaryOp::And => Some(false),
+                    BinaryOp::Or => Some(true),
+                    _ => None,
+                };
+                let skip = decided.map(|decided| {
+                    self.emit(Op::ShortCircuit { decided, target: u32::MAX }, line, col)
+                });
+                self.expr(right)?;
+                self.emit(Op::Binary(op.clone()), line, col);
+                if let Some(skip) = skip {
+                    self.patch(skip);
+                }
+            }
+            ExprKind::Index { array, index } => {
+                self.expr(array)?;
+                self.expr(index)?;
+                self.emit(Op::Index, line, col);
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+/// 收集一段语句直接定义的变量名（let），包括 if 分支内的定义，
+/// 但不进入各有作用域的 `{ }` 代码块与循环体
+fn declared_names(stmts: &[Statement], out: &mut Vec<String>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StatementKind::Let { name, .. } => out.push(name.clone()),
+            StatementKind::LetTuple { names, .. } => {
+                out.extend(names.iter().filter(|n| *n != "_").cloned())
+            }
+            StatementKind::If {
+                body, else_branch, ..
+            } => {
+                declared_names(body, out);
+                if let Some(else_stmt) = else_branch {
+                    declared_names(std::slice::from_ref(else_stmt.as_ref()), out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
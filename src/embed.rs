@@ -0,0 +1,193 @@
+// src/embed.rs
+//
+// 嵌入 API：让其它 Rust 程序直接运行 PawScript 代码、调用脚本函数，并注入宿主变量
+
+use crate::error::error::PawError;
+use crate::interpreter::host::Host;
+use crate::interpreter::interpreter::{SizeLimits, Stats};
+use crate::interpreter::value::Value;
+use crate::session::Session;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 可嵌入的 PawScript 实例
+///
+/// 多次 `eval_str` 共享同一个全局环境和类型检查作用域，
+/// 前一段代码定义的变量、函数和 record 在之后的调用中仍然可见：
+///
+/// ```
+/// use pawc::{PawScript, Value};
+///
+/// let mut paw = PawScript::new();
+/// paw.define("limit", Value::Int(3));
+/// paw.eval_str("fun double(x: Int): Int { return x * 2 }").unwrap();
+/// let v = paw.eval_str("double(limit) + 1").unwrap();
+/// assert_eq!(v, Value::Int(7));
+/// let w = paw.call_function("double", vec![Value::Int(5)]).unwrap();
+/// assert_eq!(w, Value::Int(10));
+/// ```
+pub struct PawScript {
+    session: Session,
+}
+
+impl Default for PawScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 公开接口按嵌入 API 的约定直接返回 PawError，不要求调用方解开 Box
+#[allow(clippy::result_large_err)]
+impl PawScript {
+    /// 创建一个空的实例，错误信息中的文件名为 `<eval>`
+    pub fn new() -> Self {
+        Self::with_file("<eval>")
+    }
+
+    /// 创建一个实例，并指定错误信息与相对导入所使用的文件名
+    pub fn with_file(file: &str) -> Self {
+        PawScript {
+            session: Session::new(file),
+        }
+    }
+
+    /// 开启或关闭字节码 VM（默认开启），关闭后循环由树遍历解释器执行
+    pub fn set_vm(&mut self, enabled: bool) {
+        self.session.set_vm(enabled);
+    }
+
+    /// 开启或关闭沙箱模式（默认关闭），开启后脚本不能导入模块或读取控制台输入
+    pub fn set_sandbox(&mut self, enabled: bool) {
+        self.session.set_sandbox(enabled);
+    }
+
+    /// 设置沙箱模式下字符串与容器的大小上限，超出时报 E6020；非沙箱模式不检查
+    ///
+    /// ```
+    /// use pawc::{PawScript, SizeLimits};
+    ///
+    /// let mut paw = PawScript::new();
+    /// paw.set_sandbox(true);
+    /// paw.set_size_limits(SizeLimits { max_string_bytes: 8, ..SizeLimits::default() });
+    /// let err = paw.eval_str("let s: String = \"paw\"\ns + s + s").unwrap_err();
+    /// assert_eq!(err.code(), "E6020");
+    /// ```
+    pub fn set_size_limits(&mut self, limits: SizeLimits) {
+        self.session.set_size_limits(limits);
+    }
+
+    /// 替换 `ask`、`time` 与 `random` 的输入来源，用法见 [`Host`]
+    pub fn set_host(&mut self, host: Arc<Host>) {
+        self.session.set_host(host);
+    }
+
+    /// 打开一个 `@cfg(...)` 开关，如 `"debug"`；当前平台的开关（`linux`、`unix` 等）默认打开
+    ///
+    /// ```
+    /// use pawc::{PawScript, Value};
+    ///
+    /// let mut paw = PawScript::new();
+    /// paw.enable_cfg("debug");
+    /// let v = paw.eval_str("let mode: String = \"release\"\n@cfg(\"debug\") mode = \"debug\"\nmode").unwrap();
+    /// assert_eq!(v, Value::String("debug".to_string()));
+    /// ```
+    pub fn enable_cfg(&mut self, flag: &str) {
+        self.session.enable_cfg(flag);
+    }
+
+    /// 注入或覆盖一个宿主变量；其静态类型由值推断，函数和 record 视为 `Any`
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.session.define(name, value);
+    }
+
+    /// 注册一个原生函数，`signature` 使用脚本中的函数类型写法，如 `fun(Int, Int): Int`，
+    /// 类型检查器据此检查调用；写 `Any` 则只在运行时由原生函数自行检查参数
+    ///
+    /// ```
+    /// use pawc::{PawScript, Value, ValueInner};
+    ///
+    /// let mut paw = PawScript::new();
+    /// paw.register_native("add", "fun(Int, Int): Int", |args| match (&*args[0].0, &*args[1].0) {
+    ///     (ValueInner::Int(a), ValueInner::Int(b)) => Ok(Value::Int(a + b)),
+    ///     _ => Err("add expects two Ints".into()),
+    /// })
+    /// .unwrap();
+    /// assert_eq!(paw.eval_str("add(2, 3)").unwrap(), Value::Int(5));
+    /// ```
+    pub fn register_native<F>(&mut self, name: &str, signature: &str, func: F) -> Result<(), PawError>
+    where
+        F: Fn(Vec<Value>) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.session.register_native(name, signature, func)
+    }
+
+    /// 最近一次 `eval_str` 中类型检查器给出的警告
+    pub fn warnings(&self) -> &[PawError] {
+        self.session.warnings()
+    }
+
+    /// 实例创建（或上次 `reset_stats`）以来累计的执行计数
+    ///
+    /// ```
+    /// use pawc::PawScript;
+    ///
+    /// let mut paw = PawScript::new();
+    /// paw.eval_str("fun f(n: Int): Int { return n + 1 }\nf(1)\nf(2)").unwrap();
+    /// assert_eq!(paw.stats().calls, 2);
+    /// paw.reset_stats();
+    /// assert_eq!(paw.stats().statements, 0);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        self.session.stats()
+    }
+
+    /// 把执行计数清零
+    pub fn reset_stats(&self) {
+        self.session.reset_stats();
+    }
+
+    /// 读取一个全局变量的当前值
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.session.get(name)
+    }
+
+    /// 词法分析、语法分析、类型检查并运行一段源码
+    ///
+    /// 返回值为顶层 `return` 的值；没有 return 时，若最后一条语句是表达式则返回它的值，
+    /// 否则返回 `nopaw`。得到的 Future 会被 await，未 await 的任务会在返回前全部完成。
+    /// 只有类型检查通过的代码才会更新检查器作用域。
+    ///
+    /// 内部会启动一个异步运行时，因此不能在另一个异步运行时中调用。
+    pub fn eval_str(&mut self, src: &str) -> Result<Value, PawError> {
+        self.session.eval(src)
+    }
+
+    /// 在全局环境中求值一个表达式，如调试器的监视表达式；`read_only` 为 true 时
+    /// 不能调用脚本函数或运行异步代码，求值不会改变任何变量
+    ///
+    /// ```
+    /// use pawc::{PawScript, Value};
+    ///
+    /// let mut paw = PawScript::new();
+    /// paw.eval_str("let xs: Array<Int> = [1, 2, 3]").unwrap();
+    /// assert_eq!(paw.evaluate("xs[1] * 10", true).unwrap(), Value::Int(20));
+    /// ```
+    pub fn evaluate(&mut self, expr: &str, read_only: bool) -> Result<Value, PawError> {
+        self.session.evaluate(expr, read_only)
+    }
+
+    /// 按名字调用一个脚本中定义的函数；async 函数会被 await，返回其结果
+    pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> Result<Value, PawError> {
+        self.session.call_function(name, args)
+    }
+
+    /// 重新加载源文件被修改过的模块，返回它们的路径；保留状态的规则见 `Session::reload_modules`
+    pub fn reload_modules(&mut self) -> Result<Vec<PathBuf>, PawError> {
+        self.session.reload_modules()
+    }
+
+    /// 底层会话，可用于在已有异步运行时中执行（`eval_async`）
+    pub fn session(&mut self) -> &mut Session {
+        &mut self.session
+    }
+}
@@ -1,8 +1,21 @@
 // src/error/error.rs
 
+use crate::interpreter::value::Value;
 use colored::Colorize;
 use std::fmt;
 
+/// 调用栈中的一帧：被调用的函数及其调用位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Display 中最多展示的调用帧数，更深的调用只给出省略的数量
+const MAX_SHOWN_FRAMES: usize = 16;
+
 /// 🐾 PawScript Error Type — cute but informative and spanned
 #[derive(Debug, Clone)]
 pub enum PawError {
@@ -59,9 +72,11 @@ pub enum PawError {
         column: usize,
         snippet: Option<String>,
         hint: Option<String>,
+        /// 错误离开的函数调用，最内层在前；顶层代码中的错误为空
+        backtrace: Vec<StackFrame>,
     },
 
-    /// Custom user-defined error
+    /// Custom user-defined error: a record value thrown with `bark`
     Custom {
         /// user-given error name
         name: String,
@@ -72,6 +87,31 @@ pub enum PawError {
         column: usize,
         snippet: Option<String>,
         hint: Option<String>,
+        /// 被抛出的 record 值，由类型匹配的 snatch 子句绑定
+        payload: Value,
+        /// 错误离开的函数调用，最内层在前；顶层代码中的错误为空
+        backtrace: Vec<StackFrame>,
+    },
+
+    /// Non-fatal diagnostic reported by the checker; the program still runs
+    Warning {
+        file: String,
+        code: &'static str,
+        message: String,
+        line: usize,
+        column: usize,
+        snippet: Option<String>,
+        hint: Option<String>,
+    },
+
+    /// `e?` 遇到 `err(...)` 时带着该 Result 离开当前函数；函数调用处把它转换为返回值，
+    /// snatch 不会捕获它，只有在函数之外使用 `?` 时才会到达调用方
+    Propagate {
+        file: String,
+        /// 被提前返回的 `err(...)` 值
+        value: Value,
+        line: usize,
+        column: usize,
     },
 
     /// Internal error
@@ -86,6 +126,76 @@ pub enum PawError {
     },
 }
 
+impl PawError {
+    /// 不带格式的错误描述，用于嵌套或汇总错误信息
+    pub fn message(&self) -> String {
+        match self {
+            PawError::Syntax { message, .. }
+            | PawError::Type { message, .. }
+            | PawError::Runtime { message, .. }
+            | PawError::Custom { message, .. }
+            | PawError::Warning { message, .. }
+            | PawError::Internal { message, .. } => message.clone(),
+            PawError::UndefinedVariable { name, .. } => format!("Undefined variable '{}'", name),
+            PawError::DuplicateDefinition { name, .. } => format!("Duplicate definition '{}'", name),
+            PawError::Propagate { value, .. } => format!("`?` returned {} outside of a function", value),
+        }
+    }
+
+    /// 错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            PawError::Syntax { code, .. }
+            | PawError::Type { code, .. }
+            | PawError::UndefinedVariable { code, .. }
+            | PawError::DuplicateDefinition { code, .. }
+            | PawError::Runtime { code, .. }
+            | PawError::Custom { code, .. }
+            | PawError::Warning { code, .. }
+            | PawError::Internal { code, .. } => code,
+            PawError::Propagate { .. } => "E6018",
+        }
+    }
+
+    /// 诊断所在的文件、行与列
+    pub fn location(&self) -> (&str, usize, usize) {
+        match self {
+            PawError::Syntax { file, line, column, .. }
+            | PawError::Type { file, line, column, .. }
+            | PawError::UndefinedVariable { file, line, column, .. }
+            | PawError::DuplicateDefinition { file, line, column, .. }
+            | PawError::Runtime { file, line, column, .. }
+            | PawError::Custom { file, line, column, .. }
+            | PawError::Warning { file, line, column, .. }
+            | PawError::Internal { file, line, column, .. }
+            | PawError::Propagate { file, line, column, .. } => (file, *line, *column),
+        }
+    }
+
+    /// 附带的修复提示
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            PawError::Syntax { hint, .. }
+            | PawError::Type { hint, .. }
+            | PawError::UndefinedVariable { hint, .. }
+            | PawError::DuplicateDefinition { hint, .. }
+            | PawError::Runtime { hint, .. }
+            | PawError::Custom { hint, .. }
+            | PawError::Warning { hint, .. }
+            | PawError::Internal { hint, .. } => hint.as_deref(),
+            PawError::Propagate { .. } => None,
+        }
+    }
+
+    /// 运行时错误离开的函数调用，最内层在前；其它错误返回空切片
+    pub fn backtrace(&self) -> &[StackFrame] {
+        match self {
+            PawError::Runtime { backtrace, .. } | PawError::Custom { backtrace, .. } => backtrace,
+            _ => &[],
+        }
+    }
+}
+
 impl fmt::Display for PawError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -139,7 +249,7 @@ impl fmt::Display for PawError {
                 Ok(())
             }
 
-            PawError::Runtime { file, code, message, line, column, snippet, hint } => {
+            PawError::Runtime { file, code, message, line, column, snippet, hint, backtrace } => {
                 let file_hint = format!("{}:{}:{}", file, line, column);
                 writeln!(f, "🐾 [{}] Runtime Error in {} 🐾", code, file_hint.yellow().underline())?;
                 writeln!(f, "   💥 {}", message)?;
@@ -149,10 +259,10 @@ impl fmt::Display for PawError {
                 if let Some(h) = hint {
                     writeln!(f, "   💡 Hint: {}", h)?;
                 }
-                Ok(())
+                write_backtrace(f, backtrace)
             }
 
-            PawError::Custom { name, file, code, message, line, column, snippet, hint } => {
+            PawError::Custom { name, file, code, message, line, column, snippet, hint, backtrace, .. } => {
                 let file_hint = format!("{}:{}:{}", file, line, column);
                 writeln!(f, "🐾 [{}] {} Error in {} 🐾", code, name, file_hint.yellow().underline())?;
                 writeln!(f, "   💬 {}", message)?;
@@ -162,9 +272,28 @@ impl fmt::Display for PawError {
                 if let Some(h) = hint {
                     writeln!(f, "   💡 Hint: {}", h)?;
                 }
+                write_backtrace(f, backtrace)
+            }
+
+            PawError::Warning { file, code, message, line, column, snippet, hint } => {
+                let file_hint = format!("{}:{}:{}", file, line, column);
+                writeln!(f, "🐾 [{}] Warning in {} 🐾", code.yellow(), file_hint.yellow().underline())?;
+                writeln!(f, "   ⚠️  {}", message)?;
+                if let Some(src) = snippet {
+                    writeln!(f, "   📜 {}", src)?;
+                }
+                if let Some(h) = hint {
+                    writeln!(f, "   💡 Hint: {}", h)?;
+                }
                 Ok(())
             }
 
+            PawError::Propagate { file, line, column, .. } => {
+                let file_hint = format!("{}:{}:{}", file, line, column);
+                writeln!(f, "🐾 [{}] Runtime Error in {} 🐾", self.code(), file_hint.yellow().underline())?;
+                writeln!(f, "   💥 {}", self.message())
+            }
+
             PawError::Internal { file, code, message, line, column, snippet: _, hint } => {
                 let file_hint = format!("{}:{}:{}", file, line, column);
                 writeln!(f, "🐾 [{}] Internal Error in {} 🐾", code, file_hint.yellow().underline())?;
@@ -177,3 +306,22 @@ impl fmt::Display for PawError {
         }
     }
 }
+
+/// 输出调用栈，超过 MAX_SHOWN_FRAMES 的部分只给出省略的数量
+fn write_backtrace(f: &mut fmt::Formatter<'_>, backtrace: &[StackFrame]) -> fmt::Result {
+    if backtrace.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "   🧵 Call stack (most recent call first):")?;
+    for frame in backtrace.iter().take(MAX_SHOWN_FRAMES) {
+        writeln!(
+            f,
+            "      {} called at {}:{}:{}",
+            frame.function, frame.file, frame.line, frame.column
+        )?;
+    }
+    if backtrace.len() > MAX_SHOWN_FRAMES {
+        writeln!(f, "      … {} more", backtrace.len() - MAX_SHOWN_FRAMES)?;
+    }
+    Ok(())
+}
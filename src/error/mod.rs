@@ -1 +1,2 @@
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod panic;
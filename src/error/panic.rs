@@ -0,0 +1,44 @@
+// src/error/panic.rs
+//
+// 把解释器内部的 panic 转换为 PawError::Internal 报告，
+// 脚本使用者看到的是带位置的内部错误和反馈方式，而不是 Rust 的原始输出
+
+use crate::error::error::PawError;
+use std::panic::{self, PanicHookInfo};
+
+/// 安装 panic hook；设置了 `RUST_BACKTRACE` 时仍额外输出默认的 Rust 报告，便于调试
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        eprintln!("{}", internal_error(info));
+        if std::env::var_os("RUST_BACKTRACE").is_some_and(|v| v != "0") {
+            default_hook(info);
+        }
+    }));
+}
+
+/// 由 panic 信息构造内部错误，位置为 panic 发生处的 Rust 源码位置
+pub fn internal_error(info: &PanicHookInfo<'_>) -> PawError {
+    let payload = info.payload();
+    let reason = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let (file, line, column) = info
+        .location()
+        .map(|loc| (loc.file().to_string(), loc.line() as usize, loc.column() as usize))
+        .unwrap_or_default();
+    PawError::Internal {
+        file,
+        code: "E9000",
+        message: format!("pawc crashed unexpectedly: {}", reason),
+        line,
+        column,
+        snippet: None,
+        hint: Some(
+            "This is a bug in pawc, not in your script. Please report it with the script that triggered it at https://github.com/KinLeoapple/pawc/issues"
+                .into(),
+        ),
+    }
+}
@@ -0,0 +1,80 @@
+// src/fuzz.rs
+
+use crate::ast::statement::Statement;
+use crate::error::error::PawError;
+use crate::lexer::lexer::Lexer;
+use crate::lexer::token::Token;
+use crate::parser::parser::Parser;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+/// cargo-fuzz 入口：对任意字节做词法分析，不允许 panic
+pub fn fuzz_lex(data: &[u8]) -> Vec<Token> {
+    let src = String::from_utf8_lossy(data);
+    Lexer::new(&src).tokenize()
+}
+
+/// cargo-fuzz 入口：对任意字节做词法 + 语法分析
+/// 非法输入只能返回 `PawError::Syntax`，不允许 panic
+#[allow(clippy::result_large_err)] // 与 `eval_str` 等公开入口一样直接返回 PawError
+pub fn fuzz_parse(data: &[u8]) -> Result<Vec<Statement>, PawError> {
+    parse_bytes(data).map_err(|e| *e)
+}
+
+fn parse_bytes(data: &[u8]) -> Result<Vec<Statement>, Box<PawError>> {
+    let src = String::from_utf8_lossy(data);
+    let tokens = Lexer::new(&src).tokenize();
+    Parser::new(tokens, &src, "<fuzz>").parse_program()
+}
+
+/// 语料库中一个文件的语法分析结果
+#[derive(Debug)]
+pub enum CorpusOutcome {
+    /// 解析成功
+    Parsed,
+    /// 以语法错误拒绝
+    Rejected(PawError),
+    /// 语法分析器 panic，附带 panic 信息
+    Panicked(String),
+}
+
+/// 递归解析 `dir` 下的所有文件（按路径排序），文件内容按任意字节处理，与 `fuzz_parse` 一致
+///
+/// panic 会被捕获并记录为 `Panicked`；以 `panic = "abort"` 构建（如 release）时进程仍会直接终止。
+pub fn parse_corpus(dir: &Path) -> io::Result<Vec<(PathBuf, CorpusOutcome)>> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    files.sort();
+    let mut results = Vec::with_capacity(files.len());
+    for path in files {
+        let data = fs::read(&path)?;
+        let outcome = match panic::catch_unwind(|| parse_bytes(&data)) {
+            Ok(Ok(_)) => CorpusOutcome::Parsed,
+            Ok(Err(err)) => CorpusOutcome::Rejected(*err),
+            Err(payload) => {
+                let reason = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                CorpusOutcome::Panicked(reason)
+            }
+        };
+        results.push((path, outcome));
+    }
+    Ok(results)
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, out)?;
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+// src/highlight.rs
+//
+// 基于词法分析结果的语法高亮：输出带 ANSI 颜色的终端文本或 HTML。
+// 源码逐字保留，只在 Token 与注释外包上颜色，无法识别的字符原样输出。
+
+use crate::lexer::lexer::Lexer;
+use crate::lexer::token::TokenKind;
+
+/// 高亮输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// 终端 ANSI 转义序列
+    Ansi,
+    /// `<pre class="paw">` 包裹的 HTML，颜色由 `paw-*` CSS 类决定
+    Html,
+}
+
+/// 源码片段的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Keyword,
+    Type,
+    String,
+    Number,
+    Literal,
+    Operator,
+    Comment,
+    Error,
+    Plain,
+}
+
+impl Class {
+    fn of(kind: &TokenKind) -> Class {
+        match kind {
+            TokenKind::Keyword(_) => Class::Keyword,
+            TokenKind::Type(_) => Class::Type,
+            TokenKind::StringLiteral(_)
+            | TokenKind::InterpolatedString(_)
+            | TokenKind::CharLiteral(_) => Class::String,
+            TokenKind::IntLiteral(_)
+            | TokenKind::LongLiteral(_)
+            | TokenKind::FloatLiteral(_)
+            | TokenKind::DoubleLiteral(_) => Class::Number,
+            TokenKind::BoolLiteral(_) => Class::Literal,
+            TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Star
+            | TokenKind::StarStar
+            | TokenKind::Slash
+            | TokenKind::Percent
+            | TokenKind::EqEq
+            | TokenKind::NotEq
+            | TokenKind::Lt
+            | TokenKind::Gt
+            | TokenKind::Le
+            | TokenKind::Ge
+            | TokenKind::AndAnd
+            | TokenKind::OrOr
+            | TokenKind::Not
+            | TokenKind::Assign
+            | TokenKind::LeftArrow
+            | TokenKind::FatArrow
+            | TokenKind::Range
+            | TokenKind::RangeInclusive
+            | TokenKind::Question => Class::Operator,
+            TokenKind::Comment(_) => Class::Comment,
+            TokenKind::Error(_) => Class::Error,
+            _ => Class::Plain,
+        }
+    }
+
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Class::Keyword => Some("\x1b[35m"),
+            Class::Type => Some("\x1b[36m"),
+            Class::String => Some("\x1b[32m"),
+            Class::Number | Class::Literal => Some("\x1b[33m"),
+            Class::Operator => Some("\x1b[34m"),
+            Class::Comment => Some("\x1b[90m"),
+            Class::Error => Some("\x1b[4;31m"),
+            Class::Plain => None,
+        }
+    }
+
+    fn css(self) -> Option<&'static str> {
+        match self {
+            Class::Keyword => Some("paw-keyword"),
+            Class::Type => Some("paw-type"),
+            Class::String => Some("paw-string"),
+            Class::Number => Some("paw-number"),
+            Class::Literal => Some("paw-literal"),
+            Class::Operator => Some("paw-operator"),
+            Class::Comment => Some("paw-comment"),
+            Class::Error => Some("paw-error"),
+            Class::Plain => None,
+        }
+    }
+}
+
+/// 高亮一段源码
+pub fn highlight(src: &str, format: Format) -> String {
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = Output::new(format);
+    let mut pos = 0;
+    for (tok, span) in Lexer::new(src).tokenize_spanned() {
+        // Token 之间的空白与 `#` 注释
+        let start = span.start.min(chars.len());
+        out.gap(&chars[pos..start]);
+        let end = span.end.clamp(start, chars.len());
+        out.push(Class::of(&tok.kind), &chars[start..end]);
+        pos = end;
+        if matches!(tok.kind, TokenKind::Eof) {
+            break;
+        }
+    }
+    out.gap(&chars[pos..]);
+    out.finish()
+}
+
+struct Output {
+    format: Format,
+    buf: String,
+}
+
+impl Output {
+    fn new(format: Format) -> Self {
+        let buf = match format {
+            Format::Ansi => String::new(),
+            Format::Html => "<pre class=\"paw\"><code>".to_string(),
+        };
+        Output { format, buf }
+    }
+
+    /// 输出 Token 之间的片段，其中 `#` 到行尾是注释
+    fn gap(&mut self, text: &[char]) {
+        let mut rest = text;
+        while let Some(hash) = rest.iter().position(|c| *c == '#') {
+            self.push(Class::Plain, &rest[..hash]);
+            let eol = rest[hash..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(rest.len(), |i| hash + i);
+            self.push(Class::Comment, &rest[hash..eol]);
+            rest = &rest[eol..];
+        }
+        self.push(Class::Plain, rest);
+    }
+
+    fn push(&mut self, class: Class, text: &[char]) {
+        if text.is_empty() {
+            return;
+        }
+        let text: String = text.iter().collect();
+        match self.format {
+            Format::Ansi => match class.ansi() {
+                Some(color) => {
+                    self.buf.push_str(color);
+                    self.buf.push_str(&text);
+                    self.buf.push_str("\x1b[0m");
+                }
+                None => self.buf.push_str(&text),
+            },
+            Format::Html => match class.css() {
+                Some(css) => {
+                    self.buf.push_str(&format!("<span class=\"{}\">", css));
+                    self.buf.push_str(&escape_html(&text));
+                    self.buf.push_str("</span>");
+                }
+                None => self.buf.push_str(&escape_html(&text)),
+            },
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.format == Format::Html {
+            self.buf.push_str("</code></pre>\n");
+        }
+        self.buf
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,855 @@
+// src/interpreter/builtins.rs
+//
+// 内置函数：脚本中没有同名变量或函数时，按名字调用这里的实现
+
+use crate::ast::expr::{FormatAlign, FormatSpec};
+use crate::error::error::PawError;
+use crate::interpreter::inspect::{inspect, Limits};
+use crate::interpreter::interpreter::Engine;
+use crate::interpreter::property::Generator;
+use crate::interpreter::value::{Value, ValueInner};
+use crate::runtime;
+use crate::semantic::purity::IMPURE_BUILTINS;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use vuot::Stack;
+
+impl Engine {
+    /// 调用内置函数；`name` 不是内置函数时返回 None
+    pub(crate) async fn call_builtin(
+        &mut self,
+        stack: Stack<'_>,
+        name: &str,
+        args: Vec<Value>,
+        line: usize,
+        col: usize,
+    ) -> Option<Result<Value, Box<PawError>>> {
+        if IMPURE_BUILTINS.contains(&name) {
+            if let Err(e) = self.check_writable(&format!("call '{}'", name), line, col) {
+                return Some(Err(e));
+            }
+        }
+        match name {
+            "memoize" => Some(self.builtin_memoize(args, line, col)),
+            "range" => Some(self.builtin_range(args, line, col)),
+            "zip" => Some(self.builtin_zip(args, line, col)),
+            "repeat" => Some(self.builtin_repeat(args, line, col)),
+            "sleep" => Some(self.builtin_sleep(args, line, col).await),
+            "format" => Some(self.builtin_format(args, line, col)),
+            "inspect" => Some(self.builtin_inspect(args, line, col)),
+            "assert" => Some(self.builtin_assert(args, line, col)),
+            "assert_snapshot" => Some(self.builtin_assert_snapshot(args, line, col)),
+            "assert_eq" | "assert_ne" => Some(self.builtin_assert_eq(name, args, line, col)),
+            "assert_close" => Some(self.builtin_assert_close(args, line, col)),
+            "assert_throws" => Some(self.builtin_assert_throws(stack, args, line, col).await),
+            "gen_int" | "gen_double" | "gen_bool" | "gen_string" | "gen_array" | "gen_one_of" | "gen_pair" => {
+                Some(self.builtin_generator(name, args, line, col))
+            }
+            "forall" => Some(self.builtin_forall(stack, args, line, col).await),
+            "mock_ask" | "mock_time" | "seed_random" => Some(self.builtin_mock(name, args, line, col)),
+            "ok" => Some(Ok(Value::Ok(args.into_iter().next().unwrap_or_else(Value::Null)))),
+            "err" => Some(Ok(Value::Err(args.into_iter().next().unwrap_or_else(Value::Null)))),
+            _ => None,
+        }
+    }
+
+    /// `memoize(f)`：返回按参数缓存 `f` 返回值的函数，已缓存的函数原样返回
+    fn builtin_memoize(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let func = args.into_iter().next().unwrap_or_else(Value::Null);
+        match &*func.0 {
+            ValueInner::Memoized { .. } => Ok(func),
+            ValueInner::Function { .. } | ValueInner::NativeFunction { .. } => {
+                Ok(Value::Memoized(func))
+            }
+            _ => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E4002",
+                message: format!("memoize expects a function, found {}", func.type_name()),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+        }
+    }
+
+    /// `range(end)` / `range(start, end)`：从 start（默认 0）到 end（不含）的 Int 数组
+    fn builtin_range(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let mut bounds = Vec::with_capacity(2);
+        for arg in &args {
+            match &*arg.0 {
+                ValueInner::Int(n) => bounds.push(*n),
+                _ => return Err(self.builtin_arg_error("range", "Int", arg, line, col)),
+            }
+        }
+        let (start, end) = match bounds[..] {
+            [end] => (0, end),
+            [start, end] => (start, end),
+            _ => (0, 0),
+        };
+        let len = (i64::from(end) - i64::from(start)).max(0) as usize;
+        self.check_collection_size(len, line, col)?;
+        Ok(Value::Array((start..end).map(Value::Int).collect()))
+    }
+
+    /// `zip(a, b)`：按下标配对两个数组的元素，长度取较短者
+    fn builtin_zip(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let mut arrays = Vec::with_capacity(2);
+        for arg in &args {
+            match &*arg.0 {
+                ValueInner::Array(items) => arrays.push(items.clone()),
+                _ => return Err(self.builtin_arg_error("zip", "an Array", arg, line, col)),
+            }
+        }
+        let pairs = arrays[0]
+            .iter()
+            .zip(arrays[1].iter())
+            .map(|(a, b)| Value::Array(vec![a.clone(), b.clone()]))
+            .collect();
+        Ok(Value::Array(pairs))
+    }
+
+    /// `repeat(value, n)`：由 n 个 value 组成的数组
+    fn builtin_repeat(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let n = match &*args[1].0 {
+            ValueInner::Int(n) => *n,
+            _ => return Err(self.builtin_arg_error("repeat", "Int", &args[1], line, col)),
+        };
+        if n < 0 {
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6013",
+                message: format!("repeat count must not be negative, found {}", n),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            }));
+        }
+        self.check_collection_size(n as usize, line, col)?;
+        Ok(Value::Array(vec![args[0].clone(); n as usize]))
+    }
+
+    /// `inspect(value)` / `inspect(value, depth)` / `inspect(value, depth, width)`：
+    /// 带类型、逐行缩进的调试文本，depth 限制展开的容器层数，width 限制每个容器列出的元素个数
+    fn builtin_inspect(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let mut limits = Limits::default();
+        for (i, arg) in args.iter().enumerate().skip(1) {
+            let n = match &*arg.0 {
+                ValueInner::Int(n) => *n,
+                _ => return Err(self.builtin_arg_error("inspect", "Int", arg, line, col)),
+            };
+            let what = if i == 1 { "depth" } else { "width" };
+            if n < 0 {
+                return Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E6013",
+                    message: format!("inspect {} must not be negative, found {}", what, n),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: None,
+                    backtrace: Vec::new(),
+                }));
+            }
+            if i == 1 {
+                limits.depth = n as usize;
+            } else {
+                limits.width = n as usize;
+            }
+        }
+        Ok(Value::String(inspect(&args[0], limits)))
+    }
+
+    /// `assert(condition)` / `assert(condition, message)`：条件不成立时报 E6024
+    fn builtin_assert(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let holds = match &*args[0].0 {
+            ValueInner::Bool(b) => *b,
+            _ => return Err(self.builtin_arg_error("assert", "Bool", &args[0], line, col)),
+        };
+        if holds {
+            return Ok(Value::Null());
+        }
+        let message = match args.get(1).map(|v| &*v.0) {
+            Some(ValueInner::String(s)) => format!("Assertion failed: {}", s),
+            Some(_) => return Err(self.builtin_arg_error("assert", "String", &args[1], line, col)),
+            None => "Assertion failed".to_string(),
+        };
+        Err(Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6024",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint: None,
+            backtrace: Vec::new(),
+        }))
+    }
+
+    /// `assert_eq(actual, expected)` / `assert_ne(actual, expected)`，可附带说明：按 `==` 比较，不成立时报 E6024。
+    /// 两个值都按 `inspect` 展示，占多行时在提示中给出逐行差异
+    fn builtin_assert_eq(&self, name: &str, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let note = self.assertion_note(name, args.get(2), line, col)?;
+        let (actual, expected) = (&args[0], &args[1]);
+        if (actual == expected) == (name == "assert_eq") {
+            return Ok(Value::Null());
+        }
+        let full = Limits { depth: usize::MAX, width: usize::MAX };
+        let (shown_actual, shown_expected) = (inspect(actual, full), inspect(expected, full));
+        let (detail, hint) = if name == "assert_ne" {
+            match shown_actual.contains('\n') {
+                false => (format!("both values are {}", shown_actual), None),
+                true => ("both values are equal".to_string(), Some(format!("Both values are:\n{}", shown_actual))),
+            }
+        } else if !shown_actual.contains('\n') && !shown_expected.contains('\n') {
+            (format!("expected {}, found {}", shown_expected, shown_actual), None)
+        } else {
+            (
+                "values are not equal".to_string(),
+                Some(format!("Difference (- expected, + actual):\n{}", line_diff(&shown_expected, &shown_actual))),
+            )
+        };
+        Err(self.assertion_error(note, detail, hint, line, col))
+    }
+
+    /// `assert_close(actual, expected, eps)`，可附带说明：两个数相差超过 eps（或有 NaN）时报 E6024
+    fn builtin_assert_close(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let note = self.assertion_note("assert_close", args.get(3), line, col)?;
+        let number = |v: &Value| match &*v.0 {
+            ValueInner::Int(n) => Ok(*n as f64),
+            ValueInner::Long(n) => Ok(*n as f64),
+            ValueInner::Float(n) => Ok(*n as f64),
+            ValueInner::Double(n) => Ok(*n),
+            _ => Err(self.builtin_arg_error("assert_close", "Double", v, line, col)),
+        };
+        let (actual, expected, eps) = (number(&args[0])?, number(&args[1])?, number(&args[2])?);
+        if eps.is_nan() || eps < 0.0 {
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6013",
+                message: format!("assert_close tolerance must not be negative, found {}", args[2]),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            }));
+        }
+        let off = (actual - expected).abs();
+        if off <= eps {
+            return Ok(Value::Null());
+        }
+        let detail = format!("expected {} ± {}, found {} (off by {})", args[1], args[2], args[0], off);
+        Err(self.assertion_error(note, detail, None, line, col))
+    }
+
+    /// `assert_throws(f)`，可附带说明：调用无参函数 f，它必须 bark 或出现运行时错误，返回错误信息；
+    /// f 正常返回时报 E6024
+    async fn builtin_assert_throws(
+        &mut self,
+        stack: Stack<'_>,
+        args: Vec<Value>,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        let note = self.assertion_note("assert_throws", args.get(1), line, col)?;
+        match self.call_function(stack, &args[0], Vec::new(), "assert_throws", line, col).await {
+            Ok(v) => {
+                let detail = match &*v.0 {
+                    ValueInner::Null => "expected the function to bark, but it returned normally".to_string(),
+                    _ => format!("expected the function to bark, but it returned {}", show_input(&v)),
+                };
+                Err(self.assertion_error(note, detail, None, line, col))
+            }
+            Err(err) if matches!(*err, PawError::Runtime { .. } | PawError::Custom { .. }) => {
+                Ok(Value::String(err.message()))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// 断言的可选说明参数
+    fn assertion_note(&self, name: &str, note: Option<&Value>, line: usize, col: usize) -> Result<Option<String>, Box<PawError>> {
+        match note {
+            None => Ok(None),
+            Some(v) => match &*v.0 {
+                ValueInner::String(s) => Ok(Some(s.to_string())),
+                _ => Err(self.builtin_arg_error(name, "String", v, line, col)),
+            },
+        }
+    }
+
+    /// 断言失败（E6024）：有说明时说明在前，接着是具体原因
+    fn assertion_error(&self, note: Option<String>, detail: String, hint: Option<String>, line: usize, col: usize) -> Box<PawError> {
+        let message = match note {
+            Some(note) => format!("Assertion failed: {}: {}", note, detail),
+            None => format!("Assertion failed: {}", detail),
+        };
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6024",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint,
+            backtrace: Vec::new(),
+        })
+    }
+
+    /// `assert_snapshot(name, value)`：把值与脚本旁 `__snapshots__/<脚本名>.<name>.snap` 中保存的内容比较
+    ///
+    /// 字符串按原样保存，其它值保存为不限层数与宽度的 `inspect` 文本。快照不存在时写入并通过；
+    /// 内容不同时报 E6025，开启 `update_snapshots` 时改为覆盖快照
+    fn builtin_assert_snapshot(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let name = match &*args[0].0 {
+            ValueInner::String(s) => s.clone(),
+            _ => return Err(self.builtin_arg_error("assert_snapshot", "String", &args[0], line, col)),
+        };
+        let error = |code: &'static str, message: String, hint: Option<String>| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code,
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint,
+            backtrace: Vec::new(),
+        });
+        let valid = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+        if name.is_empty() || !name.chars().all(valid) {
+            return Err(error(
+                "E6013",
+                format!("Invalid snapshot name '{}'", name),
+                Some("Use letters, digits, `_`, `-` and `.`".into()),
+            ));
+        }
+        self.check_sandbox("Writing snapshots", line, col)?;
+        let actual = match &*args[1].0 {
+            ValueInner::String(s) => s.to_string(),
+            _ => inspect(&args[1], Limits { depth: usize::MAX, width: usize::MAX }),
+        };
+        let script = Path::new(&self.file);
+        let stem = script.file_stem().map_or("script".into(), |s| s.to_string_lossy());
+        let path = script
+            .with_file_name("__snapshots__")
+            .join(format!("{}.{}.snap", stem, name));
+        let io_error = |e: std::io::Error| {
+            error("E6013", format!("Failed to access snapshot '{}': {}", path.display(), e), None)
+        };
+        match fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => return Ok(Value::Null()),
+            Ok(expected) if !self.update_snapshots => {
+                return Err(error(
+                    "E6025",
+                    format!("Snapshot '{}' does not match {}", name, path.display()),
+                    Some(format!(
+                        "{}; run `pawc test --update-snapshots` to accept the new value",
+                        first_difference(&expected, &actual)
+                    )),
+                ))
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(io_error(e)),
+            Err(_) => {}
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        fs::write(&path, actual).map_err(io_error)?;
+        Ok(Value::Null())
+    }
+
+    /// `mock_ask(answers)`、`mock_time(millis)`、`seed_random(seed)`：让 `ask`、`time` 与 `random`
+    /// 在本次运行中得到确定的结果
+    fn builtin_mock(&self, name: &str, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let number = |v: &Value| match &*v.0 {
+            ValueInner::Int(n) => Ok(*n as i64),
+            ValueInner::Long(n) => Ok(*n),
+            _ => Err(self.builtin_arg_error(name, "Long", v, line, col)),
+        };
+        match name {
+            "mock_ask" => {
+                let ValueInner::Array(items) = &*args[0].0 else {
+                    return Err(self.builtin_arg_error(name, "Array<String>", &args[0], line, col));
+                };
+                let mut answers = Vec::with_capacity(items.len());
+                for item in items.iter() {
+                    match &*item.0 {
+                        ValueInner::String(s) => answers.push(s.to_string()),
+                        _ => return Err(self.builtin_arg_error(name, "Array<String>", &args[0], line, col)),
+                    }
+                }
+                self.host.script_answers(answers);
+            }
+            "mock_time" => self.host.set_clock(number(&args[0])?),
+            _ => self.host.seed_random(number(&args[0])? as u64),
+        }
+        Ok(Value::Null())
+    }
+
+    /// `gen_int(lo, hi)` 等：创建属性测试的生成器，范围为空或长度为负时报 E6013
+    fn builtin_generator(&self, name: &str, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let invalid = |message: String| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6013",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint: None,
+            backtrace: Vec::new(),
+        });
+        let int = |i: usize| match &*args[i].0 {
+            ValueInner::Int(n) => Ok(*n),
+            _ => Err(self.builtin_arg_error(name, "Int", &args[i], line, col)),
+        };
+        let number = |i: usize| match &*args[i].0 {
+            ValueInner::Int(n) => Ok(*n as f64),
+            ValueInner::Long(n) => Ok(*n as f64),
+            ValueInner::Float(f) => Ok(*f as f64),
+            ValueInner::Double(f) => Ok(*f),
+            _ => Err(self.builtin_arg_error(name, "Double", &args[i], line, col)),
+        };
+        let max_len = |i: usize| {
+            let n = int(i)?;
+            usize::try_from(n).map_err(|_| invalid(format!("{} length must not be negative, found {}", name, n)))
+        };
+        let generator = |i: usize| match &*args[i].0 {
+            ValueInner::Generator(g) => Ok((**g).clone()),
+            _ => Err(self.builtin_arg_error(name, "Gen", &args[i], line, col)),
+        };
+        let gen = match name {
+            "gen_int" => {
+                let (lo, hi) = (int(0)?, int(1)?);
+                if lo > hi {
+                    return Err(invalid(format!("gen_int expects lo <= hi, found {} and {}", lo, hi)));
+                }
+                Generator::Int(lo, hi)
+            }
+            "gen_double" => {
+                let (lo, hi) = (number(0)?, number(1)?);
+                if !(lo.is_finite() && hi.is_finite() && lo <= hi) {
+                    return Err(invalid(format!("gen_double expects finite lo <= hi, found {} and {}", lo, hi)));
+                }
+                Generator::Double(lo, hi)
+            }
+            "gen_bool" => Generator::Bool,
+            "gen_string" => Generator::String(max_len(0)?),
+            "gen_array" => Generator::Array(Box::new(generator(0)?), max_len(1)?),
+            "gen_one_of" => match &*args[0].0 {
+                ValueInner::Array(items) if items.is_empty() => {
+                    return Err(invalid("gen_one_of needs at least one value".into()));
+                }
+                ValueInner::Array(items) => Generator::OneOf((**items).clone()),
+                _ => return Err(self.builtin_arg_error(name, "Array", &args[0], line, col)),
+            },
+            _ => Generator::Pair(Box::new(generator(0)?), Box::new(generator(1)?)),
+        };
+        Ok(Value::from_inner(ValueInner::Generator(Arc::new(gen))))
+    }
+
+    /// `forall(gen, property)` / `forall(gen, property, runs)`：用生成的 runs 个（默认 100）输入调用属性。
+    /// 属性返回 false、bark 或出现运行时错误即为失败；失败的输入会被缩小到仍然失败的最简单输入，
+    /// 然后报 E6026
+    async fn builtin_forall(
+        &mut self,
+        stack: Stack<'_>,
+        args: Vec<Value>,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        let ValueInner::Generator(gen) = &*args[0].0 else {
+            return Err(self.builtin_arg_error("forall", "Gen", &args[0], line, col));
+        };
+        let runs = match args.get(2).map(|v| &*v.0) {
+            None => 100,
+            Some(ValueInner::Int(n)) if *n >= 0 => *n as usize,
+            Some(ValueInner::Int(n)) => {
+                return Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E6013",
+                    message: format!("forall runs must not be negative, found {}", n),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: None,
+                    backtrace: Vec::new(),
+                }));
+            }
+            Some(_) => return Err(self.builtin_arg_error("forall", "Int", &args[2], line, col)),
+        };
+        let property = &args[1];
+        for run in 1..=runs {
+            let input = gen.generate(&mut *self.host.rng());
+            let Some(reason) = self.check_property(stack, property, &input, line, col).await? else {
+                continue;
+            };
+            // 依次尝试更简单的候选，换成第一个仍然失败的，直到没有候选失败或尝试次数用完
+            let (mut smallest, mut reason, mut steps, mut tries) = (input.clone(), reason, 0, 0);
+            'shrink: while tries < MAX_SHRINK_TRIES {
+                for candidate in gen.shrink(&smallest) {
+                    tries += 1;
+                    if let Some(why) = self.check_property(stack, property, &candidate, line, col).await? {
+                        (smallest, reason, steps) = (candidate, why, steps + 1);
+                        continue 'shrink;
+                    }
+                    if tries >= MAX_SHRINK_TRIES {
+                        break;
+                    }
+                }
+                break;
+            }
+            let shrunk = match steps {
+                0 => String::new(),
+                _ => format!(" (shrunk from {} in {} step(s))", show_input(&input), steps),
+            };
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6026",
+                message: format!(
+                    "Property failed after {} run(s) for input {}{}",
+                    run,
+                    show_input(&smallest),
+                    shrunk
+                ),
+                line,
+                column: col,
+                snippet: None,
+                hint: Some(reason),
+                backtrace: Vec::new(),
+            }));
+        }
+        Ok(Value::Null())
+    }
+
+    /// 用一个输入调用属性：通过时为 None，失败时为失败原因；bark 与运行时错误算作失败，其它错误照常传出
+    async fn check_property(
+        &mut self,
+        stack: Stack<'_>,
+        property: &Value,
+        input: &Value,
+        line: usize,
+        col: usize,
+    ) -> Result<Option<String>, Box<PawError>> {
+        match self.call_function(stack, property, vec![input.clone()], "forall property", line, col).await {
+            Ok(v) if matches!(&*v.0, ValueInner::Bool(false)) => Ok(Some("The property returned false".into())),
+            Ok(_) => Ok(None),
+            Err(err) if matches!(*err, PawError::Runtime { .. } | PawError::Custom { .. }) => {
+                Ok(Some(format!("The property failed: {}", err.message())))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// `sleep(ms)`：暂停当前任务 ms 毫秒；等待期间不占用执行线程，其它任务照常运行
+    async fn builtin_sleep(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let ms = match &*args[0].0 {
+            ValueInner::Int(n) => *n,
+            _ => return Err(self.builtin_arg_error("sleep", "Int", &args[0], line, col)),
+        };
+        if ms < 0 {
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6013",
+                message: format!("sleep duration must not be negative, found {}", ms),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            }));
+        }
+        runtime::sleep(Duration::from_millis(ms as u64)).await;
+        self.check_cancelled(line, col)?;
+        Ok(Value::Null())
+    }
+
+    /// 按格式说明输出值，供 `${value:spec}` 与 `format` 使用；类型不适用时报 E6013
+    pub(crate) fn format_value(
+        &self,
+        spec: &FormatSpec,
+        value: &Value,
+        line: usize,
+        col: usize,
+    ) -> Result<String, Box<PawError>> {
+        apply_format_spec(spec, value).map_err(|message| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6013",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("`+` and `0` apply to numbers; a precision applies to Float, Double and String".into()),
+            backtrace: Vec::new(),
+        }))
+    }
+
+    /// `format(template, values...)`：把模板中的 `{}` / `{n}` / `{:spec}` 依次替换为各个值的输出形式
+    fn builtin_format(&self, args: Vec<Value>, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let template = match args.first().map(|v| &*v.0) {
+            Some(ValueInner::String(s)) => s.clone(),
+            _ => {
+                let found = args.first().cloned().unwrap_or_else(Value::Null);
+                return Err(self.builtin_arg_error("format", "String", &found, line, col));
+            }
+        };
+        let values = &args[1..];
+        let format_error = |message: String| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6013",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("Write `{{` and `}}` for literal braces".into()),
+            backtrace: Vec::new(),
+        });
+        let pieces = parse_format(&template).map_err(format_error)?;
+        let used = placeholder_count(&pieces);
+        if used != values.len() {
+            return Err(format_error(format!(
+                "Format string expects {} value(s), found {}",
+                used,
+                values.len()
+            )));
+        }
+        let mut out = String::with_capacity(template.len());
+        for piece in pieces {
+            match piece {
+                FormatPiece::Text(text) => out.push_str(&text),
+                FormatPiece::Arg(i, None) => out.push_str(&values[i].to_string()),
+                FormatPiece::Arg(i, Some(spec)) => out.push_str(&self.format_value(&spec, &values[i], line, col)?),
+            }
+        }
+        Ok(Value::String(out))
+    }
+
+    /// 参数类型只能在运行时确定（如 Any）时的类型错误
+    fn builtin_arg_error(
+        &self,
+        name: &str,
+        expected: &str,
+        found: &Value,
+        line: usize,
+        col: usize,
+    ) -> Box<PawError> {
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6013",
+            message: format!(
+                "Argument to '{}' has type {}, expected {}",
+                name,
+                found.type_name(),
+                expected
+            ),
+            line,
+            column: col,
+            snippet: None,
+            hint: None,
+            backtrace: Vec::new(),
+        })
+    }
+}
+
+/// `format` 模板解析后的片段
+pub(crate) enum FormatPiece {
+    Text(String),
+    /// 第几个被替换的值（从 0 开始，不含模板本身）及其格式说明
+    Arg(usize, Option<FormatSpec>),
+}
+
+/// 解析 `format` 的模板：`{}` 依次取下一个值，`{n}` 取第 n 个值，`{{`、`}}` 表示花括号本身；
+/// 冒号之后是格式说明，如 `{:08.3}`、`{1:>6}`
+pub(crate) fn parse_format(template: &str) -> Result<Vec<FormatPiece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut digits = String::new();
+                let mut spec = None;
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(d) if d.is_ascii_digit() => digits.push(d),
+                        Some(':') => {
+                            let mut text = String::new();
+                            loop {
+                                match chars.next() {
+                                    Some('}') => break,
+                                    Some(c) => text.push(c),
+                                    None => return Err("Unclosed '{' in format string".into()),
+                                }
+                            }
+                            spec = Some(FormatSpec::parse(&text)?);
+                            break;
+                        }
+                        Some(other) => {
+                            return Err(format!("Invalid character '{}' in format placeholder", other))
+                        }
+                        None => return Err("Unclosed '{' in format string".into()),
+                    }
+                }
+                let index = if digits.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    digits
+                        .parse()
+                        .map_err(|_| format!("Placeholder index {} is too large", digits))?
+                };
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(FormatPiece::Arg(index, spec));
+            }
+            '}' => return Err("Unmatched '}' in format string".into()),
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// 模板需要的值的个数：最大下标加一
+pub(crate) fn placeholder_count(pieces: &[FormatPiece]) -> usize {
+    pieces
+        .iter()
+        .filter_map(|p| match p {
+            FormatPiece::Arg(i, _) => Some(i + 1),
+            FormatPiece::Text(_) => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// 按格式说明输出值：`+`、`0` 只用于数字，精度只用于浮点数（小数位数）与字符串（最多字符数）；
+/// 未指定对齐时数字靠右、其余靠左
+pub(crate) fn apply_format_spec(spec: &FormatSpec, value: &Value) -> Result<String, String> {
+    let value = match &*value.0 {
+        ValueInner::Optional(o) => (**o).clone().unwrap_or_else(Value::Null),
+        _ => value.clone(),
+    };
+    let numeric = matches!(
+        &*value.0,
+        ValueInner::Int(_) | ValueInner::Long(_) | ValueInner::Float(_) | ValueInner::Double(_)
+    );
+    if (spec.sign || spec.zero) && !numeric {
+        return Err(format!("Format spec `{}` needs a number, found {}", spec, value.type_name()));
+    }
+    let body = match (&*value.0, spec.precision) {
+        (ValueInner::Float(f), Some(p)) => format!("{:.*}", p, f),
+        (ValueInner::Double(d), Some(p)) => format!("{:.*}", p, d),
+        (ValueInner::String(s), Some(p)) => s.chars().take(p).collect(),
+        (_, Some(_)) => {
+            return Err(format!(
+                "Precision in format spec `{}` needs a Float, Double or String, found {}",
+                spec,
+                value.type_name()
+            ))
+        }
+        _ => value.to_string(),
+    };
+    let (sign, digits) = match body.strip_prefix('-') {
+        Some(rest) if numeric => ("-", rest),
+        _ if spec.sign => ("+", body.as_str()),
+        _ => ("", body.as_str()),
+    };
+    let len = sign.len() + digits.chars().count();
+    if len >= spec.width {
+        return Ok(format!("{}{}", sign, digits));
+    }
+    let pad = spec.width - len;
+    if spec.zero && spec.align.is_none() {
+        return Ok(format!("{}{}{}", sign, "0".repeat(pad), digits));
+    }
+    let default = if numeric { FormatAlign::Right } else { FormatAlign::Left };
+    let (left, right) = match spec.align.unwrap_or(default) {
+        FormatAlign::Left => (0, pad),
+        FormatAlign::Right => (pad, 0),
+        FormatAlign::Center => (pad / 2, pad - pad / 2),
+    };
+    let fill = spec.fill.unwrap_or(' ').to_string();
+    Ok(format!("{}{}{}{}", fill.repeat(left), sign, digits, fill.repeat(right)))
+}
+
+/// `forall` 缩小失败输入时最多尝试的候选个数
+const MAX_SHRINK_TRIES: usize = 2000;
+
+/// 失败信息中的输入：字符串带引号与转义，其余按 `say` 的形式
+fn show_input(value: &Value) -> String {
+    match &*value.0 {
+        ValueInner::String(s) => format!("{:?}", s.as_str()),
+        _ => value.to_string(),
+    }
+}
+
+/// 快照与新值第一处不同的行，用于错误提示
+fn first_difference(expected: &str, actual: &str) -> String {
+    let (mut old, mut new) = (expected.lines(), actual.lines());
+    let mut line = 1;
+    loop {
+        match (old.next(), new.next()) {
+            (Some(a), Some(b)) if a == b => line += 1,
+            (None, None) => return "The values differ only in line endings".to_string(),
+            (a, b) => {
+                let show = |l: Option<&str>| l.map_or("<end of value>".to_string(), |l| format!("`{}`", l));
+                return format!("Line {}: expected {}, found {}", line, show(a), show(b));
+            }
+        }
+    }
+}
+
+/// 两段文本的逐行差异（按最长公共子序列对齐）：相同的行以两个空格开头，
+/// 只在 expected 中的行以 `- ` 开头，只在 actual 中的行以 `+ ` 开头。行数过多时只给出第一处不同
+fn line_diff(expected: &str, actual: &str) -> String {
+    let (old, new): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return first_difference(expected, actual);
+    }
+    // lcs[i][j]：old[i..] 与 new[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+    let (mut i, mut j, mut out) = (0, 0, Vec::new());
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push(format!("  {}", old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    out.join("\n")
+}
+
+/// `line_diff` 对齐表的最大格数，超过时退回只给出第一处不同
+const MAX_DIFF_CELLS: usize = 1_000_000;
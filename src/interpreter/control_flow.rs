@@ -0,0 +1,47 @@
+// src/interpreter/control_flow.rs
+
+use crate::error::error::PawError;
+use crate::interpreter::value::Value;
+
+/// 语句执行后的控制流信号
+#[derive(Debug, Clone)]
+pub enum ControlFlow {
+    /// 顺序执行下一条语句
+    Normal,
+    /// 从当前函数返回
+    Return(Value),
+    /// 跳出最近一层循环，或带该标签的循环
+    Break(Option<String>),
+    /// 进入最近一层循环的下一轮，或带该标签的循环的下一轮
+    Continue(Option<String>),
+}
+
+impl ControlFlow {
+    /// 带 `target` 标签的 break / continue 是否作用于标签为 `label` 的循环；不带标签时作用于最近一层
+    pub fn aimed_at(target: &Option<String>, label: &Option<String>) -> bool {
+        target.is_none() || target == label
+    }
+
+    pub fn is_normal(&self) -> bool {
+        matches!(self, ControlFlow::Normal)
+    }
+
+    /// 到达函数边界时取出返回值，break/continue 不会越过函数
+    pub fn into_return(self) -> Option<Value> {
+        match self {
+            ControlFlow::Return(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// 函数体执行完毕时调用：`e?` 带出的 err(...) 在函数边界变回普通的 return
+    pub fn catch_propagated(res: Result<ControlFlow, Box<PawError>>) -> Result<ControlFlow, Box<PawError>> {
+        match res {
+            Err(err) => match *err {
+                PawError::Propagate { value, .. } => Ok(ControlFlow::Return(value)),
+                other => Err(Box::new(other)),
+            },
+            ok => ok,
+        }
+    }
+}
@@ -4,61 +4,133 @@ use crate::error::error::PawError;
 use crate::interpreter::value::{Value, ValueInner};
 use ahash::AHashMap;
 use parking_lot::RwLock;
+use std::fmt;
 use std::sync::Arc;
 
-/// 对外的环境句柄
-#[derive(Clone, Debug)]
-pub struct Env(Arc<RwLock<AHashMap<String, Value>>>);
+/// 一层作用域：自身的绑定加上指向外层作用域的链接
+struct Frame {
+    vars: RwLock<AHashMap<String, Value>>,
+    parent: Option<Env>,
+}
+
+/// 对外的环境句柄；克隆只复制引用，多个句柄共享同一层作用域
+#[derive(Clone)]
+pub struct Env(Arc<Frame>);
+
+impl fmt::Debug for Env {
+    // 只列出变量名：函数值会捕获定义它的环境，完整打印会无限递归
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<String> = self.0.vars.read().keys().cloned().collect();
+        names.sort();
+        f.debug_struct("Env")
+            .field("vars", &names)
+            .field("parent", &self.0.parent)
+            .finish()
+    }
+}
 
 impl Env {
     /// 创建一个全新空环境
     pub fn new() -> Self {
-        Env(Arc::new(RwLock::new(AHashMap::new())))
+        Env(Arc::new(Frame {
+            vars: RwLock::new(AHashMap::new()),
+            parent: None,
+        }))
     }
 
-    /// 基于父环境创建一个新环境（浅拷贝所有现有绑定）
+    /// 创建一个内层作用域：查找和赋值会沿链向外，新定义只进入内层
     pub fn with_parent(parent: &Env) -> Self {
-        // parking_lot::RwLock::read() 直接返回 guard，无需 unwrap
-        let map = parent.0.read().clone();
-        Env(Arc::new(RwLock::new(map)))
+        Env(Arc::new(Frame {
+            vars: RwLock::new(AHashMap::new()),
+            parent: Some(parent.clone()),
+        }))
     }
 
-    /// 定义或覆盖一个变量
+    /// 在当前作用域定义或覆盖一个变量
     pub fn define(&self, key: String, val: Value) {
-        let mut w = self.0.write();
-        w.insert(key, val);
+        self.0.vars.write().insert(key, val);
+    }
+
+    /// 复制整条作用域链：之后对副本的定义与赋值不会影响原环境
+    pub fn snapshot(&self) -> Env {
+        Env(Arc::new(Frame {
+            vars: RwLock::new(self.bindings()),
+            parent: self.0.parent.as_ref().map(Env::snapshot),
+        }))
     }
 
-    /// 导出当前所有绑定
+    /// 导出当前作用域自身的绑定，不含外层作用域
     pub fn bindings(&self) -> AHashMap<String, Value> {
-        self.0.read().clone()
+        self.0.vars.read().clone()
     }
 
-    /// 更新已存在变量，否则报错
-    pub fn assign(&self, key: &str, val: Value) -> Result<(), PawError> {
-        let mut w = self.0.write();
-        if w.contains_key(key) {
-            w.insert(key.to_string(), val);
-            Ok(())
-        } else {
-            Err(PawError::UndefinedVariable {
-                file: "<runtime>".into(),
-                code: "E4001",
-                name: key.into(),
-                line: 0,
-                column: 0,
-                snippet: None,
-                hint: Some("Did you declare this variable before use?".into()),
-            })
+    /// 更新最近一层定义了该变量的作用域，都没有定义时在给定位置报 E4001
+    pub fn assign(
+        &self,
+        key: &str,
+        val: Value,
+        file: &str,
+        line: usize,
+        column: usize,
+    ) -> Result<(), Box<PawError>> {
+        let mut env = self;
+        loop {
+            {
+                let mut w = env.0.vars.write();
+                if let Some(slot) = w.get_mut(key) {
+                    *slot = val;
+                    return Ok(());
+                }
+            }
+            match &env.0.parent {
+                Some(parent) => env = parent,
+                None => {
+                    return Err(Box::new(PawError::UndefinedVariable {
+                        file: file.into(),
+                        code: "E4001",
+                        name: key.into(),
+                        line,
+                        column,
+                        snippet: None,
+                        hint: Some("Did you declare this variable before use?".into()),
+                    }))
+                }
+            }
+        }
+    }
+
+    /// 在最近一层定义了该变量的作用域中原地修改它的值；变量不存在时返回 None
+    pub fn update<R>(&self, key: &str, f: impl FnOnce(&mut Value) -> R) -> Option<R> {
+        let mut env = self;
+        loop {
+            {
+                let mut w = env.0.vars.write();
+                if let Some(slot) = w.get_mut(key) {
+                    return Some(f(slot));
+                }
+            }
+            env = env.0.parent.as_ref()?;
         }
     }
 
+    /// 只在当前作用域中查找变量，不看外层作用域
+    pub fn get_local(&self, key: &str) -> Option<Value> {
+        self.0.vars.read().get(key).cloned()
+    }
+
+    /// 由内向外查找变量
     pub fn get(&self, key: &str) -> Option<Value> {
-        self.0.read().get(key).cloned()
+        let mut env = self;
+        loop {
+            if let Some(v) = env.0.vars.read().get(key) {
+                return Some(v.clone());
+            }
+            env = env.0.parent.as_ref()?;
+        }
     }
 
     /// 对单个值执行一元运算
-    pub fn unary_op(&self, op: &str, v: Value, file: &str) -> Result<Value, PawError> {
+    pub fn unary_op(&self, op: &str, v: Value, file: &str) -> Result<Value, Box<PawError>> {
         match v {
             Value(inner) => match op {
                 // 负号
@@ -66,38 +138,40 @@ impl Env {
                     ValueInner::Int(i) => Ok(Value::Int(-i)),
                     ValueInner::Long(l) => Ok(Value::Long(-l)),
                     ValueInner::Float(f) => Ok(Value::Float(-f)),
-                    other => Err(PawError::Runtime {
+                    other => Err(Box::new(PawError::Runtime {
                         file: file.into(),
-                        code: "E3013".into(),
+                        code: "E3013",
                         message: format!("Bad unary `-` on {:?}", other),
                         line: 0,
                         column: 0,
                         snippet: None,
                         hint: None,
-                    }),
+                        backtrace: Vec::new(),
+                    })),
                 },
                 // 逻辑非
                 "!" => match &*inner {
                     ValueInner::Bool(b) => Ok(Value::Bool(!b)),
-                    other => Err(PawError::Runtime {
+                    other => Err(Box::new(PawError::Runtime {
                         file: file.into(),
-                        code: "E3013".into(),
+                        code: "E3013",
                         message: format!("Bad unary `!` on {:?}", other),
                         line: 0,
                         column: 0,
                         snippet: None,
                         hint: None,
-                    }),
+                        backtrace: Vec::new(),
+                    })),
                 },
-                _ => Err(PawError::Internal {
+                _ => Err(Box::new(PawError::Internal {
                     file: file.into(),
-                    code: "E6002".into(),
+                    code: "E6002",
                     message: format!("Unknown unary operator `{}`", op),
                     line: 0,
                     column: 0,
                     snippet: None,
                     hint: None,
-                }),
+                })),
             },
         }
     }
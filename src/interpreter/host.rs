@@ -0,0 +1,118 @@
+// src/interpreter/host.rs
+//
+// 脚本与外界之间不确定的输入：`ask` 读到的回答、`time` 模块的时钟和随机数。
+// 默认连接控制台、系统时钟与随机种子；测试中可以换成预先写好的回答、固定的时钟和固定的种子，
+// 让交互式脚本的测试每次得到相同的结果。确定性模式（`pawc --deterministic`）同时固定时钟与种子
+
+use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 一次运行的输入来源，由解释器、它的子解释器与被导入的模块共享
+///
+/// ```
+/// use pawc::{Host, PawScript, Value};
+/// use std::sync::Arc;
+///
+/// let host = Arc::new(Host::new());
+/// host.script_answers(["Mochi"]);
+/// host.set_clock(1_000);
+/// let mut paw = PawScript::new();
+/// paw.set_host(host);
+/// let src = "import time\nlet name: String <- ask \"Name? \"\n\"${name} ${time.now_millis()}\"";
+/// let v = paw.eval_str(src).unwrap();
+/// assert_eq!(v, Value::String("Mochi 1000".to_string()));
+/// ```
+pub struct Host {
+    /// 预先写好的 `ask` 回答，按顺序取用；None 时从标准输入读取
+    answers: Mutex<Option<VecDeque<String>>>,
+    /// 固定的当前时间（自 Unix 纪元起的毫秒数）；None 时读取系统时钟
+    clock: Mutex<Option<i64>>,
+    /// `random` 模块与 `forall` 使用的随机数生成器，第一次使用时才从操作系统取种子
+    rng: Mutex<Option<StdRng>>,
+    /// 确定性模式：禁止导入结果取决于运行环境的标准库模块，异步任务按固定顺序逐个执行
+    deterministic: AtomicBool,
+}
+
+impl Default for Host {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Host {
+    /// 连接控制台与系统时钟、随机数种子取自操作系统的输入来源
+    pub fn new() -> Self {
+        Host {
+            answers: Mutex::new(None),
+            clock: Mutex::new(None),
+            rng: Mutex::new(None),
+            deterministic: AtomicBool::new(false),
+        }
+    }
+
+    /// 之后的 `ask` 依次得到这些回答，不再读取标准输入；回答用完后 `ask` 报 E6027
+    pub fn script_answers<I, S>(&self, answers: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        *self.answers.lock() = Some(answers.into_iter().map(Into::into).collect());
+    }
+
+    /// 把时钟固定在 `millis`（自 Unix 纪元起的毫秒数），`time.now_millis()` 之后都返回它
+    pub fn set_clock(&self, millis: i64) {
+        *self.clock.lock() = Some(millis);
+    }
+
+    /// 用固定的种子重新开始随机数序列，同一种子总是产生同样的随机数
+    pub fn seed_random(&self, seed: u64) {
+        *self.rng.lock() = Some(StdRng::seed_from_u64(seed));
+    }
+
+    /// 进入确定性模式：时钟固定在 `epoch_millis`，随机数从 `seed` 开始，并禁止导入结果取决于
+    /// 终端或进程信号的 `term` 与 `signal`（E6028），`tail` 与 `task_group` 的任务不再并发执行。
+    /// 同样的脚本与输入每次运行得到同样的输出
+    pub fn set_deterministic(&self, epoch_millis: i64, seed: u64) {
+        self.set_clock(epoch_millis);
+        self.seed_random(seed);
+        self.deterministic.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否处于确定性模式
+    pub(crate) fn is_deterministic(&self) -> bool {
+        self.deterministic.load(Ordering::Relaxed)
+    }
+
+    /// `ask` 的回答是否来自预先写好的列表
+    pub(crate) fn has_scripted_answers(&self) -> bool {
+        self.answers.lock().is_some()
+    }
+
+    /// 读取一行回答（不含换行符）；预先写好的回答用完时返回 None
+    pub(crate) fn read_line(&self) -> Option<String> {
+        if let Some(answers) = &mut *self.answers.lock() {
+            return answers.pop_front();
+        }
+        let mut buf = String::new();
+        let _ = std::io::stdin().read_line(&mut buf);
+        Some(buf.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    /// 当前时间，自 Unix 纪元起的毫秒数
+    pub(crate) fn now_millis(&self) -> Result<i64, String> {
+        if let Some(millis) = *self.clock.lock() {
+            return Ok(millis);
+        }
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?;
+        Ok(since_epoch.as_millis() as i64)
+    }
+
+    /// 随机数生成器；持有期间其它取随机数的代码会等待，用完应立即释放
+    pub(crate) fn rng(&self) -> MappedMutexGuard<'_, StdRng> {
+        MutexGuard::map(self.rng.lock(), |rng| rng.get_or_insert_with(StdRng::from_entropy))
+    }
+}
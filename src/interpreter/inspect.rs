@@ -0,0 +1,164 @@
+// src/interpreter/inspect.rs
+//
+// `inspect(value)` / `say_debug value`：供调试用的结构化输出。每个值带类型，
+// 容器逐行缩进展开；Map、Set 与 record 按内容排序，同一个值每次输出都相同
+
+use crate::interpreter::value::{Value, ValueInner};
+
+/// 输出的范围：展开的容器层数与每个容器最多列出的元素个数
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Limits {
+    pub depth: usize,
+    pub width: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { depth: 8, width: 50 }
+    }
+}
+
+/// 把值格式化为多行调试文本：超过 `depth` 层的容器折叠为 `[...]`，
+/// 每个容器最多列出 `width` 个元素，其余以 `... n more` 代替
+pub(crate) fn inspect(value: &Value, limits: Limits) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0, limits);
+    out
+}
+
+fn write_value(out: &mut String, value: &Value, level: usize, limits: Limits) {
+    match &*value.0 {
+        ValueInner::Null => out.push_str("nopaw"),
+        ValueInner::Optional(o) => match &**o {
+            Some(v) => write_value(out, v, level, limits),
+            None => out.push_str("nopaw"),
+        },
+        ValueInner::String(s) => out.push_str(&format!("String {:?}", s.as_str())),
+        ValueInner::Char(c) => out.push_str(&format!("Char {:?}", c)),
+        ValueInner::Int(_)
+        | ValueInner::Long(_)
+        | ValueInner::Float(_)
+        | ValueInner::Double(_)
+        | ValueInner::Bool(_) => out.push_str(&format!("{} {}", value.type_name(), value)),
+        ValueInner::Array(items) => {
+            let entries = items.iter().map(|v| (None, v.clone())).collect();
+            let header = format!("Array({}) [", items.len());
+            write_container(out, &header, "]", entries, level, limits);
+        }
+        ValueInner::Tuple(items) => {
+            let entries = items.iter().map(|v| (None, v.clone())).collect();
+            let header = format!("Tuple({}) (", items.len());
+            write_container(out, &header, ")", entries, level, limits);
+        }
+        ValueInner::Set(items) => {
+            let mut entries: Vec<_> = items.iter().map(|v| (None, v.clone())).collect();
+            entries.sort_by_cached_key(|(_, v)| inspect(v, limits));
+            let header = format!("Set({}) {{", items.len());
+            write_container(out, &header, "}", entries, level, limits);
+        }
+        ValueInner::Map(m) => {
+            let key_limits = Limits { depth: 0, ..limits };
+            let mut entries: Vec<_> = m
+                .iter()
+                .map(|(k, v)| (Some(format!("{} =>", inspect(k, key_limits))), v.clone()))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            let header = format!("Map({}) {{", m.len());
+            write_container(out, &header, "}", entries, level, limits);
+        }
+        ValueInner::Record(name, fields) => {
+            let header = format!("{} {{", name);
+            write_container(out, &header, "}", sorted_fields(fields.iter()), level, limits);
+        }
+        ValueInner::Enum { name, variant, payload } if payload.is_empty() => {
+            out.push_str(&format!("{}.{}", name, variant))
+        }
+        ValueInner::Enum { name, variant, payload } => {
+            let entries = payload.iter().map(|v| (None, v.clone())).collect();
+            let header = format!("{}.{}(", name, variant);
+            write_container(out, &header, ")", entries, level, limits);
+        }
+        ValueInner::Module(members) => {
+            write_container(out, "Module {", "}", sorted_fields(members.iter()), level, limits);
+        }
+        ValueInner::Result { ok, value } => {
+            out.push_str(if *ok { "ok " } else { "err " });
+            write_value(out, value, level, limits);
+        }
+        ValueInner::Function {
+            name,
+            params,
+            is_async,
+            ..
+        } => {
+            let params: Vec<String> = params.iter().map(|p| format!("{}: {}", p.name, p.ty)).collect();
+            let keyword = if *is_async { "async fun" } else { "fun" };
+            out.push_str(&format!("{} {}({})", keyword, name, params.join(", ")));
+        }
+        ValueInner::NativeFunction { name, .. } => out.push_str(&format!("native fun {}", name)),
+        ValueInner::Memoized { func, .. } => {
+            out.push_str("memoized ");
+            write_value(out, func, level, limits);
+        }
+        ValueInner::Overloaded(candidates) => {
+            out.push_str("overloaded ");
+            for (i, func) in candidates.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(" / ");
+                }
+                write_value(out, func, level, limits);
+            }
+        }
+        ValueInner::Future(_) => out.push_str("Future"),
+        ValueInner::Range(r) => out.push_str(&format!("{} {}", if r.long { "Range<Long>" } else { "Range<Int>" }, r)),
+        ValueInner::StringBuilder(buf) => out.push_str(&format!("StringBuilder {:?}", buf.lock().as_str())),
+        ValueInner::Generator(g) => out.push_str(&format!("Gen {}", g)),
+    }
+}
+
+/// record / 模块的字段，按字段名排序
+fn sorted_fields<'a>(
+    fields: impl Iterator<Item = (&'a String, &'a Value)>,
+) -> Vec<(Option<String>, Value)> {
+    let mut entries: Vec<_> = fields.map(|(k, v)| (Some(format!("{}:", k)), v.clone())).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// 输出容器：`header` 以开括号结尾，之后每个元素一行（可带标签），最后一行是 `close`
+fn write_container(
+    out: &mut String,
+    header: &str,
+    close: &str,
+    entries: Vec<(Option<String>, Value)>,
+    level: usize,
+    limits: Limits,
+) {
+    out.push_str(header);
+    if entries.is_empty() {
+        out.push_str(close);
+        return;
+    }
+    if level >= limits.depth {
+        out.push_str("...");
+        out.push_str(close);
+        return;
+    }
+    out.push('\n');
+    let indent = "  ".repeat(level + 1);
+    let hidden = entries.len().saturating_sub(limits.width);
+    for (label, value) in entries.into_iter().take(limits.width) {
+        out.push_str(&indent);
+        if let Some(label) = label {
+            out.push_str(&label);
+            out.push(' ');
+        }
+        write_value(out, &value, level + 1, limits);
+        out.push_str(",\n");
+    }
+    if hidden > 0 {
+        out.push_str(&format!("{}... {} more\n", indent, hidden));
+    }
+    out.push_str(&"  ".repeat(level));
+    out.push_str(close);
+}
@@ -1,17 +1,35 @@
 // src/interpreter/interpreter.rs
 
-use crate::ast::expr::{Expr, ExprKind};
+use crate::ast::expr::{BinaryOp, Expr, ExprKind};
 use crate::ast::method::Method;
-use crate::ast::statement::{Statement, StatementKind};
-use crate::error::error::PawError;
+use crate::ast::pattern::{LiteralKey, Pattern, PatternKind};
+use crate::ast::statement::{LoopVar, SnatchClause, Statement, StatementKind};
+use crate::ast::type_node::{TypeNode, TypeNodeKind};
+use crate::compiler::Compiler;
+use crate::error::error::{PawError, StackFrame};
+use crate::interpreter::control_flow::ControlFlow;
 use crate::interpreter::env::Env;
-use crate::interpreter::value::{Value, ValueInner};
+use crate::interpreter::host::Host;
+use crate::interpreter::inspect::{inspect, Limits};
+use crate::interpreter::numbers::{cast, cast_hint, parse_basic, parse_number};
+use crate::interpreter::signals;
+use crate::interpreter::stdlib::{is_nondeterministic, needs_host_access, std_module};
+use crate::interpreter::value::{PawMap, PawSet, RangeValue, Value, ValueInner};
 use crate::lexer::lexer::Lexer;
+use crate::parser::cfg::Cfg;
 use crate::parser::parser::Parser;
+use crate::runtime;
+use crate::semantic::imports::{check_case, missing_module_hint, module_key, module_path};
+use crate::semantic::types::PawType;
 use crate::semantic::type_checker::TypeChecker;
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
+use futures::stream::{FuturesUnordered, StreamExt};
+use parking_lot::Mutex;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
 use vuot::{Stack, StacklessFn};
 
 pub struct Interpreter<'local> {
@@ -19,151 +37,1815 @@ pub struct Interpreter<'local> {
     pub statements: &'local [Statement]
 }
 
-impl<'a> StacklessFn<'a, Result<Option<Value>, PawError>> for Interpreter<'_> {
-    async fn call(mut self, stack: Stack<'_>) -> Result<Option<Value>, PawError> {
-        self.engine.eval_statements(stack, self.statements).await
+impl<'a> StacklessFn<'a, Result<Option<Value>, Box<PawError>>> for Interpreter<'_> {
+    async fn call(mut self, stack: Stack<'_>) -> Result<Option<Value>, Box<PawError>> {
+        let result = self.engine.eval_statements(stack, self.statements).await?;
+        // 顶层结束前等待所有未被 await 的 Future
+        self.engine.join_tasks().await?;
+        Ok(result.into_return())
     }
 }
 
+/// 异步函数体，在独立的 vuot 栈上执行
+struct AsyncBody {
+    engine: Engine,
+    body: Arc<Vec<Statement>>,
+}
+
+impl<'a> StacklessFn<'a, Result<Option<Value>, Box<PawError>>> for AsyncBody {
+    async fn call(mut self, stack: Stack<'_>) -> Result<Option<Value>, Box<PawError>> {
+        let result = ControlFlow::catch_propagated(self.engine.eval_statements(stack, &self.body).await);
+        Ok(result.map_err(|e| self.engine.traced(e))?.into_return())
+    }
+}
+
+/// 调用栈中的一个节点；子引擎共享调用方的节点，进入函数时只新增一个节点而不复制整条栈
+struct CallFrame {
+    frame: StackFrame,
+    caller: Option<Arc<CallFrame>>,
+}
+
+/// 协作式取消标记，任务在执行每条语句前检查，父标记取消时子标记也视为取消
+#[derive(Default)]
+struct CancelToken {
+    cancelled: AtomicBool,
+    parent: Option<Arc<CancelToken>>,
+}
+
+impl CancelToken {
+    fn child_of(parent: &Arc<CancelToken>) -> Arc<Self> {
+        Arc::new(CancelToken {
+            cancelled: AtomicBool::new(false),
+            parent: Some(parent.clone()),
+        })
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.parent.as_ref().is_some_and(|p| p.is_cancelled())
+    }
+}
+
+/// 执行计数器，同一次运行中的子解释器、任务与模块共享
+#[derive(Default)]
+pub(crate) struct Counters {
+    statements: AtomicU64,
+    instructions: AtomicU64,
+    calls: AtomicU64,
+    allocations: AtomicU64,
+}
+
+impl Counters {
+    fn bump(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> Stats {
+        Stats {
+            statements: self.statements.load(Ordering::Relaxed),
+            instructions: self.instructions.load(Ordering::Relaxed),
+            calls: self.calls.load(Ordering::Relaxed),
+            allocations: self.allocations.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        for counter in [&self.statements, &self.instructions, &self.calls, &self.allocations] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 执行计数的快照，用于在测试中断言解释器的工作量，或让宿主按用量计费
+///
+/// 交给字节码 VM 执行的循环体按指令计入 `instructions`，不计入 `statements`；
+/// 同一段代码的两项之和在开启与关闭 VM 时不同，比较时应固定 VM 开关。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// 树遍历解释器执行的语句数
+    pub statements: u64,
+    /// 字节码 VM 执行的指令数
+    pub instructions: u64,
+    /// 脚本函数与原生函数的调用次数（含 async 函数创建 Future 的调用）
+    pub calls: u64,
+    /// 表达式新建的字符串、容器、record 与闭包数
+    pub allocations: u64,
+}
+
+/// 沙箱模式下字符串与容器的大小上限，超出时报 E6020，而不是耗尽宿主内存
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    /// 字符串的最大字节数（UTF-8），默认 16 MiB
+    pub max_string_bytes: usize,
+    /// 数组、Map 与 Set 的最大元素数，默认 1048576
+    pub max_collection_len: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        SizeLimits {
+            max_string_bytes: 16 * 1024 * 1024,
+            max_collection_len: 1 << 20,
+        }
+    }
+}
+
+/// 一个已加载的文件模块
+#[derive(Clone)]
+pub(crate) struct LoadedModule {
+    pub value: Value,
+    /// 模块的顶层作用域；重新加载它导入的模块时在这里重新绑定别名
+    pub env: Env,
+    /// 加载时源文件的修改时间，用于发现改动
+    pub modified: Option<SystemTime>,
+}
+
+/// 按规范化路径缓存的已加载模块，同一次运行中每个模块只加载一次
+pub(crate) type ModuleCache = Arc<Mutex<AHashMap<PathBuf, LoadedModule>>>;
+
+/// record 名 → 方法名 → 方法（第一个参数为 `self` 的函数），
+/// 方法跟着 record 值走，所以与子解释器和被导入的模块共享
+pub(crate) type RecordMethods = Arc<Mutex<AHashMap<String, AHashMap<String, Value>>>>;
+
+/// 已执行的 enum 声明的名字，运行时据此把类型注解中的名字区分为 enum 与 record，
+/// 与子解释器和被导入的模块共享
+pub(crate) type EnumNames = Arc<Mutex<AHashSet<String>>>;
+
 /// 主解释器
 pub struct Engine {
     pub env: Env,
     pub file: String,
+    /// 当前顶层（脚本或模块）创建的 Future，顶层结束时统一 join
+    tasks: Arc<Mutex<Vec<Value>>>,
+    /// 最近一层 task_group 收集的子任务
+    group: Option<Arc<Mutex<Vec<Value>>>>,
+    cancel: Arc<CancelToken>,
+    /// 是否把可编译的循环交给字节码 VM 执行
+    vm: bool,
+    /// 已执行过的模块，按规范化路径缓存，同一次运行中每个模块只加载一次
+    modules: ModuleCache,
+    /// 已执行的 record 声明中的方法
+    methods: RecordMethods,
+    /// 已执行的 enum 声明
+    enums: EnumNames,
+    /// 从入口脚本到当前文件、正在加载中的模块路径，用于发现循环导入
+    loading: Arc<Vec<PathBuf>>,
+    /// 当前所在的函数调用，最内层在栈顶；顶层代码为 None
+    calls: Option<Arc<CallFrame>>,
+    /// 沙箱模式：禁止导入模块和读取控制台输入
+    sandbox: bool,
+    /// 执行计数，与子解释器及其加载的模块共享
+    counters: Arc<Counters>,
+    /// 沙箱模式下的字符串与容器大小上限
+    limits: SizeLimits,
+    /// 解析被导入模块时判断 `@cfg(...)` 使用的开关
+    cfg: Arc<Cfg>,
+    /// 只读求值（见 `evaluate`）：禁止调用脚本函数、执行异步代码和修改共享的缓冲区
+    read_only: bool,
+    /// `assert_snapshot` 用新值覆盖与之不符的快照，而不是报错
+    pub(crate) update_snapshots: bool,
+    /// `ask` 的回答、时钟与随机数的来源，与子解释器及其加载的模块共享
+    pub(crate) host: Arc<Host>,
 }
 
-impl Engine {
-    /// 创建一个新的解释器实例
-    pub fn new(env: Env, file: &str) -> Self {
-        Engine {
-            env,
-            file: file.to_string(),
+impl Engine {
+    /// 创建一个新的解释器实例
+    pub fn new(env: Env, file: &str) -> Self {
+        Engine {
+            env,
+            file: file.to_string(),
+            tasks: Arc::new(Mutex::new(Vec::new())),
+            group: None,
+            cancel: Arc::new(CancelToken::default()),
+            vm: true,
+            modules: Arc::new(Mutex::new(AHashMap::new())),
+            methods: Arc::default(),
+            enums: Arc::default(),
+            loading: Arc::new(std::fs::canonicalize(file).into_iter().collect()),
+            calls: None,
+            sandbox: false,
+            counters: Arc::default(),
+            limits: SizeLimits::default(),
+            cfg: Arc::default(),
+            read_only: false,
+            update_snapshots: false,
+            host: Arc::default(),
+        }
+    }
+
+    /// 开启或关闭字节码 VM；关闭后所有代码都由树遍历解释器执行
+    pub fn set_vm(&mut self, enabled: bool) {
+        self.vm = enabled;
+    }
+
+    /// 开启或关闭沙箱模式，用于运行不受信任的脚本
+    pub fn set_sandbox(&mut self, enabled: bool) {
+        self.sandbox = enabled;
+    }
+
+    /// 设置沙箱模式下的字符串与容器大小上限；非沙箱模式不检查
+    pub fn set_size_limits(&mut self, limits: SizeLimits) {
+        self.limits = limits;
+    }
+
+    /// 开启后 `assert_snapshot` 把与快照不符的值写入快照文件（`pawc test --update-snapshots`）
+    pub fn set_update_snapshots(&mut self, enabled: bool) {
+        self.update_snapshots = enabled;
+    }
+
+    /// 换成另一个输入来源，例如在测试中预先写好 `ask` 的回答、固定时钟与随机数种子
+    pub fn set_host(&mut self, host: Arc<Host>) {
+        self.host = host;
+    }
+
+    /// 设置解析被导入模块时 `@cfg(...)` 使用的开关，应与解析脚本本身时相同
+    pub fn set_cfg(&mut self, cfg: Cfg) {
+        self.cfg = Arc::new(cfg);
+    }
+
+    /// 在当前环境中求值一个表达式字符串，供调试器的监视表达式与编辑器的“求值选中内容”使用；
+    /// 以某个暂停位置的环境创建 `Engine` 即可在该位置求值。
+    ///
+    /// `read_only` 为 true 时在环境的副本上求值，并拒绝调用脚本函数、有副作用的内置函数与原生函数、
+    /// `async` / `await` / `tail` 以及修改 StringBuilder（E6023），因此求值不会改变脚本的任何状态。表达式不经过类型检查，
+    /// 类型错误在运行时报告。
+    pub async fn evaluate(&mut self, stack: Stack<'_>, source: &str, read_only: bool) -> Result<Value, Box<PawError>> {
+        let tokens = Lexer::new(source).tokenize();
+        let expr = Parser::new(tokens, source, &self.file)
+            .with_cfg(&self.cfg)
+            .parse_expression()?;
+        if !read_only {
+            return self.eval_expr(stack, &expr).await;
+        }
+        let mut sandbox = self.child(self.env.snapshot());
+        sandbox.read_only = true;
+        sandbox.eval_expr(stack, &expr).await
+    }
+
+    /// 只读求值中不允许的操作报 E6023
+    pub(crate) fn check_writable(&self, what: &str, line: usize, col: usize) -> Result<(), Box<PawError>> {
+        match self.read_only {
+            true => Err(self.read_only_error(what, line, col)),
+            false => Ok(()),
+        }
+    }
+
+    fn read_only_error(&self, what: &str, line: usize, col: usize) -> Box<PawError> {
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6023",
+            message: format!("Cannot {} in a read-only evaluation", what),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("Read-only evaluation only reads values; evaluate without read-only to run code".into()),
+            backtrace: Vec::new(),
+        })
+    }
+
+    /// 本次运行已加载的文件模块缓存，与所有子解释器共享
+    pub(crate) fn module_cache(&self) -> ModuleCache {
+        self.modules.clone()
+    }
+
+    /// 与其它解释器共用模块缓存，例如让会话中的多次执行只加载一次模块
+    pub(crate) fn set_module_cache(&mut self, modules: ModuleCache) {
+        self.modules = modules;
+    }
+
+    /// 到目前为止的执行计数
+    pub fn stats(&self) -> Stats {
+        self.counters.snapshot()
+    }
+
+    /// 把执行计数清零
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
+    /// 与其它解释器共用执行计数，例如让会话中的多次执行累计到同一组计数器
+    pub(crate) fn set_counters(&mut self, counters: Arc<Counters>) {
+        self.counters = counters;
+    }
+
+    /// 记录一次表达式分配
+    pub(crate) fn count_allocation(&self) {
+        Counters::bump(&self.counters.allocations);
+    }
+
+    /// 记录一条在 `eval_statements` 之外执行的语句，例如会话中作为结果的末尾表达式
+    pub(crate) fn count_statement(&self) {
+        Counters::bump(&self.counters.statements);
+    }
+
+    /// 记录一条 VM 指令
+    pub(crate) fn count_instruction(&self) {
+        Counters::bump(&self.counters.instructions);
+    }
+
+    /// 注册一个宿主原生函数，脚本中可以像普通函数一样调用它
+    pub fn register_native<F>(&self, name: &str, func: F)
+    where
+        F: Fn(Vec<Value>) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.env.define(name.to_string(), Value::Native(name, func));
+    }
+
+    /// 在解释器的环境（新建的 Engine 即全局环境）中预先定义一个变量，已存在时覆盖；
+    /// 需要类型检查的脚本还应在检查器作用域中登记其类型，见 `Session::define`
+    pub fn set_global(&self, name: &str, value: Value) {
+        self.env.define(name.to_string(), value);
+    }
+
+    /// 创建子解释器，与父解释器共享同一个顶层任务列表
+    fn child(&self, env: Env) -> Self {
+        Engine {
+            env,
+            file: self.file.clone(),
+            tasks: self.tasks.clone(),
+            group: self.group.clone(),
+            cancel: self.cancel.clone(),
+            vm: self.vm,
+            modules: self.modules.clone(),
+            methods: self.methods.clone(),
+            enums: self.enums.clone(),
+            loading: self.loading.clone(),
+            calls: self.calls.clone(),
+            sandbox: self.sandbox,
+            counters: self.counters.clone(),
+            limits: self.limits,
+            cfg: self.cfg.clone(),
+            read_only: self.read_only,
+            update_snapshots: self.update_snapshots,
+            host: self.host.clone(),
+        }
+    }
+
+    /// 与其它解释器共用 record 方法表，例如让会话中后一次执行能调用前一次声明的方法
+    pub(crate) fn set_record_methods(&mut self, methods: RecordMethods) {
+        self.methods = methods;
+    }
+
+    /// 与其它解释器共用已声明的 enum 名，见 `set_record_methods`
+    pub(crate) fn set_enum_names(&mut self, enums: EnumNames) {
+        self.enums = enums;
+    }
+
+    /// 把类型注解逐层转换为运行时检查用的类型：名字按内置类型、已声明的 enum 与 record 区分，
+    /// 泛型 record 的类型实参只在检查期有意义，运行时按 record 名比较
+    pub(crate) fn runtime_type(&self, node: &TypeNode) -> PawType {
+        let boxed = |node: &TypeNode| Box::new(self.runtime_type(node));
+        match &node.kind {
+            TypeNodeKind::Name(name) => PawType::builtin(name).unwrap_or_else(|| {
+                match self.enums.lock().contains(name) {
+                    true => PawType::Enum(name.clone()),
+                    false => PawType::Record(name.clone()),
+                }
+            }),
+            TypeNodeKind::Generic { name, args } => match (name.as_str(), args.as_slice()) {
+                ("Array", [elem]) => PawType::Array(boxed(elem)),
+                ("Set", [elem]) => PawType::Set(boxed(elem)),
+                ("Range", [elem]) => PawType::Range(boxed(elem)),
+                ("Future", [inner]) => PawType::Future(boxed(inner)),
+                ("Gen", [inner]) => PawType::Gen(boxed(inner)),
+                ("Map", [k, v]) => PawType::Map(boxed(k), boxed(v)),
+                ("Result", [ok, err]) => PawType::Result(boxed(ok), boxed(err)),
+                _ => PawType::Generic(name.clone(), args.iter().map(|a| self.runtime_type(a)).collect()),
+            },
+            TypeNodeKind::Optional(inner) => PawType::Optional(boxed(inner)),
+            TypeNodeKind::Function { params, ret } => {
+                PawType::Function(params.iter().map(|p| self.runtime_type(p)).collect(), boxed(ret))
+            }
+            TypeNodeKind::Tuple(elems) => PawType::Tuple(elems.iter().map(|e| self.runtime_type(e)).collect()),
+        }
+    }
+
+    /// 取出 record 值的方法并把 `self` 绑定为该值，得到可以直接调用的函数；没有该方法时为 None
+    fn bound_method(&self, receiver: &Value, method: &str) -> Option<Value> {
+        let ValueInner::Record(record, _) = &*receiver.0 else {
+            return None;
+        };
+        let func = self.methods.lock().get(record.as_str())?.get(method)?.clone();
+        let ValueInner::Function {
+            name,
+            params,
+            body,
+            env,
+            is_async,
+        } = &*func.0
+        else {
+            return None;
+        };
+        let bound = Env::with_parent(env);
+        bound.define("self".into(), receiver.clone());
+        Some(Value::from_inner(ValueInner::Function {
+            name: name.clone(),
+            params: Arc::new(params[1..].to_vec()),
+            body: body.clone(),
+            env: bound,
+            is_async: *is_async,
+        }))
+    }
+
+    /// 进入一次函数调用：在调用栈上压入被调函数及调用位置
+    fn enter_call(&mut self, caller: &Engine, name: &str, line: usize, col: usize) {
+        self.calls = Some(Arc::new(CallFrame {
+            frame: StackFrame {
+                function: name.to_string(),
+                file: caller.file.clone(),
+                line,
+                column: col,
+            },
+            caller: caller.calls.clone(),
+        }));
+    }
+
+    /// 为离开函数的运行时错误附上当前调用栈；已带有调用栈（来自更深的调用）时保持不变
+    pub(crate) fn traced(&self, mut err: Box<PawError>) -> Box<PawError> {
+        if let PawError::Runtime { backtrace, .. } | PawError::Custom { backtrace, .. } = &mut *err {
+            if backtrace.is_empty() {
+                let mut node = self.calls.as_deref();
+                while let Some(call) = node {
+                    backtrace.push(call.frame.clone());
+                    node = call.caller.as_deref();
+                }
+            }
+        }
+        err
+    }
+
+    /// 并发等待 task_group 的所有子任务；任一失败即取消其余任务，返回所有非取消类错误
+    async fn join_group(&self, children: Vec<Value>, token: &CancelToken) -> Vec<Box<PawError>> {
+        // 确定性模式下按 spawn 的顺序逐个执行，失败后其余任务看到取消
+        if self.host.is_deterministic() {
+            let mut errors = Vec::new();
+            for child in children {
+                if let Some(Err(err)) = child.drive_pending().await {
+                    token.cancel();
+                    if err.code() != "E6008" {
+                        errors.push(err);
+                    }
+                }
+            }
+            return errors;
+        }
+        let mut running: FuturesUnordered<_> = children
+            .into_iter()
+            .map(|child| async move { child.drive_pending().await })
+            .collect();
+        let mut errors = Vec::new();
+        while let Some(res) = running.next().await {
+            if let Some(Err(err)) = res {
+                token.cancel();
+                if err.code() != "E6008" {
+                    errors.push(err);
+                }
+            }
+        }
+        errors
+    }
+
+    /// 读取、检查并执行模块文件 `path`，把得到的 Module 按规范化路径 `key` 写入模块缓存
+    async fn load_module<'a>(
+        &self,
+        stack: Stack<'a>,
+        path: &Path,
+        key: PathBuf,
+    ) -> Result<Value, Box<PawError>> {
+        // 1. 读源码，同时记下修改时间
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let src = std::fs::read_to_string(path).map_err(|e| {
+            // 根据 kind 构造英文提示
+            let message = match e.kind() {
+                ErrorKind::NotFound => {
+                    format!("Module file not found: {}", path.display())
+                }
+                ErrorKind::PermissionDenied => {
+                    format!("Permission denied reading module file: {}", path.display())
+                }
+                _ => format!("Failed to read module file: {}", path.display()),
+            };
+            PawError::Internal {
+                file: self.file.clone(),
+                code: "E1002",
+                message,
+                line: 0,
+                column: 0,
+                snippet: None,
+                hint: Some(match e.kind() {
+                    ErrorKind::NotFound => missing_module_hint(path),
+                    _ => "Check that the module file exists and the path is correct".into(),
+                }),
+            }
+        })?;
+
+        // 2. 词法 & 解析
+        let tokens = Lexer::new(&src).tokenize();
+        let mut parser = Parser::new(tokens, &src, &path.to_string_lossy()).with_cfg(&self.cfg);
+        let stmts = parser.parse_program()?;
+
+        // 3. 语义检查
+        let mut checker = TypeChecker::new(&path.to_string_lossy());
+        checker.check_program(&stmts)?;
+        for warning in checker.warnings() {
+            eprintln!("{}", warning);
+        }
+
+        // 4. 执行模块，共享模块缓存，并把自己加入加载链
+        let module_env = Env::with_parent(&self.env);
+        let mut module_interp = Engine::new(module_env.clone(), &path.to_string_lossy());
+        module_interp.vm = self.vm;
+        module_interp.modules = self.modules.clone();
+        module_interp.methods = self.methods.clone();
+        module_interp.enums = self.enums.clone();
+        module_interp.counters = self.counters.clone();
+        module_interp.cfg = self.cfg.clone();
+        module_interp.update_snapshots = self.update_snapshots;
+        module_interp.host = self.host.clone();
+        let mut loading = (*self.loading).clone();
+        loading.push(key.clone());
+        module_interp.loading = Arc::new(loading);
+        let _ = stack.run(module_interp.eval_statements(stack, &stmts)).await?;
+        // 模块顶层派生的 Future 在 import 完成前全部 join
+        module_interp.join_tasks().await?;
+
+        // 5. 收集子环境所有顶层绑定，打包成 Module 并缓存
+        let module_val = {
+            let m = module_env.bindings();
+            Value::Module(m)
+        };
+        self.modules.lock().insert(
+            key,
+            LoadedModule {
+                value: module_val.clone(),
+                env: module_env,
+                modified,
+            },
+        );
+        Ok(module_val)
+    }
+
+    /// 重新加载源文件在上次加载后被修改过的模块，返回它们的路径。
+    ///
+    /// 被修改的模块从头重新执行，顶层变量按新源码重新初始化；当前环境与其它已加载模块中
+    /// 由 `import` 绑定到旧模块的别名改为指向新模块，它们自己的状态保持不变。
+    /// 此前从模块中取出并另存的值（如 `let f = m.helper`）仍是旧版本。
+    /// 文件已被删除的模块保持原样；某个模块加载失败时返回错误，它的旧版本仍然有效
+    pub(crate) async fn reload_modules<'a>(&self, stack: Stack<'a>) -> Result<Vec<PathBuf>, Box<PawError>> {
+        let mut changed: Vec<PathBuf> = self
+            .modules
+            .lock()
+            .iter()
+            .filter(|(key, loaded)| match std::fs::metadata(key).and_then(|m| m.modified()) {
+                Ok(modified) => loaded.modified != Some(modified),
+                Err(_) => false,
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        changed.sort();
+
+        // 旧模块值 → 新模块值；重新绑定后模块自身的值也会变化，继续向导入它的地方传播
+        let mut replaced = Vec::new();
+        for key in &changed {
+            let old = self.modules.lock()[key].value.clone();
+            let new = self.load_module(stack, key, key.clone()).await?;
+            replaced.push((old, new));
+        }
+        while let Some((old, new)) = replaced.pop() {
+            rebind(&self.env, &old, &new);
+            let mut modules = self.modules.lock();
+            for loaded in modules.values_mut() {
+                if rebind(&loaded.env, &old, &new) {
+                    let value = Value::Module(loaded.env.bindings());
+                    replaced.push((std::mem::replace(&mut loaded.value, value.clone()), value));
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// 等待当前顶层所有尚未被 await 的 Future 完成，第一个错误会被返回
+    pub async fn join_tasks(&self) -> Result<(), Box<PawError>> {
+        loop {
+            let pending = std::mem::take(&mut *self.tasks.lock());
+            if pending.is_empty() {
+                return Ok(());
+            }
+            for task in pending {
+                if let Some(res) = task.drive_pending().await {
+                    res?;
+                }
+            }
+        }
+    }
+
+    /// 登记一个新的 Future，同时清理已经被 await 过的
+    fn track_task(&self, task: Value) {
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|t| match &*t.0 {
+            ValueInner::Future(cell) => cell.try_lock().is_none_or(|f| f.is_pending()),
+            _ => false,
+        });
+        tasks.push(task);
+    }
+
+    /// `tail`：立即在后台驱动 Future，返回等待后台结果的新 Future；
+    /// 新 Future 同样登记为任务，未被 await 时其错误仍会在顶层结束时报告；
+    /// 确定性模式下不在后台并发执行，任务在第一次被 await 或顶层结束时按登记顺序执行
+    fn start_task(&self, task: Value, line: usize, col: usize) -> Value {
+        if self.host.is_deterministic() {
+            self.track_task(task.clone());
+            return task;
+        }
+        let file = self.file.clone();
+        let running = runtime::spawn(async move { task.await_future().await });
+        let fut = Value::Future(Box::pin(async move {
+            match running.await {
+                Ok(Some(res)) => res,
+                Ok(None) => Err(Box::new(PawError::Runtime {
+                    file,
+                    code: "E6007",
+                    message: "Future was cancelled before it completed".into(),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: Some("A future whose task was cancelled has no result to await".into()),
+                    backtrace: Vec::new(),
+                })),
+                Err(e) => Err(Box::new(PawError::Internal {
+                    file,
+                    code: "E6006",
+                    message: format!("Async task failed: {}", e),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: None,
+                })),
+            }
+        }));
+        self.track_task(fut.clone());
+        fut
+    }
+
+    /// 把一段语句包装成惰性 Future：被 await 时才在阻塞线程池中的独立栈上运行
+    fn make_future(&self, engine: Engine, body: Arc<Vec<Statement>>, line: usize, col: usize) -> Value {
+        let file = self.file.clone();
+        let fut = Value::Future(Box::pin(async move {
+            let joined = runtime::spawn_blocking(move || {
+                futures::executor::block_on(vuot::run(AsyncBody { engine, body }))
+            })
+            .await;
+            match joined {
+                Ok(res) => Ok(res?.unwrap_or(Value::Null())),
+                Err(e) => Err(Box::new(PawError::Internal {
+                    file,
+                    code: "E6006",
+                    message: format!("Async task failed: {}", e),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: None,
+                })),
+            }
+        }));
+        self.track_task(fut.clone());
+        fut
+    }
+
+    fn pop_empty_error(&self, line: usize, col: usize) -> Box<PawError> {
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E3016",
+            message: "Cannot pop from empty array".into(),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("Ensure array is non-empty before calling pop".into()),
+            backtrace: Vec::new(),
+        })
+    }
+
+    /// 显示 `ask` 的提示并读取一行回答；沙箱模式下不能读取控制台，但可以使用预先写好的回答，
+    /// 回答用完时报 E6027
+    fn read_answer(&self, prompt: &str, line: usize, col: usize) -> Result<String, Box<PawError>> {
+        if !self.host.has_scripted_answers() {
+            self.check_sandbox("Reading console input", line, col)?;
+        }
+        print!("{}", prompt);
+        // 确保 prompt 立刻显示在终端
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+        self.host.read_line().ok_or_else(|| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6027",
+            message: "No scripted answer left for `ask`".into(),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("Add another answer to `mock_ask([...])`".into()),
+            backtrace: Vec::new(),
+        }))
+    }
+
+    /// 把 `ask` 读到的一行输入转换成声明的类型；类型检查器已保证类型可以转换
+    fn ask_value(&self, input: &str, ty: &TypeNode, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        let name = match &ty.kind {
+            // 可空类型读到空行时为 nopaw
+            TypeNodeKind::Optional(inner) => {
+                if input.trim().is_empty() {
+                    return Ok(Value::Null());
+                }
+                return self.ask_value(input, inner, line, col);
+            }
+            TypeNodeKind::Name(name) => name.as_str(),
+            _ => "String",
+        };
+        parse_basic(input, name).map_err(|message| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6016",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint: Some(format!("Enter a value of type {}", ty)),
+            backtrace: Vec::new(),
+        }))
+    }
+
+    /// `Array.sort`：稳定排序，`cmp` 返回负数表示第一个参数排在前面；
+    /// 比较函数可能是脚本函数，所以逐层归并、每次比较都 await
+    async fn sort_values(
+        &mut self,
+        stack: Stack<'_>,
+        mut items: Vec<Value>,
+        cmp: Option<&Value>,
+        line: usize,
+        col: usize,
+    ) -> Result<Vec<Value>, Box<PawError>> {
+        let Some(cmp) = cmp else {
+            let mut mismatch = None;
+            items.sort_by(|a, b| {
+                natural_order(a, b).unwrap_or_else(|| {
+                    mismatch.get_or_insert((a.type_name(), b.type_name()));
+                    std::cmp::Ordering::Equal
+                })
+            });
+            return match mismatch {
+                None => Ok(items),
+                Some((a, b)) => Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E6003",
+                    message: format!("Cannot order {} and {} in sort", a, b),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: Some("Pass a comparator: sort(fun(a: T, b: T): Int { ... })".into()),
+                    backtrace: Vec::new(),
+                })),
+            };
+        };
+        let n = items.len();
+        let mut width = 1;
+        while width < n {
+            let mut merged = Vec::with_capacity(n);
+            let mut start = 0;
+            while start < n {
+                let mid = (start + width).min(n);
+                let end = (start + 2 * width).min(n);
+                let (mut l, mut r) = (start, mid);
+                while l < mid && r < end {
+                    let order = self
+                        .call_function(stack, cmp, vec![items[r].clone(), items[l].clone()], "sort comparator", line, col)
+                        .await?;
+                    // 只有右边严格小于左边时才先取右边，保持相等元素的原有顺序
+                    let right_first = match &*order.0 {
+                        ValueInner::Int(o) => *o < 0,
+                        _ => {
+                            return Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6003",
+                                message: format!(
+                                    "sort comparator must return Int, got {}",
+                                    order.type_name()
+                                ),
+                                line,
+                                column: col,
+                                snippet: None,
+                                hint: None,
+                                backtrace: Vec::new(),
+                            }));
+                        }
+                    };
+                    if right_first {
+                        merged.push(items[r].clone());
+                        r += 1;
+                    } else {
+                        merged.push(items[l].clone());
+                        l += 1;
+                    }
+                }
+                merged.extend_from_slice(&items[l..mid]);
+                merged.extend_from_slice(&items[r..end]);
+                start = end;
+            }
+            items = merged;
+            width *= 2;
+        }
+        Ok(items)
+    }
+
+    /// 调用一个函数值；async 函数返回一个惰性的 Future，在被 await 时才执行
+    pub(crate) async fn call_function(
+        &mut self,
+        stack: Stack<'_>,
+        func_val: &Value,
+        arg_vals: Vec<Value>,
+        name: &str,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        match &*func_val.0 {
+            ValueInner::Function {
+                params,
+                body,
+                env: fenv,
+                is_async,
+                ..
+            } => {
+                self.check_writable(&format!("call '{}'", name), line, col)?;
+                Counters::bump(&self.counters.calls);
+                let mut child = self.child(Env::with_parent(fenv));
+                child.enter_call(self, name, line, col);
+                let given = arg_vals.len();
+                for (p, v) in params.iter().zip(arg_vals) {
+                    child.env.define(p.name.clone(), v);
+                }
+                // 省略的参数按顺序在被调函数的环境中求值默认值，可以引用前面的参数
+                for p in params.iter().skip(given) {
+                    if let Some(default) = &p.default {
+                        let v = stack
+                            .run(child.eval_expr(stack, default))
+                            .await
+                            .map_err(|e| child.traced(e))?;
+                        child.env.define(p.name.clone(), v);
+                    }
+                }
+                if *is_async {
+                    // —— 异步调用 ——
+                    Ok(self.make_future(child, body.clone(), line, col))
+                } else {
+                    // —— 同步调用 ——
+                    let res = ControlFlow::catch_propagated(stack.run(child.eval_statements(stack, body)).await);
+                    Ok(res.map_err(|e| child.traced(e))?.into_return().unwrap_or(Value::Null()))
+                }
+            }
+
+            // —— memoize 包装的函数：命中缓存直接返回，否则调用并缓存结果 ——
+            ValueInner::Memoized { func, cache } => {
+                let key = arg_vals
+                    .iter()
+                    .all(Value::is_cacheable)
+                    .then(|| arg_vals.clone());
+                if let Some(hit) = key.as_ref().and_then(|k| cache.lock().get(k).cloned()) {
+                    return Ok(hit);
+                }
+                let v = stack
+                    .run(self.call_function(stack, func, arg_vals, name, line, col))
+                    .await?;
+                if let Some(k) = key {
+                    cache.lock().insert(k, v.clone());
+                }
+                Ok(v)
+            }
+
+            // —— 重载的函数：按实参的运行时类型选择一个 ——
+            ValueInner::Overloaded(candidates) => {
+                let Some(func) = Value::pick_overload(candidates, &arg_vals, |ty| self.runtime_type(ty)) else {
+                    let types: Vec<&str> = arg_vals.iter().map(Value::type_name).collect();
+                    return Err(self.traced(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E3033",
+                        message: format!("No overload of '{}' accepts ({})", name, types.join(", ")),
+                        line,
+                        column: col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    })));
+                };
+                stack
+                    .run(self.call_function(stack, func, arg_vals, name, line, col))
+                    .await
+            }
+
+            // —— 宿主注册的原生函数，同步执行 ——
+            ValueInner::NativeFunction { func, pure, .. } => {
+                if !pure {
+                    self.check_writable(&format!("call native function '{}'", name), line, col)?;
+                }
+                Counters::bump(&self.counters.calls);
+                let mut native = self.child(self.env.clone());
+                native.enter_call(self, name, line, col);
+                (func.0)(arg_vals).map_err(|message| native.traced(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E6012",
+                    message,
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: Some(format!("Raised by native function '{}'", name)),
+                    backtrace: Vec::new(),
+                })))
+            }
+
+            // —— 不是函数，直接报错 ——
+            _ => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E4002",
+                message: format!("{} is not callable", name),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+        }
+    }
+
+    /// 把新值写回一个左值：变量直接赋值，`a[i]` / `a.f` 则复制容器、替换元素后再写回 `a`
+    async fn assign_place(
+        &mut self,
+        stack: Stack<'_>,
+        target: &Expr,
+        new_val: Value,
+    ) -> Result<(), Box<PawError>> {
+        match &target.kind {
+            ExprKind::Var(name) => self.env.assign(name, new_val, &self.file, target.line, target.col),
+            ExprKind::Index { array, index } => {
+                let container = stack.run(self.eval_expr(stack, array)).await?;
+                let idx = stack.run(self.eval_expr(stack, index)).await?;
+                let updated = self.set_index(&container, idx, new_val, target.line, target.col)?;
+                stack.run(self.assign_place(stack, array, updated)).await
+            }
+            ExprKind::FieldAccess { expr: object, field } => {
+                let container = stack.run(self.eval_expr(stack, object)).await?;
+                let updated = self.set_field(&container, field, new_val, target.line, target.col)?;
+                stack.run(self.assign_place(stack, object, updated)).await
+            }
+            _ => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6003",
+                message: "Invalid assignment target".into(),
+                line: target.line,
+                column: target.col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+        }
+    }
+
+    /// Map 的 insert / remove 与 Set 的 add / remove：接收者是 `a[i]`、`r.f` 之类的左值时写回，
+    /// 返回修改后的集合；临时集合的修改只体现在返回值中
+    #[allow(clippy::too_many_arguments)]
+    async fn update_collection_at(
+        &mut self,
+        stack: Stack<'_>,
+        receiver: &Expr,
+        recv: &Value,
+        method: &Method,
+        args: &[Value],
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        if let Some(len) = grown_len(recv, method, args) {
+            self.check_collection_size(len, line, col)?;
+        }
+        let mut updated = recv.clone();
+        update_collection(&mut updated, method, args);
+        if matches!(receiver.kind, ExprKind::Var(_) | ExprKind::Index { .. } | ExprKind::FieldAccess { .. }) {
+            stack.run(self.assign_place(stack, receiver, updated.clone())).await?;
+        }
+        Ok(updated)
+    }
+
+    /// 一元运算，树遍历解释器与字节码 VM 共用
+    pub(crate) fn unary_value(
+        &self,
+        op: &str,
+        v: Value,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        // 匹配操作符，每条路径都返回 Result<Value, Box<PawError>>
+        match op {
+            // 负号
+            "-" => {
+                // 解构 Value 到内部 Arc<ValueInner>
+                let Value(inner_arc) = v;
+                match &*inner_arc {
+                    ValueInner::Int(i) => i
+                        .checked_neg()
+                        .map(Value::Int)
+                        .ok_or_else(|| self.integer_overflow("E6021", format!("-({})", i), "Int", line, col)),
+                    ValueInner::Long(l) => l
+                        .checked_neg()
+                        .map(Value::Long)
+                        .ok_or_else(|| self.integer_overflow("E6021", format!("-({})", l), "Long", line, col)),
+                    ValueInner::Float(f) => Ok(Value::Float(-f)),
+                    ValueInner::Double(d) => Ok(Value::Double(-d)),
+                    other => Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E3013",
+                        message: format!("Bad unary `{}` on {:?}", op, other),
+                        line,
+                        column: col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    })),
+                }
+            }
+
+            // 逻辑非
+            "!" => {
+                let Value(inner_arc) = v;
+                match &*inner_arc {
+                    ValueInner::Bool(b) => Ok(Value::Bool(!b)),
+                    other => Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E3013",
+                        message: format!("Bad unary `{}` on {:?}", op, other),
+                        line,
+                        column: col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    })),
+                }
+            }
+
+            // 其他未知一元操作符
+            _ => Err(Box::new(PawError::Internal {
+                file: self.file.clone(),
+                code: "E6002",
+                message: format!("Unknown unary operator `{}`", op),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+            })),
+        }
+    }
+
+    /// `a ** b`：两边都是整数时按整数计算并检查溢出，有 Long 时结果为 Long；
+    /// 有浮点数时结果为 Double
+    fn pow_value(&self, l: &Value, r: &Value, line: usize, col: usize) -> Result<Value, Box<PawError>> {
+        use crate::interpreter::value::ValueInner::*;
+        let integer = |v: &ValueInner| match v {
+            Int(n) => Some(i64::from(*n)),
+            Long(n) => Some(*n),
+            _ => None,
+        };
+        let float = |v: &ValueInner| match v {
+            Float(x) => Some(f64::from(*x)),
+            Double(x) => Some(*x),
+            other => integer(other).map(|n| n as f64),
+        };
+        let (lv, rv) = (&*l.0, &*r.0);
+        if let (Some(base), Some(exp)) = (integer(lv), integer(rv)) {
+            return if let (Int(_), Int(_)) = (lv, rv) {
+                let n = self.integer_pow(base, exp, "Int", line, col)?;
+                Ok(Value::Int(n as i32))
+            } else {
+                Ok(Value::Long(self.integer_pow(base, exp, "Long", line, col)?))
+            };
+        }
+        match (float(lv), float(rv)) {
+            (Some(base), Some(exp)) => Ok(Value::Double(base.powf(exp))),
+            _ => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E3014",
+                message: format!("Cannot {:?} and {:?}", lv, rv),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+        }
+    }
+
+    /// 由求得的端点与步长构造范围：都必须是 Int 或 Long（否则 E3008），步长不能为 0（E6022）
+    pub(crate) fn range_value(
+        &self,
+        start: &Value,
+        end: &Value,
+        step: Option<&Value>,
+        inclusive: bool,
+        line: usize,
+        col: usize,
+    ) -> Result<RangeValue, Box<PawError>> {
+        let mut long = false;
+        let mut bound = |v: &Value| match &*v.0 {
+            ValueInner::Int(n) => Some(*n as i64),
+            ValueInner::Long(n) => {
+                long = true;
+                Some(*n)
+            }
+            _ => None,
+        };
+        let (Some(s), Some(e), Some(step)) = (bound(start), bound(end), step.map_or(Some(1), &mut bound)) else {
+            let parts: Vec<&str> = [Some(start), Some(end), step].iter().flatten().map(|v| v.type_name()).collect();
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E3008",
+                message: format!("Range bounds and step must be Int or Long, found {}", parts.join(", ")),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            }));
+        };
+        if step == 0 {
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6022",
+                message: "Range step cannot be zero".into(),
+                line,
+                column: col,
+                snippet: None,
+                hint: Some("Use a negative step such as `step -1` to count down".into()),
+                backtrace: Vec::new(),
+            }));
+        }
+        Ok(RangeValue {
+            start: s,
+            end: e,
+            step,
+            inclusive,
+            long,
+        })
+    }
+
+    /// 整数乘方；指数为负或结果超出 `ty`（Int 或 Long）的范围时返回 E6021
+    fn integer_pow(&self, base: i64, exp: i64, ty: &str, line: usize, col: usize) -> Result<i64, Box<PawError>> {
+        let error = |message: String, hint: &str| Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6021",
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint: Some(hint.into()),
+            backtrace: Vec::new(),
+        });
+        if exp < 0 {
+            return Err(error(
+                format!("Negative exponent {} in {} power", exp, ty),
+                "Use a floating-point base for fractional results, e.g. `2.0 ** -1`",
+            ));
+        }
+        let result = match base {
+            // 底数为 0、1、-1 时指数再大也不会溢出
+            0 | 1 if exp == 0 => Some(1),
+            0 | 1 => Some(base),
+            -1 => Some(if exp % 2 == 0 { 1 } else { -1 }),
+            _ => u32::try_from(exp).ok().and_then(|e| base.checked_pow(e)),
+        };
+        match result {
+            Some(n) if ty == "Long" || i32::try_from(n).is_ok() => Ok(n),
+            _ => Err(error(
+                format!("{} ** {} overflows {}", base, exp, ty),
+                "Use Long operands for larger integers, or a Double base for an approximate result",
+            )),
+        }
+    }
+
+    /// 整数运算的结果超出 `ty`（Int 或 Long）的范围；`expr` 为出错的运算，如 `2147483647 + 1`
+    fn integer_overflow(&self, code: &'static str, expr: String, ty: &str, line: usize, col: usize) -> Box<PawError> {
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code,
+            message: format!("{} overflows {}", expr, ty),
+            line,
+            column: col,
+            snippet: None,
+            hint: (ty == "Int").then(|| "Use Long operands for larger integers".into()),
+            backtrace: Vec::new(),
+        })
+    }
+
+    /// 二元运算，树遍历解释器与字节码 VM 共用
+    pub(crate) fn binary_value(
+        &self,
+        op: &BinaryOp,
+        l: Value,
+        r: Value,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        use crate::ast::expr::BinaryOp::*;
+        use crate::interpreter::value::ValueInner::*;
+
+        if let &EqEq = op {
+            return Ok(Value::Bool(l == r));
+        }
+        if let &NotEq = op {
+            return Ok(Value::Bool(l != r));
+        }
+        if let &Pow = op {
+            return self.pow_value(&l, &r, line, col);
+        }
+
+        let result = match (op, &*l.0, &*r.0) {
+            // —— 字符串拼接 ——
+            (Add, String(a), String(b)) => {
+                Value::String(a.as_str().to_string() + b.as_str())
+            }
+            (Add, String(a), other) => {
+                Value::String(a.as_str().to_string() + &format!("{}", other))
+            }
+            (Add, other, String(b)) => Value::String(format!("{}", other) + b.as_str()),
+
+            // —— 同类型基本情形 ——
+            // —— 整数溢出：抛出可捕获的 E6021，而不是在调试构建中 panic、在发布构建中回绕 ——
+            (Add | Sub | Mul, Int(a), Int(b)) => {
+                let (result, sym) = match op {
+                    Add => (a.checked_add(*b), "+"),
+                    Sub => (a.checked_sub(*b), "-"),
+                    _ => (a.checked_mul(*b), "*"),
+                };
+                match result {
+                    Some(n) => Value::Int(n),
+                    None => return Err(self.integer_overflow("E6021", format!("{} {} {}", a, sym, b), "Int", line, col)),
+                }
+            }
+            (Add | Sub | Mul, Long(a), Long(b)) => {
+                let (result, sym) = match op {
+                    Add => (a.checked_add(*b), "+"),
+                    Sub => (a.checked_sub(*b), "-"),
+                    _ => (a.checked_mul(*b), "*"),
+                };
+                match result {
+                    Some(n) => Value::Long(n),
+                    None => return Err(self.integer_overflow("E6021", format!("{} {} {}", a, sym, b), "Long", line, col)),
+                }
+            }
+            (Add, Float(a), Float(b)) => Value::Float(a + b),
+            (Add, Double(a), Double(b)) => Value::Double(a + b),
+
+            (Sub, Float(a), Float(b)) => Value::Float(a - b),
+            (Sub, Double(a), Double(b)) => Value::Double(a - b),
+
+            (Mul, Float(a), Float(b)) => Value::Float(a * b),
+            (Mul, Double(a), Double(b)) => Value::Double(a * b),
+
+            // —— 整数除以零：抛出可被 snatch 捕获的运行时错误，而不是让解释器 panic ——
+            (Div | Mod, Int(_), Int(0)) | (Div | Mod, Long(_), Long(0)) => {
+                return Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E6014",
+                    message: "Division by zero".into(),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: Some("Check that the divisor is not zero".into()),
+                    backtrace: Vec::new(),
+                }));
+            }
+
+            // —— 最小值除以 -1 超出范围：同样是可捕获的 E6014；取模的结果是 0，不会溢出 ——
+            (Div, Int(a), Int(b)) => match a.checked_div(*b) {
+                Some(n) => Value::Int(n),
+                None => return Err(self.integer_overflow("E6014", format!("{} / {}", a, b), "Int", line, col)),
+            },
+            (Div, Long(a), Long(b)) => match a.checked_div(*b) {
+                Some(n) => Value::Long(n),
+                None => return Err(self.integer_overflow("E6014", format!("{} / {}", a, b), "Long", line, col)),
+            },
+            (Div, Float(a), Float(b)) => Value::Float(a / b),
+            (Div, Double(a), Double(b)) => Value::Double(a / b),
+
+            (Mod, Int(a), Int(b)) => Value::Int(a.wrapping_rem(*b)),
+            (Mod, Long(a), Long(b)) => Value::Long(a.wrapping_rem(*b)),
+
+            // —— 混合 Int ↔ Float/Double ——
+            (Add, Int(a), Float(b)) => Value::Float((*a) as f32 + b),
+            (Add, Float(a), Int(b)) => Value::Float(a + (*b) as f32),
+            (Add, Int(a), Double(b)) => Value::Double((*a) as f64 + b),
+            (Add, Double(a), Int(b)) => Value::Double(a + (*b) as f64),
+            (Add, Long(a), Float(b)) => Value::Float((*a) as f32 + b),
+            (Add, Float(a), Long(b)) => Value::Float(a + (*b) as f32),
+            (Add, Long(a), Double(b)) => Value::Double((*a) as f64 + b),
+            (Add, Double(a), Long(b)) => Value::Double(a + (*b) as f64),
+
+            (Sub, Int(a), Float(b)) => Value::Float((*a) as f32 - b),
+            (Sub, Float(a), Int(b)) => Value::Float(a - (*b) as f32),
+            (Sub, Int(a), Double(b)) => Value::Double((*a) as f64 - b),
+            (Sub, Double(a), Int(b)) => Value::Double(a - (*b) as f64),
+            (Sub, Long(a), Float(b)) => Value::Float((*a) as f32 - b),
+            (Sub, Float(a), Long(b)) => Value::Float(a - (*b) as f32),
+            (Sub, Long(a), Double(b)) => Value::Double((*a) as f64 - b),
+            (Sub, Double(a), Long(b)) => Value::Double(a - (*b) as f64),
+
+            (Mul, Int(a), Float(b)) => Value::Float((*a) as f32 * b),
+            (Mul, Float(a), Int(b)) => Value::Float(a * (*b) as f32),
+            (Mul, Int(a), Double(b)) => Value::Double((*a) as f64 * b),
+            (Mul, Double(a), Int(b)) => Value::Double(a * (*b) as f64),
+            (Mul, Long(a), Float(b)) => Value::Float((*a) as f32 * b),
+            (Mul, Float(a), Long(b)) => Value::Float(a * (*b) as f32),
+            (Mul, Long(a), Double(b)) => Value::Double((*a) as f64 * b),
+            (Mul, Double(a), Long(b)) => Value::Double(a * (*b) as f64),
+
+            (Div, Int(a), Float(b)) => Value::Float((*a) as f32 / b),
+            (Div, Float(a), Int(b)) => Value::Float(a / (*b) as f32),
+            (Div, Int(a), Double(b)) => Value::Double((*a) as f64 / b),
+            (Div, Double(a), Int(b)) => Value::Double(a / (*b) as f64),
+            (Div, Long(a), Float(b)) => Value::Float((*a) as f32 / b),
+            (Div, Float(a), Long(b)) => Value::Float(a / (*b) as f32),
+            (Div, Long(a), Double(b)) => Value::Double((*a) as f64 / b),
+            (Div, Double(a), Long(b)) => Value::Double(a / (*b) as f64),
+
+            (Lt, Int(a), Int(b)) => Value::Bool(a < b),
+            (Lt, Long(a), Long(b)) => Value::Bool(a < b),
+            (Lt, Float(a), Float(b)) => Value::Bool(a < b),
+            (Lt, Double(a), Double(b)) => Value::Bool(a < b),
+
+            (Le, Int(a), Int(b)) => Value::Bool(a <= b),
+            (Le, Long(a), Long(b)) => Value::Bool(a <= b),
+            (Le, Float(a), Float(b)) => Value::Bool(a <= b),
+            (Le, Double(a), Double(b)) => Value::Bool(a <= b),
+
+            (Gt, Int(a), Int(b)) => Value::Bool(a > b),
+            (Gt, Long(a), Long(b)) => Value::Bool(a > b),
+            (Gt, Float(a), Float(b)) => Value::Bool(a > b),
+            (Gt, Double(a), Double(b)) => Value::Bool(a > b),
+
+            (Ge, Int(a), Int(b)) => Value::Bool(a >= b),
+            (Ge, Long(a), Long(b)) => Value::Bool(a >= b),
+            (Ge, Float(a), Float(b)) => Value::Bool(a >= b),
+            (Ge, Double(a), Double(b)) => Value::Bool(a >= b),
+
+            (And, Bool(a), Bool(b)) => Value::Bool(*a && *b),
+            (Or, Bool(a), Bool(b)) => Value::Bool(*a || *b),
+
+            // 不支持的组合
+            (_op, left_val, right_val) => {
+                return Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E3014",
+                    message: format!("Cannot {:?} and {:?}", left_val, right_val),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: None,
+                    backtrace: Vec::new(),
+                }))
+            }
+        };
+
+        // 字符串拼接每次都生成新字符串
+        if let String(s) = &*result.0 {
+            self.check_string_size(s.len(), line, col)?;
+            self.count_allocation();
+        }
+        Ok(result)
+    }
+
+    /// 下标取值：Array 按 Int 下标，越界为 nopaw；Map 按键取值，键不存在时报 E6029
+    pub(crate) fn index_value(
+        &self,
+        arr: &Value,
+        idx: &Value,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        // 解出内部枚举，然后匹配 Array 和 Int
+        let result = match (&*arr.0, &*idx.0) {
+            // 如果左侧是 Array，右侧是 Int，就取元素
+            (ValueInner::Array(v_arc), ValueInner::Int(i)) => {
+                // v_arc: &Arc<Vec<Value>>
+                let vec = &**v_arc;
+                vec.get(*i as usize)
+                    .cloned()
+                    .unwrap_or(Value::Null())
+            }
+            // 元组的下标已由类型检查器确认在范围内
+            (ValueInner::Tuple(items), ValueInner::Int(i)) => {
+                items.get(*i as usize).cloned().unwrap_or(Value::Null())
+            }
+            // Map 按键取值；键不存在是运行时错误，值的类型里没有 nopaw
+            (ValueInner::Map(m), _) => match m.get(idx) {
+                Some(v) => v.clone(),
+                None => {
+                    let key = match &*idx.0 {
+                        ValueInner::String(k) => format!("{:?}", k.as_str()),
+                        ValueInner::Char(c) => format!("{:?}", c),
+                        _ => idx.to_string(),
+                    };
+                    return Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E6029",
+                        message: format!("Key {} is not in the map", key),
+                        line,
+                        column: col,
+                        snippet: None,
+                        hint: Some("Check with `contains_key(k)` before reading the key".into()),
+                        backtrace: Vec::new(),
+                    }));
+                }
+            },
+            // 其余情况，都抛运行时错误
+            _ => {
+                return Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E3012",
+                    message: "Cannot index into non-array or non-int index".into(),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: None,
+                    backtrace: Vec::new(),
+                }));
+            }
+        };
+
+        Ok(result)
+    }
+
+    /// 按位置把元组 `v` 的元素定义为 `names`，`_` 跳过；
+    /// 类型为 Any 的宿主值只能在运行时确认形状
+    fn define_tuple(&mut self, names: &[String], v: &Value, line: usize, col: usize) -> Result<(), Box<PawError>> {
+        let ValueInner::Tuple(items) = &*v.0 else {
+            return Err(self.tuple_shape_error(v, names.len(), line, col));
+        };
+        if items.len() != names.len() {
+            return Err(self.tuple_shape_error(v, names.len(), line, col));
+        }
+        for (name, item) in names.iter().zip(items.iter()) {
+            if name != "_" {
+                self.env.define(name.clone(), item.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn tuple_shape_error(&self, value: &Value, names: usize, line: usize, col: usize) -> Box<PawError> {
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6017",
+            message: format!("Cannot destructure {} into {} names", value.type_name(), names),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("The value must be a tuple with one element per name".into()),
+            backtrace: Vec::new(),
+        })
+    }
+
+    /// 返回替换了 `idx` 位置元素后的新容器
+    fn set_index(
+        &self,
+        container: &Value,
+        idx: Value,
+        new_val: Value,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        match (&*container.0, &*idx.0) {
+            (ValueInner::Array(items), ValueInner::Int(i)) => {
+                if *i < 0 || *i as usize >= items.len() {
+                    return Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E6010",
+                        message: format!(
+                            "Index {} out of bounds for Array of length {}",
+                            i,
+                            items.len()
+                        ),
+                        line,
+                        column: col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    }));
+                }
+                let mut items = (**items).clone();
+                items[*i as usize] = new_val;
+                Ok(Value::Array(items))
+            }
+            (ValueInner::Map(m), _) => {
+                let mut m = (**m).clone();
+                m.insert(idx, new_val);
+                Ok(Value::from_inner(ValueInner::Map(Arc::new(m))))
+            }
+            _ => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E3012",
+                message: "Cannot assign by index into non-array or non-int index".into(),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+        }
+    }
+
+    /// 返回替换了字段 `field` 后的新 record
+    fn set_field(
+        &self,
+        container: &Value,
+        field: &str,
+        new_val: Value,
+        line: usize,
+        col: usize,
+    ) -> Result<Value, Box<PawError>> {
+        match &*container.0 {
+            ValueInner::Record(name, fields) if fields.contains_key(field) => {
+                let mut fields = (**fields).clone();
+                fields.insert(field.to_string(), new_val);
+                Ok(Value::Record(name, fields))
+            }
+            ValueInner::Record(..) => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E3015",
+                message: format!("Record has no field '{}'", field),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+            _ => Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6003",
+                message: format!("Cannot assign field '{}' on {}", field, container),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            })),
+        }
+    }
+
+    /// 沙箱模式下拒绝访问宿主资源，返回 E6015
+    pub(crate) fn check_sandbox(&self, what: &str, line: usize, col: usize) -> Result<(), Box<PawError>> {
+        if self.sandbox {
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6015",
+                message: format!("{} is not allowed in sandbox mode", what),
+                line,
+                column: col,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// 沙箱模式下字符串超过上限时返回 E6020
+    pub(crate) fn check_string_size(&self, bytes: usize, line: usize, col: usize) -> Result<(), Box<PawError>> {
+        let limit = self.limits.max_string_bytes;
+        if self.sandbox && bytes > limit {
+            let what = format!("a string of {} bytes", bytes);
+            return Err(self.quota_error(what, format!("{} bytes", limit), "--max-string-bytes", line, col));
+        }
+        Ok(())
+    }
+
+    /// 沙箱模式下数组、Map 或 Set 的元素数超过上限时返回 E6020
+    pub(crate) fn check_collection_size(&self, len: usize, line: usize, col: usize) -> Result<(), Box<PawError>> {
+        let limit = self.limits.max_collection_len;
+        if self.sandbox && len > limit {
+            let what = format!("a collection of {} elements", len);
+            return Err(self.quota_error(what, format!("{} elements", limit), "--max-collection-len", line, col));
         }
+        Ok(())
     }
 
-    /// 执行多条语句，遇到 return/throw 提前返回
+    fn quota_error(&self, what: String, limit: String, flag: &str, line: usize, col: usize) -> Box<PawError> {
+        Box::new(PawError::Runtime {
+            file: self.file.clone(),
+            code: "E6020",
+            message: format!("Quota exceeded: {} is over the sandbox limit of {}", what, limit),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some(format!("The limit can be raised with `{}` or `SizeLimits` when embedding", flag)),
+            backtrace: Vec::new(),
+        })
+    }
+
+    /// 所在任务已被取消时返回 E6008
+    pub(crate) fn check_cancelled(&self, line: usize, col: usize) -> Result<(), Box<PawError>> {
+        if self.cancel.is_cancelled() {
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E6008",
+                message: "Task cancelled".into(),
+                line,
+                column: col,
+                snippet: None,
+                hint: Some("A sibling task in the same task_group failed".into()),
+                backtrace: Vec::new(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// 尝试把循环编译为字节码并在 VM 中执行；不支持时返回 None，由树遍历解释器执行
+    fn run_compiled(&mut self, stmt: &Statement) -> Result<Option<ControlFlow>, Box<PawError>> {
+        // VM 不能调用脚本函数，登记了信号处理函数后循环改由解释器逐语句执行
+        if !self.vm || signals::has_handlers() {
+            return Ok(None);
+        }
+        match Compiler::compile_loop(stmt) {
+            Some(chunk) => self.run_chunk(&chunk).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 执行多条语句，遇到 return/break/continue/throw 提前返回
     pub async fn eval_statements<'a>(
         &mut self,
         stack: Stack<'a>,
         stmts: &[Statement],
-    ) -> Result<Option<Value>, PawError> {
+    ) -> Result<ControlFlow, Box<PawError>> {
+        // 先定义同一块中的函数，与检查器一致：函数可以在声明之前调用
+        for stmt in stmts {
+            if matches!(stmt.kind, StatementKind::FunDecl { .. }) {
+                Counters::bump(&self.counters.statements);
+                self.eval_statement(stack, stmt).await?;
+            }
+        }
         for stmt in stmts {
-            if let Some(v) = stack.run(self.eval_statement(stack, stmt)).await? {
-                return Ok(Some(v));
+            if matches!(stmt.kind, StatementKind::FunDecl { .. }) {
+                continue;
+            }
+            self.check_cancelled(stmt.line, stmt.col)?;
+            if let Some(handler) = signals::take_pending() {
+                self.call_function(stack, &handler, Vec::new(), "signal handler", stmt.line, stmt.col)
+                    .await?;
+            }
+            Counters::bump(&self.counters.statements);
+            let flow = stack.run(self.eval_statement(stack, stmt)).await?;
+            if !flow.is_normal() {
+                return Ok(flow);
             }
         }
-        Ok(None)
+        Ok(ControlFlow::Normal)
     }
 
     /// 执行单条语句
     pub async fn eval_statement<'a>(
         &mut self,
         stack: Stack<'a>,
-        stmt: &Statement) -> Result<Option<Value>, PawError> {
+        stmt: &Statement) -> Result<ControlFlow, Box<PawError>> {
         match &stmt.kind {
             StatementKind::Let { name, ty: _, value } => {
                 let v = stack.run(self.eval_expr(stack, value)).await?;
                 self.env.define(name.clone(), v);
-                Ok(None)
+                Ok(ControlFlow::Normal)
+            }
+
+            StatementKind::LetTuple { names, value, .. } => {
+                let v = stack.run(self.eval_expr(stack, value)).await?;
+                self.define_tuple(names, &v, stmt.line, stmt.col)?;
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::Assign { name, value } => {
                 let v = stack.run(self.eval_expr(stack, value)).await?;
-                self.env.assign(name, v)?;
-                Ok(None)
+                self.env.assign(name, v, &self.file, stmt.line, stmt.col)?;
+                Ok(ControlFlow::Normal)
+            }
+
+            StatementKind::AssignIndex {
+                array,
+                index,
+                value,
+            } => {
+                let container = stack.run(self.eval_expr(stack, array)).await?;
+                let idx = stack.run(self.eval_expr(stack, index)).await?;
+                let v = stack.run(self.eval_expr(stack, value)).await?;
+                let updated = self.set_index(&container, idx, v, stmt.line, stmt.col)?;
+                stack.run(self.assign_place(stack, array, updated)).await?;
+                Ok(ControlFlow::Normal)
+            }
+
+            StatementKind::AssignField {
+                object,
+                field,
+                value,
+            } => {
+                let container = stack.run(self.eval_expr(stack, object)).await?;
+                let v = stack.run(self.eval_expr(stack, value)).await?;
+                let updated = self.set_field(&container, field, v, stmt.line, stmt.col)?;
+                stack.run(self.assign_place(stack, object, updated)).await?;
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::Say(expr) => {
                 let v = stack.run(self.eval_expr(stack, expr)).await?;
                 println!("{}", v);
-                Ok(None)
+                Ok(ControlFlow::Normal)
+            }
+
+            StatementKind::SayDebug(expr) => {
+                let v = stack.run(self.eval_expr(stack, expr)).await?;
+                println!("{}", inspect(&v, Limits::default()));
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::Ask {
                 name,
-                ty: _,
+                ty,
                 prompt,
             } => {
-                print!("{}", prompt);
-                // 确保 prompt 立刻显示在终端
-                use std::io::Write;
-                let _ = std::io::stdout().flush();
-                let mut buf = String::new();
-                let _ = std::io::stdin().read_line(&mut buf);
-
-                self.env
-                    .define(name.clone(), Value::String(buf.trim_end().to_string()));
+                let answer = self.read_answer(prompt, stmt.line, stmt.col)?;
+                let value = self.ask_value(answer.trim_end(), ty, stmt.line, stmt.col)?;
+                self.env.define(name.clone(), value);
 
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::AskPrompt(prompt) => {
-                print!("{}", prompt);
-                // 同样要 flush
-                use std::io::Write;
-                let _ = std::io::stdout().flush();
-                let mut buf = String::new();
-                let _ = std::io::stdin().read_line(&mut buf);
-                Ok(None)
+                self.read_answer(prompt, stmt.line, stmt.col)?;
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::Import { module, alias } => {
+                // 标准库模块由原生函数组成，不读取文件
+                if let Some(module_val) = std_module(module, &self.host) {
+                    if needs_host_access(module) {
+                        self.check_sandbox(&format!("Importing '{}'", module.join(".")), stmt.line, stmt.col)?;
+                    }
+                    if is_nondeterministic(module) && self.host.is_deterministic() {
+                        return Err(Box::new(PawError::Runtime {
+                            file: self.file.clone(),
+                            code: "E6028",
+                            message: format!("Importing '{}' is not allowed in deterministic mode", module.join(".")),
+                            line: stmt.line,
+                            column: stmt.col,
+                            snippet: None,
+                            hint: Some("Its results depend on the terminal or on signals sent to the process".into()),
+                            backtrace: Vec::new(),
+                        }));
+                    }
+                    self.env.define(alias.clone(), module_val);
+                    return Ok(ControlFlow::Normal);
+                }
+                self.check_sandbox("Importing modules", stmt.line, stmt.col)?;
                 // 1. 拼出文件路径
-                let base_path = Path::new(&self.file);
-                let mut path = PathBuf::new();
-                path.push(base_path.parent().unwrap_or(Path::new(".")));
-                for seg in module {
-                    path.push(seg);
-                }
-                path.set_extension("paw");
-
-                // 2. 读源码
-                let src = std::fs::read_to_string(&path).map_err(|e| {
-                    // 根据 kind 构造英文提示
-                    let message = match e.kind() {
-                        ErrorKind::NotFound => {
-                            format!("Module file not found: {}", path.display())
-                        }
-                        ErrorKind::PermissionDenied => {
-                            format!("Permission denied reading module file: {}", path.display())
-                        }
-                        _ => format!("Failed to read module file: {}", path.display()),
-                    };
-                    PawError::Internal {
+                let path = module_path(&self.file, module);
+                let key = module_key(&path);
+
+                // 2. 正在加载的模块再次被导入，说明存在循环
+                if self.loading.contains(&key) {
+                    let chain: Vec<String> = self
+                        .loading
+                        .iter()
+                        .skip_while(|p| **p != key)
+                        .chain(std::iter::once(&key))
+                        .map(|p| p.display().to_string())
+                        .collect();
+                    return Err(Box::new(PawError::Runtime {
                         file: self.file.clone(),
-                        code: "E1002".into(),
-                        message,
-                        line: 0,
-                        column: 0,
+                        code: "E1005",
+                        message: format!("Circular import: {}", chain.join(" -> ")),
+                        line: stmt.line,
+                        column: stmt.col,
                         snippet: None,
                         hint: Some(
-                            "Check that the module file exists and the path is correct".into(),
+                            "Move the shared definitions into a module that both files import"
+                                .into(),
                         ),
-                    }
-                })?;
-
-                // 3. 词法 & 解析
-                let tokens = Lexer::new(&src).tokenize();
-                let mut parser = Parser::new(tokens, &src, &*path.to_string_lossy());
-                let stmts = parser.parse_program()?;
-
-                // 4. 语义检查
-                let mut checker = TypeChecker::new(&*path.to_string_lossy());
-                checker.check_program(&stmts)?;
-
-                // 5. 执行模块
-                let module_env = Env::with_parent(&self.env);
-                let mut module_interp =
-                    Engine::new(module_env.clone(), &*path.to_string_lossy());
-                let _ = stack.run(module_interp.eval_statements(stack, &stmts)).await?;
-
-                // 6. 收集子环境所有顶层绑定，打包成 Module
-                let module_val = {
-                    let m = module_env.bindings();
-                    Value::Module(m)
-                };
+                        backtrace: Vec::new(),
+                    }));
+                }
+
+                // 3. 已加载过的模块直接复用
+                let cached = self.modules.lock().get(&key).map(|m| m.value.clone());
+                if let Some(module_val) = cached {
+                    self.env.define(alias.clone(), module_val);
+                    return Ok(ControlFlow::Normal);
+                }
 
+                // 4. 读取、检查并执行模块文件
+                check_case(&self.file, &path, module, stmt.line, stmt.col)?;
+                let module_val = self.load_module(stack, &path, key).await?;
                 self.env.define(alias.clone(), module_val);
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::Return(opt) => {
@@ -172,15 +1854,15 @@ impl Engine {
                 } else {
                     Value::Null()
                 };
-                Ok(Some(v))
+                Ok(ControlFlow::Return(v))
             }
 
-            StatementKind::Break => Ok(Some(Value::Bool(true))),
-            StatementKind::Continue => Ok(Some(Value::Bool(false))),
+            StatementKind::Break(label) => Ok(ControlFlow::Break(label.clone())),
+            StatementKind::Continue(label) => Ok(ControlFlow::Continue(label.clone())),
 
             StatementKind::Expr(expr) => {
                 let _ = stack.run(self.eval_expr(stack, expr)).await?;
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::If {
@@ -192,166 +1874,424 @@ impl Engine {
                 let cond_val = stack.run(self.eval_expr(stack, condition)).await?;
 
                 // 2. 解构出内部 Arc<ValueInner>
-                if let Value(inner_arc) = cond_val.clone() {
-                    // inner_arc: Arc<ValueInner>
-                    if let ValueInner::Bool(true) = &*inner_arc {
-                        // then 分支
-                        if let Some(v) = stack.run(self.eval_statements(stack, body)).await? {
-                            return Ok(Some(v));
-                        }
-                        // 如果 then 不返回值，跳到最后的 Ok(None)
-                    } else if let Some(else_stmt) = else_branch {
-                        // else 分支（或嵌套的 if-else）
-                        if let Some(v) = stack.run(self.eval_statement(stack, else_stmt)).await? {
-                            return Ok(Some(v));
-                        }
-                    }
+                let Value(inner_arc) = cond_val;
+                if let ValueInner::Bool(true) = &*inner_arc {
+                    // then 分支，控制流信号原样向外传递
+                    return stack.run(self.eval_statements(stack, body)).await;
+                } else if let Some(else_stmt) = else_branch {
+                    // else 分支（或嵌套的 if-else）
+                    return stack.run(self.eval_statement(stack, else_stmt)).await;
                 }
 
-                // 3. 默认返回 None
-                Ok(None)
+                // 3. 条件不成立且没有 else
+                Ok(ControlFlow::Normal)
             }
 
-            StatementKind::LoopForever(body) => loop {
-                let res = stack.run(self.eval_statements(stack, body)).await?;
-                if res.is_some() {
-                    return Ok(res);
+            StatementKind::LoopForever { label, body } => {
+                if let Some(flow) = self.run_compiled(stmt)? {
+                    return Ok(flow);
                 }
-            },
+                loop {
+                    let mut scope = self.child(Env::with_parent(&self.env));
+                    match stack.run(scope.eval_statements(stack, body)).await? {
+                        ControlFlow::Break(target) if ControlFlow::aimed_at(&target, label) => break,
+                        ControlFlow::Continue(target) if ControlFlow::aimed_at(&target, label) => {}
+                        ControlFlow::Normal => {}
+                        // return 与指向外层循环的 break / continue 向外透传
+                        flow => return Ok(flow),
+                    }
+                }
+                Ok(ControlFlow::Normal)
+            }
 
-            StatementKind::LoopWhile { condition, body } => {
+            StatementKind::LoopWhile { label, condition, body } => {
+                if let Some(flow) = self.run_compiled(stmt)? {
+                    return Ok(flow);
+                }
                 loop {
                     // 1. 先求出条件
                     let cond_val = stack.run(self.eval_expr(stack, condition)).await?;
-                    // 2. 判断是否为 Bool(true)
-                    if cond_val != Value::Bool(true) {
-                        break;
+                    // 2. 必须是 Bool，false 时结束循环
+                    match &*cond_val.0 {
+                        ValueInner::Bool(true) => {}
+                        ValueInner::Bool(false) => break,
+                        _ => {
+                            return Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E3007",
+                                message: format!(
+                                    "Loop condition must be Bool, found {}",
+                                    cond_val.type_name()
+                                ),
+                                line: condition.line,
+                                column: condition.col,
+                                snippet: None,
+                                hint: None,
+                                backtrace: Vec::new(),
+                            }));
+                        }
+                    }
+                    // 3. 条件为真时执行循环体；break 结束循环，return 向外透传
+                    let mut scope = self.child(Env::with_parent(&self.env));
+                    match stack.run(scope.eval_statements(stack, body)).await? {
+                        ControlFlow::Break(target) if ControlFlow::aimed_at(&target, label) => break,
+                        ControlFlow::Continue(target) if ControlFlow::aimed_at(&target, label) => {}
+                        ControlFlow::Normal => {}
+                        // return 与指向外层循环的 break / continue 向外透传
+                        flow => return Ok(flow),
+                    }
+                }
+                Ok(ControlFlow::Normal)
+            }
+
+            StatementKind::LoopDoWhile {
+                label,
+                body,
+                condition,
+            } => {
+                if let Some(flow) = self.run_compiled(stmt)? {
+                    return Ok(flow);
+                }
+                loop {
+                    // 1. 先执行循环体；continue 同样进入条件检查
+                    let mut scope = self.child(Env::with_parent(&self.env));
+                    match stack.run(scope.eval_statements(stack, body)).await? {
+                        ControlFlow::Break(target) if ControlFlow::aimed_at(&target, label) => break,
+                        ControlFlow::Continue(target) if ControlFlow::aimed_at(&target, label) => {}
+                        ControlFlow::Normal => {}
+                        flow => return Ok(flow),
                     }
-                    // 3. 条件为真时执行循环体
-                    if let Some(v) = stack.run(self.eval_statements(stack, body)).await? {
-                        // 如果循环体里 return/break/continue 返回了值，就直接透传
-                        return Ok(Some(v));
+                    // 2. 条件必须是 Bool，false 时结束循环
+                    let cond_val = stack.run(self.eval_expr(stack, condition)).await?;
+                    match &*cond_val.0 {
+                        ValueInner::Bool(true) => {}
+                        ValueInner::Bool(false) => break,
+                        _ => {
+                            return Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E3007",
+                                message: format!(
+                                    "Loop condition must be Bool, found {}",
+                                    cond_val.type_name()
+                                ),
+                                line: condition.line,
+                                column: condition.col,
+                                snippet: None,
+                                hint: None,
+                                backtrace: Vec::new(),
+                            }));
+                        }
                     }
-                    // 否则继续下一次循环
                 }
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::LoopRange {
+                label,
                 var,
                 start,
                 end,
+                step,
+                inclusive,
                 body,
             } => {
-                // 先分别计算 start、end
+                if let Some(flow) = self.run_compiled(stmt)? {
+                    return Ok(flow);
+                }
+                // 先分别计算 start、end 与 step
                 let s_val = stack.run(self.eval_expr(stack, start)).await?;
                 let e_val = stack.run(self.eval_expr(stack, end)).await?;
-
-                use crate::interpreter::value::ValueInner;
-                // 解构出两个 i32
-                let (si, ei) = match (&*s_val.0, &*e_val.0) {
-                    (ValueInner::Int(si), ValueInner::Int(ei)) => (*si, *ei),
-                    // 如果不是 Int，就直接跳过循环
-                    _ => return Ok(None),
+                let step = match step {
+                    Some(step) => Some(stack.run(self.eval_expr(stack, step)).await?),
+                    None => None,
                 };
+                let range = self.range_value(&s_val, &e_val, step.as_ref(), *inclusive, stmt.line, stmt.col)?;
 
                 // 执行范围循环
-                for i in si..ei {
-                    self.env.define(var.clone(), Value::Int(i));
-                    if let Some(v) = stack.run(self.eval_statements(stack, body)).await? {
-                        return Ok(Some(v));
+                for i in range.iter() {
+                    // 每次迭代一层新作用域，闭包捕获的是本次迭代的循环变量
+                    let mut scope = self.child(Env::with_parent(&self.env));
+                    scope.env.define(var.clone(), i);
+                    match stack.run(scope.eval_statements(stack, body)).await? {
+                        ControlFlow::Break(target) if ControlFlow::aimed_at(&target, label) => break,
+                        ControlFlow::Continue(target) if ControlFlow::aimed_at(&target, label) => {}
+                        ControlFlow::Normal => {}
+                        // return 与指向外层循环的 break / continue 向外透传
+                        flow => return Ok(flow),
                     }
                 }
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
-            StatementKind::LoopArray { var, array, body } => {
-                // 1. 求值出数组对象
+            StatementKind::LoopArray { label, var, array, body } => {
+                if let Some(flow) = self.run_compiled(stmt)? {
+                    return Ok(flow);
+                }
+                // 1. 求值出被遍历的值
                 let arr_val = stack.run(self.eval_expr(stack, array)).await?;
-                // 2. 必须是 Array，否则跳过
-                let elems = match &*arr_val.0 {
-                    ValueInner::Array(v_arc) => &**v_arc,
-                    _ => return Ok(None),
+                // 2. 按遍历协议取元素，不能遍历的值（只可能来自 Any）在这里报错
+                let Some(items) = arr_val.loop_items() else {
+                    return Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E3018",
+                        message: format!("Cannot loop over {}", arr_val.type_name()),
+                        line: array.line,
+                        column: array.col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    }));
                 };
                 // 3. 遍历每个元素
-                for item in elems {
-                    // 将循环变量绑定到当前环境
-                    self.env.define(var.clone(), item.clone());
-                    // 执行循环体，遇到 return/break/continue 即透传
-                    if let Some(v) = stack.run(self.eval_statements(stack, body)).await? {
-                        return Ok(Some(v));
+                for item in items {
+                    // 每次迭代一层新作用域，循环变量绑定在其中
+                    let mut scope = self.child(Env::with_parent(&self.env));
+                    match var {
+                        LoopVar::Name(name) => scope.env.define(name.clone(), item),
+                        LoopVar::Tuple(names) => scope.define_tuple(names, &item, stmt.line, stmt.col)?,
+                    }
+                    // 执行循环体，break 结束循环，return 向外透传
+                    match stack.run(scope.eval_statements(stack, body)).await? {
+                        ControlFlow::Break(target) if ControlFlow::aimed_at(&target, label) => break,
+                        ControlFlow::Continue(target) if ControlFlow::aimed_at(&target, label) => {}
+                        ControlFlow::Normal => {}
+                        // return 与指向外层循环的 break / continue 向外透传
+                        flow => return Ok(flow),
                     }
                 }
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::FunDecl {
                 name,
                 params,
+                type_params: _,
                 return_type: _,
                 is_async,
+                barks: _,
                 body,
             } => {
-                let func = Value::Function(
+                let mut func = Value::Function(
                     name.clone(),
                     params.clone(),
                     body.clone(),
                     self.env.clone(),
                     *is_async,
                 );
+                // 同一作用域中已有同名函数时作为重载并入（检查器保证参数类型不同）；
+                // 检查器验证过的 `@pure` 函数缓存结果，并入重载时取出被包装的函数
+                match self.env.get_local(name) {
+                    Some(existing) => {
+                        let existing = match &*existing.0 {
+                            ValueInner::Memoized { func, .. } => func.clone(),
+                            _ => existing,
+                        };
+                        if matches!(&*existing.0, ValueInner::Function { .. } | ValueInner::Overloaded(_)) {
+                            func = Value::overload(&existing, func, |ty| self.runtime_type(ty));
+                        }
+                    }
+                    None if stmt.is_pure() => func = Value::Memoized(func),
+                    None => {}
+                }
                 self.env.define(name.clone(), func);
-                Ok(None)
+                Ok(ControlFlow::Normal)
             }
 
             StatementKind::Block(stmts) => {
                 let child_env = Env::with_parent(&self.env);
-                let mut child = Engine::new(child_env, &self.file);
-                let _ = stack.run(child.eval_statements(stack, stmts)).await?;
-                Ok(None)
+                let mut child = self.child(child_env);
+                stack.run(child.eval_statements(stack, stmts)).await
+            }
+
+            StatementKind::Match {
+                subject,
+                arms,
+                table,
+            } => {
+                let value = stack.run(self.eval_expr(stack, subject)).await?;
+                // 开头的字面量分支直接查表，其余分支按顺序尝试
+                let found = match value.match_key().and_then(|key| table.keys.get(&key)) {
+                    Some(&arm) if arm < table.linear_from => Some((arm, Vec::new())),
+                    _ => arms.iter().enumerate().skip(table.linear_from).find_map(|(i, arm)| {
+                        arm.patterns.iter().find_map(|pattern| {
+                            let mut bound = Vec::new();
+                            self.match_pattern(pattern, &value, &mut bound).then_some((i, bound))
+                        })
+                    }),
+                };
+                let Some((arm, bound)) = found else {
+                    return Ok(ControlFlow::Normal);
+                };
+                let mut child = self.child(Env::with_parent(&self.env));
+                for (name, v) in bound {
+                    child.env.define(name, v);
+                }
+                stack.run(child.eval_statements(stack, &arms[arm].body)).await
             }
 
             StatementKind::TryCatchFinally {
                 body,
-                err_name,
-                handler,
+                clauses,
                 finally,
             } => {
                 // try
                 let try_res = {
-                    let mut ti = Engine::new(Env::with_parent(&self.env), &self.file);
+                    let mut ti = self.child(Env::with_parent(&self.env));
                     stack.run(ti.eval_statements(stack, body)).await
                 };
-                match try_res {
-                    Ok(Some(v)) => return Ok(Some(v)),
-                    Ok(None) => { /* 正常 */ }
+                let flow = match try_res {
+                    Ok(flow) => flow,
                     Err(err) => {
-                        return if let PawError::Runtime { message, .. } = err {
-                            // catch
-                            let mut ci = Engine::new(Env::with_parent(&self.env), &self.file);
-                            ci.env.define(err_name.clone(), Value::String(message));
-                            let catch_r = stack.run(ci.eval_statements(stack, handler)).await?;
-                            // finally
-                            let _ = stack.run(Engine::new(Env::with_parent(&self.env), &self.file)
+                        // catch：第一个匹配的 snatch 子句处理错误，没有匹配时错误继续向外传播
+                        let Some((clause, bound)) = clauses.iter().find_map(|c| snatch_match(c, &err))
+                        else {
+                            stack.run(self.child(Env::with_parent(&self.env))
                                 .eval_statements(stack, finally))
                                 .await?;
-                            Ok(catch_r)
-                        } else {
-                            Err(err)
-                        }
+                            return Err(err);
+                        };
+                        let mut ci = self.child(Env::with_parent(&self.env));
+                        ci.env.define(clause.name.clone(), bound);
+                        let catch_r = stack.run(ci.eval_statements(stack, &clause.body)).await?;
+                        // finally
+                        let _ = stack.run(self.child(Env::with_parent(&self.env))
+                            .eval_statements(stack, finally))
+                            .await?;
+                        return Ok(catch_r);
                     }
-                }
-                // finally after normal
-                let _ = stack.run(Engine::new(Env::with_parent(&self.env), &self.file)
+                };
+                // finally：正常结束或 return/break/continue 离开 try 时都会执行
+                let _ = stack.run(self.child(Env::with_parent(&self.env))
                     .eval_statements(stack, finally))
                     .await?;
-                Ok(None)
+                Ok(flow)
+            }
+
+            StatementKind::TaskGroup(body) => {
+                let group = Arc::new(Mutex::new(Vec::new()));
+                let token = CancelToken::child_of(&self.cancel);
+                let mut scope = self.child(Env::with_parent(&self.env));
+                scope.group = Some(group.clone());
+                scope.cancel = token.clone();
+
+                let body_res = stack.run(scope.eval_statements(stack, body)).await;
+                if body_res.is_err() {
+                    token.cancel();
+                }
+                let children = std::mem::take(&mut *group.lock());
+                let mut errors = scope.join_group(children, &token).await;
+                let ret = body_res?;
+
+                match errors.len() {
+                    0 => Ok(ret),
+                    1 => Err(errors.remove(0)),
+                    n => {
+                        let details: Vec<String> =
+                            errors.iter().map(|e| format!("[{}] {}", e.code(), e.message())).collect();
+                        Err(Box::new(PawError::Runtime {
+                            file: self.file.clone(),
+                            code: "E6009",
+                            message: format!("{} tasks in task_group failed: {}", n, details.join("; ")),
+                            line: stmt.line,
+                            column: stmt.col,
+                            snippet: None,
+                            hint: None,
+                            backtrace: Vec::new(),
+                        }))
+                    }
+                }
+            }
+
+            StatementKind::Spawn(expr) => {
+                let v = stack.run(self.eval_expr(stack, expr)).await?;
+                if let (ValueInner::Future(_), Some(group)) = (&*v.0, &self.group) {
+                    group.lock().push(v.clone());
+                }
+                Ok(ControlFlow::Normal)
             }
 
-            StatementKind::RecordDecl { .. } => Ok(None),
+            StatementKind::RecordDecl { name, methods, .. } => {
+                // 方法捕获声明处的环境，按 record 名登记
+                if !methods.is_empty() {
+                    let table = methods
+                        .iter()
+                        .filter_map(|method| match &method.kind {
+                            StatementKind::FunDecl {
+                                name: method,
+                                params,
+                                body,
+                                is_async,
+                                ..
+                            } => Some((
+                                method.clone(),
+                                Value::Function(
+                                    format!("{}.{}", name, method),
+                                    params.clone(),
+                                    body.clone(),
+                                    self.env.clone(),
+                                    *is_async,
+                                ),
+                            )),
+                            _ => None,
+                        })
+                        .collect();
+                    self.methods.lock().insert(name.clone(), table);
+                }
+                Ok(ControlFlow::Normal)
+            }
+            StatementKind::ProtocolDecl { .. } => Ok(ControlFlow::Normal),
+
+            // enum 名绑定为模块：不带数据的变体是值，带数据的变体是构造函数
+            StatementKind::EnumDecl { name, variants } => {
+                let mut members = AHashMap::new();
+                for v in variants {
+                    let member = if v.payload.is_empty() {
+                        Value::Enum(name, &v.name, Vec::new())
+                    } else {
+                        let (enum_name, variant, arity) = (name.clone(), v.name.clone(), v.payload.len());
+                        Value::Native(&format!("{}.{}", name, v.name), move |args| {
+                            if args.len() != arity {
+                                return Err(format!(
+                                    "{}.{} expects {} value(s), found {}",
+                                    enum_name,
+                                    variant,
+                                    arity,
+                                    args.len()
+                                ));
+                            }
+                            Ok(Value::Enum(&enum_name, &variant, args))
+                        })
+                        .into_pure()
+                    };
+                    members.insert(v.name.clone(), member);
+                }
+                self.enums.lock().insert(name.clone());
+                self.env.define(name.clone(), Value::Module(members));
+                Ok(ControlFlow::Normal)
+            }
 
             StatementKind::Throw(expr) => {
                 let v = stack.run(self.eval_expr(stack, expr)).await?;
-                Err(PawError::Runtime {
+                // bark 一个 record：以 record 类型名作为错误名，`message` 字段作为错误信息
+                if let ValueInner::Record(name, fields) = &*v.0 {
+                    let message = fields
+                        .get("message")
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| name.to_string());
+                    return Err(Box::new(PawError::Custom {
+                        name: name.to_string(),
+                        file: self.file.clone(),
+                        code: "E6001",
+                        message,
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: Some(format!("Uncaught {}; catch it with `snatch (e: {})`", name, name)),
+                        payload: v.clone(),
+                        backtrace: Vec::new(),
+                    }));
+                }
+                Err(Box::new(PawError::Runtime {
                     file: self.file.clone(),
                     code: "E6001",
                     message: format!("{}", v),
@@ -359,13 +2299,14 @@ impl Engine {
                     column: stmt.col,
                     snippet: None,
                     hint: Some("Uncaught exception".into()),
-                })
+                    backtrace: Vec::new(),
+                }))
             }
         }
     }
 
     /// 计算表达式，返回一个可 await 的 Future
-    pub async fn eval_expr(&mut self, stack: Stack<'_>, expr: &Expr) -> Result<Value, PawError> {
+    pub async fn eval_expr(&mut self, stack: Stack<'_>, expr: &Expr) -> Result<Value, Box<PawError>> {
         match &expr.kind {
             ExprKind::LiteralInt(n) => Ok(Value::Int(*n)),
             ExprKind::LiteralLong(n) => Ok(Value::Long(*n)),
@@ -376,10 +2317,43 @@ impl Engine {
             ExprKind::LiteralChar(c) => Ok(Value::Char(*c)),
             ExprKind::LiteralNopaw => Ok(Value::Null()),
 
+            ExprKind::Lambda {
+                params,
+                is_async,
+                body,
+                ..
+            } => {
+                self.count_allocation();
+                Ok(Value::Function(
+                    "<lambda>".into(),
+                    params.clone(),
+                    body.clone(),
+                    self.env.clone(),
+                    *is_async,
+                ))
+            }
+
+            ExprKind::Interpolation(parts) => {
+                let mut out = String::new();
+                for part in parts {
+                    let v = stack.run(self.eval_expr(stack, part)).await?;
+                    out.push_str(&v.to_string());
+                    self.check_string_size(out.len(), expr.line, expr.col)?;
+                }
+                self.count_allocation();
+                Ok(Value::String(out))
+            }
+
+            ExprKind::Formatted { expr: inner, spec } => {
+                let v = stack.run(self.eval_expr(stack, inner)).await?;
+                self.count_allocation();
+                Ok(Value::String(self.format_value(spec, &v, expr.line, expr.col)?))
+            }
+
             ExprKind::Var(name) => {
                 self.env
                     .get(name.as_str())
-                    .ok_or_else(|| PawError::UndefinedVariable {
+                    .ok_or_else(|| Box::new(PawError::UndefinedVariable {
                         file: self.file.clone(),
                         code: "E4001",
                         name: name.clone(),
@@ -387,197 +2361,27 @@ impl Engine {
                         column: expr.col,
                         snippet: None,
                         hint: Some("Did you declare this variable before use?".into()),
-                    })
+                    }))
             }
 
             ExprKind::UnaryOp { op, expr: inner } => {
-                // 1. 先求值子表达式
+                // 先求值子表达式，再做同步的一元运算（字节码 VM 共用同一实现）
                 let v = stack.run(self.eval_expr(stack, inner)).await?;
-                use crate::interpreter::value::ValueInner;
-
-                // 2. 匹配操作符，本分支保证每条路径都返回 Result<Value, PawError>
-                match op.as_str() {
-                    // 负号
-                    "-" => {
-                        // 解构 Value 到内部 Arc<ValueInner>
-                        let inner_arc = match v {
-                            Value(inner) => inner,
-                        };
-                        match &*inner_arc {
-                            ValueInner::Int(i) => Ok(Value::Int(-i)),
-                            ValueInner::Long(l) => Ok(Value::Long(-l)),
-                            ValueInner::Float(f) => Ok(Value::Float(-f)),
-                            other => Err(PawError::Runtime {
-                                file: self.file.clone(),
-                                code: "E3013".into(),
-                                message: format!("Bad unary `{}` on {:?}", op, other),
-                                line: expr.line,
-                                column: expr.col,
-                                snippet: None,
-                                hint: None,
-                            }),
-                        }
-                    }
-
-                    // 逻辑非
-                    "!" => {
-                        let inner_arc = match v {
-                            Value(inner) => inner,
-                        };
-                        match &*inner_arc {
-                            ValueInner::Bool(b) => Ok(Value::Bool(!b)),
-                            other => Err(PawError::Runtime {
-                                file: self.file.clone(),
-                                code: "E3013".into(),
-                                message: format!("Bad unary `{}` on {:?}", op, other),
-                                line: expr.line,
-                                column: expr.col,
-                                snippet: None,
-                                hint: None,
-                            }),
-                        }
-                    }
-
-                    // 其他未知一元操作符
-                    _ => Err(PawError::Internal {
-                        file: self.file.clone(),
-                        code: "E6002".into(),
-                        message: format!("Unknown unary operator `{}`", op),
-                        line: expr.line,
-                        column: expr.col,
-                        snippet: None,
-                        hint: None,
-                    }),
-                }
+                self.unary_value(op, v, expr.line, expr.col)
             }
 
             ExprKind::BinaryOp { op, left, right } => {
-                // 先 await 两边
+                // 先 await 两边，再做同步的二元运算（字节码 VM 共用同一实现）；
+                // `&&` 左侧为 false、`||` 左侧为 true 时不再求右侧
                 let l = stack.run(self.eval_expr(stack, left)).await?;
-                let r = stack.run(self.eval_expr(stack, right)).await?;
-                use crate::ast::expr::BinaryOp::*;
-                use crate::interpreter::value::ValueInner::*;
-
-                if let &As = op {
-                    return Ok(r.clone());
-                }
-
-                if let &EqEq = op {
-                    return Ok(Value::Bool(l == r));
-                }
-                if let &NotEq = op {
-                    return Ok(Value::Bool(l != r));
-                }
-
-                let result = match (op, &*l.0, &*r.0) {
-                    // —— 字符串拼接 ——
-                    (Add, String(a), String(b)) => {
-                        Value::String(a.as_str().to_string() + b.as_str())
-                    }
-                    (Add, String(a), other) => {
-                        Value::String(a.as_str().to_string() + &format!("{}", other))
+                match (op, &*l.0) {
+                    (BinaryOp::And, ValueInner::Bool(false)) | (BinaryOp::Or, ValueInner::Bool(true)) => {
+                        return Ok(l)
                     }
-                    (Add, other, String(b)) => Value::String(format!("{}", other) + b.as_str()),
-
-                    // —— 同类型基本情形 ——
-                    (Add, Int(a), Int(b)) => Value::Int(a + b),
-                    (Add, Long(a), Long(b)) => Value::Long(a + b),
-                    (Add, Float(a), Float(b)) => Value::Float(a + b),
-                    (Add, Double(a), Double(b)) => Value::Double(a + b),
-
-                    (Sub, Int(a), Int(b)) => Value::Int(a - b),
-                    (Sub, Long(a), Long(b)) => Value::Long(a - b),
-                    (Sub, Float(a), Float(b)) => Value::Float(a - b),
-                    (Sub, Double(a), Double(b)) => Value::Double(a - b),
-
-                    (Mul, Int(a), Int(b)) => Value::Int(a * b),
-                    (Mul, Long(a), Long(b)) => Value::Long(a * b),
-                    (Mul, Float(a), Float(b)) => Value::Float(a * b),
-                    (Mul, Double(a), Double(b)) => Value::Double(a * b),
-
-                    (Div, Int(a), Int(b)) => Value::Int(a / b),
-                    (Div, Long(a), Long(b)) => Value::Long(a / b),
-                    (Div, Float(a), Float(b)) => Value::Float(a / b),
-                    (Div, Double(a), Double(b)) => Value::Double(a / b),
-
-                    (Mod, Int(a), Int(b)) => Value::Int(a % b),
-                    (Mod, Long(a), Long(b)) => Value::Long(a % b),
-
-                    // —— 混合 Int ↔ Float/Double ——
-                    (Add, Int(a), Float(b)) => Value::Float((*a) as f32 + b),
-                    (Add, Float(a), Int(b)) => Value::Float(a + (*b) as f32),
-                    (Add, Int(a), Double(b)) => Value::Double((*a) as f64 + b),
-                    (Add, Double(a), Int(b)) => Value::Double(a + (*b) as f64),
-                    (Add, Long(a), Float(b)) => Value::Float((*a) as f32 + b),
-                    (Add, Float(a), Long(b)) => Value::Float(a + (*b) as f32),
-                    (Add, Long(a), Double(b)) => Value::Double((*a) as f64 + b),
-                    (Add, Double(a), Long(b)) => Value::Double(a + (*b) as f64),
-
-                    (Sub, Int(a), Float(b)) => Value::Float((*a) as f32 - b),
-                    (Sub, Float(a), Int(b)) => Value::Float(a - (*b) as f32),
-                    (Sub, Int(a), Double(b)) => Value::Double((*a) as f64 - b),
-                    (Sub, Double(a), Int(b)) => Value::Double(a - (*b) as f64),
-                    (Sub, Long(a), Float(b)) => Value::Float((*a) as f32 - b),
-                    (Sub, Float(a), Long(b)) => Value::Float(a - (*b) as f32),
-                    (Sub, Long(a), Double(b)) => Value::Double((*a) as f64 - b),
-                    (Sub, Double(a), Long(b)) => Value::Double(a - (*b) as f64),
-
-                    (Mul, Int(a), Float(b)) => Value::Float((*a) as f32 * b),
-                    (Mul, Float(a), Int(b)) => Value::Float(a * (*b) as f32),
-                    (Mul, Int(a), Double(b)) => Value::Double((*a) as f64 * b),
-                    (Mul, Double(a), Int(b)) => Value::Double(a * (*b) as f64),
-                    (Mul, Long(a), Float(b)) => Value::Float((*a) as f32 * b),
-                    (Mul, Float(a), Long(b)) => Value::Float(a * (*b) as f32),
-                    (Mul, Long(a), Double(b)) => Value::Double((*a) as f64 * b),
-                    (Mul, Double(a), Long(b)) => Value::Double(a * (*b) as f64),
-
-                    (Div, Int(a), Float(b)) => Value::Float((*a) as f32 / b),
-                    (Div, Float(a), Int(b)) => Value::Float(a / (*b) as f32),
-                    (Div, Int(a), Double(b)) => Value::Double((*a) as f64 / b),
-                    (Div, Double(a), Int(b)) => Value::Double(a / (*b) as f64),
-                    (Div, Long(a), Float(b)) => Value::Float((*a) as f32 / b),
-                    (Div, Float(a), Long(b)) => Value::Float(a / (*b) as f32),
-                    (Div, Long(a), Double(b)) => Value::Double((*a) as f64 / b),
-                    (Div, Double(a), Long(b)) => Value::Double(a / (*b) as f64),
-
-                    (Lt, Int(a), Int(b)) => Value::Bool(a < b),
-                    (Lt, Long(a), Long(b)) => Value::Bool(a < b),
-                    (Lt, Float(a), Float(b)) => Value::Bool(a < b),
-                    (Lt, Double(a), Double(b)) => Value::Bool(a < b),
-
-                    (Le, Int(a), Int(b)) => Value::Bool(a <= b),
-                    (Le, Long(a), Long(b)) => Value::Bool(a <= b),
-                    (Le, Float(a), Float(b)) => Value::Bool(a <= b),
-                    (Le, Double(a), Double(b)) => Value::Bool(a <= b),
-
-                    (Gt, Int(a), Int(b)) => Value::Bool(a > b),
-                    (Gt, Long(a), Long(b)) => Value::Bool(a > b),
-                    (Gt, Float(a), Float(b)) => Value::Bool(a > b),
-                    (Gt, Double(a), Double(b)) => Value::Bool(a > b),
-
-                    (Ge, Int(a), Int(b)) => Value::Bool(a >= b),
-                    (Ge, Long(a), Long(b)) => Value::Bool(a >= b),
-                    (Ge, Float(a), Float(b)) => Value::Bool(a >= b),
-                    (Ge, Double(a), Double(b)) => Value::Bool(a >= b),
-
-                    (And, Bool(a), Bool(b)) => Value::Bool(*a && *b),
-                    (Or, Bool(a), Bool(b)) => Value::Bool(*a || *b),
-
-                    // 不支持的组合
-                    (_op, left_val, right_val) => {
-                        return Err(PawError::Runtime {
-                            file: self.file.clone(),
-                            code: "E3014",
-                            message: format!("Cannot {:?} and {:?}", left_val, right_val),
-                            line: expr.line,
-                            column: expr.col,
-                            snippet: None,
-                            hint: None,
-                        })
-                    }
-                };
-
-                Ok(result)
+                    _ => {}
+                }
+                let r = stack.run(self.eval_expr(stack, right)).await?;
+                self.binary_value(op, l, r, expr.line, expr.col)
             }
 
             ExprKind::Call { name, args } => {
@@ -587,149 +2391,196 @@ impl Engine {
                     arg_vals.push(stack.run(self.eval_expr(stack, e)).await?);
                 }
 
-                // 2. 查找函数
-                let func_val = self.env.get(name).ok_or_else(|| PawError::UndefinedVariable {
+                // 2. 查找函数，脚本中没有定义时再找内置函数
+                let Some(func_val) = self.env.get(name) else {
+                    return self
+                        .call_builtin(stack, name, arg_vals, expr.line, expr.col)
+                        .await
+                        .unwrap_or_else(|| {
+                            Err(Box::new(PawError::UndefinedVariable {
+                                file: self.file.clone(),
+                                code: "E4001",
+                                name: name.clone(),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Did you declare this function before use?".into()),
+                            }))
+                        });
+                };
+
+                // 3. 调用
+                self.call_function(stack, &func_val, arg_vals, name, expr.line, expr.col)
+                    .await
+            }
+
+            ExprKind::Cast { expr: inner, ty } => {
+                let v = stack.run(self.eval_expr(stack, inner)).await?;
+                let target = self.runtime_type(ty);
+                cast(&v, &target).map_err(|message| Box::new(PawError::Runtime {
                     file: self.file.clone(),
-                    code: "E4001",
-                    name: name.clone(),
+                    code: "E6019",
+                    message,
                     line: expr.line,
                     column: expr.col,
                     snippet: None,
-                    hint: Some("Did you declare this function before use?".into()),
-                })?;
-
-                // 3. 解出内部 ValueInner
-                use crate::interpreter::value::{Value, ValueInner};
-                let inner_arc = match func_val {
-                    Value(inner) => inner,         // 解出 Arc<ValueInner>
-                };
-
-                // 4. 匹配 Function 分支
-                match &*inner_arc {
-                    ValueInner::Function {
-                        params,
-                        body,
-                        env: fenv,
-                        is_async,
-                        ..
-                    } => {
-                        if *is_async {
-                            // —— 异步调用 ——
-                            let mut new_interp = Engine::new(Env::with_parent(fenv), &self.file);
-                            for (p, v) in params.iter().zip(arg_vals) {
-                                new_interp.env.define(p.name.clone(), v);
-                            }
-                            if let Some(ret) = stack.run(new_interp.eval_statements(stack, body)).await? {
-                                Ok(ret)
-                            } else {
-                                Ok(Value::Null())
-                            }
-                        } else {
-                            // —— 同步调用 ——
-                            let saved = self.env.clone();
-                            let mut child = Engine::new(Env::with_parent(fenv), &self.file);
-                            for (p, v) in params.iter().zip(arg_vals) {
-                                child.env.define(p.name.clone(), v);
-                            }
-                            let res = stack.run(child.eval_statements(stack, body)).await?;
-                            self.env = saved;
-                            Ok(res.unwrap_or(Value::Null()))
-                        }
-                    }
-
-                    // —— 不是函数，直接报错 —— 
-                    _ => Err(PawError::Runtime {
-                        file: self.file.clone(),
-                        code: "E4002".into(),
-                        message: format!("{} is not callable", name),
-                        line: expr.line,
-                        column: expr.col,
-                        snippet: None,
-                        hint: None,
-                    }),
-                }
+                    hint: Some(cast_hint(&v, &target)),
+                    backtrace: Vec::new(),
+                }))
             }
 
+            ExprKind::TypeTest { expr: inner, ty } => {
+                let v = stack.run(self.eval_expr(stack, inner)).await?;
+                Ok(Value::Bool(v.is_instance(&self.runtime_type(ty))))
+            }
 
-            ExprKind::Cast {
-                expr: inner,
-                ty: _ty,
+            ExprKind::Range {
+                start,
+                end,
+                step,
+                inclusive,
             } => {
-                let v = stack.run(self.eval_expr(stack, inner)).await?;
-                Ok(v)
+                let s = stack.run(self.eval_expr(stack, start)).await?;
+                let e = stack.run(self.eval_expr(stack, end)).await?;
+                let step = match step {
+                    Some(step) => Some(stack.run(self.eval_expr(stack, step)).await?),
+                    None => None,
+                };
+                let range = self.range_value(&s, &e, step.as_ref(), *inclusive, expr.line, expr.col)?;
+                self.count_allocation();
+                Ok(Value::from_inner(ValueInner::Range(range)))
             }
 
             ExprKind::ArrayLiteral(elems) => {
+                self.check_collection_size(elems.len(), expr.line, expr.col)?;
                 let mut items = Vec::with_capacity(elems.len());
                 for e in elems {
                     items.push(stack.run(self.eval_expr(stack, e)).await?);
                 }
+                self.count_allocation();
                 Ok(Value::Array(items))
             }
 
-            ExprKind::Index { array, index } => {
-                // 1. 先 Eval 两个子表达式
-                let arr_val = stack.run(self.eval_expr(stack, array)).await?;
-                let idx_val = stack.run(self.eval_expr(stack, index)).await?;
+            ExprKind::MapLiteral(entries) => {
+                let mut map = PawMap::with_capacity_and_hasher(entries.len(), Default::default());
+                for (k, v) in entries {
+                    let key = stack.run(self.eval_expr(stack, k)).await?;
+                    let val = stack.run(self.eval_expr(stack, v)).await?;
+                    map.insert(key, val);
+                }
+                self.count_allocation();
+                Ok(Value::from_inner(ValueInner::Map(Arc::new(map))))
+            }
 
-                use crate::interpreter::value::ValueInner;
+            ExprKind::SetLiteral(items) => {
+                let mut set = PawSet::with_capacity_and_hasher(items.len(), Default::default());
+                for e in items {
+                    set.insert(stack.run(self.eval_expr(stack, e)).await?);
+                }
+                self.count_allocation();
+                Ok(Value::from_inner(ValueInner::Set(Arc::new(set))))
+            }
 
-                // 2. 解出内部枚举，然后匹配 Array 和 Int
-                let result = match (&*arr_val.0, &*idx_val.0) {
-                    // 如果左侧是 Array，右侧是 Int，就取元素
-                    (ValueInner::Array(v_arc), ValueInner::Int(i)) => {
-                        // v_arc: &Arc<Vec<Value>>
-                        let vec = &**v_arc;
-                        vec.get(*i as usize)
-                            .cloned()
-                            .unwrap_or(Value::Null())
-                    }
-                    // 其余情况，都抛运行时错误
-                    _ => {
-                        return Err(PawError::Runtime {
-                            file: self.file.clone(),
-                            code: "E3012".into(),
-                            message: "Cannot index into non-array or non-int index".into(),
-                            line: expr.line,
-                            column: expr.col,
-                            snippet: None,
-                            hint: None,
-                        });
-                    }
-                };
+            ExprKind::TupleLiteral(items) => {
+                let mut values = Vec::with_capacity(items.len());
+                for e in items {
+                    values.push(stack.run(self.eval_expr(stack, e)).await?);
+                }
+                self.count_allocation();
+                Ok(Value::Tuple(values))
+            }
 
-                Ok(result)
+            ExprKind::Index { array, index } => {
+                // 先 Eval 两个子表达式，再按容器类型取值
+                let arr_val = stack.run(self.eval_expr(stack, array)).await?;
+                let idx_val = stack.run(self.eval_expr(stack, index)).await?;
+                self.index_value(&arr_val, &idx_val, expr.line, expr.col)
             }
 
-            ExprKind::RecordInit { name: _, fields } => {
+            ExprKind::RecordInit { name, fields } => {
                 let mut map = AHashMap::new();
                 for (fname, fexpr) in fields {
                     let v = stack.run(self.eval_expr(stack, fexpr)).await?;
                     map.insert(fname.clone(), v);
                 }
-                Ok(Value::Record(map))
+                self.count_allocation();
+                Ok(Value::Record(name, map))
+            }
+
+            ExprKind::Propagate { expr: inner } => {
+                let val = stack.run(self.eval_expr(stack, inner)).await?;
+                match &*val.0 {
+                    ValueInner::Result { ok: true, value } => Ok(value.clone()),
+                    ValueInner::Result { ok: false, .. } => Err(Box::new(PawError::Propagate {
+                        file: self.file.clone(),
+                        value: val.clone(),
+                        line: expr.line,
+                        column: expr.col,
+                    })),
+                    // 类型为 Any 的值只能在运行时确认
+                    _ => Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E6018",
+                        message: format!("`?` expects a Result, found {}", val.type_name()),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: Some("Wrap the value with ok(...) or err(...)".into()),
+                        backtrace: Vec::new(),
+                    })),
+                }
             }
 
             ExprKind::Await { expr: inner } => {
+                self.check_writable("await", expr.line, expr.col)?;
                 // 1. 先 eval 出一个 Value
                 let val = stack.run(self.eval_expr(stack, inner)).await?;
 
-                // 2. 解出内部的 ValueInner
-                use crate::interpreter::value::ValueInner;
-                // Value 是 tuple struct(Value(pub Arc<ValueInner>))
-                if let Value(inner_arc) = val.clone() {
-                    // inner_arc: Arc<ValueInner>
-                    if let ValueInner::Future(fut_arc) = &*inner_arc {
-                        // 3. 用 futures::lock::Mutex 异步锁
-                        let mut guard = fut_arc.lock().await;
-                        // 4. await 内部的 Future
-                        let result = guard.as_mut().await?;
-                        return Ok(result);
-                    }
+                // 2. 不是 Future 时原样返回
+                if !matches!(&*val.0, ValueInner::Future(_)) {
+                    return Ok(val);
+                }
+
+                // 3. 首次 await 驱动 Future 并缓存结果，再次 await 得到同一个结果（包括错误）；
+                //    执行中途被取消的 Future 没有结果可返回
+                match val.await_future().await {
+                    Some(res) => res,
+                    None => Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E6007",
+                        message: "Future was cancelled before it completed".into(),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: Some("A future whose task was cancelled has no result to await".into()),
+                        backtrace: Vec::new(),
+                    })),
+                }
+            }
+
+            ExprKind::Tail { expr: inner } => {
+                self.check_writable("start a task", expr.line, expr.col)?;
+                let val = stack.run(self.eval_expr(stack, inner)).await?;
+                if !matches!(&*val.0, ValueInner::Future(_)) {
+                    return Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E3037",
+                        message: format!("`tail` expects a Future, found {}", val.type_name()),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    }));
                 }
+                Ok(self.start_task(val, expr.line, expr.col))
+            }
 
-                // 5. 如果不是 Future，原样返回
-                Ok(val)
+            ExprKind::AsyncBlock(body) => {
+                self.check_writable("run an async block", expr.line, expr.col)?;
+                self.count_allocation();
+                let child = self.child(Env::with_parent(&self.env));
+                Ok(self.make_future(child, Arc::new(body.clone()), expr.line, expr.col))
             }
 
             ExprKind::FieldAccess { expr: inner, field } => {
@@ -738,36 +2589,52 @@ impl Engine {
 
                 // 2. 解出内部的 ValueInner
                 use crate::interpreter::value::ValueInner;
-                if let ValueInner::Record(map_arc) = &*obj_val.0 {
+                if let ValueInner::Record(_, map_arc) = &*obj_val.0 {
                     // map_arc: &Arc<AHashMap<String, Value>>
-                    let map: &AHashMap<String, Value> = &**map_arc;
+                    let map: &AHashMap<String, Value> = map_arc;
 
                     // 3. 在 Record map 中查字段
                     if let Some(v) = map.get(field) {
                         Ok(v.clone())
+                    } else if let Some(method) = self.bound_method(&obj_val, field) {
+                        Ok(method)
                     } else {
                         // Record 中无此字段
-                        Err(PawError::Runtime {
+                        Err(Box::new(PawError::Runtime {
                             file: self.file.clone(),
-                            code: "E3015".into(),
+                            code: "E3015",
                             message: format!("Record has no field '{}'", field),
                             line: expr.line,
                             column: expr.col,
                             snippet: None,
                             hint: None,
-                        })
+                            backtrace: Vec::new(),
+                        }))
                     }
+                } else if let ValueInner::Module(members) = &*obj_val.0 {
+                    // 模块成员：函数或常量
+                    members.get(field).cloned().ok_or_else(|| Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E6005",
+                        message: format!("Module has no member '{}'", field),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    }))
                 } else {
                     // 非 Record 类型，报错
-                    Err(PawError::Runtime {
+                    Err(Box::new(PawError::Runtime {
                         file: self.file.clone(),
-                        code: "E6003".into(),
+                        code: "E6003",
                         message: format!("Cannot access field '{}' on {:?}", field, obj_val),
                         line: expr.line,
                         column: expr.col,
                         snippet: None,
                         hint: Some(format!("Type {:?} has no fields", obj_val)),
-                    })
+                        backtrace: Vec::new(),
+                    }))
                 }
             }
 
@@ -776,6 +2643,54 @@ impl Engine {
                 method,
                 args,
             } => {
+                // push / pop 作用于变量时直接修改变量中的数组，数组未被共享时不必复制
+                if let (Method::Push | Method::Pop, ExprKind::Var(name)) = (method, &receiver.kind) {
+                    let holds_array = matches!(
+                        self.env.get(name).as_ref().map(|v| &*v.0),
+                        Some(ValueInner::Array(_))
+                    );
+                    let arity = if *method == Method::Push { 1 } else { 0 };
+                    if holds_array && args.len() == arity {
+                        let mut arg_vals = Vec::with_capacity(args.len());
+                        for a in args {
+                            arg_vals.push(stack.run(self.eval_expr(stack, a)).await?);
+                        }
+                        if let Some(ValueInner::Array(items)) = self.env.get(name).as_ref().map(|v| &*v.0) {
+                            let len = items.len() + arg_vals.len();
+                            self.check_collection_size(len, expr.line, expr.col)?;
+                        }
+                        let popped = self.env.update(name, |slot| match &*slot.0 {
+                            ValueInner::Array(_) => match Arc::make_mut(&mut slot.0) {
+                                ValueInner::Array(items) => push_or_pop(Arc::make_mut(items), method, arg_vals),
+                                _ => unreachable!(),
+                            },
+                            _ => None,
+                        });
+                        return match popped.flatten() {
+                            Some(v) => Ok(v),
+                            None => Err(self.pop_empty_error(expr.line, expr.col)),
+                        };
+                    }
+                }
+                // Map 的 insert / remove 与 Set 的 add / remove 作用于变量时同样原地修改，并返回修改后的集合
+                if let ExprKind::Var(name) = &receiver.kind {
+                    let current = self.env.get(name);
+                    if current.as_ref().is_some_and(|v| updates_collection(v, method, args.len())) {
+                        drop(current);
+                        let mut arg_vals = Vec::with_capacity(args.len());
+                        for a in args {
+                            arg_vals.push(stack.run(self.eval_expr(stack, a)).await?);
+                        }
+                        if let Some(len) = self.env.get(name).and_then(|v| grown_len(&v, method, &arg_vals)) {
+                            self.check_collection_size(len, expr.line, expr.col)?;
+                        }
+                        let updated = self.env.update(name, |slot| {
+                            update_collection(slot, method, &arg_vals);
+                            slot.clone()
+                        });
+                        return Ok(updated.unwrap_or(Value::Null()));
+                    }
+                }
                 // 1. Evaluate the receiver expression
                 let recv = stack.run(self.eval_expr(stack, receiver)).await?;
                 // 2. Evaluate all argument expressions
@@ -784,6 +2699,7 @@ impl Engine {
                     arg_vals.push(stack.run(self.eval_expr(stack, a)).await?);
                 }
                 // 3. Dispatch based on the receiver’s runtime type
+                let receiver_value = recv.clone();
                 match recv {
                     Value(inner_arc) => match &*inner_arc {
                         ValueInner::String(s) => {
@@ -807,9 +2723,9 @@ impl Engine {
                                     if let Some(p) = arg_vals[0].as_str() {
                                         Ok(Value::Bool(s.as_str().starts_with(p)))
                                     } else {
-                                        Err(PawError::Runtime {
+                                        Err(Box::new(PawError::Runtime {
                                             file: self.file.clone(),
-                                            code: "E6003".into(),
+                                            code: "E6003",
                                             message: format!(
                                                 "Method `starts_with` expects one string argument, got {:?}",
                                                 arg_vals
@@ -818,16 +2734,17 @@ impl Engine {
                                             column: expr.col,
                                             snippet: None,
                                             hint: Some("Use: someString.starts_with(otherString)".into()),
-                                        })
+                                            backtrace: Vec::new(),
+                                        }))
                                     }
                                 }
                                 Method::EndsWith if arg_vals.len() == 1 => {
                                     if let Some(p) = arg_vals[0].as_str() {
                                         Ok(Value::Bool(s.as_str().ends_with(p)))
                                     } else {
-                                        Err(PawError::Runtime {
+                                        Err(Box::new(PawError::Runtime {
                                             file: self.file.clone(),
-                                            code: "E6003".into(),
+                                            code: "E6003",
                                             message: format!(
                                                 "Method `ends_with` expects one string argument, got {:?}",
                                                 arg_vals
@@ -836,16 +2753,17 @@ impl Engine {
                                             column: expr.col,
                                             snippet: None,
                                             hint: Some("Use: someString.ends_with(otherString)".into()),
-                                        })
+                                            backtrace: Vec::new(),
+                                        }))
                                     }
                                 }
                                 Method::Contains if arg_vals.len() == 1 => {
                                     if let Some(p) = arg_vals[0].as_str() {
                                         Ok(Value::Bool(s.as_str().contains(p)))
                                     } else {
-                                        Err(PawError::Runtime {
+                                        Err(Box::new(PawError::Runtime {
                                             file: self.file.clone(),
-                                            code: "E6003".into(),
+                                            code: "E6003",
                                             message: format!(
                                                 "Method `contains` expects one string argument, got {:?}",
                                                 arg_vals
@@ -854,55 +2772,263 @@ impl Engine {
                                             column: expr.col,
                                             snippet: None,
                                             hint: Some("Use: someString.contains(otherString)".into()),
-                                        })
+                                            backtrace: Vec::new(),
+                                        }))
+                                    }
+                                }
+                                Method::ToInt | Method::ToDouble if arg_vals.len() <= 1 => {
+                                    let ty = if *method == Method::ToInt { "Int" } else { "Double" };
+                                    let separator = arg_vals.first().and_then(|v| v.as_str());
+                                    parse_number(s.as_str(), ty, separator).map_err(|message| Box::new(PawError::Runtime {
+                                        file: self.file.clone(),
+                                        code: "E6016",
+                                        message,
+                                        line: expr.line,
+                                        column: expr.col,
+                                        snippet: None,
+                                        hint: None,
+                                        backtrace: Vec::new(),
+                                    }))
+                                }
+                                Method::Split if arg_vals.len() == 1 => match arg_vals[0].as_str() {
+                                    Some(sep) => Ok(Value::Array(
+                                        s.as_str().split(sep).map(|part| Value::String(part.to_string())).collect(),
+                                    )),
+                                    None => Err(Box::new(PawError::Runtime {
+                                        file: self.file.clone(),
+                                        code: "E6003",
+                                        message: format!(
+                                            "Method `split` expects one string argument, got {:?}",
+                                            arg_vals
+                                        ),
+                                        line: expr.line,
+                                        column: expr.col,
+                                        snippet: None,
+                                        hint: Some("Use: someString.split(separator)".into()),
+                                        backtrace: Vec::new(),
+                                    })),
+                                },
+                                Method::Replace if arg_vals.len() == 2 => {
+                                    match (arg_vals[0].as_str(), arg_vals[1].as_str()) {
+                                        (Some(from), Some(to)) => {
+                                            let out = s.as_str().replace(from, to);
+                                            self.check_string_size(out.len(), expr.line, expr.col)?;
+                                            Ok(Value::String(out))
+                                        }
+                                        _ => Err(Box::new(PawError::Runtime {
+                                            file: self.file.clone(),
+                                            code: "E6003",
+                                            message: format!(
+                                                "Method `replace` expects two string arguments, got {:?}",
+                                                arg_vals
+                                            ),
+                                            line: expr.line,
+                                            column: expr.col,
+                                            snippet: None,
+                                            hint: Some("Use: someString.replace(from, to)".into()),
+                                            backtrace: Vec::new(),
+                                        })),
+                                    }
+                                }
+                                Method::Substring if arg_vals.len() == 2 => {
+                                    // 按字符（而非字节）计数，区间左闭右开
+                                    let (start, end) = match (&*arg_vals[0].0, &*arg_vals[1].0) {
+                                        (ValueInner::Int(a), ValueInner::Int(b)) => (*a, *b),
+                                        _ => {
+                                            return Err(Box::new(PawError::Runtime {
+                                                file: self.file.clone(),
+                                                code: "E6003",
+                                                message: format!(
+                                                    "Method `substring` expects two Int arguments, got {:?}",
+                                                    arg_vals
+                                                ),
+                                                line: expr.line,
+                                                column: expr.col,
+                                                snippet: None,
+                                                hint: Some("Use: someString.substring(start, end)".into()),
+                                                backtrace: Vec::new(),
+                                            }))
+                                        }
+                                    };
+                                    let len = s.as_str().chars().count();
+                                    if start < 0 || end < start || end as usize > len {
+                                        return Err(Box::new(PawError::Runtime {
+                                            file: self.file.clone(),
+                                            code: "E6010",
+                                            message: format!(
+                                                "Substring range {}..{} out of bounds for String of length {}",
+                                                start, end, len
+                                            ),
+                                            line: expr.line,
+                                            column: expr.col,
+                                            snippet: None,
+                                            hint: Some("Require 0 <= start <= end <= length()".into()),
+                                            backtrace: Vec::new(),
+                                        }));
                                     }
+                                    Ok(Value::String(
+                                        s.as_str()
+                                            .chars()
+                                            .skip(start as usize)
+                                            .take((end - start) as usize)
+                                            .collect::<String>(),
+                                    ))
                                 }
-                                _ => Err(PawError::Runtime {
+                                _ => Err(Box::new(PawError::Runtime {
                                     file: self.file.clone(),
-                                    code: "E6003".into(),
+                                    code: "E6003",
                                     message: format!("Cannot call method '{}' on String", method),
                                     line: expr.line,
                                     column: expr.col,
                                     snippet: None,
                                     hint: Some(format!("Type String has no method '{}'", method)),
-                                }),
+                                    backtrace: Vec::new(),
+                                })),
                             }
                         }
 
                         // ————— Array methods —————
                         ValueInner::Array(v_arc) => {
-                            let mut v = (**v_arc).clone();
+                            // 只读方法直接借用共享的数组，返回新数组的方法才复制
+                            let v: &Vec<Value> = v_arc;
 
                             match method {
-                                Method::Push if matches!(&arg_vals[..], [_x]) => {
-                                    v.push(arg_vals[0].clone());
-                                    Ok(Value::Array(v))
+                                // 接收者是 `a[i]`、`r.f` 之类的左值时写回；临时数组的修改随之丢弃
+                                Method::Push | Method::Pop if arg_vals.len() == usize::from(*method == Method::Push) => {
+                                    self.check_collection_size(v.len() + arg_vals.len(), expr.line, expr.col)?;
+                                    let mut items = v.clone();
+                                    let Some(result) = push_or_pop(&mut items, method, arg_vals) else {
+                                        return Err(self.pop_empty_error(expr.line, expr.col));
+                                    };
+                                    if matches!(receiver.kind, ExprKind::Index { .. } | ExprKind::FieldAccess { .. }) {
+                                        stack.run(self.assign_place(stack, receiver, Value::Array(items))).await?;
+                                    }
+                                    Ok(result)
                                 }
-                                Method::Pop if arg_vals.is_empty() => {
-                                    if let Some(x) = v.pop() {
-                                        Ok(x) // 直接把元素作为 Value::<T> 返回
-                                    } else {
-                                        // 数组空时抛出运行时错误
-                                        Err(PawError::Runtime {
+                                Method::Length if arg_vals.is_empty() => {
+                                    Ok(Value::Int(v.len() as i32))
+                                }
+                                Method::ToSet if arg_vals.is_empty() => {
+                                    Ok(Value::Set(v.iter().cloned()))
+                                }
+                                Method::Map if arg_vals.len() == 1 => {
+                                    let mut out = Vec::with_capacity(v.len());
+                                    for item in v.iter().cloned() {
+                                        let mapped = self
+                                            .call_function(stack, &arg_vals[0], vec![item], "map callback", expr.line, expr.col)
+                                            .await?;
+                                        out.push(mapped);
+                                    }
+                                    Ok(Value::Array(out))
+                                }
+                                Method::Filter if arg_vals.len() == 1 => {
+                                    let mut out = Vec::new();
+                                    for item in v.iter().cloned() {
+                                        let keep = self
+                                            .call_function(stack, &arg_vals[0], vec![item.clone()], "filter callback", expr.line, expr.col)
+                                            .await?;
+                                        match &*keep.0 {
+                                            ValueInner::Bool(true) => out.push(item),
+                                            ValueInner::Bool(false) => {}
+                                            _ => {
+                                                return Err(Box::new(PawError::Runtime {
+                                                    file: self.file.clone(),
+                                                    code: "E6003",
+                                                    message: format!(
+                                                        "filter callback must return Bool, got {}",
+                                                        keep.type_name()
+                                                    ),
+                                                    line: expr.line,
+                                                    column: expr.col,
+                                                    snippet: None,
+                                                    hint: None,
+                                                    backtrace: Vec::new(),
+                                                }));
+                                            }
+                                        }
+                                    }
+                                    Ok(Value::Array(out))
+                                }
+                                Method::Reduce if arg_vals.len() == 2 => {
+                                    let mut acc = arg_vals[1].clone();
+                                    for item in v.iter().cloned() {
+                                        acc = self
+                                            .call_function(stack, &arg_vals[0], vec![acc, item], "reduce callback", expr.line, expr.col)
+                                            .await?;
+                                    }
+                                    Ok(acc)
+                                }
+                                Method::Join if arg_vals.len() == 1 => {
+                                    let Some(sep) = arg_vals[0].as_str() else {
+                                        return Err(Box::new(PawError::Runtime {
                                             file: self.file.clone(),
-                                            code: "E3016".into(), // 你可以定义一个新的错误码
-                                            message: "Cannot pop from empty array".into(),
+                                            code: "E6003",
+                                            message: format!(
+                                                "Method `join` expects one string argument, got {:?}",
+                                                arg_vals
+                                            ),
                                             line: expr.line,
                                             column: expr.col,
                                             snippet: None,
-                                            hint: Some(
-                                                "Ensure array is non-empty before calling pop"
-                                                    .into(),
+                                            hint: Some("Use: someArray.join(separator)".into()),
+                                            backtrace: Vec::new(),
+                                        }));
+                                    };
+                                    let parts: Vec<String> = v.iter().map(|item| item.to_string()).collect();
+                                    let out = parts.join(sep);
+                                    self.check_string_size(out.len(), expr.line, expr.col)?;
+                                    Ok(Value::String(out))
+                                }
+                                Method::Reverse if arg_vals.is_empty() => {
+                                    Ok(Value::Array(v.iter().rev().cloned().collect()))
+                                }
+                                Method::Slice if arg_vals.len() == 2 => {
+                                    let (start, end) = match (&*arg_vals[0].0, &*arg_vals[1].0) {
+                                        (ValueInner::Int(a), ValueInner::Int(b)) => (*a, *b),
+                                        _ => {
+                                            return Err(Box::new(PawError::Runtime {
+                                                file: self.file.clone(),
+                                                code: "E6003",
+                                                message: format!(
+                                                    "Method `slice` expects two Int arguments, got {:?}",
+                                                    arg_vals
+                                                ),
+                                                line: expr.line,
+                                                column: expr.col,
+                                                snippet: None,
+                                                hint: Some("Use: someArray.slice(start, end)".into()),
+                                                backtrace: Vec::new(),
+                                            }))
+                                        }
+                                    };
+                                    if start < 0 || end < start || end as usize > v.len() {
+                                        return Err(Box::new(PawError::Runtime {
+                                            file: self.file.clone(),
+                                            code: "E6010",
+                                            message: format!(
+                                                "Slice range {}..{} out of bounds for Array of length {}",
+                                                start,
+                                                end,
+                                                v.len()
                                             ),
-                                        })
+                                            line: expr.line,
+                                            column: expr.col,
+                                            snippet: None,
+                                            hint: Some("Require 0 <= start <= end <= length()".into()),
+                                            backtrace: Vec::new(),
+                                        }));
                                     }
+                                    Ok(Value::Array(v[start as usize..end as usize].to_vec()))
                                 }
-                                Method::Length if arg_vals.is_empty() => {
-                                    Ok(Value::Int(v.len() as i32))
+                                Method::Sort if arg_vals.len() <= 1 => {
+                                    let sorted = self
+                                        .sort_values(stack, v.clone(), arg_vals.first(), expr.line, expr.col)
+                                        .await?;
+                                    Ok(Value::Array(sorted))
                                 }
-                                _ => Err(PawError::Runtime {
+                                _ => Err(Box::new(PawError::Runtime {
                                     file: self.file.clone(),
-                                    code: "E6003".into(),
+                                    code: "E6003",
                                     message: format!("Cannot call method '{}' on Array", method),
                                     line: expr.line,
                                     column: expr.col,
@@ -910,95 +3036,411 @@ impl Engine {
                                     hint: Some(
                                         "Type Array has no such method or wrong args".into(),
                                     ),
-                                }),
+                                    backtrace: Vec::new(),
+                                })),
                             }
                         }
 
+                        // ————— Map methods —————
+                        ValueInner::Map(m_arc) => match (method, arg_vals.as_slice()) {
+                            (Method::Insert, [_, _]) | (Method::Remove, [_]) => {
+                                stack
+                                    .run(self.update_collection_at(stack, receiver, &receiver_value, method, &arg_vals, expr.line, expr.col))
+                                    .await
+                            }
+                            (Method::ContainsKey, [k]) => Ok(Value::Bool(m_arc.contains_key(k))),
+                            (Method::Keys, []) => Ok(Value::Array(m_arc.keys().cloned().collect())),
+                            (Method::Values, []) => Ok(Value::Array(m_arc.values().cloned().collect())),
+                            (Method::Length, []) => Ok(Value::Int(m_arc.len() as i32)),
+                            _ => Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6003",
+                                message: format!("Cannot call method '{}' on Map", method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Type Map has no such method or wrong args".into()),
+                                backtrace: Vec::new(),
+                            })),
+                        },
+
+                        // ————— Set methods —————
+                        ValueInner::Set(items) => match (method, arg_vals.as_slice()) {
+                            (Method::Add, [_]) | (Method::Remove, [_]) => {
+                                stack
+                                    .run(self.update_collection_at(stack, receiver, &receiver_value, method, &arg_vals, expr.line, expr.col))
+                                    .await
+                            }
+                            (Method::Contains, [x]) => Ok(Value::Bool(items.contains(x))),
+                            (Method::Length, []) => Ok(Value::Int(items.len() as i32)),
+                            (Method::ToArray, []) => Ok(Value::Array(items.iter().cloned().collect())),
+                            (Method::Union | Method::Intersection | Method::Difference, [other])
+                                if matches!(&*other.0, ValueInner::Set(_)) =>
+                            {
+                                let ValueInner::Set(other) = &*other.0 else { unreachable!() };
+                                let set: PawSet = match method {
+                                    Method::Union => items.union(other).cloned().collect(),
+                                    Method::Intersection => items.intersection(other).cloned().collect(),
+                                    _ => items.difference(other).cloned().collect(),
+                                };
+                                Ok(Value::from_inner(ValueInner::Set(Arc::new(set))))
+                            }
+                            _ => Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6003",
+                                message: format!("Cannot call method '{}' on Set", method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Type Set has no such method or wrong args".into()),
+                                backtrace: Vec::new(),
+                            })),
+                        },
+
+                        // ————— Result methods —————
+                        ValueInner::Result { ok, value } => match (method, arg_vals.as_slice()) {
+                            (Method::IsOk, []) => Ok(Value::Bool(*ok)),
+                            (Method::IsErr, []) => Ok(Value::Bool(!*ok)),
+                            (Method::Unwrap, []) if *ok => Ok(value.clone()),
+                            (Method::Unwrap, []) => Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6018",
+                                message: format!("Called unwrap() on err({})", value),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Check is_ok() first, or use unwrap_or(default)".into()),
+                                backtrace: Vec::new(),
+                            })),
+                            (Method::UnwrapOr, [default]) => {
+                                Ok(if *ok { value.clone() } else { default.clone() })
+                            }
+                            (Method::Error, []) => Ok(if *ok { Value::Null() } else { value.clone() }),
+                            _ => Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6003",
+                                message: format!("Cannot call method '{}' on Result", method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Type Result has no such method or wrong args".into()),
+                                backtrace: Vec::new(),
+                            })),
+                        },
+
+                        // ————— Range methods：按端点与步长计算，不展开 —————
+                        ValueInner::Range(range) => match (method.as_str(), arg_vals.as_slice()) {
+                            ("length", []) => match i32::try_from(range.len()) {
+                                Ok(n) => Ok(Value::Int(n)),
+                                Err(_) => Err(Box::new(PawError::Runtime {
+                                    file: self.file.clone(),
+                                    code: "E6022",
+                                    message: format!("Range {} has more than {} elements", range, i32::MAX),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                    backtrace: Vec::new(),
+                                })),
+                            },
+                            ("contains", [n]) => match &*n.0 {
+                                ValueInner::Int(n) => Ok(Value::Bool(range.contains(*n as i64))),
+                                ValueInner::Long(n) => Ok(Value::Bool(range.contains(*n))),
+                                _ => Ok(Value::Bool(false)),
+                            },
+                            ("to_array", []) => {
+                                let len = usize::try_from(range.len()).unwrap_or(usize::MAX);
+                                self.check_collection_size(len, expr.line, expr.col)?;
+                                self.count_allocation();
+                                Ok(Value::Array(range.iter().collect()))
+                            }
+                            _ => Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6003",
+                                message: format!("Cannot call method '{}' on Range", method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Type Range has no such method or wrong args".into()),
+                                backtrace: Vec::new(),
+                            })),
+                        },
+
+                        // ————— StringBuilder methods：原地修改，append / clear 返回同一个缓冲区 —————
+                        ValueInner::StringBuilder(buf) => match (method.as_str(), arg_vals.as_slice()) {
+                            ("append", _) | ("clear", _) if self.read_only => {
+                                Err(self.read_only_error("modify a StringBuilder", expr.line, expr.col))
+                            }
+                            ("append", [piece]) => {
+                                // 先格式化再加锁：piece 可能就是这个缓冲区本身
+                                let text = piece.to_string();
+                                let mut buf = buf.lock();
+                                self.check_string_size(buf.len() + text.len(), expr.line, expr.col)?;
+                                buf.push_str(&text);
+                                Ok(Value(inner_arc.clone()))
+                            }
+                            ("clear", []) => {
+                                buf.lock().clear();
+                                Ok(Value(inner_arc.clone()))
+                            }
+                            ("to_string", []) => Ok(Value::String(buf.lock().clone())),
+                            ("length", []) => Ok(Value::Int(buf.lock().chars().count() as i32)),
+                            _ => Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E6003",
+                                message: format!("Cannot call method '{}' on StringBuilder", method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("Type StringBuilder has no such method or wrong args".into()),
+                                backtrace: Vec::new(),
+                            })),
+                        },
+
                         // ————— Module: property lookup or immediate call —————
                         ValueInner::Module(module_map_arc) => {
                             let module_map = &**module_map_arc;
                             let key = method.as_str();
 
                             if let Some(member_val) = module_map.get(key) {
-                                if let ValueInner::Function {
-                                    params,
-                                    body,
-                                    env: fenv,
-                                    is_async,
-                                    name: _,
-                                } = &*member_val.0
+                                if let ValueInner::Function { .. }
+                                | ValueInner::NativeFunction { .. }
+                                | ValueInner::Memoized { .. }
+                                | ValueInner::Overloaded(_) = &*member_val.0
                                 {
-                                    let params = (**params).clone();
-                                    let body = (**body).clone();
-                                    let fenv = fenv.clone();
-                                    let is_async = *is_async;
-
-                                    // Async function call
-                                    if is_async {
-                                        let mut new_i =
-                                            Engine::new(Env::with_parent(&fenv), &self.file);
-                                        for (p, v) in params.iter().zip(arg_vals.into_iter()) {
-                                            new_i.env.define(p.name.clone(), v);
-                                        }
-                                        if let Some(ret) = stack.run(new_i.eval_statements(stack, &body)).await? {
-                                            Ok(ret)
-                                        } else {
-                                            Ok(Value::Null())
-                                        }
-                                    }
-                                    // Sync function call
-                                    else {
-                                        let saved = self.env.clone();
-                                        let mut child =
-                                            Engine::new(Env::with_parent(&fenv), &self.file);
-                                        for (p, v) in params.iter().zip(arg_vals.into_iter()) {
-                                            child.env.define(p.name.clone(), v);
-                                        }
-                                        let res = stack.run(child.eval_statements(stack, &body)).await?;
-                                        self.env = saved;
-                                        Ok(res.unwrap_or(Value::Null()))
-                                    }
+                                    let member_val = member_val.clone();
+                                    self.call_function(stack, &member_val, arg_vals, key, expr.line, expr.col)
+                                        .await
                                 }
                                 // Non‐function: only zero‐arg property access
                                 else if arg_vals.is_empty() {
                                     Ok(member_val.clone())
                                 } else {
-                                    Err(PawError::Runtime {
+                                    Err(Box::new(PawError::Runtime {
                                         file: self.file.clone(),
-                                        code: "E6003".into(),
+                                        code: "E6003",
                                         message: format!("Cannot call method '{}' on Module", key),
                                         line: expr.line,
                                         column: expr.col,
                                         snippet: None,
                                         hint: Some(format!("Type Module has no method '{}'", key)),
-                                    })
+                                        backtrace: Vec::new(),
+                                    }))
                                 }
                             } else {
-                                Err(PawError::Runtime {
+                                Err(Box::new(PawError::Runtime {
                                     file: self.file.clone(),
-                                    code: "E6005".into(),
+                                    code: "E6005",
                                     message: format!("Module has no member '{}'", method),
                                     line: expr.line,
                                     column: expr.col,
                                     snippet: None,
                                     hint: None,
-                                })
+                                    backtrace: Vec::new(),
+                                }))
+                            }
+                        }
+
+                        // ————— Record: call a function-typed field —————
+                        ValueInner::Record(_, fields) if fields.contains_key(method.as_str()) => {
+                            let member_val = fields[method.as_str()].clone();
+                            self.call_function(stack, &member_val, arg_vals, method.as_str(), expr.line, expr.col)
+                                .await
+                        }
+
+                        // ————— Record: call a method declared in the record —————
+                        ValueInner::Record(record, _) => {
+                            let receiver = Value(inner_arc.clone());
+                            match self.bound_method(&receiver, method.as_str()) {
+                                Some(bound) => {
+                                    let name = format!("{}.{}", record, method);
+                                    self.call_function(stack, &bound, arg_vals, &name, expr.line, expr.col)
+                                        .await
+                                }
+                                None => Err(Box::new(PawError::Runtime {
+                                    file: self.file.clone(),
+                                    code: "E6003",
+                                    message: format!("Record `{}` has no method '{}'", record, method),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                    backtrace: Vec::new(),
+                                })),
                             }
                         }
 
                         // ————— Fallback for everything else —————
-                        other => Err(PawError::Runtime {
+                        other => Err(Box::new(PawError::Runtime {
                             file: self.file.clone(),
-                            code: "E6003".into(),
+                            code: "E6003",
                             message: format!("Cannot call method '{}' on {:?}", method, other),
                             line: expr.line,
                             column: expr.col,
                             snippet: None,
                             hint: Some(format!("Type {:?} has no method '{}'", other, method)),
-                        }),
+                            backtrace: Vec::new(),
+                        })),
                     },
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+impl Engine {
+    /// 值是否匹配模式，匹配时把模式绑定的变量依次放入 `bound`；
+    /// Optional 值除 `_`、变量与类型模式外按内部的值匹配，空的 Optional 只匹配 `nopaw`
+    fn match_pattern(&self, pattern: &Pattern, value: &Value, bound: &mut Vec<(String, Value)>) -> bool {
+        let inner = match &*value.0 {
+            ValueInner::Optional(o) => (**o).clone().unwrap_or_else(Value::Null),
+            _ => value.clone(),
+        };
+        match &pattern.kind {
+            PatternKind::Wildcard => true,
+            PatternKind::Bind(name) => {
+                bound.push((name.clone(), value.clone()));
+                true
+            }
+            PatternKind::Literal(lit) => literal_matches(lit, &inner),
+            PatternKind::Typed { name, ty } => {
+                let ty = self.runtime_type(ty);
+                if !value.is_instance(&ty) {
+                    return false;
+                }
+                if name != "_" {
+                    let v = if matches!(ty, PawType::Optional(_)) { value } else { &inner };
+                    bound.push((name.clone(), v.clone()));
+                }
+                true
+            }
+            PatternKind::Record { name, fields } => match &*inner.0 {
+                ValueInner::Record(r, values) if **r == *name => fields
+                    .iter()
+                    .all(|(field, p)| values.get(field).is_some_and(|v| self.match_pattern(p, v, bound))),
+                _ => false,
+            },
+            PatternKind::Tuple(items) => match &*inner.0 {
+                ValueInner::Tuple(values) if values.len() == items.len() => {
+                    items.iter().zip(values.iter()).all(|(p, v)| self.match_pattern(p, v, bound))
+                }
+                _ => false,
+            },
+            PatternKind::Variant {
+                enum_name,
+                variant,
+                args,
+            } => match &*inner.0 {
+                ValueInner::Enum { name, variant: v, payload } if **name == *enum_name && **v == *variant => {
+                    args.as_ref().is_none_or(|args| {
+                        args.len() == payload.len()
+                            && args.iter().zip(payload.iter()).all(|(p, v)| self.match_pattern(p, v, bound))
+                    })
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// 字面量模式是否等于该值：Int 与 Long 按数值比较，Float 与 Double 混合时按 Float 精度比较
+fn literal_matches(lit: &Expr, value: &Value) -> bool {
+    match (&lit.kind, &*value.0) {
+        (ExprKind::LiteralFloat(f), ValueInner::Float(v)) => (f - v).abs() < f32::EPSILON,
+        (ExprKind::LiteralFloat(f), ValueInner::Double(v)) => (f - *v as f32).abs() < f32::EPSILON,
+        (ExprKind::LiteralDouble(f), ValueInner::Double(v)) => (f - v).abs() < f64::EPSILON,
+        (ExprKind::LiteralDouble(f), ValueInner::Float(v)) => (*f as f32 - v).abs() < f32::EPSILON,
+        _ => LiteralKey::of(lit).is_some_and(|key| value.match_key() == Some(key)),
+    }
+}
+
+/// snatch 子句能否捕获该错误；能捕获时返回子句及绑定到错误变量上的值：
+/// 带类型的子句只捕获同名 record 错误并绑定该 record，不带类型的子句捕获所有运行时错误并绑定错误信息
+fn snatch_match<'c>(clause: &'c SnatchClause, err: &PawError) -> Option<(&'c SnatchClause, Value)> {
+    match (&clause.ty, err) {
+        (Some(ty), PawError::Custom { name, payload, .. })
+            if matches!(&ty.kind, TypeNodeKind::Name(n) | TypeNodeKind::Generic { name: n, .. } if n == name) =>
+        {
+            Some((clause, payload.clone()))
+        }
+        (None, PawError::Runtime { message, .. } | PawError::Custom { message, .. }) => {
+            Some((clause, Value::String(message.clone())))
+        }
+        _ => None,
+    }
+}
+
+/// 把 `env` 自身绑定中指向 `old` 的变量改为 `new`，返回是否有改动
+fn rebind(env: &Env, old: &Value, new: &Value) -> bool {
+    let mut changed = false;
+    for (name, value) in env.bindings() {
+        if Arc::ptr_eq(&value.0, &old.0) {
+            env.define(name, new.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// `sort()` 不带比较函数时使用的自然顺序；类型不同或无法比较时返回 None
+fn natural_order(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (&*a.0, &*b.0) {
+        (ValueInner::Int(x), ValueInner::Int(y)) => Some(x.cmp(y)),
+        (ValueInner::Long(x), ValueInner::Long(y)) => Some(x.cmp(y)),
+        (ValueInner::Float(x), ValueInner::Float(y)) => Some(x.total_cmp(y)),
+        (ValueInner::Double(x), ValueInner::Double(y)) => Some(x.total_cmp(y)),
+        (ValueInner::String(x), ValueInner::String(y)) => Some(x.cmp(y)),
+        (ValueInner::Char(x), ValueInner::Char(y)) => Some(x.cmp(y)),
+        (ValueInner::Bool(x), ValueInner::Bool(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+/// `method` 是否是修改 `value` 这个 Map（insert / remove）或 Set（add / remove）的方法
+fn updates_collection(value: &Value, method: &Method, argc: usize) -> bool {
+    matches!(
+        (&*value.0, method, argc),
+        (ValueInner::Map(_), Method::Insert, 2)
+            | (ValueInner::Map(_), Method::Remove, 1)
+            | (ValueInner::Set(_), Method::Add | Method::Remove, 1)
+    )
+}
+
+/// insert / add 加入新的键或元素时集合修改后的大小；不增长时为 None
+fn grown_len(value: &Value, method: &Method, args: &[Value]) -> Option<usize> {
+    match (&*value.0, method, args) {
+        (ValueInner::Map(m), Method::Insert, [k, _]) if !m.contains_key(k) => Some(m.len() + 1),
+        (ValueInner::Set(items), Method::Add, [x]) if !items.contains(x) => Some(items.len() + 1),
+        _ => None,
+    }
+}
+
+/// 原地执行 Map 的 insert / remove 或 Set 的 add / remove；集合被共享时先复制
+fn update_collection(value: &mut Value, method: &Method, args: &[Value]) {
+    match (Arc::make_mut(&mut value.0), method, args) {
+        (ValueInner::Map(m), Method::Insert, [k, v]) => {
+            Arc::make_mut(m).insert(k.clone(), v.clone());
+        }
+        (ValueInner::Map(m), Method::Remove, [k]) => {
+            Arc::make_mut(m).shift_remove(k);
+        }
+        (ValueInner::Set(items), Method::Add, [x]) => {
+            Arc::make_mut(items).insert(x.clone());
+        }
+        (ValueInner::Set(items), Method::Remove, [x]) => {
+            Arc::make_mut(items).shift_remove(x);
+        }
+        _ => {}
+    }
+}
+
+/// 对数组执行 push（返回 nopaw）或 pop（返回移除的元素）；对空数组 pop 时返回 None
+fn push_or_pop(items: &mut Vec<Value>, method: &Method, args: Vec<Value>) -> Option<Value> {
+    match method {
+        Method::Push => {
+            items.extend(args);
+            Some(Value::Null())
+        }
+        _ => items.pop(),
+    }
+}
@@ -1,3 +1,12 @@
+pub(crate) mod builtins;
+pub(crate) mod control_flow;
 pub(crate) mod env;
+pub mod host;
+pub(crate) mod inspect;
+pub(crate) mod numbers;
+pub(crate) mod property;
+pub(crate) mod signals;
+pub(crate) mod stdlib;
 pub(crate) mod value;
-pub mod interpreter;
\ No newline at end of file
+pub(crate) mod vm;
+pub mod interpreter;
@@ -0,0 +1,156 @@
+// src/interpreter/numbers.rs
+//
+// 把文本解析成数字与其它基础类型：`ask` 读取输入、CLI 的 `--define` 与 String 的 to_int / to_double 共用。
+// 规则与系统区域设置无关：小数点只认 `.`，不接受千位分隔符，
+// 除非调用方显式给出要忽略的分隔符。`as` 的运行时转换也在这里。
+
+use crate::interpreter::value::{Value, ValueInner};
+use crate::semantic::types::PawType;
+
+/// 去掉首尾空白后按 `ty`（Int、Long、Float、Double）解析；
+/// `separator` 只在两个数字之间出现时被忽略，如 `"1,000"` 与 `","`
+pub(crate) fn parse_number(text: &str, ty: &str, separator: Option<&str>) -> Result<Value, String> {
+    let trimmed = text.trim();
+    let cleaned = match separator {
+        Some(sep) => strip_separator(trimmed, sep)?,
+        None => trimmed.to_string(),
+    };
+    let parsed = match ty {
+        "Int" => cleaned.parse().map(Value::Int).map_err(|e| e.to_string()),
+        "Long" => cleaned.parse().map(Value::Long).map_err(|e| e.to_string()),
+        "Float" => parse_float(&cleaned).map(|f| Value::Float(f as f32)),
+        "Double" => parse_float(&cleaned).map(Value::Double),
+        _ => Err(format!("{} is not a number type", ty)),
+    };
+    parsed.map_err(|reason| format!("Cannot parse {:?} as {}: {}", text, ty, reason))
+}
+
+/// 按基础类型名 `ty` 转换文本：数字见 parse_number，Bool 只接受 true / false，
+/// Char 必须恰好一个字符，其它类型名原样得到 String
+pub(crate) fn parse_basic(text: &str, ty: &str) -> Result<Value, String> {
+    match ty {
+        "Int" | "Long" | "Float" | "Double" => parse_number(text, ty, None),
+        "Bool" => match text.trim() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err(format!("Cannot parse {:?} as Bool: expected true or false", text)),
+        },
+        "Char" => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Value::Char(c)),
+                _ => Err(format!("Cannot parse {:?} as Char: expected exactly one character", text)),
+            }
+        }
+        _ => Ok(Value::String(text.to_string())),
+    }
+}
+
+/// 浮点数只接受十进制写法（可带指数），拒绝 `inf`、`NaN` 之类的特殊值
+fn parse_float(text: &str) -> Result<f64, String> {
+    if text.chars().any(|c| c.is_ascii_alphabetic() && c != 'e' && c != 'E') {
+        return Err("invalid float literal".into());
+    }
+    text.parse().map_err(|e: std::num::ParseFloatError| e.to_string())
+}
+
+/// 删除夹在两个数字之间的分隔符；出现在其它位置的分隔符视为输入有误
+fn strip_separator(text: &str, sep: &str) -> Result<String, String> {
+    if sep.is_empty() || sep.contains(|c: char| c.is_ascii_digit() || c == '.' || c == '-' || c == '+') {
+        return Err(format!("{:?} cannot be used as a digit separator", sep));
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(sep) {
+        let before = rest[..pos].chars().next_back();
+        let after = rest[pos + sep.len()..].chars().next();
+        let between_digits = before.is_some_and(|c| c.is_ascii_digit())
+            && after.is_some_and(|c| c.is_ascii_digit());
+        if !between_digits {
+            return Err(format!("misplaced separator {:?}", sep));
+        }
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + sep.len()..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `value as ty`：数字之间按目标类型转换（整数越界、浮点数非有限或超出整数范围时失败，
+/// 浮点数转整数向零截断），基础类型与 String 互转（解析规则同 parse_basic），
+/// Char 与 Int / Long 按 Unicode 码点互转；其它目标类型只检查值是否属于该类型
+pub(crate) fn cast(value: &Value, ty: &PawType) -> Result<Value, String> {
+    use ValueInner as V;
+    let converted = match (&*value.0, ty) {
+        (_, PawType::Any) => Some(value.clone()),
+        (
+            V::String(s),
+            PawType::Int | PawType::Long | PawType::Float | PawType::Double | PawType::Bool | PawType::Char,
+        ) => return parse_basic(s, &ty.to_string()),
+        (
+            V::Int(_) | V::Long(_) | V::Float(_) | V::Double(_) | V::Bool(_) | V::Char(_) | V::String(_),
+            PawType::String,
+        ) => Some(Value::String(value.to_string())),
+        (V::Char(c), PawType::Int) => Some(Value::Int(*c as i32)),
+        (V::Char(c), PawType::Long) => Some(Value::Long(*c as i64)),
+        (V::Int(n), PawType::Char) => u32::try_from(*n).ok().and_then(char::from_u32).map(Value::Char),
+        (V::Long(n), PawType::Char) => u32::try_from(*n).ok().and_then(char::from_u32).map(Value::Char),
+        (V::Int(n), _) => integer(*n as i64, ty),
+        (V::Long(n), _) => integer(*n, ty),
+        (V::Float(f), _) => float(*f as f64, ty),
+        (V::Double(d), _) => float(*d, ty),
+        _ => None,
+    };
+    match converted {
+        Some(v) => Ok(v),
+        None if value.is_instance(ty) => Ok(value.clone()),
+        None => Err(format!("Cannot cast {} `{}` to {}", value.type_name(), value, ty)),
+    }
+}
+
+/// `value as ty` 失败时的提示，按值的类型说明怎样的值才能转换
+pub(crate) fn cast_hint(value: &Value, ty: &PawType) -> String {
+    use ValueInner as V;
+    let number = |v: &V| matches!(v, V::Int(_) | V::Long(_) | V::Float(_) | V::Double(_));
+    match (&*value.0, ty) {
+        (V::String(_), PawType::Int | PawType::Long | PawType::Float | PawType::Double) => format!(
+            "Text converts to {} only when it is a valid number; use `to_int` / `to_double` with a separator \
+             such as \",\" for digit groups, and handle bad input with `sniff`",
+            ty
+        ),
+        (V::String(_), PawType::Bool) => "Text converts to Bool only when it is `true` or `false`".into(),
+        (V::String(_), PawType::Char) => "Text converts to Char only when it has exactly one character".into(),
+        (v, PawType::Char) if number(v) => "Only integers that are Unicode code points convert to Char".into(),
+        (v, _) if number(v) && ty.is_numeric() => {
+            format!("The value does not fit in {}; check its range before casting", ty)
+        }
+        (V::Null, _) => "Check the value against `nopaw` before casting".into(),
+        (V::Optional(o), _) if o.is_none() => "Check the value against `nopaw` before casting".into(),
+        _ => format!("Check the value with `is {}` before casting", ty),
+    }
+}
+
+/// 整数转换为数字类型，超出 Int 范围时失败
+fn integer(n: i64, ty: &PawType) -> Option<Value> {
+    match ty {
+        PawType::Int => i32::try_from(n).ok().map(Value::Int),
+        PawType::Long => Some(Value::Long(n)),
+        PawType::Float => Some(Value::Float(n as f32)),
+        PawType::Double => Some(Value::Double(n as f64)),
+        _ => None,
+    }
+}
+
+/// 浮点数转换为数字类型：转整数时向零截断，非有限值或超出范围时失败；
+/// Double 转 Float 时有限值变为无穷大也算失败
+fn float(x: f64, ty: &PawType) -> Option<Value> {
+    // 截断后落在 [min, -min) 内才能表示；NaN 的比较总为 false
+    let truncate = |min: f64| Some(x.trunc()).filter(|t| *t >= min && *t < -min);
+    match ty {
+        PawType::Int => truncate(i32::MIN as f64).map(|t| Value::Int(t as i32)),
+        PawType::Long => truncate(i64::MIN as f64).map(|t| Value::Long(t as i64)),
+        PawType::Float => Some(x as f32).filter(|f| f.is_finite() || !x.is_finite()).map(Value::Float),
+        PawType::Double => Some(Value::Double(x)),
+        _ => None,
+    }
+}
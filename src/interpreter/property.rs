@@ -0,0 +1,177 @@
+// src/interpreter/property.rs
+//
+// 属性测试的生成器：`gen_int(0, 100)` 等内置函数返回的 `Gen<T>` 值。
+// 生成器随机产生输入，`forall` 找到让属性失败的输入后，用 `shrink` 逐步换成更简单的输入
+
+use crate::interpreter::value::{Value, ValueInner};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// 一个值生成器
+#[derive(Debug, Clone)]
+pub enum Generator {
+    /// `[lo, hi]` 中的 Int，两端都包含
+    Int(i32, i32),
+    /// `[lo, hi]` 中的 Double
+    Double(f64, f64),
+    Bool,
+    /// 由可打印 ASCII 字符组成、最多 n 个字符的 String
+    String(usize),
+    /// 元素来自内层生成器、最多 n 个元素的 Array
+    Array(Box<Generator>, usize),
+    /// 从给定的值中选一个，越靠前越简单
+    OneOf(Vec<Value>),
+    /// 两个生成器的值组成的元组
+    Pair(Box<Generator>, Box<Generator>),
+}
+
+impl Generator {
+    /// 随机生成一个值；数值有一定概率取边界值与 0，容器有一定概率为空
+    pub(crate) fn generate(&self, rng: &mut impl Rng) -> Value {
+        match self {
+            Generator::Int(lo, hi) => {
+                let edges = [*lo, *hi, 0.clamp(*lo, *hi)];
+                Value::Int(match rng.gen_ratio(1, 8) {
+                    true => *edges.choose(rng).unwrap(),
+                    false => rng.gen_range(*lo..=*hi),
+                })
+            }
+            Generator::Double(lo, hi) => {
+                let edges = [*lo, *hi, 0.0f64.clamp(*lo, *hi)];
+                Value::Double(match rng.gen_ratio(1, 8) || lo == hi {
+                    true => *edges.choose(rng).unwrap(),
+                    false => rng.gen_range(*lo..=*hi),
+                })
+            }
+            Generator::Bool => Value::Bool(rng.gen()),
+            Generator::String(max) => {
+                let len = length(rng, *max);
+                Value::String((0..len).map(|_| rng.gen_range(' '..='~')).collect::<String>())
+            }
+            Generator::Array(elem, max) => {
+                let len = length(rng, *max);
+                Value::Array((0..len).map(|_| elem.generate(rng)).collect())
+            }
+            Generator::OneOf(values) => values.choose(rng).cloned().unwrap_or_else(Value::Null),
+            Generator::Pair(a, b) => Value::Tuple(vec![a.generate(rng), b.generate(rng)]),
+        }
+    }
+
+    /// 比 `value` 更简单的候选值，最简单的在前；已经最简单时为空。
+    /// 数值向 0（或最接近 0 的边界）靠近，容器先变短再简化元素
+    pub(crate) fn shrink(&self, value: &Value) -> Vec<Value> {
+        match (self, &*value.0) {
+            (Generator::Int(lo, hi), ValueInner::Int(v)) => {
+                let target = 0.clamp(*lo, *hi) as i64;
+                let mut out = Vec::new();
+                // target、中点……直到只差 1，二分式地逼近
+                let mut d = *v as i64 - target;
+                while d != 0 {
+                    out.push(Value::Int((*v as i64 - d) as i32));
+                    d /= 2;
+                }
+                out
+            }
+            (Generator::Double(lo, hi), ValueInner::Double(v)) => {
+                let target = 0.0f64.clamp(*lo, *hi);
+                let mut out = Vec::new();
+                if *v != target {
+                    out.push(Value::Double(target));
+                }
+                if v.trunc() != *v && v.trunc() != target && (*lo..=*hi).contains(&v.trunc()) {
+                    out.push(Value::Double(v.trunc()));
+                }
+                let mid = target + (v - target) / 2.0;
+                if (v - target).abs() > 1e-6 && mid != target {
+                    out.push(Value::Double(mid));
+                }
+                out
+            }
+            (Generator::Bool, ValueInner::Bool(true)) => vec![Value::Bool(false)],
+            (Generator::String(_), ValueInner::String(s)) => {
+                let chars: Vec<char> = s.chars().collect();
+                let mut out: Vec<Value> = shorter(&chars)
+                    .into_iter()
+                    .map(|c| Value::String(c.into_iter().collect::<String>()))
+                    .collect();
+                for i in 0..chars.len() {
+                    if chars[i] != 'a' {
+                        let mut simpler = chars.clone();
+                        simpler[i] = 'a';
+                        out.push(Value::String(simpler.into_iter().collect::<String>()));
+                    }
+                }
+                out
+            }
+            (Generator::Array(elem, _), ValueInner::Array(items)) => {
+                let mut out: Vec<Value> = shorter(items).into_iter().map(Value::Array).collect();
+                for (i, item) in items.iter().enumerate() {
+                    for simpler in elem.shrink(item) {
+                        let mut items = (**items).clone();
+                        items[i] = simpler;
+                        out.push(Value::Array(items));
+                    }
+                }
+                out
+            }
+            (Generator::OneOf(values), _) => {
+                let index = values.iter().position(|v| v == value).unwrap_or(0);
+                values[..index].to_vec()
+            }
+            (Generator::Pair(a, b), ValueInner::Tuple(items)) if items.len() == 2 => {
+                let (x, y) = (&items[0], &items[1]);
+                let firsts = a.shrink(x).into_iter().map(|s| Value::Tuple(vec![s, y.clone()]));
+                let seconds = b.shrink(y).into_iter().map(|s| Value::Tuple(vec![x.clone(), s]));
+                firsts.chain(seconds).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 按创建它的内置函数调用输出，供 `inspect` 使用
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Generator::Int(lo, hi) => write!(f, "gen_int({}, {})", lo, hi),
+            Generator::Double(lo, hi) => write!(f, "gen_double({:?}, {:?})", lo, hi),
+            Generator::Bool => write!(f, "gen_bool()"),
+            Generator::String(max) => write!(f, "gen_string({})", max),
+            Generator::Array(elem, max) => write!(f, "gen_array({}, {})", elem, max),
+            Generator::OneOf(values) => {
+                let items: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "gen_one_of([{}])", items.join(", "))
+            }
+            Generator::Pair(a, b) => write!(f, "gen_pair({}, {})", a, b),
+        }
+    }
+}
+
+/// 容器的随机长度：有一定概率为 0，其余在 `[0, max]` 中均匀分布
+fn length(rng: &mut impl Rng, max: usize) -> usize {
+    if rng.gen_ratio(1, 8) {
+        0
+    } else {
+        rng.gen_range(0..=max)
+    }
+}
+
+/// 比 `items` 更短的候选：空、前一半，以及去掉每一个元素
+fn shorter<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![Vec::new()];
+    if items.len() > 2 {
+        out.push(items[..items.len() / 2].to_vec());
+    }
+    for i in 0..items.len() {
+        if items.len() > 1 {
+            let mut fewer = items.to_vec();
+            fewer.remove(i);
+            out.push(fewer);
+        }
+    }
+    out
+}
@@ -0,0 +1,86 @@
+// src/interpreter/signals.rs
+//
+// 脚本通过 `signal` 模块登记的信号处理函数。信号到达时只置位标志，
+// 处理函数由解释器在下一次中断检查（每条语句执行之前）时，在当时正在运行的任务中调用。
+
+use crate::interpreter::value::Value;
+use parking_lot::Mutex;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::low_level;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// 可以登记处理函数的信号
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Signal {
+    /// SIGINT，通常来自 Ctrl+C
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+}
+
+impl Signal {
+    const ALL: [Signal; 2] = [Signal::Interrupt, Signal::Terminate];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn number(self) -> i32 {
+        match self {
+            Signal::Interrupt => SIGINT,
+            Signal::Terminate => SIGTERM,
+        }
+    }
+}
+
+/// 已到达、尚未交给处理函数的信号
+static PENDING: [AtomicBool; 2] = [AtomicBool::new(false), AtomicBool::new(false)];
+/// 各信号当前的处理函数，后登记的替换先登记的
+static HANDLERS: Mutex<[Option<Value>; 2]> = Mutex::new([None, None]);
+/// 是否登记过任何处理函数；没有时中断检查只需读这一个标志
+static ARMED: AtomicBool = AtomicBool::new(false);
+static INSTALLED: [Once; 2] = [Once::new(), Once::new()];
+
+/// 为 `signal` 登记处理函数；首次登记时接管该信号，此后它不再直接结束进程
+pub(crate) fn set_handler(signal: Signal, handler: Value) -> Result<(), String> {
+    let i = signal.index();
+    let mut result = Ok(());
+    INSTALLED[i].call_once(|| {
+        // 信号处理上下文里只做原子操作：置位标志；若上一次的信号还没被处理，
+        // 说明脚本没有机会响应（例如卡在阻塞调用中），第二次信号直接结束进程
+        let action = move || {
+            if PENDING[i].swap(true, Ordering::SeqCst) {
+                low_level::exit(128 + signal.number());
+            }
+        };
+        // SAFETY: action 只访问静态原子变量并调用 async-signal-safe 的 exit
+        result = unsafe { low_level::register(signal.number(), action) }
+            .map(|_| ())
+            .map_err(|e| format!("Cannot handle {:?}: {}", signal, e));
+    });
+    result?;
+    HANDLERS.lock()[i] = Some(handler);
+    ARMED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// 是否登记过处理函数；此时循环不交给字节码 VM，以便在语句之间调用处理函数
+pub(crate) fn has_handlers() -> bool {
+    ARMED.load(Ordering::Relaxed)
+}
+
+/// 取出一个已到达的信号对应的处理函数，同时清除其待处理标志
+pub(crate) fn take_pending() -> Option<Value> {
+    if !has_handlers() {
+        return None;
+    }
+    Signal::ALL.into_iter().find_map(|signal| {
+        let i = signal.index();
+        if PENDING[i].swap(false, Ordering::SeqCst) {
+            HANDLERS.lock()[i].clone()
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,548 @@
+// src/interpreter/stdlib.rs
+//
+// 标准库模块：`import <name>` 优先在这里查找，找到时得到由原生函数组成的模块，
+// 不再去磁盘上读取同名 .paw 文件。每个成员附带静态类型，类型检查器据此检查调用。
+
+use crate::interpreter::host::Host;
+use crate::interpreter::signals::{self, Signal};
+use crate::interpreter::value::{Value, ValueInner};
+use crate::semantic::types::PawType;
+use ahash::AHashMap;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::io::{IsTerminal, Read, Write};
+use std::sync::Arc;
+
+/// 标准库模块成员的静态类型
+#[derive(Debug, Clone)]
+pub(crate) enum MemberType {
+    /// 按脚本写法给出的类型，如 `fun(Double): Double` 或常量的 `Double`
+    Fixed(&'static str),
+    /// 接受 n 个任意数值参数，结果为其中最宽的数值类型（Int < Long < Float < Double）
+    Numeric(usize),
+    /// 接受一个 Array<T>，结果为元素类型 T
+    Element,
+    /// 接受一个 Array<T>，结果为同类型的新数组
+    SameArray,
+}
+
+/// 标准库模块的一个成员：名字、静态类型与值
+type Member = (&'static str, MemberType, Value);
+
+/// 按导入路径查找标准库模块的成员；`time` 与 `random` 从 `host` 读取时钟和随机数
+fn members(path: &[String], host: &Arc<Host>) -> Option<Vec<Member>> {
+    let [name] = path else {
+        return None;
+    };
+    match name.as_str() {
+        "time" => Some(time(host)),
+        "math" => Some(math()),
+        "random" => Some(random(host)),
+        "fs" => Some(fs()),
+        "term" => Some(term()),
+        "signal" => Some(signal()),
+        "string" => Some(string()),
+        _ => None,
+    }
+}
+
+/// 按导入路径查找标准库模块，返回运行时的模块值；没有副作用的模块中的原生函数标记为 pure
+pub(crate) fn std_module(path: &[String], host: &Arc<Host>) -> Option<Value> {
+    let pure = is_pure_module(path);
+    let members: AHashMap<String, Value> = members(path, host)?
+        .into_iter()
+        .map(|(name, _, value)| (name.to_string(), if pure { value.into_pure() } else { value }))
+        .collect();
+    Some(Value::Module(members))
+}
+
+/// `path` 是否为标准库模块
+pub(crate) fn is_std_module(path: &[String]) -> bool {
+    members(path, &Arc::default()).is_some()
+}
+
+/// 模块是否访问宿主的文件系统、终端或进程信号；沙箱模式下禁止导入这类模块
+pub(crate) fn needs_host_access(path: &[String]) -> bool {
+    matches!(path, [name] if name == "fs" || name == "term" || name == "signal")
+}
+
+/// 模块的结果是否取决于运行环境（终端是否存在、按键、进程信号）；确定性模式下禁止导入这类模块
+pub(crate) fn is_nondeterministic(path: &[String]) -> bool {
+    matches!(path, [name] if name == "term" || name == "signal")
+}
+
+/// 模块的函数是否没有副作用（只依赖参数），`@pure` 函数只能使用这类模块
+pub(crate) fn is_pure_module(path: &[String]) -> bool {
+    matches!(path, [name] if name == "math" || name == "string")
+}
+
+/// 查找标准库模块成员的静态类型；模块没有该成员时返回 None
+pub(crate) fn std_member_type(path: &[String], member: &str) -> Option<MemberType> {
+    members(path, &Arc::default())?
+        .into_iter()
+        .find(|(name, _, _)| *name == member)
+        .map(|(_, ty, _)| ty)
+}
+
+impl MemberType {
+    /// 固定类型解析为 PawType；数值多态的函数返回 None
+    pub(crate) fn fixed(&self) -> Option<PawType> {
+        match self {
+            MemberType::Fixed(ty) => Some(PawType::parse(ty)),
+            MemberType::Numeric(_) | MemberType::Element | MemberType::SameArray => None,
+        }
+    }
+}
+
+/// `time`：时间相关工具
+fn time(host: &Arc<Host>) -> Vec<Member> {
+    let host = host.clone();
+    vec![(
+        "now_millis",
+        MemberType::Fixed("fun(): Long"),
+        // 自 Unix 纪元起的毫秒数
+        Value::Native("now_millis", move |_| Ok(Value::Long(host.now_millis()?))),
+    )]
+}
+
+/// `string`：字符串工具
+fn string() -> Vec<Member> {
+    vec![(
+        "builder",
+        MemberType::Fixed("fun(): StringBuilder"),
+        // 空的 StringBuilder；在循环中拼接大量片段时代替 `s = s + piece`
+        Value::Native("builder", |_| {
+            Ok(Value::from_inner(ValueInner::StringBuilder(Arc::new(
+                parking_lot::Mutex::new(String::new()),
+            ))))
+        }),
+    )]
+}
+
+/// `math`：常用数学函数与常量
+fn math() -> Vec<Member> {
+    vec![
+        ("pi", MemberType::Fixed("Double"), Value::Double(std::f64::consts::PI)),
+        ("e", MemberType::Fixed("Double"), Value::Double(std::f64::consts::E)),
+        (
+            "abs",
+            MemberType::Numeric(1),
+            Value::Native("abs", |args| {
+                Ok(match &*args[0].0 {
+                    ValueInner::Int(n) => Value::Int(n.checked_abs().ok_or("abs overflows Int")?),
+                    ValueInner::Long(n) => Value::Long(n.checked_abs().ok_or("abs overflows Long")?),
+                    ValueInner::Float(f) => Value::Float(f.abs()),
+                    _ => Value::Double(number("abs", &args[0])?.abs()),
+                })
+            }),
+        ),
+        (
+            "min",
+            MemberType::Numeric(2),
+            Value::Native("min", |args| pick("min", &args, |o| o != Ordering::Greater)),
+        ),
+        (
+            "max",
+            MemberType::Numeric(2),
+            Value::Native("max", |args| pick("max", &args, |o| o != Ordering::Less)),
+        ),
+        (
+            "floor",
+            MemberType::Numeric(1),
+            Value::Native("floor", |args| round_with("floor", &args[0], f64::floor)),
+        ),
+        (
+            "ceil",
+            MemberType::Numeric(1),
+            Value::Native("ceil", |args| round_with("ceil", &args[0], f64::ceil)),
+        ),
+        (
+            "round",
+            MemberType::Numeric(1),
+            Value::Native("round", |args| round_with("round", &args[0], f64::round)),
+        ),
+        (
+            "sqrt",
+            MemberType::Fixed("fun(Double): Double"),
+            Value::Native("sqrt", |args| Ok(Value::Double(number("sqrt", &args[0])?.sqrt()))),
+        ),
+        (
+            "pow",
+            MemberType::Fixed("fun(Double, Double): Double"),
+            Value::Native("pow", |args| {
+                let base = number("pow", &args[0])?;
+                let exp = number("pow", &args[1])?;
+                Ok(Value::Double(base.powf(exp)))
+            }),
+        ),
+    ]
+}
+
+/// `random`：随机数，使用 `host` 的随机数生成器
+fn random(host: &Arc<Host>) -> Vec<Member> {
+    // 每个成员各持有一份 host
+    let native = |name: &'static str, f: fn(&Host, Vec<Value>) -> Result<Value, String>| {
+        let host = host.clone();
+        Value::Native(name, move |args| f(&host, args))
+    };
+    vec![
+        (
+            "int",
+            MemberType::Fixed("fun(Int, Int): Int"),
+            // [lo, hi) 中的随机整数，与 `lo..hi` 的范围一致
+            native("int", |host, args| match (&*args[0].0, &*args[1].0) {
+                (ValueInner::Int(lo), ValueInner::Int(hi)) if lo < hi => {
+                    Ok(Value::Int(host.rng().gen_range(*lo..*hi)))
+                }
+                (ValueInner::Int(lo), ValueInner::Int(hi)) => {
+                    Err(format!("random.int expects lo < hi, found {}..{}", lo, hi))
+                }
+                _ => Err(format!(
+                    "random.int expects two Int arguments, found {} and {}",
+                    args[0].type_name(),
+                    args[1].type_name()
+                )),
+            }),
+        ),
+        (
+            "float",
+            MemberType::Fixed("fun(): Double"),
+            // [0, 1) 中的随机浮点数
+            native("float", |host, _| Ok(Value::Double(host.rng().gen::<f64>()))),
+        ),
+        (
+            "choice",
+            MemberType::Element,
+            native("choice", |host, args| {
+                array("choice", &args[0])?
+                    .choose(&mut *host.rng())
+                    .cloned()
+                    .ok_or_else(|| "random.choice from an empty array".to_string())
+            }),
+        ),
+        (
+            "shuffle",
+            MemberType::SameArray,
+            // 返回打乱后的新数组，原数组不变
+            native("shuffle", |host, args| {
+                let mut items = array("shuffle", &args[0])?.to_vec();
+                items.shuffle(&mut *host.rng());
+                Ok(Value::Array(items))
+            }),
+        ),
+    ]
+}
+
+/// `fs`：读写文本文件与列出目录，相对路径以当前工作目录为准；
+/// IO 失败时报运行时错误，可以被 sniff / snatch 捕获
+fn fs() -> Vec<Member> {
+    vec![
+        (
+            "read_text",
+            MemberType::Fixed("fun(String): String"),
+            Value::Native("read_text", |args| {
+                let path = path_arg("read_text", &args[0])?;
+                std::fs::read_to_string(path)
+                    .map(Value::String)
+                    .map_err(|e| format!("Cannot read '{}': {}", path, e))
+            }),
+        ),
+        (
+            "write_text",
+            MemberType::Fixed("fun(String, String): Void"),
+            // 覆盖写入，文件不存在时创建
+            Value::Native("write_text", |args| {
+                let path = path_arg("write_text", &args[0])?;
+                let content = args[1]
+                    .as_str()
+                    .ok_or_else(|| format!("fs.write_text expects String content, found {}", args[1].type_name()))?;
+                std::fs::write(path, content)
+                    .map(|_| Value::Null())
+                    .map_err(|e| format!("Cannot write '{}': {}", path, e))
+            }),
+        ),
+        (
+            "exists",
+            MemberType::Fixed("fun(String): Bool"),
+            Value::Native("exists", |args| {
+                let path = path_arg("exists", &args[0])?;
+                Ok(Value::Bool(std::path::Path::new(path).exists()))
+            }),
+        ),
+        (
+            "list_dir",
+            MemberType::Fixed("fun(String): Array<String>"),
+            // 目录中的条目名，按名字排序
+            Value::Native("list_dir", |args| {
+                let path = path_arg("list_dir", &args[0])?;
+                let entries = std::fs::read_dir(path).map_err(|e| format!("Cannot list '{}': {}", path, e))?;
+                let mut names = Vec::new();
+                for entry in entries {
+                    let entry = entry.map_err(|e| format!("Cannot list '{}': {}", path, e))?;
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+                names.sort();
+                Ok(Value::Array(names.into_iter().map(Value::String).collect()))
+            }),
+        ),
+    ]
+}
+
+/// `term`：终端控制。颜色与光标操作输出 ANSI 转义序列，
+/// 标准输出是否为终端由脚本通过 `is_tty` 自行判断
+fn term() -> Vec<Member> {
+    vec![
+        (
+            "is_tty",
+            MemberType::Fixed("fun(): Bool"),
+            // 标准输出是终端时为 true，重定向到文件或管道时为 false
+            Value::Native("is_tty", |_| Ok(Value::Bool(std::io::stdout().is_terminal()))),
+        ),
+        (
+            "color",
+            MemberType::Fixed("fun(String, String): String"),
+            Value::Native("color", |args| {
+                let text = string_arg("term.color", &args[0])?;
+                let name = string_arg("term.color", &args[1])?;
+                let code = match name {
+                    "black" => 30,
+                    "red" => 31,
+                    "green" => 32,
+                    "yellow" => 33,
+                    "blue" => 34,
+                    "magenta" => 35,
+                    "cyan" => 36,
+                    "white" => 37,
+                    "gray" => 90,
+                    _ => {
+                        return Err(format!(
+                            "Unknown color '{}'; expected black, red, green, yellow, blue, magenta, cyan, white or gray",
+                            name
+                        ))
+                    }
+                };
+                Ok(Value::String(format!("\x1b[{}m{}\x1b[0m", code, text)))
+            }),
+        ),
+        (
+            "bold",
+            MemberType::Fixed("fun(String): String"),
+            Value::Native("bold", |args| {
+                Ok(Value::String(format!("\x1b[1m{}\x1b[0m", string_arg("term.bold", &args[0])?)))
+            }),
+        ),
+        (
+            "clear",
+            MemberType::Fixed("fun(): Void"),
+            // 清屏并把光标移到左上角
+            Value::Native("clear", |_| emit("\x1b[2J\x1b[H")),
+        ),
+        (
+            "move_to",
+            MemberType::Fixed("fun(Int, Int): Void"),
+            // 行、列都从 0 开始
+            Value::Native("move_to", |args| match (&*args[0].0, &*args[1].0) {
+                (ValueInner::Int(row), ValueInner::Int(col)) if *row >= 0 && *col >= 0 => {
+                    emit(&format!("\x1b[{};{}H", row + 1, col + 1))
+                }
+                _ => Err(format!(
+                    "term.move_to expects two non-negative Int arguments, found {} and {}",
+                    args[0], args[1]
+                )),
+            }),
+        ),
+        (
+            "hide_cursor",
+            MemberType::Fixed("fun(): Void"),
+            Value::Native("hide_cursor", |_| emit("\x1b[?25l")),
+        ),
+        (
+            "show_cursor",
+            MemberType::Fixed("fun(): Void"),
+            Value::Native("show_cursor", |_| emit("\x1b[?25h")),
+        ),
+        (
+            "read_key",
+            MemberType::Fixed("fun(): String"),
+            Value::Native("read_key", |_| read_key().map(Value::String)),
+        ),
+    ]
+}
+
+/// 写出控制序列并立即刷新，使其与 `say` 的输出按顺序生效
+fn emit(seq: &str) -> Result<Value, String> {
+    let mut out = std::io::stdout();
+    out.write_all(seq.as_bytes())
+        .and_then(|_| out.flush())
+        .map(|_| Value::Null())
+        .map_err(|e| format!("Cannot write to the terminal: {}", e))
+}
+
+/// 读取一次按键，不需要回车也不回显。可打印字符原样返回，
+/// 其它按键返回 `enter`、`tab`、`backspace`、`escape`、`up`、`down`、`left`、`right` 等名字；
+/// 标准输入不是终端时退化为从输入流读取一个字符
+fn read_key() -> Result<String, String> {
+    use crossterm::event::{read, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+    if !std::io::stdin().is_terminal() {
+        return read_char();
+    }
+    enable_raw_mode().map_err(|e| format!("Cannot read a key: {}", e))?;
+    let key = loop {
+        match read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => break Ok(key),
+            Ok(_) => continue,
+            Err(e) => break Err(format!("Cannot read a key: {}", e)),
+        }
+    };
+    // 无论读取是否成功都要恢复终端模式
+    let _ = disable_raw_mode();
+    let key = key?;
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        return Err("Interrupted by Ctrl+C".into());
+    }
+    Ok(match key.code {
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".into(),
+        KeyCode::Tab => "tab".into(),
+        KeyCode::Backspace => "backspace".into(),
+        KeyCode::Esc => "escape".into(),
+        KeyCode::Up => "up".into(),
+        KeyCode::Down => "down".into(),
+        KeyCode::Left => "left".into(),
+        KeyCode::Right => "right".into(),
+        KeyCode::Delete => "delete".into(),
+        KeyCode::Home => "home".into(),
+        KeyCode::End => "end".into(),
+        other => other.to_string().to_lowercase(),
+    })
+}
+
+/// 从非终端的标准输入读取一个 UTF-8 字符，换行视为 `enter`
+fn read_char() -> Result<String, String> {
+    let mut stdin = std::io::stdin().lock();
+    let mut buf = Vec::with_capacity(4);
+    let mut byte = [0u8; 1];
+    loop {
+        match stdin.read(&mut byte) {
+            Ok(0) => return Err("Cannot read a key: end of input".into()),
+            Ok(_) => buf.push(byte[0]),
+            Err(e) => return Err(format!("Cannot read a key: {}", e)),
+        }
+        match std::str::from_utf8(&buf) {
+            Ok("\n") => return Ok("enter".into()),
+            // Windows 换行 `\r\n` 中的 `\r` 直接跳过
+            Ok("\r") => buf.clear(),
+            Ok(s) => return Ok(s.to_string()),
+            Err(e) if e.error_len().is_some() || buf.len() >= 4 => {
+                return Err("Cannot read a key: input is not valid UTF-8".into())
+            }
+            Err(_) => continue,
+        }
+    }
+}
+
+/// `signal`：登记进程信号的处理函数。处理函数在下一条语句执行前运行，
+/// 之后脚本照常继续，需要结束时由脚本自己决定（例如让循环条件变为 false）
+fn signal() -> Vec<Member> {
+    vec![
+        (
+            "on_interrupt",
+            MemberType::Fixed("fun(fun(): Void): Void"),
+            Value::Native("on_interrupt", |args| handle(Signal::Interrupt, &args[0])),
+        ),
+        (
+            "on_terminate",
+            MemberType::Fixed("fun(fun(): Void): Void"),
+            Value::Native("on_terminate", |args| handle(Signal::Terminate, &args[0])),
+        ),
+    ]
+}
+
+fn handle(signal: Signal, handler: &Value) -> Result<Value, String> {
+    if handler.type_name() != "Function" {
+        return Err(format!("Signal handler must be a function, found {}", handler.type_name()));
+    }
+    signals::set_handler(signal, handler.clone())?;
+    Ok(Value::Null())
+}
+
+/// 取出字符串参数
+fn string_arg<'a>(name: &str, v: &'a Value) -> Result<&'a str, String> {
+    v.as_str()
+        .ok_or_else(|| format!("{} expects a String, found {}", name, v.type_name()))
+}
+
+/// 取出路径参数
+fn path_arg<'a>(name: &str, v: &'a Value) -> Result<&'a str, String> {
+    v.as_str()
+        .ok_or_else(|| format!("fs.{} expects a String path, found {}", name, v.type_name()))
+}
+
+/// 取出数组参数的元素
+fn array<'a>(name: &str, v: &'a Value) -> Result<&'a [Value], String> {
+    match &*v.0 {
+        ValueInner::Array(items) => Ok(items),
+        _ => Err(format!("random.{} expects an Array, found {}", name, v.type_name())),
+    }
+}
+
+/// 把任意数值参数转换为 f64
+fn number(name: &str, v: &Value) -> Result<f64, String> {
+    match &*v.0 {
+        ValueInner::Int(n) => Ok(*n as f64),
+        ValueInner::Long(n) => Ok(*n as f64),
+        ValueInner::Float(f) => Ok(*f as f64),
+        ValueInner::Double(f) => Ok(*f),
+        _ => Err(format!("{} expects a number, found {}", name, v.type_name())),
+    }
+}
+
+/// 数值类型的宽度，与检查器中数值运算结果类型的规则一致
+fn rank(v: &Value) -> u8 {
+    match &*v.0 {
+        ValueInner::Int(_) => 0,
+        ValueInner::Long(_) => 1,
+        ValueInner::Float(_) => 2,
+        _ => 3,
+    }
+}
+
+/// 把数值转换为宽度为 `rank` 的类型
+fn widen(v: &Value, rank: u8) -> Value {
+    match (&*v.0, rank) {
+        (ValueInner::Int(n), 1) => Value::Long(*n as i64),
+        (ValueInner::Int(n), 2) => Value::Float(*n as f32),
+        (ValueInner::Long(n), 2) => Value::Float(*n as f32),
+        (ValueInner::Int(n), 3) => Value::Double(*n as f64),
+        (ValueInner::Long(n), 3) => Value::Double(*n as f64),
+        (ValueInner::Float(f), 3) => Value::Double(*f as f64),
+        _ => v.clone(),
+    }
+}
+
+/// min / max：两个参数统一到较宽的类型后比较，`keep_first(a 与 b 的大小关系)` 为真时返回 a
+fn pick(name: &str, args: &[Value], keep_first: fn(Ordering) -> bool) -> Result<Value, String> {
+    let rank = rank(&args[0]).max(rank(&args[1]));
+    let (a, b) = (widen(&args[0], rank), widen(&args[1], rank));
+    let order = match (&*a.0, &*b.0) {
+        (ValueInner::Int(x), ValueInner::Int(y)) => x.cmp(y),
+        (ValueInner::Long(x), ValueInner::Long(y)) => x.cmp(y),
+        // NaN 与任何数比较都视为相等，返回第一个参数
+        _ => number(name, &a)?
+            .partial_cmp(&number(name, &b)?)
+            .unwrap_or(Ordering::Equal),
+    };
+    Ok(if keep_first(order) { a } else { b })
+}
+
+/// floor / ceil / round：整数原样返回，浮点数按 `f` 取整并保持原类型
+fn round_with(name: &str, v: &Value, f: fn(f64) -> f64) -> Result<Value, String> {
+    match &*v.0 {
+        ValueInner::Int(_) | ValueInner::Long(_) => Ok(v.clone()),
+        ValueInner::Float(x) => Ok(Value::Float(f(*x as f64) as f32)),
+        _ => Ok(Value::Double(f(number(name, v)?))),
+    }
+}
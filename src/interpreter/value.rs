@@ -1,16 +1,173 @@
 // src/interpreter/value.rs
 
 use crate::ast::param::Param;
+use crate::ast::pattern::LiteralKey;
 use crate::ast::statement::Statement;
+use crate::ast::type_node::TypeNode;
 use crate::error::error::PawError;
 use crate::interpreter::env::Env;
+use crate::interpreter::property::Generator;
+use crate::semantic::types::PawType;
 use ahash::AHashMap;
+use indexmap::{IndexMap, IndexSet};
 use futures::lock::Mutex;
 use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{f64, fmt};
 
+/// Map 的存储：按插入顺序遍历，`say`、`keys()` 与 `loop` 每次运行得到相同的顺序
+pub type PawMap = IndexMap<Value, Value, ahash::RandomState>;
+/// Set 的存储，同样按插入顺序遍历
+pub type PawSet = IndexSet<Value, ahash::RandomState>;
+
+/// 异步函数调用产生的 Future
+pub type PawFuture = Pin<Box<dyn Future<Output = Result<Value, Box<PawError>>> + Send>>;
+
+/// Future 的执行状态：首次 await 时驱动，结果缓存下来供之后的 await 复用
+pub enum FutureState {
+    /// 尚未开始执行
+    Pending(PawFuture),
+    /// 正在执行；执行中途被取消时会停留在这个状态
+    Running,
+    /// 已完成，缓存结果
+    Done(Result<Value, Box<PawError>>),
+}
+
+impl FutureState {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, FutureState::Pending(_))
+    }
+}
+
+impl fmt::Debug for FutureState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FutureState::Pending(_) => write!(f, "Pending"),
+            FutureState::Running => write!(f, "Running"),
+            FutureState::Done(r) => write!(f, "Done({:?})", r),
+        }
+    }
+}
+
+/// 宿主注册的原生函数；返回 Err 时在调用处抛出可被 snatch 捕获的运行时错误
+#[derive(Clone)]
+pub struct NativeFn(pub Arc<dyn Fn(Vec<Value>) -> Result<Value, String> + Send + Sync>);
+
+impl fmt::Debug for NativeFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
+
+/// `start..end` / `start..=end` 按 `step` 前进的整数范围；`long` 表示元素为 Long，否则为 Int
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RangeValue {
+    pub start: i64,
+    pub end: i64,
+    /// 不为 0；负数时从 start 向下数到 end
+    pub step: i64,
+    pub inclusive: bool,
+    pub long: bool,
+}
+
+impl RangeValue {
+    /// 范围内的元素个数，不实际展开
+    pub fn len(&self) -> u64 {
+        let (from, to) = if self.step > 0 {
+            (self.start as i128, self.end as i128)
+        } else {
+            (-(self.start as i128), -(self.end as i128))
+        };
+        let to = if self.inclusive { to } else { to - 1 };
+        if from > to {
+            return 0;
+        }
+        let count = (to - from) / (self.step as i128).abs() + 1;
+        u64::try_from(count).unwrap_or(u64::MAX)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        let (lo, hi) = if self.step > 0 { (self.start, self.end) } else { (self.end, self.start) };
+        let inside = if self.inclusive {
+            lo <= n && n <= hi
+        } else if self.step > 0 {
+            lo <= n && n < hi
+        } else {
+            lo < n && n <= hi
+        };
+        inside && (n as i128 - self.start as i128) % self.step as i128 == 0
+    }
+
+    /// 逐个产生元素的迭代器，范围再大也不会一次性分配
+    pub fn iter(&self) -> RangeIter {
+        RangeIter {
+            next: self.start,
+            remaining: self.len(),
+            step: self.step,
+            long: self.long,
+        }
+    }
+}
+
+impl fmt::Display for RangeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dots = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start, dots, self.end)?;
+        if self.step != 1 {
+            write!(f, " step {}", self.step)?;
+        }
+        Ok(())
+    }
+}
+
+/// RangeValue 的迭代器；按剩余个数结束，最后一步越过 i64 边界也不会溢出
+#[derive(Debug, Clone)]
+pub struct RangeIter {
+    next: i64,
+    remaining: u64,
+    step: i64,
+    long: bool,
+}
+
+impl Iterator for RangeIter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let n = self.next;
+        self.remaining -= 1;
+        self.next = n.wrapping_add(self.step);
+        // Int 范围的元素都在两个 Int 端点之间
+        Some(if self.long { Value::Long(n) } else { Value::Int(n as i32) })
+    }
+}
+
+/// `loop x in v` 依次得到的元素；范围逐个产生，其余容器先复制出元素，循环中修改原变量不影响遍历
+pub(crate) enum LoopItems {
+    Items(std::vec::IntoIter<Value>),
+    Range(RangeIter),
+}
+
+impl Iterator for LoopItems {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        match self {
+            LoopItems::Items(items) => items.next(),
+            LoopItems::Range(range) => range.next(),
+        }
+    }
+}
+
 #[derive(Debug,Clone)]
 pub enum ValueInner {
     Int(i32),
@@ -21,8 +178,29 @@ pub enum ValueInner {
     Char(char),
     String(Arc<String>),
     Array(Arc<Vec<Value>>),
-    Record(Arc<AHashMap<String, Value>>),
+    /// 键值对，按插入顺序遍历；删除键时其余的键保持原来的顺序
+    Map(Arc<PawMap>),
+    /// 不含重复元素的集合，按插入顺序遍历
+    Set(Arc<PawSet>),
+    /// 定长元组，元素按位置访问
+    Tuple(Arc<Vec<Value>>),
+    /// `ok(value)`（ok 为 true）或 `err(value)`
+    Result { ok: bool, value: Value },
+    /// record 值：类型名与字段
+    Record(Arc<String>, Arc<AHashMap<String, Value>>),
+    /// enum 值：enum 名、变体名与按位置排列的数据
+    Enum {
+        name: Arc<String>,
+        variant: Arc<String>,
+        payload: Arc<Vec<Value>>,
+    },
     Module(Arc<AHashMap<String, Value>>),
+    /// `0..10`、`10..=0 step -2` 等整数范围，遍历时才逐个产生元素
+    Range(RangeValue),
+    /// `string.builder()` 创建的可变字符串缓冲区；按引用共享，复制变量不会复制内容
+    StringBuilder(Arc<parking_lot::Mutex<String>>),
+    /// `gen_int(0, 100)` 等创建的属性测试生成器，由 `forall` 使用
+    Generator(Arc<Generator>),
     Function {
         name: Arc<String>,
         params: Arc<Vec<Param>>,
@@ -30,18 +208,118 @@ pub enum ValueInner {
         env: Env,
         is_async: bool,
     },
-    Future(Arc<Mutex<Pin<Box<dyn Future<Output=Result<Value, PawError>> + Send>>>>),
+    /// 宿主注册的原生函数
+    NativeFunction {
+        name: Arc<String>,
+        func: NativeFn,
+        /// 没有副作用（只依赖参数），只读求值中也可以调用
+        pure: bool,
+    },
+    /// 异步调用或 async 代码块产生的 Future
+    Future(Arc<Mutex<FutureState>>),
+    /// `memoize(f)` 返回的函数：按参数缓存 `func` 的返回值
+    Memoized {
+        func: Value,
+        cache: Arc<parking_lot::Mutex<AHashMap<Vec<Value>, Value>>>,
+    },
+    /// 同一作用域中同名的多个 `fun`，按声明顺序排列；调用时按参数选择其中一个
+    Overloaded(Arc<Vec<Value>>),
     Null,
     Optional(Arc<Option<Value>>),
 }
 
+impl ValueInner {
+    /// 把独占的容器中的元素移入 `out`，容器本身留空；共享的容器不受影响
+    fn take_children(&mut self, out: &mut Vec<Value>) {
+        match self {
+            ValueInner::Array(items)
+            | ValueInner::Tuple(items)
+            | ValueInner::Overloaded(items)
+            | ValueInner::Enum { payload: items, .. } => {
+                if let Some(items) = Arc::get_mut(items) {
+                    out.append(items);
+                }
+            }
+            ValueInner::Map(m) => {
+                if let Some(m) = Arc::get_mut(m) {
+                    for (k, v) in m.drain(..) {
+                        out.push(k);
+                        out.push(v);
+                    }
+                }
+            }
+            ValueInner::Set(items) => {
+                if let Some(items) = Arc::get_mut(items) {
+                    out.extend(items.drain(..));
+                }
+            }
+            ValueInner::Record(_, fields) | ValueInner::Module(fields) => {
+                if let Some(fields) = Arc::get_mut(fields) {
+                    out.extend(fields.drain().map(|(_, v)| v));
+                }
+            }
+            ValueInner::Optional(o) => {
+                if let Some(o) = Arc::get_mut(o) {
+                    out.extend(o.take());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 逐层析构：嵌套很深的值（如循环中 `a = [a]` 十万次）按层放入一个栈中依次释放，
+/// 不会随嵌套深度递归而耗尽线程栈
+impl Drop for ValueInner {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        self.take_children(&mut pending);
+        while let Some(mut value) = pending.pop() {
+            if let Some(inner) = Arc::get_mut(&mut value.0) {
+                inner.take_children(&mut pending);
+            }
+        }
+    }
+}
+
+/// Float / Double 输出的小数位数；`usize::MAX` 表示按能精确还原的最短形式输出
+static FLOAT_PRECISION: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// 设置进程内 Float / Double 输出的小数位数（至少 1 位）；None 恢复为最短形式
+pub fn set_float_precision(digits: Option<usize>) {
+    let digits = digits.map_or(usize::MAX, |d| d.max(1));
+    FLOAT_PRECISION.store(digits, Ordering::Relaxed);
+}
+
+/// 输出浮点数：有限值总带小数点，避免与 Int 混淆；
+/// 格式化参数中的精度（如 `{:.2}`）优先于全局设置
+fn write_float<T: fmt::Display>(f: &mut fmt::Formatter<'_>, value: T, finite: bool) -> fmt::Result {
+    if !finite {
+        return write!(f, "{}", value);
+    }
+    let digits = match f.precision() {
+        Some(p) => p.max(1),
+        None => FLOAT_PRECISION.load(Ordering::Relaxed),
+    };
+    if digits != usize::MAX {
+        write!(f, "{:.*}", digits, value)
+    } else {
+        let shortest = value.to_string();
+        if shortest.contains('.') {
+            write!(f, "{}", shortest)
+        } else {
+            write!(f, "{}.0", shortest)
+        }
+    }
+}
+
 impl fmt::Display for ValueInner {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ValueInner::Int(i)       => write!(f, "{}", i),
             ValueInner::Long(l)      => write!(f, "{}", l),
-            ValueInner::Float(fl)    => write!(f, "{}", fl),
-            ValueInner::Double(d)    => write!(f, "{}", d),
+            ValueInner::Float(fl)    => write_float(f, fl, fl.is_finite()),
+            ValueInner::Double(d)    => write_float(f, d, d.is_finite()),
             ValueInner::Bool(b)      => write!(f, "{}", b),
             ValueInner::Char(c)      => write!(f, "{}", c),
             ValueInner::String(s)    => write!(f, "{}", s),
@@ -57,14 +335,45 @@ impl fmt::Display for ValueInner {
                 let items: Vec<String> = arr.iter().map(|v| v.to_string()).collect();
                 write!(f, "[{}]", items.join(", "))
             }
-            ValueInner::Record(r)    => {
-                let fields: Vec<String> =
-                    r.iter().map(|(k,v)| format!("{}: {}", k, v)).collect();
+            ValueInner::Map(m)       => {
+                let entries: Vec<String> =
+                    m.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            ValueInner::Set(items)   => {
+                let items: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "{{{}}}", items.join(", "))
+            }
+            ValueInner::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "({})", items.join(", "))
+            }
+            ValueInner::Result { ok, value } => {
+                write!(f, "{}({})", if *ok { "ok" } else { "err" }, value)
+            }
+            ValueInner::Record(_, r) => {
+                // 字段按名字排序，与 `inspect` 相同，每次运行输出相同
+                let mut fields: Vec<(&String, &Value)> = r.iter().collect();
+                fields.sort_by_key(|(k, _)| *k);
+                let fields: Vec<String> = fields.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
                 write!(f, "{{{}}}", fields.join(", "))
             }
+            ValueInner::Enum { name, variant, payload } if payload.is_empty() => {
+                write!(f, "{}.{}", name, variant)
+            }
+            ValueInner::Enum { name, variant, payload } => {
+                let items: Vec<String> = payload.iter().map(|v| v.to_string()).collect();
+                write!(f, "{}.{}({})", name, variant, items.join(", "))
+            }
             ValueInner::Module(_)    => write!(f, "<module>"),
+            ValueInner::Range(r) => write!(f, "{}", r),
+            ValueInner::StringBuilder(buf) => write!(f, "{}", buf.lock()),
+            ValueInner::Generator(_) => write!(f, "<generator>"),
             ValueInner::Function {..}=> write!(f, "<function>"),
+            ValueInner::NativeFunction { name, .. } => write!(f, "<native function {}>", name),
             ValueInner::Future {..}  => write!(f, "<future>"),
+            ValueInner::Memoized { func, .. } => write!(f, "{}", func),
+            ValueInner::Overloaded(_) => write!(f, "<function>"),
         }
     }
 }
@@ -73,12 +382,154 @@ impl fmt::Display for ValueInner {
 #[derive(Clone, Debug)]
 pub struct Value(pub Arc<ValueInner>);
 
+// 构造器沿用 ValueInner 的变体名，`Value::Int(1)` 读起来与枚举构造一致
+#[allow(non_snake_case)]
 impl Value {
     /// 从内部枚举构造
     pub fn from_inner(inner: ValueInner) -> Self {
         Value(Arc::new(inner))
     }
 
+    /// 运行时类型名，用于错误信息
+    pub fn type_name(&self) -> &'static str {
+        match &*self.0 {
+            ValueInner::Int(_) => "Int",
+            ValueInner::Long(_) => "Long",
+            ValueInner::Float(_) => "Float",
+            ValueInner::Double(_) => "Double",
+            ValueInner::Bool(_) => "Bool",
+            ValueInner::Char(_) => "Char",
+            ValueInner::String(_) => "String",
+            ValueInner::Array(_) => "Array",
+            ValueInner::Map(_) => "Map",
+            ValueInner::Set(_) => "Set",
+            ValueInner::Tuple(_) => "Tuple",
+            ValueInner::Result { .. } => "Result",
+            ValueInner::Record(..) => "Record",
+            ValueInner::Enum { .. } => "Enum",
+            ValueInner::Module(_) => "Module",
+            ValueInner::Range(_) => "Range",
+            ValueInner::StringBuilder(_) => "StringBuilder",
+            ValueInner::Generator(_) => "Gen",
+            ValueInner::Function { .. }
+            | ValueInner::NativeFunction { .. }
+            | ValueInner::Memoized { .. }
+            | ValueInner::Overloaded(_) => "Function",
+            ValueInner::Future(_) => "Future",
+            ValueInner::Null => "Nopaw",
+            ValueInner::Optional(_) => "Optional",
+        }
+    }
+
+    /// 推断宿主注入的值在类型检查器中的静态类型；函数、record 等无法还原签名的值视为 Any
+    pub(crate) fn static_type(&self) -> PawType {
+        // 元素类型一致时取该类型，否则退化为 Any
+        fn common<'a>(mut items: impl Iterator<Item = &'a Value>) -> PawType {
+            let first = match items.next() {
+                Some(v) => v.static_type(),
+                None => return PawType::Any,
+            };
+            if items.all(|v| v.static_type() == first) {
+                first
+            } else {
+                PawType::Any
+            }
+        }
+        match &*self.0 {
+            ValueInner::Int(_) => PawType::Int,
+            ValueInner::Long(_) => PawType::Long,
+            ValueInner::Float(_) => PawType::Float,
+            ValueInner::Double(_) => PawType::Double,
+            ValueInner::Bool(_) => PawType::Bool,
+            ValueInner::Char(_) => PawType::Char,
+            ValueInner::String(_) => PawType::String,
+            ValueInner::Array(items) => PawType::Array(Box::new(common(items.iter()))),
+            ValueInner::Map(m) => PawType::Map(
+                Box::new(common(m.keys())),
+                Box::new(common(m.values())),
+            ),
+            ValueInner::Set(items) => PawType::Set(Box::new(common(items.iter()))),
+            ValueInner::Tuple(items) => PawType::Tuple(items.iter().map(Value::static_type).collect()),
+            ValueInner::Result { ok: true, value } => {
+                PawType::Result(Box::new(value.static_type()), Box::new(PawType::Any))
+            }
+            ValueInner::Result { ok: false, value } => {
+                PawType::Result(Box::new(PawType::Any), Box::new(value.static_type()))
+            }
+            ValueInner::Module(_) => PawType::Module,
+            ValueInner::Enum { name, .. } => PawType::Enum(name.to_string()),
+            ValueInner::Range(r) => PawType::Range(Box::new(if r.long { PawType::Long } else { PawType::Int })),
+            ValueInner::StringBuilder(_) => PawType::StringBuilder,
+            ValueInner::Generator(_) => PawType::Gen(Box::new(PawType::Any)),
+            ValueInner::Null => PawType::Optional(Box::new(PawType::Any)),
+            ValueInner::Optional(o) => PawType::Optional(Box::new(match &**o {
+                Some(v) => v.static_type(),
+                None => PawType::Any,
+            })),
+            ValueInner::Future(_) => PawType::Future(Box::new(PawType::Any)),
+            ValueInner::Record(..)
+            | ValueInner::Function { .. }
+            | ValueInner::NativeFunction { .. }
+            | ValueInner::Memoized { .. }
+            | ValueInner::Overloaded(_) => PawType::Any,
+        }
+    }
+
+    /// match 跳转表中的键：Int 与 Long 按数值归为同一种键，Optional 取内部的值，浮点数、容器等没有键
+    pub(crate) fn match_key(&self) -> Option<LiteralKey> {
+        Some(match &*self.0 {
+            ValueInner::Int(n) => LiteralKey::Integer(*n as i64),
+            ValueInner::Long(n) => LiteralKey::Integer(*n),
+            ValueInner::Bool(b) => LiteralKey::Bool(*b),
+            ValueInner::Char(c) => LiteralKey::Char(*c),
+            ValueInner::String(s) => LiteralKey::String(s.to_string()),
+            ValueInner::Null => LiteralKey::Nopaw,
+            ValueInner::Optional(o) => match &**o {
+                Some(v) => return v.match_key(),
+                None => LiteralKey::Nopaw,
+            },
+            _ => return None,
+        })
+    }
+
+    /// `value is ty`：值在运行时是否属于该类型，容器逐个检查元素；
+    /// 函数、Future 与 protocol 无法在运行时区分，检查器不允许用 `is` 测试
+    pub(crate) fn is_instance(&self, ty: &PawType) -> bool {
+        match (&*self.0, ty) {
+            (_, PawType::Any) => true,
+            (ValueInner::Null, PawType::Optional(_)) => true,
+            (ValueInner::Optional(o), _) => match &**o {
+                Some(v) => v.is_instance(ty),
+                None => matches!(ty, PawType::Optional(_)),
+            },
+            (_, PawType::Optional(inner)) => self.is_instance(inner),
+            (ValueInner::Int(_), PawType::Int)
+            | (ValueInner::Long(_), PawType::Long)
+            | (ValueInner::Float(_), PawType::Float)
+            | (ValueInner::Double(_), PawType::Double)
+            | (ValueInner::Bool(_), PawType::Bool)
+            | (ValueInner::Char(_), PawType::Char)
+            | (ValueInner::String(_), PawType::String)
+            | (ValueInner::Module(_), PawType::Module)
+            | (ValueInner::StringBuilder(_), PawType::StringBuilder) => true,
+            (ValueInner::Range(r), PawType::Range(t)) => **t == if r.long { PawType::Long } else { PawType::Int },
+            (ValueInner::Array(items), PawType::Array(t)) => items.iter().all(|v| v.is_instance(t)),
+            (ValueInner::Set(items), PawType::Set(t)) => items.iter().all(|v| v.is_instance(t)),
+            (ValueInner::Map(m), PawType::Map(k, v)) => {
+                m.iter().all(|(key, val)| key.is_instance(k) && val.is_instance(v))
+            }
+            (ValueInner::Tuple(items), PawType::Tuple(ts)) => {
+                items.len() == ts.len() && items.iter().zip(ts).all(|(v, t)| v.is_instance(t))
+            }
+            (ValueInner::Result { ok, value }, PawType::Result(t, e)) => {
+                value.is_instance(if *ok { t } else { e })
+            }
+            (ValueInner::Record(name, _), PawType::Record(r) | PawType::Generic(r, _)) => **name == *r,
+            (ValueInner::Enum { name, .. }, PawType::Enum(e)) => **name == *e,
+            _ => false,
+        }
+    }
+
     // 常见类型构造器
     pub fn Int(v: i32) -> Self {
         Value::from_inner(ValueInner::Int(v))
@@ -104,8 +555,30 @@ impl Value {
     pub fn Array(v: Vec<Value>) -> Self {
         Value::from_inner(ValueInner::Array(Arc::new(v)))
     }
-    pub fn Record(m: AHashMap<String, Value>) -> Self {
-        Value::from_inner(ValueInner::Record(Arc::new(m)))
+    pub fn Map(m: impl IntoIterator<Item = (Value, Value)>) -> Self {
+        Value::from_inner(ValueInner::Map(Arc::new(m.into_iter().collect())))
+    }
+    pub fn Set(items: impl IntoIterator<Item = Value>) -> Self {
+        Value::from_inner(ValueInner::Set(Arc::new(items.into_iter().collect())))
+    }
+    pub fn Tuple(items: Vec<Value>) -> Self {
+        Value::from_inner(ValueInner::Tuple(Arc::new(items)))
+    }
+    pub fn Ok(value: Value) -> Self {
+        Value::from_inner(ValueInner::Result { ok: true, value })
+    }
+    pub fn Err(error: Value) -> Self {
+        Value::from_inner(ValueInner::Result { ok: false, value: error })
+    }
+    pub fn Record(name: &str, m: AHashMap<String, Value>) -> Self {
+        Value::from_inner(ValueInner::Record(Arc::new(name.to_string()), Arc::new(m)))
+    }
+    pub fn Enum(name: &str, variant: &str, payload: Vec<Value>) -> Self {
+        Value::from_inner(ValueInner::Enum {
+            name: Arc::new(name.to_string()),
+            variant: Arc::new(variant.to_string()),
+            payload: Arc::new(payload),
+        })
     }
     pub fn Module(m: AHashMap<String, Value>) -> Self {
         Value::from_inner(ValueInner::Module(Arc::new(m)))
@@ -134,16 +607,171 @@ impl Value {
         })
     }
 
+    /// 原生函数构造
+    pub fn Native<F>(name: &str, func: F) -> Self
+    where
+        F: Fn(Vec<Value>) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        Value::from_inner(ValueInner::NativeFunction {
+            name: Arc::new(name.to_string()),
+            func: NativeFn(Arc::new(func)),
+            pure: false,
+        })
+    }
+
+    /// 把原生函数标记为没有副作用；其它值原样返回
+    pub(crate) fn into_pure(self) -> Self {
+        match &*self.0 {
+            ValueInner::NativeFunction { name, func, .. } => Value::from_inner(ValueInner::NativeFunction {
+                name: name.clone(),
+                func: func.clone(),
+                pure: true,
+            }),
+            _ => self,
+        }
+    }
+
+    /// 包装一个函数值，按参数缓存其返回值
+    pub fn Memoized(func: Value) -> Self {
+        Value::from_inner(ValueInner::Memoized {
+            func,
+            cache: Arc::new(parking_lot::Mutex::new(AHashMap::new())),
+        })
+    }
+
+    /// 把新声明的同名函数并入已有的函数：参数类型相同的旧版本被替换（如循环中重复执行的声明），
+    /// 否则作为新的重载追加
+    pub(crate) fn overload(existing: &Value, func: Value, types: impl Fn(&TypeNode) -> PawType) -> Value {
+        let mut candidates = match &*existing.0 {
+            ValueInner::Overloaded(list) => list.to_vec(),
+            _ => vec![existing.clone()],
+        };
+        let key = func.param_types(&types);
+        match candidates.iter_mut().find(|c| c.param_types(&types) == key) {
+            Some(slot) => *slot = func,
+            None => candidates.push(func),
+        }
+        Value::from_inner(ValueInner::Overloaded(Arc::new(candidates)))
+    }
+
+    /// 脚本函数的参数类型，由 `types` 把类型注解转换为运行时的类型；其它值为 None
+    fn param_types(&self, types: &impl Fn(&TypeNode) -> PawType) -> Option<Vec<PawType>> {
+        match &*self.0 {
+            ValueInner::Function { params, .. } => Some(params.iter().map(|p| types(&p.ty)).collect()),
+            _ => None,
+        }
+    }
+
+    /// 按实参选择重载：参数个数必须相同；只看运行时能判断的参数类型（见 `PawType::is_runtime_checkable`），
+    /// 值完全符合得 2 分、需要数值转换得 1 分、不符合则排除，取总分最高者，同分时取先声明的。
+    /// 类型检查器按同样的规则选择，并拒绝同分的调用
+    pub(crate) fn pick_overload<'a>(
+        candidates: &'a [Value],
+        args: &[Value],
+        types: impl Fn(&TypeNode) -> PawType,
+    ) -> Option<&'a Value> {
+        let mut best: Option<(usize, &Value)> = None;
+        for candidate in candidates {
+            let Some(params) = candidate.param_types(&types) else { continue };
+            if params.len() != args.len() {
+                continue;
+            }
+            let score: Option<usize> = params
+                .iter()
+                .zip(args)
+                .filter(|(param, _)| param.is_runtime_checkable())
+                .map(|(param, arg)| {
+                    if arg.is_instance(param) {
+                        Some(2)
+                    } else {
+                        param.overload_score(&arg.static_type()).filter(|s| *s == 1)
+                    }
+                })
+                .sum();
+            if let Some(score) = score {
+                if best.is_none_or(|(s, _)| score > s) {
+                    best = Some((score, candidate));
+                }
+            }
+        }
+        best.map(|(_, c)| c)
+    }
+
     //// Future 构造
-    pub fn Future(
-        fut: Pin<Box<dyn Future<Output = Result<Value, PawError>> + Send>>
-    ) -> Self {
-        Value::from_inner(ValueInner::Future(Arc::new(Mutex::new(fut))))
+    pub fn Future(fut: PawFuture) -> Self {
+        Value::from_inner(ValueInner::Future(Arc::new(Mutex::new(FutureState::Pending(fut)))))
+    }
+
+    /// await 一个 Future：首次 await 时驱动并缓存结果，之后的 await 直接返回缓存。
+    /// 不是 Future，或 Future 在完成前被取消时返回 None
+    pub(crate) async fn await_future(&self) -> Option<Result<Value, Box<PawError>>> {
+        self.poll_future(false).await
+    }
+
+    /// 只驱动尚未开始的 Future；已经 await 过的返回 None，其结果已交给 await 它的代码处理
+    pub(crate) async fn drive_pending(&self) -> Option<Result<Value, Box<PawError>>> {
+        self.poll_future(true).await
+    }
+
+    async fn poll_future(&self, only_pending: bool) -> Option<Result<Value, Box<PawError>>> {
+        let ValueInner::Future(cell) = &*self.0 else {
+            return None;
+        };
+        // 持有锁直到完成，同时 await 同一个 Future 的其它任务会等待并读取缓存
+        let mut state = cell.lock().await;
+        match std::mem::replace(&mut *state, FutureState::Running) {
+            FutureState::Pending(fut) => {
+                let res = fut.await;
+                *state = FutureState::Done(res.clone());
+                Some(res)
+            }
+            FutureState::Done(res) => {
+                *state = FutureState::Done(res.clone());
+                (!only_pending).then_some(res)
+            }
+            FutureState::Running => None,
+        }
     }
     
 }
 
 impl Value {
+    /// 能否作为缓存键：函数、Future 等按引用区分的值与任何值都不相等，缓存它们只会无限增长
+    pub(crate) fn is_cacheable(&self) -> bool {
+        match &*self.0 {
+            ValueInner::Array(items) | ValueInner::Tuple(items) | ValueInner::Enum { payload: items, .. } => {
+                items.iter().all(Value::is_cacheable)
+            }
+            ValueInner::Set(items) => items.iter().all(Value::is_cacheable),
+            ValueInner::Map(m) => m.iter().all(|(k, v)| k.is_cacheable() && v.is_cacheable()),
+            ValueInner::Record(_, r) | ValueInner::Module(r) => r.values().all(Value::is_cacheable),
+            ValueInner::Optional(o) => o.as_ref().as_ref().is_none_or(Value::is_cacheable),
+            ValueInner::Result { value, .. } => value.is_cacheable(),
+            ValueInner::Function { .. }
+            | ValueInner::NativeFunction { .. }
+            | ValueInner::Memoized { .. }
+            | ValueInner::Overloaded(_)
+            | ValueInner::StringBuilder(_)
+            | ValueInner::Generator(_)
+            | ValueInner::Future(_) => false,
+            _ => true,
+        }
+    }
+
+    /// 遍历协议：Array、Set 与 Range 产生元素，Map 产生 `(键, 值)` 元组，String 产生 Char；
+    /// 其它值不能遍历，返回 None
+    pub(crate) fn loop_items(&self) -> Option<LoopItems> {
+        let items: Vec<Value> = match &*self.0 {
+            ValueInner::Array(items) => (**items).clone(),
+            ValueInner::Set(items) => items.iter().cloned().collect(),
+            ValueInner::Map(m) => m.iter().map(|(k, v)| Value::Tuple(vec![k.clone(), v.clone()])).collect(),
+            ValueInner::String(s) => s.chars().map(Value::Char).collect(),
+            ValueInner::Range(range) => return Some(LoopItems::Range(range.iter())),
+            _ => return None,
+        };
+        Some(LoopItems::Items(items.into_iter()))
+    }
+
     /// 如果自己是字符串，就返回 &str，否则返回 None
     pub fn as_str(&self) -> Option<&str> {
         use crate::interpreter::value::ValueInner;
@@ -157,8 +785,11 @@ impl Value {
     pub fn into_array(self) -> Option<Vec<Value>> {
         use crate::interpreter::value::ValueInner;
         match Arc::try_unwrap(self.0) {
-            Ok(inner) => match inner {
-                ValueInner::Array(v) => Some(Arc::try_unwrap(v).unwrap_or_else(|v_arc| (*v_arc).clone())),
+            Ok(mut inner) => match &mut inner {
+                ValueInner::Array(v) => Some(match Arc::get_mut(v) {
+                    Some(items) => std::mem::take(items),
+                    None => (**v).clone(),
+                }),
                 _ => None,
             },
             Err(arc) => match &*arc {
@@ -186,7 +817,8 @@ impl From<&str> for Value {
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", &*self.0)
+        // 直接转发，保留调用方给出的精度等格式化参数
+        fmt::Display::fmt(&*self.0, f)
     }
 }
 
@@ -203,8 +835,20 @@ impl PartialEq for Value {
             (Char(a), Char(b)) => a == b,
             (String(a), String(b)) => a == b,
             (Array(a), Array(b)) => a == b,
-            (Record(a), Record(b)) => a == b,
+            (Map(a), Map(b)) => a == b,
+            (Set(a), Set(b)) => a == b,
+            (Tuple(a), Tuple(b)) => a == b,
+            (Result { ok: a, value: x }, Result { ok: b, value: y }) => a == b && x == y,
+            (Record(na, a), Record(nb, b)) => na == nb && a == b,
+            (
+                Enum { name: na, variant: va, payload: a },
+                Enum { name: nb, variant: vb, payload: b },
+            ) => na == nb && va == vb && a == b,
             (Module(a), Module(b)) => a == b,
+            (Range(a), Range(b)) => a == b,
+            // 可变缓冲区按身份比较
+            (StringBuilder(a), StringBuilder(b)) => Arc::ptr_eq(a, b),
+            (Generator(a), Generator(b)) => Arc::ptr_eq(a, b),
             (Null, Null) => true,
             (Optional(a), Optional(b)) => a == b,
             _ => false,
@@ -213,3 +857,39 @@ impl PartialEq for Value {
 }
 
 impl Eq for Value {}
+
+// Hash 与 PartialEq 保持一致，供 Map 键使用；Function/Future 等按类型统一哈希
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use ValueInner::*;
+        std::mem::discriminant(&*self.0).hash(state);
+        match &*self.0 {
+            Int(i) => i.hash(state),
+            Long(l) => l.hash(state),
+            Bool(b) => b.hash(state),
+            Char(c) => c.hash(state),
+            String(s) => s.hash(state),
+            Array(a) | Tuple(a) => a.hash(state),
+            Optional(o) => o.hash(state),
+            Result { ok, value } => (ok, value).hash(state),
+            Enum { name, variant, payload } => (name, variant, payload).hash(state),
+            Range(r) => r.hash(state),
+            // 无序容器：逐项单独哈希后求和，与遍历顺序无关
+            Map(m) => unordered_hash(m.iter()).hash(state),
+            Set(items) => unordered_hash(items.iter().map(|v| (v, ()))).hash(state),
+            Record(_, r) | Module(r) => unordered_hash(r.iter()).hash(state),
+            // 浮点数相等带误差，无法稳定哈希，只按类型区分
+            _ => {}
+        }
+    }
+}
+
+fn unordered_hash<K: Hash, V: Hash>(entries: impl Iterator<Item = (K, V)>) -> u64 {
+    entries
+        .map(|entry| {
+            let mut h = std::collections::hash_map::DefaultHasher::new();
+            entry.hash(&mut h);
+            h.finish()
+        })
+        .fold(0u64, u64::wrapping_add)
+}
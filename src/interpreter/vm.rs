@@ -0,0 +1,249 @@
+// src/interpreter/vm.rs
+//
+// 执行循环字节码的栈式虚拟机。整段字节码同步运行，不再为每个节点创建 future；
+// 运算语义与树遍历解释器共用 Engine 上的 unary_value / binary_value / index_value。
+
+use crate::compiler::bytecode::{Chunk, Op, Slot};
+use crate::error::error::PawError;
+use crate::interpreter::control_flow::ControlFlow;
+use crate::interpreter::interpreter::Engine;
+use crate::interpreter::value::{LoopItems, RangeIter, Value, ValueInner};
+
+/// 具名槽位在本次执行中的修改情况，决定退出时如何写回环境
+#[derive(Clone, Copy, PartialEq)]
+enum Dirty {
+    Clean,
+    Assigned,
+    Defined,
+}
+
+/// 隐藏槽位中的循环状态
+enum Hidden {
+    Empty,
+    Range(RangeIter),
+    Iter(LoopItems),
+}
+
+impl Engine {
+    /// 执行一段字节码；无论正常结束还是出错，修改过的变量都会写回环境
+    pub(crate) fn run_chunk(&mut self, chunk: &Chunk) -> Result<ControlFlow, Box<PawError>> {
+        let mut slots: Vec<Option<Value>> = chunk
+            .names
+            .iter()
+            .zip(&chunk.slots)
+            .map(|(name, kind)| match kind {
+                Slot::Global => self.env.get(name),
+                Slot::Local { .. } => None,
+            })
+            .collect();
+        let mut dirty = vec![Dirty::Clean; slots.len()];
+        let result = self.exec(chunk, &mut slots, &mut dirty);
+        let (line, column) = chunk.positions.first().copied().unwrap_or_default();
+        let globals = chunk.names.iter().zip(&chunk.slots).zip(slots).zip(dirty);
+        for (((name, kind), value), state) in globals {
+            if let (Slot::Global, Some(v)) = (kind, value) {
+                match state {
+                    Dirty::Defined => self.env.define(name.clone(), v),
+                    // 进入时已从环境读到该变量，写回不会失败
+                    Dirty::Assigned => self.env.assign(name, v, &self.file, line, column)?,
+                    Dirty::Clean => {}
+                }
+            }
+        }
+        result
+    }
+
+    fn exec(
+        &mut self,
+        chunk: &Chunk,
+        slots: &mut [Option<Value>],
+        dirty: &mut [Dirty],
+    ) -> Result<ControlFlow, Box<PawError>> {
+        let mut stack: Vec<Value> = Vec::with_capacity(16);
+        let mut hidden: Vec<Hidden> = (0..chunk.hidden).map(|_| Hidden::Empty).collect();
+        let mut pc = 0usize;
+
+        while pc < chunk.ops.len() {
+            let (line, col) = chunk.positions[pc];
+            let op = &chunk.ops[pc];
+            pc += 1;
+            self.count_instruction();
+            match op {
+                Op::Const(idx) => stack.push(chunk.constants[*idx as usize].clone()),
+                Op::Load(slot) => {
+                    let slot = resolve(chunk, slots, *slot);
+                    let v = slots[slot]
+                        .clone()
+                        .ok_or_else(|| self.undefined(&chunk.names[slot], line, col))?;
+                    stack.push(v);
+                }
+                Op::Clear(slot) => slots[*slot as usize] = None,
+                Op::Store(slot) => {
+                    let slot = resolve(chunk, slots, *slot);
+                    if slots[slot].is_none() {
+                        return Err(self.undefined(&chunk.names[slot], line, col));
+                    }
+                    slots[slot] = Some(pop(&mut stack));
+                    if dirty[slot] == Dirty::Clean {
+                        dirty[slot] = Dirty::Assigned;
+                    }
+                }
+                Op::Define(slot) => {
+                    slots[*slot as usize] = Some(pop(&mut stack));
+                    dirty[*slot as usize] = Dirty::Defined;
+                }
+                Op::Neg => {
+                    let v = pop(&mut stack);
+                    stack.push(self.unary_value("-", v, line, col)?);
+                }
+                Op::Not => {
+                    let v = pop(&mut stack);
+                    stack.push(self.unary_value("!", v, line, col)?);
+                }
+                Op::Binary(bin) => {
+                    let r = pop(&mut stack);
+                    let l = pop(&mut stack);
+                    stack.push(self.binary_value(bin, l, r, line, col)?);
+                }
+                Op::Index => {
+                    let idx = pop(&mut stack);
+                    let arr = pop(&mut stack);
+                    stack.push(self.index_value(&arr, &idx, line, col)?);
+                }
+                Op::Concat(n) => {
+                    let parts = stack.split_off(stack.len() - *n as usize);
+                    let mut out = String::new();
+                    for part in parts {
+                        out.push_str(&part.to_string());
+                    }
+                    self.check_string_size(out.len(), line, col)?;
+                    self.count_allocation();
+                    stack.push(Value::String(out));
+                }
+                Op::Format(idx) => {
+                    let v = pop(&mut stack);
+                    let text = self.format_value(&chunk.specs[*idx as usize], &v, line, col)?;
+                    self.count_allocation();
+                    stack.push(Value::String(text));
+                }
+                Op::Pop => {
+                    pop(&mut stack);
+                }
+                Op::Say => println!("{}", pop(&mut stack)),
+                Op::Jump(target) => {
+                    let target = *target as usize;
+                    // 向回跳即进入下一轮循环，与逐语句检查一样及时响应取消
+                    if target < pc {
+                        self.check_cancelled(line, col)?;
+                    }
+                    pc = target;
+                }
+                Op::JumpUnlessTrue(target) => {
+                    if !matches!(&*pop(&mut stack).0, ValueInner::Bool(true)) {
+                        pc = *target as usize;
+                    }
+                }
+                Op::ShortCircuit { decided, target } => {
+                    if matches!(stack.last().map(|v| &*v.0), Some(ValueInner::Bool(b)) if b == decided) {
+                        pc = *target as usize;
+                    }
+                }
+                Op::LoopCond(target) => {
+                    let cond = pop(&mut stack);
+                    match &*cond.0 {
+                        ValueInner::Bool(true) => {}
+                        ValueInner::Bool(false) => pc = *target as usize,
+                        _ => {
+                            return Err(Box::new(PawError::Runtime {
+                                file: self.file.clone(),
+                                code: "E3007",
+                                message: format!(
+                                    "Loop condition must be Bool, found {}",
+                                    cond.type_name()
+                                ),
+                                line,
+                                column: col,
+                                snippet: None,
+                                hint: None,
+                                backtrace: Vec::new(),
+                            }));
+                        }
+                    }
+                }
+                Op::RangeInit { range, step, inclusive } => {
+                    let step = if *step { Some(pop(&mut stack)) } else { None };
+                    let e_val = pop(&mut stack);
+                    let s_val = pop(&mut stack);
+                    let r = self.range_value(&s_val, &e_val, step.as_ref(), *inclusive, line, col)?;
+                    hidden[*range as usize] = Hidden::Range(r.iter());
+                }
+                Op::RangeNext { range, var, exit } => match &mut hidden[*range as usize] {
+                    Hidden::Range(iter) => match iter.next() {
+                        Some(v) => {
+                            slots[*var as usize] = Some(v);
+                            dirty[*var as usize] = Dirty::Defined;
+                        }
+                        None => pc = *exit as usize,
+                    },
+                    _ => pc = *exit as usize,
+                },
+                Op::IterInit(h) => {
+                    let v = pop(&mut stack);
+                    let Some(items) = v.loop_items() else {
+                        return Err(Box::new(PawError::Runtime {
+                            file: self.file.clone(),
+                            code: "E3018",
+                            message: format!("Cannot loop over {}", v.type_name()),
+                            line,
+                            column: col,
+                            snippet: None,
+                            hint: None,
+                            backtrace: Vec::new(),
+                        }));
+                    };
+                    hidden[*h as usize] = Hidden::Iter(items);
+                }
+                Op::IterNext { iter, var, exit } => match &mut hidden[*iter as usize] {
+                    Hidden::Iter(items) => match items.next() {
+                        Some(v) => {
+                            slots[*var as usize] = Some(v);
+                            dirty[*var as usize] = Dirty::Defined;
+                        }
+                        None => pc = *exit as usize,
+                    },
+                    _ => pc = *exit as usize,
+                },
+                Op::Return => return Ok(ControlFlow::Return(pop(&mut stack))),
+            }
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    fn undefined(&self, name: &str, line: usize, col: usize) -> Box<PawError> {
+        Box::new(PawError::UndefinedVariable {
+            file: self.file.clone(),
+            code: "E4001",
+            name: name.to_string(),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("Did you declare this variable before use?".into()),
+        })
+    }
+}
+
+/// 块内局部变量尚未定义时，沿 `outer` 找到外层同名变量的槽位
+fn resolve(chunk: &Chunk, slots: &[Option<Value>], mut slot: u32) -> usize {
+    while slots[slot as usize].is_none() {
+        match chunk.slots[slot as usize] {
+            Slot::Local { outer } => slot = outer,
+            Slot::Global => break,
+        }
+    }
+    slot as usize
+}
+
+/// 编译器保证每次弹栈前都有值
+fn pop(stack: &mut Vec<Value>) -> Value {
+    stack.pop().expect("bytecode stack underflow")
+}
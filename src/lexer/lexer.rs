@@ -1,5 +1,6 @@
 // src/lexer/lexer.rs
-use crate::lexer::token::{Token, TokenKind};
+use crate::lexer::token::{StringPart, Token, TokenKind};
+use std::ops::Range;
 
 pub struct Lexer {
     src: Vec<char>,
@@ -29,6 +30,28 @@ impl Lexer {
         tokens
     }
 
+    /// 与 `tokenize` 相同，但同时给出每个 Token 在源码中的字符区间（按 char 计数），
+    /// 区间之间只有空白和注释；供语法高亮等需要还原源码的工具使用
+    pub fn tokenize_spanned(mut self) -> Vec<(Token, Range<usize>)> {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            self.src
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == '\n')
+                .map(|(i, _)| i + 1),
+        );
+        let mut tokens = Vec::new();
+        loop {
+            let tok = self.next_token();
+            let start = line_starts[tok.line - 1] + tok.column - 1;
+            let is_eof = matches!(tok.kind, TokenKind::Eof);
+            tokens.push((tok, start..self.pos));
+            if is_eof { break; }
+        }
+        tokens
+    }
+
     fn next_char(&mut self) -> Option<char> {
         if let Some(&c) = self.src.get(self.pos) {
             self.pos += 1;
@@ -44,6 +67,83 @@ impl Lexer {
         }
     }
 
+    /// 读取 `${` 之后直到匹配的 `}` 为止的源码（不含 `}`），
+    /// 其中可以包含花括号和带插值的字符串；到达输入末尾则返回 None。
+    /// 不在任何括号内的第一个 `:` 之后是格式说明，与源码分开返回
+    fn read_interpolation(&mut self) -> Option<(String, Option<String>)> {
+        enum Mode {
+            // 代码，记录未闭合的 `{` 数量
+            Code(usize),
+            Str,
+        }
+        let mut code = String::new();
+        let mut modes = vec![Mode::Code(0)];
+        // 最外层代码中未闭合的 `(` 与 `[` 数量，以及格式说明前的 `:` 的位置
+        let mut nesting = 0usize;
+        let mut spec_at = None;
+        while let Some(c) = self.next_char() {
+            let outermost = modes.len() == 1;
+            match modes.last_mut()? {
+                Mode::Code(depth) => match c {
+                    '{' => *depth += 1,
+                    '}' if *depth > 0 => *depth -= 1,
+                    '}' => {
+                        modes.pop();
+                        if modes.is_empty() {
+                            let spec = spec_at.map(|at: usize| {
+                                let spec = code[at + 1..].to_string();
+                                code.truncate(at);
+                                spec
+                            });
+                            return Some((code, spec));
+                        }
+                    }
+                    '"' => modes.push(Mode::Str),
+                    // 字符字面量原样保留，其中的括号和 `:` 不计
+                    '\'' => {
+                        code.push(c);
+                        while let Some(nc) = self.next_char() {
+                            code.push(nc);
+                            if nc == '\\' {
+                                code.extend(self.next_char());
+                            } else if nc == '\'' {
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+                    '(' | '[' if outermost => nesting += 1,
+                    ')' | ']' if outermost => nesting = nesting.saturating_sub(1),
+                    ':' if outermost && *depth == 0 && nesting == 0 && spec_at.is_none() => {
+                        spec_at = Some(code.len());
+                    }
+                    _ => {}
+                },
+                Mode::Str => match c {
+                    '"' => {
+                        modes.pop();
+                    }
+                    '\\' => {
+                        code.push(c);
+                        if let Some(esc) = self.next_char() {
+                            code.push(esc);
+                        }
+                        continue;
+                    }
+                    '$' if self.peek_char() == Some('{') => {
+                        self.next_char();
+                        code.push_str("${");
+                        modes.push(Mode::Code(0));
+                        continue;
+                    }
+                    _ => {}
+                },
+            }
+            code.push(c);
+        }
+        None
+    }
+
     fn peek_char(&self) -> Option<char> {
         self.src.get(self.pos).copied()
     }
@@ -67,9 +167,13 @@ impl Lexer {
             None => return Token::new(TokenKind::Eof, start_line, start_col),
         };
 
-        // Range operator `..`
+        // Range operator `..` / `..=`
         if c == '.' && self.peek_char() == Some('.') {
             self.next_char();
+            if self.peek_char() == Some('=') {
+                self.next_char();
+                return Token::new(TokenKind::RangeInclusive, start_line, start_col);
+            }
             return Token::new(TokenKind::Range, start_line, start_col);
         }
         match c {
@@ -77,7 +181,14 @@ impl Lexer {
             ']' => Token::new(TokenKind::RBracket, start_line, start_col),
             '+' => Token::new(TokenKind::Plus, start_line, start_col),
             '-' => Token::new(TokenKind::Minus, start_line, start_col),
-            '*' => Token::new(TokenKind::Star, start_line, start_col),
+            '*' => {
+                if self.peek_char() == Some('*') {
+                    self.next_char();
+                    Token::new(TokenKind::StarStar, start_line, start_col)
+                } else {
+                    Token::new(TokenKind::Star, start_line, start_col)
+                }
+            }
             '/' => Token::new(TokenKind::Slash, start_line, start_col),
             '%' => Token::new(TokenKind::Percent, start_line, start_col),
             '(' => Token::new(TokenKind::LParen, start_line, start_col),
@@ -88,11 +199,15 @@ impl Lexer {
             ':' => Token::new(TokenKind::Colon, start_line, start_col),
             '.' => Token::new(TokenKind::Dot, start_line, start_col),
             '?' => Token::new(TokenKind::Question, start_line, start_col),
+            '@' => Token::new(TokenKind::At, start_line, start_col),
 
             '=' => {
                 if self.peek_char() == Some('=') {
                     self.next_char();
                     Token::new(TokenKind::EqEq, start_line, start_col)
+                } else if self.peek_char() == Some('>') {
+                    self.next_char();
+                    Token::new(TokenKind::FatArrow, start_line, start_col)
                 } else {
                     Token::new(TokenKind::Assign, start_line, start_col)
                 }
@@ -135,12 +250,38 @@ impl Lexer {
                     if nc == '\n' { break; }
                     self.next_char();
                 }
-                return self.next_token();
+                self.next_token()
             }
             '"' => {
                 let mut s = String::new();
+                let mut parts = Vec::new();
+                let mut closed = false;
                 while let Some(nc) = self.next_char() {
-                    if nc == '"' { break; }
+                    if nc == '"' {
+                        closed = true;
+                        break;
+                    }
+                    if nc == '$' && self.peek_char() == Some('{') {
+                        self.next_char();
+                        let (line, column) = (self.line, self.column);
+                        let Some((src, spec)) = self.read_interpolation() else {
+                            return Token::new(
+                                TokenKind::Error("Unterminated string interpolation".into()),
+                                start_line,
+                                start_col,
+                            );
+                        };
+                        if !s.is_empty() {
+                            parts.push(StringPart::Text(std::mem::take(&mut s)));
+                        }
+                        parts.push(StringPart::Code {
+                            src,
+                            spec,
+                            line,
+                            column,
+                        });
+                        continue;
+                    }
                     if nc == '\\' {
                         if let Some(esc) = self.next_char() {
                             match esc {
@@ -149,6 +290,7 @@ impl Lexer {
                                 'r' => s.push('\r'),
                                 '\\' => s.push('\\'),
                                 '"' => s.push('"'),
+                                '$' => s.push('$'),
                                 other => { s.push('\\'); s.push(other); }
                             }
                             continue;
@@ -158,19 +300,39 @@ impl Lexer {
                     }
                     s.push(nc);
                 }
-                Token::new(TokenKind::StringLiteral(s), start_line, start_col)
+                if !closed {
+                    return Token::new(
+                        TokenKind::Error("Unterminated string literal".into()),
+                        start_line,
+                        start_col,
+                    );
+                }
+                if parts.is_empty() {
+                    return Token::new(TokenKind::StringLiteral(s), start_line, start_col);
+                }
+                if !s.is_empty() {
+                    parts.push(StringPart::Text(s));
+                }
+                Token::new(TokenKind::InterpolatedString(parts), start_line, start_col)
             }
             '\'' => {
-                let ch = self.next_char().unwrap_or('\0');
-                self.next_char();
-                Token::new(TokenKind::CharLiteral(ch), start_line, start_col)
+                let ch = self.next_char();
+                match (ch, self.next_char()) {
+                    (Some(ch), Some('\'')) => Token::new(TokenKind::CharLiteral(ch), start_line, start_col),
+                    _ => Token::new(
+                        TokenKind::Error("Unterminated char literal".into()),
+                        start_line,
+                        start_col,
+                    ),
+                }
             }
             c if c.is_ascii_digit() => self.lex_number(c, start_line, start_col),
             c if c.is_alphabetic() || c == '_' => {
                 let mut ident = c.to_string();
                 while let Some(nc) = self.peek_char() {
                     if nc.is_alphanumeric() || nc == '_' {
-                        ident.push(self.next_char().unwrap());
+                        self.next_char();
+                        ident.push(nc);
                     } else { break; }
                 }
                 let kind = match ident.as_str() {
@@ -178,17 +340,18 @@ impl Lexer {
                     "false" => TokenKind::BoolLiteral(false),
                     // 关键字
                     kw @ "import" | kw @ "fun" | kw @ "async" | kw @ "await" |
-                    kw @ "let" | kw @ "say" | kw @ "ask" | kw @ "as" |
+                    kw @ "let" | kw @ "say" | kw @ "say_debug" | kw @ "ask" | kw @ "as" | kw @ "is" |
                     kw @ "if" | kw @ "else" | kw @ "loop" | kw @ "forever" |
                     kw @ "return" | kw @ "break" | kw @ "continue" |
-                    kw @ "in" | kw @ "bark" | kw @ "sniff" |
-                    kw @ "snatch" | kw @ "lastly" | kw @ "nopaw" | kw @ "record" => {
+                    kw @ "in" | kw @ "bark" | kw @ "sniff" | kw @ "match" |
+                    kw @ "snatch" | kw @ "lastly" | kw @ "nopaw" | kw @ "record" | kw @ "enum" | kw @ "protocol" |
+                    kw @ "task_group" | kw @ "spawn" | kw @ "tail" => {
                         TokenKind::Keyword(kw.into())
                     }
                     // 类型
                     ty @ "Int" | ty @ "Long" | ty @ "Float" | ty @ "Double" |
                     ty @ "String" | ty @ "Char" | ty @ "Bool" | ty @ "Any" |
-                    ty @ "Void" | ty @ "Array" => TokenKind::Type(ty.into()),
+                    ty @ "Void" | ty @ "Array" | ty @ "Map" => TokenKind::Type(ty.into()),
                     _ => TokenKind::Identifier(ident.clone()),
                 };
                 Token::new(kind, start_line, start_col)
@@ -203,7 +366,8 @@ impl Lexer {
         // 整数部分
         while let Some(c) = self.peek_char() {
             if c.is_ascii_digit() {
-                num.push(self.next_char().unwrap());
+                self.next_char();
+                num.push(c);
             } else {
                 break;
             }
@@ -212,11 +376,13 @@ impl Lexer {
         // 如果是小数点，且不是范围操作符 ".."
         if self.peek_char() == Some('.') && self.src.get(self.pos + 1) != Some(&'.') {
             // 吃掉 '.'
-            num.push(self.next_char().unwrap());
+            self.next_char();
+            num.push('.');
             // 小数部分
             while let Some(c2) = self.peek_char() {
                 if c2.is_ascii_digit() {
-                    num.push(self.next_char().unwrap());
+                    self.next_char();
+                    num.push(c2);
                 } else {
                     break;
                 }
@@ -8,6 +8,8 @@ pub enum TokenKind {
     DoubleLiteral(f64),
     LongLiteral(i64),
     StringLiteral(String),
+    /// 含有 `${...}` 的字符串
+    InterpolatedString(Vec<StringPart>),
     CharLiteral(char),
     BoolLiteral(bool),
     
@@ -20,6 +22,8 @@ pub enum TokenKind {
     Plus,
     Minus,
     Star,
+    /// `**`
+    StarStar,
     Slash,
     Percent,
     EqEq,
@@ -33,8 +37,12 @@ pub enum TokenKind {
     Not,
     Assign,
     LeftArrow,
+    FatArrow, // "=>"
     Range,    // ".."
+    RangeInclusive, // "..="
     Question,
+    /// `@`，属性的开头，如 `@cfg("debug")`
+    At,
 
     // Delimiters
     LParen,
@@ -52,6 +60,19 @@ pub enum TokenKind {
     Error(String),
 }
 
+/// 插值字符串的组成部分
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    /// `${...}` 中的源码、`:` 之后的格式说明及源码的起始位置
+    Code {
+        src: String,
+        spec: Option<String>,
+        line: usize,
+        column: usize,
+    },
+}
+
 /// 带源位置信息的 Token
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
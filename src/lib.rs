@@ -0,0 +1,30 @@
+// cli、error、lexer、parser、interpreter 沿用 `x/x.rs` 的目录布局
+#![allow(clippy::module_inception)]
+
+use once_cell::sync::OnceCell;
+
+pub mod ast;
+pub mod cli;
+pub mod compiler;
+pub mod embed;
+pub mod error;
+pub mod fuzz;
+pub mod highlight;
+pub mod interpreter;
+pub mod lexer;
+pub mod parser;
+pub mod playground;
+pub mod runtime;
+pub mod semantic;
+pub mod session;
+pub mod testing;
+
+pub use embed::PawScript;
+pub use error::error::PawError;
+pub use interpreter::host::Host;
+pub use interpreter::interpreter::{SizeLimits, Stats};
+pub use interpreter::value::{set_float_precision, RangeValue, Value, ValueInner};
+pub use session::Session;
+
+/// 执行线程的栈大小（MiB），由 CLI 的 `--stack-size` 设置；未设置时为 1
+pub static STACK_SIZE: OnceCell<usize> = OnceCell::new();
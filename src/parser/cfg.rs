@@ -0,0 +1,40 @@
+// src/parser/cfg.rs
+//
+// 条件编译：`@cfg(...)` 标注的语句只在条件成立时保留，否则在解析阶段丢弃，
+// 既不参与类型检查，也没有任何运行时开销
+
+use std::collections::BTreeSet;
+
+/// `@cfg(...)` 判断时使用的开关集合
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cfg {
+    flags: BTreeSet<String>,
+}
+
+impl Default for Cfg {
+    fn default() -> Self {
+        Cfg::new()
+    }
+}
+
+impl Cfg {
+    /// 只打开当前平台开关的集合：操作系统名（`linux`、`macos`、`windows` 等）与系统族（`unix` 或 `windows`）
+    pub fn new() -> Self {
+        let mut cfg = Cfg {
+            flags: BTreeSet::new(),
+        };
+        cfg.enable(std::env::consts::OS);
+        cfg.enable(std::env::consts::FAMILY);
+        cfg
+    }
+
+    /// 打开一个开关，如命令行的 `--cfg debug`
+    pub fn enable(&mut self, flag: &str) {
+        self.flags.insert(flag.to_string());
+    }
+
+    /// 开关是否打开
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+}
@@ -1,18 +1,32 @@
 // src/parser.rs
 
-use crate::ast::expr::{BinaryOp, Expr, ExprKind};
+use crate::ast::expr::{BinaryOp, Expr, ExprKind, FormatSpec};
 use crate::ast::method::Method;
 use crate::ast::param::Param;
-use crate::ast::statement::{Statement, StatementKind};
+use crate::ast::pattern::{MatchArm, MatchTable, Pattern, PatternKind};
+use crate::ast::statement::{Attribute, EnumVariant, LoopVar, SnatchClause, Statement, StatementKind};
+use crate::ast::type_node::{TypeNode, TypeNodeKind};
 use crate::error::error::PawError;
 use crate::lexer::lexer::Lexer;
-use crate::lexer::token::{Token, TokenKind};
+use crate::lexer::token::{StringPart, Token, TokenKind};
+use crate::parser::cfg::Cfg;
+
+/// 表达式 / 代码块的最大嵌套深度，防止恶意输入撑爆调用栈
+const MAX_NESTING_DEPTH: usize = 128;
+
+/// `**` 的优先级，是二元运算符中最高的
+const POW_PREC: u8 = 8;
 
 pub struct Parser {
     tokens: Vec<Token>,
     position: usize,
     lines: Vec<String>,
     file: String,
+    depth: usize,
+    /// 解析 match 的对象时为 true：`name {` 不当作 record 初始化，留给分支列表
+    no_record_init: bool,
+    /// `@cfg(...)` 使用的开关
+    cfg: Cfg,
 }
 
 impl Parser {
@@ -22,9 +36,18 @@ impl Parser {
             position: 0,
             lines: source.lines().map(|l| l.to_string()).collect(),
             file: filename.into(),
+            depth: 0,
+            no_record_init: false,
+            cfg: Cfg::new(),
         }
     }
 
+    /// 使用给定的开关判断 `@cfg(...)`，默认只打开当前平台的开关
+    pub fn with_cfg(mut self, cfg: &Cfg) -> Self {
+        self.cfg = cfg.clone();
+        self
+    }
+
     // --- Utility methods ---
     fn peek(&self) -> Option<&Token> {
         self.tokens.get(self.position)
@@ -52,23 +75,37 @@ impl Parser {
         t
     }
 
+    /// 当前 token 的位置；越过末尾时取最后一个 token（Eof）的位置
     fn wrap_position(&self) -> (usize, usize) {
-        if let Some(tok) = self.peek() {
-            (tok.line, tok.column)
-        } else {
-            (0, 0)
+        match self.peek().or(self.tokens.last()) {
+            Some(tok) => (tok.line, tok.column),
+            None => (1, 1),
         }
     }
+
+    /// token 已经读完时的语法错误，位置指向源码末尾
+    fn eof_error(&self) -> Box<PawError> {
+        let (line, column) = self.wrap_position();
+        Box::new(PawError::Syntax {
+            file: self.file.clone(),
+            code: "E1001",
+            message: "Unexpected EOF".into(),
+            line,
+            column,
+            snippet: self.snippet(line),
+            hint: None,
+        })
+    }
     fn snippet(&self, line: usize) -> Option<String> {
-        self.lines.get(line.saturating_sub(1)).cloned().into()
+        self.lines.get(line.saturating_sub(1)).cloned()
     }
 
-    fn expect_token(&mut self, expected: TokenKind) -> Result<(), PawError> {
+    fn expect_token(&mut self, expected: TokenKind) -> Result<(), Box<PawError>> {
         if let Some(tok) = self.next() {
             if tok.kind == expected {
                 Ok(())
             } else {
-                Err(PawError::Syntax {
+                Err(Box::new(PawError::Syntax {
                     file: self.file.clone(),
                     code: "E1001",
                     message: format!("Expected {:?}, got {:?}", expected, tok.kind),
@@ -76,29 +113,21 @@ impl Parser {
                     column: tok.column,
                     snippet: self.snippet(tok.line),
                     hint: Some("Check token".into()),
-                })
+                }))
             }
         } else {
-            Err(PawError::Syntax {
-                file: self.file.clone(),
-                code: "E1001",
-                message: "Unexpected EOF".into(),
-                line: 0,
-                column: 0,
-                snippet: None,
-                hint: None,
-            })
+            Err(self.eof_error())
         }
     }
 
-    fn expect_keyword(&mut self, kw: &str) -> Result<(), PawError> {
+    fn expect_keyword(&mut self, kw: &str) -> Result<(), Box<PawError>> {
         if let Some(tok) = self.next() {
             if let TokenKind::Keyword(ref k) = tok.kind {
                 if k == kw {
                     return Ok(());
                 }
             }
-            Err(PawError::Syntax {
+            Err(Box::new(PawError::Syntax {
                 file: self.file.clone(),
                 code: "E1001",
                 message: format!("Expected keyword '{}', got {:?}", kw, tok.kind),
@@ -106,26 +135,18 @@ impl Parser {
                 column: tok.column,
                 snippet: self.snippet(tok.line),
                 hint: Some("Check keyword".into()),
-            })
+            }))
         } else {
-            Err(PawError::Syntax {
-                file: self.file.clone(),
-                code: "E1001",
-                message: "Unexpected EOF".into(),
-                line: 0,
-                column: 0,
-                snippet: None,
-                hint: None,
-            })
+            Err(self.eof_error())
         }
     }
 
-    fn expect_identifier(&mut self) -> Result<String, PawError> {
+    fn expect_identifier(&mut self) -> Result<String, Box<PawError>> {
         if let Some(tok) = self.next() {
             if let TokenKind::Identifier(name) = tok.kind {
                 Ok(name)
             } else {
-                Err(PawError::Syntax {
+                Err(Box::new(PawError::Syntax {
                     file: self.file.clone(),
                     code: "E1001",
                     message: format!("Expected identifier, got {:?}", tok.kind),
@@ -133,35 +154,297 @@ impl Parser {
                     column: tok.column,
                     snippet: self.snippet(tok.line),
                     hint: None,
-                })
+                }))
             }
         } else {
-            Err(PawError::Syntax {
+            Err(self.eof_error())
+        }
+    }
+
+    /// 读取一个字符串字面量（ask 的提示语），`what` 说明它出现的位置
+    fn expect_string_literal(&mut self, what: &str) -> Result<String, Box<PawError>> {
+        let (line, column) = self.wrap_position();
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::StringLiteral(s),
+                ..
+            }) => Ok(s),
+            tok => Err(Box::new(PawError::Syntax {
                 file: self.file.clone(),
                 code: "E1001",
-                message: "Unexpected EOF".into(),
-                line: 0,
-                column: 0,
-                snippet: None,
+                message: format!("Expected string literal {}, got {:?}", what, tok.map(|t| t.kind)),
+                line,
+                column,
+                snippet: self.snippet(line),
                 hint: None,
-            })
+            })),
         }
     }
 
+    /// 进入一层嵌套，超过上限时报语法错误而不是栈溢出
+    fn enter_nesting(&mut self) -> Result<(), Box<PawError>> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING_DEPTH {
+            let (line, column) = self.wrap_position();
+            return Err(Box::new(PawError::Syntax {
+                file: self.file.clone(),
+                code: "E1004",
+                message: format!("Nesting deeper than {} levels", MAX_NESTING_DEPTH),
+                line,
+                column,
+                snippet: self.snippet(line),
+                hint: Some("Split deeply nested code into smaller pieces".into()),
+            }));
+        }
+        Ok(())
+    }
+
+    /// 词法阶段产生的 Error token 统一转成语法错误
+    fn check_lex_errors(&self) -> Result<(), Box<PawError>> {
+        for tok in &self.tokens {
+            if let TokenKind::Error(msg) = &tok.kind {
+                return Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1003",
+                    message: msg.clone(),
+                    line: tok.line,
+                    column: tok.column,
+                    snippet: self.snippet(tok.line),
+                    hint: Some("Check for stray characters or unterminated literals".into()),
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// 把插值字符串的各部分解析成表达式：`${...}` 中的源码单独词法分析后，
+    /// 临时替换 token 流交给 parse_expr，位置信息换算回外层源码；
+    /// 带格式说明的部分包成 Formatted
+    fn parse_interpolation(
+        &mut self,
+        parts: Vec<StringPart>,
+        line: usize,
+        col: usize,
+    ) -> Result<Expr, Box<PawError>> {
+        let mut exprs = Vec::new();
+        for part in parts {
+            match part {
+                StringPart::Text(s) => exprs.push(Expr::new(ExprKind::LiteralString(s), line, col)),
+                StringPart::Code {
+                    src,
+                    spec,
+                    line: code_line,
+                    column: code_col,
+                } => {
+                    let tokens = Lexer::new(&src)
+                        .tokenize()
+                        .into_iter()
+                        .map(|mut tok| {
+                            if tok.line == 1 {
+                                tok.column += code_col - 1;
+                            }
+                            tok.line += code_line - 1;
+                            tok
+                        })
+                        .collect();
+                    let saved_tokens = std::mem::replace(&mut self.tokens, tokens);
+                    let saved_pos = std::mem::replace(&mut self.position, 0);
+                    let res = self.check_lex_errors().and_then(|_| {
+                        let e = self.parse_expr()?;
+                        match self.peek() {
+                            Some(tok) if tok.kind != TokenKind::Eof => Err(Box::new(PawError::Syntax {
+                                file: self.file.clone(),
+                                code: "E1001",
+                                message: format!("Unexpected {:?} in string interpolation", tok.kind),
+                                line: tok.line,
+                                column: tok.column,
+                                snippet: self.snippet(tok.line),
+                                hint: Some("`${...}` must contain a single expression".into()),
+                            })),
+                            _ => Ok(e),
+                        }
+                    });
+                    self.tokens = saved_tokens;
+                    self.position = saved_pos;
+                    let e = res?;
+                    match spec {
+                        Some(spec) => {
+                            let spec = FormatSpec::parse(&spec).map_err(|message| PawError::Syntax {
+                                file: self.file.clone(),
+                                code: "E1001",
+                                message,
+                                line: code_line,
+                                column: code_col,
+                                snippet: self.snippet(code_line),
+                                hint: Some("Format specs look like `${x:08.3}`: [[fill]align][+][0][width][.precision]".into()),
+                            })?;
+                            let (line, col) = (e.line, e.col);
+                            exprs.push(Expr::new(ExprKind::Formatted { expr: Box::new(e), spec }, line, col));
+                        }
+                        None => exprs.push(e),
+                    }
+                }
+            }
+        }
+        Ok(Expr::new(ExprKind::Interpolation(exprs), line, col))
+    }
+
     // --- Top-level parse ---
-    pub fn parse_program(&mut self) -> Result<Vec<Statement>, PawError> {
+    pub fn parse_program(&mut self) -> Result<Vec<Statement>, Box<PawError>> {
+        self.check_lex_errors()?;
         let mut stmts = Vec::new();
         while !matches!(self.peek_kind(), Some(TokenKind::Eof)) {
-            stmts.push(self.parse_statement()?);
+            stmts.extend(self.parse_item()?);
         }
         Ok(stmts)
     }
 
-    pub fn parse_statement(&mut self) -> Result<Statement, PawError> {
-        while matches!(
-            self.peek_kind(),
-            Some(TokenKind::Comment(_)) | Some(TokenKind::Error(_))
-        ) {
+    /// 解析单个表达式，其后只能有注释，供调试器的监视表达式等求值接口使用
+    pub fn parse_expression(&mut self) -> Result<Expr, Box<PawError>> {
+        self.check_lex_errors()?;
+        let expr = self.parse_expr()?;
+        while matches!(self.peek_kind(), Some(TokenKind::Comment(_))) {
+            self.next();
+        }
+        match self.peek() {
+            Some(tok) if tok.kind != TokenKind::Eof => Err(Box::new(PawError::Syntax {
+                file: self.file.clone(),
+                code: "E1001",
+                message: format!("Unexpected {:?} after the expression", tok.kind),
+                line: tok.line,
+                column: tok.column,
+                snippet: self.snippet(tok.line),
+                hint: Some("Only a single expression can be evaluated here".into()),
+            })),
+            _ => Ok(expr),
+        }
+    }
+
+    /// 代码块或顶层中的一条语句，可以带 `@cfg(...)`，条件不成立时解析后丢弃。
+    /// `@cfg(...) { ... }` 一次标注多条语句，它们直接并入所在的代码块，不开启新的作用域；
+    /// 其它属性（如 `@deprecated`）附在每条标注的声明上
+    fn parse_item(&mut self) -> Result<Vec<Statement>, Box<PawError>> {
+        while matches!(self.peek_kind(), Some(TokenKind::Comment(_))) {
+            self.next();
+        }
+        if !self.peek_token(TokenKind::At) {
+            return Ok(vec![self.parse_statement()?]);
+        }
+        let mut keep = true;
+        let mut attributes = Vec::new();
+        while self.peek_token(TokenKind::At) {
+            self.next();
+            let (line, column) = self.wrap_position();
+            let name = self.expect_identifier()?;
+            match name.as_str() {
+                "cfg" => {
+                    self.expect_token(TokenKind::LParen)?;
+                    keep &= self.parse_cfg_predicate()?;
+                    self.expect_token(TokenKind::RParen)?;
+                }
+                "deprecated" => {
+                    // 说明可以省略：`@deprecated` 或 `@deprecated("use bar instead")`
+                    let mut note = None;
+                    if self.peek_token(TokenKind::LParen) {
+                        self.next();
+                        note = Some(self.expect_string_literal("as the `@deprecated` note")?);
+                        self.expect_token(TokenKind::RParen)?;
+                    }
+                    attributes.push((Attribute::Deprecated(note), line, column));
+                }
+                "pure" => attributes.push((Attribute::Pure, line, column)),
+                _ => {
+                    return Err(Box::new(PawError::Syntax {
+                        file: self.file.clone(),
+                        code: "E1001",
+                        message: format!("Unknown attribute `@{}`", name),
+                        line,
+                        column,
+                        snippet: self.snippet(line),
+                        hint: Some("Supported attributes: `@cfg(\"flag\")`, `@deprecated(\"note\")`, `@pure`".into()),
+                    }));
+                }
+            }
+            while matches!(self.peek_kind(), Some(TokenKind::Comment(_))) {
+                self.next();
+            }
+        }
+        let mut stmts = if self.peek_token(TokenKind::LBrace) {
+            self.parse_block()?
+        } else {
+            vec![self.parse_statement()?]
+        };
+        for stmt in &mut stmts {
+            for (attr, line, column) in &attributes {
+                if *attr == Attribute::Pure && !matches!(stmt.kind, StatementKind::FunDecl { .. }) {
+                    return Err(Box::new(PawError::Syntax {
+                        file: self.file.clone(),
+                        code: "E1001",
+                        message: "`@pure` can only mark a function".into(),
+                        line: *line,
+                        column: *column,
+                        snippet: self.snippet(*line),
+                        hint: Some("Put it before a `fun` declaration".into()),
+                    }));
+                }
+                if stmt.declared_name().is_none() {
+                    return Err(Box::new(PawError::Syntax {
+                        file: self.file.clone(),
+                        code: "E1001",
+                        message: "`@deprecated` can only mark a declaration".into(),
+                        line: *line,
+                        column: *column,
+                        snippet: self.snippet(*line),
+                        hint: Some("Put it before a `fun`, `let`, `record`, `enum` or `protocol` declaration".into()),
+                    }));
+                }
+                stmt.attributes.push(attr.clone());
+            }
+        }
+        Ok(if keep { stmts } else { Vec::new() })
+    }
+
+    /// `@cfg` 的条件：开关名字符串，可用 `!`、`&&`、`||` 与括号组合
+    fn parse_cfg_predicate(&mut self) -> Result<bool, Box<PawError>> {
+        let mut any = self.parse_cfg_all()?;
+        while self.peek_token(TokenKind::OrOr) {
+            self.next();
+            any |= self.parse_cfg_all()?;
+        }
+        Ok(any)
+    }
+
+    fn parse_cfg_all(&mut self) -> Result<bool, Box<PawError>> {
+        let mut all = self.parse_cfg_atom()?;
+        while self.peek_token(TokenKind::AndAnd) {
+            self.next();
+            all &= self.parse_cfg_atom()?;
+        }
+        Ok(all)
+    }
+
+    fn parse_cfg_atom(&mut self) -> Result<bool, Box<PawError>> {
+        self.enter_nesting()?;
+        let result = match self.peek_kind() {
+            Some(TokenKind::Not) => {
+                self.next();
+                self.parse_cfg_atom().map(|on| !on)
+            }
+            Some(TokenKind::LParen) => {
+                self.next();
+                let on = self.parse_cfg_predicate();
+                on.and_then(|on| self.expect_token(TokenKind::RParen).map(|_| on))
+            }
+            _ => self
+                .expect_string_literal("as a `@cfg` flag")
+                .map(|flag| self.cfg.is_enabled(&flag)),
+        };
+        self.depth -= 1;
+        result
+    }
+
+    pub fn parse_statement(&mut self) -> Result<Statement, Box<PawError>> {
+        while matches!(self.peek_kind(), Some(TokenKind::Comment(_))) {
             self.next();
         }
         let (line, col) = self.wrap_position();
@@ -169,10 +452,20 @@ impl Parser {
         if self.peek_keyword("record") {
             return self.parse_record_decl();
         }
-        if self.peek_keyword("async") {
+        if self.peek_keyword("enum") {
+            return self.parse_enum_decl();
+        }
+        if self.peek_keyword("protocol") {
+            return self.parse_protocol_decl();
+        }
+        // `fun(` / `async fun(` 开头的是匿名函数表达式，交给下面的表达式语句处理
+        if self.peek_keyword("async")
+            && matches!(self.peek_n_kind(1), Some(TokenKind::Keyword(k)) if k == "fun")
+            && self.peek_n_kind(2) != Some(&TokenKind::LParen)
+        {
             return self.parse_fun_statement(true);
         }
-        if self.peek_keyword("fun") {
+        if self.peek_keyword("fun") && self.peek_n_kind(1) != Some(&TokenKind::LParen) {
             return self.parse_fun_statement(false);
         }
         if self.peek_keyword("let") {
@@ -186,6 +479,11 @@ impl Parser {
         if self.peek_keyword("say") {
             return self.parse_say_statement();
         }
+        if self.peek_keyword("say_debug") {
+            self.next();
+            let expr = self.parse_expr()?;
+            return Ok(Statement::new(StatementKind::SayDebug(expr), line, col));
+        }
         if self.peek_keyword("ask") {
             return self.parse_ask_prompt_statement();
         }
@@ -207,45 +505,97 @@ impl Parser {
         if self.peek_keyword("sniff") {
             return self.parse_try_catch_finally();
         }
+        if self.peek_keyword("match") {
+            return self.parse_match_statement();
+        }
+        if self.peek_keyword("task_group") {
+            self.next();
+            let body = self.parse_block()?;
+            return Ok(Statement::new(StatementKind::TaskGroup(body), line, col));
+        }
+        if self.peek_keyword("spawn") {
+            self.next();
+            let expr = self.parse_expr()?;
+            return Ok(Statement::new(StatementKind::Spawn(expr), line, col));
+        }
         if self.peek_keyword("break") {
             self.next();
-            return Ok(Statement::new(StatementKind::Break, line, col));
+            let label = self.loop_label(line);
+            return Ok(Statement::new(StatementKind::Break(label), line, col));
         }
         if self.peek_keyword("continue") {
             self.next();
-            return Ok(Statement::new(StatementKind::Continue, line, col));
+            let label = self.loop_label(line);
+            return Ok(Statement::new(StatementKind::Continue(label), line, col));
         }
 
         let expr = self.parse_expr()?;
+        // 复合左值赋值：a[i] = v / p.x = v
+        if self.peek_token(TokenKind::Assign) {
+            self.next();
+            let value = self.parse_expr()?;
+            return match expr.kind {
+                ExprKind::Index { array, index } => Ok(Statement::new(
+                    StatementKind::AssignIndex {
+                        array: *array,
+                        index: *index,
+                        value,
+                    },
+                    line,
+                    col,
+                )),
+                ExprKind::FieldAccess { expr: object, field } => Ok(Statement::new(
+                    StatementKind::AssignField {
+                        object: *object,
+                        field,
+                        value,
+                    },
+                    line,
+                    col,
+                )),
+                _ => Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1001",
+                    message: "Invalid assignment target".into(),
+                    line,
+                    column: col,
+                    snippet: self.snippet(line),
+                    hint: Some("Only variables, indexed elements and record fields can be assigned".into()),
+                })),
+            };
+        }
         Ok(Statement::new(StatementKind::Expr(expr), line, col))
     }
 
     // 以下方法补全于 `impl Parser` 中
 
-    /// 解析 `fun` 或 `async fun` 声明
-    fn parse_fun_statement(&mut self, is_async: bool) -> Result<Statement, PawError> {
+    /// 解析 `fun` 或 `async fun` 声明，签名后可以带 `barks`
+    fn parse_fun_statement(&mut self, is_async: bool) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         if is_async {
             self.expect_keyword("async")?;
         }
         self.expect_keyword("fun")?;
         let name = self.expect_identifier()?;
+        let type_params = self.parse_type_params()?;
         self.expect_token(TokenKind::LParen)?;
         let params = self.parse_params()?;
         self.expect_token(TokenKind::RParen)?;
-        let return_type = if self.peek_token(TokenKind::Colon) {
+        let return_type = self.parse_return_type()?;
+        // `barks` 不是关键字，只在签名之后、函数体之前有意义
+        let barks = matches!(self.peek_kind(), Some(TokenKind::Identifier(word)) if word == "barks");
+        if barks {
             self.next();
-            Some(self.parse_type()?)
-        } else {
-            None
-        };
+        }
         let body = self.parse_block()?;
         Ok(Statement::new(
             StatementKind::FunDecl {
                 name,
+                type_params,
                 params,
                 return_type,
                 is_async,
+                barks,
                 body,
             },
             line,
@@ -253,14 +603,49 @@ impl Parser {
         ))
     }
 
-    /// 解析 `record Name { field: Type, ... }` 声明
-    fn parse_record_decl(&mut self) -> Result<Statement, PawError> {
+    /// 解析 `record Name<T>: P1, P2 { field: Type, ... fun method(...) { ... } }` 声明，类型参数与
+    /// protocol 列表可省略；方法的参数表前插入 `self: Name`，泛型 record 为 `self: Name<T>`
+    fn parse_record_decl(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("record")?;
         let name = self.expect_identifier()?;
+        let type_params = self.parse_type_params()?;
+        let mut protocols = Vec::new();
+        if self.peek_token(TokenKind::Colon) {
+            self.next();
+            loop {
+                protocols.push(self.expect_identifier()?);
+                if !self.peek_token(TokenKind::Comma) {
+                    break;
+                }
+                self.next();
+            }
+        }
         self.expect_token(TokenKind::LBrace)?;
         let mut fields = Vec::new();
+        let mut methods = Vec::new();
         while !self.peek_token(TokenKind::RBrace) {
+            if self.peek_keyword("fun") || self.peek_keyword("async") {
+                let is_async = self.peek_keyword("async");
+                let mut method = self.parse_fun_statement(is_async)?;
+                if let StatementKind::FunDecl { params, .. } = &mut method.kind {
+                    self.reject_defaults(params, "record methods")?;
+                    let receiver = match type_params.is_empty() {
+                        true => TypeNode::name(&name, method.line, method.col),
+                        false => {
+                            let args = type_params
+                                .iter()
+                                .map(|t| TypeNode::name(t, method.line, method.col))
+                                .collect();
+                            let kind = TypeNodeKind::Generic { name: name.clone(), args };
+                            TypeNode::new(kind, method.line, method.col)
+                        }
+                    };
+                    params.insert(0, Param::new("self".into(), receiver, method.line, method.col));
+                }
+                methods.push(method);
+                continue;
+            }
             let field_name = self.expect_identifier()?;
             self.expect_token(TokenKind::Colon)?;
             let ty = self.parse_type()?;
@@ -271,40 +656,153 @@ impl Parser {
         }
         self.expect_token(TokenKind::RBrace)?;
         Ok(Statement::new(
-            StatementKind::RecordDecl { name, fields },
+            StatementKind::RecordDecl {
+                name,
+                type_params,
+                fields,
+                protocols,
+                methods,
+            },
             line,
             col,
         ))
     }
 
-    /// 解析 `let` 或 `let ... <- ask "..."` 语句
-    fn parse_let_statement(&mut self) -> Result<Statement, PawError> {
+    /// 解析 `enum Name { A(T1, T2), B }` 声明；变体之间的逗号可以省略
+    fn parse_enum_decl(&mut self) -> Result<Statement, Box<PawError>> {
+        let (line, col) = self.wrap_position();
+        self.expect_keyword("enum")?;
+        let name = self.expect_identifier()?;
+        self.expect_token(TokenKind::LBrace)?;
+        let mut variants = Vec::new();
+        while !self.peek_token(TokenKind::RBrace) {
+            let (vline, vcol) = self.wrap_position();
+            let variant = self.expect_identifier()?;
+            let mut payload = Vec::new();
+            if self.peek_token(TokenKind::LParen) {
+                self.next();
+                while !self.peek_token(TokenKind::RParen) {
+                    payload.push(self.parse_type()?);
+                    if !self.peek_token(TokenKind::Comma) {
+                        break;
+                    }
+                    self.next();
+                }
+                self.expect_token(TokenKind::RParen)?;
+            }
+            variants.push(EnumVariant {
+                name: variant,
+                payload,
+                line: vline,
+                col: vcol,
+            });
+            if self.peek_token(TokenKind::Comma) {
+                self.next();
+            }
+        }
+        self.expect_token(TokenKind::RBrace)?;
+        Ok(Statement::new(StatementKind::EnumDecl { name, variants }, line, col))
+    }
+
+    /// 解析 `protocol Name { field: Type  fun method(a: T): R }` 声明；
+    /// 方法要求转成同名的函数类型字段
+    fn parse_protocol_decl(&mut self) -> Result<Statement, Box<PawError>> {
+        let (line, col) = self.wrap_position();
+        self.expect_keyword("protocol")?;
+        let name = self.expect_identifier()?;
+        self.expect_token(TokenKind::LBrace)?;
+        let mut members = Vec::new();
+        while !self.peek_token(TokenKind::RBrace) {
+            let (m_line, m_col) = self.wrap_position();
+            if self.peek_keyword("fun") {
+                self.next();
+                let method = self.expect_identifier()?;
+                self.expect_token(TokenKind::LParen)?;
+                let params = self.parse_params()?;
+                self.reject_defaults(&params, "protocol members")?;
+                self.expect_token(TokenKind::RParen)?;
+                let ret = self
+                    .parse_return_type()?
+                    .unwrap_or_else(|| TypeNode::name("Void", m_line, m_col));
+                let ty = TypeNode::new(
+                    TypeNodeKind::Function {
+                        params: params.into_iter().map(|p| p.ty).collect(),
+                        ret: Box::new(ret),
+                    },
+                    m_line,
+                    m_col,
+                );
+                members.push(Param::new(method, ty, m_line, m_col));
+            } else {
+                let field = self.expect_identifier()?;
+                self.expect_token(TokenKind::Colon)?;
+                let ty = self.parse_type()?;
+                members.push(Param::new(field, ty, m_line, m_col));
+            }
+            if self.peek_token(TokenKind::Comma) {
+                self.next();
+            }
+        }
+        self.expect_token(TokenKind::RBrace)?;
+        Ok(Statement::new(
+            StatementKind::ProtocolDecl { name, members },
+            line,
+            col,
+        ))
+    }
+
+    /// 解析 `let`、`let (a, b) = ...` 或 `let ... <- ask "..."` 语句
+    fn parse_let_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("let")?;
+        // 解构元组：let (a, b) = expr，类型注解可省略
+        if self.peek_token(TokenKind::LParen) {
+            self.next();
+            let mut names = vec![self.expect_identifier()?];
+            while self.peek_token(TokenKind::Comma) {
+                self.next();
+                names.push(self.expect_identifier()?);
+            }
+            self.expect_token(TokenKind::RParen)?;
+            let ty = self.parse_return_type()?;
+            self.expect_token(TokenKind::Assign)?;
+            let value = self.parse_expr()?;
+            return Ok(Statement::new(
+                StatementKind::LetTuple { names, ty, value },
+                line,
+                col,
+            ));
+        }
         let name = self.expect_identifier()?;
-        self.expect_token(TokenKind::Colon)?;
+        // 省略类型注解时只能是 `let x = value`；ask 按声明的类型转换输入，不能省略
+        if !self.peek_token(TokenKind::Colon) {
+            if self.peek_token(TokenKind::LeftArrow) {
+                let (line, col) = self.wrap_position();
+                return Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1001",
+                    message: format!("`ask` needs a declared type for '{}'", name),
+                    line,
+                    column: col,
+                    snippet: self.snippet(line),
+                    hint: Some(format!("Write `let {}: String <- ask ...`", name)),
+                }));
+            }
+            self.expect_token(TokenKind::Assign)?;
+            let value = self.parse_expr()?;
+            return Ok(Statement::new(
+                StatementKind::Let { name, ty: None, value },
+                line,
+                col,
+            ));
+        }
+        self.next();
         let ty = self.parse_type()?;
         // 支持 ask 初始化
         if self.peek_token(TokenKind::LeftArrow) {
             self.next();
             self.expect_keyword("ask")?;
-            let prompt = match self.next() {
-                Some(Token {
-                    kind: TokenKind::StringLiteral(s),
-                    ..
-                }) => s,
-                tok => {
-                    return Err(PawError::Syntax {
-                        file: self.file.clone(),
-                        code: "E1001",
-                        message: format!("Expected string literal after ask, got {:?}", tok),
-                        line,
-                        column: col,
-                        snippet: None,
-                        hint: None,
-                    })
-                }
-            };
+            let prompt = self.expect_string_literal("after ask")?;
             return Ok(Statement::new(
                 StatementKind::Ask { name, ty, prompt },
                 line,
@@ -315,14 +813,14 @@ impl Parser {
         self.expect_token(TokenKind::Assign)?;
         let value = self.parse_expr()?;
         Ok(Statement::new(
-            StatementKind::Let { name, ty, value },
+            StatementKind::Let { name, ty: Some(ty), value },
             line,
             col,
         ))
     }
 
     /// 解析赋值语句 `x = expr`
-    fn parse_assign_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_assign_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         let name = self.expect_identifier()?;
         self.expect_token(TokenKind::Assign)?;
@@ -335,7 +833,7 @@ impl Parser {
     }
 
     /// 解析 `say expr` 语句
-    fn parse_say_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_say_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("say")?;
         let expr = self.parse_expr()?;
@@ -343,31 +841,15 @@ impl Parser {
     }
 
     /// 解析 `ask "..."` 提示语句
-    fn parse_ask_prompt_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_ask_prompt_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("ask")?;
-        let prompt = match self.next() {
-            Some(Token {
-                kind: TokenKind::StringLiteral(s),
-                ..
-            }) => s,
-            tok => {
-                return Err(PawError::Syntax {
-                    file: self.file.clone(),
-                    code: "E1001",
-                    message: format!("Expected string literal in ask, got {:?}", tok),
-                    line,
-                    column: col,
-                    snippet: None,
-                    hint: None,
-                })
-            }
-        };
+        let prompt = self.expect_string_literal("in ask")?;
         Ok(Statement::new(StatementKind::AskPrompt(prompt), line, col))
     }
 
     /// 解析 `import a.b.c as d` 语句
-    fn parse_import_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_import_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("import")?;
         let mut module = Vec::new();
@@ -382,7 +864,20 @@ impl Parser {
             self.next();
             self.expect_identifier()?
         } else {
-            module.last().cloned().unwrap()
+            match module.last() {
+                Some(last) => last.clone(),
+                None => {
+                    return Err(Box::new(PawError::Syntax {
+                        file: self.file.clone(),
+                        code: "E1001",
+                        message: "Import path is empty".into(),
+                        line,
+                        column: col,
+                        snippet: self.snippet(line),
+                        hint: Some("Use: import a.b.c [as name]".into()),
+                    }))
+                }
+            }
         };
         Ok(Statement::new(
             StatementKind::Import { module, alias },
@@ -392,7 +887,7 @@ impl Parser {
     }
 
     /// 解析 `return [expr]` 语句
-    fn parse_return_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_return_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("return")?;
         let expr = if !self.peek_token(TokenKind::RBrace) {
@@ -404,7 +899,7 @@ impl Parser {
     }
 
     /// 解析 `bark expr` 异常抛出
-    fn parse_throw(&mut self) -> Result<Statement, PawError> {
+    fn parse_throw(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("bark")?;
         let expr = self.parse_expr()?;
@@ -412,7 +907,7 @@ impl Parser {
     }
 
     /// 解析 `if cond { ... } [else ...]`
-    fn parse_if_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_if_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("if")?;
         let condition = self.parse_expr()?;
@@ -443,14 +938,84 @@ impl Parser {
     }
 
     /// 解析各种 loop：forever / range / while
-    fn parse_loop_statement(&mut self) -> Result<Statement, PawError> {
+    fn parse_loop_statement(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("loop")?;
+        // 可选的标签 `loop outer: ...`
+        let label = match (self.peek_kind(), self.peek_n_kind(1)) {
+            (Some(TokenKind::Identifier(name)), Some(TokenKind::Colon)) => {
+                let name = name.clone();
+                self.position += 2;
+                Some(name)
+            }
+            _ => None,
+        };
         // forever
         if self.peek_keyword("forever") {
             self.next();
             let body = self.parse_block()?;
-            return Ok(Statement::new(StatementKind::LoopForever(body), line, col));
+            return Ok(Statement::new(StatementKind::LoopForever { label, body }, line, col));
+        }
+        // `loop { ... } while cond`：`while` 必须与 `}` 在同一行
+        if self.peek_token(TokenKind::LBrace) {
+            let body = self.parse_block()?;
+            let close_line = self.tokens[self.position - 1].line;
+            match self.peek() {
+                Some(Token {
+                    kind: TokenKind::Identifier(word),
+                    line: word_line,
+                    ..
+                }) if word == "while" && *word_line == close_line => {
+                    self.next();
+                }
+                _ => {
+                    let (line, column) = self.wrap_position();
+                    return Err(Box::new(PawError::Syntax {
+                        file: self.file.clone(),
+                        code: "E1001",
+                        message: "Expected `while` after the loop body".into(),
+                        line,
+                        column,
+                        snippet: self.snippet(line),
+                        hint: Some(
+                            "Write `loop { ... } while cond` on the line of the closing `}`, or `loop forever { ... }`"
+                                .into(),
+                        ),
+                    }));
+                }
+            }
+            let condition = self.parse_expr()?;
+            return Ok(Statement::new(
+                StatementKind::LoopDoWhile {
+                    label,
+                    body,
+                    condition,
+                },
+                line,
+                col,
+            ));
+        }
+        // `loop (k, v) in m`：解构每个元素
+        if let Some(names) = self.peek_loop_tuple() {
+            // 跳过 `(`、名字与逗号、`)` 以及 `in`
+            self.position += names.len() * 2 + 2;
+            let array = self.parse_expr()?;
+            let body = self.parse_block()?;
+            // 与表达式一致，`(x)` 只是加了括号的 x
+            let var = match <[String; 1]>::try_from(names) {
+                Ok([name]) => LoopVar::Name(name),
+                Err(names) => LoopVar::Tuple(names),
+            };
+            return Ok(Statement::new(
+                StatementKind::LoopArray {
+                    label,
+                    var,
+                    array,
+                    body,
+                },
+                line,
+                col,
+            ));
         }
         // —— range-loop 或 array-loop 都是 “ident in …” 开头 ——
         if let Some(TokenKind::Identifier(var)) = self.peek_kind().cloned() {
@@ -459,19 +1024,24 @@ impl Parser {
                 self.next(); // 消耗变量名
                 self.next(); // 消耗 `in`
 
-                // 先 parse_expr 拿到第一个 Expr，既可能是 range 的 start，也可能是 array 本身
-                let first = self.parse_expr()?;
-
-                // 如果紧接着是 `..`，就是 range-loop
-                if self.peek_token(TokenKind::Range) {
-                    self.next(); // consume `..`
-                    let end = self.parse_expr()?;
+                // 字面写出的范围是 range-loop，不必先构造范围值
+                let array = self.parse_expr()?;
+                if let ExprKind::Range {
+                    start,
+                    end,
+                    step,
+                    inclusive,
+                } = array.kind
+                {
                     let body = self.parse_block()?;
                     return Ok(Statement::new(
                         StatementKind::LoopRange {
+                            label,
                             var,
-                            start: first,
-                            end,
+                            start: *start,
+                            end: *end,
+                            step: step.map(|s| *s),
+                            inclusive,
                             body,
                         },
                         line,
@@ -479,11 +1049,15 @@ impl Parser {
                     ));
                 }
 
-                // 否则就是 array-loop
-                let array = first;
+                // 否则就是 array-loop（包括存放在变量中的范围值）
                 let body = self.parse_block()?;
                 return Ok(Statement::new(
-                    StatementKind::LoopArray { var, array, body },
+                    StatementKind::LoopArray {
+                        label,
+                        var: LoopVar::Name(var),
+                        array,
+                        body,
+                    },
                     line,
                     col,
                 ));
@@ -493,22 +1067,63 @@ impl Parser {
         let condition = self.parse_expr()?;
         let body = self.parse_block()?;
         Ok(Statement::new(
-            StatementKind::LoopWhile { condition, body },
+            StatementKind::LoopWhile {
+                label,
+                condition,
+                body,
+            },
             line,
             col,
         ))
     }
 
-    /// 解析 `sniff { ... } snatch(err) { ... } [lastly { ... }]`
-    fn parse_try_catch_finally(&mut self) -> Result<Statement, PawError> {
+    /// `break` / `continue` 之后同一行上的标签；换行后的名字属于下一条语句
+    fn loop_label(&mut self, line: usize) -> Option<String> {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Identifier(name),
+                line: label_line,
+                ..
+            }) if *label_line == line => {
+                let name = name.clone();
+                self.next();
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// 当前位置是否为 `(a, b, ...) in`；是则返回其中的名字，不移动位置。
+    /// 只看记号，因此 `loop (x > 0) { ... }` 仍按条件循环解析
+    fn peek_loop_tuple(&self) -> Option<Vec<String>> {
+        if !self.peek_token(TokenKind::LParen) {
+            return None;
+        }
+        let mut names = Vec::new();
+        let mut n = 1;
+        loop {
+            match self.peek_n_kind(n)? {
+                TokenKind::Identifier(name) => names.push(name.clone()),
+                _ => return None,
+            }
+            match self.peek_n_kind(n + 1)? {
+                TokenKind::Comma => n += 2,
+                TokenKind::RParen => break,
+                _ => return None,
+            }
+        }
+        matches!(self.peek_n_kind(n + 2), Some(TokenKind::Keyword(k)) if k == "in").then_some(names)
+    }
+
+    /// 解析 `sniff { ... } snatch(err[: Type]) { ... } ... [lastly { ... }]`，至少一个 snatch
+    fn parse_try_catch_finally(&mut self) -> Result<Statement, Box<PawError>> {
         let (line, col) = self.wrap_position();
         self.expect_keyword("sniff")?;
         let body = self.parse_block()?;
-        self.expect_keyword("snatch")?;
-        self.expect_token(TokenKind::LParen)?;
-        let err_name = self.expect_identifier()?;
-        self.expect_token(TokenKind::RParen)?;
-        let handler = self.parse_block()?;
+        let mut clauses = vec![self.parse_snatch_clause()?];
+        while self.peek_keyword("snatch") {
+            clauses.push(self.parse_snatch_clause()?);
+        }
         let finally = if self.peek_keyword("lastly") {
             self.next();
             self.parse_block()?
@@ -518,8 +1133,7 @@ impl Parser {
         Ok(Statement::new(
             StatementKind::TryCatchFinally {
                 body,
-                err_name,
-                handler,
+                clauses,
                 finally,
             },
             line,
@@ -527,8 +1141,204 @@ impl Parser {
         ))
     }
 
+    /// 解析 `snatch (err) { ... }` 或 `snatch (err: Type) { ... }`
+    fn parse_snatch_clause(&mut self) -> Result<SnatchClause, Box<PawError>> {
+        let (line, col) = self.wrap_position();
+        self.expect_keyword("snatch")?;
+        self.expect_token(TokenKind::LParen)?;
+        let name = self.expect_identifier()?;
+        let ty = if self.peek_token(TokenKind::Colon) {
+            self.next();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect_token(TokenKind::RParen)?;
+        let body = self.parse_block()?;
+        Ok(SnatchClause {
+            name,
+            ty,
+            body,
+            line,
+            col,
+        })
+    }
+
+    /// 解析 `match value { p1, p2 => { ... } ... }`，并为开头的字面量分支建立跳转表
+    fn parse_match_statement(&mut self) -> Result<Statement, Box<PawError>> {
+        let (line, col) = self.wrap_position();
+        self.expect_keyword("match")?;
+        let outer = std::mem::replace(&mut self.no_record_init, true);
+        let subject = self.parse_expr();
+        self.no_record_init = outer;
+        let subject = subject?;
+        self.expect_token(TokenKind::LBrace)?;
+        let mut arms = Vec::new();
+        while !self.peek_token(TokenKind::RBrace) {
+            let (line, col) = self.wrap_position();
+            let mut patterns = vec![self.parse_pattern()?];
+            while self.peek_token(TokenKind::Comma) {
+                self.next();
+                patterns.push(self.parse_pattern()?);
+            }
+            self.expect_token(TokenKind::FatArrow)?;
+            let body = self.parse_block()?;
+            arms.push(MatchArm {
+                patterns,
+                body,
+                line,
+                col,
+            });
+        }
+        self.expect_token(TokenKind::RBrace)?;
+        let table = MatchTable::new(&arms);
+        Ok(Statement::new(
+            StatementKind::Match {
+                subject,
+                arms,
+                table,
+            },
+            line,
+            col,
+        ))
+    }
+
+    /// 解析一个 match 模式
+    fn parse_pattern(&mut self) -> Result<Pattern, Box<PawError>> {
+        self.enter_nesting()?;
+        let result = self.parse_pattern_inner();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_pattern_inner(&mut self) -> Result<Pattern, Box<PawError>> {
+        let (line, col) = self.wrap_position();
+        let tok = self.next().ok_or_else(|| self.eof_error())?;
+        let literal = |kind| PatternKind::Literal(Expr::new(kind, line, col));
+        let kind = match tok.kind {
+            TokenKind::IntLiteral(n) => literal(ExprKind::LiteralInt(n)),
+            TokenKind::LongLiteral(n) => literal(ExprKind::LiteralLong(n)),
+            TokenKind::FloatLiteral(f) => literal(ExprKind::LiteralFloat(f)),
+            TokenKind::DoubleLiteral(f) => literal(ExprKind::LiteralDouble(f)),
+            TokenKind::StringLiteral(s) => literal(ExprKind::LiteralString(s)),
+            TokenKind::CharLiteral(c) => literal(ExprKind::LiteralChar(c)),
+            TokenKind::BoolLiteral(b) => literal(ExprKind::LiteralBool(b)),
+            TokenKind::Keyword(k) if k == "nopaw" => literal(ExprKind::LiteralNopaw),
+            // 负数字面量
+            TokenKind::Minus if matches!(
+                self.peek_kind(),
+                Some(
+                    TokenKind::IntLiteral(_)
+                        | TokenKind::LongLiteral(_)
+                        | TokenKind::FloatLiteral(_)
+                        | TokenKind::DoubleLiteral(_)
+                )
+            ) =>
+            {
+                literal(match self.next().map(|t| t.kind) {
+                    Some(TokenKind::IntLiteral(n)) => ExprKind::LiteralInt(n.wrapping_neg()),
+                    Some(TokenKind::LongLiteral(n)) => ExprKind::LiteralLong(n.wrapping_neg()),
+                    Some(TokenKind::FloatLiteral(f)) => ExprKind::LiteralFloat(-f),
+                    Some(TokenKind::DoubleLiteral(f)) => ExprKind::LiteralDouble(-f),
+                    _ => unreachable!("checked by peek"),
+                })
+            }
+            TokenKind::Identifier(name) if self.peek_token(TokenKind::Colon) => {
+                self.next();
+                PatternKind::Typed {
+                    name,
+                    ty: self.parse_type()?,
+                }
+            }
+            TokenKind::Identifier(name) if self.peek_token(TokenKind::LBrace) => {
+                self.next();
+                let mut fields = Vec::new();
+                while !self.peek_token(TokenKind::RBrace) {
+                    let (fline, fcol) = self.wrap_position();
+                    let field = self.expect_identifier()?;
+                    let pattern = if self.peek_token(TokenKind::Colon) {
+                        self.next();
+                        self.parse_pattern()?
+                    } else {
+                        Pattern::new(PatternKind::Bind(field.clone()), fline, fcol)
+                    };
+                    fields.push((field, pattern));
+                    if self.peek_token(TokenKind::Comma) {
+                        self.next();
+                    }
+                }
+                self.expect_token(TokenKind::RBrace)?;
+                PatternKind::Record { name, fields }
+            }
+            // `Shape.Circle(p, ...)` / `Shape.Empty`：enum 变体
+            TokenKind::Identifier(enum_name) if self.peek_token(TokenKind::Dot) => {
+                self.next();
+                let variant = self.expect_identifier()?;
+                let args = if self.peek_token(TokenKind::LParen) {
+                    self.next();
+                    let mut args = Vec::new();
+                    while !self.peek_token(TokenKind::RParen) {
+                        args.push(self.parse_pattern()?);
+                        if !self.peek_token(TokenKind::Comma) {
+                            break;
+                        }
+                        self.next();
+                    }
+                    self.expect_token(TokenKind::RParen)?;
+                    Some(args)
+                } else {
+                    None
+                };
+                PatternKind::Variant {
+                    enum_name,
+                    variant,
+                    args,
+                }
+            }
+            TokenKind::Identifier(name) if name == "_" => PatternKind::Wildcard,
+            TokenKind::Identifier(name) => PatternKind::Bind(name),
+            // `(p1, p2)` 是元组模式，`(p)` 只是分组
+            TokenKind::LParen => {
+                let first = self.parse_pattern()?;
+                if !self.peek_token(TokenKind::Comma) {
+                    self.expect_token(TokenKind::RParen)?;
+                    return Ok(first);
+                }
+                let mut items = vec![first];
+                while self.peek_token(TokenKind::Comma) {
+                    self.next();
+                    items.push(self.parse_pattern()?);
+                }
+                self.expect_token(TokenKind::RParen)?;
+                PatternKind::Tuple(items)
+            }
+            other => {
+                return Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1001",
+                    message: format!("Expected a pattern, got {:?}", other),
+                    line,
+                    column: col,
+                    snippet: self.snippet(line),
+                    hint: Some(
+                        "Patterns are literals, `nopaw`, `_`, names, `name: Type`, `Record { field }` and tuples"
+                            .into(),
+                    ),
+                }));
+            }
+        };
+        Ok(Pattern::new(kind, line, col))
+    }
+
     /// 一元操作和 await
-    fn parse_unary_expr(&mut self) -> Result<Expr, PawError> {
+    fn parse_unary_expr(&mut self) -> Result<Expr, Box<PawError>> {
+        self.enter_nesting()?;
+        let result = self.parse_unary_operand();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_unary_operand(&mut self) -> Result<Expr, Box<PawError>> {
         let (line, col) = self.wrap_position();
 
         // await e
@@ -544,10 +1354,23 @@ impl Parser {
             });
         }
 
-        // -e
+        // tail e
+        if self.peek_keyword("tail") {
+            self.next();
+            let inner = self.parse_unary_expr()?;
+            return Ok(Expr {
+                kind: ExprKind::Tail {
+                    expr: Box::new(inner),
+                },
+                line,
+                col,
+            });
+        }
+
+        // -e；乘方比负号结合得更紧：`-2 ** 2` 是 `-(2 ** 2)`
         if self.peek_token(TokenKind::Minus) {
             self.next();
-            let e = self.parse_unary_expr()?;
+            let e = self.parse_binary_expr(POW_PREC)?;
             return Ok(Expr {
                 kind: ExprKind::UnaryOp {
                     op: "-".into(),
@@ -577,9 +1400,44 @@ impl Parser {
     }
 
     /// 字面量、变量、调用、索引、RecordInit、属性访问…
-    fn parse_primary(&mut self) -> Result<Expr, PawError> {
+    fn parse_primary(&mut self) -> Result<Expr, Box<PawError>> {
         let (line, col) = self.wrap_position();
         
+        // async { ... } 代码块
+        if self.peek_keyword("async") && self.peek_n_kind(1) == Some(&TokenKind::LBrace) {
+            self.next();
+            let body = self.parse_block()?;
+            return Ok(Expr::new(ExprKind::AsyncBlock(body), line, col));
+        }
+
+        // 匿名函数：fun(params): Ret { ... } / async fun(params) { ... }
+        if self.peek_keyword("fun")
+            || (self.peek_keyword("async")
+                && matches!(self.peek_n_kind(1), Some(TokenKind::Keyword(k)) if k == "fun"))
+        {
+            let is_async = self.peek_keyword("async");
+            if is_async {
+                self.next();
+            }
+            self.expect_keyword("fun")?;
+            self.expect_token(TokenKind::LParen)?;
+            let params = self.parse_params()?;
+            self.reject_defaults(&params, "lambdas")?;
+            self.expect_token(TokenKind::RParen)?;
+            let return_type = self.parse_return_type()?;
+            let body = self.parse_block()?;
+            return Ok(Expr::new(
+                ExprKind::Lambda {
+                    params,
+                    return_type,
+                    is_async,
+                    body,
+                },
+                line,
+                col,
+            ));
+        }
+
         // nopaw 字面量
         if self.peek_keyword("nopaw") {
             self.next();
@@ -607,14 +1465,17 @@ impl Parser {
             TokenKind::FloatLiteral(f) => Expr::new(ExprKind::LiteralFloat(f), line, col),
             TokenKind::DoubleLiteral(f) => Expr::new(ExprKind::LiteralDouble(f), line, col),
             TokenKind::StringLiteral(s) => Expr::new(ExprKind::LiteralString(s), line, col),
+            TokenKind::InterpolatedString(parts) => self.parse_interpolation(parts, line, col)?,
             TokenKind::CharLiteral(c) => Expr::new(ExprKind::LiteralChar(c), line, col),
             TokenKind::BoolLiteral(b) => Expr::new(ExprKind::LiteralBool(b), line, col),
 
             TokenKind::Identifier(name) => {
                 // 只有在紧跟 `{` 且 `{` 之后马上是字段名（Identifier）的情况下，
                 // 我们才把它当成 record initializer；否则让后续的 parse_block 去消费这个 `{`
-                if self.peek_token(TokenKind::LBrace)
+                if !self.no_record_init
+                    && self.peek_token(TokenKind::LBrace)
                     && matches!(self.peek_n_kind(1), Some(TokenKind::Identifier(_)))
+                    && self.peek_n_kind(2) == Some(&TokenKind::Colon)
                 {
                     // RecordInit
                     self.next(); // consume '{'
@@ -634,6 +1495,18 @@ impl Parser {
                         line,
                         col,
                     }
+                } else if name == "Set" && self.peek_token(TokenKind::LBrace) {
+                    // Set 字面量 Set {a, b, ...}，`Set {}` 为空集合
+                    self.next(); // consume '{'
+                    let mut items = Vec::new();
+                    while !self.peek_token(TokenKind::RBrace) {
+                        items.push(self.parse_expr()?);
+                        if self.peek_token(TokenKind::Comma) {
+                            self.next();
+                        }
+                    }
+                    self.expect_token(TokenKind::RBrace)?;
+                    Expr::new(ExprKind::SetLiteral(items), line, col)
                 } else {
                     Expr {
                         kind: ExprKind::Var(name),
@@ -645,8 +1518,24 @@ impl Parser {
 
             TokenKind::LParen => {
                 let e = self.parse_expr()?;
-                self.expect_token(TokenKind::RParen)?;
-                return Ok(e); // 早返回，避免吞掉后缀
+                // 带逗号的是元组字面量 (a, b, ...)，否则只是括号分组
+                if self.peek_token(TokenKind::Comma) {
+                    let mut items = vec![e];
+                    while self.peek_token(TokenKind::Comma) {
+                        self.next();
+                        items.push(self.parse_expr()?);
+                    }
+                    self.expect_token(TokenKind::RParen)?;
+                    Expr::new(ExprKind::TupleLiteral(items), line, col)
+                } else {
+                    self.expect_token(TokenKind::RParen)?;
+                    // 分组之后只接受 `?`，如 `(await load())?`
+                    if self.peek_token(TokenKind::Question) {
+                        self.next();
+                        return Ok(Expr::new(ExprKind::Propagate { expr: Box::new(e) }, line, col));
+                    }
+                    return Ok(e); // 早返回，避免吞掉后缀
+                }
             }
 
             TokenKind::LBracket => {
@@ -661,8 +1550,24 @@ impl Parser {
                 Expr::new(ExprKind::ArrayLiteral(elems), line, col)
             }
 
+            TokenKind::LBrace => {
+                // Map 字面量 {k: v, ...}
+                let mut entries = Vec::new();
+                while !self.peek_token(TokenKind::RBrace) {
+                    let key = self.parse_expr()?;
+                    self.expect_token(TokenKind::Colon)?;
+                    let value = self.parse_expr()?;
+                    entries.push((key, value));
+                    if self.peek_token(TokenKind::Comma) {
+                        self.next();
+                    }
+                }
+                self.expect_token(TokenKind::RBrace)?;
+                Expr::new(ExprKind::MapLiteral(entries), line, col)
+            }
+
             other => {
-                return Err(PawError::Syntax {
+                return Err(Box::new(PawError::Syntax {
                     file: self.file.clone(),
                     code: "E1001",
                     message: format!("Unexpected token in primary: {:?}", other),
@@ -670,11 +1575,11 @@ impl Parser {
                     column: col,
                     snippet: self.snippet(line),
                     hint: Some("Check expression syntax".into()),
-                });
+                }));
             }
         };
 
-        // —— 后缀循环：函数调用 / 索引 / 方法调用 ——
+        // —— 后缀循环：函数调用 / 索引 / 方法调用 / `?` ——
         loop {
             match self.peek_kind() {
                 // 普通函数调用 foo(...)
@@ -699,14 +1604,14 @@ impl Parser {
                         ExprKind::FieldAccess { expr: obj, field } => Expr {
                             kind: ExprKind::MethodCall {
                                 receiver: obj,
-                                method: self.parse_method(&*field),
+                                method: self.parse_method(&field),
                                 args,
                             },
                             line,
                             col,
                         },
                         _ => {
-                            return Err(PawError::Syntax {
+                            return Err(Box::new(PawError::Syntax {
                                 file: self.file.clone(),
                                 code: "E1001",
                                 message: "Invalid call target".into(),
@@ -714,7 +1619,7 @@ impl Parser {
                                 column: cc,
                                 snippet: self.snippet(cl),
                                 hint: None,
-                            });
+                            }));
                         }
                     };
                 }
@@ -744,6 +1649,16 @@ impl Parser {
                         col,
                     };
                 }
+                Some(TokenKind::Question) => {
+                    self.next();
+                    expr = Expr {
+                        kind: ExprKind::Propagate {
+                            expr: Box::new(expr),
+                        },
+                        line,
+                        col,
+                    };
+                }
                 _ => break,
             }
         }
@@ -752,12 +1667,19 @@ impl Parser {
     }
 
     /// parse `{ … }`，返回一组 Statement
-    fn parse_block(&mut self) -> Result<Vec<Statement>, PawError> {
+    fn parse_block(&mut self) -> Result<Vec<Statement>, Box<PawError>> {
+        self.enter_nesting()?;
+        let result = self.parse_block_body();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_block_body(&mut self) -> Result<Vec<Statement>, Box<PawError>> {
         // consume `{`
         self.expect_token(TokenKind::LBrace)?;
         let mut stmts = Vec::new();
         while !self.peek_token(TokenKind::RBrace) {
-            stmts.push(self.parse_statement()?);
+            stmts.extend(self.parse_item()?);
         }
         // consume `}`
         self.expect_token(TokenKind::RBrace)?;
@@ -765,8 +1687,62 @@ impl Parser {
     }
 
     /// parse 类型标注，比如 `Array<Int?>`
-    fn parse_type(&mut self) -> Result<String, PawError> {
-        let mut ty = match self.next() {
+    fn parse_type(&mut self) -> Result<TypeNode, Box<PawError>> {
+        self.enter_nesting()?;
+        let result = self.parse_type_inner();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_type_inner(&mut self) -> Result<TypeNode, Box<PawError>> {
+        let (line, col) = self.wrap_position();
+        // 函数类型：fun(A, B): R，省略返回类型时为 Void
+        if self.peek_keyword("fun") {
+            self.next();
+            self.expect_token(TokenKind::LParen)?;
+            let mut params = Vec::new();
+            while !self.peek_token(TokenKind::RParen) {
+                params.push(self.parse_type()?);
+                if !self.peek_token(TokenKind::Comma) {
+                    break;
+                }
+                self.next();
+            }
+            self.expect_token(TokenKind::RParen)?;
+            let ret = self.parse_return_type()?.unwrap_or_else(|| TypeNode::name("Void", line, col));
+            return Ok(TypeNode::new(
+                TypeNodeKind::Function {
+                    params,
+                    ret: Box::new(ret),
+                },
+                line,
+                col,
+            ));
+        }
+        // 元组类型 (A, B, ...)，至少两个元素
+        if self.peek_token(TokenKind::LParen) {
+            self.next();
+            let mut elems = vec![self.parse_type()?];
+            while self.peek_token(TokenKind::Comma) {
+                self.next();
+                elems.push(self.parse_type()?);
+            }
+            self.expect_token(TokenKind::RParen)?;
+            if elems.len() < 2 {
+                return Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1001",
+                    message: "A tuple type needs at least two element types".into(),
+                    line,
+                    column: col,
+                    snippet: self.snippet(line),
+                    hint: Some("Write the element type without parentheses".into()),
+                }));
+            }
+            let ty = TypeNode::new(TypeNodeKind::Tuple(elems), line, col);
+            return Ok(self.optional_suffix(ty));
+        }
+        let name = match self.next() {
             Some(Token {
                 kind: TokenKind::Type(s),
                 ..
@@ -776,38 +1752,94 @@ impl Parser {
                 ..
             }) => s,
             other => {
-                return Err(PawError::Syntax {
+                return Err(Box::new(PawError::Syntax {
                     file: self.file.clone(),
                     code: "E1001",
-                    message: format!("Expected type, got {:?}", other),
-                    line: 0,
-                    column: 0,
-                    snippet: None,
+                    message: format!("Expected type, got {:?}", other.map(|t| t.kind)),
+                    line,
+                    column: col,
+                    snippet: self.snippet(line),
                     hint: None,
-                })
+                }))
             }
         };
-        if self.peek_token(TokenKind::Lt) {
+        let ty = if self.peek_token(TokenKind::Lt) {
+            // 类型参数，如 Array<T>、Map<K, V>
             self.next();
-            let inner = self.parse_type()?;
+            let mut args = vec![self.parse_type()?];
+            while self.peek_token(TokenKind::Comma) {
+                self.next();
+                args.push(self.parse_type()?);
+            }
             self.expect_token(TokenKind::Gt)?;
-            ty = format!("{}<{}>", ty, inner);
-        }
+            TypeNode::new(TypeNodeKind::Generic { name, args }, line, col)
+        } else {
+            TypeNode::new(TypeNodeKind::Name(name), line, col)
+        };
+        Ok(self.optional_suffix(ty))
+    }
+
+    /// 类型后的 `?` 把它变成可选类型
+    fn optional_suffix(&mut self, ty: TypeNode) -> TypeNode {
         if self.peek_token(TokenKind::Question) {
             self.next();
-            ty.push('?');
+            let (line, col) = (ty.line, ty.col);
+            TypeNode::new(TypeNodeKind::Optional(Box::new(ty)), line, col)
+        } else {
+            ty
+        }
+    }
+
+    /// 可选的 `: 返回类型`
+    fn parse_return_type(&mut self) -> Result<Option<TypeNode>, Box<PawError>> {
+        if self.peek_token(TokenKind::Colon) {
+            self.next();
+            Ok(Some(self.parse_type()?))
+        } else {
+            Ok(None)
         }
-        Ok(ty)
     }
 
     /// parse 任意表达式的入口
-    pub fn parse_expr(&mut self) -> Result<Expr, PawError> {
+    pub fn parse_expr(&mut self) -> Result<Expr, Box<PawError>> {
+        let (line, col) = self.wrap_position();
         // 从最低优先级开始
-        self.parse_binary_expr(0)
+        let start = self.parse_binary_expr(0)?;
+        // 范围 `start..end [step s]` 的优先级低于所有二元运算
+        let inclusive = match self.peek_kind() {
+            Some(TokenKind::Range) => false,
+            Some(TokenKind::RangeInclusive) => true,
+            _ => return Ok(start),
+        };
+        self.next();
+        let end = self.parse_binary_expr(0)?;
+        // `step` 不是关键字，只有与上界写在同一行时才是步长
+        let end_line = self.tokens[self.position - 1].line;
+        let step = match self.peek() {
+            Some(Token {
+                kind: TokenKind::Identifier(name),
+                line: step_line,
+                ..
+            }) if name == "step" && *step_line == end_line => {
+                self.next();
+                Some(Box::new(self.parse_binary_expr(0)?))
+            }
+            _ => None,
+        };
+        Ok(Expr::new(
+            ExprKind::Range {
+                start: Box::new(start),
+                end: Box::new(end),
+                step,
+                inclusive,
+            },
+            line,
+            col,
+        ))
     }
 
     /// 最低优先级入口：parse_expr 调用它
-    fn parse_binary_expr(&mut self, min_prec: u8) -> Result<Expr, PawError> {
+    fn parse_binary_expr(&mut self, min_prec: u8) -> Result<Expr, Box<PawError>> {
         let (line, col) = self.wrap_position();
         // 先读左边
         let mut left = self.parse_unary_expr()?;
@@ -827,6 +1859,20 @@ impl Parser {
                     };
                     continue;
                 }
+                // `e is T` 与比较运算符同级
+                if k == "is" && min_prec <= 5 {
+                    self.next();
+                    let ty = self.parse_type()?;
+                    left = Expr {
+                        kind: ExprKind::TypeTest {
+                            expr: Box::new(left),
+                            ty,
+                        },
+                        line,
+                        col,
+                    };
+                    continue;
+                }
             }
 
             let (prec, right_assoc, op) = match self.peek_kind() {
@@ -835,6 +1881,7 @@ impl Parser {
                 Some(TokenKind::Star) => (7, false, BinaryOp::Mul),
                 Some(TokenKind::Slash) => (7, false, BinaryOp::Div),
                 Some(TokenKind::Percent) => (7, false, BinaryOp::Mod),
+                Some(TokenKind::StarStar) => (POW_PREC, true, BinaryOp::Pow),
                 Some(TokenKind::EqEq) => (5, false, BinaryOp::EqEq),
                 Some(TokenKind::NotEq) => (5, false, BinaryOp::NotEq),
                 Some(TokenKind::Lt) => (5, false, BinaryOp::Lt),
@@ -864,8 +1911,8 @@ impl Parser {
         Ok(left)
     }
 
-    /// 参数列表：fun foo(a: Int, b: String?) { … }
-    fn parse_params(&mut self) -> Result<Vec<Param>, PawError> {
+    /// 参数列表：fun foo(a: Int, b: String? = nopaw) { … }
+    fn parse_params(&mut self) -> Result<Vec<Param>, Box<PawError>> {
         let mut params = Vec::new();
         while !self.peek_token(TokenKind::RParen) {
             // 名字
@@ -875,7 +1922,23 @@ impl Parser {
             self.expect_token(TokenKind::Colon)?;
             // 类型
             let ty = self.parse_type()?;
-            params.push(Param::new(name, ty, p_line, p_col));
+            let mut param = Param::new(name, ty, p_line, p_col);
+            // 默认值：`= expr`；有默认值的参数之后不能再出现必填参数
+            if self.peek_token(TokenKind::Assign) {
+                self.next();
+                param.default = Some(self.parse_expr()?);
+            } else if params.iter().any(|p: &Param| p.default.is_some()) {
+                return Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1001",
+                    message: format!("Parameter '{}' needs a default value", param.name),
+                    line: p_line,
+                    column: p_col,
+                    snippet: self.snippet(p_line),
+                    hint: Some("Parameters with default values must come after the required ones".into()),
+                }));
+            }
+            params.push(param);
             // 如果逗号，继续
             if self.peek_token(TokenKind::Comma) {
                 self.next();
@@ -884,6 +1947,53 @@ impl Parser {
         Ok(params)
     }
 
+    /// 默认值只能用于具名函数：lambda 与 protocol 成员按函数类型调用，必须传齐参数
+    /// 声明名字后可选的类型参数表 `<T, U>`，参数不能重名
+    fn parse_type_params(&mut self) -> Result<Vec<String>, Box<PawError>> {
+        let mut type_params: Vec<String> = Vec::new();
+        if !self.peek_token(TokenKind::Lt) {
+            return Ok(type_params);
+        }
+        self.next();
+        loop {
+            let (line, col) = self.wrap_position();
+            let param = self.expect_identifier()?;
+            if type_params.contains(&param) {
+                return Err(Box::new(PawError::Syntax {
+                    file: self.file.clone(),
+                    code: "E1001",
+                    message: format!("Duplicate type parameter '{}'", param),
+                    line,
+                    column: col,
+                    snippet: self.snippet(line),
+                    hint: Some("Give each type parameter a different name".into()),
+                }));
+            }
+            type_params.push(param);
+            if !self.peek_token(TokenKind::Comma) {
+                break;
+            }
+            self.next();
+        }
+        self.expect_token(TokenKind::Gt)?;
+        Ok(type_params)
+    }
+
+    fn reject_defaults(&self, params: &[Param], what: &str) -> Result<(), Box<PawError>> {
+        match params.iter().find(|p| p.default.is_some()) {
+            Some(p) => Err(Box::new(PawError::Syntax {
+                file: self.file.clone(),
+                code: "E1001",
+                message: format!("Parameters of {} cannot have default values", what),
+                line: p.line,
+                column: p.col,
+                snippet: self.snippet(p.line),
+                hint: Some(format!("Remove the default value of '{}'", p.name)),
+            })),
+            None => Ok(()),
+        }
+    }
+
     fn parse_method(&self, name: &str) -> Method {
         match name {
             "trim" => Method::Trim,
@@ -897,9 +2007,37 @@ impl Parser {
             "starts_with" => Method::StartsWith,
             "ends_with" => Method::EndsWith,
             "contains" => Method::Contains,
+            "split" => Method::Split,
+            "replace" => Method::Replace,
+            "substring" => Method::Substring,
+            "to_int" => Method::ToInt,
+            "to_double" => Method::ToDouble,
             "push" => Method::Push,
             "pop" => Method::Pop,
-            _ => Method::Other,
+            "insert" => Method::Insert,
+            "remove" => Method::Remove,
+            "contains_key" => Method::ContainsKey,
+            "keys" => Method::Keys,
+            "values" => Method::Values,
+            "map" => Method::Map,
+            "filter" => Method::Filter,
+            "reduce" => Method::Reduce,
+            "join" => Method::Join,
+            "sort" => Method::Sort,
+            "reverse" => Method::Reverse,
+            "slice" => Method::Slice,
+            "to_set" => Method::ToSet,
+            "add" => Method::Add,
+            "union" => Method::Union,
+            "intersection" => Method::Intersection,
+            "difference" => Method::Difference,
+            "to_array" => Method::ToArray,
+            "is_ok" => Method::IsOk,
+            "is_err" => Method::IsErr,
+            "unwrap" => Method::Unwrap,
+            "unwrap_or" => Method::UnwrapOr,
+            "error" => Method::Error,
+            other => Method::Other(other.to_string()),
         }
     }
 }
@@ -0,0 +1,309 @@
+// src/playground.rs
+//
+// 在线 Playground 的后端：一个只依赖标准库的小型 HTTP 服务。
+// `POST /run` 的请求体即 PawScript 源码，每次请求启动一个沙箱模式的 pawc 子进程执行，
+// 子进程受时间与内存上限约束，输出被截获后以 JSON 返回。
+// 连接由固定数量的工作线程处理，同时运行的子进程不超过工作线程数；工作线程都忙时新连接排队等待。
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 请求头的最大长度
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// Playground 服务的配置
+#[derive(Debug, Clone)]
+pub struct PlaygroundConfig {
+    /// 监听地址，如 `127.0.0.1:8080`；端口为 0 时由系统分配
+    pub addr: String,
+    /// 执行脚本的 pawc 可执行文件
+    pub exe: PathBuf,
+    /// 单次执行的时间上限
+    pub timeout: Duration,
+    /// 单次执行的内存上限（MiB）
+    pub max_memory: usize,
+    /// stdout、stderr 各自保留的最大字节数，超出部分被丢弃
+    pub max_output: usize,
+    /// 源码的最大字节数
+    pub max_source: usize,
+    /// 工作线程数，即同时处理的请求数上限
+    pub workers: usize,
+    /// 接收一个请求（请求头与请求体）的时间上限，超时的连接以 408 关闭
+    pub request_timeout: Duration,
+}
+
+/// 一次执行的结果
+struct Outcome {
+    status: &'static str,
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+    duration: Duration,
+    truncated: bool,
+}
+
+/// 绑定地址并持续接受连接，交给固定数量的工作线程处理
+pub fn serve(config: PlaygroundConfig) -> io::Result<()> {
+    let listener = TcpListener::bind(&config.addr)?;
+    println!(
+        "🐾 PawScript playground listening on http://{}",
+        listener.local_addr()?
+    );
+    io::stdout().flush()?;
+    let workers = config.workers.max(1);
+    let config = Arc::new(config);
+    // 队列满时 accept 循环阻塞，更多的连接留在系统的监听队列中
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(workers);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..workers {
+        let config = config.clone();
+        let receiver = receiver.clone();
+        thread::spawn(move || work(&receiver, &config));
+    }
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        if sender.send(stream).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 工作线程：逐个处理队列中的连接
+fn work(receiver: &Mutex<Receiver<TcpStream>>, config: &PlaygroundConfig) {
+    loop {
+        // 取出连接后立即释放锁，其它工作线程可以继续等待下一个连接
+        let next = receiver.lock().ok().and_then(|r| r.recv().ok());
+        let Some(stream) = next else { return };
+        // 客户端提前断开等 IO 错误只影响这一个连接
+        let _ = handle(stream, config);
+    }
+}
+
+/// 读取时把套接字的超时设为到截止时间为止的剩余时间，
+/// 因此慢速发送的客户端也不能让一个请求占用工作线程超过 `request_timeout`
+struct DeadlineReader {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// 处理一个连接；没有在 `request_timeout` 内收到完整请求时回复 408
+fn handle(mut stream: TcpStream, config: &PlaygroundConfig) -> io::Result<()> {
+    stream.set_write_timeout(Some(config.request_timeout))?;
+    let reader = BufReader::new(DeadlineReader {
+        stream: stream.try_clone()?,
+        deadline: Instant::now() + config.request_timeout,
+    });
+    match handle_request(&mut stream, reader, config) {
+        Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock) => {
+            respond(&mut stream, "408 Request Timeout", &error_json("The request was not received in time"))
+        }
+        result => result,
+    }
+}
+
+fn handle_request(
+    stream: &mut TcpStream,
+    mut reader: BufReader<DeadlineReader>,
+    config: &PlaygroundConfig,
+) -> io::Result<()> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0usize;
+    let mut header_bytes = request_line.len();
+    loop {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line)?;
+        header_bytes += n;
+        if n == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+        if header_bytes > MAX_HEADER_BYTES {
+            return respond(stream, "431 Request Header Fields Too Large", &error_json("Request headers are too large"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    match (method.as_str(), path.as_str()) {
+        ("OPTIONS", _) => respond(stream, "204 No Content", ""),
+        ("POST", "/run") => {
+            if content_length > config.max_source {
+                // 先读掉请求体再回复，否则客户端可能在读到响应前收到连接重置
+                io::copy(&mut reader.take(content_length as u64), &mut io::sink())?;
+                let message = format!("Source is larger than {} bytes", config.max_source);
+                return respond(stream, "413 Payload Too Large", &error_json(&message));
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            let Ok(source) = String::from_utf8(body) else {
+                return respond(stream, "400 Bad Request", &error_json("Source must be valid UTF-8"));
+            };
+            let outcome = execute(&source, config)?;
+            respond(stream, "200 OK", &outcome.to_json())
+        }
+        (_, "/run") => respond(stream, "405 Method Not Allowed", &error_json("Use POST /run")),
+        _ => respond(stream, "404 Not Found", &error_json("Not found")),
+    }
+}
+
+/// 在沙箱模式的子进程中执行源码，超时则终止子进程
+fn execute(source: &str, config: &PlaygroundConfig) -> io::Result<Outcome> {
+    let start = Instant::now();
+    let mut child = Command::new(&config.exe)
+        .args(["--sandbox", "--max-memory"])
+        .arg(config.max_memory.to_string())
+        .arg("-")
+        .env("NO_COLOR", "1")
+        .env_remove("RUST_BACKTRACE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let truncated = Arc::new(AtomicBool::new(false));
+    let stdout = capture(child.stdout.take(), config.max_output, &truncated);
+    let stderr = capture(child.stderr.take(), config.max_output, &truncated);
+    if let Some(mut stdin) = child.stdin.take() {
+        // 子进程可能在读完源码前就退出，写入失败由退出状态体现
+        let _ = stdin.write_all(source.as_bytes());
+    }
+
+    let status = wait_until(&mut child, start + config.timeout)?;
+    let duration = start.elapsed();
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    let status_name = match status {
+        None => "timeout",
+        Some(s) if s.success() => "ok",
+        // 超出内存上限时分配失败，进程由运行时打印该信息后中止
+        Some(_) if stderr.contains("memory allocation of") => "memory_limit",
+        Some(_) => "error",
+    };
+    Ok(Outcome {
+        status: status_name,
+        stdout,
+        stderr,
+        exit_code: status.and_then(|s| s.code()),
+        duration,
+        truncated: truncated.load(Ordering::Relaxed),
+    })
+}
+
+/// 等待子进程结束；到达截止时间仍未结束则终止它并返回 None
+fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// 在后台线程中读完管道，只保留前 `limit` 个字节
+fn capture<P: Read + Send + 'static>(pipe: Option<P>, limit: usize, truncated: &Arc<AtomicBool>) -> JoinHandle<String> {
+    let truncated = truncated.clone();
+    thread::spawn(move || {
+        let Some(mut pipe) = pipe else {
+            return String::new();
+        };
+        let mut kept = Vec::new();
+        let mut buf = [0u8; 8192];
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let room = limit.saturating_sub(kept.len());
+            if n > room {
+                truncated.store(true, Ordering::Relaxed);
+            }
+            kept.extend_from_slice(&buf[..n.min(room)]);
+        }
+        String::from_utf8_lossy(&kept).into_owned()
+    })
+}
+
+impl Outcome {
+    fn to_json(&self) -> String {
+        let exit_code = self
+            .exit_code
+            .map_or_else(|| "null".to_string(), |c| c.to_string());
+        format!(
+            "{{\"status\":{},\"stdout\":{},\"stderr\":{},\"exit_code\":{},\"duration_ms\":{},\"truncated\":{}}}",
+            json_string(self.status),
+            json_string(&self.stdout),
+            json_string(&self.stderr),
+            exit_code,
+            self.duration.as_millis(),
+            self.truncated
+        )
+    }
+}
+
+fn error_json(message: &str) -> String {
+    format!("{{\"error\":{}}}", json_string(message))
+}
+
+/// 把字符串编码为 JSON 字符串字面量
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// 写出响应并关闭连接；允许任意来源跨域访问，方便网页直接调用
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    let content_type = if body.is_empty() { "" } else { "Content-Type: application/json\r\n" };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
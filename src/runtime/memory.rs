@@ -0,0 +1,76 @@
+// src/runtime/memory.rs
+//
+// 可限制总量的全局分配器。由 pawc 可执行文件注册为 `#[global_allocator]`，
+// `--max-memory` 设置上限后，超出上限的分配返回空指针，进程按分配失败终止。
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 当前已分配的字节数
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// 分配上限（字节），默认不限制
+static LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// 设置进程可分配内存的上限（字节）
+pub fn set_memory_limit(bytes: usize) {
+    LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// 统计分配量的系统分配器包装
+pub struct LimitedAlloc;
+
+impl LimitedAlloc {
+    /// 预留 `size` 字节，超出上限时撤销并返回 false
+    fn reserve(size: usize) -> bool {
+        let before = ALLOCATED.fetch_add(size, Ordering::Relaxed);
+        if before.saturating_add(size) > LIMIT.load(Ordering::Relaxed) {
+            ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+}
+
+unsafe impl GlobalAlloc for LimitedAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !Self::reserve(layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let ptr = System.alloc(layout);
+        if ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if !Self::reserve(layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let ptr = System.alloc_zeroed(layout);
+        if ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() && !Self::reserve(new_size - layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if new_ptr.is_null() {
+            if new_size > layout.size() {
+                ALLOCATED.fetch_sub(new_size - layout.size(), Ordering::Relaxed);
+            }
+        } else if new_size < layout.size() {
+            ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,141 @@
+// src/runtime/mod.rs
+//
+// 异步运行时适配层：
+// - 开启 `tokio-runtime` feature（默认）时，使用 tokio 多线程调度器、计时器与阻塞线程池
+// - 关闭时退回到 futures 执行器 + 标准库线程，适合最小化构建
+
+pub mod memory;
+
+use std::future::Future;
+use std::time::Duration;
+
+/// 在一个栈大小为 `stack_size` 字节的新线程上创建并驱动顶层 Future 直到完成
+///
+/// 解释器本身通过 vuot 把调用栈放在堆上，但输出、比较深层嵌套的值等操作仍会消耗线程栈，
+/// 因此顶层执行不依赖宿主线程（如主线程）的栈大小；tokio 工作线程使用同样的栈大小。
+/// vuot 的 Future 不能跨线程移动，所以传入的是在新线程上构造 Future 的闭包。
+pub fn block_on<M, F>(make: M, stack_size: usize) -> Result<F::Output, String>
+where
+    M: FnOnce() -> F + Send,
+    F: Future,
+    F::Output: Send,
+{
+    std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .name("pawc-main".into())
+            .stack_size(stack_size)
+            .spawn_scoped(scope, move || drive(make(), stack_size))
+            .map_err(|e| e.to_string())?;
+        handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+#[cfg(feature = "tokio-runtime")]
+fn drive<F: Future>(fut: F, stack_size: usize) -> Result<F::Output, String> {
+    let cpus = num_cpus::get().max(1);
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(cpus)
+        .thread_stack_size(stack_size)
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(rt.block_on(fut))
+}
+
+#[cfg(not(feature = "tokio-runtime"))]
+fn drive<F: Future>(fut: F, _stack_size: usize) -> Result<F::Output, String> {
+    Ok(futures::executor::block_on(fut))
+}
+
+/// 在独立线程上执行一个会阻塞的闭包，返回可 await 的结果
+#[cfg(feature = "tokio-runtime")]
+pub async fn spawn_blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.map_err(|e| e.to_string())
+}
+
+/// 在独立线程上执行一个会阻塞的闭包，返回可 await 的结果
+#[cfg(not(feature = "tokio-runtime"))]
+pub async fn spawn_blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = futures::channel::oneshot::channel();
+    let stack_size = crate::STACK_SIZE.get().copied().unwrap_or(1) * 1024 * 1024;
+    std::thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || {
+            let _ = tx.send(f());
+        })
+        .map_err(|e| e.to_string())?;
+    rx.await.map_err(|e| e.to_string())
+}
+
+/// 立即在后台开始执行一个 Future，返回等待其结果的 Future
+#[cfg(feature = "tokio-runtime")]
+pub fn spawn<F>(fut: F) -> impl Future<Output = Result<F::Output, String>> + Send
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (tx, rx) = futures::channel::oneshot::channel();
+    let task = async move {
+        let _ = tx.send(fut.await);
+    };
+    // 不在 tokio 运行时中（例如嵌入方用其它执行器驱动）时，退回到独立线程
+    match tokio::runtime::Handle::try_current() {
+        Ok(rt) => drop(rt.spawn(task)),
+        Err(_) => drop(std::thread::spawn(move || futures::executor::block_on(task))),
+    }
+    async move { rx.await.map_err(|e| e.to_string()) }
+}
+
+/// 立即在后台开始执行一个 Future，返回等待其结果的 Future
+#[cfg(not(feature = "tokio-runtime"))]
+pub fn spawn<F>(fut: F) -> impl Future<Output = Result<F::Output, String>> + Send
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (tx, rx) = futures::channel::oneshot::channel();
+    let stack_size = crate::STACK_SIZE.get().copied().unwrap_or(1) * 1024 * 1024;
+    let started = std::thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || {
+            let _ = tx.send(futures::executor::block_on(fut));
+        })
+        .map_err(|e| e.to_string());
+    async move {
+        started?;
+        rx.await.map_err(|e| e.to_string())
+    }
+}
+
+/// 异步等待一段时间
+#[cfg(feature = "tokio-runtime")]
+pub async fn sleep(duration: Duration) {
+    // 不在 tokio 运行时中时没有计时器可用，退回到独立线程
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => tokio::time::sleep(duration).await,
+        Err(_) => {
+            let (tx, rx) = futures::channel::oneshot::channel();
+            std::thread::spawn(move || {
+                std::thread::sleep(duration);
+                let _ = tx.send(());
+            });
+            let _ = rx.await;
+        }
+    }
+}
+
+/// 异步等待一段时间
+#[cfg(not(feature = "tokio-runtime"))]
+pub async fn sleep(duration: Duration) {
+    let _ = spawn_blocking(move || std::thread::sleep(duration)).await;
+}
@@ -0,0 +1,299 @@
+// src/semantic/graph.rs
+//
+// `pawc graph`：沿着 import 收集一个项目的模块依赖图，输出 DOT 或 JSON。
+// 模块文件的查找与读取和检查器共用 `imports` 中的解析规则；导入循环中的模块与边会被标出
+
+use crate::ast::statement::Statement;
+use crate::error::error::PawError;
+use crate::interpreter::stdlib::is_std_module;
+use crate::lexer::lexer::Lexer;
+use crate::parser::cfg::Cfg;
+use crate::parser::parser::Parser;
+use crate::playground::json_string;
+use crate::semantic::imports::{collect_imports, module_key, module_path, read_module};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// 图中节点的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// 入口脚本或模块文件
+    File,
+    /// 标准库模块，没有源码
+    Std,
+    /// 找不到（或大小写不符）的模块文件
+    Missing,
+}
+
+impl NodeKind {
+    fn name(self) -> &'static str {
+        match self {
+            NodeKind::File => "file",
+            NodeKind::Std => "std",
+            NodeKind::Missing => "missing",
+        }
+    }
+}
+
+/// 一个模块；文件模块以路径命名，标准库模块以模块名命名
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub name: String,
+    pub kind: NodeKind,
+    /// 是否处在某个导入循环中
+    pub in_cycle: bool,
+}
+
+/// `from` 中的 import 语句指向 `to`；同一对模块之间只保留第一条
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    /// import 语句中的模块名，如 `utils.math`
+    pub module: String,
+    pub line: usize,
+    /// 两端是否处在同一个导入循环中
+    pub in_cycle: bool,
+}
+
+/// 模块依赖图；节点 0 是入口脚本，其余按被发现的顺序编号
+#[derive(Debug, Default)]
+pub struct ImportGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// 每个导入循环涉及的节点，按编号排序
+    pub cycles: Vec<Vec<usize>>,
+    /// 读取或解析模块时的错误；出错的模块仍在图中，只是没有出边
+    pub errors: Vec<PawError>,
+}
+
+/// 收集 `file`（其语句为 `program`）直接或间接导入的全部模块。
+/// 模块按 `cfg` 中的开关解析 `@cfg(...)`，被关掉的 import 不在图中
+pub fn import_graph(file: &str, program: &[Statement], cfg: &Cfg) -> ImportGraph {
+    let mut graph = ImportGraph::default();
+    let mut ids: HashMap<PathBuf, usize> = HashMap::new();
+    ids.insert(module_key(Path::new(file)), 0);
+    graph.nodes.push(GraphNode {
+        name: file.to_string(),
+        kind: NodeKind::File,
+        in_cycle: false,
+    });
+    // 待展开的模块：编号、文件名与语句
+    let mut pending = VecDeque::from([(0, file.to_string(), program.to_vec())]);
+    while let Some((from, file, stmts)) = pending.pop_front() {
+        let mut imports = Vec::new();
+        collect_imports(&stmts, &mut imports);
+        for (module, line, col) in imports {
+            let dotted = module.join(".");
+            let std = is_std_module(module);
+            let path = module_path(&file, module);
+            let key = match std {
+                true => PathBuf::from(format!("<std>/{}", dotted)),
+                false => module_key(&path),
+            };
+            let to = match ids.get(&key) {
+                Some(&id) => id,
+                None => {
+                    let id = graph.nodes.len();
+                    ids.insert(key, id);
+                    let name = path.to_string_lossy().to_string();
+                    let kind = if std {
+                        NodeKind::Std
+                    } else {
+                        match read_module(&file, &path, module, line, col) {
+                            Ok(src) => {
+                                let tokens = Lexer::new(&src).tokenize();
+                                match Parser::new(tokens, &src, &name).with_cfg(cfg).parse_program() {
+                                    Ok(stmts) => pending.push_back((id, name.clone(), stmts)),
+                                    Err(e) => graph.errors.push(*e),
+                                }
+                                NodeKind::File
+                            }
+                            Err(e) => {
+                                graph.errors.push(*e);
+                                NodeKind::Missing
+                            }
+                        }
+                    };
+                    graph.nodes.push(GraphNode {
+                        name: if std { dotted.clone() } else { name },
+                        kind,
+                        in_cycle: false,
+                    });
+                    id
+                }
+            };
+            if !graph.edges.iter().any(|e| e.from == from && e.to == to) {
+                graph.edges.push(GraphEdge {
+                    from,
+                    to,
+                    module: dotted,
+                    line,
+                    in_cycle: false,
+                });
+            }
+        }
+    }
+    graph.mark_cycles();
+    graph
+}
+
+impl ImportGraph {
+    /// 找出强连通分量（Tarjan 算法），多于一个节点或导入自身的分量就是一个导入循环
+    fn mark_cycles(&mut self) {
+        let n = self.nodes.len();
+        let mut succ = vec![Vec::new(); n];
+        for edge in &self.edges {
+            succ[edge.from].push(edge.to);
+        }
+        let mut state = Tarjan {
+            succ: &succ,
+            index: vec![None; n],
+            low: vec![0; n],
+            stack: Vec::new(),
+            on_stack: vec![false; n],
+            next: 0,
+            components: Vec::new(),
+        };
+        for v in 0..n {
+            if state.index[v].is_none() {
+                state.visit(v);
+            }
+        }
+        let mut component_of = vec![None; n];
+        let mut cycles: Vec<Vec<usize>> = state
+            .components
+            .into_iter()
+            .filter(|c| c.len() > 1 || succ[c[0]].contains(&c[0]))
+            .collect();
+        for cycle in &mut cycles {
+            cycle.sort_unstable();
+        }
+        cycles.sort();
+        for (i, cycle) in cycles.iter().enumerate() {
+            for &v in cycle {
+                component_of[v] = Some(i);
+                self.nodes[v].in_cycle = true;
+            }
+        }
+        for edge in &mut self.edges {
+            edge.in_cycle = component_of[edge.from].is_some() && component_of[edge.from] == component_of[edge.to];
+        }
+        self.cycles = cycles;
+    }
+
+    /// Graphviz DOT 文本：标准库模块为虚线框，找不到的模块为点线框，导入循环中的模块与边为红色
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph imports {\n  node [shape=box];\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let mut attrs = vec![format!("label={}", json_string(&node.name))];
+            match node.kind {
+                NodeKind::File => {}
+                NodeKind::Std => attrs.push("style=dashed".into()),
+                NodeKind::Missing => attrs.push("style=dotted".into()),
+            }
+            if node.in_cycle {
+                attrs.push("color=red".into());
+            }
+            out.push_str(&format!("  n{} [{}];\n", id, attrs.join(", ")));
+        }
+        for edge in &self.edges {
+            let mut attrs = vec![format!("label={}", json_string(&edge.module))];
+            if edge.in_cycle {
+                attrs.push("color=red".into());
+            }
+            out.push_str(&format!("  n{} -> n{} [{}];\n", edge.from, edge.to, attrs.join(", ")));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// JSON 对象：`nodes`（`id`、`name`、`kind`、`cycle`）、`edges`（`from`、`to`、`import`、`line`、`cycle`）
+    /// 与 `cycles`（每个循环的节点编号）
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| {
+                format!(
+                    "{{\"id\":{},\"name\":{},\"kind\":{},\"cycle\":{}}}",
+                    id,
+                    json_string(&node.name),
+                    json_string(node.kind.name()),
+                    node.in_cycle
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|edge| {
+                format!(
+                    "{{\"from\":{},\"to\":{},\"import\":{},\"line\":{},\"cycle\":{}}}",
+                    edge.from,
+                    edge.to,
+                    json_string(&edge.module),
+                    edge.line,
+                    edge.in_cycle
+                )
+            })
+            .collect();
+        let cycles: Vec<String> = self
+            .cycles
+            .iter()
+            .map(|cycle| {
+                let ids: Vec<String> = cycle.iter().map(|id| id.to_string()).collect();
+                format!("[{}]", ids.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"nodes\":[{}],\n\"edges\":[{}],\n\"cycles\":[{}]}}\n",
+            nodes.join(",\n "),
+            edges.join(",\n "),
+            cycles.join(",")
+        )
+    }
+}
+
+/// Tarjan 强连通分量算法的状态
+struct Tarjan<'a> {
+    succ: &'a [Vec<usize>],
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    next: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, v: usize) {
+        self.index[v] = Some(self.next);
+        self.low[v] = self.next;
+        self.next += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+        for &w in &self.succ[v] {
+            match self.index[w] {
+                None => {
+                    self.visit(w);
+                    self.low[v] = self.low[v].min(self.low[w]);
+                }
+                Some(i) if self.on_stack[w] => self.low[v] = self.low[v].min(i),
+                Some(_) => {}
+            }
+        }
+        if Some(self.low[v]) == self.index[v] {
+            let mut component = Vec::new();
+            while let Some(w) = self.stack.pop() {
+                self.on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// src/semantic/imports.rs
+//
+// 运行之前沿着 import 图检查被导入的文件模块：每个模块单独解析、检查，
+// 诊断归属于出错的模块文件；标准库模块没有源码，不在图中
+
+use crate::ast::statement::{Statement, StatementKind};
+use crate::error::error::PawError;
+use crate::interpreter::stdlib::is_std_module;
+use crate::lexer::lexer::Lexer;
+use crate::parser::cfg::Cfg;
+use crate::parser::parser::Parser;
+use crate::semantic::type_checker::TypeChecker;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+/// 导入图的检查结果
+#[derive(Debug, Default)]
+pub struct ImportCheck {
+    /// 检查过的模块文件数，不含入口脚本
+    pub modules: usize,
+    /// 每个模块的第一个错误；读不到的模块文件报告在 import 语句处
+    pub errors: Vec<PawError>,
+    pub warnings: Vec<PawError>,
+}
+
+/// 目录形式的模块（包）中依次查找的入口文件
+const PACKAGE_ENTRIES: [&str; 2] = ["mod.paw", "index.paw"];
+
+/// `importer` 中的 `import a.b` 对应的模块文件：相对 importer 所在目录的 `a/b.paw`；
+/// 它不存在而 `a/b/mod.paw` 或 `a/b/index.paw` 存在时取后者，一个目录因此可以作为一个模块。
+/// 扩展名直接接在最后一段之后，不会替换段名中已有的点；结果按字面规范化。
+/// 检查器与解释器共用这里的解析规则，两边找到的总是同一个文件
+pub(crate) fn module_path(importer: &str, module: &[String]) -> PathBuf {
+    let mut path = PathBuf::from(importer);
+    path.pop();
+    let Some((last, dirs)) = module.split_last() else {
+        return normalize(&path);
+    };
+    path.extend(dirs);
+    let file = normalize(&path.join(format!("{}.paw", last)));
+    if file.is_file() {
+        return file;
+    }
+    let dir = normalize(&path.join(last));
+    PACKAGE_ENTRIES
+        .iter()
+        .map(|entry| dir.join(entry))
+        .find(|entry| entry.is_file())
+        // 都不存在时报告单文件的写法
+        .unwrap_or(file)
+}
+
+/// 找不到模块文件时的提示，列出单文件与目录两种写法
+pub(crate) fn missing_module_hint(path: &Path) -> String {
+    let dir = path.with_extension("");
+    format!(
+        "Create {} or, for a package directory, {} or {}",
+        path.display(),
+        dir.join(PACKAGE_ENTRIES[0]).display(),
+        dir.join(PACKAGE_ENTRIES[1]).display()
+    )
+}
+
+/// 按字面规范化路径：去掉 `.`，`..` 与前一段抵消，不访问文件系统。
+/// 文件还不存在时也能得到稳定的写法；Windows 的 `\\?\` 路径不会自行处理 `..`，也需要先规范化
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                // 根目录之上没有父目录
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// 模块缓存与循环检测使用的键：文件存在时为规范路径，否则为规范化后的原路径
+pub(crate) fn module_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 检查 `path`（`file` 中的 `import module` 解析出的文件）在 `file` 所在目录之下的各段
+/// 与磁盘上的文件名大小写一致，包括目录模块的入口文件。
+/// 大小写不敏感的文件系统（Windows、macOS）也能打开写错大小写的模块，换到 Linux 就找不到，
+/// 因此在所有平台上都报 E1002，并给出磁盘上的写法
+pub(crate) fn check_case(
+    file: &str,
+    path: &Path,
+    module: &[String],
+    line: usize,
+    col: usize,
+) -> Result<(), Box<PawError>> {
+    let names: Vec<&std::ffi::OsStr> = path.iter().collect();
+    // 模块名各段不含 `.` 与 `..`，path 总以 importer 所在目录的规范写法开头
+    let base = normalize(Path::new(file).parent().unwrap_or(Path::new("")));
+    let split = base.iter().count().min(names.len());
+    let mut dir: PathBuf = names[..split].iter().collect();
+    for name in &names[split..] {
+        let listed = if dir.as_os_str().is_empty() { Path::new(".") } else { &dir };
+        // 目录读不到时交给随后的读取报告“找不到模块”
+        let Ok(entries) = std::fs::read_dir(listed) else {
+            return Ok(());
+        };
+        let wanted = name.to_string_lossy().to_lowercase();
+        let mut found = None;
+        for entry in entries.flatten() {
+            let actual = entry.file_name();
+            if actual == *name {
+                found = None;
+                break;
+            }
+            if actual.to_string_lossy().to_lowercase() == wanted {
+                found = Some(actual);
+            }
+        }
+        match found {
+            Some(actual) => {
+                let on_disk = dir.join(&actual);
+                return Err(Box::new(PawError::Internal {
+                    file: file.to_string(),
+                    code: "E1002",
+                    message: format!(
+                        "Module path {} does not match the case of {} on disk",
+                        path.display(),
+                        on_disk.display()
+                    ),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: Some(format!(
+                        "Module paths are case-sensitive on every platform; rename the file or fix `import {}`",
+                        module.join(".")
+                    )),
+                }));
+            }
+            None => dir.push(name),
+        }
+    }
+    Ok(())
+}
+
+/// 读取 `file` 中的 `import module` 解析出的模块文件 `path`；大小写不符或读不到时报 E1002，
+/// 错误报告在 import 语句处
+pub(crate) fn read_module(
+    file: &str,
+    path: &Path,
+    module: &[String],
+    line: usize,
+    col: usize,
+) -> Result<String, Box<PawError>> {
+    check_case(file, path, module, line, col)?;
+    std::fs::read_to_string(path).map_err(|e| Box::new(PawError::Internal {
+        file: file.to_string(),
+        code: "E1002",
+        message: match e.kind() {
+            ErrorKind::NotFound => format!("Module file not found: {}", path.display()),
+            _ => format!("Failed to read module file: {}", path.display()),
+        },
+        line,
+        column: col,
+        snippet: None,
+        hint: Some(missing_module_hint(path)),
+    }))
+}
+
+/// 检查 `file`（其语句为 `program`，本身已检查过）直接或间接导入的文件模块。
+/// `depth` 限制向下检查的层数：Some(0) 不检查任何模块，Some(1) 只检查直接导入的模块，None 不限；
+/// 每个模块只检查一次，因此循环导入也会结束，循环本身留给运行时报告。模块按 `cfg` 中的开关解析 `@cfg(...)`
+pub fn check_imports(file: &str, program: &[Statement], depth: Option<usize>, cfg: &Cfg) -> ImportCheck {
+    let mut result = ImportCheck::default();
+    let mut seen = HashSet::new();
+    seen.insert(module_key(Path::new(file)));
+    visit(file, program, 0, depth, cfg, &mut seen, &mut result);
+    result
+}
+
+fn visit(
+    file: &str,
+    program: &[Statement],
+    level: usize,
+    depth: Option<usize>,
+    cfg: &Cfg,
+    seen: &mut HashSet<PathBuf>,
+    result: &mut ImportCheck,
+) {
+    if depth.is_some_and(|max| level >= max) {
+        return;
+    }
+    let mut imports = Vec::new();
+    collect_imports(program, &mut imports);
+    for (module, line, col) in imports {
+        if is_std_module(module) {
+            continue;
+        }
+        let path = module_path(file, module);
+        if !seen.insert(module_key(&path)) {
+            continue;
+        }
+        let name = path.to_string_lossy().to_string();
+        let src = match read_module(file, &path, module, line, col) {
+            Ok(src) => src,
+            Err(e) => {
+                result.errors.push(*e);
+                continue;
+            }
+        };
+        result.modules += 1;
+        let tokens = Lexer::new(&src).tokenize();
+        let stmts = match Parser::new(tokens, &src, &name).with_cfg(cfg).parse_program() {
+            Ok(stmts) => stmts,
+            Err(e) => {
+                result.errors.push(*e);
+                continue;
+            }
+        };
+        let mut checker = TypeChecker::new(&name);
+        if let Err(e) = checker.check_program(&stmts) {
+            result.errors.push(*e);
+        }
+        result.warnings.extend(checker.warnings());
+        visit(&name, &stmts, level + 1, depth, cfg, seen, result);
+    }
+}
+
+/// 收集语句中的 import（包括函数体、分支与循环体内的），按出现顺序
+pub(crate) fn collect_imports<'a>(stmts: &'a [Statement], out: &mut Vec<(&'a [String], usize, usize)>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StatementKind::Import { module, .. } => out.push((module, stmt.line, stmt.col)),
+            StatementKind::If {
+                body, else_branch, ..
+            } => {
+                collect_imports(body, out);
+                if let Some(else_stmt) = else_branch {
+                    collect_imports(std::slice::from_ref(else_stmt.as_ref()), out);
+                }
+            }
+            StatementKind::LoopForever { body, .. }
+            | StatementKind::LoopWhile { body, .. }
+            | StatementKind::LoopDoWhile { body, .. }
+            | StatementKind::LoopRange { body, .. }
+            | StatementKind::LoopArray { body, .. }
+            | StatementKind::FunDecl { body, .. }
+            | StatementKind::Block(body)
+            | StatementKind::TaskGroup(body) => collect_imports(body, out),
+            StatementKind::Match { arms, .. } => {
+                for arm in arms {
+                    collect_imports(&arm.body, out);
+                }
+            }
+            StatementKind::TryCatchFinally {
+                body,
+                clauses,
+                finally,
+            } => {
+                collect_imports(body, out);
+                for clause in clauses {
+                    collect_imports(&clause.body, out);
+                }
+                collect_imports(finally, out);
+            }
+            _ => {}
+        }
+    }
+}
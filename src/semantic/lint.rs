@@ -0,0 +1,846 @@
+// src/semantic/lint.rs
+//
+// `pawc lint` 的静态分析：在类型检查之外找出未使用的变量、不可达代码、恒定条件、
+// 变量遮蔽与被丢弃的结果。每条规则有名字，可以单独或按规则集开关；
+// 类型检查器自己的警告（W3001 ~ W3004）也归入对应的规则
+
+use crate::ast::expr::{BinaryOp, Expr, ExprKind};
+use crate::ast::method::Method;
+use crate::ast::param::Param;
+use crate::ast::statement::{LoopVar, Statement, StatementKind};
+use crate::ast::type_node::{TypeNode, TypeNodeKind};
+use crate::error::error::PawError;
+use std::collections::{BTreeSet, HashMap};
+
+/// 一条 lint 规则
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// 局部变量定义后从未读取
+    UnusedVariable,
+    /// `return`、`break`、`continue`、`bark` 之后的语句
+    UnreachableCode,
+    /// `if` 或循环的条件是常量
+    ConstantCondition,
+    /// 内层定义与外层变量同名
+    Shadowing,
+    /// 有返回值的调用、不修改接收者的内置方法或没有副作用的表达式单独成句
+    DiscardedResult,
+    /// 类型检查器的 W3001：await 的不是 Future
+    Await,
+    /// 类型检查器的 W3002 / W3003：match 不穷尽或分支不可达
+    Match,
+    /// 类型检查器的 W3004：使用已弃用的声明
+    Deprecated,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 8] = [
+        Rule::UnusedVariable,
+        Rule::UnreachableCode,
+        Rule::ConstantCondition,
+        Rule::Shadowing,
+        Rule::DiscardedResult,
+        Rule::Await,
+        Rule::Match,
+        Rule::Deprecated,
+    ];
+
+    /// 命令行与 JSON 输出中使用的名字
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::UnusedVariable => "unused-variable",
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::ConstantCondition => "constant-condition",
+            Rule::Shadowing => "shadowing",
+            Rule::DiscardedResult => "discarded-result",
+            Rule::Await => "await",
+            Rule::Match => "match",
+            Rule::Deprecated => "deprecated",
+        }
+    }
+
+    /// 规则产生的警告码
+    pub fn codes(self) -> &'static [&'static str] {
+        match self {
+            Rule::UnusedVariable => &["W3005"],
+            Rule::UnreachableCode => &["W3006"],
+            Rule::ConstantCondition => &["W3007"],
+            Rule::Shadowing => &["W3008"],
+            Rule::DiscardedResult => &["W3009"],
+            Rule::Await => &["W3001"],
+            Rule::Match => &["W3002", "W3003"],
+            Rule::Deprecated => &["W3004"],
+        }
+    }
+
+    /// 警告码所属的规则
+    pub fn of_code(code: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.codes().contains(&code))
+    }
+}
+
+/// 选中的规则集合
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: BTreeSet<Rule>,
+}
+
+/// 命名的规则集；`default` 不含容易误报的 shadowing
+const RULE_SETS: [(&str, &[Rule]); 4] = [
+    ("all", &Rule::ALL),
+    (
+        "default",
+        &[
+            Rule::UnusedVariable,
+            Rule::UnreachableCode,
+            Rule::ConstantCondition,
+            Rule::DiscardedResult,
+            Rule::Await,
+            Rule::Match,
+            Rule::Deprecated,
+        ],
+    ),
+    ("checker", &[Rule::Await, Rule::Match, Rule::Deprecated]),
+    ("flow", &[Rule::UnreachableCode, Rule::ConstantCondition]),
+];
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::parse("default").expect("the default rule set exists")
+    }
+}
+
+impl RuleSet {
+    /// 解析逗号分隔的规则名与规则集名，如 `flow,unused-variable`
+    pub fn parse(list: &str) -> Result<RuleSet, String> {
+        let mut rules = BTreeSet::new();
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if let Some((_, set)) = RULE_SETS.iter().find(|(set, _)| *set == name) {
+                rules.extend(set.iter().copied());
+            } else if let Some(rule) = Rule::ALL.into_iter().find(|rule| rule.name() == name) {
+                rules.insert(rule);
+            } else {
+                let known: Vec<&str> = RULE_SETS
+                    .iter()
+                    .map(|(set, _)| *set)
+                    .chain(Rule::ALL.iter().map(|rule| rule.name()))
+                    .collect();
+                return Err(format!("unknown rule '{}'; expected one of {}", name, known.join(", ")));
+            }
+        }
+        Ok(RuleSet { rules })
+    }
+
+    /// 去掉 other 中的规则
+    pub fn without(&self, other: &RuleSet) -> RuleSet {
+        RuleSet {
+            rules: self.rules.difference(&other.rules).copied().collect(),
+        }
+    }
+
+    pub fn contains(&self, rule: Rule) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    /// 警告是否属于选中的规则；不属于任何规则的警告总是保留
+    pub fn reports(&self, warning: &PawError) -> bool {
+        Rule::of_code(warning.code()).is_none_or(|rule| self.contains(rule))
+    }
+}
+
+/// 对一个文件的语法树运行选中的分析，返回 W3005 ~ W3009 警告；
+/// 顶层的变量可能被导入方使用，不报告未使用，最后一条顶层语句是脚本的结果，不算被丢弃
+pub fn lint_program(file: &str, program: &[Statement], rules: &RuleSet) -> Vec<PawError> {
+    let mut functions = HashMap::new();
+    collect_functions(program, &mut functions);
+    let mut linter = Linter {
+        file: file.to_string(),
+        rules,
+        scopes: Vec::new(),
+        functions,
+        warnings: Vec::new(),
+    };
+    linter.block(program, Vec::new(), true);
+    linter.warnings
+}
+
+/// 各个函数名的返回类型；同名函数中有一个不返回值时为 None
+fn collect_functions(stmts: &[Statement], out: &mut HashMap<String, Option<TypeNode>>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StatementKind::FunDecl {
+                name,
+                return_type,
+                body,
+                ..
+            } => {
+                let returns = return_type.clone().filter(|ty| ty.to_string() != "Void");
+                let entry = out.entry(name.clone()).or_insert_with(|| returns.clone());
+                if returns.is_none() {
+                    *entry = None;
+                }
+                collect_functions(body, out);
+            }
+            StatementKind::Block(body)
+            | StatementKind::TaskGroup(body)
+            | StatementKind::LoopForever { body, .. }
+            | StatementKind::LoopWhile { body, .. }
+            | StatementKind::LoopDoWhile { body, .. }
+            | StatementKind::LoopRange { body, .. }
+            | StatementKind::LoopArray { body, .. } => collect_functions(body, out),
+            StatementKind::If {
+                body, else_branch, ..
+            } => {
+                collect_functions(body, out);
+                if let Some(other) = else_branch {
+                    collect_functions(std::slice::from_ref(other.as_ref()), out);
+                }
+            }
+            StatementKind::TryCatchFinally {
+                body,
+                clauses,
+                finally,
+            } => {
+                collect_functions(body, out);
+                clauses.iter().for_each(|clause| collect_functions(&clause.body, out));
+                collect_functions(finally, out);
+            }
+            StatementKind::Match { arms, .. } => {
+                arms.iter().for_each(|arm| collect_functions(&arm.body, out));
+            }
+            StatementKind::RecordDecl { methods, .. } => {
+                for method in methods {
+                    if let StatementKind::FunDecl { body, .. } = &method.kind {
+                        collect_functions(body, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 作用域中的一个变量
+struct Binding {
+    name: String,
+    line: usize,
+    col: usize,
+    used: bool,
+    /// 是否参与未使用检查：顶层变量、参数、模式与 snatch 绑定不参与
+    checked: bool,
+    /// 由注解或初始值得知的内置类型
+    receiver: Option<Receiver>,
+}
+
+/// 带有内置方法的类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Receiver {
+    String,
+    Array,
+    Map,
+    Set,
+    Result,
+}
+
+impl Receiver {
+    fn of_type(ty: &TypeNode) -> Option<Receiver> {
+        let name = match &ty.kind {
+            TypeNodeKind::Name(name) | TypeNodeKind::Generic { name, .. } => name,
+            _ => return None,
+        };
+        match name.as_str() {
+            "String" => Some(Receiver::String),
+            "Array" => Some(Receiver::Array),
+            "Map" => Some(Receiver::Map),
+            "Set" => Some(Receiver::Set),
+            "Result" => Some(Receiver::Result),
+            _ => None,
+        }
+    }
+
+    /// 只返回结果、不修改接收者也不调用传入函数的方法：返回结果的内置类型（不是上面几种时为 None）；
+    /// 方法会修改接收者、调用函数或不属于该类型时返回 None
+    fn pure_method(self, method: &Method, argc: usize) -> Option<Option<Receiver>> {
+        use Receiver::*;
+        match (self, method) {
+            (String, Method::Trim | Method::ToUppercase | Method::ToLowercase | Method::Replace | Method::Substring)
+            | (Array, Method::Join) => Some(Some(String)),
+            (String, Method::Split)
+            | (Array, Method::Reverse | Method::Slice)
+            | (Map, Method::Keys | Method::Values)
+            | (Set, Method::ToArray) => Some(Some(Array)),
+            // 带比较函数的 sort 会调用该函数
+            (Array, Method::Sort) if argc == 0 => Some(Some(Array)),
+            (Array, Method::ToSet) | (Set, Method::Union | Method::Intersection | Method::Difference) => Some(Some(Set)),
+            (String, Method::ToInt | Method::ToDouble) => Some(Some(Result)),
+            (String, Method::Length | Method::StartsWith | Method::EndsWith | Method::Contains)
+            | (Array, Method::Length | Method::LengthArr)
+            | (Map, Method::Length | Method::ContainsKey)
+            | (Set, Method::Length | Method::Contains)
+            | (Result, Method::IsOk | Method::IsErr | Method::UnwrapOr | Method::Error) => Some(None),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Receiver::String => "String",
+            Receiver::Array => "Array",
+            Receiver::Map => "Map",
+            Receiver::Set => "Set",
+            Receiver::Result => "Result",
+        }
+    }
+}
+
+/// 让后续语句无法执行的语句
+#[derive(Clone, Copy)]
+struct Exit {
+    what: &'static str,
+    line: usize,
+    col: usize,
+}
+
+struct Linter<'a> {
+    file: String,
+    rules: &'a RuleSet,
+    scopes: Vec<Vec<Binding>>,
+    functions: HashMap<String, Option<TypeNode>>,
+    warnings: Vec<PawError>,
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, rule: Rule, message: String, line: usize, col: usize, hint: Option<String>) {
+        if !self.rules.contains(rule) {
+            return;
+        }
+        self.warnings.push(PawError::Warning {
+            file: self.file.clone(),
+            code: rule.codes()[0],
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint,
+        });
+    }
+
+    /// 在当前作用域定义变量；与外层变量同名时报告遮蔽
+    fn bind(&mut self, name: &str, line: usize, col: usize, checked: bool) {
+        if name == "_" {
+            return;
+        }
+        let outer = self.scopes[..self.scopes.len() - 1]
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter())
+            .find(|binding| binding.name == name)
+            .map(|binding| (binding.line, binding.col));
+        if let Some((at_line, at_col)) = outer {
+            let hint = format!("The outer '{}' is defined at {}:{}:{}", name, self.file, at_line, at_col);
+            self.warn(
+                Rule::Shadowing,
+                format!("'{}' shadows a variable of an enclosing scope", name),
+                line,
+                col,
+                Some(hint),
+            );
+        }
+        let checked = checked && self.scopes.len() > 1 && !name.starts_with('_');
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name: name.to_string(),
+                line,
+                col,
+                used: false,
+                checked,
+                receiver: None,
+            });
+        }
+    }
+
+    /// 读取变量：标记最近一层同名的定义为已使用
+    fn use_name(&mut self, name: &str) {
+        let found = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|binding| binding.name == name));
+        if let Some(binding) = found {
+            binding.used = true;
+        }
+    }
+
+    /// 最近一层同名变量的内置类型
+    fn receiver_of_name(&self, name: &str) -> Option<Receiver> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().rev().find(|binding| binding.name == name))
+            .and_then(|binding| binding.receiver)
+    }
+
+    /// 表达式的值属于哪种内置类型：字面量、有注解或初始值已知的变量、声明了返回类型的函数调用，
+    /// 以及对它们调用不修改接收者的方法
+    fn receiver(&self, expr: &Expr) -> Option<Receiver> {
+        match &expr.kind {
+            ExprKind::LiteralString(_) | ExprKind::Interpolation(_) => Some(Receiver::String),
+            ExprKind::ArrayLiteral(_) => Some(Receiver::Array),
+            ExprKind::MapLiteral(_) => Some(Receiver::Map),
+            ExprKind::SetLiteral(_) => Some(Receiver::Set),
+            ExprKind::Var(name) => self.receiver_of_name(name),
+            ExprKind::Call { name, .. } if !self.is_variable(name) => {
+                self.functions.get(name)?.as_ref().and_then(Receiver::of_type)
+            }
+            ExprKind::MethodCall { receiver, method, args } => {
+                self.receiver(receiver)?.pure_method(method, args.len())?
+            }
+            _ => None,
+        }
+    }
+
+    fn is_variable(&self, name: &str) -> bool {
+        self.scopes.iter().flatten().any(|binding| binding.name == name)
+    }
+
+    fn params(&mut self, params: &[Param]) -> Vec<Binding> {
+        for param in params {
+            if let Some(default) = &param.default {
+                self.expr(default);
+            }
+        }
+        params
+            .iter()
+            .map(|param| Binding {
+                name: param.name.clone(),
+                line: param.line,
+                col: param.col,
+                used: false,
+                checked: false,
+                receiver: Receiver::of_type(&param.ty),
+            })
+            .collect()
+    }
+
+    /// 在新的作用域中检查一串语句，返回其中第一条让后续语句无法执行的语句
+    fn block(&mut self, stmts: &[Statement], scope: Vec<Binding>, program: bool) -> Option<Exit> {
+        self.scopes.push(scope);
+        let mut exit: Option<Exit> = None;
+        let mut reported = false;
+        for (i, stmt) in stmts.iter().enumerate() {
+            if let (Some(exit), false) = (exit, reported) {
+                reported = true;
+                let hint = format!(
+                    "Execution never gets past the {} at {}:{}:{}",
+                    exit.what, self.file, exit.line, exit.col
+                );
+                self.warn(Rule::UnreachableCode, "Unreachable code".into(), stmt.line, stmt.col, Some(hint));
+            }
+            if let StatementKind::Expr(expr) = &stmt.kind {
+                if !(program && i + 1 == stmts.len()) {
+                    self.discarded(expr);
+                }
+            }
+            let leaves = self.statement(stmt);
+            exit = exit.or(leaves);
+        }
+        for binding in self.scopes.pop().unwrap_or_default() {
+            if binding.checked && !binding.used {
+                let hint = format!("Remove it, or rename it to '_{}' if that is intended", binding.name);
+                self.warn(
+                    Rule::UnusedVariable,
+                    format!("Variable '{}' is never used", binding.name),
+                    binding.line,
+                    binding.col,
+                    Some(hint),
+                );
+            }
+        }
+        exit
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> Option<Exit> {
+        let exit = |what| {
+            Some(Exit {
+                what,
+                line: stmt.line,
+                col: stmt.col,
+            })
+        };
+        match &stmt.kind {
+            StatementKind::Let { name, ty, value } => {
+                self.expr(value);
+                let receiver = match ty {
+                    Some(ty) => Receiver::of_type(ty),
+                    None => self.receiver(value),
+                };
+                self.bind(name, stmt.line, stmt.col, true);
+                if let Some(binding) = self.scopes.last_mut().and_then(|scope| scope.last_mut()) {
+                    if binding.name == *name {
+                        binding.receiver = receiver;
+                    }
+                }
+            }
+            StatementKind::LetTuple { names, value, .. } => {
+                self.expr(value);
+                for name in names {
+                    self.bind(name, stmt.line, stmt.col, true);
+                }
+            }
+            StatementKind::Ask { name, .. } => self.bind(name, stmt.line, stmt.col, true),
+            StatementKind::AskPrompt(_) | StatementKind::Import { .. } => {}
+            StatementKind::Say(expr)
+            | StatementKind::SayDebug(expr)
+            | StatementKind::Expr(expr)
+            | StatementKind::Spawn(expr) => self.expr(expr),
+            StatementKind::Assign { value, .. } => self.expr(value),
+            StatementKind::AssignIndex { array, index, value } => {
+                self.expr(array);
+                self.expr(index);
+                self.expr(value);
+            }
+            StatementKind::AssignField { object, value, .. } => {
+                self.expr(object);
+                self.expr(value);
+            }
+            StatementKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+                return exit("`return`");
+            }
+            StatementKind::Break(_) => return exit("`break`"),
+            StatementKind::Continue(_) => return exit("`continue`"),
+            StatementKind::Throw(value) => {
+                self.expr(value);
+                return exit("`bark`");
+            }
+            StatementKind::If {
+                condition,
+                body,
+                else_branch,
+            } => {
+                self.expr(condition);
+                if let Some(value) = const_bool(condition) {
+                    let hint = match (value, else_branch.is_some()) {
+                        (true, true) => "The `else` branch never runs",
+                        (true, false) => "The body always runs; the `if` can be removed",
+                        (false, _) => "The body never runs; use `@cfg(...)` to switch code off",
+                    };
+                    self.constant_condition(condition, value, hint);
+                }
+                let then = self.block(body, Vec::new(), false);
+                let other = else_branch.as_ref().and_then(|other| self.statement(other));
+                if then.is_some() && other.is_some() {
+                    return exit("`if` whose branches all leave");
+                }
+            }
+            StatementKind::LoopForever { body, .. } | StatementKind::TaskGroup(body) => {
+                self.block(body, Vec::new(), false);
+            }
+            StatementKind::LoopWhile { condition, body, .. } => {
+                self.expr(condition);
+                if let Some(value) = const_bool(condition) {
+                    let hint = match value {
+                        true => "Write `loop { ... }` for an endless loop",
+                        false => "The loop body never runs",
+                    };
+                    self.constant_condition(condition, value, hint);
+                }
+                self.block(body, Vec::new(), false);
+            }
+            StatementKind::LoopDoWhile { body, condition, .. } => {
+                self.block(body, Vec::new(), false);
+                self.expr(condition);
+                if let Some(value) = const_bool(condition) {
+                    let hint = match value {
+                        true => "Write `loop { ... }` for an endless loop",
+                        false => "The body runs exactly once; the loop can be removed",
+                    };
+                    self.constant_condition(condition, value, hint);
+                }
+            }
+            StatementKind::LoopRange {
+                var,
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => {
+                self.expr(start);
+                self.expr(end);
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+                self.scopes.push(Vec::new());
+                self.bind(var, stmt.line, stmt.col, true);
+                let vars = self.scopes.pop().unwrap_or_default();
+                self.block(body, vars, false);
+            }
+            StatementKind::LoopArray { var, array, body, .. } => {
+                self.expr(array);
+                self.scopes.push(Vec::new());
+                match var {
+                    LoopVar::Name(name) => self.bind(name, stmt.line, stmt.col, true),
+                    LoopVar::Tuple(names) => {
+                        for name in names {
+                            self.bind(name, stmt.line, stmt.col, true);
+                        }
+                    }
+                }
+                let vars = self.scopes.pop().unwrap_or_default();
+                self.block(body, vars, false);
+            }
+            StatementKind::Match { subject, arms, .. } => {
+                self.expr(subject);
+                for arm in arms {
+                    self.scopes.push(Vec::new());
+                    for pattern in &arm.patterns {
+                        let mut names = Vec::new();
+                        pattern.bindings(&mut names);
+                        for name in names {
+                            self.bind(name, pattern.line, pattern.col, false);
+                        }
+                    }
+                    let bindings = self.scopes.pop().unwrap_or_default();
+                    self.block(&arm.body, bindings, false);
+                }
+            }
+            StatementKind::FunDecl { params, body, .. } => self.function(params, body),
+            StatementKind::Block(body) => return self.block(body, Vec::new(), false),
+            StatementKind::TryCatchFinally {
+                body,
+                clauses,
+                finally,
+            } => {
+                self.block(body, Vec::new(), false);
+                for clause in clauses {
+                    self.scopes.push(Vec::new());
+                    self.bind(&clause.name, clause.line, clause.col, false);
+                    let caught = self.scopes.pop().unwrap_or_default();
+                    self.block(&clause.body, caught, false);
+                }
+                self.block(finally, Vec::new(), false);
+            }
+            StatementKind::RecordDecl { fields, methods, .. } => {
+                self.params(fields);
+                for method in methods {
+                    self.statement(method);
+                }
+            }
+            StatementKind::ProtocolDecl { members, .. } => {
+                self.params(members);
+            }
+            StatementKind::EnumDecl { .. } => {}
+        }
+        None
+    }
+
+    /// 函数与匿名函数：参数单独一层作用域，函数体中的定义可以遮蔽参数
+    fn function(&mut self, params: &[Param], body: &[Statement]) {
+        let params = self.params(params);
+        self.scopes.push(params);
+        self.block(body, Vec::new(), false);
+        self.scopes.pop();
+    }
+
+    fn constant_condition(&mut self, condition: &Expr, value: bool, hint: &str) {
+        self.warn(
+            Rule::ConstantCondition,
+            format!("Condition `{}` is always {}", condition, value),
+            condition.line,
+            condition.col,
+            Some(hint.to_string()),
+        );
+    }
+
+    /// 单独成句的表达式：有返回值的函数调用、不修改接收者的内置方法或没有副作用的表达式，其值被丢弃；
+    /// Map 的 insert / remove、Set 的 add / remove 与 Array 的 push / pop 原地修改变量，不在此列
+    fn discarded(&mut self, expr: &Expr) {
+        let call = match &expr.kind {
+            ExprKind::Await { expr: inner } => inner,
+            _ => expr,
+        };
+        if let ExprKind::MethodCall { receiver, method, args } = &call.kind {
+            let Some(ty) = self.receiver(receiver) else {
+                return;
+            };
+            if ty.pure_method(method, args.len()).is_some() {
+                let hint = format!(
+                    "`{}` only returns a value and leaves the {} unchanged; use the value or bind it with `let`",
+                    method,
+                    ty.name()
+                );
+                self.warn(
+                    Rule::DiscardedResult,
+                    format!("The result of `{}` is discarded", expr),
+                    expr.line,
+                    expr.col,
+                    Some(hint),
+                );
+            }
+        } else if let ExprKind::Call { name, .. } = &call.kind {
+            if self.is_variable(name) {
+                return;
+            }
+            if let Some(Some(ty)) = self.functions.get(name) {
+                let hint = format!("'{}' returns {}; use the value or bind it with `let`", name, ty);
+                self.warn(
+                    Rule::DiscardedResult,
+                    format!("The result of `{}` is discarded", expr),
+                    expr.line,
+                    expr.col,
+                    Some(hint),
+                );
+            }
+        } else if is_pure(expr) {
+            self.warn(
+                Rule::DiscardedResult,
+                format!("The value of `{}` is never used", expr),
+                expr.line,
+                expr.col,
+                Some("The expression has no effect; remove it or bind it with `let`".into()),
+            );
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::LiteralInt(_)
+            | ExprKind::LiteralLong(_)
+            | ExprKind::LiteralFloat(_)
+            | ExprKind::LiteralDouble(_)
+            | ExprKind::LiteralString(_)
+            | ExprKind::LiteralChar(_)
+            | ExprKind::LiteralBool(_)
+            | ExprKind::LiteralNopaw => {}
+            ExprKind::Var(name) => self.use_name(name),
+            ExprKind::Call { name, args } => {
+                self.use_name(name);
+                self.exprs(args);
+            }
+            ExprKind::Interpolation(parts)
+            | ExprKind::ArrayLiteral(parts)
+            | ExprKind::SetLiteral(parts)
+            | ExprKind::TupleLiteral(parts) => self.exprs(parts),
+            ExprKind::Formatted { expr: inner, .. }
+            | ExprKind::UnaryOp { expr: inner, .. }
+            | ExprKind::Cast { expr: inner, .. }
+            | ExprKind::TypeTest { expr: inner, .. }
+            | ExprKind::FieldAccess { expr: inner, .. }
+            | ExprKind::Await { expr: inner }
+            | ExprKind::Propagate { expr: inner }
+            | ExprKind::Tail { expr: inner } => self.expr(inner),
+            ExprKind::BinaryOp { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::MethodCall { receiver, args, .. } => {
+                self.expr(receiver);
+                self.exprs(args);
+            }
+            ExprKind::Range { start, end, step, .. } => {
+                self.expr(start);
+                self.expr(end);
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+            }
+            ExprKind::MapLiteral(entries) => {
+                for (key, value) in entries {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::Index { array, index } => {
+                self.expr(array);
+                self.expr(index);
+            }
+            ExprKind::RecordInit { fields, .. } => {
+                for (_, value) in fields {
+                    self.expr(value);
+                }
+            }
+            ExprKind::AsyncBlock(body) => {
+                self.block(body, Vec::new(), false);
+            }
+            ExprKind::Lambda { params, body, .. } => self.function(params, body),
+        }
+    }
+}
+
+/// 求值没有副作用的表达式：字面量、变量与它们的运算组合
+fn is_pure(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::LiteralInt(_)
+        | ExprKind::LiteralLong(_)
+        | ExprKind::LiteralFloat(_)
+        | ExprKind::LiteralDouble(_)
+        | ExprKind::LiteralString(_)
+        | ExprKind::LiteralChar(_)
+        | ExprKind::LiteralBool(_)
+        | ExprKind::LiteralNopaw
+        | ExprKind::Var(_)
+        | ExprKind::Lambda { .. } => true,
+        ExprKind::Interpolation(parts)
+        | ExprKind::ArrayLiteral(parts)
+        | ExprKind::SetLiteral(parts)
+        | ExprKind::TupleLiteral(parts) => parts.iter().all(is_pure),
+        ExprKind::Formatted { expr: inner, .. }
+        | ExprKind::UnaryOp { expr: inner, .. }
+        | ExprKind::Cast { expr: inner, .. }
+        | ExprKind::TypeTest { expr: inner, .. }
+        | ExprKind::FieldAccess { expr: inner, .. } => is_pure(inner),
+        ExprKind::BinaryOp { left, right, .. } => is_pure(left) && is_pure(right),
+        ExprKind::MapLiteral(entries) => entries.iter().all(|(k, v)| is_pure(k) && is_pure(v)),
+        ExprKind::RecordInit { fields, .. } => fields.iter().all(|(_, v)| is_pure(v)),
+        _ => false,
+    }
+}
+
+/// 只由字面量组成、值在检查时就确定的条件
+fn const_bool(expr: &Expr) -> Option<bool> {
+    match &expr.kind {
+        ExprKind::LiteralBool(b) => Some(*b),
+        ExprKind::UnaryOp { op, expr: inner } if op == "!" => const_bool(inner).map(|b| !b),
+        ExprKind::BinaryOp { op, left, right } => {
+            if let (Some(l), Some(r)) = (const_int(left), const_int(right)) {
+                return match op {
+                    BinaryOp::EqEq => Some(l == r),
+                    BinaryOp::NotEq => Some(l != r),
+                    BinaryOp::Lt => Some(l < r),
+                    BinaryOp::Le => Some(l <= r),
+                    BinaryOp::Gt => Some(l > r),
+                    BinaryOp::Ge => Some(l >= r),
+                    _ => None,
+                };
+            }
+            let (l, r) = (const_bool(left)?, const_bool(right)?);
+            match op {
+                BinaryOp::And => Some(l && r),
+                BinaryOp::Or => Some(l || r),
+                BinaryOp::EqEq => Some(l == r),
+                BinaryOp::NotEq => Some(l != r),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 整数字面量（可带负号）的值
+fn const_int(expr: &Expr) -> Option<i64> {
+    match &expr.kind {
+        ExprKind::LiteralInt(n) => Some(*n as i64),
+        ExprKind::LiteralLong(n) => Some(*n),
+        ExprKind::UnaryOp { op, expr: inner } if op == "-" => const_int(inner).and_then(i64::checked_neg),
+        _ => None,
+    }
+}
@@ -1,3 +1,7 @@
+pub mod graph;
+pub mod imports;
+pub mod lint;
+pub mod purity;
 pub mod scope;
 pub mod type_checker;
 pub mod types;
\ No newline at end of file
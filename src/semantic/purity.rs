@@ -0,0 +1,517 @@
+// src/semantic/purity.rs
+//
+// `@pure` 函数的副作用分析：函数体不能输出或读取输入、不能使用有副作用的标准库模块、
+// 不能读写函数之外的变量，调用的函数也必须没有副作用。
+// 没有标注的 `fun` 与 record 方法同样会被推断，`@pure` 函数可以调用其中没有副作用的那些
+
+use crate::ast::expr::{Expr, ExprKind};
+use crate::ast::method::Method;
+use crate::ast::param::Param;
+use crate::ast::statement::{LoopVar, Statement, StatementKind};
+use crate::ast::type_node::TypeNodeKind;
+use crate::error::error::PawError;
+use crate::interpreter::stdlib::{is_pure_module, is_std_module};
+use crate::semantic::scope::{PawType, Scope};
+use std::collections::{HashMap, HashSet};
+
+/// 有副作用的内置函数；只读求值中同样不能调用
+pub(crate) const IMPURE_BUILTINS: [&str; 6] = ["sleep", "assert_snapshot", "forall", "mock_ask", "mock_time", "seed_random"];
+
+/// 函数体中第一处副作用：说明与位置
+struct Effect {
+    what: String,
+    line: usize,
+    col: usize,
+}
+
+/// 函数体之外的名字是什么
+#[derive(Clone, Copy)]
+enum Outer {
+    /// 没有副作用的函数、类型名、`math` 等模块，或检查器会另行报错的未知名字
+    Harmless,
+    /// 有副作用的函数
+    Function,
+    /// 变量，读写它都依赖函数之外的状态
+    Variable,
+    /// 有副作用或成员未知的模块
+    Module,
+}
+
+/// 推断一串语句中 `fun` 与 record 方法有没有副作用并登记到作用域，再验证其中的 `@pure` 函数（E3051）。
+/// 需在函数签名预注册之后、逐条检查语句之前调用，这时同一代码块中的 let 与 import 还未定义，按语句收集
+pub(crate) fn check_block(file: &str, stmts: &[Statement], scope: &mut Scope) -> Result<(), Box<PawError>> {
+    let mut functions: Vec<(&str, &Statement)> = Vec::new();
+    let mut methods: Vec<(&str, &Statement)> = Vec::new();
+    let mut block_names: HashMap<&str, Outer> = HashMap::new();
+    for stmt in stmts {
+        match &stmt.kind {
+            StatementKind::FunDecl { name, .. } => functions.push((name, stmt)),
+            StatementKind::RecordDecl { methods: decls, .. } => {
+                for method in decls {
+                    if let StatementKind::FunDecl { name, .. } = &method.kind {
+                        methods.push((name, method));
+                    }
+                }
+            }
+            StatementKind::Let { name, .. } | StatementKind::Ask { name, .. } => {
+                block_names.insert(name, Outer::Variable);
+            }
+            StatementKind::LetTuple { names, .. } => {
+                for name in names {
+                    block_names.insert(name, Outer::Variable);
+                }
+            }
+            StatementKind::Import { module, alias } => {
+                let harmless = is_std_module(module) && is_pure_module(module);
+                block_names.insert(alias, if harmless { Outer::Harmless } else { Outer::Module });
+            }
+            _ => {}
+        }
+    }
+    if functions.is_empty() && methods.is_empty() {
+        return Ok(());
+    }
+    // 先假定全部没有副作用，反复去掉有副作用的，直到不再变化；同名的重载或方法有一个有副作用就都算有
+    let mut pure_functions: HashSet<&str> = functions.iter().map(|(name, _)| *name).collect();
+    let method_names: HashSet<&str> = methods.iter().map(|(name, _)| *name).collect();
+    let mut pure_methods = method_names.clone();
+    loop {
+        let analysis = Analysis {
+            scope,
+            block_names: &block_names,
+            pure_functions: &pure_functions,
+            pure_methods: &pure_methods,
+            methods: &method_names,
+        };
+        let impure_functions: Vec<&str> = functions
+            .iter()
+            .filter(|(name, stmt)| pure_functions.contains(name) && analysis.first_effect(stmt).is_some())
+            .map(|(name, _)| *name)
+            .collect();
+        let impure_methods: Vec<&str> = methods
+            .iter()
+            .filter(|(name, stmt)| pure_methods.contains(name) && analysis.first_effect(stmt).is_some())
+            .map(|(name, _)| *name)
+            .collect();
+        if impure_functions.is_empty() && impure_methods.is_empty() {
+            break;
+        }
+        for name in impure_functions {
+            pure_functions.remove(name);
+        }
+        for name in impure_methods {
+            pure_methods.remove(name);
+        }
+    }
+    for name in &pure_functions {
+        scope.mark_effect_free(name);
+    }
+    for (name, _) in &methods {
+        if !pure_methods.contains(name) {
+            scope.mark_impure_method(name);
+        }
+    }
+    let analysis = Analysis {
+        scope,
+        block_names: &block_names,
+        pure_functions: &pure_functions,
+        pure_methods: &pure_methods,
+        methods: &method_names,
+    };
+    for (name, stmt) in &functions {
+        if !stmt.is_pure() {
+            continue;
+        }
+        let effect = match &stmt.kind {
+            StatementKind::FunDecl { is_async: true, .. } => Some(Effect {
+                what: "is async".into(),
+                line: stmt.line,
+                col: stmt.col,
+            }),
+            _ => analysis.first_effect(stmt),
+        };
+        if let Some(effect) = effect {
+            return Err(Box::new(PawError::Type {
+                file: file.to_string(),
+                code: "E3051",
+                message: format!("`@pure` function '{}' {}", name, effect.what),
+                line: effect.line,
+                column: effect.col,
+                snippet: None,
+                hint: Some(
+                    "A pure function only computes its result from its arguments: no `say` or `ask`, \
+                     no fs, time, random, term or signal modules, no variables from outside the function; \
+                     remove `@pure` or move the effect to the caller"
+                        .into(),
+                ),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// 一轮分析用到的外部信息
+struct Analysis<'a> {
+    scope: &'a Scope,
+    /// 同一代码块中尚未定义的 let、ask 与 import
+    block_names: &'a HashMap<&'a str, Outer>,
+    /// 本轮仍假定没有副作用的函数与方法
+    pure_functions: &'a HashSet<&'a str>,
+    pure_methods: &'a HashSet<&'a str>,
+    /// 同一代码块中声明的全部方法名
+    methods: &'a HashSet<&'a str>,
+}
+
+impl Analysis<'_> {
+    /// 函数声明中第一处副作用
+    fn first_effect(&self, stmt: &Statement) -> Option<Effect> {
+        let StatementKind::FunDecl { params, body, .. } = &stmt.kind else {
+            return None;
+        };
+        let mut walker = Walker {
+            analysis: self,
+            scopes: Vec::new(),
+            effect: None,
+        };
+        // 函数类型的参数由调用方传入，调用或传递它们的效果未知
+        let frame = params
+            .iter()
+            .map(|p| (p.name.clone(), matches!(p.ty.kind, TypeNodeKind::Function { .. })))
+            .collect();
+        walker.scopes.push(frame);
+        walker.defaults(params);
+        walker.block(body);
+        walker.effect
+    }
+
+    fn outer(&self, name: &str) -> Outer {
+        if let Some(&kind) = self.block_names.get(name) {
+            return kind;
+        }
+        let scope = self.scope;
+        if self.pure_functions.contains(name) || scope.is_effect_free(name) {
+            return Outer::Harmless;
+        }
+        if scope.record_fields(name).is_some()
+            || scope.enum_variants(name).is_some()
+            || scope.protocol_members(name).is_some()
+        {
+            return Outer::Harmless;
+        }
+        if let Some(path) = scope.std_module(name) {
+            return match is_pure_module(&path) {
+                true => Outer::Harmless,
+                false => Outer::Module,
+            };
+        }
+        match scope.lookup(name) {
+            Some(PawType::Module) => Outer::Module,
+            Some(_) if scope.is_function(name) => Outer::Function,
+            Some(_) => Outer::Variable,
+            None if IMPURE_BUILTINS.contains(&name) => Outer::Function,
+            None => Outer::Harmless,
+        }
+    }
+}
+
+/// 遍历一个函数体，记录第一处副作用
+struct Walker<'a> {
+    analysis: &'a Analysis<'a>,
+    /// 函数体内定义的名字；值为 true 的是函数类型的参数
+    scopes: Vec<HashMap<String, bool>>,
+    effect: Option<Effect>,
+}
+
+impl Walker<'_> {
+    fn report(&mut self, what: String, line: usize, col: usize) {
+        if self.effect.is_none() {
+            self.effect = Some(Effect { what, line, col });
+        }
+    }
+
+    fn bind(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), false);
+        }
+    }
+
+    /// 函数体内的名字：Some(true) 为函数类型的参数，None 表示在函数之外
+    fn local(&self, name: &str) -> Option<bool> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    /// 使用一个名字：`called` 为 true 时是调用它
+    fn use_name(&mut self, name: &str, called: bool, line: usize, col: usize) {
+        let verb = if called { "calls" } else { "uses" };
+        match self.local(name) {
+            Some(false) => {}
+            Some(true) => self.report(
+                format!("{} the function parameter '{}', whose effects are unknown", verb, name),
+                line,
+                col,
+            ),
+            None => match self.analysis.outer(name) {
+                Outer::Harmless => {}
+                Outer::Function => self.report(format!("{} '{}', which is not pure", verb, name), line, col),
+                Outer::Variable => self.report(
+                    format!("reads '{}', which is defined outside the function", name),
+                    line,
+                    col,
+                ),
+                Outer::Module => self.report(format!("uses the module '{}'", name), line, col),
+            },
+        }
+    }
+
+    /// 赋值目标的根变量必须在函数体内定义
+    fn assign_to(&mut self, name: &str, line: usize, col: usize) {
+        if self.local(name).is_none() {
+            self.report(
+                format!("assigns to '{}', which is defined outside the function", name),
+                line,
+                col,
+            );
+        }
+    }
+
+    fn defaults(&mut self, params: &[Param]) {
+        for param in params {
+            if let Some(default) = &param.default {
+                self.expr(default);
+            }
+        }
+    }
+
+    /// 新的作用域；其中的 `fun` 可以在声明之前调用，先定义它们
+    fn block(&mut self, stmts: &[Statement]) {
+        let mut frame = HashMap::new();
+        for stmt in stmts {
+            if let StatementKind::FunDecl { name, .. } = &stmt.kind {
+                frame.insert(name.clone(), false);
+            }
+        }
+        self.scopes.push(frame);
+        for stmt in stmts {
+            if self.effect.is_some() {
+                break;
+            }
+            self.statement(stmt);
+        }
+        self.scopes.pop();
+    }
+
+    fn function(&mut self, params: &[Param], body: &[Statement]) {
+        self.scopes.push(params.iter().map(|p| (p.name.clone(), false)).collect());
+        self.defaults(params);
+        self.block(body);
+        self.scopes.pop();
+    }
+
+    fn statement(&mut self, stmt: &Statement) {
+        let (line, col) = (stmt.line, stmt.col);
+        match &stmt.kind {
+            StatementKind::Let { name, value, .. } => {
+                self.expr(value);
+                self.bind(name);
+            }
+            StatementKind::LetTuple { names, value, .. } => {
+                self.expr(value);
+                for name in names {
+                    self.bind(name);
+                }
+            }
+            StatementKind::Say(_) | StatementKind::SayDebug(_) => {
+                self.report("prints with `say`".into(), line, col)
+            }
+            StatementKind::Ask { .. } | StatementKind::AskPrompt(_) => {
+                self.report("reads input with `ask`".into(), line, col)
+            }
+            StatementKind::Spawn(_) => self.report("starts a task with `spawn`".into(), line, col),
+            StatementKind::TaskGroup(_) => self.report("starts a task group".into(), line, col),
+            StatementKind::Import { module, alias } => {
+                if !(is_std_module(module) && is_pure_module(module)) {
+                    self.report(format!("imports the module '{}'", module.join(".")), line, col);
+                }
+                self.bind(alias);
+            }
+            StatementKind::Expr(expr) | StatementKind::Throw(expr) => self.expr(expr),
+            StatementKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            StatementKind::Break(_) | StatementKind::Continue(_) => {}
+            StatementKind::Assign { name, value } => {
+                self.assign_to(name, line, col);
+                self.expr(value);
+            }
+            StatementKind::AssignIndex { array, index, value } => {
+                self.target(array, line, col);
+                self.expr(index);
+                self.expr(value);
+            }
+            StatementKind::AssignField { object, value, .. } => {
+                self.target(object, line, col);
+                self.expr(value);
+            }
+            StatementKind::If {
+                condition,
+                body,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.block(body);
+                if let Some(other) = else_branch {
+                    self.statement(other);
+                }
+            }
+            StatementKind::LoopForever { body, .. } | StatementKind::Block(body) => self.block(body),
+            StatementKind::LoopWhile { condition, body, .. } | StatementKind::LoopDoWhile { body, condition, .. } => {
+                self.expr(condition);
+                self.block(body);
+            }
+            StatementKind::LoopRange {
+                var,
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => {
+                self.expr(start);
+                self.expr(end);
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+                self.scopes.push(HashMap::from([(var.clone(), false)]));
+                self.block(body);
+                self.scopes.pop();
+            }
+            StatementKind::LoopArray { var, array, body, .. } => {
+                self.expr(array);
+                let names = match var {
+                    LoopVar::Name(name) => vec![name.clone()],
+                    LoopVar::Tuple(names) => names.clone(),
+                };
+                self.scopes.push(names.into_iter().map(|n| (n, false)).collect());
+                self.block(body);
+                self.scopes.pop();
+            }
+            StatementKind::Match { subject, arms, .. } => {
+                self.expr(subject);
+                for arm in arms {
+                    let mut names = Vec::new();
+                    for pattern in &arm.patterns {
+                        pattern.bindings(&mut names);
+                    }
+                    self.scopes.push(names.into_iter().map(|n| (n.to_string(), false)).collect());
+                    self.block(&arm.body);
+                    self.scopes.pop();
+                }
+            }
+            StatementKind::FunDecl { params, body, .. } => self.function(params, body),
+            StatementKind::TryCatchFinally {
+                body,
+                clauses,
+                finally,
+            } => {
+                self.block(body);
+                for clause in clauses {
+                    self.scopes.push(HashMap::from([(clause.name.clone(), false)]));
+                    self.block(&clause.body);
+                    self.scopes.pop();
+                }
+                self.block(finally);
+            }
+            StatementKind::RecordDecl { .. } | StatementKind::ProtocolDecl { .. } | StatementKind::EnumDecl { .. } => {}
+        }
+    }
+
+    /// 下标或字段赋值的目标：沿着下标与字段找到根变量
+    fn target(&mut self, target: &Expr, line: usize, col: usize) {
+        match &target.kind {
+            ExprKind::Var(name) => self.assign_to(name, line, col),
+            ExprKind::Index { array, index } => {
+                self.expr(index);
+                self.target(array, line, col);
+            }
+            ExprKind::FieldAccess { expr, .. } => self.target(expr, line, col),
+            _ => self.expr(target),
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::LiteralInt(_)
+            | ExprKind::LiteralLong(_)
+            | ExprKind::LiteralFloat(_)
+            | ExprKind::LiteralDouble(_)
+            | ExprKind::LiteralString(_)
+            | ExprKind::LiteralChar(_)
+            | ExprKind::LiteralBool(_)
+            | ExprKind::LiteralNopaw => {}
+            ExprKind::Var(name) => self.use_name(name, false, expr.line, expr.col),
+            ExprKind::Call { name, args } => {
+                self.exprs(args);
+                self.use_name(name, true, expr.line, expr.col);
+            }
+            ExprKind::Interpolation(parts)
+            | ExprKind::ArrayLiteral(parts)
+            | ExprKind::SetLiteral(parts)
+            | ExprKind::TupleLiteral(parts) => self.exprs(parts),
+            ExprKind::Formatted { expr: inner, .. }
+            | ExprKind::UnaryOp { expr: inner, .. }
+            | ExprKind::Cast { expr: inner, .. }
+            | ExprKind::TypeTest { expr: inner, .. }
+            | ExprKind::FieldAccess { expr: inner, .. }
+            | ExprKind::Propagate { expr: inner }
+            | ExprKind::Tail { expr: inner } => self.expr(inner),
+            ExprKind::Await { .. } => self.report("awaits a future".into(), expr.line, expr.col),
+            ExprKind::AsyncBlock(_) => self.report("starts an `async` block".into(), expr.line, expr.col),
+            ExprKind::BinaryOp { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::MethodCall { receiver, method, args } => {
+                self.expr(receiver);
+                self.exprs(args);
+                if let Method::Other(name) = method {
+                    let analysis = self.analysis;
+                    let assumed = analysis.pure_methods.contains(name.as_str());
+                    if !assumed && (analysis.methods.contains(name.as_str()) || analysis.scope.is_impure_method(name)) {
+                        self.report(format!("calls the method '{}', which is not pure", name), expr.line, expr.col);
+                    }
+                }
+            }
+            ExprKind::Range { start, end, step, .. } => {
+                self.expr(start);
+                self.expr(end);
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+            }
+            ExprKind::MapLiteral(entries) => {
+                for (key, value) in entries {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::Index { array, index } => {
+                self.expr(array);
+                self.expr(index);
+            }
+            ExprKind::RecordInit { fields, .. } => {
+                for (_, value) in fields {
+                    self.expr(value);
+                }
+            }
+            ExprKind::Lambda { params, body, .. } => self.function(params, body),
+        }
+    }
+}
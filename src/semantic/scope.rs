@@ -1,7 +1,7 @@
 // src/semantic/scope.rs
 
 use crate::error::error::PawError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 pub(crate) use crate::semantic::types::PawType;
 
@@ -9,14 +9,71 @@ pub(crate) use crate::semantic::types::PawType;
 #[derive(Clone, Debug)]
 pub struct Scope {
     symbols: HashMap<String, PawType>,
+    /// 符号名 → 定义所在的文件与位置；宿主注入的符号没有位置
+    positions: HashMap<String, (String, usize, usize)>,
+    /// record 名 → 字段列表
+    records: HashMap<String, Vec<(String, PawType)>>,
+    /// record 名 → 方法列表，类型不含 `self` 参数
+    methods: HashMap<String, Vec<(String, PawType)>>,
+    /// enum 名 → 变体列表（变体名与数据类型）
+    enums: HashMap<String, Vec<(String, Vec<PawType>)>>,
+    /// protocol 名 → 要求的成员列表
+    protocols: HashMap<String, Vec<(String, PawType)>>,
+    /// record 名 → 声明遵循的 protocol
+    conformances: HashMap<String, Vec<String>>,
+    /// 模块别名 → 导入路径，只登记检查期已知成员的标准库模块
+    std_modules: HashMap<String, Vec<String>>,
+    /// 带默认值参数的函数名 → 必须传入的参数个数
+    required: HashMap<String, usize>,
+    /// 标注了 `@deprecated` 的名字 → 说明
+    deprecated: HashMap<String, Option<String>>,
+    /// `fun` 声明的名字 → 各个重载的函数类型，按声明顺序排列
+    functions: HashMap<String, Vec<PawType>>,
+    /// `fun` 与 record 方法（键为 `Record.method`）的参数名；重载的函数不登记
+    param_names: HashMap<String, Vec<String>>,
+    /// 推断为没有副作用的 `fun`
+    effect_free: HashSet<String>,
+    /// 有副作用的 record 方法名（不区分 record）
+    impure_methods: HashSet<String>,
+    /// 声明了 `barks` 的 `fun` 与 record 方法（键为 `Record.method`）
+    barking: HashSet<String>,
+    /// 被 `x is T` 收窄的变量 → 收窄后的类型与收窄的编号；声明的类型仍在外层的 `symbols` 中
+    narrowed: HashMap<String, (PawType, usize)>,
+    /// 泛型 record 名 → 类型参数
+    record_params: HashMap<String, Vec<String>>,
+    /// 泛型函数或 record 声明内可见的类型参数
+    type_params: Vec<String>,
     parent: Option<Arc<Scope>>,
 }
 
+impl Default for Scope {
+    fn default() -> Self {
+        Scope::new()
+    }
+}
+
 impl Scope {
     /// 创建一个新的空作用域
     pub fn new() -> Self {
         Scope {
             symbols: HashMap::new(),
+            positions: HashMap::new(),
+            records: HashMap::new(),
+            methods: HashMap::new(),
+            enums: HashMap::new(),
+            protocols: HashMap::new(),
+            conformances: HashMap::new(),
+            std_modules: HashMap::new(),
+            required: HashMap::new(),
+            deprecated: HashMap::new(),
+            functions: HashMap::new(),
+            param_names: HashMap::new(),
+            effect_free: HashSet::new(),
+            impure_methods: HashSet::new(),
+            barking: HashSet::new(),
+            narrowed: HashMap::new(),
+            record_params: HashMap::new(),
+            type_params: Vec::new(),
             parent: None,
         }
     }
@@ -25,11 +82,28 @@ impl Scope {
     pub fn with_parent(parent: &Scope) -> Self {
         Scope {
             symbols: HashMap::new(),
+            positions: HashMap::new(),
+            records: HashMap::new(),
+            methods: HashMap::new(),
+            enums: HashMap::new(),
+            protocols: HashMap::new(),
+            conformances: HashMap::new(),
+            std_modules: HashMap::new(),
+            required: HashMap::new(),
+            deprecated: HashMap::new(),
+            functions: HashMap::new(),
+            param_names: HashMap::new(),
+            effect_free: HashSet::new(),
+            impure_methods: HashSet::new(),
+            barking: HashSet::new(),
+            narrowed: HashMap::new(),
+            record_params: HashMap::new(),
+            type_params: Vec::new(),
             parent: Some(Arc::new(parent.clone())),
         }
     }
 
-    /// 在当前作用域中定义一个新符号，若已存在则返回 Err
+    /// 在当前作用域中定义一个新符号，若已存在则返回 Err，提示中给出先前定义的位置
     pub fn define(
         &mut self,
         name: &str,
@@ -37,29 +111,364 @@ impl Scope {
         line: usize,
         column: usize,
         filename: &str,
-    ) -> Result<(), PawError> {
+    ) -> Result<(), Box<PawError>> {
         if self.symbols.contains_key(name) {
-            Err(PawError::DuplicateDefinition {
+            Err(Box::new(PawError::DuplicateDefinition {
                 file: filename.to_string(),
                 code: "E2005",
                 name: name.to_string(),
                 line,
                 column,
                 snippet: None,
-                hint: Some("Try a different name".into()),
-            })
+                hint: Some(match self.defined_at(name) {
+                    Some(at) => format!("'{}' is already defined at {}; try a different name", name, at),
+                    None => "Try a different name".into(),
+                }),
+            }))
         } else {
             self.symbols.insert(name.to_string(), ty);
+            self.positions
+                .insert(name.to_string(), (filename.to_string(), line, column));
             Ok(())
         }
     }
 
+    /// 定义一个 `fun`；当前作用域中已有同名的 `fun` 时，参数类型不同则作为重载加入，
+    /// 相同则与其它重复定义一样报 E2005
+    pub fn define_function(
+        &mut self,
+        name: &str,
+        ty: PawType,
+        line: usize,
+        column: usize,
+        filename: &str,
+    ) -> Result<(), Box<PawError>> {
+        let PawType::Function(params, _) = &ty else {
+            return self.define(name, ty, line, column, filename);
+        };
+        let Some(overloads) = self.functions.get_mut(name) else {
+            self.define(name, ty.clone(), line, column, filename)?;
+            self.functions.insert(name.to_string(), vec![ty]);
+            return Ok(());
+        };
+        if !overloads.iter().any(|o| matches!(o, PawType::Function(p, _) if p == params)) {
+            overloads.push(ty);
+            return Ok(());
+        }
+        Err(Box::new(PawError::DuplicateDefinition {
+            file: filename.to_string(),
+            code: "E2005",
+            name: name.to_string(),
+            line,
+            column,
+            snippet: None,
+            hint: Some(match self.defined_at(name) {
+                Some(at) => format!(
+                    "'{}' with the same parameter types is already defined (first at {}); overloads must differ in their parameters",
+                    name, at
+                ),
+                None => "Overloads must differ in their parameters".into(),
+            }),
+        }))
+    }
+
+    /// 名字对应的重载列表；只看最近的同名定义，它不是有多个重载的 `fun` 时返回 None
+    pub fn overloads(&self, name: &str) -> Option<Vec<PawType>> {
+        if self.symbols.contains_key(name) {
+            self.functions.get(name).filter(|o| o.len() > 1).cloned()
+        } else if let Some(parent) = &self.parent {
+            parent.overloads(name)
+        } else {
+            None
+        }
+    }
+
+    /// 定义或覆盖当前作用域中的符号，供宿主注入全局变量
+    pub fn set(&mut self, name: &str, ty: PawType) {
+        self.functions.remove(name);
+        self.positions.remove(name);
+        self.symbols.insert(name.to_string(), ty);
+    }
+
     /// 定义一个模块别名
-    pub fn define_module(&mut self, alias: &str, line: usize, col: usize, file: &str) -> Result<(), PawError> {
+    pub fn define_module(&mut self, alias: &str, line: usize, col: usize, file: &str) -> Result<(), Box<PawError>> {
         self.define(alias, PawType::Module, line, col, file)
     }
 
-    /// 向上查找符号类型，若未找到返回 None
+    /// 登记一个指向标准库模块的别名（别名本身需另行 define）
+    pub fn define_std_module(&mut self, alias: &str, path: Vec<String>) {
+        self.std_modules.insert(alias.to_string(), path);
+    }
+
+    /// 向上查找别名对应的标准库模块路径；文件模块的成员在检查期未知，返回 None
+    pub fn std_module(&self, alias: &str) -> Option<Vec<String>> {
+        if let Some(path) = self.std_modules.get(alias) {
+            Some(path.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.std_module(alias)
+        } else {
+            None
+        }
+    }
+
+    /// 登记函数必须传入的参数个数（其余参数有默认值）
+    pub fn define_required(&mut self, name: &str, count: usize) {
+        self.required.insert(name.to_string(), count);
+    }
+
+    /// 向上查找名字对应函数必须传入的参数个数；遇到同名的其他定义时停止，返回 None
+    pub fn required_params(&self, name: &str) -> Option<usize> {
+        if self.symbols.contains_key(name) {
+            self.required.get(name).copied()
+        } else if let Some(parent) = &self.parent {
+            parent.required_params(name)
+        } else {
+            None
+        }
+    }
+
+    /// 登记函数或方法的参数名，供调用处的错误指明是哪个参数
+    pub fn define_param_names(&mut self, name: &str, names: Vec<String>) {
+        self.param_names.insert(name.to_string(), names);
+    }
+
+    /// 向上查找函数或方法的参数名；遇到同名的其他定义时停止，返回 None
+    pub fn param_names(&self, name: &str) -> Option<Vec<String>> {
+        if let Some(names) = self.param_names.get(name) {
+            Some(names.clone())
+        } else if self.symbols.contains_key(name) {
+            None
+        } else if let Some(parent) = &self.parent {
+            parent.param_names(name)
+        } else {
+            None
+        }
+    }
+
+    /// 把当前作用域中的名字标记为已弃用
+    pub fn deprecate(&mut self, name: &str, note: Option<String>) {
+        self.deprecated.insert(name.to_string(), note);
+    }
+
+    /// 名字是否已弃用，是则返回说明；与 `required_params` 一样只看最近的同名定义
+    pub fn deprecation(&self, name: &str) -> Option<Option<String>> {
+        if self.symbols.contains_key(name) {
+            self.deprecated.get(name).cloned()
+        } else if let Some(parent) = &self.parent {
+            parent.deprecation(name)
+        } else {
+            None
+        }
+    }
+
+    /// 把当前作用域中的 `fun` 标记为没有副作用
+    pub fn mark_effect_free(&mut self, name: &str) {
+        self.effect_free.insert(name.to_string());
+    }
+
+    /// 名字是否为没有副作用的 `fun`；只看最近的同名定义
+    pub fn is_effect_free(&self, name: &str) -> bool {
+        if self.symbols.contains_key(name) {
+            self.effect_free.contains(name)
+        } else if let Some(parent) = &self.parent {
+            parent.is_effect_free(name)
+        } else {
+            false
+        }
+    }
+
+    /// 名字最近的定义是否为 `fun` 声明
+    pub fn is_function(&self, name: &str) -> bool {
+        if self.symbols.contains_key(name) {
+            self.functions.contains_key(name)
+        } else if let Some(parent) = &self.parent {
+            parent.is_function(name)
+        } else {
+            false
+        }
+    }
+
+    /// 登记一个有副作用的 record 方法名
+    pub fn mark_impure_method(&mut self, name: &str) {
+        self.impure_methods.insert(name.to_string());
+    }
+
+    /// 当前或外层作用域中是否有同名的方法有副作用
+    pub fn is_impure_method(&self, name: &str) -> bool {
+        self.impure_methods.contains(name) || self.parent.as_ref().is_some_and(|p| p.is_impure_method(name))
+    }
+
+    /// 登记声明了 `barks` 的函数或方法
+    pub fn mark_barking(&mut self, name: &str) {
+        self.barking.insert(name.to_string());
+    }
+
+    /// 名字最近的定义是否声明了 `barks`；方法名（`Record.method`）不在符号表中，向外层查找
+    pub fn is_barking(&self, name: &str) -> bool {
+        if self.barking.contains(name) {
+            true
+        } else if self.symbols.contains_key(name) {
+            false
+        } else {
+            self.parent.as_ref().is_some_and(|p| p.is_barking(name))
+        }
+    }
+
+    /// 登记 record 的字段列表（名字本身需另行 define）
+    pub fn define_record(&mut self, name: &str, fields: Vec<(String, PawType)>) {
+        self.records.insert(name.to_string(), fields);
+    }
+
+    /// 向上查找 record 的字段列表
+    pub fn record_fields(&self, name: &str) -> Option<Vec<(String, PawType)>> {
+        if let Some(fields) = self.records.get(name) {
+            Some(fields.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.record_fields(name)
+        } else {
+            None
+        }
+    }
+
+    /// 登记泛型 record 的类型参数
+    pub fn define_record_params(&mut self, name: &str, params: Vec<String>) {
+        self.record_params.insert(name.to_string(), params);
+    }
+
+    /// 向上查找泛型 record 的类型参数；非泛型 record 为 None
+    pub fn record_params(&self, name: &str) -> Option<Vec<String>> {
+        if let Some(params) = self.record_params.get(name) {
+            Some(params.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.record_params(name)
+        } else {
+            None
+        }
+    }
+
+    /// 在当前作用域中引入类型参数，声明内的类型注解可以引用它们
+    pub fn define_type_params(&mut self, params: &[String]) {
+        self.type_params.extend(params.iter().cloned());
+    }
+
+    /// 名字是否是外层声明引入的类型参数
+    pub fn is_type_param(&self, name: &str) -> bool {
+        self.type_params.iter().any(|p| p == name) || self.parent.as_ref().is_some_and(|p| p.is_type_param(name))
+    }
+
+    /// 登记 record 的方法列表
+    pub fn define_methods(&mut self, record: &str, methods: Vec<(String, PawType)>) {
+        self.methods.insert(record.to_string(), methods);
+    }
+
+    /// 向上查找 record 的方法列表
+    pub fn record_methods(&self, record: &str) -> Option<Vec<(String, PawType)>> {
+        if let Some(methods) = self.methods.get(record) {
+            Some(methods.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.record_methods(record)
+        } else {
+            None
+        }
+    }
+
+    /// record 的字段与方法，可以用 `r.name` 读取或以 `r.name(...)` 调用
+    pub fn record_members(&self, record: &str) -> Option<Vec<(String, PawType)>> {
+        let mut members = self.record_fields(record)?;
+        members.extend(self.record_methods(record).unwrap_or_default());
+        Some(members)
+    }
+
+    /// 登记 enum 的变体列表（名字本身需另行 define）
+    pub fn define_enum(&mut self, name: &str, variants: Vec<(String, Vec<PawType>)>) {
+        self.enums.insert(name.to_string(), variants);
+    }
+
+    /// 向上查找 enum 的变体列表
+    pub fn enum_variants(&self, name: &str) -> Option<Vec<(String, Vec<PawType>)>> {
+        if let Some(variants) = self.enums.get(name) {
+            Some(variants.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.enum_variants(name)
+        } else {
+            None
+        }
+    }
+
+    /// 登记 protocol 要求的成员列表
+    pub fn define_protocol(&mut self, name: &str, members: Vec<(String, PawType)>) {
+        self.protocols.insert(name.to_string(), members);
+    }
+
+    /// 向上查找 protocol 要求的成员列表
+    pub fn protocol_members(&self, name: &str) -> Option<Vec<(String, PawType)>> {
+        if let Some(members) = self.protocols.get(name) {
+            Some(members.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.protocol_members(name)
+        } else {
+            None
+        }
+    }
+
+    /// 登记 record 声明遵循的 protocol
+    pub fn define_conformance(&mut self, record: &str, protocols: Vec<String>) {
+        self.conformances.insert(record.to_string(), protocols);
+    }
+
+    /// record 是否声明遵循 protocol
+    pub fn conforms(&self, record: &str, protocol: &str) -> bool {
+        if let Some(protocols) = self.conformances.get(record) {
+            protocols.iter().any(|p| p == protocol)
+        } else if let Some(parent) = &self.parent {
+            parent.conforms(record, protocol)
+        } else {
+            false
+        }
+    }
+
+    /// 在当前作用域中把外层的变量收窄为 `ty`；`id` 区分每一次收窄，赋值使其失效
+    pub fn narrow(&mut self, name: &str, ty: PawType, id: usize) {
+        self.narrowed.insert(name.to_string(), (ty, id));
+    }
+
+    /// 从内到外所有仍然作用于 `name` 的收窄编号
+    pub fn narrowings(&self, name: &str, out: &mut Vec<usize>) {
+        if self.symbols.contains_key(name) {
+            return;
+        }
+        if let Some((_, id)) = self.narrowed.get(name) {
+            out.push(*id);
+        }
+        if let Some(parent) = &self.parent {
+            parent.narrowings(name, out);
+        }
+    }
+
+    /// 读取变量时的类型：最近一次没有失效（不在 `dead` 中）的收窄，没有时为声明的类型
+    pub fn lookup_narrowed(&self, name: &str, dead: &HashSet<usize>) -> Option<PawType> {
+        if let Some(t) = self.symbols.get(name) {
+            return Some(t.clone());
+        }
+        match (self.narrowed.get(name), &self.parent) {
+            (Some((ty, id)), _) if !dead.contains(id) => Some(ty.clone()),
+            (_, Some(parent)) => parent.lookup_narrowed(name, dead),
+            _ => None,
+        }
+    }
+
+    /// 向上查找符号的定义位置，格式为 `file:line:col`；未找到或由宿主注入时返回 None
+    pub fn defined_at(&self, name: &str) -> Option<String> {
+        if let Some((file, line, col)) = self.positions.get(name) {
+            Some(format!("{}:{}:{}", file, line, col))
+        } else if let Some(parent) = &self.parent {
+            parent.defined_at(name)
+        } else {
+            None
+        }
+    }
+
+    /// 向上查找符号声明的类型，不考虑收窄；若未找到返回 None
     pub fn lookup(&self, name: &str) -> Option<PawType> {
         if let Some(t) = self.symbols.get(name) {
             Some(t.clone())
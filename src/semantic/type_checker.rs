@@ -1,415 +1,1598 @@
-use crate::ast::expr::{Expr, ExprKind};
+use crate::ast::expr::{BinaryOp, Expr, ExprKind, FormatSpec};
 use crate::ast::param::Param;
-use crate::ast::statement::{Statement, StatementKind};
+use crate::ast::pattern::{Pattern, PatternKind};
+use crate::ast::type_node::{TypeNode, TypeNodeKind};
+use crate::ast::statement::{LoopVar, Statement, StatementKind};
 use crate::error::error::PawError;
+use crate::interpreter::builtins::{parse_format, placeholder_count, FormatPiece};
+use crate::interpreter::stdlib::{is_std_module, std_member_type, MemberType};
+use crate::semantic::purity;
 use crate::semantic::scope::{PawType, Scope};
-use std::collections::HashSet;
+use crate::semantic::types::HASHABLE_TYPES;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// 静态类型检查器
 pub struct TypeChecker {
     pub scope: Scope,
-    pub throwing_functions: HashSet<String>,
     current_fn: Option<String>,
     current_file: String,
+    /// 是否位于 task_group 代码块内（spawn 只能出现在其中）
+    in_task_group: bool,
+    /// 是否位于循环体内（break / continue 只能出现在其中），进入函数体时重置
+    in_loop: bool,
+    /// 位于没有声明 `barks` 的函数体内、sniff 之外：此处 bark 的错误没有人处理，
+    /// 不能调用声明了 `barks` 的函数。顶层、lambda 与 async 代码块中为 false
+    barks_unhandled: bool,
+    /// 外层到内层的循环标签（`loop outer: ...`），进入函数体时重置
+    loop_labels: Vec<String>,
+    /// 位于 record 方法体内（包括其中的函数、lambda 与 async 代码块）：`self` 是接收者的副本，
+    /// 给它的字段或元素赋值会在方法返回后丢失，因此报 E3053
+    in_method: bool,
+    /// 当前函数体（或 lambda / async 代码块）中收集到的 return 类型及位置，嵌套代码块共享
+    returns: Rc<RefCell<Vec<(PawType, usize, usize)>>>,
+    /// 所在函数 / lambda 声明的返回类型，`e?` 据此确认可以提前返回 err；顶层与 async 代码块中为 None
+    fn_return: Option<PawType>,
+    /// 检查过程中产生的警告，所有子检查器共享
+    warnings: Rc<RefCell<Vec<PawError>>>,
+    /// 覆盖了所有可能值的 match 语句的位置，供 return 路径分析使用，所有子检查器共享
+    exhaustive_matches: Rc<RefCell<HashSet<(usize, usize)>>>,
+    /// 因为变量被赋了不符合收窄类型的值而失效的收窄编号（见 `Scope::narrow`），所有子检查器共享
+    dead_narrowings: Rc<RefCell<HashSet<usize>>>,
 }
 
+/// 下一次 `x is T` 收窄的编号
+static NEXT_NARROWING: AtomicUsize = AtomicUsize::new(0);
+
 impl TypeChecker {
     pub fn new(filename: &str) -> Self {
         Self {
             scope: Scope::new(),
-            throwing_functions: HashSet::new(),
             current_fn: None,
             current_file: filename.into(),
+            in_task_group: false,
+            in_loop: false,
+            barks_unhandled: false,
+            loop_labels: Vec::new(),
+            in_method: false,
+            returns: Rc::default(),
+            fn_return: None,
+            warnings: Rc::default(),
+            exhaustive_matches: Rc::default(),
+            dead_narrowings: Rc::default(),
         }
     }
 
     pub fn with_parent(parent: &Scope, filename: &str) -> Self {
         Self {
             scope: Scope::with_parent(parent),
-            throwing_functions: HashSet::new(),
             current_fn: None,
             current_file: filename.into(),
+            in_task_group: false,
+            in_loop: false,
+            barks_unhandled: false,
+            loop_labels: Vec::new(),
+            in_method: false,
+            returns: Rc::default(),
+            fn_return: None,
+            warnings: Rc::default(),
+            exhaustive_matches: Rc::default(),
+            dead_narrowings: Rc::default(),
         }
     }
 
-    /// 顶级入口：预注册函数签名并检查所有语句
-    pub fn check_program(&mut self, stmts: &[Statement]) -> Result<(), PawError> {
-        // 1. 预注册函数名和签名
-        for stmt in stmts {
-            if let StatementKind::FunDecl {
-                name,
-                return_type,
-                params: _params,
-                ..
-            } = &stmt.kind
-            {
-                let ret_ty = return_type
-                    .as_deref()
-                    .map(PawType::from_str)
-                    .unwrap_or(PawType::Void);
-                self.scope
-                    .define(name, ret_ty, stmt.line, stmt.col, &self.current_file)
-                    .map_err(|_| PawError::DuplicateDefinition {
-                        file: self.current_file.clone(),
-                        code: "E2005",
-                        name: name.clone(),
-                        line: stmt.line,
-                        column: stmt.col,
-                        snippet: None,
-                        hint: Some("Function already defined".into()),
-                    })?;
-            }
+    /// 读取变量时的类型：考虑仍然有效的 `x is T` 收窄
+    fn var_type(&self, name: &str) -> Option<PawType> {
+        self.scope.lookup_narrowed(name, &self.dead_narrowings.borrow())
+    }
+
+    /// 为嵌套代码块（if / loop / sniff 等）创建子检查器，继承当前函数等上下文
+    fn nested(&self) -> Self {
+        let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
+        child.current_fn = self.current_fn.clone();
+        child.in_task_group = self.in_task_group;
+        child.in_loop = self.in_loop;
+        child.barks_unhandled = self.barks_unhandled;
+        child.loop_labels = self.loop_labels.clone();
+        child.in_method = self.in_method;
+        child.returns = self.returns.clone();
+        child.fn_return = self.fn_return.clone();
+        child.warnings = self.warnings.clone();
+        child.exhaustive_matches = self.exhaustive_matches.clone();
+        child.dead_narrowings = self.dead_narrowings.clone();
+        child
+    }
+
+    /// 没有人处理 bark 的地方（见 `barks_unhandled`）调用了声明 `barks` 的函数或方法时报 E3052
+    fn check_barks_handled(&self, callee: &str, expr: &Expr) -> Result<(), Box<PawError>> {
+        if !self.barks_unhandled || !self.scope.is_barking(callee) {
+            return Ok(());
         }
-        // 2. 检查每条语句
-        for stmt in stmts {
-            self.check_statement(stmt)?;
+        let caller = self.current_fn.clone().unwrap_or_default();
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3052",
+            message: format!("Function '{}' calls '{}', which barks, without handling it", caller, callee),
+            line: expr.line,
+            column: expr.col,
+            snippet: None,
+            hint: Some(format!(
+                "Call it inside `sniff {{ ... }} snatch (e) {{ ... }}`, or declare `barks` on '{}'",
+                caller
+            )),
+        }))
+    }
+
+    /// 函数类型不带 `barks`：在同样的地方把声明 `barks` 的函数当作值（赋给变量、作为参数传递）也报 E3052，
+    /// 否则通过函数值调用就绕过了检查
+    fn check_barks_value(&self, name: &str, expr: &Expr) -> Result<(), Box<PawError>> {
+        if !self.barks_unhandled || !self.scope.is_barking(name) {
+            return Ok(());
         }
-        Ok(())
+        let caller = self.current_fn.clone().unwrap_or_default();
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3052",
+            message: format!("Function '{}' uses '{}', which barks, as a value without handling it", caller, name),
+            line: expr.line,
+            column: expr.col,
+            snippet: None,
+            hint: Some(format!(
+                "Function values do not carry `barks`; use it inside `sniff {{ ... }} snatch (e) {{ ... }}`, \
+                 or declare `barks` on '{}'",
+                caller
+            )),
+        }))
     }
 
-    pub fn check_statement(&mut self, stmt: &Statement) -> Result<(), PawError> {
-        match &stmt.kind {
-            StatementKind::Let {
-                name,
-                ty: declared_str,
-                value,
-            } => {
-                // 1. 推断出值的类型
-                let mut inferred = self.check_expr(value)?;
+    /// 方法中 `self.x = ...`、`self.xs[i] = ...` 只会修改方法自己的副本，报 E3053
+    fn check_self_assignment(&self, stmt: &Statement) -> Result<(), Box<PawError>> {
+        let mut target = match &stmt.kind {
+            StatementKind::AssignIndex { array, .. } => array,
+            StatementKind::AssignField { object, .. } => object,
+            _ => return Ok(()),
+        };
+        while let ExprKind::Index { array: inner, .. } | ExprKind::FieldAccess { expr: inner, .. } = &target.kind {
+            target = inner;
+        }
+        if !self.in_method || !matches!(&target.kind, ExprKind::Var(name) if name == "self") {
+            return Ok(());
+        }
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3053",
+            message: "Cannot assign to a field of `self`: `self` is an immutable copy of the record".into(),
+            line: stmt.line,
+            column: stmt.col,
+            snippet: None,
+            hint: Some("Return a new record instead, e.g. `return Point { x: self.x + 1, y: self.y }`".into()),
+        }))
+    }
 
-                // 2. 把声明的字符串转成 PawType，Unknown 的情况下尝试从 scope 拿用户定义的
-                let mut declared_ty = match PawType::from_str(declared_str) {
-                    PawType::Unknown => self.scope.lookup(declared_str).unwrap_or(PawType::Unknown),
-                    other => other,
-                };
+    /// 检查循环体用的子检查器：带标签时记录标签，与外层循环的标签重名时报错
+    fn loop_body(&self, label: &Option<String>, line: usize, col: usize) -> Result<Self, Box<PawError>> {
+        let mut child = self.nested();
+        child.in_loop = true;
+        if let Some(label) = label {
+            if self.loop_labels.contains(label) {
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3048",
+                    message: format!("Loop label `{}` is already used by an enclosing loop", label),
+                    line,
+                    column: col,
+                    snippet: None,
+                    hint: Some("Give the inner loop a different label".into()),
+                }));
+            }
+            child.loop_labels.push(label.clone());
+        }
+        Ok(child)
+    }
 
-                // 3. 如果是 nopaw 字面量，就直接当作 declared_ty
-                if let ExprKind::LiteralNopaw = &value.kind {
-                    inferred = declared_ty.clone();
+    /// 条件成立时使用的子检查器：条件（及其 `&&` 连接的各项）中的 `x is T` 把变量 x 收窄为 T。
+    /// 收窄只影响读取；赋值仍按声明的类型检查，赋入不符合 T 的值后收窄失效
+    fn narrowed(&self, condition: &Expr) -> Result<Self, Box<PawError>> {
+        fn tests<'a>(cond: &'a Expr, out: &mut Vec<(&'a str, &'a TypeNode)>) {
+            match &cond.kind {
+                ExprKind::TypeTest { expr, ty } => {
+                    if let ExprKind::Var(name) = &expr.kind {
+                        out.push((name, ty));
+                    }
+                }
+                ExprKind::BinaryOp {
+                    op: BinaryOp::And,
+                    left,
+                    right,
+                } => {
+                    tests(left, out);
+                    tests(right, out);
                 }
+                _ => {}
+            }
+        }
+        let mut child = self.nested();
+        let mut found = Vec::new();
+        tests(condition, &mut found);
+        for (name, ty) in found {
+            if child.scope.lookup(name).is_some() {
+                let ty = child.resolve_type(ty)?;
+                child.scope.narrow(name, ty, NEXT_NARROWING.fetch_add(1, Ordering::Relaxed));
+            }
+        }
+        Ok(child)
+    }
 
-                // 4. 检查兼容性：Exact，T→T?，以及任意数值类型互转
-                let ok = if inferred == declared_ty {
-                    true
-                } else if let PawType::Optional(inner) = &declared_ty {
-                    // T → Optional<T>
-                    &inferred == inner.as_ref()
-                } else if inferred.is_numeric() && declared_ty.is_numeric() {
-                    // 不同数值类型之间也允许
-                    true
-                } else {
-                    false
-                };
+    /// 为函数 / lambda / async 代码块的函数体创建子检查器：
+    /// 重新收集 return、不在循环内，但与外层共享警告列表
+    fn body_checker(&self) -> Self {
+        let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
+        child.in_method = self.in_method;
+        child.warnings = self.warnings.clone();
+        child.exhaustive_matches = self.exhaustive_matches.clone();
+        child.dead_narrowings = self.dead_narrowings.clone();
+        child
+    }
 
-                if !ok {
-                    return Err(PawError::Type {
-                        file: self.current_file.clone(),
-                        code: "E3003",
-                        message: format!(
-                            "Type mismatch in let '{}': expected {}, found {}",
-                            name, declared_ty, inferred
-                        ),
-                        line: stmt.line,
-                        column: stmt.col,
-                        snippet: None,
-                        hint: Some("Ensure assigned value matches declared type".into()),
-                    });
-                }
+    /// 引入类型参数的函数体检查器：泛型函数或 record 声明中的类型注解可以引用这些参数
+    fn with_type_params(&self, type_params: &[String]) -> Self {
+        let mut child = self.body_checker();
+        child.scope.define_type_params(type_params);
+        child
+    }
 
-                // 5. 把真正的 PawType 存到 scope
-                self.scope
-                    .define(&*name, declared_ty, stmt.line, stmt.col, &self.current_file)?;
+    /// 目前为止收集到的警告
+    pub fn warnings(&self) -> Vec<PawError> {
+        self.warnings.borrow().clone()
+    }
+
+    fn warn(&self, code: &'static str, message: String, line: usize, col: usize, hint: Option<String>) {
+        self.warnings.borrow_mut().push(PawError::Warning {
+            file: self.current_file.clone(),
+            code,
+            message,
+            line,
+            column: col,
+            snippet: None,
+            hint,
+        });
+    }
+
+    /// 核对子检查器收集到的 return：类型必须与声明一致，或可提升到声明的 Optional
+    fn check_collected_returns(
+        &self,
+        sub: &TypeChecker,
+        declared: &PawType,
+        what: &str,
+    ) -> Result<(), Box<PawError>> {
+        for (actual, line, col) in sub.returns.borrow().iter() {
+            let ok = actual == declared
+                || matches!(declared, PawType::Optional(inner) if actual == inner.as_ref())
+                || self.upcasts(declared, actual);
+            if !ok {
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3004",
+                    message: format!(
+                        "Return type mismatch in {}: declared {}, found {}",
+                        what, declared, actual
+                    ),
+                    line: *line,
+                    column: *col,
+                    snippet: None,
+                    hint: await_hint(declared, actual)
+                        .or_else(|| Some("Ensure return matches declared return type".into())),
+                }));
             }
+        }
+        Ok(())
+    }
 
-            StatementKind::Assign { name, value } => {
-                // 1. 拿到变量声明时的类型
-                let declared_ty = self.scope.lookup(name).unwrap_or(PawType::Any);
-                // 2. 推断出待赋值表达式的类型
-                let mut inferred = self.check_expr(value)?;
-                // 3. 如果赋值的是 nopaw 字面量，且声明类型本身是 Optional<T>，则直接当成 declared_ty
-                if let ExprKind::LiteralNopaw = &value.kind {
-                    if let PawType::Optional(_) = &declared_ty {
-                        inferred = declared_ty.clone();
+    /// 把类型注解转换为 PawType：内置类型名直接对应，其余名字按作用域解析为 protocol 或 record。
+    /// 这里不要求 record 已经登记，声明之间因此可以前向引用；需要确认时用 resolve_type
+    fn type_of(&self, node: &TypeNode) -> Result<PawType, Box<PawError>> {
+        Ok(match &node.kind {
+            TypeNodeKind::Name(name) if self.scope.is_type_param(name) => PawType::TypeParam(name.clone()),
+            TypeNodeKind::Name(name) => match PawType::parse(name) {
+                PawType::Record(name) if self.scope.protocol_members(&name).is_some() => {
+                    PawType::Protocol(name)
+                }
+                PawType::Record(name) if self.scope.enum_variants(&name).is_some() => PawType::Enum(name),
+                PawType::Record(name) if self.scope.record_params(&name).is_some() => {
+                    return Err(self.record_arity(node, &name, 0));
+                }
+                ty => ty,
+            },
+            TypeNodeKind::Generic { name, args } => match (name.as_str(), args.as_slice()) {
+                ("Array", [elem]) => PawType::Array(Box::new(self.type_of(elem)?)),
+                ("Future", [inner]) => PawType::Future(Box::new(self.type_of(inner)?)),
+                ("Gen", [inner]) => PawType::Gen(Box::new(self.type_of(inner)?)),
+                ("Map", [k, v]) => PawType::Map(Box::new(self.type_of(k)?), Box::new(self.type_of(v)?)),
+                ("Set", [elem]) => PawType::Set(Box::new(self.type_of(elem)?)),
+                ("Range", [elem]) => match self.type_of(elem)? {
+                    elem @ (PawType::Int | PawType::Long) => PawType::Range(Box::new(elem)),
+                    other => {
+                        return Err(self.bad_type(node, format!("`Range` takes Int or Long, found {}", other)));
                     }
+                },
+                ("Result", [ok, err]) => {
+                    PawType::Result(Box::new(self.type_of(ok)?), Box::new(self.type_of(err)?))
                 }
-                // 4. 检查兼容性：
-                //    - 精确相等
-                //    - T -> Optional<T>
-                //    - 不同数值类型之间互相赋值也允许
-                let ok = if inferred == declared_ty {
-                    true
-                } else if let PawType::Optional(inner) = &declared_ty {
-                    &inferred == inner.as_ref()
-                } else if inferred.is_numeric() && declared_ty.is_numeric() {
-                    true
-                } else {
-                    false
-                };
-                if !ok {
-                    return Err(PawError::Type {
-                        file: self.current_file.clone(),
-                        code: "E3003",
-                        message: format!(
-                            "Type mismatch in assign '{}': expected {}, found {}",
-                            name, declared_ty, inferred
+                ("Array" | "Future" | "Gen" | "Map" | "Set" | "Range" | "Result", _) => {
+                    let expected = if matches!(name.as_str(), "Map" | "Result") { 2 } else { 1 };
+                    return Err(self.bad_type(
+                        node,
+                        format!(
+                            "`{}` expects {} type argument(s), found {}",
+                            name,
+                            expected,
+                            args.len()
                         ),
-                        line: stmt.line,
-                        column: stmt.col,
-                        snippet: None,
-                        hint: Some("Ensure assigned value matches declared type".into()),
-                    });
+                    ));
                 }
+                _ => match self.scope.record_params(name) {
+                    Some(params) if params.len() == args.len() => PawType::Generic(
+                        name.clone(),
+                        args.iter().map(|a| self.type_of(a)).collect::<Result<_, _>>()?,
+                    ),
+                    Some(_) => return Err(self.record_arity(node, name, args.len())),
+                    None => {
+                        return Err(self.bad_type(node, format!("`{}` does not take type arguments", name)));
+                    }
+                },
+            },
+            TypeNodeKind::Optional(inner) => PawType::Optional(Box::new(self.type_of(inner)?)),
+            TypeNodeKind::Function { params, ret } => PawType::Function(
+                params.iter().map(|p| self.type_of(p)).collect::<Result<_, _>>()?,
+                Box::new(self.type_of(ret)?),
+            ),
+            TypeNodeKind::Tuple(elems) => {
+                PawType::Tuple(elems.iter().map(|e| self.type_of(e)).collect::<Result<_, _>>()?)
             }
+        })
+    }
 
-            StatementKind::FunDecl {
-                name,
-                params,
-                return_type,
-                body,
-                is_async: _is_async,
-            } => {
-                // 切换到当前函数
-                let prev_fn = self.current_fn.clone();
-                self.current_fn = Some(name.clone());
-
-                // 在子作用域中检查函数体
-                let mut sub = TypeChecker::with_parent(&self.scope, &self.current_file);
-                // 参数入作用域
-                for Param {
-                    name: pn, ty: pty, ..
-                } in params
-                {
-                    let t = PawType::from_str(pty);
-                    sub.scope
-                        .define(pn, t, stmt.line, stmt.col, &self.current_file)
-                        .map_err(|_| PawError::DuplicateDefinition {
-                            file: self.current_file.clone(),
-                            code: "E2005",
-                            name: pn.clone(),
-                            line: stmt.line,
-                            column: stmt.col,
-                            snippet: None,
-                            hint: None,
-                        })?;
+    /// 解析类型注解，并确认其中引用的 record 都已声明；报错位置指向出错的那一层类型
+    fn resolve_type(&self, node: &TypeNode) -> Result<PawType, Box<PawError>> {
+        match &node.kind {
+            TypeNodeKind::Name(_) => {}
+            TypeNodeKind::Generic { args: elems, .. } | TypeNodeKind::Tuple(elems) => {
+                for elem in elems {
+                    self.resolve_type(elem)?;
                 }
-                // 先检查函数体内部所有语句
-                sub.check_program(body)?;
-
-                // 如果声明了返回类型，就扫描所有 return 语句，确保类型一致或可提升到 Optional
-                if let Some(ret_ty_str) = return_type {
-                    let declared = PawType::from_str(ret_ty_str);
-                    // 递归扫描函数体里的 return
-                    fn scan_returns(
-                        stmts: &[Statement],
-                        declared: &PawType,
-                        checker: &mut TypeChecker,
-                        file: &str,
-                    ) -> Result<(), PawError> {
-                        for stmt in stmts {
-                            match &stmt.kind {
-                                StatementKind::Return(opt_expr) => {
-                                    let actual = if let Some(expr) = opt_expr {
-                                        checker.check_expr(expr)?
-                                    } else {
-                                        PawType::Void
-                                    };
-                                    let ok = &actual == declared
-                                        || matches!(declared, PawType::Optional(inner) if &actual == inner.as_ref());
-                                    if !ok {
-                                        return Err(PawError::Type {
-                                            file: file.to_string(),
-                                            code: "E3004",
-                                            message: format!(
-                                                "Return type mismatch in function '{}': declared {}, found {}",
-                                                checker.current_fn.as_deref().unwrap_or("<anon>"),
-                                                declared,
-                                                actual
-                                            ),
-                                            line: stmt.line,
-                                            column: stmt.col,
-                                            snippet: None,
-                                            hint: Some("Ensure return matches declared return type".into()),
-                                        });
-                                    }
-                                }
-                                StatementKind::Block(inner) => {
-                                    scan_returns(inner, declared, checker, file)?
-                                }
-                                StatementKind::If {
-                                    body, else_branch, ..
-                                } => {
-                                    scan_returns(body, declared, checker, file)?;
-                                    if let Some(else_stmt) = else_branch {
-                                        scan_returns(
-                                            &[(*else_stmt.clone())],
-                                            declared,
-                                            checker,
-                                            file,
-                                        )?;
-                                    }
-                                }
-                                StatementKind::LoopForever(body)
-                                | StatementKind::LoopWhile { body, .. } => {
-                                    scan_returns(body, declared, checker, file)?
-                                }
-                                StatementKind::LoopRange { body, .. } => {
-                                    scan_returns(body, declared, checker, file)?
-                                }
-                                StatementKind::TryCatchFinally {
-                                    body,
-                                    handler,
-                                    finally,
-                                    ..
-                                } => {
-                                    scan_returns(body, declared, checker, file)?;
-                                    scan_returns(handler, declared, checker, file)?;
-                                    scan_returns(finally, declared, checker, file)?;
-                                }
-                                _ => {}
-                            }
-                        }
-                        Ok(())
-                    }
-                    // 执行扫描
-                    scan_returns(body, &declared, &mut sub, &self.current_file)?;
+            }
+            TypeNodeKind::Optional(inner) => {
+                self.resolve_type(inner)?;
+            }
+            TypeNodeKind::Function { params, ret } => {
+                for p in params {
+                    self.resolve_type(p)?;
                 }
-
-                // 将子检查器收集到的 throwing_functions 合并回来
-                self.throwing_functions.extend(sub.throwing_functions);
-                self.current_fn = prev_fn;
+                self.resolve_type(ret)?;
+            }
+        }
+        let ty = self.type_of(node)?;
+        match &ty {
+            PawType::Record(name) if self.scope.record_fields(name).is_none() => Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3028",
+                message: format!("Unknown type `{}`", name),
+                line: node.line,
+                column: node.col,
+                snippet: None,
+                hint: Some("Declare it with `record` or `protocol` before use".into()),
+            })),
+            // 与字面量相同的限制：键与元素必须可以哈希，泛型参数留到实例化时
+            PawType::Map(key, _) | PawType::Set(key)
+                if !key.is_hashable() && !matches!(**key, PawType::TypeParam(_)) =>
+            {
+                let (what, plural) = match ty {
+                    PawType::Map(..) => ("a Map key", "Map keys"),
+                    _ => ("a Set element", "Set elements"),
+                };
+                Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3026",
+                    message: format!("{} cannot be used as {}", key, what),
+                    line: node.line,
+                    column: node.col,
+                    snippet: None,
+                    hint: Some(format!("{} must be {}", plural, HASHABLE_TYPES)),
+                }))
             }
+            _ => Ok(ty),
+        }
+    }
 
-            StatementKind::If {
-                condition,
-                body,
-                else_branch,
-            } => {
-                let cond_ty = self.check_expr(condition)?;
-                if cond_ty != PawType::Bool {
-                    return Err(PawError::Type {
+    /// 范围的端点与步长必须是 Int 或 Long，任一为 Long 时元素为 Long；Any 留给运行时检查
+    fn range_element(&mut self, start: &Expr, end: &Expr, step: Option<&Expr>) -> Result<PawType, Box<PawError>> {
+        let mut elem = PawType::Int;
+        let parts = [("start", Some(start)), ("end", Some(end)), ("step", step)];
+        for (which, part) in parts {
+            let Some(part) = part else { continue };
+            let t = self.check_expr(part)?;
+            match t {
+                PawType::Long => elem = PawType::Long,
+                PawType::Any if elem == PawType::Int => elem = PawType::Any,
+                PawType::Int | PawType::Any => {}
+                _ => {
+                    return Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
-                        code: "E3006",
-                        message: "If condition must be Bool".into(),
-                        line: stmt.line,
-                        column: stmt.col,
+                        code: "E3008",
+                        message: format!("Range {} must be Int or Long, found {}", which, t),
+                        line: part.line,
+                        column: part.col,
                         snippet: None,
-                        hint: None,
-                    });
+                        hint: Some(if t.is_numeric() {
+                            "Convert it with `as Int` or `as Long`".to_string()
+                        } else {
+                            "Ranges iterate over Int or Long values".into()
+                        }),
+                    }));
                 }
-                let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
-                child.check_program(body)?;
-                if let Some(else_stmt) = else_branch {
-                    child.check_statement(else_stmt)?;
+            }
+        }
+        Ok(elem)
+    }
+
+    fn bad_type(&self, node: &TypeNode, message: String) -> Box<PawError> {
+        Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3028",
+            message,
+            line: node.line,
+            column: node.col,
+            snippet: None,
+            hint: Some(
+                "Generic types are Array<T>, Map<K, V>, Set<T>, Range<T>, Result<T, E>, Future<T>, Gen<T> \
+                 and records declared with type parameters"
+                    .into(),
+            ),
+        })
+    }
+
+    /// 泛型 record 的类型实参个数与声明的类型参数个数不同
+    fn record_arity(&self, node: &TypeNode, name: &str, found: usize) -> Box<PawError> {
+        let params = self.scope.record_params(name).unwrap_or_default();
+        Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3028",
+            message: format!("`{}` expects {} type argument(s), found {}", name, params.len(), found),
+            line: node.line,
+            column: node.col,
+            snippet: None,
+            hint: Some(format!("Write it as `{}<{}>`", name, params.join(", "))),
+        })
+    }
+
+    /// record 的字段类型；泛型 record 的类型参数在字段类型中解析为 `PawType::TypeParam`
+    fn field_types(&self, type_params: &[String], fields: &[Param]) -> Result<Vec<(String, PawType)>, Box<PawError>> {
+        if !type_params.is_empty() {
+            return self.with_type_params(type_params).field_types(&[], fields);
+        }
+        fields.iter().map(|p| Ok((p.name.clone(), self.type_of(&p.ty)?))).collect()
+    }
+
+    /// 由参数列表和返回类型构造函数类型，省略返回类型时为 Void；async 函数返回 Future<R>；
+    /// 泛型函数的类型参数在签名中解析为 `PawType::TypeParam`
+    fn function_type(
+        &self,
+        type_params: &[String],
+        params: &[Param],
+        return_type: Option<&TypeNode>,
+        is_async: bool,
+    ) -> Result<PawType, Box<PawError>> {
+        if !type_params.is_empty() {
+            return self.with_type_params(type_params).function_type(&[], params, return_type, is_async);
+        }
+        let ret = match return_type {
+            Some(node) => self.type_of(node)?,
+            None => PawType::Void,
+        };
+        Ok(PawType::Function(
+            params.iter().map(|p| self.type_of(&p.ty)).collect::<Result<_, _>>()?,
+            Box::new(if is_async {
+                PawType::Future(Box::new(ret))
+            } else {
+                ret
+            }),
+        ))
+    }
+
+    /// 能否把 `actual` 类型的值放到声明为 `declared` 的位置：在 `PawType::accepts` 之外，
+    /// 还允许把 record 放到它遵循的 protocol 位置
+    fn accepts(&self, declared: &PawType, actual: &PawType) -> bool {
+        declared.accepts(actual) || self.upcasts(declared, actual)
+    }
+
+    /// `actual` 是否经由 protocol 遵循关系成为 `declared`；数组、Map 与 Optional 按元素比较，
+    /// 因为这些值在赋值时整体复制，不会被当作原类型再写入
+    fn upcasts(&self, declared: &PawType, actual: &PawType) -> bool {
+        match (declared, actual) {
+            (PawType::Protocol(p), PawType::Record(r) | PawType::Generic(r, _)) => self.scope.conforms(r, p),
+            // 类型实参逐个相同，推断不出的实参（Any）可以放到任何实例位置
+            (PawType::Generic(dn, da), PawType::Generic(an, aa)) => {
+                dn == an && da.len() == aa.len() && da.iter().zip(aa).all(|(d, a)| d == a || *a == PawType::Any)
+            }
+            (PawType::Optional(d), PawType::Optional(a)) => self.upcasts(d, a),
+            (PawType::Optional(d), a) => self.upcasts(d, a),
+            (PawType::Array(d), PawType::Array(a)) => d == a || self.upcasts(d, a),
+            (PawType::Map(dk, dv), PawType::Map(ak, av)) => dk == ak && (dv == av || self.upcasts(dv, av)),
+            // ok(x) / err(e) 推断出的另一半为 Any，可以放到任何 Result 位置
+            (PawType::Result(dt, de), PawType::Result(at, ae)) => {
+                let fits = |d: &PawType, a: &PawType| d == a || *a == PawType::Any || self.upcasts(d, a);
+                fits(dt, at) && fits(de, ae)
+            }
+            (PawType::Tuple(d), PawType::Tuple(a)) => {
+                d.len() == a.len() && d.iter().zip(a).all(|(d, a)| self.accepts(d, a))
+            }
+            _ => false,
+        }
+    }
+
+    /// 推断放到 `expected` 位置的值的类型：空数组 / 空 Set 字面量直接取 `expected`；
+    /// 元素类型为 protocol 的数组字面量逐个检查元素，因此不同的 record 可以放进同一个 `Array<Shape>`；
+    /// 元组字面量按位置逐个推断，`nopaw`、`[]` 等元素因此也能取得对应位置的类型
+    fn check_value(&mut self, value: &Expr, expected: &PawType) -> Result<PawType, Box<PawError>> {
+        if let (ExprKind::SetLiteral(items), PawType::Set(_)) = (&value.kind, expected) {
+            if items.is_empty() {
+                return Ok(expected.clone());
+            }
+        }
+        if let (ExprKind::TupleLiteral(items), PawType::Tuple(elem_tys)) = (&value.kind, expected) {
+            if items.len() == elem_tys.len() {
+                let mut tys = Vec::with_capacity(items.len());
+                for (item, elem_ty) in items.iter().zip(elem_tys) {
+                    let t = self.check_value(item, elem_ty)?;
+                    let fits = self.accepts(elem_ty, &t)
+                        || (matches!(item.kind, ExprKind::LiteralNopaw) && matches!(elem_ty, PawType::Optional(_)))
+                        || (is_empty_map_literal(item) && matches!(elem_ty, PawType::Map(..)));
+                    tys.push(if fits { elem_ty.clone() } else { t });
                 }
+                return Ok(PawType::Tuple(tys));
+            }
+        }
+        let (ExprKind::ArrayLiteral(elems), PawType::Array(elem_ty)) = (&value.kind, expected) else {
+            return self.check_expr(value);
+        };
+        if elems.is_empty() {
+            return Ok(expected.clone());
+        }
+        if !matches!(**elem_ty, PawType::Protocol(_)) {
+            return self.check_expr(value);
+        }
+        for e in elems {
+            let t = self.check_expr(e)?;
+            if !self.accepts(elem_ty, &t) {
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3010",
+                    message: format!("Array element of type {} does not conform to {}", t, elem_ty),
+                    line: e.line,
+                    column: e.col,
+                    snippet: None,
+                    hint: Some(format!("Declare the record with `record Name: {}`", elem_ty)),
+                }));
             }
+        }
+        Ok(expected.clone())
+    }
 
-            StatementKind::LoopForever(body) => {
-                let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
-                child.check_program(body)?;
+    /// Void 表达式不产生值：不能绑定到变量或字段、参与比较与运算、作为参数传递或放进集合
+    fn reject_void(&self, ty: &PawType, value: &Expr, role: &str) -> Result<(), Box<PawError>> {
+        if *ty != PawType::Void {
+            return Ok(());
+        }
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3043",
+            message: format!("`{}` has type Void and cannot be {}", value, role),
+            line: value.line,
+            column: value.col,
+            snippet: None,
+            hint: Some("A Void call produces no value; call it as a statement on its own".into()),
+        }))
+    }
+
+    /// 检查必须产生值的表达式，见 reject_void
+    fn check_operand(&mut self, value: &Expr, role: &str) -> Result<PawType, Box<PawError>> {
+        let ty = self.check_expr(value)?;
+        self.reject_void(&ty, value, role)?;
+        Ok(ty)
+    }
+
+    /// record 必须提供 protocol 要求的每个成员，且类型完全一致
+    fn check_conformance(
+        &self,
+        record: &str,
+        protocol: &str,
+        line: usize,
+        col: usize,
+    ) -> Result<(), Box<PawError>> {
+        let Some(required) = self.scope.protocol_members(protocol) else {
+            return Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3028",
+                message: format!("Unknown protocol `{}`", protocol),
+                line,
+                column: col,
+                snippet: None,
+                hint: Some("Declare it with `protocol` before use".into()),
+            }));
+        };
+        let fields = self.scope.record_members(record).unwrap_or_default();
+        for (member, ty) in required {
+            let problem = match fields.iter().find(|(n, _)| *n == member) {
+                None => format!("missing member `{}: {}`", member, ty),
+                Some((_, found)) if *found != ty => {
+                    format!("member `{}` has type {}, expected {}", member, found, ty)
+                }
+                Some(_) => continue,
+            };
+            return Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3038",
+                message: format!(
+                    "Record `{}` does not conform to protocol `{}`: {}",
+                    record, protocol, problem
+                ),
+                line,
+                column: col,
+                snippet: None,
+                hint: Some(format!("Add the members required by `{}`", protocol)),
+            }));
+        }
+        Ok(())
+    }
+
+    /// 声明了返回值的函数必须在所有路径上 return（或 bark）；
+    /// 末尾是缺少 `else` 的 if/else-if 链时，指出未覆盖的条件
+    fn check_return_paths(
+        &self,
+        fn_name: &str,
+        declared: &PawType,
+        body: &[Statement],
+        line: usize,
+        col: usize,
+    ) -> Result<(), Box<PawError>> {
+        let exhaustive = self.exhaustive_matches.borrow();
+        // Void / Any 无需返回值，T? 走到末尾时隐式返回 nopaw
+        if matches!(declared, PawType::Void | PawType::Any | PawType::Optional(_))
+            || always_returns(body, &exhaustive)
+        {
+            return Ok(());
+        }
+        if let Some(last) = body.last() {
+            let mut conditions = Vec::new();
+            if if_chain_without_else(last, &mut conditions, &exhaustive) {
+                let listed: Vec<String> = conditions.iter().map(|c| format!("`{}`", c)).collect();
+                let uncovered = match listed.as_slice() {
+                    [only] => format!("{} does not hold", only),
+                    _ => format!("none of {} hold", listed.join(", ")),
+                };
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3031",
+                    message: format!(
+                        "if/else-if chain in function '{}' has no final `else`: nothing is returned when {}",
+                        fn_name, uncovered
+                    ),
+                    line: last.line,
+                    column: last.col,
+                    snippet: None,
+                    hint: Some("Add an `else` branch that returns a value".into()),
+                }));
             }
+        }
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3031",
+            message: format!(
+                "Function '{}' may finish without returning a value of type {}",
+                fn_name, declared
+            ),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some("Make sure every path ends with `return` or `bark`".into()),
+        }))
+    }
 
-            StatementKind::LoopWhile { condition, body } => {
-                let c = self.check_expr(condition)?;
-                if c != PawType::Bool {
-                    return Err(PawError::Type {
+    /// 顶级入口：先收集所有声明（protocol、record、函数签名），再检查所有语句，
+    /// 因此声明之间可以互相前向引用
+    pub fn check_program(&mut self, stmts: &[Statement]) -> Result<(), Box<PawError>> {
+        // 1. 预注册 enum 名，变体的数据类型等 record 登记后再解析；
+        //    运行时 enum 名是以变体为成员的模块
+        for stmt in stmts {
+            if let StatementKind::EnumDecl { name, .. } = &stmt.kind {
+                self.scope
+                    .define(name, PawType::Module, stmt.line, stmt.col, &self.current_file)
+                    .map_err(|_| PawError::DuplicateDefinition {
                         file: self.current_file.clone(),
-                        code: "E3007",
-                        message: "Loop condition must be Bool".into(),
+                        code: "E2005",
+                        name: name.clone(),
                         line: stmt.line,
                         column: stmt.col,
                         snippet: None,
-                        hint: None,
-                    });
-                }
-                let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
-                child.check_program(body)?;
+                        hint: Some("Enum already defined".into()),
+                    })?;
+                self.scope.define_enum(name, Vec::new());
             }
-
-            StatementKind::LoopRange {
-                var,
-                start,
-                end,
-                body,
-            } => {
-                let s = self.check_expr(start)?;
-                let e = self.check_expr(end)?;
-                if s != e {
-                    return Err(PawError::Type {
+        }
+        // 2. 预注册 protocol 名，成员要等所有 protocol 登记后再解析，以便互相引用
+        for stmt in stmts {
+            if let StatementKind::ProtocolDecl { name, .. } = &stmt.kind {
+                self.scope
+                    .define(
+                        name,
+                        PawType::Protocol(name.clone()),
+                        stmt.line,
+                        stmt.col,
+                        &self.current_file,
+                    )
+                    .map_err(|_| PawError::DuplicateDefinition {
                         file: self.current_file.clone(),
-                        code: "E3008",
-                        message: format!("Range bounds mismatch: {} vs {}", s, e),
+                        code: "E2005",
+                        name: name.clone(),
                         line: stmt.line,
                         column: stmt.col,
                         snippet: None,
-                        hint: None,
-                    });
-                }
-                let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
-                child
-                    .scope
-                    .define(var, s.clone(), stmt.line, stmt.col, &self.current_file)?;
-                child.check_program(body)?;
+                        hint: Some("Protocol already defined".into()),
+                    })?;
+                self.scope.define_protocol(name, Vec::new());
+            }
+        }
+        for stmt in stmts {
+            if let StatementKind::ProtocolDecl { name, members } = &stmt.kind {
+                let member_types = members
+                    .iter()
+                    .map(|p| Ok((p.name.clone(), self.type_of(&p.ty)?)))
+                    .collect::<Result<_, Box<PawError>>>()?;
+                self.scope.define_protocol(name, member_types);
+            }
+        }
+        // 3. 预注册 record 名和字段，再解析 enum 变体的数据类型
+        for stmt in stmts {
+            if let StatementKind::RecordDecl {
+                name,
+                type_params,
+                protocols,
+                ..
+            } = &stmt.kind
+            {
+                self.scope
+                    .define(
+                        name,
+                        PawType::Record(name.clone()),
+                        stmt.line,
+                        stmt.col,
+                        &self.current_file,
+                    )
+                    .map_err(|_| PawError::DuplicateDefinition {
+                        file: self.current_file.clone(),
+                        code: "E2005",
+                        name: name.clone(),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: Some("Record already defined".into()),
+                    })?;
+                if !type_params.is_empty() {
+                    self.scope.define_record_params(name, type_params.clone());
+                }
+                self.scope.define_conformance(name, protocols.clone());
+            }
+        }
+        // 字段与方法的签名可以引用任何 record（包括之后声明的泛型 record），等所有 record 名登记之后再登记
+        for stmt in stmts {
+            if let StatementKind::RecordDecl {
+                name,
+                type_params,
+                fields,
+                ..
+            } = &stmt.kind
+            {
+                let field_types = self.field_types(type_params, fields)?;
+                self.scope.define_record(name, field_types);
+            }
+        }
+        for stmt in stmts {
+            if let StatementKind::RecordDecl {
+                name,
+                type_params,
+                fields,
+                methods,
+                ..
+            } = &stmt.kind
+            {
+                self.define_methods(name, type_params, fields, methods)?;
+            }
+        }
+        for stmt in stmts {
+            if let StatementKind::EnumDecl { name, variants } = &stmt.kind {
+                let variant_types = variants
+                    .iter()
+                    .map(|v| {
+                        let payload = v.payload.iter().map(|t| self.type_of(t)).collect::<Result<_, _>>()?;
+                        Ok((v.name.clone(), payload))
+                    })
+                    .collect::<Result<_, Box<PawError>>>()?;
+                self.scope.define_enum(name, variant_types);
             }
+        }
+        // 4. 预注册函数名和签名
+        for stmt in stmts {
+            if let StatementKind::FunDecl {
+                name,
+                type_params,
+                return_type,
+                params,
+                is_async,
+                barks,
+                ..
+            } = &stmt.kind
+            {
+                let fn_ty = self.function_type(type_params, params, return_type.as_ref(), *is_async)?;
+                // 同名的 fun 参数类型不同时作为重载
+                self.scope
+                    .define_function(name, fn_ty, stmt.line, stmt.col, &self.current_file)?;
+                // 重载中有一个声明了 `barks`，调用这个名字就要处理
+                if *barks {
+                    self.scope.mark_barking(name);
+                }
+                let required = params.iter().take_while(|p| p.default.is_none()).count();
+                if required < params.len() {
+                    self.scope.define_required(name, required);
+                }
+            }
+        }
+        for stmt in stmts {
+            self.check_overload_set(stmt)?;
+            if let StatementKind::FunDecl { name, params, .. } = &stmt.kind {
+                if self.scope.overloads(name).is_none() {
+                    self.scope
+                        .define_param_names(name, params.iter().map(|p| p.name.clone()).collect());
+                }
+            }
+        }
+        // 推断函数与方法有没有副作用，验证 `@pure` 函数
+        purity::check_block(&self.current_file, stmts, &mut self.scope)?;
+        // 登记 `@deprecated`：函数与类型已预注册，let 要等定义之后
+        for stmt in stmts {
+            if !matches!(stmt.kind, StatementKind::Let { .. }) {
+                self.deprecate(stmt);
+            }
+        }
+        // 5. 检查每条语句
+        for stmt in stmts {
+            self.check_statement(stmt)?;
+            if matches!(stmt.kind, StatementKind::Let { .. }) {
+                self.deprecate(stmt);
+            }
+        }
+        Ok(())
+    }
 
-            StatementKind::Return(opt) => {
-                if let Some(e) = opt {
-                    let _ = self.check_expr(e)?;
+    /// 登记 record 的方法签名；方法不能与字段或另一个方法同名。
+    /// 泛型 record 的类型参数在方法签名中可见，方法也可以有自己的类型参数
+    fn define_methods(
+        &mut self,
+        record: &str,
+        record_params: &[String],
+        fields: &[Param],
+        methods: &[Statement],
+    ) -> Result<(), Box<PawError>> {
+        let mut signatures: Vec<(String, PawType)> = Vec::new();
+        for method in methods {
+            let StatementKind::FunDecl {
+                name,
+                type_params,
+                params,
+                return_type,
+                is_async,
+                barks,
+                ..
+            } = &method.kind
+            else {
+                continue;
+            };
+            let taken = if fields.iter().any(|f| f.name == *name) {
+                Some("a field")
+            } else if signatures.iter().any(|(n, _)| n == name) {
+                Some("another method")
+            } else {
+                None
+            };
+            if let Some(taken) = taken {
+                return Err(Box::new(PawError::DuplicateDefinition {
+                    file: self.current_file.clone(),
+                    code: "E2005",
+                    name: format!("{}.{}", record, name),
+                    line: method.line,
+                    column: method.col,
+                    snippet: None,
+                    hint: Some(format!("Record `{}` already has {} named '{}'", record, taken, name)),
+                }));
+            }
+            let type_params = [record_params, type_params.as_slice()].concat();
+            let fn_ty = self.function_type(&type_params, &params[1..], return_type.as_ref(), *is_async)?;
+            let names = params[1..].iter().map(|p| p.name.clone()).collect();
+            self.scope.define_param_names(&format!("{}.{}", record, name), names);
+            if *barks {
+                self.scope.mark_barking(&format!("{}.{}", record, name));
+            }
+            signatures.push((name.clone(), fn_ty));
+        }
+        self.scope.define_methods(record, signatures);
+        Ok(())
+    }
+
+    /// 重载的函数不能有默认值；参数个数相同的重载必须在某个运行时能判断的参数类型上不同，
+    /// 否则运行时无法从实参选出其中一个（E3049）
+    fn check_overload_set(&self, stmt: &Statement) -> Result<(), Box<PawError>> {
+        let StatementKind::FunDecl {
+            name,
+            type_params,
+            params,
+            return_type,
+            is_async,
+            ..
+        } = &stmt.kind
+        else {
+            return Ok(());
+        };
+        let Some(overloads) = self.scope.overloads(name) else {
+            return Ok(());
+        };
+        let error = |message: String, hint: &str| Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3049",
+            message,
+            line: stmt.line,
+            column: stmt.col,
+            snippet: None,
+            hint: Some(hint.into()),
+        });
+        if self.scope.required_params(name).is_some() || params.iter().any(|p| p.default.is_some()) {
+            return Err(error(
+                format!("Overloaded function '{}' cannot have default parameter values", name),
+                "Declare another overload with fewer parameters instead",
+            ));
+        }
+        let PawType::Function(own, _) = self.function_type(type_params, params, return_type.as_ref(), *is_async)?
+        else {
+            return Ok(());
+        };
+        for other in &overloads {
+            let PawType::Function(theirs, _) = other else { continue };
+            if *theirs == own || theirs.len() != own.len() {
+                continue;
+            }
+            let apart = own
+                .iter()
+                .zip(theirs)
+                .any(|(a, b)| a != b && a.is_runtime_checkable() && b.is_runtime_checkable());
+            if !apart {
+                return Err(error(
+                    format!("This overload of '{}' cannot be told apart from {} at run time", name, other),
+                    "Overloads with the same number of parameters must differ in a parameter of type \
+                     Int, Long, Float, Double, Bool, Char or String, or an Optional, Array, Set, Map or tuple of those",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 调用重载的函数：先按参数个数筛选，再按运行时能判断的参数类型打分（与 `Value::pick_overload` 规则相同），
+    /// 选中唯一的最高分者后按它的签名检查全部实参
+    fn check_overloaded_call(
+        &self,
+        name: &str,
+        overloads: Vec<PawType>,
+        arg_tys: &[PawType],
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Result<PawType, Box<PawError>> {
+        let listing = overloads.iter().map(|o| o.to_string()).collect::<Vec<_>>().join("; ");
+        let error = |code: &'static str, message: String| Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code,
+            message,
+            line: expr.line,
+            column: expr.col,
+            snippet: None,
+            hint: Some(format!("Overloads: {}", listing)),
+        });
+        let candidates: Vec<(Vec<PawType>, PawType)> = overloads
+            .iter()
+            .filter_map(|o| match o {
+                PawType::Function(params, ret) if params.len() == args.len() => Some((params.clone(), (**ret).clone())),
+                _ => None,
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(error(
+                "E3032",
+                format!("No overload of '{}' takes {} argument(s)", name, args.len()),
+            ));
+        }
+        // 在区分重载的位置上传入 Any 时只能到运行时再选；各重载返回类型相同时仍可得知结果类型
+        let deferred = candidates.iter().any(|(params, _)| {
+            params.iter().zip(arg_tys).any(|(p, a)| p.is_runtime_checkable() && *a == PawType::Any)
+        });
+        if deferred {
+            let ret = &candidates[0].1;
+            return Ok(match candidates.iter().all(|(_, r)| r == ret) {
+                true => ret.clone(),
+                false => PawType::Any,
+            });
+        }
+        let scored: Vec<(usize, &(Vec<PawType>, PawType))> = candidates
+            .iter()
+            .filter_map(|candidate| {
+                let score: Option<usize> = candidate
+                    .0
+                    .iter()
+                    .zip(arg_tys)
+                    .filter(|(p, _)| p.is_runtime_checkable())
+                    .map(|(p, a)| p.overload_score(a))
+                    .sum();
+                score.map(|s| (s, candidate))
+            })
+            .collect();
+        let Some(best) = scored.iter().map(|(s, _)| *s).max() else {
+            let types: Vec<String> = arg_tys.iter().map(|t| t.to_string()).collect();
+            return Err(error(
+                "E3033",
+                format!("No overload of '{}' accepts ({})", name, types.join(", ")),
+            ));
+        };
+        let mut winners = scored.iter().filter(|(s, _)| *s == best);
+        let (_, (params, ret)) = winners.next().expect("at least one overload scored");
+        if winners.next().is_some() {
+            return Err(error("E3049", format!("Ambiguous call to overloaded function '{}'", name)));
+        }
+        self.check_call_args(name, params.clone(), ret.clone(), arg_tys, args, expr)
+    }
+
+    fn deprecate(&mut self, stmt: &Statement) {
+        if let (Some(note), Some(name)) = (stmt.deprecation(), stmt.declared_name()) {
+            self.scope.deprecate(name, note.map(str::to_string));
+        }
+    }
+
+    /// 使用了标注 `@deprecated` 的名字时给出 W3004，附上说明与定义位置；弃用函数自身的递归调用不警告
+    fn warn_deprecated(&self, name: &str, line: usize, col: usize) {
+        if self.current_fn.as_deref() == Some(name) {
+            return;
+        }
+        let Some(note) = self.scope.deprecation(name) else {
+            return;
+        };
+        let message = match note {
+            Some(note) => format!("'{}' is deprecated: {}", name, note),
+            None => format!("'{}' is deprecated", name),
+        };
+        let hint = self.scope.defined_at(name).map(|at| format!("'{}' is defined at {}", name, at));
+        self.warn("W3004", message, line, col, hint);
+    }
+
+    pub fn check_statement(&mut self, stmt: &Statement) -> Result<(), Box<PawError>> {
+        // 循环体中的赋值使收窄失效时，下一轮开始前的读取也不能再用收窄的类型：带着失效的收窄重新检查一遍
+        if matches!(
+            stmt.kind,
+            StatementKind::LoopForever { .. }
+                | StatementKind::LoopWhile { .. }
+                | StatementKind::LoopDoWhile { .. }
+                | StatementKind::LoopRange { .. }
+                | StatementKind::LoopArray { .. }
+        ) {
+            let dead = self.dead_narrowings.borrow().len();
+            let (warned, returned) = (self.warnings.borrow().len(), self.returns.borrow().len());
+            self.check_statement_once(stmt)?;
+            if self.dead_narrowings.borrow().len() > dead {
+                self.warnings.borrow_mut().truncate(warned);
+                self.returns.borrow_mut().truncate(returned);
+                self.check_statement_once(stmt)?;
+            }
+            return Ok(());
+        }
+        self.check_statement_once(stmt)
+    }
+
+    fn check_statement_once(&mut self, stmt: &Statement) -> Result<(), Box<PawError>> {
+        match &stmt.kind {
+            StatementKind::Let {
+                name,
+                ty: None,
+                value,
+            } => {
+                // 省略类型：变量的类型就是值的类型，值本身说明不了类型时要求写出来
+                let inferred = self.check_operand(value, &format!("bound to '{}'", name))?;
+                if is_undetermined(value, &inferred) {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3050",
+                        message: format!("Cannot infer the type of '{}' from `{}`", name, value),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: Some(format!("Declare the type, e.g. `let {}: {} = {}`", name, example_type(&inferred), value)),
+                    }));
                 }
+                self.scope
+                    .define(name, inferred, stmt.line, stmt.col, &self.current_file)?;
             }
 
-            StatementKind::LoopArray { var, array, body } => {
-                // 1. 推断出 array 表达式的类型
-                let arr_ty = self.check_expr(array)?;
-                // 2. 必须是 Array<T>，取出 inner
-                let elem_ty = match arr_ty {
-                    PawType::Array(inner) => *inner,
+            StatementKind::Let {
+                name,
+                ty: Some(declared),
+                value,
+            } => {
+                // 1. 把声明的字符串转成 PawType，用户定义的 record / protocol 按名字解析
+                let declared_ty = self.resolve_type(declared)?;
+                if declared_ty == PawType::Void {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3043",
+                        message: format!("Variable '{}' cannot have type Void", name),
+                        line: declared.line,
+                        column: declared.col,
+                        snippet: None,
+                        hint: Some("Void has no values; use Any or an Optional type".into()),
+                    }));
+                }
+
+                // 2. 推断出值的类型
+                let mut inferred = self.check_value(value, &declared_ty)?;
+                self.reject_void(&inferred, value, &format!("bound to '{}'", name))?;
+
+                // 3. 如果是 nopaw 字面量或空 Map 字面量，就直接当作 declared_ty
+                if let ExprKind::LiteralNopaw = &value.kind {
+                    inferred = declared_ty.clone();
+                }
+                if is_empty_map_literal(value) && matches!(declared_ty, PawType::Map(..)) {
+                    inferred = declared_ty.clone();
+                }
+
+                // 4. 检查兼容性：Exact，Any，T→T?，以及任意数值类型互转
+                if !self.accepts(&declared_ty, &inferred) {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3003",
+                        message: format!(
+                            "Type mismatch in let '{}': expected {}, found {}",
+                            name, declared_ty, inferred
+                        ),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: await_hint(&declared_ty, &inferred)
+                            .or_else(|| Some("Ensure assigned value matches declared type".into())),
+                    }));
+                }
+
+                // 5. 把真正的 PawType 存到 scope
+                self.scope
+                    .define(name, declared_ty, stmt.line, stmt.col, &self.current_file)?;
+            }
+
+            StatementKind::LetTuple {
+                names,
+                ty: declared,
+                value,
+            } => {
+                // 有类型注解时按注解检查，否则直接取值的类型
+                let tuple_ty = match declared {
+                    Some(node) => {
+                        let declared_ty = self.resolve_type(node)?;
+                        let inferred = self.check_value(value, &declared_ty)?;
+                        if !self.accepts(&declared_ty, &inferred) {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3003",
+                                message: format!(
+                                    "Type mismatch in let ({}): expected {}, found {}",
+                                    names.join(", "),
+                                    declared_ty,
+                                    inferred
+                                ),
+                                line: stmt.line,
+                                column: stmt.col,
+                                snippet: None,
+                                hint: Some("Ensure assigned value matches declared type".into()),
+                            }));
+                        }
+                        declared_ty
+                    }
+                    None => self.check_expr(value)?,
+                };
+                let elem_tys = match tuple_ty {
+                    PawType::Tuple(elems) if elems.len() == names.len() => elems,
+                    PawType::Any => vec![PawType::Any; names.len()],
                     other => {
-                        return Err(PawError::Type {
+                        return Err(Box::new(PawError::Type {
                             file: self.current_file.clone(),
-                            code: "E3018", // 新增一个错误码，比如 E3018
-                            message: format!("Expected Array<T> in loop, found {}", other),
+                            code: "E3041",
+                            message: format!("Cannot destructure {} into {} names", other, names.len()),
                             line: stmt.line,
                             column: stmt.col,
                             snippet: None,
-                            hint: Some("Loop over an Array<T> only".into()),
-                        });
+                            hint: Some("List one name per tuple element; use `_` to skip one".into()),
+                        }));
+                    }
+                };
+                for (name, ty) in names.iter().zip(elem_tys) {
+                    if name != "_" {
+                        self.scope
+                            .define(name, ty, stmt.line, stmt.col, &self.current_file)?;
+                    }
+                }
+            }
+
+            StatementKind::Assign { name, value } => {
+                // 1. 拿到变量声明时的类型（不考虑 `is` 收窄），未声明的变量不能赋值
+                let declared_ty = self.scope.lookup(name).ok_or_else(|| {
+                    Box::new(PawError::UndefinedVariable {
+                        file: self.current_file.clone(),
+                        code: "E4001",
+                        name: name.clone(),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: Some("Declare it with `let` before assigning to it".into()),
+                    })
+                })?;
+                // 2. 推断出待赋值表达式的类型
+                let mut inferred = self.check_value(value, &declared_ty)?;
+                self.reject_void(&inferred, value, &format!("assigned to '{}'", name))?;
+                // 3. 如果赋值的是 nopaw 字面量，且声明类型本身是 Optional<T>，则直接当成 declared_ty
+                if let ExprKind::LiteralNopaw = &value.kind {
+                    if let PawType::Optional(_) = &declared_ty {
+                        inferred = declared_ty.clone();
+                    }
+                }
+                if is_empty_map_literal(value) && matches!(declared_ty, PawType::Map(..)) {
+                    inferred = declared_ty.clone();
+                }
+                // 4. 检查兼容性（见 PawType::accepts）
+                if !self.accepts(&declared_ty, &inferred) {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3003",
+                        message: format!(
+                            "Type mismatch in assign '{}': expected {}, found {}",
+                            name, declared_ty, inferred
+                        ),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: await_hint(&declared_ty, &inferred)
+                            .or_else(|| Some("Ensure assigned value matches declared type".into())),
+                    }));
+                }
+                // 5. 赋入的值不符合收窄后的类型时，之后的读取回到声明的类型
+                if self.var_type(name).is_some_and(|narrowed| !self.accepts(&narrowed, &inferred)) {
+                    let mut ids = Vec::new();
+                    self.scope.narrowings(name, &mut ids);
+                    self.dead_narrowings.borrow_mut().extend(ids);
+                }
+            }
+
+            StatementKind::AssignIndex { .. } | StatementKind::AssignField { .. } => {
+                self.check_self_assignment(stmt)?;
+                // 元组不可变，整体重新赋值即可
+                if let StatementKind::AssignIndex { array, .. } = &stmt.kind {
+                    if let PawType::Tuple(_) = self.check_expr(array)? {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3041",
+                            message: "Tuple elements cannot be assigned".into(),
+                            line: stmt.line,
+                            column: stmt.col,
+                            snippet: None,
+                            hint: Some("Build a new tuple and assign the whole variable".into()),
+                        }));
+                    }
+                }
+                // 复用索引 / 字段访问表达式的检查得到目标类型（同时校验容器）
+                let (kind, value, what) = match &stmt.kind {
+                    StatementKind::AssignIndex {
+                        array,
+                        index,
+                        value,
+                    } => (
+                        ExprKind::Index {
+                            array: Box::new(array.clone()),
+                            index: Box::new(index.clone()),
+                        },
+                        value,
+                        "indexed element".to_string(),
+                    ),
+                    StatementKind::AssignField {
+                        object,
+                        field,
+                        value,
+                    } => (
+                        ExprKind::FieldAccess {
+                            expr: Box::new(object.clone()),
+                            field: field.clone(),
+                        },
+                        value,
+                        format!("field '{}'", field),
+                    ),
+                    _ => unreachable!(),
+                };
+                let target = Expr {
+                    kind,
+                    line: stmt.line,
+                    col: stmt.col,
+                };
+                let elem_ty = self.check_expr(&target)?;
+                let mut inferred = self.check_operand(value, &format!("assigned to {}", what))?;
+                if let ExprKind::LiteralNopaw = &value.kind {
+                    if let PawType::Optional(_) = &elem_ty {
+                        inferred = elem_ty.clone();
+                    }
+                }
+                if !self.accepts(&elem_ty, &inferred) {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3003",
+                        message: format!(
+                            "Type mismatch in assignment to {}: expected {}, found {}",
+                            what,
+                            elem_ty, inferred
+                        ),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: await_hint(&elem_ty, &inferred)
+                            .or_else(|| Some("Ensure assigned value matches the target's type".into())),
+                    }));
+                }
+            }
+
+            StatementKind::FunDecl {
+                name,
+                type_params,
+                params,
+                return_type,
+                body,
+                is_async: _is_async,
+                barks,
+            } => {
+                // 切换到当前函数
+                let prev_fn = self.current_fn.clone();
+                self.current_fn = Some(name.clone());
+
+                // 在子作用域中检查函数体，泛型函数的类型参数在签名与函数体中可见
+                let mut sub = self.with_type_params(type_params);
+                sub.current_fn = Some(name.clone());
+                sub.barks_unhandled = !*barks;
+                let declared = return_type.as_ref().map(|node| sub.resolve_type(node)).transpose()?;
+                sub.fn_return = declared.clone();
+                // 参数入作用域
+                for Param {
+                    name: pn,
+                    ty: pty,
+                    default,
+                    ..
+                } in params
+                {
+                    let t = sub.resolve_type(pty)?;
+                    // 默认值在函数体的作用域中检查，可以引用前面的参数
+                    if let Some(default) = default {
+                        sub.check_default(pn, &t, default)?;
                     }
+                    sub.scope
+                        .define(pn, t, stmt.line, stmt.col, &self.current_file)
+                        .map_err(|_| PawError::DuplicateDefinition {
+                            file: self.current_file.clone(),
+                            code: "E2005",
+                            name: pn.clone(),
+                            line: stmt.line,
+                            column: stmt.col,
+                            snippet: None,
+                            hint: None,
+                        })?;
+                }
+                // 先检查函数体内部所有语句
+                sub.check_program(body)?;
+
+                // 如果声明了返回类型，逐个核对函数体中收集到的 return，确保类型一致或可提升到 Optional
+                if let Some(declared) = declared {
+                    self.check_collected_returns(&sub, &declared, &format!("function '{}'", name))?;
+                    self.check_return_paths(name, &declared, body, stmt.line, stmt.col)?;
+                }
+
+                self.current_fn = prev_fn;
+            }
+
+            StatementKind::If {
+                condition,
+                body,
+                else_branch,
+            } => {
+                let cond_ty = self.check_expr(condition)?;
+                if cond_ty != PawType::Bool {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3006",
+                        message: "If condition must be Bool".into(),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: None,
+                    }));
+                }
+                self.narrowed(condition)?.check_program(body)?;
+                if let Some(else_stmt) = else_branch {
+                    self.nested().check_statement(else_stmt)?;
+                }
+            }
+
+            StatementKind::LoopForever { label, body } => {
+                self.loop_body(label, stmt.line, stmt.col)?.check_program(body)?;
+            }
+
+            StatementKind::LoopWhile { label, condition, body } => {
+                // 条件必须是 Bool（Any 留给运行时检查）
+                let c = self.check_expr(condition)?;
+                if c != PawType::Bool && c != PawType::Any {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3007",
+                        message: "Loop condition must be Bool".into(),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: None,
+                    }));
+                }
+                self.loop_body(label, stmt.line, stmt.col)?.check_program(body)?;
+            }
+
+            StatementKind::LoopDoWhile {
+                label,
+                body,
+                condition,
+            } => {
+                // 循环体中定义的变量在条件中不可见
+                self.loop_body(label, stmt.line, stmt.col)?.check_program(body)?;
+                let c = self.check_expr(condition)?;
+                if c != PawType::Bool && c != PawType::Any {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3007",
+                        message: "Loop condition must be Bool".into(),
+                        line: condition.line,
+                        column: condition.col,
+                        snippet: None,
+                        hint: None,
+                    }));
+                }
+            }
+
+            StatementKind::LoopRange {
+                label,
+                var,
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => {
+                let elem = self.range_element(start, end, step.as_ref())?;
+                let mut child = self.loop_body(label, stmt.line, stmt.col)?;
+                child
+                    .scope
+                    .define(var, elem, stmt.line, stmt.col, &self.current_file)?;
+                child.check_program(body)?;
+            }
+
+            StatementKind::Return(opt) => {
+                // 在 return 所在的作用域中求类型，交给外层函数 / lambda / async 代码块核对
+                let ty = match opt {
+                    Some(e) => self.check_expr(e)?,
+                    None => PawType::Void,
                 };
-                // 3. 在子作用域中把循环变量绑定为 elem_ty
-                let mut child = TypeChecker::with_parent(&self.scope, &self.current_file);
-                child.scope.define(
-                    var,
-                    elem_ty.clone(),
-                    stmt.line,
-                    stmt.col,
-                    &self.current_file,
-                )?;
-                // 4. 检查循环体
+                self.returns.borrow_mut().push((ty, stmt.line, stmt.col));
+            }
+
+            StatementKind::LoopArray { label, var, array, body } => {
+                // 1. 推断出被遍历的表达式的类型，Any 留给运行时检查
+                let arr_ty = self.check_expr(array)?;
+                let Some(elem_ty) = arr_ty.loop_element() else {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3018",
+                        message: format!("Cannot loop over {}", arr_ty),
+                        line: array.line,
+                        column: array.col,
+                        snippet: None,
+                        hint: Some(
+                            "Loop over an Array, Set, Range, String (its characters) or Map (its key-value pairs)"
+                                .into(),
+                        ),
+                    }));
+                };
+                // 2. 在子作用域中绑定循环变量；`(k, v)` 按位置取元组元素的类型
+                let mut child = self.loop_body(label, stmt.line, stmt.col)?;
+                match var {
+                    LoopVar::Name(name) => {
+                        child
+                            .scope
+                            .define(name, elem_ty, stmt.line, stmt.col, &self.current_file)?;
+                    }
+                    LoopVar::Tuple(names) => {
+                        let elem_tys = match elem_ty {
+                            PawType::Tuple(elems) if elems.len() == names.len() => elems,
+                            PawType::Any => vec![PawType::Any; names.len()],
+                            other => {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3041",
+                                    message: format!("Cannot destructure {} into {} names", other, names.len()),
+                                    line: stmt.line,
+                                    column: stmt.col,
+                                    snippet: None,
+                                    hint: Some("List one name per tuple element; use `_` to skip one".into()),
+                                }));
+                            }
+                        };
+                        for (name, ty) in names.iter().zip(elem_tys) {
+                            if name != "_" {
+                                child
+                                    .scope
+                                    .define(name, ty, stmt.line, stmt.col, &self.current_file)?;
+                            }
+                        }
+                    }
+                }
+                // 3. 检查循环体
                 child.check_program(body)?;
             }
 
             StatementKind::Throw(expr) => {
                 let ty = self.check_expr(expr)?;
-                if ty != PawType::String {
-                    return Err(PawError::Type {
+                if !matches!(ty, PawType::String | PawType::Record(_) | PawType::Generic(..)) {
+                    return Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3001",
-                        message: format!("Cannot bark non-string: {}", ty),
+                        message: format!("Cannot bark {}", ty),
                         line: stmt.line,
                         column: stmt.col,
                         snippet: None,
-                        hint: Some("Only String may be thrown".into()),
-                    });
-                }
-                if let Some(fn_name) = &self.current_fn {
-                    self.throwing_functions.insert(fn_name.clone());
+                        hint: Some("Only a String or a record value may be thrown".into()),
+                    }));
                 }
             }
 
             StatementKind::Ask { name, ty, .. } => {
-                let expected = PawType::from_str(ty);
+                let expected = self.resolve_type(ty)?;
+                // 输入只能转换成基本类型（可以是可空的）
+                let readable = match &expected {
+                    PawType::Optional(inner) => inner.as_ref(),
+                    other => other,
+                };
+                if !readable.is_numeric()
+                    && !matches!(readable, PawType::String | PawType::Bool | PawType::Char | PawType::Any)
+                {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3039",
+                        message: format!("Cannot read a value of type {} with ask", expected),
+                        line: ty.line,
+                        column: ty.col,
+                        snippet: None,
+                        hint: Some("ask reads String, Int, Long, Float, Double, Bool or Char".into()),
+                    }));
+                }
                 self.scope
-                    .define(name, expected, stmt.line, stmt.col, &*self.current_file)
+                    .define(name, expected, stmt.line, stmt.col, &self.current_file)
                     .map_err(|_| PawError::DuplicateDefinition {
                         file: self.current_file.clone(),
                         code: "E2005",
@@ -421,103 +1604,1164 @@ impl TypeChecker {
                     })?;
             }
 
-            StatementKind::Import { module: _, alias } => {
-                // 模块别名注册成 Module
-                self.scope
-                    .define(
-                        &*alias,
-                        PawType::Module,
-                        stmt.line,
-                        stmt.col,
-                        &self.current_file,
-                    )
-                    .map_err(|_| PawError::DuplicateDefinition {
+            StatementKind::Import { module, alias } => {
+                // 运行时导入直接覆盖同名变量，因此别名不能与任何可见的名字（包括外层作用域中的）重名
+                if let Some(existing) = self.scope.lookup(alias) {
+                    let what = if existing == PawType::Module {
+                        "a module"
+                    } else {
+                        "a variable or function"
+                    };
+                    let at = match self.scope.defined_at(alias) {
+                        Some(at) => format!(" at {}", at),
+                        None => " by the host".into(),
+                    };
+                    return Err(Box::new(PawError::DuplicateDefinition {
                         file: self.current_file.clone(),
                         code: "E2005",
                         name: alias.clone(),
                         line: stmt.line,
                         column: stmt.col,
                         snippet: None,
-                        hint: Some("Module already imported".into()),
-                    })?;
+                        hint: Some(format!(
+                            "'{}' already names {} defined{}; import it under another name with `import {} as ...`",
+                            alias,
+                            what,
+                            at,
+                            module.join(".")
+                        )),
+                    }));
+                }
+                // 模块别名注册成 Module，标准库模块同时登记其路径，供检查成员类型
+                if is_std_module(module) {
+                    self.scope.define_std_module(alias, module.clone());
+                }
+                self.scope.define(
+                    alias,
+                    PawType::Module,
+                    stmt.line,
+                    stmt.col,
+                    &self.current_file,
+                )?;
                 return Ok(());
             }
 
-            StatementKind::Say(_)
-            | StatementKind::AskPrompt(_)
-            | StatementKind::Block(_)
-            | StatementKind::Continue
-            | StatementKind::Break
-            | StatementKind::Expr(_) => {
-                // 这几种语句无需额外检查或已经在 check_expr 中处理
-                if let StatementKind::Expr(e) = &stmt.kind {
-                    let _ = self.check_expr(e)?;
-                }
-            }
-            StatementKind::RecordDecl { name, fields, .. } => {
-                // 把字段列表转换成 Vec<(String, PawType)>
-                let field_types: Vec<(String, PawType)> = fields
-                    .iter()
-                    .map(|p| (p.name.clone(), PawType::from_str(&p.ty)))
-                    .collect();
-                self.scope
-                    .define(
-                        name,
-                        PawType::Record(field_types),
-                        stmt.line,
-                        stmt.col,
-                        &self.current_file,
-                    )
-                    .map_err(|_| PawError::DuplicateDefinition {
-                        file: self.current_file.clone(),
-                        code: "E2005",
-                        name: name.clone(),
-                        line: stmt.line,
-                        column: stmt.col,
-                        snippet: None,
-                        hint: Some("Record already defined".into()),
-                    })?;
+            StatementKind::TaskGroup(body) => {
+                let mut child = self.nested();
+                child.in_task_group = true;
+                child.check_program(body)?;
+            }
+
+            StatementKind::Spawn(expr) => {
+                if !self.in_task_group {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3027",
+                        message: "`spawn` used outside of a task_group".into(),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: Some("Wrap the spawn in `task_group { ... }`".into()),
+                    }));
+                }
+                let _ = self.check_expr(expr)?;
+            }
+
+            StatementKind::Say(e) | StatementKind::SayDebug(e) | StatementKind::Expr(e) => {
+                let _ = self.check_expr(e)?;
+            }
+            StatementKind::Block(body) => {
+                self.nested().check_program(body)?;
+            }
+            StatementKind::Continue(label) | StatementKind::Break(label) => {
+                let keyword = if matches!(stmt.kind, StatementKind::Break(_)) {
+                    "break"
+                } else {
+                    "continue"
+                };
+                if !self.in_loop {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3034",
+                        message: format!("`{}` used outside of a loop", keyword),
+                        line: stmt.line,
+                        column: stmt.col,
+                        snippet: None,
+                        hint: Some(format!(
+                            "`{}` may only appear inside a loop body; functions and async blocks start outside any loop",
+                            keyword
+                        )),
+                    }));
+                }
+                if let Some(label) = label {
+                    if !self.loop_labels.contains(label) {
+                        let names: Vec<&str> = self.loop_labels.iter().map(String::as_str).collect();
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3048",
+                            message: format!("Unknown loop label `{}` in `{}`", label, keyword),
+                            line: stmt.line,
+                            column: stmt.col,
+                            snippet: None,
+                            hint: Some(match closest_name(label, &names) {
+                                Some(s) => format!("Did you mean `{}`?", s),
+                                None => "Only labels of enclosing loops in the same function can be used".into(),
+                            }),
+                        }));
+                    }
+                }
+            }
+            StatementKind::AskPrompt(_) => {
+                // 无需额外检查
+            }
+            StatementKind::RecordDecl {
+                name,
+                type_params,
+                fields,
+                protocols,
+                methods,
+            } => {
+                // record 已在 check_program 中登记，这里只校验字段类型，
+                // 字段可以引用自身或之后声明的 record
+                let mut inner = self.with_type_params(type_params);
+                for p in fields {
+                    inner.resolve_type(&p.ty)?;
+                }
+                // 方法体按函数检查，`self` 是第一个参数
+                inner.in_method = true;
+                for method in methods {
+                    inner.check_statement(method)?;
+                }
+                for protocol in protocols {
+                    self.warn_deprecated(protocol, stmt.line, stmt.col);
+                    self.check_conformance(name, protocol, stmt.line, stmt.col)?;
+                }
+            }
+            StatementKind::ProtocolDecl { members, .. } => {
+                for p in members {
+                    self.resolve_type(&p.ty)?;
+                }
+            }
+            StatementKind::EnumDecl { variants, .. } => {
+                let mut seen = HashSet::new();
+                for v in variants {
+                    if !seen.insert(v.name.as_str()) {
+                        return Err(Box::new(PawError::DuplicateDefinition {
+                            file: self.current_file.clone(),
+                            code: "E2005",
+                            name: v.name.clone(),
+                            line: v.line,
+                            column: v.col,
+                            snippet: None,
+                            hint: Some("Variant already defined".into()),
+                        }));
+                    }
+                    for t in &v.payload {
+                        self.resolve_type(t)?;
+                    }
+                }
+            }
+            StatementKind::Match { subject, arms, .. } => {
+                let subject_ty = self.check_operand(subject, "matched")?;
+                let mut earlier: Vec<&Pattern> = Vec::new();
+                for arm in arms {
+                    if self.patterns_exhaust(&earlier, &subject_ty) {
+                        self.warn(
+                            "W3003",
+                            "Unreachable match arm: earlier arms already match every value".into(),
+                            arm.line,
+                            arm.col,
+                            Some("Remove the arm or move it before the arm that matches everything".into()),
+                        );
+                    }
+                    let mut child = self.nested();
+                    for pattern in &arm.patterns {
+                        let mut names = Vec::new();
+                        pattern.bindings(&mut names);
+                        if arm.patterns.len() > 1 && !names.is_empty() {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3045",
+                                message: format!(
+                                    "Pattern `{}` binds `{}`, but arms with several patterns cannot bind variables",
+                                    names[0], names[0]
+                                ),
+                                line: pattern.line,
+                                column: pattern.col,
+                                snippet: None,
+                                hint: Some("Split the patterns into separate arms".into()),
+                            }));
+                        }
+                        child.check_pattern(pattern, &subject_ty)?;
+                    }
+                    child.check_program(&arm.body)?;
+                    earlier.extend(&arm.patterns);
+                }
+                if self.patterns_exhaust(&earlier, &subject_ty) {
+                    self.exhaustive_matches.borrow_mut().insert((stmt.line, stmt.col));
+                } else {
+                    self.warn(
+                        "W3002",
+                        format!("Non-exhaustive match: some values of type {} match no arm", subject_ty),
+                        stmt.line,
+                        stmt.col,
+                        Some("Add a `_ => { ... }` arm for the remaining values".into()),
+                    );
+                }
+            }
+
+            StatementKind::TryCatchFinally {
+                body,
+                clauses,
+                finally,
+            } => {
+                // 主体中 bark 的错误交给 snatch 处理，可以调用声明了 `barks` 的函数
+                let mut inner = self.nested();
+                inner.barks_unhandled = false;
+                inner.check_program(body)?;
+
+                // Catch 分支：在子作用域里定义错误变量，然后检查子句主体
+                let mut caught: Vec<String> = Vec::new();
+                let mut catch_all = false;
+                for clause in clauses {
+                    let err_ty = match &clause.ty {
+                        Some(ty) => {
+                            let err_ty = self.resolve_type(ty)?;
+                            if !matches!(err_ty, PawType::Record(_)) {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3035",
+                                    message: format!("Cannot snatch by type {}: only record errors have a type", err_ty),
+                                    line: ty.line,
+                                    column: ty.col,
+                                    snippet: None,
+                                    hint: Some("Use `snatch (e)` to catch String errors".into()),
+                                }));
+                            }
+                            err_ty
+                        }
+                        None => PawType::String,
+                    };
+                    // 排在捕获所有错误的子句或同类型子句之后的子句永远不会执行
+                    if catch_all || clause.ty.as_ref().is_some_and(|ty| caught.contains(&ty.to_string())) {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3036",
+                            message: "Unreachable snatch clause: its errors are caught by an earlier clause".into(),
+                            line: clause.line,
+                            column: clause.col,
+                            snippet: None,
+                            hint: Some("Put typed `snatch (e: T)` clauses before `snatch (e)`".into()),
+                        }));
+                    }
+                    match &clause.ty {
+                        Some(ty) => caught.push(ty.to_string()),
+                        None => catch_all = true,
+                    }
+
+                    let mut catch_checker = self.nested();
+                    catch_checker
+                        .scope
+                        .define(
+                            &clause.name,
+                            err_ty,
+                            clause.line,
+                            clause.col,
+                            &self.current_file,
+                        )
+                        .map_err(|_| PawError::DuplicateDefinition {
+                            file: self.current_file.clone(),
+                            code: "E2005",
+                            name: clause.name.clone(),
+                            line: clause.line,
+                            column: clause.col,
+                            snippet: None,
+                            hint: None,
+                        })?;
+                    catch_checker.check_program(&clause.body)?;
+                }
+
+                // Finally 分支也要在新作用域检查
+                self.nested().check_program(finally)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查 match 模式能否匹配 `ty` 类型的值，并在当前（分支的）作用域中定义模式绑定的变量；
+    /// Optional 值除 `nopaw`、`_`、变量名与类型模式外都按内部的值匹配
+    fn check_pattern(&mut self, pattern: &Pattern, ty: &PawType) -> Result<(), Box<PawError>> {
+        let inner = match ty {
+            PawType::Optional(inner) => inner.as_ref(),
+            ty => ty,
+        };
+        let never = |this: &Self, what: String| Box::new(PawError::Type {
+            file: this.current_file.clone(),
+            code: "E3045",
+            message: format!("Pattern {} can never match a value of type {}", what, ty),
+            line: pattern.line,
+            column: pattern.col,
+            snippet: None,
+            hint: Some("Match against literals, records or tuples of the matched type".into()),
+        });
+        match &pattern.kind {
+            PatternKind::Wildcard => {}
+            PatternKind::Bind(name) => self.bind_pattern(name, ty.clone(), pattern)?,
+            PatternKind::Literal(lit) => {
+                let lit_ty = self.check_expr(lit)?;
+                let fits = match (&lit.kind, inner) {
+                    (ExprKind::LiteralNopaw, _) => matches!(ty, PawType::Optional(_) | PawType::Any),
+                    (_, PawType::Any) => true,
+                    _ => {
+                        lit_ty == *inner
+                            || (lit_ty.is_integer() && inner.is_integer())
+                            || (lit_ty.is_float() && inner.is_float())
+                    }
+                };
+                if !fits {
+                    return Err(never(self, format!("`{}`", lit)));
+                }
+            }
+            PatternKind::Typed { name, ty: node } => {
+                let to = self.resolve_type(node)?;
+                if !to.is_testable() || (!self.accepts(ty, &to) && !self.accepts(&to, ty)) {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3044",
+                        message: if to.is_testable() {
+                            format!("Pattern `{}: {}` can never match a value of type {}", name, to, ty)
+                        } else {
+                            format!("`{}` cannot be tested in a pattern at runtime", to)
+                        },
+                        line: node.line,
+                        column: node.col,
+                        snippet: None,
+                        hint: Some(
+                            "Type patterns test basic types, records, and Optional, Array, Map, Set, Tuple or Result types built from them"
+                                .into(),
+                        ),
+                    }));
+                }
+                if name != "_" {
+                    self.bind_pattern(name, to, pattern)?;
+                }
+            }
+            PatternKind::Record { name, fields } => {
+                let Some(declared) = self.scope.record_fields(name) else {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3028",
+                        message: format!("Unknown type `{}`", name),
+                        line: pattern.line,
+                        column: pattern.col,
+                        snippet: None,
+                        hint: Some("Declare it with `record` before matching on it".into()),
+                    }));
+                };
+                let fits = match inner {
+                    PawType::Any => true,
+                    PawType::Record(r) | PawType::Generic(r, _) => r == name,
+                    PawType::Protocol(p) => self.scope.conforms(name, p),
+                    _ => false,
+                };
+                if !fits {
+                    return Err(never(self, format!("`{} {{ .. }}`", name)));
+                }
+                // 泛型 record 的字段类型代入实例的类型实参，不知道实参时为 Any
+                let bindings = self.type_args(inner);
+                let declared: Vec<(String, PawType)> = declared
+                    .into_iter()
+                    .map(|(n, t)| (n, t.substitute(&bindings, &|_| PawType::Any)))
+                    .collect();
+                for (field, sub) in fields {
+                    let Some((_, field_ty)) = declared.iter().find(|(n, _)| n == field) else {
+                        let names: Vec<&str> = declared.iter().map(|(n, _)| n.as_str()).collect();
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3015",
+                            message: format!("Record `{}` has no field `{}`", name, field),
+                            line: sub.line,
+                            column: sub.col,
+                            snippet: None,
+                            hint: closest_name(field, &names).map(|s| format!("Did you mean `{}`?", s)),
+                        }));
+                    };
+                    self.check_pattern(sub, field_ty)?;
+                }
+            }
+            PatternKind::Tuple(items) => match inner {
+                PawType::Tuple(elems) if elems.len() == items.len() => {
+                    for (item, elem) in items.iter().zip(elems) {
+                        self.check_pattern(item, elem)?;
+                    }
+                }
+                PawType::Any => {
+                    for item in items {
+                        self.check_pattern(item, &PawType::Any)?;
+                    }
+                }
+                _ => return Err(never(self, format!("with {} elements", items.len()))),
+            },
+            PatternKind::Variant {
+                enum_name,
+                variant,
+                args,
+            } => {
+                if self.scope.enum_variants(enum_name).is_none() {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3028",
+                        message: format!("Unknown type `{}`", enum_name),
+                        line: pattern.line,
+                        column: pattern.col,
+                        snippet: None,
+                        hint: Some("Declare it with `enum` before matching on it".into()),
+                    }));
+                }
+                if !matches!(inner, PawType::Any) && *inner != PawType::Enum(enum_name.clone()) {
+                    return Err(never(self, format!("`{}.{}`", enum_name, variant)));
+                }
+                let payload = self.enum_variant(enum_name, variant, pattern.line, pattern.col)?;
+                if let Some(args) = args {
+                    if args.len() != payload.len() {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3045",
+                            message: format!(
+                                "Variant `{}.{}` carries {} value(s), but the pattern has {}",
+                                enum_name,
+                                variant,
+                                payload.len(),
+                                args.len()
+                            ),
+                            line: pattern.line,
+                            column: pattern.col,
+                            snippet: None,
+                            hint: Some(format!(
+                                "Write one pattern per value, or `{}.{}` to ignore them",
+                                enum_name, variant
+                            )),
+                        }));
+                    }
+                    for (arg, t) in args.iter().zip(&payload) {
+                        self.check_pattern(arg, t)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 在分支作用域中定义模式变量；同一模式中重复的名字报错
+    fn bind_pattern(&mut self, name: &str, ty: PawType, pattern: &Pattern) -> Result<(), Box<PawError>> {
+        self.scope
+            .define(name, ty, pattern.line, pattern.col, &self.current_file)
+            .map_err(|_| Box::new(PawError::DuplicateDefinition {
+                file: self.current_file.clone(),
+                code: "E2005",
+                name: name.to_string(),
+                line: pattern.line,
+                column: pattern.col,
+                snippet: None,
+                hint: Some("Each name can be bound only once in a pattern".into()),
+            }))
+    }
+
+    /// 这些模式合起来是否能匹配 `ty` 类型的所有值
+    fn patterns_exhaust(&self, patterns: &[&Pattern], ty: &PawType) -> bool {
+        if patterns.iter().any(|p| self.pattern_covers(p, ty)) {
+            return true;
+        }
+        let has = |key: &ExprKind| {
+            patterns
+                .iter()
+                .any(|p| matches!(&p.kind, PatternKind::Literal(lit) if lit.kind == *key))
+        };
+        match ty {
+            PawType::Bool => has(&ExprKind::LiteralBool(true)) && has(&ExprKind::LiteralBool(false)),
+            PawType::Optional(inner) => has(&ExprKind::LiteralNopaw) && self.patterns_exhaust(patterns, inner),
+            // 每个变体都要有一个分支匹配它携带的所有值
+            PawType::Enum(name) => self.scope.enum_variants(name).is_some_and(|variants| {
+                variants.iter().all(|(variant, payload)| {
+                    patterns.iter().any(|p| match &p.kind {
+                        PatternKind::Variant {
+                            enum_name,
+                            variant: v,
+                            args,
+                        } => {
+                            enum_name == name
+                                && v == variant
+                                && args.as_ref().is_none_or(|args| {
+                                    args.iter().zip(payload).all(|(a, t)| self.pattern_covers(a, t))
+                                })
+                        }
+                        _ => false,
+                    })
+                })
+            }),
+            _ => false,
+        }
+    }
+
+    /// 单个模式是否匹配 `ty` 类型的所有值
+    fn pattern_covers(&self, pattern: &Pattern, ty: &PawType) -> bool {
+        match (&pattern.kind, ty) {
+            (PatternKind::Wildcard | PatternKind::Bind(_), _) => true,
+            (PatternKind::Typed { ty: node, .. }, _) => match self.type_of(node) {
+                Ok(to) => {
+                    to == PawType::Any
+                        || to == *ty
+                        || matches!(&to, PawType::Optional(inner) if **inner == *ty)
+                }
+                Err(_) => false,
+            },
+            (PatternKind::Record { name, fields }, PawType::Record(r) | PawType::Generic(r, _)) => {
+                let declared = self.scope.record_fields(r).unwrap_or_default();
+                name == r
+                    && fields.iter().all(|(field, sub)| {
+                        declared
+                            .iter()
+                            .find(|(n, _)| n == field)
+                            .is_some_and(|(_, t)| self.pattern_covers(sub, t))
+                    })
+            }
+            (PatternKind::Tuple(items), PawType::Tuple(elems)) => {
+                items.len() == elems.len() && items.iter().zip(elems).all(|(p, t)| self.pattern_covers(p, t))
+            }
+            _ => false,
+        }
+    }
+
+    /// 检查内置函数调用并给出返回类型；`name` 不是内置函数时返回 None
+    fn check_builtin_call(
+        &self,
+        name: &str,
+        arg_tys: &[PawType],
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Option<Result<PawType, Box<PawError>>> {
+        let (signature, arities): (&str, &[usize]) = match name {
+            "memoize" => ("memoize(f: fun(...): T): fun(...): T", &[1]),
+            "range" => ("range(end: Int) or range(start: Int, end: Int): Array<Int>", &[1, 2]),
+            "zip" => ("zip(a: Array<A>, b: Array<B>): Array<Array<T>>", &[2]),
+            "repeat" => ("repeat(value: T, n: Int): Array<T>", &[2]),
+            "sleep" => ("sleep(ms: Int): Void", &[1]),
+            "ok" => ("ok(value: T): Result<T, E>", &[1]),
+            "err" => ("err(error: E): Result<T, E>", &[1]),
+            "inspect" => ("inspect(value: T, depth: Int, width: Int): String", &[1, 2, 3]),
+            "assert" => ("assert(condition: Bool, message: String): Void", &[1, 2]),
+            "assert_snapshot" => ("assert_snapshot(name: String, value: T): Void", &[2]),
+            "assert_eq" => ("assert_eq(actual: T, expected: T, message: String): Void", &[2, 3]),
+            "assert_ne" => ("assert_ne(actual: T, expected: T, message: String): Void", &[2, 3]),
+            "assert_close" => (
+                "assert_close(actual: Double, expected: Double, eps: Double, message: String): Void",
+                &[3, 4],
+            ),
+            "assert_throws" => ("assert_throws(f: fun(): T, message: String): String", &[1, 2]),
+            "gen_int" => ("gen_int(lo: Int, hi: Int): Gen<Int>", &[2]),
+            "gen_double" => ("gen_double(lo: Double, hi: Double): Gen<Double>", &[2]),
+            "gen_bool" => ("gen_bool(): Gen<Bool>", &[0]),
+            "gen_string" => ("gen_string(max_len: Int): Gen<String>", &[1]),
+            "gen_array" => ("gen_array(gen: Gen<T>, max_len: Int): Gen<Array<T>>", &[2]),
+            "gen_one_of" => ("gen_one_of(values: Array<T>): Gen<T>", &[1]),
+            "gen_pair" => ("gen_pair(a: Gen<A>, b: Gen<B>): Gen<(A, B)>", &[2]),
+            "forall" => ("forall(gen: Gen<T>, property: fun(T): Bool, runs: Int): Void", &[2, 3]),
+            "mock_ask" => ("mock_ask(answers: Array<String>): Void", &[1]),
+            "mock_time" => ("mock_time(millis: Long): Void", &[1]),
+            "seed_random" => ("seed_random(seed: Long): Void", &[1]),
+            // 参数个数可变，在下面单独检查
+            "format" => ("format(template: String, values...): String", &[]),
+            _ => return None,
+        };
+        if name == "format" {
+            return Some(self.check_format_call(signature, arg_tys, args, expr));
+        }
+        if !arities.contains(&arg_tys.len()) {
+            let expected: Vec<String> = arities.iter().map(|n| n.to_string()).collect();
+            return Some(Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3032",
+                message: format!(
+                    "Function '{}' expects {} argument(s), found {}",
+                    name,
+                    expected.join(" or "),
+                    arg_tys.len()
+                ),
+                line: expr.line,
+                column: expr.col,
+                snippet: None,
+                hint: Some(format!("Signature: {}", signature)),
+            })));
+        }
+        let mismatch = |i: usize, expected: &str| Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3033",
+            message: format!(
+                "Argument to '{}' has type {}, expected {}",
+                name, arg_tys[i], expected
+            ),
+            line: args[i].line,
+            column: args[i].col,
+            snippet: None,
+            hint: Some(format!("Signature: {}", signature)),
+        });
+        let is_int = |t: &PawType| matches!(t, PawType::Int | PawType::Any);
+        let elem = |t: &PawType| match t {
+            PawType::Array(inner) => Some((**inner).clone()),
+            PawType::Any => Some(PawType::Any),
+            _ => None,
+        };
+        let gen = |t: &PawType| match t {
+            PawType::Gen(inner) => Some((**inner).clone()),
+            PawType::Any => Some(PawType::Any),
+            _ => None,
+        };
+        Some(match name {
+            // memoize 返回与参数签名相同的函数
+            "memoize" => match &arg_tys[0] {
+                f @ (PawType::Function(..) | PawType::Any) => Ok(f.clone()),
+                _ => Err(mismatch(0, "a function")),
+            },
+            "range" => match arg_tys.iter().position(|t| !is_int(t)) {
+                Some(i) => Err(mismatch(i, "Int")),
+                None => Ok(PawType::Array(Box::new(PawType::Int))),
+            },
+            // 两边元素类型相同时保留该类型，否则每一对的类型为 Array<Any>
+            "zip" => match (elem(&arg_tys[0]), elem(&arg_tys[1])) {
+                (None, _) => Err(mismatch(0, "an Array")),
+                (_, None) => Err(mismatch(1, "an Array")),
+                (Some(a), Some(b)) => {
+                    let pair = if a == b { a } else { PawType::Any };
+                    Ok(PawType::Array(Box::new(PawType::Array(Box::new(pair)))))
+                }
+            },
+            "repeat" => {
+                if is_int(&arg_tys[1]) {
+                    Ok(PawType::Array(Box::new(arg_tys[0].clone())))
+                } else {
+                    Err(mismatch(1, "Int"))
+                }
+            }
+            "sleep" => {
+                if is_int(&arg_tys[0]) {
+                    Ok(PawType::Void)
+                } else {
+                    Err(mismatch(0, "Int"))
+                }
+            }
+            "inspect" => match arg_tys.iter().skip(1).position(|t| !is_int(t)) {
+                Some(i) => Err(mismatch(i + 1, "Int")),
+                None => Ok(PawType::String),
+            },
+            "assert" => match &arg_tys[0] {
+                PawType::Bool | PawType::Any => match arg_tys.get(1) {
+                    Some(PawType::String | PawType::Any) | None => Ok(PawType::Void),
+                    Some(_) => Err(mismatch(1, "String")),
+                },
+                _ => Err(mismatch(0, "Bool")),
+            },
+            "assert_snapshot" => match &arg_tys[0] {
+                PawType::String | PawType::Any => Ok(PawType::Void),
+                _ => Err(mismatch(0, "String")),
+            },
+            // 两个值的类型至少要有一方接受另一方，否则比较永远不会成立
+            "assert_eq" | "assert_ne" => {
+                let (actual, expected) = (&arg_tys[0], &arg_tys[1]);
+                if !actual.accepts(expected) && !expected.accepts(actual) {
+                    Err(mismatch(1, &actual.to_string()))
+                } else if !matches!(arg_tys.get(2), Some(PawType::String | PawType::Any) | None) {
+                    Err(mismatch(2, "String"))
+                } else {
+                    Ok(PawType::Void)
+                }
+            }
+            "assert_close" => match arg_tys.iter().take(3).position(|t| !t.is_numeric() && *t != PawType::Any) {
+                Some(i) => Err(mismatch(i, "Double")),
+                None if !matches!(arg_tys.get(3), Some(PawType::String | PawType::Any) | None) => {
+                    Err(mismatch(3, "String"))
+                }
+                None => Ok(PawType::Void),
+            },
+            "assert_throws" => match &arg_tys[0] {
+                PawType::Function(params, _) if params.is_empty() => match arg_tys.get(1) {
+                    Some(PawType::String | PawType::Any) | None => Ok(PawType::String),
+                    Some(_) => Err(mismatch(1, "String")),
+                },
+                PawType::Any => Ok(PawType::String),
+                _ => Err(mismatch(0, "fun(): T")),
+            },
+            "gen_int" => match arg_tys.iter().position(|t| !is_int(t)) {
+                Some(i) => Err(mismatch(i, "Int")),
+                None => Ok(PawType::Gen(Box::new(PawType::Int))),
+            },
+            "gen_double" => match arg_tys.iter().position(|t| !t.is_numeric() && *t != PawType::Any) {
+                Some(i) => Err(mismatch(i, "Double")),
+                None => Ok(PawType::Gen(Box::new(PawType::Double))),
+            },
+            "gen_bool" => Ok(PawType::Gen(Box::new(PawType::Bool))),
+            "gen_string" => match is_int(&arg_tys[0]) {
+                true => Ok(PawType::Gen(Box::new(PawType::String))),
+                false => Err(mismatch(0, "Int")),
+            },
+            "gen_array" => match (gen(&arg_tys[0]), is_int(&arg_tys[1])) {
+                (None, _) => Err(mismatch(0, "a Gen")),
+                (_, false) => Err(mismatch(1, "Int")),
+                (Some(t), true) => Ok(PawType::Gen(Box::new(PawType::Array(Box::new(t))))),
+            },
+            "gen_one_of" => match elem(&arg_tys[0]) {
+                Some(t) => Ok(PawType::Gen(Box::new(t))),
+                None => Err(mismatch(0, "an Array")),
+            },
+            "gen_pair" => match (gen(&arg_tys[0]), gen(&arg_tys[1])) {
+                (None, _) => Err(mismatch(0, "a Gen")),
+                (_, None) => Err(mismatch(1, "a Gen")),
+                (Some(a), Some(b)) => Ok(PawType::Gen(Box::new(PawType::Tuple(vec![a, b])))),
+            },
+            // 属性接受一个生成的值，返回 Bool（false 表示失败）或 Void（用 assert 检查）
+            "forall" => {
+                let Some(input) = gen(&arg_tys[0]) else {
+                    return Some(Err(mismatch(0, "a Gen")));
+                };
+                let fits = match &arg_tys[1] {
+                    PawType::Function(params, ret) => {
+                        params.len() == 1
+                            && params[0].accepts(&input)
+                            && matches!(**ret, PawType::Bool | PawType::Void | PawType::Any)
+                    }
+                    t => *t == PawType::Any,
+                };
+                if !fits {
+                    Err(mismatch(1, &format!("fun({}): Bool or fun({}): Void", input, input)))
+                } else if arg_tys.len() == 3 && !is_int(&arg_tys[2]) {
+                    Err(mismatch(2, "Int"))
+                } else {
+                    Ok(PawType::Void)
+                }
+            }
+            "mock_ask" => match elem(&arg_tys[0]) {
+                Some(PawType::String | PawType::Any) => Ok(PawType::Void),
+                _ => Err(mismatch(0, "Array<String>")),
+            },
+            "mock_time" | "seed_random" => match &arg_tys[0] {
+                PawType::Int | PawType::Long | PawType::Any => Ok(PawType::Void),
+                _ => Err(mismatch(0, "Long")),
+            },
+            // 另一半类型由 let、参数或返回类型的声明确定
+            "ok" => Ok(PawType::Result(Box::new(arg_tys[0].clone()), Box::new(PawType::Any))),
+            "err" => Ok(PawType::Result(Box::new(PawType::Any), Box::new(arg_tys[0].clone()))),
+            _ => unreachable!(),
+        })
+    }
+
+    /// `format(template, values...)`：模板是字面量时，值的个数必须与占位符一致
+    fn check_format_call(
+        &self,
+        signature: &str,
+        arg_tys: &[PawType],
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Result<PawType, Box<PawError>> {
+        let bad_arity = |message: String| Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3032",
+            message,
+            line: expr.line,
+            column: expr.col,
+            snippet: None,
+            hint: Some(format!("Signature: {}", signature)),
+        });
+        let Some(template_ty) = arg_tys.first() else {
+            return Err(bad_arity(
+                "Function 'format' expects at least 1 argument, found 0".into(),
+            ));
+        };
+        if !matches!(template_ty, PawType::String | PawType::Any) {
+            return Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3033",
+                message: format!(
+                    "Argument to 'format' has type {}, expected String",
+                    template_ty
+                ),
+                line: args[0].line,
+                column: args[0].col,
+                snippet: None,
+                hint: Some(format!("Signature: {}", signature)),
+            }));
+        }
+        if let ExprKind::LiteralString(template) = &args[0].kind {
+            let pieces = parse_format(template).map_err(|message| PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3033",
+                message,
+                line: args[0].line,
+                column: args[0].col,
+                snippet: None,
+                hint: Some("Write `{{` and `}}` for literal braces".into()),
+            })?;
+            let used = placeholder_count(&pieces);
+            if used != args.len() - 1 {
+                return Err(bad_arity(format!(
+                    "Format string expects {} value(s), found {}",
+                    used,
+                    args.len() - 1
+                )));
+            }
+            for piece in &pieces {
+                if let FormatPiece::Arg(i, Some(spec)) = piece {
+                    self.check_format_spec(spec, &arg_tys[i + 1], &args[i + 1])?;
+                }
+            }
+        }
+        Ok(PawType::String)
+    }
+
+    /// 格式说明能否用于该类型的值：`+`、`0` 需要数字，精度需要浮点数或字符串；Any 留给运行时确认
+    fn check_format_spec(&self, spec: &FormatSpec, ty: &PawType, expr: &Expr) -> Result<(), Box<PawError>> {
+        let fits = *ty == PawType::Any
+            || ((!spec.sign && !spec.zero) || ty.is_numeric())
+                && (spec.precision.is_none() || ty.is_float() || *ty == PawType::String);
+        if fits {
+            return Ok(());
+        }
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3047",
+            message: format!("Format spec `{}` cannot format a value of type {}", spec, ty),
+            line: expr.line,
+            column: expr.col,
+            snippet: None,
+            hint: Some("`+` and `0` apply to numbers; a precision applies to Float, Double and String".into()),
+        }))
+    }
+
+    /// 检查参数默认值的类型：必须能赋给参数类型
+    fn check_default(&mut self, name: &str, ty: &PawType, default: &Expr) -> Result<(), Box<PawError>> {
+        let found = self.check_value(default, ty)?;
+        let nopaw_ok = matches!(default.kind, ExprKind::LiteralNopaw) && matches!(ty, PawType::Optional(_));
+        if nopaw_ok || self.accepts(ty, &found) {
+            return Ok(());
+        }
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3003",
+            message: format!(
+                "Type mismatch in default value of parameter '{}': expected {}, found {}",
+                name, ty, found
+            ),
+            line: default.line,
+            column: default.col,
+            snippet: None,
+            hint: await_hint(ty, &found),
+        }))
+    }
+
+    /// 按函数签名检查参数个数与类型，返回调用结果的类型
+    fn check_call_args(
+        &self,
+        name: &str,
+        mut params: Vec<PawType>,
+        mut ret: PawType,
+        arg_tys: &[PawType],
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Result<PawType, Box<PawError>> {
+        // 泛型函数在调用处按实参确定类型参数，再按代入后的签名检查
+        if params.iter().chain([&ret]).any(PawType::has_type_params) {
+            (params, ret) = self.instantiate(params, ret, arg_tys);
+        }
+        let names = self.scope.param_names(name).filter(|names| names.len() >= params.len());
+        if params.len() != args.len() {
+            let signature = PawType::Function(params.clone(), Box::new(ret));
+            return Err(self.arity_error(name, &params, names.as_deref(), signature, args, expr));
+        }
+        for (i, ((param, actual), arg)) in params.iter().zip(arg_tys).zip(args).enumerate() {
+            let nopaw_ok = matches!(arg.kind, ExprKind::LiteralNopaw)
+                && matches!(param, PawType::Optional(_));
+            if !nopaw_ok && !self.accepts(param, actual) {
+                let which = match &names {
+                    Some(names) => format!("Argument '{}' of", names[i]),
+                    None => "Argument to".into(),
+                };
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3033",
+                    message: format!("{} '{}' has type {}, expected {}", which, name, actual, param),
+                    line: arg.line,
+                    column: arg.col,
+                    snippet: None,
+                    hint: await_hint(param, actual),
+                }));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// 实参个数不符（E3032）：多传的报在第一个多余的实参处，少传的报在调用处并列出缺少的参数
+    fn arity_error(
+        &self,
+        name: &str,
+        params: &[PawType],
+        names: Option<&[String]>,
+        signature: PawType,
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Box<PawError> {
+        let (line, column, hint) = match args.get(params.len()) {
+            Some(extra) => (extra.line, extra.col, format!("Signature: {}", signature)),
+            None => {
+                let missing: Vec<String> = (args.len()..params.len())
+                    .map(|i| match names {
+                        Some(names) => format!("{}: {}", names[i], params[i]),
+                        None => params[i].to_string(),
+                    })
+                    .collect();
+                let hint = format!("Missing {}; signature: {}", missing.join(", "), signature);
+                (expr.line, expr.col, hint)
+            }
+        };
+        Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3032",
+            message: format!(
+                "Function '{}' expects {} argument(s), found {}",
+                name,
+                params.len(),
+                args.len()
+            ),
+            line,
+            column,
+            snippet: None,
+            hint: Some(hint),
+        })
+    }
+
+    /// 由实参类型推断签名中的类型参数并代入；推断不出的参数视为 Any，
+    /// 外层泛型声明中的类型参数（调用处仍可见）保持不变
+    fn instantiate(&self, params: Vec<PawType>, ret: PawType, arg_tys: &[PawType]) -> (Vec<PawType>, PawType) {
+        let mut bindings = HashMap::new();
+        for (param, actual) in params.iter().zip(arg_tys) {
+            param.bind_params(actual, &mut bindings);
+        }
+        let unbound = |name: &str| match self.scope.is_type_param(name) {
+            true => PawType::TypeParam(name.to_string()),
+            false => PawType::Any,
+        };
+        let params = params.iter().map(|p| p.substitute(&bindings, &unbound)).collect();
+        (params, ret.substitute(&bindings, &unbound))
+    }
+
+    /// 泛型 record 实例的类型参数 → 类型实参；其它类型没有类型实参
+    fn type_args(&self, ty: &PawType) -> HashMap<String, PawType> {
+        match ty {
+            PawType::Generic(name, args) => {
+                let params = self.scope.record_params(name).unwrap_or_default();
+                params.into_iter().zip(args.iter().cloned()).collect()
+            }
+            _ => HashMap::new(),
+        }
+    }
+
+    /// 可以用 `r.name` 读取的成员：record 的字段与方法，或 protocol 要求的成员；
+    /// 泛型 record 实例的成员类型代入类型实参，方法自己的类型参数留到调用处推断
+    fn members_of(&self, ty: &PawType) -> Option<Vec<(String, PawType)>> {
+        match ty {
+            PawType::Record(rec) => self.scope.record_members(rec),
+            PawType::Generic(rec, _) => {
+                let bindings = self.type_args(ty);
+                let keep = |name: &str| PawType::TypeParam(name.to_string());
+                let members = self.scope.record_members(rec)?;
+                Some(members.into_iter().map(|(n, t)| (n, t.substitute(&bindings, &keep))).collect())
+            }
+            PawType::Protocol(p) => self.scope.protocol_members(p),
+            _ => None,
+        }
+    }
+
+    /// 接收者是否为 enum 名（而不是同名的其他变量），是则返回该名字
+    fn enum_receiver(&self, receiver: &Expr) -> Option<String> {
+        match &receiver.kind {
+            ExprKind::Var(name)
+                if self.scope.lookup(name) == Some(PawType::Module)
+                    && self.scope.enum_variants(name).is_some() =>
+            {
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// 查找 enum 的变体，返回其数据类型；变体不存在时报 E3046
+    fn enum_variant(&self, enum_name: &str, variant: &str, line: usize, col: usize) -> Result<Vec<PawType>, Box<PawError>> {
+        let variants = self.scope.enum_variants(enum_name).unwrap_or_default();
+        if let Some((_, payload)) = variants.iter().find(|(v, _)| v == variant) {
+            return Ok(payload.clone());
+        }
+        let names: Vec<&str> = variants.iter().map(|(v, _)| v.as_str()).collect();
+        Err(Box::new(PawError::Type {
+            file: self.current_file.clone(),
+            code: "E3046",
+            message: format!("Enum `{}` has no variant `{}`", enum_name, variant),
+            line,
+            column: col,
+            snippet: None,
+            hint: Some(match closest_name(variant, &names) {
+                Some(name) => format!("Did you mean `{}.{}`?", enum_name, name),
+                None => format!("Variants of `{}`: {}", enum_name, names.join(", ")),
+            }),
+        }))
+    }
+
+    /// 检查 `Enum.Variant` 与 `Enum.Variant(args)`：不带数据的变体本身就是值，
+    /// 带数据的变体不调用时是构造函数
+    fn check_variant(
+        &self,
+        enum_name: &str,
+        variant: &str,
+        call: Option<(&[PawType], &[Expr])>,
+        expr: &Expr,
+    ) -> Result<PawType, Box<PawError>> {
+        self.warn_deprecated(enum_name, expr.line, expr.col);
+        let payload = self.enum_variant(enum_name, variant, expr.line, expr.col)?;
+        let ty = PawType::Enum(enum_name.to_string());
+        match call {
+            Some((arg_tys, args)) => {
+                let name = format!("{}.{}", enum_name, variant);
+                self.check_call_args(&name, payload, ty, arg_tys, args, expr)
             }
-            StatementKind::TryCatchFinally {
-                body,
-                err_name,
-                handler,
-                finally,
-            } => {
-                // 先忽略 try 里抛出的错误，正常检查主体
-                let _ = TypeChecker::with_parent(&self.scope, &self.current_file)
-                    .check_program(body)?; // 或者你的批量检查方法名
+            None if payload.is_empty() => Ok(ty),
+            None => Ok(PawType::Function(payload, Box::new(ty))),
+        }
+    }
 
-                // Catch 分支：在子作用域里把 err_name 定义成 String，然后检查 handler
-                let mut catch_checker = TypeChecker::with_parent(&self.scope, &self.current_file);
-                catch_checker
-                    .scope
-                    .define(
-                        err_name,
-                        PawType::String,
-                        stmt.line,
-                        stmt.col,
-                        &self.current_file,
-                    )
-                    .map_err(|_| PawError::DuplicateDefinition {
+    /// 检查 `模块.成员` 形式的访问：标准库模块按成员类型检查，文件模块的成员为 Any
+    fn check_module_member(&self, module: &Expr, field: &str, expr: &Expr) -> Result<PawType, Box<PawError>> {
+        let alias = match &module.kind {
+            ExprKind::Var(alias) => alias.as_str(),
+            _ => "",
+        };
+        let Some(path) = self.scope.std_module(alias) else {
+            return Ok(PawType::Any);
+        };
+        match std_member_type(&path, field) {
+            Some(ty) => Ok(ty.fixed().unwrap_or(PawType::Any)),
+            None => Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3021",
+                message: format!("Module '{}' has no member '{}'", alias, field),
+                line: expr.line,
+                column: expr.col,
+                snippet: None,
+                hint: None,
+            })),
+        }
+    }
+
+    /// 检查数值多态的标准库函数：参数都须为数值，结果为其中最宽的数值类型
+    fn check_numeric_call(
+        &self,
+        name: &str,
+        arity: usize,
+        arg_tys: &[PawType],
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Result<PawType, Box<PawError>> {
+        let signature = format!("{}({}): widest argument type", name, vec!["number"; arity].join(", "));
+        if arg_tys.len() != arity {
+            return Err(Box::new(PawError::Type {
+                file: self.current_file.clone(),
+                code: "E3032",
+                message: format!(
+                    "Function '{}' expects {} argument(s), found {}",
+                    name,
+                    arity,
+                    arg_tys.len()
+                ),
+                line: expr.line,
+                column: expr.col,
+                snippet: None,
+                hint: Some(format!("Signature: {}", signature)),
+            }));
+        }
+        let mut widest = PawType::Int;
+        for (ty, arg) in arg_tys.iter().zip(args) {
+            widest = match (ty, &widest) {
+                (PawType::Any, _) | (_, PawType::Any) => PawType::Any,
+                (PawType::Double, _) | (_, PawType::Double) => PawType::Double,
+                (PawType::Float, _) | (_, PawType::Float) => PawType::Float,
+                (PawType::Long, _) | (_, PawType::Long) => PawType::Long,
+                (PawType::Int, _) => PawType::Int,
+                _ => {
+                    return Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
-                        code: "E2005",
-                        name: err_name.clone(),
-                        line: stmt.line,
-                        column: stmt.col,
+                        code: "E3033",
+                        message: format!(
+                            "Argument to '{}' has type {}, expected a number",
+                            name, ty
+                        ),
+                        line: arg.line,
+                        column: arg.col,
                         snippet: None,
-                        hint: None,
-                    })?;
-                catch_checker.check_program(handler)?;
+                        hint: Some(format!("Signature: {}", signature)),
+                    }));
+                }
+            };
+        }
+        Ok(widest)
+    }
 
-                // Finally 分支也要在新作用域检查
-                TypeChecker::with_parent(&self.scope, &self.current_file).check_program(finally)?;
+    /// 检查接受一个数组的标准库函数：`same_array` 为真时结果为同类型数组，否则为元素类型
+    fn check_array_call(
+        &self,
+        name: &str,
+        same_array: bool,
+        arg_tys: &[PawType],
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Result<PawType, Box<PawError>> {
+        let signature = if same_array {
+            format!("{}(Array<T>): Array<T>", name)
+        } else {
+            format!("{}(Array<T>): T", name)
+        };
+        let elem = match (arg_tys, args) {
+            ([PawType::Array(elem)], _) => (**elem).clone(),
+            ([PawType::Any], _) => return Ok(PawType::Any),
+            ([ty], [arg]) => {
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3033",
+                    message: format!("Argument to '{}' has type {}, expected an Array", name, ty),
+                    line: arg.line,
+                    column: arg.col,
+                    snippet: None,
+                    hint: Some(format!("Signature: {}", signature)),
+                }));
             }
-        }
-        Ok(())
+            _ => {
+                return Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3032",
+                    message: format!(
+                        "Function '{}' expects 1 argument(s), found {}",
+                        name,
+                        arg_tys.len()
+                    ),
+                    line: expr.line,
+                    column: expr.col,
+                    snippet: None,
+                    hint: Some(format!("Signature: {}", signature)),
+                }));
+            }
+        };
+        Ok(if same_array {
+            PawType::Array(Box::new(elem))
+        } else {
+            elem
+        })
     }
 
-    pub fn check_expr(&mut self, expr: &Expr) -> Result<PawType, PawError> {
+    pub fn check_expr(&mut self, expr: &Expr) -> Result<PawType, Box<PawError>> {
         match &expr.kind {
             ExprKind::LiteralInt(_) => Ok(PawType::Int),
             ExprKind::LiteralLong(_) => Ok(PawType::Long),
@@ -528,10 +2772,21 @@ impl TypeChecker {
             ExprKind::LiteralChar(_) => Ok(PawType::Char),
             ExprKind::LiteralNopaw => Ok(PawType::Optional(Box::new(PawType::Any))),
 
-            ExprKind::Var(n) => self
-                .scope
-                .lookup(n)
-                .ok_or_else(|| PawError::UndefinedVariable {
+            ExprKind::Var(n) => {
+                self.warn_deprecated(n, expr.line, expr.col);
+                self.check_barks_value(n, expr)?;
+                if self.scope.overloads(n).is_some() {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3049",
+                        message: format!("'{}' is overloaded and cannot be used as a value", n),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: Some(format!("Call '{}' directly, or wrap the overload you need in a lambda", n)),
+                    }));
+                }
+                self.var_type(n).ok_or_else(|| Box::new(PawError::UndefinedVariable {
                     file: self.current_file.clone(),
                     code: "E4001",
                     name: n.clone(),
@@ -539,14 +2794,15 @@ impl TypeChecker {
                     column: expr.col,
                     snippet: None,
                     hint: Some("Did you declare this variable before use?".into()),
-                }),
+                }))
+            }
 
             ExprKind::UnaryOp { op, expr: inner } => {
                 let t = self.check_expr(inner)?;
                 match op.as_str() {
                     "-" if t.is_numeric() => Ok(t),
                     "!" if t == PawType::Bool => Ok(PawType::Bool),
-                    _ => Err(PawError::Type {
+                    _ => Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3013",
                         message: format!("Bad unary '{}' on {}", op, t),
@@ -554,14 +2810,20 @@ impl TypeChecker {
                         column: expr.col,
                         snippet: None,
                         hint: None,
-                    }),
+                    })),
                 }
             }
 
             ExprKind::BinaryOp { op, left, right } => {
-                let l = self.check_expr(left)?;
-                let r = self.check_expr(right)?;
-                l.binary_result(op, &r).map_err(|msg| PawError::Type {
+                let role = format!("an operand of `{}`", op);
+                let l = self.check_operand(left, &role)?;
+                // `x is T && ...` 的右侧已知 x 为 T
+                let r = if *op == BinaryOp::And {
+                    self.narrowed(left)?.check_operand(right, &role)?
+                } else {
+                    self.check_operand(right, &role)?
+                };
+                l.binary_result(op, &r).map_err(|msg| Box::new(PawError::Type {
                     file: self.current_file.clone(),
                     code: "E3014",
                     message: msg,
@@ -569,49 +2831,130 @@ impl TypeChecker {
                     column: expr.col,
                     snippet: None,
                     hint: None,
-                })
+                }))
             }
 
             ExprKind::Call { name, args } => {
+                let mut arg_tys = Vec::new();
                 for a in args {
-                    let _ = self.check_expr(a)?;
+                    arg_tys.push(self.check_operand(a, "passed as an argument")?);
                 }
                 // 模块调用一律 Any
                 if name.contains('.') {
-                    Ok(PawType::Any)
-                } else {
-                    self.scope
-                        .lookup(name)
-                        .ok_or_else(|| PawError::UndefinedVariable {
-                            file: self.current_file.clone(),
-                            code: "E4001",
-                            name: name.clone(),
-                            line: expr.line,
-                            column: expr.col,
-                            snippet: None,
-                            hint: None,
-                        })
+                    return Ok(PawType::Any);
+                }
+                self.warn_deprecated(name, expr.line, expr.col);
+                self.check_barks_handled(name, expr)?;
+                if let Some(overloads) = self.scope.overloads(name) {
+                    return self.check_overloaded_call(name, overloads, &arg_tys, args, expr);
+                }
+                // 脚本中没有定义时再找内置函数
+                let Some(callee) = self.var_type(name) else {
+                    return self
+                        .check_builtin_call(name, &arg_tys, args, expr)
+                        .unwrap_or_else(|| {
+                            Err(Box::new(PawError::UndefinedVariable {
+                                file: self.current_file.clone(),
+                                code: "E4001",
+                                name: name.clone(),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: None,
+                            }))
+                        });
+                };
+                match callee {
+                    PawType::Function(mut params, ret) => {
+                        // 省略的尾部参数使用默认值
+                        if let Some(required) = self.scope.required_params(name) {
+                            if args.len() < required || args.len() > params.len() {
+                                // 多传的报在第一个多余的实参处
+                                let (line, column) = args.get(params.len()).map_or((expr.line, expr.col), |a| (a.line, a.col));
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3032",
+                                    message: format!(
+                                        "Function '{}' expects {} to {} argument(s), found {}",
+                                        name,
+                                        required,
+                                        params.len(),
+                                        args.len()
+                                    ),
+                                    line,
+                                    column,
+                                    snippet: None,
+                                    hint: Some(format!("Signature: {}", PawType::Function(params, ret))),
+                                }));
+                            }
+                            params.truncate(args.len());
+                        }
+                        self.check_call_args(name, params, *ret, &arg_tys, args, expr)
+                    }
+                    // 类型为 Any 的值（如未标注类型的参数）在运行时才检查
+                    PawType::Any => Ok(PawType::Any),
+                    other => Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E4002",
+                        message: format!("'{}' is not callable (type {})", name, other),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: None,
+                    })),
                 }
             }
 
             ExprKind::Cast { expr: inner, ty } => {
                 let from = self.check_expr(inner)?;
-                let to = PawType::from_str(ty);
-                if to == PawType::Any || from == to || (from.is_numeric() && to.is_numeric()) {
+                let to = self.resolve_type(ty)?;
+                if from.casts_to(&to) {
                     Ok(to)
                 } else {
-                    Err(PawError::Type {
+                    Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3009",
                         message: format!("Cannot cast {} to {}", from, to),
                         line: expr.line,
                         column: expr.col,
                         snippet: None,
-                        hint: None,
-                    })
+                        hint: Some(
+                            "`as` converts between numbers, between basic types and String, and between Char and Int"
+                                .into(),
+                        ),
+                    }))
                 }
             }
 
+            ExprKind::TypeTest { expr: inner, ty } => {
+                let from = self.check_operand(inner, "tested with `is`")?;
+                let to = self.resolve_type(ty)?;
+                let message = if !to.is_testable() {
+                    format!("`{}` cannot be tested with `is` at runtime", to)
+                } else if !self.accepts(&from, &to) && !self.accepts(&to, &from) {
+                    format!("`{}` has type {} and can never be {}", inner, from, to)
+                } else {
+                    return Ok(PawType::Bool);
+                };
+                Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3044",
+                    message,
+                    line: expr.line,
+                    column: expr.col,
+                    snippet: None,
+                    hint: Some(
+                        "`is` tests basic types, records, and Optional, Array, Map, Set, Tuple or Result types built from them"
+                            .into(),
+                    ),
+                }))
+            }
+
+            ExprKind::Range { start, end, step, .. } => {
+                let elem = self.range_element(start, end, step.as_deref())?;
+                Ok(PawType::Range(Box::new(elem)))
+            }
+
             ExprKind::ArrayLiteral(elems) => {
                 // 1. 初始类型设为 Any
                 let mut elem_ty = PawType::Any;
@@ -625,7 +2968,7 @@ impl TypeChecker {
                         continue;
                     }
                     // 否则正常推断这个元素的类型
-                    let t = self.check_expr(e)?;
+                    let t = self.check_operand(e, "stored in an Array")?;
 
                     if elem_ty == PawType::Any {
                         // 第一个真值元素决定类型
@@ -637,7 +2980,7 @@ impl TypeChecker {
                         if &t == inner.as_ref() {
                             // OK，保持 Optional(X)
                         } else {
-                            return Err(PawError::Type {
+                            return Err(Box::new(PawError::Type {
                                 file: self.current_file.clone(),
                                 code: "E3010",
                                 message: format!("Array elements mismatch: {} vs {}", elem_ty, t),
@@ -645,14 +2988,14 @@ impl TypeChecker {
                                 column: expr.col,
                                 snippet: None,
                                 hint: None,
-                            });
+                            }));
                         }
                     } else if let PawType::Optional(inner2) = t.clone() {
                         // t 是 Optional(X)，且 elem_ty == X，就把 elem_ty 提升为 Optional(X)
                         if elem_ty == *inner2 {
                             elem_ty = PawType::Optional(Box::new(elem_ty));
                         } else {
-                            return Err(PawError::Type {
+                            return Err(Box::new(PawError::Type {
                                 file: self.current_file.clone(),
                                 code: "E3010",
                                 message: format!("Array elements mismatch: {} vs {}", elem_ty, t),
@@ -660,11 +3003,11 @@ impl TypeChecker {
                                 column: expr.col,
                                 snippet: None,
                                 hint: None,
-                            });
+                            }));
                         }
                     } else {
                         // 其它任意组合都报错
-                        return Err(PawError::Type {
+                        return Err(Box::new(PawError::Type {
                             file: self.current_file.clone(),
                             code: "E3010",
                             message: format!("Array elements mismatch: {} vs {}", elem_ty, t),
@@ -672,7 +3015,7 @@ impl TypeChecker {
                             column: expr.col,
                             snippet: None,
                             hint: None,
-                        });
+                        }));
                     }
                 }
 
@@ -686,11 +3029,126 @@ impl TypeChecker {
                 Ok(PawType::Array(Box::new(final_ty)))
             }
 
+            ExprKind::MapLiteral(entries) => {
+                // 空字面量为 Map<Any, Any>，由 let/赋值处按声明类型确定
+                let mut key_ty = PawType::Any;
+                let mut val_ty = PawType::Any;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    let kt = self.check_expr(k)?;
+                    let vt = self.check_operand(v, "stored in a Map")?;
+                    if !kt.is_hashable() {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3026",
+                            message: format!("{} cannot be used as a Map key", kt),
+                            line: k.line,
+                            column: k.col,
+                            snippet: None,
+                            hint: Some(format!("Map keys must be {}", HASHABLE_TYPES)),
+                        }));
+                    }
+                    if i == 0 {
+                        key_ty = kt;
+                        val_ty = vt;
+                    } else if kt != key_ty || vt != val_ty {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3010",
+                            message: format!(
+                                "Map entries mismatch: {}: {} vs {}: {}",
+                                key_ty, val_ty, kt, vt
+                            ),
+                            line: k.line,
+                            column: k.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                }
+                Ok(PawType::Map(Box::new(key_ty), Box::new(val_ty)))
+            }
+
+            ExprKind::SetLiteral(items) => {
+                // 空字面量为 Set<Any>，由 let/赋值处按声明类型确定
+                let mut elem_ty = PawType::Any;
+                for (i, item) in items.iter().enumerate() {
+                    let t = self.check_expr(item)?;
+                    if !t.is_hashable() {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3026",
+                            message: format!("{} cannot be used as a Set element", t),
+                            line: item.line,
+                            column: item.col,
+                            snippet: None,
+                            hint: Some(format!("Set elements must be {}", HASHABLE_TYPES)),
+                        }));
+                    }
+                    if i == 0 {
+                        elem_ty = t;
+                    } else if t != elem_ty {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3010",
+                            message: format!("Set elements mismatch: {} vs {}", elem_ty, t),
+                            line: item.line,
+                            column: item.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                }
+                Ok(PawType::Set(Box::new(elem_ty)))
+            }
+
+            ExprKind::TupleLiteral(items) => Ok(PawType::Tuple(
+                items
+                    .iter()
+                    .map(|i| self.check_operand(i, "stored in a tuple"))
+                    .collect::<Result<_, _>>()?,
+            )),
+
             ExprKind::Index { array, index } => {
                 let at = self.check_expr(array)?;
                 let it = self.check_expr(index)?;
+                if let PawType::Map(key, val) = at {
+                    // Map 按键索引
+                    if it != *key && *key != PawType::Any {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3011",
+                            message: format!("Map key must be {}, found {}", key, it),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                    return Ok(*val);
+                }
+                if let PawType::Tuple(elems) = &at {
+                    // 元组按位置取元素，下标必须是范围内的整数字面量，元素类型才能确定
+                    return match index.kind {
+                        ExprKind::LiteralInt(i) if i >= 0 && (i as usize) < elems.len() => {
+                            Ok(elems[i as usize].clone())
+                        }
+                        _ => Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3041",
+                            message: format!(
+                                "Tuple index must be an Int literal from 0 to {}, found `{}`",
+                                elems.len() - 1,
+                                index
+                            ),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: Some("Destructure with `let (a, b) = ...` to name each element".into()),
+                        })),
+                    };
+                }
                 if it != PawType::Int {
-                    return Err(PawError::Type {
+                    return Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3011",
                         message: format!("Index must be Int, found {}", it),
@@ -698,12 +3156,12 @@ impl TypeChecker {
                         column: expr.col,
                         snippet: None,
                         hint: None,
-                    });
+                    }));
                 }
                 if let PawType::Array(inner) = at {
                     Ok(*inner)
                 } else {
-                    Err(PawError::Type {
+                    Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3012",
                         message: format!("Cannot index into {}", at),
@@ -711,229 +3169,933 @@ impl TypeChecker {
                         column: expr.col,
                         snippet: None,
                         hint: None,
-                    })
+                    }))
                 }
             }
 
             ExprKind::FieldAccess { expr: inner, field } => {
+                if let Some(enum_name) = self.enum_receiver(inner) {
+                    return self.check_variant(&enum_name, field, None, expr);
+                }
                 let ot = self.check_expr(inner)?;
-                if let PawType::Record(fields) = ot {
+                if ot == PawType::Module {
+                    return self.check_module_member(inner, field, expr);
+                }
+                if let Some(fields) = self.members_of(&ot) {
                     fields
                         .into_iter()
                         .find(|(n, _)| n == field)
                         .map(|(_, t)| t)
-                        .ok_or_else(|| PawError::Type {
+                        .ok_or_else(|| Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3015",
+                            message: format!("Record has no field {}", field),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        }))
+                } else {
+                    Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3016",
+                        message: format!("{} is not a record", ot),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: None,
+                    }))
+                }
+            }
+
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                // 1. 推断出接收者的类型
+                let recv_t = self.check_expr(receiver)?;
+                // 2. 推断出所有参数类型
+                let mut arg_types = Vec::with_capacity(args.len());
+                for arg in args {
+                    arg_types.push(self.check_operand(arg, "passed as an argument")?);
+                }
+
+                // —— String 方法 ——
+                if recv_t == PawType::String {
+                    match method.as_str() {
+                        "trim" | "to_uppercase" | "to_lowercase" => {
+                            // 无参数
+                            if !arg_types.is_empty() {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3023",
+                                    message: format!(
+                                        "Method '{}' on String takes no arguments, found {}",
+                                        method,
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            Ok(PawType::String)
+                        }
+                        "length" => {
+                            if !arg_types.is_empty() {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3023",
+                                    message: format!(
+                                        "Method 'length' on String takes no arguments, found {}",
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            Ok(PawType::Int)
+                        }
+                        "starts_with" | "ends_with" | "contains" => {
+                            // 这些方法需要且仅需要一个 String 参数
+                            if arg_types.len() != 1 {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3024",
+                                    message: format!(
+                                        "Method '{}' on String requires 1 argument, found {}",
+                                        method,
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            if arg_types[0] != PawType::String {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3025",
+                                    message: format!(
+                                        "Method '{}' on String requires String argument, found {}",
+                                        method, arg_types[0]
+                                    ),
+                                    line: args[0].line,
+                                    column: args[0].col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            Ok(PawType::Bool)
+                        }
+                        "to_int" | "to_double" => {
+                            // 可选参数：数字之间允许出现、解析时忽略的分隔符
+                            if arg_types.len() > 1 {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3024",
+                                    message: format!(
+                                        "Method '{}' on String requires 0 or 1 argument(s), found {}",
+                                        method,
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            if let Some(sep) = arg_types.first() {
+                                if !matches!(sep, PawType::String | PawType::Any) {
+                                    return Err(Box::new(PawError::Type {
+                                        file: self.current_file.clone(),
+                                        code: "E3025",
+                                        message: format!(
+                                            "Method '{}' on String requires String argument, found {}",
+                                            method, sep
+                                        ),
+                                        line: args[0].line,
+                                        column: args[0].col,
+                                        snippet: None,
+                                        hint: None,
+                                    }));
+                                }
+                            }
+                            Ok(if method.as_str() == "to_int" { PawType::Int } else { PawType::Double })
+                        }
+                        "split" | "replace" | "substring" => {
+                            // split(sep) / replace(from, to) / substring(start, end)
+                            let (params, ret) = match method.as_str() {
+                                "split" => (
+                                    vec![PawType::String],
+                                    PawType::Array(Box::new(PawType::String)),
+                                ),
+                                "replace" => (vec![PawType::String, PawType::String], PawType::String),
+                                _ => (vec![PawType::Int, PawType::Int], PawType::String),
+                            };
+                            if arg_types.len() != params.len() {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3024",
+                                    message: format!(
+                                        "Method '{}' on String requires {} argument(s), found {}",
+                                        method,
+                                        params.len(),
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            for (i, (param, actual)) in params.iter().zip(&arg_types).enumerate() {
+                                if actual != param && *actual != PawType::Any {
+                                    return Err(Box::new(PawError::Type {
+                                        file: self.current_file.clone(),
+                                        code: "E3025",
+                                        message: format!(
+                                            "Method '{}' on String requires {} argument, found {}",
+                                            method, param, actual
+                                        ),
+                                        line: args[i].line,
+                                        column: args[i].col,
+                                        snippet: None,
+                                        hint: None,
+                                    }));
+                                }
+                            }
+                            Ok(ret)
+                        }
+                        _ => Err(Box::new(PawError::Type {
                             file: self.current_file.clone(),
-                            code: "E3015",
-                            message: format!("Record has no field {}", field),
+                            code: "E3021",
+                            message: format!("Type String has no method '{}'", method),
                             line: expr.line,
                             column: expr.col,
                             snippet: None,
                             hint: None,
-                        })
-                } else {
-                    Err(PawError::Type {
-                        file: self.current_file.clone(),
-                        code: "E3016",
-                        message: format!("{} is not a record", ot),
-                        line: expr.line,
-                        column: expr.col,
-                        snippet: None,
-                        hint: None,
-                    })
-                }
-            }
-
-            ExprKind::MethodCall {
-                receiver,
-                method,
-                args,
-            } => {
-                // 1. 推断出接收者的类型
-                let recv_t = self.check_expr(receiver)?;
-                // 2. 推断出所有参数类型
-                let mut arg_types = Vec::with_capacity(args.len());
-                for arg in args {
-                    arg_types.push(self.check_expr(arg)?);
+                        })),
+                    }
                 }
-
-                // —— String 方法 ——
-                if recv_t == PawType::String {
+                // —— Array 方法 ——
+                else if let PawType::Array(inner) = recv_t.clone() {
                     match method.as_str() {
-                        "trim" | "to_uppercase" | "to_lowercase" => {
-                            // 无参数
+                        "push" => {
+                            // push 需要且仅需要一个参数，类型要与 inner 匹配
+                            if arg_types.len() != 1 {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3024",
+                                    message: format!(
+                                        "Method 'push' on Array requires 1 argument, found {}",
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            if arg_types[0] != *inner {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3022",
+                                    message: format!(
+                                        "Method 'push' on Array requires {} argument, found {}",
+                                        inner, arg_types[0]
+                                    ),
+                                    line: args[0].line,
+                                    column: args[0].col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            // push 原地修改接收者，接收者不是左值时修改会丢失
+                            if !matches!(
+                                receiver.kind,
+                                ExprKind::Var(_) | ExprKind::Index { .. } | ExprKind::FieldAccess { .. }
+                            ) {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3040",
+                                    message: "push modifies the array in place, but its receiver is a temporary value".into(),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: Some("Store the array in a variable first".into()),
+                                }));
+                            }
+                            Ok(PawType::Void)
+                        }
+                        "pop" => {
                             if !arg_types.is_empty() {
-                                return Err(PawError::Type {
+                                return Err(Box::new(PawError::Type {
                                     file: self.current_file.clone(),
                                     code: "E3023",
                                     message: format!(
-                                        "Method '{}' on String takes no arguments, found {}",
-                                        method,
+                                        "Method 'pop' on Array takes no arguments, found {}",
                                         arg_types.len()
                                     ),
                                     line: expr.line,
                                     column: expr.col,
                                     snippet: None,
                                     hint: None,
-                                });
+                                }));
                             }
-                            Ok(PawType::String)
+                            Ok(*inner)
                         }
                         "length" => {
                             if !arg_types.is_empty() {
-                                return Err(PawError::Type {
+                                return Err(Box::new(PawError::Type {
                                     file: self.current_file.clone(),
                                     code: "E3023",
                                     message: format!(
-                                        "Method 'length' on String takes no arguments, found {}",
+                                        "Method 'length' on Array takes no arguments, found {}",
                                         arg_types.len()
                                     ),
                                     line: expr.line,
                                     column: expr.col,
                                     snippet: None,
                                     hint: None,
-                                });
+                                }));
                             }
                             Ok(PawType::Int)
                         }
-                        "starts_with" | "ends_with" | "contains" => {
-                            // 这些方法需要且仅需要一个 String 参数
+                        "to_set" => {
+                            if !arg_types.is_empty() {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3023",
+                                    message: format!(
+                                        "Method 'to_set' on Array takes no arguments, found {}",
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            if !inner.is_hashable() {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3021",
+                                    message: format!("{} cannot be used as a Set element", inner),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: Some(format!("Set elements must be {}", HASHABLE_TYPES)),
+                                }));
+                            }
+                            Ok(PawType::Set(inner))
+                        }
+                        "join" => {
+                            // join(sep) 只对 Array<String> 可用
                             if arg_types.len() != 1 {
-                                return Err(PawError::Type {
+                                return Err(Box::new(PawError::Type {
                                     file: self.current_file.clone(),
                                     code: "E3024",
                                     message: format!(
-                                        "Method '{}' on String requires 1 argument, found {}",
-                                        method,
+                                        "Method 'join' on Array requires 1 argument, found {}",
                                         arg_types.len()
                                     ),
                                     line: expr.line,
                                     column: expr.col,
                                     snippet: None,
                                     hint: None,
-                                });
+                                }));
                             }
-                            if arg_types[0] != PawType::String {
-                                return Err(PawError::Type {
+                            if !matches!(*inner, PawType::String | PawType::Any) {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3021",
+                                    message: format!(
+                                        "Method 'join' requires Array<String>, found Array<{}>",
+                                        inner
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: Some("Convert the elements to String first, e.g. with map".into()),
+                                }));
+                            }
+                            if !matches!(arg_types[0], PawType::String | PawType::Any) {
+                                return Err(Box::new(PawError::Type {
                                     file: self.current_file.clone(),
                                     code: "E3025",
                                     message: format!(
-                                        "Method '{}' on String requires String argument, found {}",
-                                        method, arg_types[0]
+                                        "Method 'join' on Array requires String argument, found {}",
+                                        arg_types[0]
+                                    ),
+                                    line: args[0].line,
+                                    column: args[0].col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            Ok(PawType::String)
+                        }
+                        "map" | "filter" | "reduce" => {
+                            // map(f: fun(T): U) / filter(f: fun(T): Bool) / reduce(f: fun(A, T): A, init: A)
+                            let arity = if method.as_str() == "reduce" { 2 } else { 1 };
+                            if arg_types.len() != arity {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3024",
+                                    message: format!(
+                                        "Method '{}' on Array requires {} argument(s), found {}",
+                                        method,
+                                        arity,
+                                        arg_types.len()
+                                    ),
+                                    line: expr.line,
+                                    column: expr.col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            let acc = arg_types.get(1).cloned().unwrap_or(PawType::Any);
+                            let expected_params = if arity == 2 {
+                                vec![acc.clone(), (*inner).clone()]
+                            } else {
+                                vec![(*inner).clone()]
+                            };
+                            let ret = match &arg_types[0] {
+                                PawType::Function(ps, ret)
+                                    if ps.len() == expected_params.len()
+                                        && ps.iter().zip(&expected_params).all(|(p, e)| p.accepts(e)) =>
+                                {
+                                    (**ret).clone()
+                                }
+                                PawType::Any => PawType::Any,
+                                other => {
+                                    let params: Vec<String> =
+                                        expected_params.iter().map(|p| p.to_string()).collect();
+                                    return Err(Box::new(PawError::Type {
+                                        file: self.current_file.clone(),
+                                        code: "E3022",
+                                        message: format!(
+                                            "Callback of '{}' on {} must accept ({}), found {}",
+                                            method,
+                                            recv_t,
+                                            params.join(", "),
+                                            other
+                                        ),
+                                        line: args[0].line,
+                                        column: args[0].col,
+                                        snippet: None,
+                                        hint: None,
+                                    }));
+                                }
+                            };
+                            let ret_ok = match method.as_str() {
+                                "filter" => PawType::Bool.accepts(&ret),
+                                "reduce" => acc.accepts(&ret),
+                                _ => true,
+                            };
+                            if !ret_ok {
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3022",
+                                    message: format!(
+                                        "Callback of '{}' must return {}, found {}",
+                                        method,
+                                        if method.as_str() == "filter" { PawType::Bool } else { acc },
+                                        ret
+                                    ),
+                                    line: args[0].line,
+                                    column: args[0].col,
+                                    snippet: None,
+                                    hint: None,
+                                }));
+                            }
+                            Ok(match method.as_str() {
+                                "map" => PawType::Array(Box::new(ret)),
+                                "filter" => recv_t,
+                                _ => acc,
+                            })
+                        }
+                        "sort" | "reverse" | "slice" => {
+                            // sort() / sort(cmp: fun(T, T): Int) / reverse() / slice(start: Int, end: Int)
+                            let arities: &[usize] = match method.as_str() {
+                                "sort" => &[0, 1],
+                                "reverse" => &[0],
+                                _ => &[2],
+                            };
+                            if !arities.contains(&arg_types.len()) {
+                                let expected: Vec<String> = arities.iter().map(|n| n.to_string()).collect();
+                                return Err(Box::new(PawError::Type {
+                                    file: self.current_file.clone(),
+                                    code: "E3024",
+                                    message: format!(
+                                        "Method '{}' on Array requires {} argument(s), found {}",
+                                        method,
+                                        expected.join(" or "),
+                                        arg_types.len()
                                     ),
                                     line: expr.line,
                                     column: expr.col,
                                     snippet: None,
                                     hint: None,
-                                });
+                                }));
+                            }
+                            match (method.as_str(), &arg_types[..]) {
+                                // 不带比较函数时按元素的自然顺序排序
+                                ("sort", []) if !inner.is_numeric()
+                                    && !matches!(*inner, PawType::String | PawType::Char | PawType::Bool | PawType::Any) =>
+                                {
+                                    return Err(Box::new(PawError::Type {
+                                        file: self.current_file.clone(),
+                                        code: "E3021",
+                                        message: format!(
+                                            "Elements of {} have no natural order",
+                                            recv_t
+                                        ),
+                                        line: expr.line,
+                                        column: expr.col,
+                                        snippet: None,
+                                        hint: Some("Pass a comparator: sort(fun(a: T, b: T): Int { ... })".into()),
+                                    }));
+                                }
+                                ("sort", [cmp]) => {
+                                    let expected = PawType::Function(
+                                        vec![(*inner).clone(), (*inner).clone()],
+                                        Box::new(PawType::Int),
+                                    );
+                                    if !self.accepts(&expected, cmp) {
+                                        return Err(Box::new(PawError::Type {
+                                            file: self.current_file.clone(),
+                                            code: "E3022",
+                                            message: format!(
+                                                "Comparator of 'sort' on {} must be {}, found {}",
+                                                recv_t, expected, cmp
+                                            ),
+                                            line: args[0].line,
+                                            column: args[0].col,
+                                            snippet: None,
+                                            hint: None,
+                                        }));
+                                    }
+                                }
+                                ("slice", _) => {
+                                    if let Some(i) = arg_types
+                                        .iter()
+                                        .position(|t| !matches!(t, PawType::Int | PawType::Any))
+                                    {
+                                        return Err(Box::new(PawError::Type {
+                                            file: self.current_file.clone(),
+                                            code: "E3025",
+                                            message: format!(
+                                                "Method 'slice' on Array requires Int argument, found {}",
+                                                arg_types[i]
+                                            ),
+                                            line: args[i].line,
+                                            column: args[i].col,
+                                            snippet: None,
+                                            hint: None,
+                                        }));
+                                    }
+                                }
+                                _ => {}
                             }
+                            Ok(recv_t)
+                        }
+                        _ => {
+                            Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3021",
+                                message: format!(
+                                    "Type {} has no method '{}'",
+                                    PawType::Array(inner),
+                                    method
+                                ),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: None,
+                            }))
+                        }
+                    }
+                }
+                // —— Map 方法 ——
+                else if let PawType::Map(key, val) = recv_t.clone() {
+                    let expect_args = |n: usize| -> Result<(), Box<PawError>> {
+                        if arg_types.len() != n {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3024",
+                                message: format!(
+                                    "Method '{}' on Map requires {} argument(s), found {}",
+                                    method,
+                                    n,
+                                    arg_types.len()
+                                ),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: None,
+                            }));
+                        }
+                        Ok(())
+                    };
+                    let check_arg = |i: usize, expected: &PawType| -> Result<(), Box<PawError>> {
+                        if &arg_types[i] != expected && *expected != PawType::Any {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3022",
+                                message: format!(
+                                    "Argument {} of '{}' on Map: expected {}, found {}",
+                                    i + 1,
+                                    method,
+                                    expected,
+                                    arg_types[i]
+                                ),
+                                line: args[i].line,
+                                column: args[i].col,
+                                snippet: None,
+                                hint: None,
+                            }));
+                        }
+                        Ok(())
+                    };
+                    match method.as_str() {
+                        "insert" => {
+                            expect_args(2)?;
+                            check_arg(0, &key)?;
+                            check_arg(1, &val)?;
+                            Ok(recv_t)
+                        }
+                        "remove" => {
+                            expect_args(1)?;
+                            check_arg(0, &key)?;
+                            Ok(recv_t)
+                        }
+                        "contains_key" => {
+                            expect_args(1)?;
+                            check_arg(0, &key)?;
                             Ok(PawType::Bool)
                         }
-                        _ => Err(PawError::Type {
+                        "keys" => {
+                            expect_args(0)?;
+                            Ok(PawType::Array(key))
+                        }
+                        "values" => {
+                            expect_args(0)?;
+                            Ok(PawType::Array(val))
+                        }
+                        "length" => {
+                            expect_args(0)?;
+                            Ok(PawType::Int)
+                        }
+                        _ => Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3021",
+                            message: format!("Type {} has no method '{}'", recv_t, method),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        })),
+                    }
+                }
+                // —— Set 方法 ——
+                else if let PawType::Set(elem) = recv_t.clone() {
+                    // 每个方法的参数类型与返回类型
+                    let (params, ret) = match method.as_str() {
+                        "add" | "remove" => (vec![(*elem).clone()], recv_t.clone()),
+                        "contains" => (vec![(*elem).clone()], PawType::Bool),
+                        "union" | "intersection" | "difference" => (vec![recv_t.clone()], recv_t.clone()),
+                        "length" => (Vec::new(), PawType::Int),
+                        "to_array" => (Vec::new(), PawType::Array(elem.clone())),
+                        _ => {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3021",
+                                message: format!("Type {} has no method '{}'", recv_t, method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: None,
+                            }));
+                        }
+                    };
+                    if arg_types.len() != params.len() {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3024",
+                            message: format!(
+                                "Method '{}' on Set requires {} argument(s), found {}",
+                                method,
+                                params.len(),
+                                arg_types.len()
+                            ),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                    for (i, (param, actual)) in params.iter().zip(&arg_types).enumerate() {
+                        if actual != param && *actual != PawType::Any && *elem != PawType::Any {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3025",
+                                message: format!(
+                                    "Argument {} of '{}' on {}: expected {}, found {}",
+                                    i + 1,
+                                    method,
+                                    recv_t,
+                                    param,
+                                    actual
+                                ),
+                                line: args[i].line,
+                                column: args[i].col,
+                                snippet: None,
+                                hint: None,
+                            }));
+                        }
+                    }
+                    Ok(ret)
+                }
+                // —— Result 方法 ——
+                else if let PawType::Result(ok, err) = recv_t.clone() {
+                    let (params, ret) = match method.as_str() {
+                        "is_ok" | "is_err" => (Vec::new(), PawType::Bool),
+                        "unwrap" => (Vec::new(), (*ok).clone()),
+                        "unwrap_or" => (vec![(*ok).clone()], (*ok).clone()),
+                        "error" => (Vec::new(), PawType::Optional(err.clone())),
+                        _ => {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3021",
+                                message: format!("Type {} has no method '{}'", recv_t, method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: None,
+                            }));
+                        }
+                    };
+                    if arg_types.len() != params.len() {
+                        return Err(Box::new(PawError::Type {
                             file: self.current_file.clone(),
-                            code: "E3021",
-                            message: format!("Type String has no method '{}'", method),
+                            code: "E3024",
+                            message: format!(
+                                "Method '{}' on Result requires {} argument(s), found {}",
+                                method,
+                                params.len(),
+                                arg_types.len()
+                            ),
                             line: expr.line,
                             column: expr.col,
                             snippet: None,
                             hint: None,
-                        }),
+                        }));
                     }
-                }
-                // —— Array 方法 ——
-                else if let PawType::Array(inner) = recv_t.clone() {
-                    match method.as_str() {
-                        "push" => {
-                            // push 需要且仅需要一个参数，类型要与 inner 匹配
-                            if arg_types.len() != 1 {
-                                return Err(PawError::Type {
-                                    file: self.current_file.clone(),
-                                    code: "E3024",
-                                    message: format!(
-                                        "Method 'push' on Array requires 1 argument, found {}",
-                                        arg_types.len()
-                                    ),
-                                    line: expr.line,
-                                    column: expr.col,
-                                    snippet: None,
-                                    hint: None,
-                                });
-                            }
-                            if arg_types[0] != *inner {
-                                return Err(PawError::Type {
-                                    file: self.current_file.clone(),
-                                    code: "E3022",
-                                    message: format!(
-                                        "push 参数类型不匹配：expected {}, found {}",
-                                        inner, arg_types[0]
-                                    ),
-                                    line: expr.line,
-                                    column: expr.col,
-                                    snippet: None,
-                                    hint: None,
-                                });
-                            }
-                            Ok(PawType::Void)
-                        }
-                        "pop" => {
-                            if !arg_types.is_empty() {
-                                return Err(PawError::Type {
-                                    file: self.current_file.clone(),
-                                    code: "E3023",
-                                    message: format!(
-                                        "Method 'pop' on Array takes no arguments, found {}",
-                                        arg_types.len()
-                                    ),
-                                    line: expr.line,
-                                    column: expr.col,
-                                    snippet: None,
-                                    hint: None,
-                                });
-                            }
-                            Ok(*inner)
+                    for (i, (param, actual)) in params.iter().zip(&arg_types).enumerate() {
+                        if !self.accepts(param, actual) {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3025",
+                                message: format!(
+                                    "Argument {} of '{}' on {}: expected {}, found {}",
+                                    i + 1,
+                                    method,
+                                    recv_t,
+                                    param,
+                                    actual
+                                ),
+                                line: args[i].line,
+                                column: args[i].col,
+                                snippet: None,
+                                hint: None,
+                            }));
                         }
-                        "length" => {
-                            if !arg_types.is_empty() {
-                                return Err(PawError::Type {
-                                    file: self.current_file.clone(),
-                                    code: "E3023",
-                                    message: format!(
-                                        "Method 'length' on Array takes no arguments, found {}",
-                                        arg_types.len()
-                                    ),
-                                    line: expr.line,
-                                    column: expr.col,
-                                    snippet: None,
-                                    hint: None,
-                                });
-                            }
-                            Ok(PawType::Int)
+                    }
+                    Ok(ret)
+                }
+                // —— StringBuilder 方法 ——
+                else if recv_t == PawType::StringBuilder {
+                    let (params, ret) = match method.as_str() {
+                        "append" => (vec![PawType::Any], PawType::StringBuilder),
+                        "to_string" => (Vec::new(), PawType::String),
+                        "length" => (Vec::new(), PawType::Int),
+                        "clear" => (Vec::new(), PawType::StringBuilder),
+                        _ => {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3021",
+                                message: format!("Type {} has no method '{}'", recv_t, method),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some("StringBuilder has append(value), to_string(), length() and clear()".into()),
+                            }));
                         }
+                    };
+                    if arg_types.len() != params.len() {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3024",
+                            message: format!(
+                                "Method '{}' on StringBuilder requires {} argument(s), found {}",
+                                method,
+                                params.len(),
+                                arg_types.len()
+                            ),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                    Ok(ret)
+                }
+                // —— Range 方法 ——
+                else if let PawType::Range(elem) = &recv_t {
+                    let (params, ret) = match method.as_str() {
+                        "length" => (Vec::new(), PawType::Int),
+                        "contains" => (vec![PawType::Long], PawType::Bool),
+                        "to_array" => (Vec::new(), PawType::Array(elem.clone())),
                         _ => {
-                            return Err(PawError::Type {
+                            return Err(Box::new(PawError::Type {
                                 file: self.current_file.clone(),
                                 code: "E3021",
-                                message: format!(
-                                    "Type {} has no method '{}'",
-                                    PawType::Array(inner),
-                                    method
-                                ),
+                                message: format!("Type {} has no method '{}'", recv_t, method),
                                 line: expr.line,
                                 column: expr.col,
                                 snippet: None,
+                                hint: Some("Range has length(), contains(n) and to_array()".into()),
+                            }));
+                        }
+                    };
+                    if arg_types.len() != params.len() {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3024",
+                            message: format!(
+                                "Method '{}' on {} requires {} argument(s), found {}",
+                                method,
+                                recv_t,
+                                params.len(),
+                                arg_types.len()
+                            ),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                    for (i, actual) in arg_types.iter().enumerate() {
+                        if !actual.is_integer() && *actual != PawType::Any {
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3025",
+                                message: format!(
+                                    "Argument {} of '{}' on {}: expected Int or Long, found {}",
+                                    i + 1,
+                                    method,
+                                    recv_t,
+                                    actual
+                                ),
+                                line: args[i].line,
+                                column: args[i].col,
+                                snippet: None,
                                 hint: None,
-                            });
+                            }));
                         }
                     }
+                    Ok(ret)
                 }
                 // —— Module 方法 ——
                 else if recv_t == PawType::Module {
-                    // import 进来的模块对任意方法调用均返回 Any
-                    Ok(PawType::Any)
+                    if let Some(enum_name) = self.enum_receiver(receiver) {
+                        return self.check_variant(&enum_name, method.as_str(), Some((&arg_types, args)), expr);
+                    }
+                    let alias = match &receiver.kind {
+                        ExprKind::Var(alias) => alias.as_str(),
+                        _ => "",
+                    };
+                    let Some(path) = self.scope.std_module(alias) else {
+                        // 文件模块的成员在检查期未知，任意调用均返回 Any
+                        return Ok(PawType::Any);
+                    };
+                    let member = format!("{}.{}", alias, method);
+                    match std_member_type(&path, method.as_str()) {
+                        Some(MemberType::Numeric(arity)) => {
+                            self.check_numeric_call(&member, arity, &arg_types, args, expr)
+                        }
+                        Some(MemberType::Element) => {
+                            self.check_array_call(&member, false, &arg_types, args, expr)
+                        }
+                        Some(MemberType::SameArray) => {
+                            self.check_array_call(&member, true, &arg_types, args, expr)
+                        }
+                        Some(ty) => match ty.fixed() {
+                            Some(PawType::Function(params, ret)) => {
+                                self.check_call_args(&member, params, *ret, &arg_types, args, expr)
+                            }
+                            _ => Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3021",
+                                message: format!("'{}' is not a function", member),
+                                line: expr.line,
+                                column: expr.col,
+                                snippet: None,
+                                hint: Some(format!("Use '{}' without parentheses", member)),
+                            })),
+                        },
+                        None => Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3021",
+                            message: format!("Module '{}' has no member '{}'", alias, method),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        })),
+                    }
+                }
+                // —— Record / Protocol：调用函数类型的字段 ——
+                else if let Some(fields) = self.members_of(&recv_t) {
+                    let member = format!("{}.{}", recv_t, method);
+                    if let PawType::Record(record) | PawType::Generic(record, _) = &recv_t {
+                        self.check_barks_handled(&format!("{}.{}", record, method), expr)?;
+                    }
+                    match fields.into_iter().find(|(n, _)| n == method.as_str()) {
+                        Some((_, PawType::Function(params, ret))) => {
+                            self.check_call_args(&member, params, *ret, &arg_types, args, expr)
+                        }
+                        Some((_, PawType::Any)) => Ok(PawType::Any),
+                        Some((_, ty)) => Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3021",
+                            message: format!("'{}' is not a function, found {}", member, ty),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: Some(format!("Use '{}' without parentheses", member)),
+                        })),
+                        None => Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3021",
+                            message: format!("Type {} has no method '{}'", recv_t, method),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: None,
+                        })),
+                    }
                 }
                 // —— 其它类型不支持 MethodCall ——
                 else {
-                    Err(PawError::Type {
+                    Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3021",
                         message: format!("Type {} has no method '{}'", recv_t, method),
@@ -941,11 +4103,12 @@ impl TypeChecker {
                         column: expr.col,
                         snippet: None,
                         hint: None,
-                    })
+                    }))
                 }
             }
 
             ExprKind::RecordInit { name, fields } => {
+                self.warn_deprecated(name, expr.line, expr.col);
                 // 1. 拿 record 定义
                 let rec_ty = self
                     .scope
@@ -961,10 +4124,13 @@ impl TypeChecker {
                     })?
                     .clone();
                 // 2. 必须是 Record(...) 类型
-                let defs = if let PawType::Record(defs) = rec_ty.clone() {
+                let defs = if let Some(defs) = match &rec_ty {
+                    PawType::Record(rec) => self.scope.record_fields(rec),
+                    _ => None,
+                } {
                     defs
                 } else {
-                    return Err(PawError::Type {
+                    return Err(Box::new(PawError::Type {
                         file: self.current_file.clone(),
                         code: "E3016",
                         message: format!("{} is not a record type", rec_ty),
@@ -972,30 +4138,64 @@ impl TypeChecker {
                         column: expr.col,
                         snippet: None,
                         hint: None,
-                    });
+                    }));
                 };
-                // 3. 逐字段检查
+                // 3. 逐字段检查；泛型 record 的类型参数按字段值依次推断
+                let type_params = self.scope.record_params(name).unwrap_or_default();
+                let mut bindings: HashMap<String, PawType> = HashMap::new();
+                let mut seen: HashSet<&str> = HashSet::new();
                 for (fname, fexpr) in fields {
-                    // 找到期望类型
-                    let expected = defs
-                        .iter()
-                        .find(|(n, _)| n == fname)
-                        .map(|(_, t)| t.clone())
-                        .ok_or_else(|| PawError::Type {
+                    if !seen.insert(fname.as_str()) {
+                        return Err(Box::new(PawError::Type {
                             file: self.current_file.clone(),
-                            code: "E3015",
-                            message: format!("Record `{}` has no field `{}`", name, fname),
-                            line: expr.line,
-                            column: expr.col,
+                            code: "E3029",
+                            message: format!("Field `{}` specified twice", fname),
+                            line: fexpr.line,
+                            column: fexpr.col,
                             snippet: None,
-                            hint: None,
-                        })?;
+                            hint: Some("Remove one of the duplicated fields".into()),
+                        }));
+                    }
+                    // 找到期望类型；找不到时在尚未使用的字段中给出拼写建议
+                    let declared = match defs.iter().find(|(n, _)| n == fname) {
+                        Some((_, t)) => t.clone(),
+                        None => {
+                            let unused: Vec<&str> = defs
+                                .iter()
+                                .map(|(n, _)| n.as_str())
+                                .filter(|n| !fields.iter().any(|(f, _)| f == n))
+                                .collect();
+                            let hint = match closest_name(fname, &unused) {
+                                Some(s) => Some(format!("Did you mean `{}`?", s)),
+                                None if !unused.is_empty() => {
+                                    Some(format!("Unused fields: {}", unused.join(", ")))
+                                }
+                                None => None,
+                            };
+                            return Err(Box::new(PawError::Type {
+                                file: self.current_file.clone(),
+                                code: "E3015",
+                                message: format!("Record `{}` has no field `{}`", name, fname),
+                                line: fexpr.line,
+                                column: fexpr.col,
+                                snippet: None,
+                                hint,
+                            }));
+                        }
+                    };
                     // nopaw 视为 expected；否则递归检查
-                    let actual = if let ExprKind::LiteralNopaw = &fexpr.kind {
-                        expected.clone()
-                    } else {
-                        self.check_expr(fexpr)?
+                    let mut expected = declared.substitute(&bindings, &|_| PawType::Any);
+                    let actual = match &fexpr.kind {
+                        ExprKind::LiteralNopaw => None,
+                        _ => Some(self.check_value(fexpr, &expected)?),
                     };
+                    if !type_params.is_empty() {
+                        if let Some(actual) = &actual {
+                            declared.bind_params(actual, &mut bindings);
+                        }
+                        expected = declared.substitute(&bindings, &|_| PawType::Any);
+                    }
+                    let actual = actual.unwrap_or_else(|| expected.clone());
                     // 允许 T 和 T? 互赋
                     let ok = if actual == expected {
                         true
@@ -1005,24 +4205,343 @@ impl TypeChecker {
                         false
                     };
                     if !ok {
-                        return Err(PawError::Type {
+                        return Err(Box::new(PawError::Type {
                             file: self.current_file.clone(),
                             code: "E3017",
                             message: format!(
                                 "Field `{}` of record `{}`: expected {}, found {}",
                                 fname, name, expected, actual
                             ),
+                            line: fexpr.line,
+                            column: fexpr.col,
+                            snippet: None,
+                            hint: None,
+                        }));
+                    }
+                }
+                // 4. 所有声明的字段都必须提供
+                let missing: Vec<String> = defs
+                    .iter()
+                    .filter(|(n, _)| !seen.contains(n.as_str()))
+                    .map(|(n, _)| format!("`{}`", n))
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3030",
+                        message: format!(
+                            "Missing field{} {} in record `{}`",
+                            if missing.len() > 1 { "s" } else { "" },
+                            missing.join(", "),
+                            name
+                        ),
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: Some("All fields must be provided at initialization".into()),
+                    }));
+                }
+                if type_params.is_empty() {
+                    return Ok(rec_ty);
+                }
+                let args = type_params.iter().map(|p| bindings.get(p).cloned().unwrap_or(PawType::Any)).collect();
+                Ok(PawType::Generic(name.clone(), args))
+            }
+
+            ExprKind::Lambda {
+                params,
+                return_type,
+                is_async,
+                body,
+            } => {
+                // 与 FunDecl 相同：参数进入子作用域，再检查函数体和 return
+                let mut sub = self.body_checker();
+                sub.current_fn = Some("<lambda>".into());
+                let declared = return_type.as_ref().map(|node| self.resolve_type(node)).transpose()?;
+                sub.fn_return = declared.clone();
+                for Param { name: pn, ty: pty, .. } in params {
+                    let t = self.resolve_type(pty)?;
+                    sub.scope
+                        .define(pn, t, expr.line, expr.col, &self.current_file)
+                        .map_err(|_| PawError::DuplicateDefinition {
+                            file: self.current_file.clone(),
+                            code: "E2005",
+                            name: pn.clone(),
                             line: expr.line,
                             column: expr.col,
                             snippet: None,
                             hint: None,
-                        });
+                        })?;
+                }
+                sub.check_program(body)?;
+                if let Some(declared) = declared {
+                    self.check_collected_returns(&sub, &declared, "lambda")?;
+                    self.check_return_paths("<lambda>", &declared, body, expr.line, expr.col)?;
+                }
+                self.function_type(&[], params, return_type.as_ref(), *is_async)
+            }
+
+            ExprKind::Interpolation(parts) => {
+                for part in parts {
+                    self.check_operand(part, "interpolated into a string")?;
+                }
+                Ok(PawType::String)
+            }
+
+            ExprKind::Formatted { expr: inner, spec } => {
+                let ty = self.check_operand(inner, "interpolated into a string")?;
+                self.check_format_spec(spec, &ty, inner)?;
+                Ok(PawType::String)
+            }
+
+            // await Future<T> 得到 T；对非 Future 的 await 原样返回，并给出警告
+            ExprKind::Await { expr: inner } => match self.check_expr(inner)? {
+                PawType::Future(result) => Ok(*result),
+                PawType::Any => Ok(PawType::Any),
+                other => {
+                    self.warn(
+                        "W3001",
+                        format!("`await` on a value of type {}, which is not a Future, has no effect", other),
+                        expr.line,
+                        expr.col,
+                        Some("Remove the `await`, or call an async function to get a Future".into()),
+                    );
+                    Ok(other)
+                }
+            },
+
+            // Result<T, E>? 得到 T；所在函数必须返回能容纳 E 的 Result
+            ExprKind::Propagate { expr: inner } => {
+                let (ok_ty, err_ty) = match self.check_expr(inner)? {
+                    PawType::Result(ok, err) => (*ok, *err),
+                    PawType::Any => (PawType::Any, PawType::Any),
+                    other => {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3042",
+                            message: format!("`?` expects a Result, found {}", other),
+                            line: expr.line,
+                            column: expr.col,
+                            snippet: None,
+                            hint: Some("Wrap the value with ok(...) or err(...)".into()),
+                        }));
+                    }
+                };
+                match &self.fn_return {
+                    Some(PawType::Result(_, declared_err)) if self.accepts(declared_err, &err_ty) => Ok(ok_ty),
+                    other => Err(Box::new(PawError::Type {
+                        file: self.current_file.clone(),
+                        code: "E3042",
+                        message: match other {
+                            Some(ret) => format!(
+                                "`?` returns Result<_, {}> early, but the function returns {}",
+                                err_ty, ret
+                            ),
+                            None => "`?` can only be used inside a function that declares a Result return type"
+                                .into(),
+                        },
+                        line: expr.line,
+                        column: expr.col,
+                        snippet: None,
+                        hint: Some(format!(
+                            "Declare the function as returning Result<..., {}>, or handle the error with is_ok() / unwrap_or()",
+                            err_ty
+                        )),
+                    })),
+                }
+            }
+
+            // tail Future<T> 仍得到 Future<T>，只是立即开始执行
+            ExprKind::Tail { expr: inner } => match self.check_expr(inner)? {
+                ty @ (PawType::Future(_) | PawType::Any) => Ok(ty),
+                other => Err(Box::new(PawError::Type {
+                    file: self.current_file.clone(),
+                    code: "E3037",
+                    message: format!("`tail` expects a Future, found {}", other),
+                    line: expr.line,
+                    column: expr.col,
+                    snippet: None,
+                    hint: Some("Call an async function or use an `async { }` block after `tail`".into()),
+                })),
+            },
+
+            ExprKind::AsyncBlock(body) => {
+                // 在子作用域检查代码块，Future 的结果类型取自其中的 return
+                let mut sub = self.body_checker();
+                sub.check_program(body)?;
+                let returns = std::mem::take(&mut *sub.returns.borrow_mut());
+                let mut result = PawType::Void;
+                for (i, (ty, line, col)) in returns.into_iter().enumerate() {
+                    if i == 0 {
+                        result = ty;
+                    } else if ty != result {
+                        return Err(Box::new(PawError::Type {
+                            file: self.current_file.clone(),
+                            code: "E3004",
+                            message: format!(
+                                "Return type mismatch in async block: first return is {}, found {}",
+                                result, ty
+                            ),
+                            line,
+                            column: col,
+                            snippet: None,
+                            hint: Some("All returns of an async block must have the same type".into()),
+                        }));
                     }
                 }
-                Ok(rec_ty)
+                Ok(PawType::Future(Box::new(result)))
+            }
+        }
+    }
+}
+
+/// 把 Future<T> 放到需要 T 的位置时，提示加上 await
+fn await_hint(expected: &PawType, found: &PawType) -> Option<String> {
+    match found {
+        PawType::Future(inner) if expected.accepts(inner) => {
+            Some(format!("Add `await` to use the {} result of the Future", inner))
+        }
+        _ => None,
+    }
+}
+
+/// 语句列表是否在所有路径上都以 return / bark 结束；`exhaustive` 是检查时确认穷尽的 match 的位置
+fn always_returns(stmts: &[Statement], exhaustive: &HashSet<(usize, usize)>) -> bool {
+    stmts.iter().any(|stmt| stmt_always_returns(stmt, exhaustive))
+}
+
+fn stmt_always_returns(stmt: &Statement, exhaustive: &HashSet<(usize, usize)>) -> bool {
+    match &stmt.kind {
+        StatementKind::Return(_) | StatementKind::Throw(_) => true,
+        StatementKind::If {
+            body, else_branch, ..
+        } => else_branch
+            .as_ref()
+            .is_some_and(|e| always_returns(body, exhaustive) && stmt_always_returns(e, exhaustive)),
+        StatementKind::Block(body) | StatementKind::TaskGroup(body) => always_returns(body, exhaustive),
+        // 穷尽、且每个分支都 return 的 match
+        StatementKind::Match { arms, .. } => {
+            exhaustive.contains(&(stmt.line, stmt.col))
+                && arms.iter().all(|arm| always_returns(&arm.body, exhaustive))
+        }
+        // 没有 break 的无限循环只能通过 return / bark 离开
+        StatementKind::LoopForever { label, body } => !breaks_out(body, label),
+        StatementKind::TryCatchFinally {
+            body,
+            clauses,
+            finally,
+        } => {
+            (always_returns(body, exhaustive) && clauses.iter().all(|c| always_returns(&c.body, exhaustive)))
+                || always_returns(finally, exhaustive)
+        }
+        _ => false,
+    }
+}
+
+/// 是否含有跳出当前循环（标签为 `label`）的 break：内层循环中只算带该标签的 break
+fn breaks_out(stmts: &[Statement], label: &Option<String>) -> bool {
+    fn scan(stmts: &[Statement], label: &Option<String>, nested: bool) -> bool {
+        stmts.iter().any(|stmt| match &stmt.kind {
+            StatementKind::Break(target) => match target {
+                None => !nested,
+                Some(_) => target == label,
+            },
+            StatementKind::If {
+                body, else_branch, ..
+            } => {
+                scan(body, label, nested)
+                    || else_branch
+                        .as_ref()
+                        .is_some_and(|e| scan(std::slice::from_ref(e), label, nested))
+            }
+            StatementKind::Block(body) | StatementKind::TaskGroup(body) => scan(body, label, nested),
+            StatementKind::Match { arms, .. } => arms.iter().any(|arm| scan(&arm.body, label, nested)),
+            StatementKind::TryCatchFinally {
+                body,
+                clauses,
+                finally,
+            } => {
+                scan(body, label, nested)
+                    || clauses.iter().any(|c| scan(&c.body, label, nested))
+                    || scan(finally, label, nested)
+            }
+            StatementKind::LoopForever { body, .. }
+            | StatementKind::LoopWhile { body, .. }
+            | StatementKind::LoopDoWhile { body, .. }
+            | StatementKind::LoopRange { body, .. }
+            | StatementKind::LoopArray { body, .. } => label.is_some() && scan(body, label, true),
+            _ => false,
+        })
+    }
+    scan(stmts, label, false)
+}
+
+/// 判断语句是否是每个分支都 return、但缺少最终 `else` 的 if/else-if 链，并收集各分支的条件
+fn if_chain_without_else(
+    stmt: &Statement,
+    conditions: &mut Vec<Expr>,
+    exhaustive: &HashSet<(usize, usize)>,
+) -> bool {
+    match &stmt.kind {
+        StatementKind::If {
+            condition,
+            body,
+            else_branch,
+        } if always_returns(body, exhaustive) => {
+            conditions.push(condition.clone());
+            match else_branch.as_deref() {
+                None => true,
+                Some(next) => if_chain_without_else(next, conditions, exhaustive),
             }
+        }
+        _ => false,
+    }
+}
 
-            ExprKind::Await { expr: inner } => self.check_expr(inner),
+/// 在候选名字中找与 `name` 编辑距离最近（且不超过 2）的一个，用于拼写提示
+fn closest_name<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    fn distance(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut prev: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut cur = vec![i + 1];
+            for (j, cb) in b.iter().enumerate() {
+                let cost = if ca == *cb { 0 } else { 1 };
+                cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+            }
+            prev = cur;
         }
+        prev[b.len()]
     }
+    candidates
+        .iter()
+        .map(|c| (distance(name, c), *c))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// `let x = value` 中值说明不了的类型：类型未知，或者 nopaw、容器与元组字面量、`ok`/`err`
+/// 中没有元素或另一半可以确定的部分（推断为 Any）
+fn is_undetermined(value: &Expr, ty: &PawType) -> bool {
+    let constructed = match &value.kind {
+        ExprKind::LiteralNopaw
+        | ExprKind::ArrayLiteral(_)
+        | ExprKind::MapLiteral(_)
+        | ExprKind::SetLiteral(_)
+        | ExprKind::TupleLiteral(_) => true,
+        ExprKind::Call { name, .. } => name == "ok" || name == "err",
+        _ => false,
+    };
+    ty.mentions(&|t| *t == PawType::Unknown || (constructed && *t == PawType::Any))
+}
+
+/// 无法推断时提示中示范的类型：把 Any 换成 T
+fn example_type(ty: &PawType) -> String {
+    ty.to_string().replace("Any", "T")
+}
+
+/// 空 Map 字面量 `{}` 的类型由声明决定
+fn is_empty_map_literal(expr: &Expr) -> bool {
+    matches!(&expr.kind, ExprKind::MapLiteral(entries) if entries.is_empty())
 }
@@ -1,8 +1,8 @@
 // File: src/semantic/types.rs
 
+use std::collections::HashMap;
 use std::fmt;
 use crate::ast::expr::BinaryOp;
-use crate::ast::expr::BinaryOp::{Add, And, Div, EqEq, Ge, Gt, Le, Lt, Mod, Mul, NotEq, Or, Sub};
 
 /// 支持的类型
 #[derive(Clone, Debug, PartialEq)]
@@ -20,28 +20,132 @@ pub enum PawType {
     Optional(Box<PawType>),
     /// 数组类型，如 Array<T>
     Array(Box<PawType>),
-    /// 记录类型，字段名和字段类型列表
-    Record(Vec<(String, PawType)>),
+    /// 映射类型，如 Map<K, V>
+    Map(Box<PawType>, Box<PawType>),
+    /// 集合类型，如 Set<T>
+    Set(Box<PawType>),
+    /// 整数范围，如 Range<Int>、Range<Long>，元素类型与端点相同
+    Range(Box<PawType>),
+    /// 预期内失败的结果，如 Result<T, E>：`ok(T)` 或 `err(E)`
+    Result(Box<PawType>, Box<PawType>),
+    /// 元组类型，如 (Int, String)，至少两个元素
+    Tuple(Vec<PawType>),
+    /// 调用 async 函数或 async 代码块得到的 Future，如 Future<T>，await 后得到 T
+    Future(Box<PawType>),
+    /// 函数类型，如 fun(Int, String): Bool
+    Function(Vec<PawType>, Box<PawType>),
+    /// 记录类型（按名字的名义类型），字段在作用域中延迟解析，因此可以自引用
+    Record(String),
+    /// 泛型 record 的实例，如 `Box<Int>`；类型实参只在检查期存在，运行时按 record 名区分
+    Generic(String, Vec<PawType>),
+    /// 泛型函数或 record 声明中的类型参数，如 `fun first<T>(...)` 中的 `T`，
+    /// 只与自身相同，调用处按实参替换为具体类型
+    TypeParam(String),
+    /// enum 类型（按名字的名义类型），变体列表在作用域中
+    Enum(String),
+    /// 协议类型：接受声明遵循该协议的 record，只能访问协议要求的成员
+    Protocol(String),
     /// 模块类型，用于 import
     Module,
+    /// `string.builder()` 创建的可变字符串缓冲区
+    StringBuilder,
+    /// 属性测试中产生 T 类型随机值的生成器，如 Gen<Int>
+    Gen(Box<PawType>),
     /// 未知类型，用于错误恢复
     Unknown,
 }
 
 impl PawType {
     /// 从脚本里的类型名字符串解析出 PawType
-    /// 支持 T?, Array<T>, 以及基础类型名称
-    pub fn from_str(s: &str) -> Self {
+    /// 支持 T?, Array<T>, (A, B), fun(A, B): R, 以及基础类型名称
+    pub fn parse(s: &str) -> Self {
+        // 函数类型 fun(A, B): R，返回类型省略时为 Void；需先于 '?' 判断，
+        // 因为 fun(Int): Int? 表示返回 Int? 的函数
+        if let Some(rest) = s.strip_prefix("fun(") {
+            return match find_closing_paren(rest) {
+                Some(close) => {
+                    let params = if rest[..close].trim().is_empty() {
+                        Vec::new()
+                    } else {
+                        split_type_args(&rest[..close])
+                            .into_iter()
+                            .map(PawType::parse)
+                            .collect()
+                    };
+                    let ret = match rest[close + 1..].trim().strip_prefix(':') {
+                        Some(r) => PawType::parse(r.trim()),
+                        None if rest[close + 1..].trim().is_empty() => PawType::Void,
+                        None => PawType::Unknown,
+                    };
+                    PawType::Function(params, Box::new(ret))
+                }
+                None => PawType::Unknown,
+            };
+        }
         // 可选类型后缀 '?'
         if let Some(inner) = s.strip_suffix('?') {
-            return PawType::Optional(Box::new(PawType::from_str(inner)));
+            return PawType::Optional(Box::new(PawType::parse(inner)));
+        }
+        // 元组 (A, B, ...)
+        if let Some(inner) = s.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+            return match split_type_args(inner) {
+                elems if elems.len() >= 2 => {
+                    PawType::Tuple(elems.into_iter().map(PawType::parse).collect())
+                }
+                _ => PawType::Unknown,
+            };
         }
         // 泛型 Array<T>
         if let Some(inner) = s.strip_prefix("Array<").and_then(|rest| rest.strip_suffix('>')) {
-            return PawType::Array(Box::new(PawType::from_str(inner)));
+            return PawType::Array(Box::new(PawType::parse(inner)));
+        }
+        // 泛型 Set<T>
+        if let Some(inner) = s.strip_prefix("Set<").and_then(|rest| rest.strip_suffix('>')) {
+            return PawType::Set(Box::new(PawType::parse(inner)));
+        }
+        // 泛型 Range<T>
+        if let Some(inner) = s.strip_prefix("Range<").and_then(|rest| rest.strip_suffix('>')) {
+            return PawType::Range(Box::new(PawType::parse(inner)));
         }
-        // 基础类型
-        match s {
+        // 泛型 Future<T>
+        if let Some(inner) = s.strip_prefix("Future<").and_then(|rest| rest.strip_suffix('>')) {
+            return PawType::Future(Box::new(PawType::parse(inner)));
+        }
+        // 泛型 Gen<T>
+        if let Some(inner) = s.strip_prefix("Gen<").and_then(|rest| rest.strip_suffix('>')) {
+            return PawType::Gen(Box::new(PawType::parse(inner)));
+        }
+        // 泛型 Map<K, V>
+        if let Some(inner) = s.strip_prefix("Map<").and_then(|rest| rest.strip_suffix('>')) {
+            return match split_type_args(inner).as_slice() {
+                [k, v] => PawType::Map(
+                    Box::new(PawType::parse(k)),
+                    Box::new(PawType::parse(v)),
+                ),
+                _ => PawType::Unknown,
+            };
+        }
+        // 泛型 Result<T, E>
+        if let Some(inner) = s.strip_prefix("Result<").and_then(|rest| rest.strip_suffix('>')) {
+            return match split_type_args(inner).as_slice() {
+                [t, e] => PawType::Result(
+                    Box::new(PawType::parse(t)),
+                    Box::new(PawType::parse(e)),
+                ),
+                _ => PawType::Unknown,
+            };
+        }
+        // 基础类型；其余合法标识符视为用户定义的 record，检查器再按作用域区分 record 与 protocol
+        match PawType::builtin(s) {
+            Some(ty) => ty,
+            None if is_type_name(s) => PawType::Record(s.to_string()),
+            None => PawType::Unknown,
+        }
+    }
+
+    /// 不带类型参数的内置类型名对应的类型；其它名字为 None
+    pub(crate) fn builtin(name: &str) -> Option<Self> {
+        Some(match name {
             "Int" => PawType::Int,
             "Long" => PawType::Long,
             "Float" => PawType::Float,
@@ -52,8 +156,9 @@ impl PawType {
             "Void" => PawType::Void,
             "Any" => PawType::Any,
             "Module" => PawType::Module,
-            _ => PawType::Unknown,
-        }
+            "StringBuilder" => PawType::StringBuilder,
+            _ => return None,
+        })
     }
 }
 
@@ -70,22 +175,270 @@ impl fmt::Display for PawType {
             PawType::Void => write!(f, "Void"),
             PawType::Any => write!(f, "Any"),
             PawType::Module => write!(f, "Module"),
+            PawType::StringBuilder => write!(f, "StringBuilder"),
             PawType::Unknown => write!(f, "Unknown"),
             PawType::Optional(inner) => write!(f, "{}?", inner),
             PawType::Array(elem) => write!(f, "Array<{}>", elem),
-            PawType::Record(fields) => {
-                // 打印成 {x: Int, y: String}
-                let parts: Vec<String> = fields
-                    .iter()
-                    .map(|(n, t)| format!("{}: {}", n, t))
-                    .collect();
-                write!(f, "{{{}}}", parts.join(", "))
+            PawType::Map(k, v) => write!(f, "Map<{}, {}>", k, v),
+            PawType::Set(elem) => write!(f, "Set<{}>", elem),
+            PawType::Range(elem) => write!(f, "Range<{}>", elem),
+            PawType::Result(ok, err) => write!(f, "Result<{}, {}>", ok, err),
+            PawType::Tuple(elems) => {
+                let parts: Vec<String> = elems.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", parts.join(", "))
+            }
+            PawType::Future(inner) => write!(f, "Future<{}>", inner),
+            PawType::Gen(inner) => write!(f, "Gen<{}>", inner),
+            PawType::Function(params, ret) => {
+                let parts: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "fun({}): {}", parts.join(", "), ret)
+            }
+            PawType::Generic(name, args) => {
+                let parts: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}<{}>", name, parts.join(", "))
+            }
+            PawType::Record(name)
+            | PawType::Enum(name)
+            | PawType::Protocol(name)
+            | PawType::TypeParam(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// 是否是合法的类型名（标识符）
+fn is_type_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// 找到与已消费的 '(' 匹配的 ')' 的位置
+fn find_closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 按顶层逗号切分泛型参数，如 "String, Array<Int>" → ["String", "Array<Int>"]
+fn split_type_args(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
             }
+            _ => {}
         }
     }
+    parts.push(s[start..].trim());
+    parts
 }
 
+/// `is_hashable` 接受的类型，用作报错提示；修改其中一处时另一处要一起改
+pub(crate) const HASHABLE_TYPES: &str = "Int, Long, Bool, Char, String, an enum, a Range, Any, or a tuple of these";
+
 impl PawType {
+    /// 可以作为 Map 键或 Set 元素的类型（见 `HASHABLE_TYPES`）；元素都可哈希的元组也可以
+    pub(crate) fn is_hashable(&self) -> bool {
+        match self {
+            PawType::Tuple(elems) => elems.iter().all(PawType::is_hashable),
+            _ => matches!(
+                self,
+                PawType::Int
+                    | PawType::Long
+                    | PawType::Bool
+                    | PawType::Char
+                    | PawType::String
+                    | PawType::Enum(_)
+                    | PawType::Range(_)
+                    | PawType::Any
+            ),
+        }
+    }
+
+    /// `loop x in v` 中 x 的类型：Array、Set、Range 取元素，Map 取 (键, 值) 元组，String 取 Char；
+    /// 不能遍历时返回 None
+    pub(crate) fn loop_element(&self) -> Option<PawType> {
+        Some(match self {
+            PawType::Array(inner) | PawType::Set(inner) | PawType::Range(inner) => (**inner).clone(),
+            PawType::Map(k, v) => PawType::Tuple(vec![(**k).clone(), (**v).clone()]),
+            PawType::String => PawType::Char,
+            PawType::Any => PawType::Any,
+            _ => return None,
+        })
+    }
+
+    /// 能用 `is` 在运行时测试的类型：函数签名、Future 的结果、泛型的类型实参
+    /// 与 protocol 遵循关系在运行时无从得知
+    pub(crate) fn is_testable(&self) -> bool {
+        match self {
+            PawType::Optional(t) | PawType::Array(t) | PawType::Set(t) => t.is_testable(),
+            PawType::Map(a, b) | PawType::Result(a, b) => a.is_testable() && b.is_testable(),
+            PawType::Tuple(elems) => elems.iter().all(PawType::is_testable),
+            PawType::Function(..)
+            | PawType::Future(_)
+            | PawType::Gen(_)
+            | PawType::Generic(..)
+            | PawType::TypeParam(_)
+            | PawType::Protocol(_)
+            | PawType::Void
+            | PawType::Unknown => false,
+            _ => true,
+        }
+    }
+
+    /// `self as to` 能否通过检查：数字之间、基础类型与 String 之间、Char 与 Int / Long 之间可以转换；
+    /// 把 Any（包括容器元素中的 Any）或可选类型细化为能在运行时测试的类型也可以，由运行时确认
+    pub(crate) fn casts_to(&self, to: &PawType) -> bool {
+        let scalar = |t: &PawType| t.is_numeric() || matches!(t, PawType::Bool | PawType::Char | PawType::String);
+        match (self, to) {
+            _ if self == to || *to == PawType::Any => true,
+            _ if to.is_testable() && self.refines_to(to) => true,
+            (PawType::String, _) | (_, PawType::String) => scalar(self) && scalar(to),
+            (PawType::Char, PawType::Int | PawType::Long) | (PawType::Int | PawType::Long, PawType::Char) => true,
+            _ => self.is_numeric() && to.is_numeric(),
+        }
+    }
+
+    /// `to` 是否只是把 `self` 中的 Any 换成具体类型、或去掉了可选
+    fn refines_to(&self, to: &PawType) -> bool {
+        match (self, to) {
+            (PawType::Any, _) => true,
+            (PawType::Optional(a), PawType::Optional(b))
+            | (PawType::Array(a), PawType::Array(b))
+            | (PawType::Set(a), PawType::Set(b))
+            | (PawType::Range(a), PawType::Range(b)) => a.refines_to(b),
+            (PawType::Optional(a), b) => a.refines_to(b),
+            (PawType::Map(ak, av), PawType::Map(bk, bv)) | (PawType::Result(ak, av), PawType::Result(bk, bv)) => {
+                ak.refines_to(bk) && av.refines_to(bv)
+            }
+            (PawType::Tuple(a), PawType::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.refines_to(y))
+            }
+            _ => self == to,
+        }
+    }
+
+    /// 能否把 `actual` 类型的值放到声明为 `self` 的位置：类型相同、任一方为 Any、
+    /// T → T?、nopaw → T?，以及数值类型之间
+    pub(crate) fn accepts(&self, actual: &PawType) -> bool {
+        if self == actual || *self == PawType::Any || *actual == PawType::Any {
+            return true;
+        }
+        match (self, actual) {
+            (PawType::Optional(_), PawType::Optional(inner)) if **inner == PawType::Any => true,
+            (PawType::Optional(inner), _) => inner.accepts(actual),
+            _ => self.is_numeric() && actual.is_numeric(),
+        }
+    }
+
+    /// 把声明类型 `self` 与实参类型 `actual` 按结构对齐，为其中的类型参数记下对应的类型；
+    /// 同一参数以第一次绑定为准，Any 不绑定任何参数，结构对不上的部分跳过，留给之后的兼容性检查
+    pub(crate) fn bind_params(&self, actual: &PawType, bindings: &mut HashMap<String, PawType>) {
+        match (self, actual) {
+            (_, PawType::Any | PawType::Unknown) => {}
+            (PawType::TypeParam(name), _) => {
+                bindings.entry(name.clone()).or_insert_with(|| actual.clone());
+            }
+            (PawType::Optional(d), PawType::Optional(a))
+            | (PawType::Array(d), PawType::Array(a))
+            | (PawType::Set(d), PawType::Set(a))
+            | (PawType::Range(d), PawType::Range(a))
+            | (PawType::Future(d), PawType::Future(a))
+            | (PawType::Gen(d), PawType::Gen(a)) => d.bind_params(a, bindings),
+            (PawType::Optional(d), a) => d.bind_params(a, bindings),
+            (PawType::Map(dk, dv), PawType::Map(ak, av)) | (PawType::Result(dk, dv), PawType::Result(ak, av)) => {
+                dk.bind_params(ak, bindings);
+                dv.bind_params(av, bindings);
+            }
+            (PawType::Tuple(d), PawType::Tuple(a)) | (PawType::Generic(_, d), PawType::Generic(_, a)) => {
+                for (d, a) in d.iter().zip(a) {
+                    d.bind_params(a, bindings);
+                }
+            }
+            (PawType::Function(dp, dr), PawType::Function(ap, ar)) => {
+                for (d, a) in dp.iter().zip(ap) {
+                    d.bind_params(a, bindings);
+                }
+                dr.bind_params(ar, bindings);
+            }
+            _ => {}
+        }
+    }
+
+    /// 把类型参数替换为 `bindings` 中记下的类型，没有绑定的参数交给 `unbound` 决定
+    pub(crate) fn substitute(
+        &self,
+        bindings: &HashMap<String, PawType>,
+        unbound: &dyn Fn(&str) -> PawType,
+    ) -> PawType {
+        let sub = |t: &PawType| Box::new(t.substitute(bindings, unbound));
+        let all = |ts: &[PawType]| ts.iter().map(|t| t.substitute(bindings, unbound)).collect();
+        match self {
+            PawType::TypeParam(name) => bindings.get(name).cloned().unwrap_or_else(|| unbound(name)),
+            PawType::Optional(t) => PawType::Optional(sub(t)),
+            PawType::Array(t) => PawType::Array(sub(t)),
+            PawType::Set(t) => PawType::Set(sub(t)),
+            PawType::Range(t) => PawType::Range(sub(t)),
+            PawType::Future(t) => PawType::Future(sub(t)),
+            PawType::Gen(t) => PawType::Gen(sub(t)),
+            PawType::Map(k, v) => PawType::Map(sub(k), sub(v)),
+            PawType::Result(t, e) => PawType::Result(sub(t), sub(e)),
+            PawType::Tuple(ts) => PawType::Tuple(all(ts)),
+            PawType::Generic(name, args) => PawType::Generic(name.clone(), all(args)),
+            PawType::Function(params, ret) => PawType::Function(all(params), sub(ret)),
+            other => other.clone(),
+        }
+    }
+
+    /// 类型中是否出现类型参数
+    pub(crate) fn has_type_params(&self) -> bool {
+        match self {
+            PawType::TypeParam(_) => true,
+            PawType::Optional(t)
+            | PawType::Array(t)
+            | PawType::Set(t)
+            | PawType::Range(t)
+            | PawType::Future(t)
+            | PawType::Gen(t) => t.has_type_params(),
+            PawType::Map(a, b) | PawType::Result(a, b) => a.has_type_params() || b.has_type_params(),
+            PawType::Tuple(ts) | PawType::Generic(_, ts) => ts.iter().any(PawType::has_type_params),
+            PawType::Function(params, ret) => params.iter().any(PawType::has_type_params) || ret.has_type_params(),
+            _ => false,
+        }
+    }
+
+    /// 类型本身或其中的任何一部分满足 `pred`
+    pub(crate) fn mentions(&self, pred: &dyn Fn(&PawType) -> bool) -> bool {
+        if pred(self) {
+            return true;
+        }
+        match self {
+            PawType::Optional(t)
+            | PawType::Array(t)
+            | PawType::Set(t)
+            | PawType::Range(t)
+            | PawType::Future(t)
+            | PawType::Gen(t) => t.mentions(pred),
+            PawType::Map(a, b) | PawType::Result(a, b) => a.mentions(pred) || b.mentions(pred),
+            PawType::Tuple(ts) | PawType::Generic(_, ts) => ts.iter().any(|t| t.mentions(pred)),
+            PawType::Function(params, ret) => params.iter().any(|t| t.mentions(pred)) || ret.mentions(pred),
+            _ => false,
+        }
+    }
+
     pub(crate) fn is_numeric(&self) -> bool {
         matches!(
             self,
@@ -93,6 +446,48 @@ impl PawType {
         )
     }
 
+    /// 运行时能否仅凭值判断属于该类型：基本类型，以及由它们构成的 Optional、Array、Set、Map 与元组。
+    /// record 与 protocol 在运行时无法区分，函数、Future 等也看不出参数类型，重载只按这些类型选择
+    pub(crate) fn is_runtime_checkable(&self) -> bool {
+        match self {
+            PawType::Int
+            | PawType::Long
+            | PawType::Float
+            | PawType::Double
+            | PawType::Bool
+            | PawType::Char
+            | PawType::String => true,
+            PawType::Optional(t) | PawType::Array(t) | PawType::Set(t) => t.is_runtime_checkable(),
+            PawType::Map(k, v) => k.is_runtime_checkable() && v.is_runtime_checkable(),
+            PawType::Tuple(ts) => ts.iter().all(PawType::is_runtime_checkable),
+            _ => false,
+        }
+    }
+
+    /// 参数类型为 self 的重载对 `arg` 类型实参的匹配程度：完全符合为 2，需要数值转换为 1，不接受为 None
+    pub(crate) fn overload_score(&self, arg: &PawType) -> Option<usize> {
+        let inner = match self {
+            PawType::Optional(inner) => inner.as_ref(),
+            other => other,
+        };
+        let nopaw = *arg == PawType::Optional(Box::new(PawType::Any));
+        if self == arg || inner == arg || (nopaw && inner != self) {
+            Some(2)
+        } else if inner.is_numeric() && arg.is_numeric() {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        matches!(self, PawType::Int | PawType::Long)
+    }
+
+    pub(crate) fn is_float(&self) -> bool {
+        matches!(self, PawType::Float | PawType::Double)
+    }
+
     pub(crate) fn binary_result(&self, op: &BinaryOp, rhs: &PawType) -> Result<PawType, String> {
         use crate::ast::expr::BinaryOp::*;
         // 字符串 concat
@@ -112,6 +507,9 @@ impl PawType {
             };
             return match op {
                 Add | Sub | Mul | Div | Mod => Ok(out),
+                // 整数乘方保持整数类型，有浮点数时为 Double
+                Pow if out.is_float() => Ok(PawType::Double),
+                Pow => Ok(out),
                 EqEq | NotEq | Lt | Le | Gt | Ge => Ok(PawType::Bool),
                 _ => Err(format!("Unsupported operator {:?} for numeric types", op)),
             };
@@ -0,0 +1,475 @@
+// src/session.rs
+//
+// 增量执行会话：类型检查器作用域与运行时环境一起保存，每段代码先检查再执行，
+// 检查通过后才提交作用域，保证两者始终一致。REPL 与嵌入 API 共用这一实现。
+
+use crate::ast::statement::{Statement, StatementKind};
+use crate::error::error::PawError;
+use crate::interpreter::env::Env;
+use crate::interpreter::host::Host;
+use crate::interpreter::interpreter::{Counters, Engine, EnumNames, ModuleCache, RecordMethods, SizeLimits, Stats};
+use crate::interpreter::value::{Value, ValueInner};
+use crate::lexer::lexer::Lexer;
+use crate::parser::cfg::Cfg;
+use crate::parser::parser::Parser;
+use crate::runtime;
+use crate::semantic::scope::Scope;
+use crate::semantic::types::PawType;
+use crate::semantic::type_checker::TypeChecker;
+use crate::STACK_SIZE;
+use std::path::PathBuf;
+use std::sync::Arc;
+use vuot::{Stack, StacklessFn};
+
+/// 一个持续的执行会话
+///
+/// 多次 `eval` 共享同一个全局环境和类型检查作用域，
+/// 前一段代码定义的变量、函数和 record 在之后的调用中仍然可见。
+pub struct Session {
+    env: Env,
+    scope: Scope,
+    warnings: Vec<PawError>,
+    file: String,
+    vm: bool,
+    sandbox: bool,
+    limits: SizeLimits,
+    /// 各次执行共用的模块缓存，每个模块文件在会话中只加载一次
+    modules: ModuleCache,
+    /// 各次执行中声明的 record 方法
+    methods: RecordMethods,
+    /// 各次执行中声明的 enum
+    enums: EnumNames,
+    /// 各次执行累计的执行计数
+    counters: Arc<Counters>,
+    /// 脚本与被导入模块中 `@cfg(...)` 使用的开关
+    cfg: Cfg,
+    /// `assert_snapshot` 是否覆盖不符的快照
+    update_snapshots: bool,
+    /// `ask`、`time` 与 `random` 的输入来源，各次执行共用
+    host: Arc<Host>,
+}
+
+// 公开接口直接返回 PawError，内部的 Box<PawError> 在这里解开
+#[allow(clippy::result_large_err)]
+impl Session {
+    /// 创建一个会话，`file` 用于错误信息与相对导入
+    pub fn new(file: &str) -> Self {
+        Session {
+            env: Env::new(),
+            scope: Scope::new(),
+            warnings: Vec::new(),
+            file: file.to_string(),
+            vm: true,
+            sandbox: false,
+            limits: SizeLimits::default(),
+            modules: Arc::default(),
+            methods: Arc::default(),
+            enums: Arc::default(),
+            counters: Arc::default(),
+            cfg: Cfg::new(),
+            update_snapshots: false,
+            host: Arc::default(),
+        }
+    }
+
+    /// 开启或关闭字节码 VM（默认开启），关闭后循环由树遍历解释器执行
+    pub fn set_vm(&mut self, enabled: bool) {
+        self.vm = enabled;
+    }
+
+    /// 开启或关闭沙箱模式（默认关闭），开启后脚本不能导入模块或读取控制台输入
+    pub fn set_sandbox(&mut self, enabled: bool) {
+        self.sandbox = enabled;
+    }
+
+    /// 设置沙箱模式下字符串与容器的大小上限，超出时报 E6020；非沙箱模式不检查
+    pub fn set_size_limits(&mut self, limits: SizeLimits) {
+        self.limits = limits;
+    }
+
+    /// 开启后 `assert_snapshot` 用新值覆盖与之不符的快照（默认关闭，不符时报 E6025）
+    pub fn set_update_snapshots(&mut self, enabled: bool) {
+        self.update_snapshots = enabled;
+    }
+
+    /// 替换 `ask`、`time` 与 `random` 的输入来源，测试中用它提供预先写好的回答、固定的时钟和种子
+    pub fn set_host(&mut self, host: Arc<Host>) {
+        self.host = host;
+    }
+
+    /// 替换 `@cfg(...)` 使用的整个开关集合
+    pub fn set_cfg(&mut self, cfg: Cfg) {
+        self.cfg = cfg;
+    }
+
+    /// 打开一个 `@cfg(...)` 开关，之后解析的代码与模块都能看到；当前平台的开关默认打开
+    pub fn enable_cfg(&mut self, flag: &str) {
+        self.cfg.enable(flag);
+    }
+
+    /// 注入或覆盖一个宿主变量；其静态类型由值推断，函数和 record 视为 `Any`
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.scope.set(name, value.static_type());
+        self.env.define(name.to_string(), value);
+    }
+
+    /// 注册一个原生函数，`signature` 使用脚本中的函数类型写法，如 `fun(Int, Int): Int`；
+    /// 写 `Any` 则只在运行时由原生函数自行检查参数
+    pub fn register_native<F>(&mut self, name: &str, signature: &str, func: F) -> Result<(), PawError>
+    where
+        F: Fn(Vec<Value>) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        let ty = PawType::parse(signature);
+        if !matches!(ty, PawType::Function(..) | PawType::Any) {
+            return Err(PawError::Type {
+                file: self.file.clone(),
+                code: "E3028",
+                message: format!(
+                    "Invalid signature '{}' for native function '{}'",
+                    signature, name
+                ),
+                line: 0,
+                column: 0,
+                snippet: None,
+                hint: Some("Write the signature as a function type, e.g. `fun(Int): String`".into()),
+            });
+        }
+        self.scope.set(name, ty);
+        self.env.define(name.to_string(), Value::Native(name, func));
+        Ok(())
+    }
+
+    /// 最近一次 `eval` 中类型检查器给出的警告
+    pub fn warnings(&self) -> &[PawError] {
+        &self.warnings
+    }
+
+    /// 会话创建（或上次 `reset_stats`）以来累计的执行计数
+    pub fn stats(&self) -> Stats {
+        self.counters.snapshot()
+    }
+
+    /// 把执行计数清零
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
+    /// 读取一个全局变量的当前值
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.env.get(name)
+    }
+
+    /// 词法分析、语法分析、类型检查并运行一段源码
+    ///
+    /// 返回值为顶层 `return` 的值；没有 return 时，若最后一条语句是表达式则返回它的值，
+    /// 否则返回 `nopaw`。得到的 Future 会被 await，未 await 的任务会在返回前全部完成。
+    /// 只有类型检查通过的代码才会更新检查器作用域。
+    ///
+    /// 内部会启动一个异步运行时，因此不能在另一个异步运行时中调用，此时请使用 `eval_async`。
+    pub fn eval(&mut self, src: &str) -> Result<Value, PawError> {
+        let ast = self.check(src).map_err(|e| *e)?;
+        let engine = self.engine();
+        self.block_on(|| {
+            vuot::run(EvalProgram {
+                engine,
+                statements: &ast,
+            })
+        })
+        .map_err(|e| *e)
+    }
+
+    /// 与 `eval` 相同，但在调用方所在的异步运行时中执行
+    pub async fn eval_async(&mut self, src: &str) -> Result<Value, PawError> {
+        let ast = self.check(src).map_err(|e| *e)?;
+        let engine = self.engine();
+        vuot::run(EvalProgram {
+            engine,
+            statements: &ast,
+        })
+        .await
+        .map_err(|e| *e)
+    }
+
+    /// 在会话的全局环境中求值一个表达式，不做类型检查，也不更新检查器作用域；
+    /// `read_only` 为 true 时求值不会改变任何变量（见 `Engine::evaluate`）
+    pub fn evaluate(&mut self, expr: &str, read_only: bool) -> Result<Value, PawError> {
+        let engine = self.engine();
+        self.block_on(|| {
+            vuot::run(Evaluate {
+                engine,
+                source: expr,
+                read_only,
+            })
+        })
+        .map_err(|e| *e)
+    }
+
+    /// 与 `evaluate` 相同，但在调用方所在的异步运行时中执行
+    pub async fn evaluate_async(&mut self, expr: &str, read_only: bool) -> Result<Value, PawError> {
+        vuot::run(Evaluate {
+            engine: self.engine(),
+            source: expr,
+            read_only,
+        })
+        .await
+        .map_err(|e| *e)
+    }
+
+    /// 解析并检查一段源码，通过后提交检查器作用域
+    fn check(&mut self, src: &str) -> Result<Vec<Statement>, Box<PawError>> {
+        let tokens = Lexer::new(src).tokenize();
+        let ast = Parser::new(tokens, src, &self.file).with_cfg(&self.cfg).parse_program()?;
+
+        let mut tc = TypeChecker::new(&self.file);
+        tc.scope = self.scope.clone();
+        tc.check_program(&ast)?;
+        self.warnings = tc.warnings();
+        self.scope = tc.scope;
+        Ok(ast)
+    }
+
+    fn engine(&self) -> Engine {
+        let mut engine = Engine::new(self.env.clone(), &self.file);
+        engine.set_vm(self.vm);
+        engine.set_sandbox(self.sandbox);
+        engine.set_size_limits(self.limits);
+        engine.set_module_cache(self.modules.clone());
+        engine.set_record_methods(self.methods.clone());
+        engine.set_enum_names(self.enums.clone());
+        engine.set_counters(self.counters.clone());
+        engine.set_cfg(self.cfg.clone());
+        engine.set_update_snapshots(self.update_snapshots);
+        engine.set_host(self.host.clone());
+        engine
+    }
+
+    /// 重新加载会话中源文件被修改过的模块，返回它们的路径
+    ///
+    /// 被修改的模块从头重新执行：模块的顶层变量按新源码重新初始化，
+    /// 会话与其它模块中由 `import` 绑定的别名改为指向新模块，它们自己的变量保持不变；
+    /// 此前从模块中取出并另存的值（如 `let f = m.helper`）仍是旧版本。
+    /// 新源码无法通过检查或执行出错时返回错误，该模块的旧版本继续有效。
+    pub fn reload_modules(&mut self) -> Result<Vec<PathBuf>, PawError> {
+        let engine = self.engine();
+        self.block_on(|| vuot::run(ReloadModules { engine })).map_err(|e| *e)
+    }
+
+    /// 与 `reload_modules` 相同，但在调用方所在的异步运行时中执行
+    pub async fn reload_modules_async(&mut self) -> Result<Vec<PathBuf>, PawError> {
+        vuot::run(ReloadModules {
+            engine: self.engine(),
+        })
+        .await
+        .map_err(|e| *e)
+    }
+
+    /// 按名字调用一个脚本中定义的函数；async 函数会被 await，返回其结果
+    pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> Result<Value, PawError> {
+        let call = self.prepare_call(name, args).map_err(|e| *e)?;
+        self.block_on(|| vuot::run(call)).map_err(|e| *e)
+    }
+
+    /// 与 `call_function` 相同，但在调用方所在的异步运行时中执行
+    pub async fn call_function_async(
+        &mut self,
+        name: &str,
+        args: Vec<Value>,
+    ) -> Result<Value, PawError> {
+        let call = self.prepare_call(name, args).map_err(|e| *e)?;
+        vuot::run(call).await.map_err(|e| *e)
+    }
+
+    /// 查找函数并检查参数个数
+    fn prepare_call(&self, name: &str, args: Vec<Value>) -> Result<CallFunction, Box<PawError>> {
+        let func = self.env.get(name).ok_or_else(|| PawError::UndefinedVariable {
+            file: self.file.clone(),
+            code: "E4001",
+            name: name.to_string(),
+            line: 0,
+            column: 0,
+            snippet: None,
+            hint: Some("Define the function before calling it".into()),
+        })?;
+        // memoize 包装的函数按被包装的函数检查参数个数
+        let target = match &*func.0 {
+            ValueInner::Memoized { func, .. } => func,
+            _ => &func,
+        };
+        // 有默认值的尾部参数可以省略
+        let (required, arity) = match &*target.0 {
+            ValueInner::Function { params, .. } => {
+                (params.iter().take_while(|p| p.default.is_none()).count(), params.len())
+            }
+            ValueInner::NativeFunction { .. } | ValueInner::Overloaded(_) => (args.len(), args.len()),
+            _ => {
+                return Err(Box::new(PawError::Runtime {
+                    file: self.file.clone(),
+                    code: "E4002",
+                    message: format!("{} is not callable", name),
+                    line: 0,
+                    column: 0,
+                    snippet: None,
+                    hint: None,
+                    backtrace: Vec::new(),
+                }));
+            }
+        };
+        if args.len() < required || args.len() > arity {
+            let expected = if required == arity {
+                arity.to_string()
+            } else {
+                format!("{} to {}", required, arity)
+            };
+            return Err(Box::new(PawError::Runtime {
+                file: self.file.clone(),
+                code: "E3032",
+                message: format!(
+                    "Function '{}' expects {} argument(s), found {}",
+                    name,
+                    expected,
+                    args.len()
+                ),
+                line: 0,
+                column: 0,
+                snippet: None,
+                hint: None,
+                backtrace: Vec::new(),
+            }));
+        }
+        // 宿主传入的实参没有经过类型检查器，按值检查运行时能判断的参数类型；数值之间允许转换
+        let engine = self.engine();
+        if let ValueInner::Function { params, .. } = &*target.0 {
+            for (param, arg) in params.iter().zip(&args) {
+                let expected = engine.runtime_type(&param.ty);
+                let found = arg.static_type();
+                if expected.is_runtime_checkable()
+                    && !arg.is_instance(&expected)
+                    && expected.overload_score(&found).is_none()
+                {
+                    return Err(Box::new(PawError::Runtime {
+                        file: self.file.clone(),
+                        code: "E3033",
+                        message: format!(
+                            "Argument '{}' of '{}' has type {}, expected {}",
+                            param.name, name, found, expected
+                        ),
+                        line: 0,
+                        column: 0,
+                        snippet: None,
+                        hint: None,
+                        backtrace: Vec::new(),
+                    }));
+                }
+            }
+        }
+        Ok(CallFunction {
+            engine,
+            func,
+            args,
+            name: name.to_string(),
+        })
+    }
+
+    /// 在新的运行时上驱动一次执行
+    fn block_on<T: Send, M, F>(&self, make: M) -> Result<T, Box<PawError>>
+    where
+        M: FnOnce() -> F + Send,
+        F: std::future::Future<Output = Result<T, Box<PawError>>>,
+    {
+        let stack_size = STACK_SIZE.get().copied().unwrap_or(1) * 1024 * 1024;
+        runtime::block_on(make, stack_size).map_err(|e| PawError::Internal {
+            file: self.file.clone(),
+            code: "E6011",
+            message: format!("Failed to start the async runtime: {}", e),
+            line: 0,
+            column: 0,
+            snippet: None,
+            hint: None,
+        })?
+    }
+}
+
+/// 执行一段程序，返回顶层 return 或末尾表达式的值
+struct EvalProgram<'local> {
+    engine: Engine,
+    statements: &'local [Statement],
+}
+
+impl<'a> StacklessFn<'a, Result<Value, Box<PawError>>> for EvalProgram<'_> {
+    async fn call(mut self, stack: Stack<'_>) -> Result<Value, Box<PawError>> {
+        let (body, tail) = match self.statements.split_last() {
+            Some((last, rest)) => match &last.kind {
+                StatementKind::Expr(e) => (rest, Some(e)),
+                _ => (self.statements, None),
+            },
+            None => (self.statements, None),
+        };
+        let flow = self.engine.eval_statements(stack, body).await?;
+        let value = match (flow.into_return(), tail) {
+            (Some(v), _) => v,
+            (None, Some(e)) => {
+                self.engine.count_statement();
+                self.engine.eval_expr(stack, e).await?
+            }
+            (None, None) => Value::Null(),
+        };
+        let value = resolve(value).await?;
+        self.engine.join_tasks().await?;
+        Ok(value)
+    }
+}
+
+/// 求值单个表达式
+struct Evaluate<'local> {
+    engine: Engine,
+    source: &'local str,
+    read_only: bool,
+}
+
+impl<'a> StacklessFn<'a, Result<Value, Box<PawError>>> for Evaluate<'_> {
+    async fn call(mut self, stack: Stack<'_>) -> Result<Value, Box<PawError>> {
+        let value = self.engine.evaluate(stack, self.source, self.read_only).await?;
+        let value = resolve(value).await?;
+        self.engine.join_tasks().await?;
+        Ok(value)
+    }
+}
+
+/// 以宿主提供的参数调用一个函数值
+struct CallFunction {
+    engine: Engine,
+    func: Value,
+    args: Vec<Value>,
+    name: String,
+}
+
+impl<'a> StacklessFn<'a, Result<Value, Box<PawError>>> for CallFunction {
+    async fn call(mut self, stack: Stack<'_>) -> Result<Value, Box<PawError>> {
+        let value = self
+            .engine
+            .call_function(stack, &self.func, self.args, &self.name, 0, 0)
+            .await?;
+        let value = resolve(value).await?;
+        self.engine.join_tasks().await?;
+        Ok(value)
+    }
+}
+
+/// 重新加载被修改过的模块
+struct ReloadModules {
+    engine: Engine,
+}
+
+impl<'a> StacklessFn<'a, Result<Vec<PathBuf>, Box<PawError>>> for ReloadModules {
+    async fn call(self, stack: Stack<'_>) -> Result<Vec<PathBuf>, Box<PawError>> {
+        self.engine.reload_modules(stack).await
+    }
+}
+
+/// 若结果是 Future，则等待它完成并返回其结果；运行时结束后 Future 无法再被驱动
+async fn resolve(value: Value) -> Result<Value, Box<PawError>> {
+    match value.await_future().await {
+        Some(res) => res,
+        None => Ok(value),
+    }
+}
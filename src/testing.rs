@@ -0,0 +1,112 @@
+// src/testing.rs
+//
+// `pawc test`：脚本中名为 `test_*` 的顶层无参函数就是测试。
+// 每个测试在新的会话中先执行文件的顶层代码再调用，测试之间互不影响；
+// 测试以 bark、运行时错误或失败的断言（`assert`、`assert_eq` 等与 `assert_snapshot`）判为失败
+
+use crate::ast::statement::{Statement, StatementKind};
+use crate::error::error::PawError;
+use crate::interpreter::host::Host;
+use crate::lexer::lexer::Lexer;
+use crate::parser::cfg::Cfg;
+use crate::parser::parser::Parser;
+use crate::session::Session;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 运行测试的选项
+pub struct TestOptions {
+    /// `@cfg(...)` 使用的开关
+    pub cfg: Cfg,
+    /// 用新值覆盖与之不符的快照，而不是判为失败
+    pub update_snapshots: bool,
+    /// 每个测试开始时用这个种子重置 `random` 与 `forall` 的随机数；None 时每次运行都不同
+    pub seed: Option<u64>,
+}
+
+/// 一个测试函数的结果
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: Result<(), PawError>,
+}
+
+/// 展开测试路径：文件原样保留，目录递归查找 `*_test.paw`；结果按路径排序
+pub fn find_test_files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found = Vec::new();
+            collect_tests(path, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn collect_tests(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_tests(&path, out)?;
+        } else if path.file_name().is_some_and(|n| n.to_string_lossy().ends_with("_test.paw")) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 按声明顺序运行文件中的测试；文件无法读取、解析、通过检查或顶层代码出错时返回该错误
+pub fn run_test_file(path: &Path, options: &TestOptions) -> Result<Vec<TestResult>, Box<PawError>> {
+    let file = path.to_string_lossy();
+    let src = fs::read_to_string(path).map_err(|e| PawError::Internal {
+        file: file.to_string(),
+        code: "E1000",
+        message: format!("Failed to read script '{}': {}", path.display(), e),
+        line: 0,
+        column: 0,
+        snippet: None,
+        hint: Some("Ensure the file exists and is readable.".into()),
+    })?;
+    let load = || {
+        let mut session = Session::new(&file);
+        session.set_cfg(options.cfg.clone());
+        session.set_update_snapshots(options.update_snapshots);
+        if let Some(seed) = options.seed {
+            let host = Arc::new(Host::new());
+            host.seed_random(seed);
+            session.set_host(host);
+        }
+        session.eval(&src).map(|_| session).map_err(Box::new)
+    };
+    // 先执行一次顶层代码，错误归于整个文件；第一个测试沿用这个会话
+    let mut loaded = Some(load()?);
+    let tokens = Lexer::new(&src).tokenize();
+    let ast = Parser::new(tokens, &src, &file).with_cfg(&options.cfg).parse_program()?;
+    let mut results = Vec::new();
+    for name in test_names(&ast) {
+        let outcome = match loaded.take().map_or_else(load, Ok) {
+            Ok(mut session) => session.call_function(&name, Vec::new()).map(|_| ()),
+            Err(err) => Err(*err),
+        };
+        results.push(TestResult { name, outcome });
+    }
+    Ok(results)
+}
+
+/// 顶层声明的、没有参数的 `test_*` 函数
+fn test_names(ast: &[Statement]) -> Vec<String> {
+    ast.iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::FunDecl { name, params, .. } if name.starts_with("test_") && params.is_empty() => {
+                Some(name.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,90 @@
+// tests/arithmetic.rs
+//
+// 算术运算的运行时错误：整数除以零、最小值除以 -1 不会让解释器 panic，而是抛出可捕获的 E6014；
+// 整数加减乘与取负溢出时抛出 E6021；
+// 以及浮点数的输出格式
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::eval;
+
+#[test]
+fn int_and_long_division_by_zero_report_e6014() {
+    for src in [
+        "let z: Int = 0\n1 / z",
+        "let z: Int = 0\n1 % z",
+    ] {
+        let err = PawScript::new().eval_str(src).unwrap_err();
+        assert_eq!(err.code(), "E6014", "{}", src);
+    }
+}
+
+#[test]
+fn minimum_divided_by_minus_one_reports_e6014() {
+    for (src, message) in [
+        ("let a: Int = -2147483647 - 1\nlet b: Int = -1\na / b", "-2147483648 / -1 overflows Int"),
+        ("let a: Long = -9223372036854775807L - 1L\nlet b: Long = -1L\na / b", "-9223372036854775808 / -1 overflows Long"),
+    ] {
+        let err = PawScript::new().eval_str(src).unwrap_err();
+        assert_eq!((err.code(), err.message()), ("E6014", message.to_string()), "{}", src);
+    }
+    // 取模的结果是 0，不会溢出
+    assert_eq!(eval("let a: Int = -2147483647 - 1\nlet b: Int = -1\na % b"), Value::Int(0));
+    assert_eq!(eval("let a: Long = -9223372036854775807L - 1L\nlet b: Long = -1L\na % b"), Value::Long(0));
+    let v = eval("let a: Int = -2147483647 - 1\nlet m: String = \"\"\nsniff { say a / -1 } snatch (e) { m = e }\nm");
+    assert_eq!(v, Value::String("-2147483648 / -1 overflows Int"));
+}
+
+#[test]
+fn integer_overflow_reports_e6021() {
+    for (src, message) in [
+        ("2147483647 + 1", "2147483647 + 1 overflows Int"),
+        ("let a: Int = -2147483647\na - 2", "-2147483647 - 2 overflows Int"),
+        ("65536 * 65536", "65536 * 65536 overflows Int"),
+        ("9223372036854775807L + 1L", "9223372036854775807 + 1 overflows Long"),
+        ("let a: Int = -2147483647 - 1\nsay -a", "-(-2147483648) overflows Int"),
+    ] {
+        let err = PawScript::new().eval_str(src).unwrap_err();
+        assert_eq!((err.code(), err.message()), ("E6021", message.to_string()), "{}", src);
+    }
+    // 换成 Long 即可得到正确结果
+    assert_eq!(eval("2147483647L + 1L"), Value::Long(2147483648));
+    assert_eq!(eval("let a: Int = 2147483647\nlet m: String = \"\"\nsniff { say a * 2 } snatch (e) { m = e }\nm"), Value::String("2147483647 * 2 overflows Int"));
+}
+
+#[test]
+fn division_by_zero_reports_position() {
+    let err = PawScript::new()
+        .eval_str("let z: Int = 0\nsay 1 + 10 / z")
+        .unwrap_err();
+    let text = err.to_string();
+    assert!(text.contains("Division by zero"), "{}", text);
+    assert!(text.contains(":2:"), "{}", text);
+}
+
+#[test]
+fn division_by_zero_can_be_snatched() {
+    let v = eval(
+        r#"
+let caught: String = ""
+loop d in [2, 0, 5] {
+  sniff {
+    say 10 % d
+  } snatch (e) {
+    caught = e
+  } lastly {
+  }
+}
+caught
+"#,
+    );
+    assert_eq!(v, Value::String("Division by zero"));
+}
+
+#[test]
+fn floats_always_print_a_decimal_point() {
+    assert_eq!(eval("2.0").to_string(), "2.0");
+    assert_eq!(eval("\"${1.5 * 2.0}\"").to_string(), "3.0");
+    assert_eq!(format!("{:.3}", eval("2.0")), "2.000");
+}
@@ -0,0 +1,123 @@
+// tests/arrays.rs
+//
+// Array 的 sort / reverse / slice 返回新数组、原数组保持不变；push / pop 原地修改接收者
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+#[test]
+fn sort_uses_natural_order_and_leaves_the_source_alone() {
+    let v = eval_string(
+        r#"
+let xs: Array<Int> = [3, 1, 2, 1]
+let sorted: Array<Int> = xs.sort()
+"${sorted} ${xs}"
+"#,
+    );
+    assert_eq!(v, "[1, 1, 2, 3] [3, 1, 2, 1]");
+    assert_eq!(eval_string(r#"["pear", "apple", "fig"].sort()"#), "[apple, fig, pear]");
+}
+
+#[test]
+fn sort_with_comparator_is_stable() {
+    let v = eval_string(
+        r#"
+let words: Array<String> = ["bb", "a", "cc", "d", "eee"]
+words.sort(fun(a: String, b: String): Int { return a.length() - b.length() })
+"#,
+    );
+    assert_eq!(v, "[a, d, bb, cc, eee]");
+    let v = eval_string(
+        "[5, 3, 9, 1, 7, 2].sort(fun(a: Int, b: Int): Int { return b - a })",
+    );
+    assert_eq!(v, "[9, 7, 5, 3, 2, 1]");
+}
+
+#[test]
+fn reverse_and_slice() {
+    assert_eq!(eval_string("[1, 2, 3].reverse()"), "[3, 2, 1]");
+    assert_eq!(eval_string("[1, 2, 3, 4].slice(1, 3)"), "[2, 3]");
+    assert_eq!(eval_string("[1, 2].slice(2, 2)"), "[]");
+    assert_eq!(error_code("say [1, 2].slice(1, 3)"), "E6010");
+}
+
+#[test]
+fn sort_rules_are_checked() {
+    assert_eq!(
+        error_code("say [1, 2].sort(fun(a: Int, b: Int): Bool { return a < b })"),
+        "E3022"
+    );
+    assert_eq!(error_code("say [[1], [2]].sort()"), "E3021");
+    assert_eq!(error_code("say [1].slice(0)"), "E3024");
+    assert_eq!(error_code(r#"say [1].slice(0, "1")"#), "E3025");
+    // 类型为 Any 的宿主数组可能混有不同类型的元素，只能在运行时发现
+    let mut paw = PawScript::new();
+    paw.define("xs", Value::Array(vec![Value::Int(1), Value::String("a")]));
+    assert_eq!(paw.eval_str("say xs.sort()").unwrap_err().code(), "E6003");
+}
+
+#[test]
+fn methods_keep_the_element_type() {
+    assert_eq!(
+        error_code("let xs: Array<String> = [1, 2].reverse()"),
+        "E3003"
+    );
+    let v: Value = PawScript::new()
+        .eval_str("let xs: Array<Int> = [4, 2].sort()\nxs[0]")
+        .unwrap();
+    assert_eq!(v, Value::Int(2));
+}
+
+#[test]
+fn push_and_pop_modify_the_variable() {
+    let v = eval_string(
+        r#"
+let xs: Array<Int> = [1, 2]
+let alias: Array<Int> = xs
+xs.push(3)
+let last: Int = xs.pop()
+xs.push(last * 10)
+"${xs} ${alias}"
+"#,
+    );
+    assert_eq!(v, "[1, 2, 30] [1, 2]");
+}
+
+#[test]
+fn push_and_pop_reach_elements_and_fields() {
+    let v = eval_string(
+        r#"
+record Bag { items: Array<String> }
+let bag: Bag = Bag { items: [] }
+bag.items.push("a")
+bag.items.push("b")
+let grid: Array<Array<Int>> = [[0], [1]]
+grid[0].push(7)
+grid[1].pop()
+"${bag.items} ${grid}"
+"#,
+    );
+    assert_eq!(v, "[a, b] [[0, 7], []]");
+}
+
+#[test]
+fn push_inside_functions_and_loops() {
+    let v = eval_string(
+        r#"
+let seen: Array<Int> = []
+fun note(n: Int): Void { seen.push(n) }
+loop i in 0..4 { note(i * i) }
+seen
+"#,
+    );
+    assert_eq!(v, "[0, 1, 4, 9]");
+}
+
+#[test]
+fn push_needs_a_place_and_pop_needs_elements() {
+    assert_eq!(error_code("[1, 2].push(3)"), "E3040");
+    assert_eq!(eval_string("[1, 2].pop()"), "2");
+    assert_eq!(error_code("let xs: Array<Int> = []\nsay xs.pop()"), "E3016");
+}
@@ -0,0 +1,102 @@
+// tests/assertions.rs
+//
+// `assert_eq`、`assert_ne`、`assert_close` 与 `assert_throws`：失败时报 E6024，两个值按 `inspect` 展示，
+// 多行的值在提示中给出逐行差异；`pawc test` 照常把它们的失败记为测试失败
+
+mod common;
+
+use std::fs;
+use std::process::Command;
+use common::{eval_string, failure};
+
+#[test]
+fn passing_assertions_return_normally() {
+    let v = eval_string(
+        r#"
+record Cat { name: String  lives: Int }
+assert_eq(1 + 2, 3)
+assert_eq([1, 2], [1, 2], "arrays")
+assert_eq(Cat { name: "Mochi", lives: 9 }, Cat { name: "Mochi", lives: 9 })
+assert_ne("paw", "claw")
+assert_close(0.1 + 0.2, 0.3, 0.000001)
+assert_close(10, 10.5, 1)
+let message = assert_throws(fun(): Int { bark "no fish" })
+let zero = 0
+let crash = assert_throws(fun(): Int { return 1 / zero }, "division")
+"${message}|${crash.length() > 0}"
+"#,
+    );
+    assert_eq!(v, "no fish|true");
+}
+
+#[test]
+fn failures_show_both_values() {
+    let (code, message, _, hint) = failure("assert_eq(2 + 2, 5)");
+    assert_eq!(code, "E6024");
+    assert_eq!(message, "Assertion failed: expected Int 5, found Int 4");
+    assert_eq!(hint, "");
+    let (_, message, _, _) = failure("assert_eq(\"paw\", \"claw\", \"names\")");
+    assert_eq!(message, "Assertion failed: names: expected String \"claw\", found String \"paw\"");
+    let (_, message, _, _) = failure("assert_ne(3, 3)");
+    assert_eq!(message, "Assertion failed: both values are Int 3");
+    let (_, message, _, _) = failure("assert_close(1.0, 1.5, 0.1)");
+    assert_eq!(message, "Assertion failed: expected 1.5 ± 0.1, found 1.0 (off by 0.5)");
+    let (_, message, _, _) = failure("assert_throws(fun(): Int { return 7 })");
+    assert_eq!(message, "Assertion failed: expected the function to bark, but it returned 7");
+    // 负的容差是参数错误，不是断言失败
+    assert_eq!(failure("assert_close(1.0, 1.0, -1.0)").0, "E6013");
+}
+
+#[test]
+fn multi_line_values_get_a_line_diff() {
+    let (code, message, _, hint) = failure("assert_eq([1, 2, 3], [1, 5, 3])");
+    assert_eq!(code, "E6024");
+    assert_eq!(message, "Assertion failed: values are not equal");
+    assert_eq!(
+        hint,
+        "Difference (- expected, + actual):\n  Array(3) [\n    Int 1,\n-   Int 5,\n+   Int 2,\n    Int 3,\n  ]"
+    );
+    let (_, message, _, hint) = failure("assert_ne({\"a\": [1]}, {\"a\": [1]})");
+    assert_eq!(message, "Assertion failed: both values are equal");
+    assert!(hint.starts_with("Both values are:\nMap(1) {"), "{}", hint);
+}
+
+#[test]
+fn assertions_are_type_checked() {
+    for src in [
+        "assert_eq(1, \"1\")",
+        "assert_eq(1, 1, 2)",
+        "assert_close(\"1\", 1.0, 0.1)",
+        "assert_throws(fun(n: Int): Int { return n })",
+        "assert_throws(1)",
+    ] {
+        assert_eq!(failure(src).0, "E3033", "{}", src);
+    }
+    assert_eq!(failure("assert_eq(1)").0, "E3032");
+    // assert_throws 的结果是错误信息
+    assert_eq!(failure("let n: Int = assert_throws(fun(): Int { bark \"x\" })").0, "E3003");
+}
+
+#[test]
+fn the_test_runner_reports_the_diff() {
+    let dir = std::env::temp_dir().join(format!("pawc-assertions-runner-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("lists_test.paw"),
+        "fun test_same(): Void { assert_eq([1, 2], [1, 2]) }\nfun test_diff(): Void { assert_eq([1, 2], [1, 3], \"lists\") }\n",
+    )
+    .unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .current_dir(&dir)
+        .args(["test", "lists_test.paw"])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let all = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
+    assert!(all.contains("Tested 1 file(s): 1 passed, 1 failed"), "{}", all);
+    assert!(all.contains("Assertion failed: lists: values are not equal"), "{}", all);
+    assert!(all.contains("-   Int 3,") && all.contains("+   Int 2,"), "{}", all);
+    let _ = fs::remove_dir_all(&dir);
+}
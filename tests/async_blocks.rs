@@ -0,0 +1,53 @@
+// tests/async_blocks.rs
+//
+// `async { ... }` 表达式：求值为 Future<T>，T 是代码块中 return 的类型；
+// 代码块捕获外层变量，在被 await 时才执行，其中的 bark 在 await 处抛出
+
+mod common;
+
+use common::{error_code, eval_string, pawc, script};
+
+#[test]
+fn blocks_run_when_awaited() {
+    let path = script(
+        "async-blocks",
+        "order",
+        r#"let base: Int = 20
+let f = async {
+  say "in block"
+  return base + 1
+}
+say "before"
+let r: Int = await f
+say r * 2
+let v: Future<Void> = async { say "void" }
+await v
+"#,
+    );
+    let out = pawc(&[], &path);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "before\nin block\n42\nvoid\n");
+}
+
+#[test]
+fn blocks_are_typed_futures() {
+    assert_eq!(eval_string("let f: Future<String> = async { return \"paw\" }\nawait f"), "paw");
+    assert_eq!(eval_string("let n: Int = await async { return 6 * 7 }\nn"), "42");
+    for src in [
+        "let f: Future<String> = async { return 1 }",
+        "let f: Int = async { return 1 }",
+        "let x: Int = await async { return \"s\" }",
+    ] {
+        assert_eq!(error_code(src), "E3003", "{}", src);
+    }
+}
+
+#[test]
+fn barks_surface_at_the_await() {
+    assert_eq!(
+        eval_string(
+            "let f = async { bark \"no\" }\nlet out: String = \"\"\nsniff { await f } snatch (e) { out = \"caught ${e}\" }\nout"
+        ),
+        "caught no"
+    );
+}
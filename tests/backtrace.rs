@@ -0,0 +1,86 @@
+// tests/backtrace.rs
+//
+// 运行时错误附带的调用栈：记录错误离开的每一层函数调用及其调用位置
+
+use pawc::PawScript;
+
+fn fail(src: &str) -> pawc::PawError {
+    PawScript::with_file("main.paw")
+        .eval_str(src)
+        .err()
+        .unwrap_or_else(|| panic!("script should fail:\n{}", src))
+}
+
+/// 调用栈中每一帧的 (函数名, 行号)
+fn frames(err: &pawc::PawError) -> Vec<(String, usize)> {
+    err.backtrace()
+        .iter()
+        .map(|f| (f.function.clone(), f.line))
+        .collect()
+}
+
+#[test]
+fn nested_calls_are_listed_innermost_first() {
+    let err = fail(
+        r#"fun inner(x: Int): Int {
+  return 10 / x
+}
+fun outer(x: Int): Int {
+  return inner(x - 1) + 1
+}
+outer(1)
+"#,
+    );
+    assert_eq!(err.code(), "E6014");
+    assert_eq!(
+        frames(&err),
+        vec![("inner".to_string(), 5), ("outer".to_string(), 7)]
+    );
+    let text = err.to_string();
+    assert!(text.contains("Call stack"), "{}", text);
+    assert!(text.contains("inner called at main.paw:5:"), "{}", text);
+}
+
+#[test]
+fn top_level_errors_have_no_call_stack() {
+    let err = fail("bark \"boom\"");
+    assert!(err.backtrace().is_empty());
+    assert!(!err.to_string().contains("Call stack"));
+}
+
+#[test]
+fn async_functions_and_callbacks_appear_in_call_stack() {
+    let err = fail(
+        r#"async fun load(n: Int): Int {
+  if n > 2 { bark "too big" }
+  return n
+}
+fun check(n: Int): Int {
+  return await load(n)
+}
+[1, 5].map(check)
+"#,
+    );
+    assert_eq!(
+        frames(&err),
+        vec![
+            ("load".to_string(), 6),
+            ("map callback".to_string(), 8),
+        ]
+    );
+}
+
+#[test]
+fn deep_recursion_is_truncated_in_display() {
+    let err = fail(
+        r#"fun down(n: Int): Int {
+  if n == 0 { bark "bottom" }
+  return down(n - 1)
+}
+down(100)
+"#,
+    );
+    assert_eq!(err.backtrace().len(), 101);
+    let text = err.to_string();
+    assert!(text.contains("… 85 more"), "{}", text);
+}
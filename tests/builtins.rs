@@ -0,0 +1,148 @@
+// tests/builtins.rs
+//
+// 内置函数：脚本中没有同名定义时可直接调用
+
+mod common;
+
+use pawc::Value;
+use common::{error_code, eval, eval_string};
+
+// —— memoize ——
+
+#[test]
+fn memoize_caches_by_arguments() {
+    let v = eval(
+        r#"
+let calls: Int = 0
+fun square(x: Int): Int {
+  calls = calls + 1
+  return x * x
+}
+let sq: fun(Int): Int = memoize(square)
+let total: Int = sq(4) + sq(4) + sq(5) + sq(4)
+total * 10 + calls
+"#,
+    );
+    assert_eq!(v, Value::Int(732));
+}
+
+#[test]
+fn memoize_speeds_up_recursion() {
+    // 未缓存时 fib(46) 需要数十亿次调用
+    let v = eval(
+        r#"
+fun fib(n: Int): Int {
+  if n < 2 { return n }
+  return fib(n - 1) + fib(n - 2)
+}
+fib = memoize(fib)
+fib(46)
+"#,
+    );
+    assert_eq!(v, Value::Int(1836311903));
+}
+
+#[test]
+fn memoize_does_not_cache_errors() {
+    let v = eval(
+        r#"
+let attempts: Int = 0
+fun flaky(x: Int): Int {
+  attempts = attempts + 1
+  if attempts == 1 { bark "first call fails" }
+  return x
+}
+let f: fun(Int): Int = memoize(flaky)
+sniff { f(1) } snatch (e) { }
+f(1) + f(1) + attempts
+"#,
+    );
+    assert_eq!(v, Value::Int(4));
+}
+
+#[test]
+fn memoize_rejects_non_functions() {
+    assert_eq!(error_code("memoize(3)"), "E3033");
+    assert_eq!(error_code("fun f(): Int { return 1 }\nmemoize(f, f)"), "E3032");
+}
+
+#[test]
+fn user_definition_shadows_builtin() {
+    let v = eval(
+        r#"
+fun memoize(x: Int): Int { return x + 1 }
+memoize(41)
+"#,
+    );
+    assert_eq!(v, Value::Int(42));
+}
+
+// —— range / zip / repeat ——
+
+#[test]
+fn range_builds_int_arrays() {
+    assert_eq!(eval_string("range(4)"), "[0, 1, 2, 3]");
+    assert_eq!(eval_string("range(2, 5)"), "[2, 3, 4]");
+    assert_eq!(eval_string("range(5, 2)"), "[]");
+    assert_eq!(error_code(r#"range("3")"#), "E3033");
+    assert_eq!(error_code("range(1, 2, 3)"), "E3032");
+}
+
+#[test]
+fn zip_pairs_up_to_the_shorter_array() {
+    let v = eval(
+        r#"
+let pairs: Array<Array<Int>> = zip(range(3), [10, 20])
+let total: Int = 0
+loop p in pairs { total = total + p[0] * p[1] }
+total
+"#,
+    );
+    assert_eq!(v, Value::Int(20));
+    assert_eq!(eval_string(r#"zip([1], ["a"])"#), "[[1, a]]");
+    assert_eq!(error_code("zip([1], 2)"), "E3033");
+}
+
+#[test]
+fn repeat_fills_an_array() {
+    assert_eq!(eval_string(r#"repeat("ab", 3)"#), "[ab, ab, ab]");
+    assert_eq!(eval_string("repeat(1, 0)"), "[]");
+    assert_eq!(error_code("repeat(1, 0 - 1)"), "E6013");
+    assert_eq!(
+        error_code("let xs: Array<String> = repeat(1, 2)"),
+        "E3003"
+    );
+}
+
+// —— format ——
+
+#[test]
+fn format_substitutes_positional_values() {
+    assert_eq!(
+        eval_string(r#"let name: String = "Tom"
+format("{} has {} paws", name, 4)"#),
+        "Tom has 4 paws"
+    );
+    assert_eq!(eval_string(r#"format("{1}-{0}-{1}", "a", 2.0)"#), "2.0-a-2.0");
+    assert_eq!(eval_string(r#"format("{{}} {}", [1, 2])"#), "{} [1, 2]");
+}
+
+#[test]
+fn format_literal_templates_are_checked() {
+    assert_eq!(error_code(r#"format("{} and {}", 1)"#), "E3032");
+    assert_eq!(error_code(r#"format("{}", 1, 2)"#), "E3032");
+    assert_eq!(error_code(r#"format("{oops}", 1)"#), "E3033");
+    assert_eq!(error_code("format(1)"), "E3033");
+    assert_eq!(error_code("format()"), "E3032");
+}
+
+#[test]
+fn format_dynamic_templates_are_checked_at_runtime() {
+    assert_eq!(
+        error_code(r#"let t: String = "{} {}"
+format(t, 1)"#),
+        "E6013"
+    );
+    assert_eq!(eval_string(r#"let t: String = "<{}>"
+format(t, nopaw)"#), "<Nopaw>");
+}
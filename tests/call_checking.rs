@@ -0,0 +1,60 @@
+// tests/call_checking.rs
+//
+// 调用处的实参检查：个数不符报 E3032，多传时指向第一个多余的实参，少传时列出缺少的参数；
+// 类型不符报 E3033 并指向出错的实参，能查到参数名时给出参数名
+
+mod common;
+
+use common::failure;
+
+const GREET: &str = "fun greet(name: String, times: Int): String { return name }\n";
+
+#[test]
+fn wrong_argument_types_point_at_the_argument() {
+    let (code, message, at, _) = failure(&format!("{}greet(\"Mochi\", \"twice\")", GREET));
+    assert_eq!(code, "E3033");
+    assert_eq!(message, "Argument 'times' of 'greet' has type String, expected Int");
+    assert_eq!(at, (2, 16));
+    // 方法的参数同样带名字，self 不计入
+    let (_, message, at, _) = failure(
+        "record Cat {\n  lives: Int\n  fun lose(n: Int): Int { return self.lives - n }\n}\n\
+         let c = Cat { lives: 9 }\nc.lose(true)",
+    );
+    assert_eq!(message, "Argument 'n' of 'Cat.lose' has type Bool, expected Int");
+    assert_eq!(at, (6, 8));
+    // 变量中的函数没有参数名
+    let (_, message, at, _) = failure("let twice = fun(n: Int): Int { return n * 2 }\nlet x: Int = twice(\"2\")");
+    assert_eq!(message, "Argument to 'twice' has type String, expected Int");
+    assert_eq!(at, (2, 20));
+}
+
+#[test]
+fn arity_errors_show_what_is_missing_or_extra() {
+    let (code, message, at, hint) = failure(&format!("{}greet(\"Mochi\")", GREET));
+    assert_eq!(code, "E3032");
+    assert_eq!(message, "Function 'greet' expects 2 argument(s), found 1");
+    assert_eq!(at, (2, 1));
+    assert_eq!(hint, "Missing times: Int; signature: fun(String, Int): String");
+
+    let (code, _, at, hint) = failure(&format!("{}greet(\"Mochi\", 2, 3, 4)", GREET));
+    assert_eq!(code, "E3032");
+    assert_eq!(at, (2, 19));
+    assert_eq!(hint, "Signature: fun(String, Int): String");
+    // 有默认值时同样指向多余的实参
+    let (_, message, at, _) = failure("fun f(a: Int, b: Int = 1): Int { return a + b }\nf(1, 2, 3)");
+    assert_eq!(message, "Function 'f' expects 1 to 2 argument(s), found 3");
+    assert_eq!(at, (2, 9));
+}
+
+#[test]
+fn builtin_method_arguments_point_at_the_argument() {
+    let (code, message, at, _) = failure("let xs: Array<Int> = [1]\nxs.push(\"two\")");
+    assert_eq!(code, "E3022");
+    assert_eq!(message, "Method 'push' on Array requires Int argument, found String");
+    assert_eq!(at, (2, 9));
+    let (code, _, at, _) = failure("let s: String = \"paw\"\nlet t: String = s.replace(\"p\", 1)");
+    assert_eq!(code, "E3025");
+    assert_eq!(at, (2, 32));
+    let (_, _, at, _) = failure("let s: String = \"paw\"\nlet b: Bool = s.starts_with(1)");
+    assert_eq!(at, (2, 29));
+}
@@ -0,0 +1,103 @@
+// tests/casts.rs
+//
+// `as`：数字之间、基础类型与 String 之间、Char 与 Int 之间的转换，
+// 转换不了的值在运行时报 E6019
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string, failure};
+
+#[test]
+fn numbers_convert_between_types() {
+    let v = eval_string(
+        r#"
+let big: Long = 40000000000L
+let i: Int = 7
+let d: Double = -2.75
+let n: Int = 300L as Int
+let f: Float = i as Float
+"${n + 1} ${f} ${d as Int} ${d as Long} ${(big as Double) / 2.0} ${i as Double}"
+"#,
+    );
+    assert_eq!(v, "301 7.0 -2 -2 20000000000.0 7.0");
+}
+
+#[test]
+fn basic_types_convert_to_and_from_strings() {
+    let v = eval_string(
+        r#"
+let port: Int = " 8080 " as Int
+let ratio: Double = "0.25" as Double
+let flag: Bool = "true" as Bool
+let c: Char = "x" as Char
+let s: String = 42 as String
+"${port + 1} ${ratio * 2.0} ${flag} ${c} ${s.length()} ${'y' as String + true as String}"
+"#,
+    );
+    assert_eq!(v, "8081 0.5 true x 2 ytrue");
+}
+
+#[test]
+fn chars_convert_to_code_points() {
+    let v = eval_string(r#""${'A' as Int} ${(('a' as Int) + 2) as Char} ${128049 as Char} ${'é' as Long}""#);
+    assert_eq!(v, "65 c 🐱 233");
+}
+
+#[test]
+fn impossible_conversions_fail_at_runtime() {
+    assert_eq!(error_code(r#"say "12abc" as Int"#), "E6019");
+    assert_eq!(error_code(r#"say "" as Char"#), "E6019");
+    assert_eq!(error_code("say 40000000000L as Int"), "E6019");
+    assert_eq!(error_code(r#"say ("1e300" as Double) as Float"#), "E6019");
+    assert_eq!(error_code("say 10000000000.0 as Int"), "E6019");
+    assert_eq!(error_code("let n: Int = -1\nsay n as Char"), "E6019");
+    assert_eq!(error_code("let x: Any = [1, 2]\nsay x as Int"), "E6019");
+}
+
+#[test]
+fn cast_hints_depend_on_the_value() {
+    let hint = |src: &str| failure(src).3;
+    assert!(hint(r#"say "12abc" as Int"#).contains("`to_int` / `to_double`"));
+    assert!(hint(r#"say "yes" as Bool"#).contains("`true` or `false`"));
+    assert!(hint(r#"say "ab" as Char"#).contains("exactly one character"));
+    assert!(hint("let n: Int = -1\nsay n as Char").contains("Unicode code points"));
+    assert!(hint("say 40000000000L as Int").contains("does not fit in Int"));
+    assert!(hint("let maybe: Int? = nopaw\nsay maybe as Int").contains("`nopaw`"));
+    assert_eq!(hint("let x: Any = [1, 2]\nsay x as Int"), "Check the value with `is Int` before casting");
+}
+
+#[test]
+fn any_values_are_checked_when_cast() {
+    let v = eval_string(
+        r#"
+fun first(xs: Any): Int {
+  let ints: Array<Int> = xs as Array<Int>
+  return ints[0]
+}
+let raw: Any = "5"
+"${first([4, 5])} ${(raw as Int) + 1}"
+"#,
+    );
+    assert_eq!(v, "4 6");
+}
+
+#[test]
+fn unsupported_casts_are_type_errors() {
+    assert_eq!(error_code("say true as Int"), "E3009");
+    assert_eq!(error_code("say [1] as String"), "E3009");
+    assert_eq!(error_code("say 'a' as Double"), "E3009");
+    assert_eq!(error_code("protocol P { n: Int }\nlet x: Any = 1\nsay x as P"), "E3009");
+}
+
+#[test]
+fn optionals_and_untyped_elements_can_be_refined() {
+    let v = eval_string("let maybe: Int? = 3\nlet n: Int = maybe as Int\nn + 1");
+    assert_eq!(v, "4");
+    assert_eq!(error_code("let maybe: Int? = nopaw\nsay maybe as Int"), "E6019");
+    let mut paw = PawScript::new();
+    paw.define("ports", Value::Array(vec![Value::Int(80), Value::Int(443)]));
+    paw.define("labels", Value::Array(vec![Value::Int(1), Value::String("a")]));
+    assert_eq!(paw.eval_str("let p: Array<Int> = ports as Array<Int>\np[1]").unwrap(), Value::Int(443));
+    assert_eq!(paw.eval_str("say labels as Array<String>").unwrap_err().code(), "E6019");
+}
@@ -0,0 +1,118 @@
+// tests/cfg.rs
+//
+// 条件编译：`@cfg(...)` 标注的语句按开关保留或丢弃，丢弃的代码不做类型检查；
+// 开关来自当前平台、嵌入 API 与命令行的 `--cfg`
+
+mod common;
+
+use pawc::PawScript;
+use std::process::Command;
+use common::{error_code, write_files};
+
+fn eval_with(flags: &[&str], src: &str) -> String {
+    let mut paw = PawScript::new();
+    for flag in flags {
+        paw.enable_cfg(flag);
+    }
+    paw.eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e))
+        .to_string()
+}
+
+const MODES: &str = r#"
+@cfg("debug")
+fun mode(): String { return "debug" }
+@cfg(!"debug")
+fun mode(): String { return "release" }
+let extra: String = ""
+@cfg("debug" && "verbose") extra = "+verbose"
+@cfg("trace" || ("verbose" && !"quiet")) extra = extra + "+loud"
+"${mode()}${extra}"
+"#;
+
+#[test]
+fn statements_follow_the_flags() {
+    assert_eq!(eval_with(&[], MODES), "release");
+    assert_eq!(eval_with(&["debug"], MODES), "debug");
+    assert_eq!(eval_with(&["debug", "verbose"], MODES), "debug+verbose+loud");
+    assert_eq!(eval_with(&["verbose", "quiet"], MODES), "release");
+    assert_eq!(eval_with(&["trace"], MODES), "release+loud");
+}
+
+#[test]
+fn disabled_code_is_not_checked() {
+    let src = "let n: Int = 1\n@cfg(\"never\") {\n  let s: String = n\n  say undefined_name\n}\nn";
+    assert_eq!(eval_with(&[], src), "1");
+    let src = "@cfg(\"never\") let s: String = 1\n@cfg(!\"never\") let s: Int = 2\ns";
+    assert_eq!(eval_with(&[], src), "2");
+    // `@cfg(...) { }` 中的定义并入所在的作用域
+    assert_eq!(eval_with(&[], "@cfg(!\"never\") {\n  let a: Int = 2\n  fun twice(n: Int): Int { return n * 2 }\n}\ntwice(a)"), "4");
+    // 块内与函数体内的语句同样可以标注，多个属性全部成立时才保留
+    let src = "fun f(): Int {\n  let x: Int = 1\n  @cfg(\"a\")\n  # 注释不影响\n  @cfg(\"b\")\n  x = 10\n  return x\n}\nf()";
+    assert_eq!(eval_with(&["a"], src), "1");
+    assert_eq!(eval_with(&["a", "b"], src), "10");
+}
+
+#[test]
+fn platform_flags_are_on_by_default() {
+    let v = eval_with(&[], "let os: String = \"other\"\n@cfg(\"linux\") os = \"linux\"\n@cfg(\"windows\") os = \"windows\"\n@cfg(\"macos\") os = \"macos\"\nos");
+    let expected = match std::env::consts::OS {
+        os @ ("linux" | "windows" | "macos") => os,
+        _ => "other",
+    };
+    assert_eq!(v, expected);
+    let family = eval_with(&[], "let unix: Bool = false\n@cfg(\"unix\") unix = true\nunix");
+    assert_eq!(family, cfg!(unix).to_string());
+}
+
+#[test]
+fn malformed_attributes_are_syntax_errors() {
+    assert_eq!(error_code("@cfg(debug) say 1"), "E1001");
+    assert_eq!(error_code("@cfg(\"a\" say 1"), "E1001");
+    assert_eq!(error_code("@inline fun f(): Void { }"), "E1001");
+    assert_eq!(error_code("@cfg(\"a\")"), "E1001");
+}
+
+#[test]
+fn the_cli_flag_reaches_imported_modules() {
+    let dir = write_files(
+        "cfg",
+        "cli",
+        &[
+            ("log.paw", "let level: String = \"info\"\n@cfg(\"debug\") level = \"debug\"\n"),
+            ("main.paw", "import log\nlet extra: String = \"\"\n@cfg(\"debug\") extra = \"!\"\nsay \"${log.level}${extra}\"\n"),
+        ],
+    );
+    let run = |flags: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_pawc"))
+            .args(flags)
+            .arg(dir.join("main.paw"))
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+    assert_eq!(run(&[]), "info");
+    assert_eq!(run(&["--cfg", "debug"]), "debug!");
+
+    // `pawc check` 按同样的开关检查模块
+    let dir = write_files(
+        "cfg",
+        "check",
+        &[("lib.paw", "@cfg(\"strict\") let x: Int = \"oops\"\n"), ("main.paw", "import lib\n")],
+    );
+    let check = |flags: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_pawc"))
+            .arg("check")
+            .args(flags)
+            .arg(dir.join("main.paw"))
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap()
+            .status
+            .success()
+    };
+    assert!(check(&[]));
+    assert!(!check(&["--cfg", "strict"]));
+}
@@ -0,0 +1,118 @@
+// tests/checked_barks.rs
+//
+// 签名后的 `barks`：调用声明了 `barks` 的函数或方法时，调用方必须在 sniff 中调用或自己也声明 `barks`，
+// 否则报 E3052；把这样的函数当作值使用也一样；顶层、lambda 与 async 代码块不受检查
+
+mod common;
+
+use pawc::Session;
+use common::{eval_string, failure};
+
+const PARSE: &str = "fun parse_age(s: String): Int barks {\n  if s == \"\" { bark \"empty age\" }\n  return s.to_int()\n}\n";
+
+#[test]
+fn handled_or_declared_calls_are_accepted() {
+    let v = eval_string(&format!(
+        r#"{}
+record Cat {{
+  lives: Int
+  fun lose(n: Int): Int barks {{
+    if n > self.lives {{ bark "not enough lives" }}
+    return self.lives - n
+  }}
+  fun lose_all(): Int barks {{ return self.lose(self.lives) }}
+}}
+fun age_or_zero(s: String): Int {{
+  sniff {{
+    if s == "?" {{ return 0 }}
+    return parse_age(s)
+  }} snatch (e) {{
+    return -1
+  }}
+}}
+fun double_age(s: String): Int barks {{ return parse_age(s) * 2 }}
+fun safe_lose(c: Cat, n: Int): Int {{
+  sniff {{ return c.lose(n) }} snatch (e) {{ return 0 }}
+}}
+let c = Cat {{ lives: 9 }}
+let later = fun(): Int {{ return parse_age("") }}
+"${{age_or_zero("")}} ${{age_or_zero("12")}} ${{double_age("21")}} ${{safe_lose(c, 10)}} ${{c.lose_all()}} ${{parse_age("3")}}"
+"#,
+        PARSE
+    ));
+    assert_eq!(v, "-1 12 42 0 0 3");
+}
+
+#[test]
+fn unhandled_calls_are_reported() {
+    let (code, message, at, hint) = failure(&format!("{}fun f(s: String): Int {{\n  return parse_age(s) + 1\n}}", PARSE));
+    assert_eq!(code, "E3052");
+    assert_eq!(message, "Function 'f' calls 'parse_age', which barks, without handling it");
+    assert_eq!(at, (6, 10));
+    assert_eq!(hint, "Call it inside `sniff { ... } snatch (e) { ... }`, or declare `barks` on 'f'");
+    // snatch 与 lastly 中的调用没有被处理
+    let (code, _, at, _) = failure(&format!(
+        "{}fun f(s: String): Int {{\n  sniff {{ return 1 }} snatch (e) {{ return parse_age(s) }}\n}}",
+        PARSE
+    ));
+    assert_eq!((code, at), ("E3052", (6, 42)));
+    // 方法按 record 查找
+    let (_, message, _, _) = failure(
+        "record Cat {\n  lives: Int\n  fun lose(n: Int): Int barks { return n }\n}\n\
+         fun f(c: Cat): Int {\n  if c.lives > 0 { return c.lose(1) }\n  return 0\n}",
+    );
+    assert_eq!(message, "Function 'f' calls 'Cat.lose', which barks, without handling it");
+}
+
+#[test]
+fn barking_functions_used_as_values_are_reported() {
+    // 赋给函数类型的变量后再调用
+    let (code, message, at, hint) = failure(&format!(
+        "{}fun f(): Int {{\n  let h: fun(String): Int = parse_age\n  return h(\"\")\n}}",
+        PARSE
+    ));
+    assert_eq!(code, "E3052");
+    assert_eq!(message, "Function 'f' uses 'parse_age', which barks, as a value without handling it");
+    assert_eq!(at, (6, 29));
+    assert!(hint.starts_with("Function values do not carry `barks`"), "{}", hint);
+    // 作为参数传给不声明 `barks` 的函数
+    let (code, message, at, _) = failure(&format!(
+        "{}fun apply(g: fun(String): Int): Int {{ return g(\"\") }}\nfun f(): Int {{\n  return apply(parse_age)\n}}",
+        PARSE
+    ));
+    assert_eq!(code, "E3052");
+    assert_eq!(message, "Function 'f' uses 'parse_age', which barks, as a value without handling it");
+    assert_eq!(at, (7, 16));
+    // sniff 中或声明了 `barks` 的函数中可以当作值使用
+    let v = eval_string(&format!(
+        r#"{}fun apply(g: fun(String): Int, s: String): Int {{ return g(s) }}
+fun f(s: String): Int {{
+  sniff {{ return apply(parse_age, s) }} snatch (e) {{ return -1 }}
+}}
+fun g(s: String): Int barks {{ return apply(parse_age, s) }}
+"${{f("")}} ${{g("7")}}"
+"#,
+        PARSE
+    ));
+    assert_eq!(v, "-1 7");
+}
+
+#[test]
+fn shadowing_and_sessions_follow_the_nearest_definition() {
+    // 参数遮蔽了同名的 barks 函数
+    let v = eval_string(&format!(
+        "{}fun f(parse_age: fun(String): Int): Int {{ return parse_age(\"5\") }}\nf(fun(s: String): Int {{ return 5 }})",
+        PARSE
+    ));
+    assert_eq!(v, "5");
+    let mut session = Session::new("<test>");
+    session.eval(PARSE).unwrap();
+    let err = session.eval("fun f(): Int { return parse_age(\"1\") }").unwrap_err();
+    assert_eq!(err.code(), "E3052");
+    assert!(session.eval("fun g(): Int barks { return parse_age(\"1\") }").is_ok());
+}
+
+#[test]
+fn barks_is_not_a_keyword() {
+    assert_eq!(eval_string("let barks = 2\nfun f(barks: Int): Int { return barks * 2 }\nf(barks)"), "4");
+}
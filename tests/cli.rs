@@ -0,0 +1,212 @@
+// tests/cli.rs
+//
+// 通过命令行运行脚本：`--stack-size` 决定执行线程的栈大小，`--float-precision` 固定浮点数的小数位数，
+// `--define` 注入全局变量，`--check-imports` 与 `pawc check` 在执行前检查导入图
+
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+use common::{pawc, script, write_files};
+
+#[test]
+fn deep_recursion_runs_with_default_stack() {
+    let path = script(
+        "cli",
+        "recursion",
+        r#"
+fun depth(n: Int): Int {
+  if n == 0 { return 0 }
+  return 1 + depth(n - 1)
+}
+say depth(200000)
+"#,
+    );
+    let out = pawc(&[], &path);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "200000");
+}
+
+#[test]
+fn stack_size_flag_runs_deep_recursion() {
+    let path = script(
+        "cli",
+        "stack-size",
+        r#"
+fun depth(n: Int): Int {
+  if n == 0 { return 0 }
+  return 1 + depth(n - 1)
+}
+say depth(100000)
+"#,
+    );
+    for size in ["1", "64"] {
+        let out = pawc(&["--stack-size", size], &path);
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "100000");
+    }
+}
+
+#[test]
+fn deeply_nested_values_are_dropped_without_recursion() {
+    // 析构时逐层释放，嵌套深度不受线程栈大小限制
+    let path = script(
+        "cli",
+        "nested",
+        r#"
+let a: Any = 0
+let m: Any = 0
+loop i in 0..100000 {
+  a = [a]
+  m = {"next": m}
+}
+say "built"
+a = 0
+m = 0
+say "dropped"
+"#,
+    );
+    let out = pawc(&[], &path);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "built\ndropped\n");
+}
+
+#[test]
+fn timings_report_each_phase() {
+    let path = script("cli", "timings", "say 1 + 2\n");
+    let out = pawc(&["--timings"], &path);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "3\n");
+    let report = String::from_utf8_lossy(&out.stderr);
+    for phase in ["lex", "parse", "check", "execute", "total", "modules"] {
+        assert!(report.contains(&format!("   {} ", phase)), "missing {}:\n{}", phase, report);
+    }
+}
+
+#[test]
+fn timings_stop_at_the_failing_phase() {
+    let path = script("cli", "timings-fail", "let x: Int = \"a\"\n");
+    let out = pawc(&["--timings"], &path);
+    let report = String::from_utf8_lossy(&out.stderr);
+    assert!(report.contains("   check "), "{}", report);
+    assert!(!report.contains("   execute "), "{}", report);
+    assert!(report.contains("[E3003]"), "{}", report);
+}
+
+#[test]
+fn float_precision_flag_fixes_the_decimal_places() {
+    let path = script("cli", "floats", "say 2.0\nsay 1.0 / 3.0\nsay [0.5, 2.0]\n");
+    let out = pawc(&[], &path);
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "2.0\n0.3333333333333333\n[0.5, 2.0]\n");
+    let out = pawc(&["--float-precision", "2"], &path);
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "2.00\n0.33\n[0.50, 2.00]\n");
+}
+
+#[test]
+fn define_flag_seeds_typed_globals() {
+    let path = script("cli", "define", "say \"${user} ${port + 1} ${verbose}\"\n");
+    let out = pawc(&["-D", "user=mochi", "--define", "port:Int=8080", "--define", "verbose:Bool=true"], &path);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "mochi 8081 true\n");
+    // 值不符合类型、名字不合法时在执行前报错
+    for bad in ["port:Int=80x", "2fast=1", "port"] {
+        let out = pawc(&["--define", bad], &path);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("--define"));
+    }
+    // 脚本按声明的类型检查注入的变量
+    let path = script("cli", "define-typed", "let n: Int = port\n");
+    let out = pawc(&["--define", "port=8080"], &path);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("E3003"));
+}
+
+/// 以 `input` 作为标准输入运行脚本
+fn pawc_with_input(path: &Path, input: &str) -> Output {
+    use std::io::Write;
+    use std::process::Stdio;
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn ask_converts_input_to_the_declared_type() {
+    let path = script(
+        "cli",
+        "ask",
+        "let n: Int <- ask \"n? \"\nlet d: Double <- ask \"d? \"\nlet maybe: Int? <- ask \"\"\nlet ok: Bool <- ask \"\"\nsay n + 1\nsay d * 2.0\nsay maybe\nsay ok\n",
+    );
+    let out = pawc_with_input(&path, " 41 \n1.25\n\ntrue\n");
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "n? d? 42\n2.5\nNopaw\ntrue\n");
+
+    let out = pawc_with_input(&path, "4,1\n");
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("[E6016]") && stderr.contains("as Int"), "{}", stderr);
+}
+
+#[test]
+fn imported_modules_are_checked_before_running() {
+    let path = write_files(
+        "cli",
+        "imports",
+        &[
+            ("main.paw", "say \"started\"\nimport lib.shapes\nsay shapes.area(2)\n"),
+            ("lib/shapes.paw", "import units\nfun area(r: Int): Int { return r * r }\n"),
+            ("lib/units.paw", "let scale: Int = \"big\"\n"),
+        ],
+    )
+    .join("main.paw");
+    // 错误在执行之前报告，并归属于出错的模块文件
+    let out = pawc(&[], &path);
+    assert!(!out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("E3003") && stderr.contains("units.paw"), "{}", stderr);
+
+    // 限制深度后，深层模块的错误仍在导入时才出现
+    let out = pawc(&["--check-imports", "1"], &path);
+    assert!(!out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "started\n");
+    let out = pawc(&["--check-imports", "0"], &path);
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "started\n");
+}
+
+#[test]
+fn check_subcommand_reports_every_file() {
+    let path = write_files(
+        "cli",
+        "check",
+        &[
+            ("main.paw", "import a\nimport b\nimport missing\nimport math\nsay a.x\n"),
+            ("a.paw", "let x: Int = true\n"),
+            ("b.paw", "import a\nlet y: Int = \"y\"\n"),
+        ],
+    )
+    .join("main.paw");
+    let out = pawc(&["check"], &path);
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "🐾 Checked 3 file(s): 3 error(s), 0 warning(s)\n"
+    );
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    for expected in ["a.paw", "b.paw", "E1002", "missing.paw"] {
+        assert!(stderr.contains(expected), "{}\n{}", expected, stderr);
+    }
+
+    let good = script("cli", "check-ok", "let n: Int = 1\nsay n\n");
+    let out = pawc(&["check"], &good);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "🐾 Checked 1 file(s): 0 error(s), 0 warning(s)\n"
+    );
+}
@@ -0,0 +1,97 @@
+// tests/common/mod.rs
+//
+// 各测试文件共用的辅助函数：求值脚本、取出错误，以及在临时目录中写入脚本或项目并运行 pawc。
+// 每个测试文件只用到其中一部分
+
+#![allow(dead_code)]
+
+use pawc::{PawScript, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+pub fn eval(src: &str) -> Value {
+    PawScript::new()
+        .eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e))
+}
+
+pub fn eval_string(src: &str) -> String {
+    eval(src).to_string()
+}
+
+pub fn error_code(src: &str) -> &'static str {
+    PawScript::new()
+        .eval_str(src)
+        .err()
+        .unwrap_or_else(|| panic!("script should fail:\n{}", src))
+        .code()
+}
+
+/// 运行成功的脚本产生的警告码
+pub fn warning_codes(src: &str) -> Vec<&'static str> {
+    let mut paw = PawScript::new();
+    paw.eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e));
+    paw.warnings().iter().map(|w| w.code()).collect()
+}
+
+/// 检查失败时的错误码、说明、位置与提示
+pub fn failure(src: &str) -> (&'static str, String, (usize, usize), String) {
+    let err = PawScript::new()
+        .eval_str(src)
+        .err()
+        .unwrap_or_else(|| panic!("script should fail:\n{}", src));
+    let (_, line, col) = err.location();
+    (err.code(), err.message(), (line, col), err.hint().unwrap_or_default().to_string())
+}
+
+/// 分别用树遍历解释器与字节码虚拟机求值，两者的结果必须相同
+pub fn both_ways(src: &str) -> String {
+    let mut paw = PawScript::new();
+    paw.set_vm(false);
+    let walked = paw.eval_str(src).unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e));
+    let compiled = eval_string(src);
+    assert_eq!(walked.to_string(), compiled, "{}", src);
+    compiled
+}
+
+/// 清空并创建临时目录 `pawc-<feature>-<name>-<pid>`
+pub fn temp_dir(feature: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pawc-{}-{}-{}", feature, name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 在临时目录中写入若干文件（路径可带子目录），返回目录
+pub fn write_files(feature: &str, name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = temp_dir(feature, name);
+    for (file, src) in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, src).unwrap();
+    }
+    dir
+}
+
+/// 把脚本写入临时目录中的 main.paw，返回脚本路径
+pub fn script(feature: &str, name: &str, src: &str) -> PathBuf {
+    write_files(feature, name, &[("main.paw", src)]).join("main.paw")
+}
+
+/// 以脚本所在目录为当前文件运行导入
+pub fn script_in(dir: &Path, main: &str) -> PawScript {
+    PawScript::with_file(dir.join(main).to_str().unwrap())
+}
+
+/// 运行 pawc 命令行：关闭颜色与 Rust 的 backtrace，输出不受环境影响
+pub fn pawc(args: &[&str], path: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .env_remove("RUST_BACKTRACE")
+        .env("NO_COLOR", "1")
+        .args(args)
+        .arg(path)
+        .output()
+        .unwrap()
+}
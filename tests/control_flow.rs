@@ -0,0 +1,685 @@
+// tests/control_flow.rs
+//
+// return / break / continue 在各种嵌套组合下的语义：
+// - return 结束整个函数，无论嵌套在多少层循环、if、sniff 中
+// - break / continue 只作用于最近一层循环，不会越过函数边界
+// - lastly 在 try 块以任何方式离开时都会执行
+
+mod common;
+
+use pawc::Value;
+use common::{error_code, eval, eval_string, failure};
+
+// —— return ——
+
+#[test]
+fn return_exits_loop_forever() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let i: Int = 0
+  loop forever {
+    i = i + 1
+    if i == 3 { return i }
+  }
+  return -1
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(3));
+}
+
+#[test]
+fn return_exits_loop_while() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let i: Int = 0
+  loop i < 10 {
+    if i == 4 { return i * 10 }
+    i = i + 1
+  }
+  return -1
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(40));
+}
+
+#[test]
+fn return_exits_loop_range() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  loop i in 0..10 {
+    if i == 7 { return i }
+  }
+  return -1
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(7));
+}
+
+#[test]
+fn return_exits_loop_array() {
+    let v = eval(
+        r#"
+fun f(xs: Array<String>): String {
+  loop x in xs {
+    if x == "b" { return x }
+  }
+  return "none"
+}
+f(["a", "b", "c"])
+"#,
+    );
+    assert_eq!(v, Value::String("b"));
+}
+
+#[test]
+fn return_exits_all_nested_loops() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  loop i in 0..5 {
+    loop j in 0..5 {
+      loop forever {
+        if i * j == 6 { return i * 10 + j }
+        break
+      }
+    }
+  }
+  return -1
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(23));
+}
+
+#[test]
+fn return_from_else_branch_inside_loop() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  loop i in 0..10 {
+    if i < 5 {
+      continue
+    } else if i == 5 {
+      let x: Int = 0
+    } else {
+      return i
+    }
+  }
+  return -1
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(6));
+}
+
+#[test]
+fn return_inside_sniff_inside_loop() {
+    let v = eval(
+        r#"
+fun f(): String {
+  let log: String = ""
+  loop i in 0..5 {
+    sniff {
+      if i == 2 { return log + "r" }
+      log = log + i
+    } snatch (e) {
+      log = log + "c"
+    } lastly {
+      log = log + "f"
+    }
+  }
+  return "unreachable"
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::String("0f1fr"));
+}
+
+#[test]
+fn return_inside_snatch_inside_loop() {
+    let v = eval(
+        r#"
+fun f(): String {
+  loop i in 0..5 {
+    sniff {
+      bark "boom"
+    } snatch (e) {
+      return "caught " + e
+    } lastly {
+    }
+  }
+  return "unreachable"
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::String("caught boom"));
+}
+
+#[test]
+fn return_value_is_not_double_counted_by_outer_loop() {
+    // 内层函数的 return 只结束内层函数，外层循环继续
+    let v = eval(
+        r#"
+fun inner(n: Int): Int {
+  loop forever {
+    return n * 2
+  }
+  return -1
+}
+fun outer(): Int {
+  let sum: Int = 0
+  loop i in 0..4 {
+    sum = sum + inner(i)
+  }
+  return sum
+}
+outer()
+"#,
+    );
+    assert_eq!(v, Value::Int(12));
+}
+
+#[test]
+fn lambda_return_does_not_exit_enclosing_loop() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let count: Int = 0
+  loop i in 0..5 {
+    let g: fun(Int): Int = fun(x: Int): Int { return x }
+    count = count + g(1)
+  }
+  return count
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(5));
+}
+
+#[test]
+fn return_inside_async_function_loop() {
+    let v = eval(
+        r#"
+async fun f(): Int {
+  loop i in 0..100 {
+    if i == 9 { return i }
+  }
+  return -1
+}
+await f()
+"#,
+    );
+    assert_eq!(v, Value::Int(9));
+}
+
+#[test]
+fn return_without_value_in_loop() {
+    let v = eval(
+        r#"
+fun f(xs: Array<Int>): Int? {
+  loop x in xs {
+    if x > 1 { return x }
+  }
+}
+f([0, 1])
+"#,
+    );
+    assert_eq!(v, Value::Null());
+}
+
+#[test]
+fn returned_bools_are_not_break_or_continue() {
+    // return true / false 是普通的返回值，不会被循环当成 break / continue
+    let v = eval_string(
+        r#"
+fun has(xs: Array<Int>, n: Int): Bool {
+  loop x in xs {
+    if x == n { return true }
+  }
+  return false
+}
+fun first_odd_is_big(xs: Array<Int>): Bool {
+  loop x in xs {
+    if x % 2 == 0 { continue }
+    return x > 10
+  }
+  return false
+}
+let found: Int = 0
+loop i in 0..5 {
+  if has([1, 3], i) { found = found + 1 }
+}
+"${has([1, 2], 2)} ${has([1, 2], 5)} ${first_odd_is_big([2, 3, 11])} ${first_odd_is_big([4, 13])} ${found}"
+"#,
+    );
+    assert_eq!(v, "true false false true 2");
+}
+
+// —— break ——
+
+#[test]
+fn break_exits_only_innermost_loop() {
+    let v = eval_string(
+        r#"
+fun f(): String {
+  let log: String = ""
+  loop i in 0..3 {
+    loop j in 0..3 {
+      if j == 1 { break }
+      log = log + i + j + " "
+    }
+  }
+  return log
+}
+f()
+"#,
+    );
+    assert_eq!(v, "00 10 20 ");
+}
+
+#[test]
+fn break_in_each_loop_kind() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let n: Int = 0
+  loop forever {
+    n = n + 1
+    break
+  }
+  loop n < 100 {
+    n = n + 1
+    if n == 5 { break }
+  }
+  loop i in 0..100 {
+    if i == 3 { break }
+    n = n + 1
+  }
+  loop x in [1, 2, 3] {
+    if x == 2 { break }
+    n = n + 100
+  }
+  return n
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(108));
+}
+
+#[test]
+fn break_inside_sniff_runs_lastly() {
+    let v = eval_string(
+        r#"
+fun f(): String {
+  let log: String = ""
+  loop i in 0..5 {
+    sniff {
+      if i == 1 { break }
+      log = log + "t"
+    } snatch (e) {
+      log = log + "c"
+    } lastly {
+      log = log + "f"
+    }
+  }
+  return log
+}
+f()
+"#,
+    );
+    assert_eq!(v, "tff");
+}
+
+#[test]
+fn break_inside_snatch_exits_loop() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let n: Int = 0
+  loop forever {
+    n = n + 1
+    sniff {
+      bark "stop"
+    } snatch (e) {
+      break
+    } lastly {
+    }
+  }
+  return n
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(1));
+}
+
+#[test]
+fn break_in_nested_if_chain() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let last: Int = 0
+  loop i in 0..10 {
+    if i > 2 {
+      if i % 2 == 0 {
+        break
+      }
+    }
+    last = i
+  }
+  return last
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(3));
+}
+
+// —— continue ——
+
+#[test]
+fn continue_skips_rest_of_body() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let sum: Int = 0
+  loop i in 0..10 {
+    if i % 2 == 1 { continue }
+    sum = sum + i
+  }
+  return sum
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(20));
+}
+
+#[test]
+fn continue_in_while_and_array_loops() {
+    let v = eval(
+        r#"
+fun f(): Int {
+  let i: Int = 0
+  let hits: Int = 0
+  loop i < 6 {
+    i = i + 1
+    if i == 3 { continue }
+    hits = hits + 1
+  }
+  loop x in [1, 2, 3, 4] {
+    if x < 3 { continue }
+    hits = hits + 10
+  }
+  return hits
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(25));
+}
+
+#[test]
+fn continue_affects_only_innermost_loop() {
+    let v = eval_string(
+        r#"
+fun f(): String {
+  let log: String = ""
+  loop i in 0..2 {
+    loop j in 0..3 {
+      if j == 1 { continue }
+      log = log + i + j + " "
+    }
+    log = log + "| "
+  }
+  return log
+}
+f()
+"#,
+    );
+    assert_eq!(v, "00 02 | 10 12 | ");
+}
+
+#[test]
+fn continue_inside_sniff_runs_lastly() {
+    let v = eval_string(
+        r#"
+fun f(): String {
+  let log: String = ""
+  loop i in 0..3 {
+    sniff {
+      if i == 1 { continue }
+      log = log + i
+    } snatch (e) {
+    } lastly {
+      log = log + "f"
+    }
+  }
+  return log
+}
+f()
+"#,
+    );
+    assert_eq!(v, "0ff2f");
+}
+
+// —— lastly：用 bark 观察，不依赖在 sniff 中修改外层变量 ——
+
+#[test]
+fn lastly_runs_when_break_leaves_sniff() {
+    let v = eval(
+        r#"
+fun f(): String {
+  sniff {
+    loop i in 0..5 {
+      sniff {
+        if i == 1 { break }
+      } snatch (e) {
+      } lastly {
+        if i == 1 { bark "lastly after break" }
+      }
+    }
+  } snatch (e) {
+    return e
+  } lastly {
+  }
+  return "lastly skipped"
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::String("lastly after break"));
+}
+
+#[test]
+fn lastly_runs_when_continue_leaves_sniff() {
+    let v = eval(
+        r#"
+fun f(): String {
+  sniff {
+    loop i in 0..5 {
+      sniff {
+        if i == 2 { continue }
+      } snatch (e) {
+      } lastly {
+        if i == 2 { bark "lastly after continue" }
+      }
+    }
+  } snatch (e) {
+    return e
+  } lastly {
+  }
+  return "lastly skipped"
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::String("lastly after continue"));
+}
+
+#[test]
+fn lastly_runs_when_return_leaves_sniff() {
+    let v = eval(
+        r#"
+fun g(): Int {
+  loop forever {
+    sniff {
+      return 1
+    } snatch (e) {
+    } lastly {
+      bark "lastly after return"
+    }
+  }
+  return 0
+}
+fun f(): String {
+  sniff {
+    g()
+  } snatch (e) {
+    return e
+  } lastly {
+  }
+  return "lastly skipped"
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::String("lastly after return"));
+}
+
+#[test]
+fn break_in_sniff_stops_loop() {
+    // break 若被 sniff 吞掉，这里会永远循环
+    let v = eval(
+        r#"
+fun f(): Int {
+  loop forever {
+    sniff {
+      break
+    } snatch (e) {
+    } lastly {
+    }
+  }
+  return 99
+}
+f()
+"#,
+    );
+    assert_eq!(v, Value::Int(99));
+}
+
+// —— 顶层 ——
+
+#[test]
+fn top_level_return_stops_script() {
+    let v = eval(
+        r#"
+let x: Int = 1
+loop i in 0..10 {
+  if i == 2 { return x + i }
+}
+x = 100
+return x
+"#,
+    );
+    assert_eq!(v, Value::Int(3));
+}
+
+#[test]
+fn top_level_break_and_continue_in_loops() {
+    let v = eval(
+        r#"
+let n: Int = 0
+loop i in 0..10 {
+  if i == 1 { continue }
+  if i == 4 { break }
+  n = n + i
+}
+n
+"#,
+    );
+    assert_eq!(v, Value::Int(5));
+}
+
+// —— 静态拒绝 ——
+
+#[test]
+fn break_outside_loop_is_rejected() {
+    assert_eq!(error_code("break"), "E3034");
+    assert_eq!(error_code("if true { continue }"), "E3034");
+    let (code, message, position, hint) = failure("fun f(): Void {\n  say 1\n  continue\n}");
+    assert_eq!((code, position), ("E3034", (3, 3)));
+    assert_eq!(message, "`continue` used outside of a loop");
+    assert_eq!(
+        hint,
+        "`continue` may only appear inside a loop body; functions and async blocks start outside any loop"
+    );
+}
+
+#[test]
+fn loops_inside_functions_and_matches_inside_loops_are_accepted() {
+    let v = eval_string(
+        r#"
+let log: String = ""
+loop i in 0..3 {
+  fun g(): Int {
+    loop forever { break }
+    return 1
+  }
+  match i { 1 => { continue } _ => { } }
+  log = log + i + g()
+}
+log
+"#,
+    );
+    assert_eq!(v, "0121");
+}
+
+#[test]
+fn break_in_function_declared_inside_loop_is_rejected() {
+    let src = r#"
+loop i in 0..3 {
+  fun g(): Void {
+    break
+  }
+}
+"#;
+    assert_eq!(error_code(src), "E3034");
+}
+
+#[test]
+fn continue_in_lambda_inside_loop_is_rejected() {
+    let src = r#"
+loop i in 0..3 {
+  let g: fun(): Void = fun() { continue }
+}
+"#;
+    assert_eq!(error_code(src), "E3034");
+}
+
+#[test]
+fn break_in_async_block_inside_loop_is_rejected() {
+    let src = r#"
+loop forever {
+  let f: Int = async {
+    break
+  }
+}
+"#;
+    assert_eq!(error_code(src), "E3034");
+}
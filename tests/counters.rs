@@ -0,0 +1,76 @@
+// tests/counters.rs
+//
+// 执行计数：语句、VM 指令、函数调用与表达式分配，运行后通过 `PawScript::stats` 查询
+
+use pawc::{PawScript, Stats};
+
+fn stats_of(src: &str, vm: bool) -> Stats {
+    let mut paw = PawScript::new();
+    paw.set_vm(vm);
+    paw.eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e));
+    paw.stats()
+}
+
+#[test]
+fn statements_and_calls_are_counted() {
+    let src = r#"
+fun add(a: Int, b: Int): Int {
+  return a + b
+}
+let x: Int = add(1, 2)
+let y: Int = add(x, add(x, 3))
+y
+"#;
+    let stats = stats_of(src, false);
+    // 顶层 4 条语句，加上三次调用 add 各执行 1 条 return
+    assert_eq!(stats.statements, 7);
+    assert_eq!(stats.calls, 3);
+    assert_eq!(stats.instructions, 0);
+    assert_eq!(stats.allocations, 0);
+}
+
+#[test]
+fn compiled_loops_count_instructions() {
+    let src = "let total: Int = 0\nloop i in 0..100 { total = total + i }\ntotal";
+    let walked = stats_of(src, false);
+    assert_eq!(walked.statements, 103);
+    assert_eq!(walked.instructions, 0);
+
+    let compiled = stats_of(src, true);
+    assert_eq!(compiled.statements, 3);
+    assert!(compiled.instructions > 100, "{:?}", compiled);
+    // 同一段代码每次运行的计数相同，可以在 CI 中直接断言
+    assert_eq!(stats_of(src, true), compiled);
+}
+
+#[test]
+fn allocations_cover_literals_strings_and_closures() {
+    let src = r#"
+record P { x: Int }
+let xs: Array<Int> = [1, 2, 3]
+let m: Map<String, Int> = {"a": 1}
+let p: P = P { x: 1 }
+let a: String = "a"
+let s: String = a + "b"
+let t: String = "${s}!"
+let f: fun(Int): Int = fun(n: Int): Int { return n }
+let n: Int = 1 + 2
+"#;
+    assert_eq!(stats_of(src, false).allocations, 6);
+}
+
+#[test]
+fn counts_accumulate_across_evals_until_reset() {
+    let mut paw = PawScript::new();
+    paw.eval_str("fun twice(n: Int): Int { return n * 2 }").unwrap();
+    paw.eval_str("twice(1)").unwrap();
+    paw.call_function("twice", vec![pawc::Value::Int(2)]).unwrap();
+    assert_eq!(paw.stats().calls, 2);
+    assert_eq!(paw.stats().statements, 4);
+
+    paw.reset_stats();
+    assert_eq!(paw.stats(), Stats::default());
+    paw.eval_str("async fun later(): Int { return twice(3) }\nawait later()").unwrap();
+    assert_eq!(paw.stats().calls, 2);
+}
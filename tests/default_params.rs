@@ -0,0 +1,84 @@
+// tests/default_params.rs
+//
+// 参数默认值：调用时可以省略尾部有默认值的参数，默认值在被调函数的环境中求值
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+const GREET: &str = r#"
+fun greet(name: String, greeting: String = "Hi", mark: String = "!"): String {
+  return "${greeting}, ${name}${mark}"
+}
+"#;
+
+#[test]
+fn trailing_arguments_can_be_omitted() {
+    assert_eq!(eval_string(&format!("{}greet(\"Ann\")", GREET)), "Hi, Ann!");
+    assert_eq!(eval_string(&format!("{}greet(\"Ann\", \"Hello\")", GREET)), "Hello, Ann!");
+    assert_eq!(eval_string(&format!("{}greet(\"Ann\", \"Yo\", \"?\")", GREET)), "Yo, Ann?");
+}
+
+#[test]
+fn defaults_are_evaluated_in_the_callee() {
+    // 默认值可以引用前面的参数，每次调用重新求值
+    let v = eval_string(
+        r#"
+let calls: Int = 0
+fun next(): Int {
+  calls = calls + 1
+  return calls
+}
+fun span(start: Int, end: Int = start + 10, id: Int = next()): String {
+  return "${start}..${end}#${id}"
+}
+"${span(1)} ${span(2, 3)} ${span(5)} ${span(0, 0, 0)}"
+"#,
+    );
+    assert_eq!(v, "1..11#1 2..3#2 5..15#3 0..0#0");
+    // 可选类型的参数可以默认为 nopaw；嵌套函数同样适用
+    let v = eval_string(
+        r#"
+fun outer(): String {
+  fun label(n: Int, scale: Int? = nopaw): String {
+    match scale {
+      nopaw => { return "${n}" }
+      _ => { return "${n}x${scale}" }
+    }
+  }
+  return label(3) + "/" + label(4, 2)
+}
+outer()
+"#,
+    );
+    assert_eq!(v, "3/4x2");
+}
+
+#[test]
+fn arity_and_default_types_are_checked() {
+    assert_eq!(error_code(&format!("{}greet()", GREET)), "E3032");
+    assert_eq!(error_code(&format!("{}greet(\"a\", \"b\", \"c\", \"d\")", GREET)), "E3032");
+    assert_eq!(error_code(&format!("{}greet(\"a\", 1)", GREET)), "E3033");
+    assert_eq!(error_code("fun f(n: Int = \"one\"): Int { return n }"), "E3003");
+    // 默认值不能引用后面的参数
+    assert_eq!(error_code("fun f(a: Int = b, b: Int = 1): Int { return a }"), "E4001");
+}
+
+#[test]
+fn defaults_must_come_last_on_named_functions() {
+    assert_eq!(error_code("fun f(a: Int = 1, b: Int): Int { return a + b }"), "E1001");
+    assert_eq!(error_code("let f = fun(a: Int = 1): Int { return a }"), "E1001");
+    assert_eq!(error_code("protocol P {\n  fun f(a: Int = 1): Int\n}"), "E1001");
+}
+
+#[test]
+fn host_calls_may_omit_defaults() {
+    let mut paw = PawScript::new();
+    paw.eval_str(GREET).unwrap();
+    let v = paw.call_function("greet", vec![Value::from("Bo")]).unwrap();
+    assert_eq!(v.to_string(), "Hi, Bo!");
+    let err = paw.call_function("greet", vec![]).unwrap_err();
+    assert_eq!(err.code(), "E3032");
+    assert!(err.to_string().contains("1 to 3"), "{}", err);
+}
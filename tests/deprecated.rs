@@ -0,0 +1,87 @@
+// tests/deprecated.rs
+//
+// `@deprecated`：使用弃用的函数、变量或类型时给出 W3004 警告，附上说明与定义位置
+
+mod common;
+
+use pawc::PawScript;
+use common::error_code;
+
+/// 运行脚本，返回所有警告的文本
+fn warnings(src: &str) -> Vec<String> {
+    let mut paw = PawScript::new();
+    paw.eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e));
+    paw.warnings()
+        .iter()
+        .map(|w| {
+            assert_eq!(w.code(), "W3004", "{}", w);
+            w.to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn each_call_site_is_reported() {
+    let src = r#"
+@deprecated("use bar instead")
+fun foo(n: Int): Int { return n }
+fun bar(n: Int): Int { return n }
+let a: Int = foo(1)
+let b: Int = foo(2) + bar(3)
+"#;
+    let found = warnings(src);
+    assert_eq!(found.len(), 2, "{:?}", found);
+    assert!(found[0].contains("'foo' is deprecated: use bar instead"), "{}", found[0]);
+    assert!(found[0].contains(":5:"), "{}", found[0]);
+    assert!(found[1].contains(":6:"), "{}", found[1]);
+    // 提示中给出定义位置
+    assert!(found[0].contains("defined at <eval>:3:1"), "{}", found[0]);
+}
+
+#[test]
+fn variables_records_and_enums_can_be_deprecated() {
+    let src = r#"
+@deprecated
+let limit: Int = 10
+@deprecated("use Size")
+record Point { x: Int, y: Int }
+@deprecated("use Shade")
+enum Color { Red, Green }
+let twice: Int = limit * 2
+let p: Point = Point { x: 1, y: 2 }
+let c: Color = Color.Red
+"#;
+    let found = warnings(src);
+    assert_eq!(found.len(), 3, "{:?}", found);
+    assert!(found[0].contains("'limit' is deprecated"), "{}", found[0]);
+    assert!(found[1].contains("'Point' is deprecated: use Size"), "{}", found[1]);
+    assert!(found[2].contains("'Color' is deprecated: use Shade"), "{}", found[2]);
+}
+
+#[test]
+fn shadowing_and_recursion_are_not_reported() {
+    let src = r#"
+@deprecated("gone")
+fun count(n: Int): Int {
+  if n == 0 { return 0 }
+  return count(n - 1)
+}
+fun local(): Int {
+  let count: Int = 3
+  return count
+}
+local()
+"#;
+    assert_eq!(warnings(src), Vec::<String>::new());
+    // 调用前的函数（预注册）同样警告
+    let src = "fun new(): Int { return old() }\n@deprecated\nfun old(): Int { return 1 }\nnew()";
+    assert_eq!(warnings(src).len(), 1);
+}
+
+#[test]
+fn only_declarations_can_be_marked() {
+    assert_eq!(error_code("@deprecated say 1"), "E1001");
+    assert_eq!(error_code("@deprecated(1) fun f(): Void { }"), "E1001");
+    assert_eq!(error_code("@deprecated let (a, b) = (1, 2)"), "E1001");
+}
@@ -0,0 +1,114 @@
+// tests/deterministic.rs
+//
+// `--deterministic`：时钟固定在 `--epoch`，随机数从 `--seed` 开始，禁止导入 `term` 与 `signal`（E6028）；
+// 任务按固定顺序逐个执行；Map 与 Set 按插入顺序遍历，record 的字段按名字输出，同样的脚本每次运行输出相同
+
+mod common;
+
+use pawc::{Host, PawScript, Value};
+use std::fs;
+use std::sync::Arc;
+use common::{pawc, script};
+
+const REPORT: &str = r#"
+import time
+import random
+record Cat { name: String  lives: Int  age: Int }
+let ages: Map<String, Int> = {"zeta": 1, "alpha": 2, "mid": 3}
+ages["beta"] = 4
+ages = ages.remove("alpha")
+let ids: Set<Int> = Set {30, 10, 20}
+say ages
+say ids.add(5).to_array()
+say Cat { name: "Mochi", lives: 9, age: 3 }
+say time.now_millis()
+say random.int(1, 1000000)
+"#;
+
+#[test]
+fn runs_are_reproducible() {
+    let path = script("deterministic", "repeat", REPORT);
+    let flags = ["--deterministic", "--epoch", "1700000000000", "--seed", "7"];
+    let first = pawc(&flags, &path);
+    assert!(first.status.success(), "{}", String::from_utf8_lossy(&first.stderr));
+    let stdout = String::from_utf8_lossy(&first.stdout).to_string();
+    assert!(
+        stdout.starts_with("{zeta: 1, mid: 3, beta: 4}\n[30, 10, 20, 5]\n{age: 3, lives: 9, name: Mochi}\n1700000000000\n"),
+        "{}",
+        stdout
+    );
+    for _ in 0..3 {
+        assert_eq!(String::from_utf8_lossy(&pawc(&flags, &path).stdout), stdout);
+    }
+    // 换一个种子得到另一串随机数，时钟默认为 0
+    let other = pawc(&["--deterministic", "--seed", "8"], &path);
+    let other = String::from_utf8_lossy(&other.stdout).to_string();
+    assert!(other.contains("\n0\n"), "{}", other);
+    assert_ne!(other.lines().last(), stdout.lines().last());
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn concurrent_tasks_run_in_a_fixed_order() {
+    let path = script(
+        "deterministic",
+        "tasks",
+        r#"
+async fun w(n: Int): Int {
+  sleep(0)
+  say n
+  return n
+}
+let fs: Array<Future<Int>> = []
+loop i in 1..=6 { fs.push(tail w(i)) }
+say "started"
+loop f in fs { await f }
+task_group {
+  spawn w(7)
+  spawn w(8)
+  spawn w(9)
+}
+"#,
+    );
+    for _ in 0..8 {
+        let out = pawc(&["--deterministic"], &path);
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "started\n1\n2\n3\n4\n5\n6\n7\n8\n9\n");
+    }
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn environment_dependent_modules_are_refused() {
+    for module in ["term", "signal"] {
+        let path = script("deterministic", module, &format!("import {}\nsay 1\n", module));
+        let out = pawc(&["--deterministic"], &path);
+        assert!(!out.status.success());
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("[E6028]"), "{}", stderr);
+        assert!(stderr.contains(&format!("Importing '{}' is not allowed in deterministic mode", module)), "{}", stderr);
+        // 不开启时照常导入
+        assert!(pawc(&[], &path).status.success());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
+
+#[test]
+fn epoch_and_seed_need_deterministic_mode() {
+    let path = script("deterministic", "flags", "say 1\n");
+    let out = pawc(&["--seed", "3"], &path);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--deterministic"));
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn embedders_use_the_host() {
+    let host = Arc::new(Host::new());
+    host.set_deterministic(1_000, 1);
+    let mut paw = PawScript::new();
+    paw.set_host(host);
+    let v = paw.eval_str("import time\ntime.now_millis()").unwrap();
+    assert_eq!(v, Value::Long(1_000));
+    assert_eq!(paw.eval_str("import term").unwrap_err().code(), "E6028");
+}
@@ -0,0 +1,87 @@
+// tests/do_while.rs
+//
+// 后置条件循环 `loop { ... } while cond`：循环体至少执行一次，continue 进入条件检查
+
+mod common;
+
+use common::{both_ways, error_code, eval_string};
+
+#[test]
+fn the_body_runs_before_the_first_test() {
+    assert_eq!(both_ways("let n: Int = 0\nloop { n = n + 1 } while n < 3\nn"), "3");
+    // 条件一开始就不成立时也执行一次
+    assert_eq!(both_ways("let n: Int = 10\nloop { n = n + 1 } while n < 3\nn"), "11");
+    let v = both_ways(
+        r#"
+let digits: String = ""
+let n: Int = 0
+loop {
+  digits = "${n % 10}" + digits
+  n = n / 10
+} while n > 0
+digits
+"#,
+    );
+    assert_eq!(v, "0");
+}
+
+#[test]
+fn continue_tests_the_condition_and_break_leaves() {
+    let v = both_ways(
+        r#"
+let out: String = ""
+let i: Int = 0
+loop {
+  i = i + 1
+  if i % 2 == 0 { continue }
+  if i > 7 { break }
+  out = out + "${i} "
+} while i < 10
+"${out}${i}"
+"#,
+    );
+    assert_eq!(v, "1 3 5 7 9");
+    // 从内层循环 continue 外层的后置条件循环
+    let v = both_ways(
+        r#"
+let rounds: Int = 0
+let hits: Int = 0
+loop outer: {
+  rounds = rounds + 1
+  loop j in 0..5 {
+    if j == rounds { continue outer }
+    hits = hits + 1
+  }
+} while rounds < 3
+"${rounds} ${hits}"
+"#,
+    );
+    assert_eq!(v, "3 6");
+}
+
+#[test]
+fn works_inside_functions_with_return() {
+    let v = eval_string(
+        r#"
+fun first_square_over(limit: Int): Int {
+  let n: Int = 0
+  loop {
+    n = n + 1
+    if n * n > limit { return n }
+  } while true
+  return -1
+}
+first_square_over(50)
+"#,
+    );
+    assert_eq!(v, "8");
+}
+
+#[test]
+fn conditions_are_checked() {
+    assert_eq!(error_code("loop { } while 1"), "E3007");
+    // 循环体中的变量在条件中不可见
+    assert_eq!(error_code("loop { let done: Bool = true } while !done"), "E4001");
+    assert_eq!(error_code("let n: Int = 0\nloop { n = n + 1 }"), "E1001");
+    assert_eq!(error_code("let n: Int = 0\nloop { n = n + 1 }\nwhile n < 3"), "E1001");
+}
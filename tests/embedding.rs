@@ -0,0 +1,44 @@
+// tests/embedding.rs
+//
+// 把解释器嵌入 Rust 程序：`define` 注入宿主值（按值推断静态类型），`eval_str` 返回顶层 return、
+// 结尾表达式或 nopaw，`call_function` 调用脚本函数并检查参数
+
+use pawc::{PawScript, Value};
+
+#[test]
+fn host_values_are_typed_by_their_value() {
+    let mut paw = PawScript::new();
+    paw.define("limit", Value::Int(3));
+    paw.define("names", Value::Array(vec![Value::String("a"), Value::String("b")]));
+    paw.eval_str("fun double(x: Int): Int { return x * 2 }").unwrap();
+    assert_eq!(paw.eval_str("double(limit) + 1").unwrap(), Value::Int(7));
+    assert_eq!(paw.eval_str("names.join(\"-\")").unwrap(), Value::String("a-b"));
+    assert_eq!(paw.eval_str("let s: String = limit").unwrap_err().code(), "E3003");
+}
+
+#[test]
+fn eval_str_returns_the_script_result() {
+    let mut paw = PawScript::new();
+    assert_eq!(paw.eval_str("return 5\nsay \"not reached\"").unwrap(), Value::Int(5));
+    assert_eq!(paw.eval_str("let x: Int = 1\nx + 1").unwrap(), Value::Int(2));
+    assert_eq!(paw.eval_str("let y: Int = 1").unwrap(), Value::Null());
+    assert_eq!(paw.eval_str("async fun f(): Int { return 4 }\nf()").unwrap(), Value::Int(4));
+}
+
+#[test]
+fn script_functions_can_be_called() {
+    let mut paw = PawScript::new();
+    paw.eval_str(r#"fun greet(name: String, times: Int): String { return "${name}${times}" }"#).unwrap();
+    assert_eq!(
+        paw.call_function("greet", vec![Value::String("paw"), Value::Int(2)]).unwrap(),
+        Value::String("paw2")
+    );
+    assert_eq!(paw.call_function("missing", vec![]).unwrap_err().code(), "E4001");
+    let err = paw.call_function("greet", vec![Value::Int(1), Value::Int(2)]).unwrap_err();
+    assert_eq!(err.code(), "E3033");
+    assert_eq!(err.message(), "Argument 'name' of 'greet' has type Int, expected String");
+    // 数值实参按参数类型转换
+    paw.eval_str("fun half(x: Double): Double { return x / 2.0 }").unwrap();
+    assert_eq!(paw.call_function("half", vec![Value::Int(3)]).unwrap(), Value::Double(1.5));
+    assert_eq!(paw.call_function("greet", vec![Value::Int(1)]).unwrap_err().code(), "E3032");
+}
@@ -0,0 +1,126 @@
+// tests/enums.rs
+//
+// `enum Name { A(T), B }`：变体构造、相等比较、`is` 测试与 match 解构
+
+mod common;
+
+use common::{error_code, eval_string, warning_codes};
+
+const SHAPES: &str = r#"
+enum Shape {
+  Circle(Double)
+  Rect(Double, Double)
+  Empty
+}
+fun area(s: Shape): Double {
+  match s {
+    Shape.Circle(r) => { return 3.0 * r * r }
+    Shape.Rect(w, h) => { return w * h }
+    Shape.Empty => { return 0.0 }
+  }
+}
+"#;
+
+#[test]
+fn variants_are_constructed_and_matched() {
+    let v = eval_string(&format!(
+        "{}\nlet shapes: Array<Shape> = [Shape.Circle(1.0), Shape.Rect(2.0, 3.0), Shape.Empty]\n\
+         let total: Double = 0.0\nloop s in shapes {{ total = total + area(s) }}\ntotal",
+        SHAPES
+    ));
+    assert_eq!(v, "9.0");
+}
+
+#[test]
+fn enum_values_print_compare_and_test() {
+    let v = eval_string(&format!(
+        r#"{}
+let c: Shape = Shape.Rect(1.5, 2.0)
+let make: fun(Double): Shape = Shape.Circle
+let a: Any = make(2.0)
+"${{c}} ${{Shape.Empty}} ${{c == Shape.Rect(1.5, 2.0)}} ${{c == Shape.Empty}} ${{a is Shape}} ${{a is Int}}"
+"#,
+        SHAPES
+    ));
+    assert_eq!(v, "Shape.Rect(1.5, 2.0) Shape.Empty true false true false");
+    let v = eval_string(&format!(
+        "{}\nlet seen: Set<Shape> = Set {{Shape.Empty, Shape.Empty, Shape.Circle(1.0)}}\n\
+         \"${{seen.length()}} ${{inspect(Shape.Circle(1.0))}}\"",
+        SHAPES
+    ));
+    assert_eq!(v, "2 Shape.Circle(\n  Double 1.0,\n)");
+}
+
+#[test]
+fn patterns_nest_and_ignore_payloads() {
+    let v = eval_string(
+        r#"
+enum Token { Num(Int), Word(String), Pair(Token, Token), End }
+fun show(t: Token): String {
+  match t {
+    Token.Num(0) => { return "zero" }
+    Token.Num(n) => { return "num ${n}" }
+    Token.Pair(Token.Num(a), Token.Num(b)) => { return "sum ${a + b}" }
+    Token.Pair => { return "pair" }
+    Token.Word(w) => { return w }
+    Token.End => { return "end" }
+  }
+}
+"${show(Token.Num(0))} ${show(Token.Num(4))} ${show(Token.Pair(Token.Num(1), Token.Num(2)))} ${show(Token.Pair(Token.End, Token.End))} ${show(Token.Word("hi"))} ${show(Token.End)}"
+"#,
+    );
+    assert_eq!(v, "zero num 4 sum 3 pair hi end");
+}
+
+#[test]
+fn exhaustiveness_counts_variants() {
+    let src = "enum Light { Red, Amber, Green }\nlet l: Light = Light.Red\n\
+               match l { Light.Red => { say 1 } Light.Green => { say 2 } }";
+    assert_eq!(warning_codes(src), vec!["W3002"]);
+    let src = "enum Light { Red, Amber, Green }\nlet l: Light = Light.Red\n\
+               match l { Light.Red, Light.Amber => { say 1 } Light.Green => { say 2 } }";
+    assert!(warning_codes(src).is_empty());
+}
+
+#[test]
+fn rejects_bad_variants_and_arguments() {
+    assert_eq!(error_code(&format!("{}\nShape.Square(1.0)", SHAPES)), "E3046");
+    assert_eq!(error_code(&format!("{}\nShape.Circle(1.0, 2.0)", SHAPES)), "E3032");
+    assert_eq!(error_code(&format!("{}\nShape.Circle(\"big\")", SHAPES)), "E3033");
+    assert_eq!(error_code(&format!("{}\nlet n: Int = Shape.Empty", SHAPES)), "E3003");
+    assert_eq!(
+        error_code(&format!("{}\nlet s: Shape = Shape.Empty\nmatch s {{ Shape.Rect(w) => {{ }} }}", SHAPES)),
+        "E3045"
+    );
+    assert_eq!(
+        error_code(&format!("{}\nlet s: Shape = Shape.Empty\nmatch s {{ Shape.Cube => {{ }} }}", SHAPES)),
+        "E3046"
+    );
+    assert_eq!(error_code("enum E { A, A }"), "E2005");
+    assert_eq!(error_code("enum E { A(Nope) }"), "E3028");
+}
+
+#[test]
+fn enum_types_are_told_apart_from_records_at_runtime() {
+    let v = eval_string(&format!(
+        r#"{}
+record Point {{ x: Int, y: Int }}
+let e: Any = Shape.Empty
+let p: Any = Point {{ x: 1, y: 2 }}
+let c: Any = Shape.Circle(1.0)
+let items: Array<Any> = [e, p, c]
+let out: String = ""
+loop item in items {{
+  match item {{
+    s: Shape => {{ out = out + "shape ${{area(s)}} " }}
+    p: Point => {{ out = out + "point ${{p.x}} " }}
+  }}
+}}
+let a: Any = items[0]
+let maybe: Shape? = a as Shape
+"${{out}}${{a is Shape}} ${{a is Point}} ${{items[1] is Shape}} ${{maybe}}"
+"#,
+        SHAPES
+    ));
+    assert_eq!(v, "shape 0.0 point 1 shape 3.0 true false false Shape.Empty");
+}
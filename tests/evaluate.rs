@@ -0,0 +1,93 @@
+// tests/evaluate.rs
+//
+// 表达式求值接口：在已有环境中求值单个表达式，供调试器监视表达式与编辑器悬停使用；
+// 只读模式下求值不会改变脚本状态
+
+use pawc::{PawScript, Value};
+
+fn session() -> PawScript {
+    let mut paw = PawScript::new();
+    paw.eval_str(
+        r#"
+let count: Int = 2
+let names: Array<String> = ["ann", "bo"]
+record Point { x: Int, y: Int }
+let origin: Point = Point { x: 3, y: 4 }
+enum Shape { Dot, Circle(Double) }
+fun bump(): Int {
+  count = count + 1
+  return count
+}
+import string
+import math
+import fs
+let buf: StringBuilder = string.builder()
+"#,
+    )
+    .unwrap();
+    paw
+}
+
+fn read_only_code(paw: &mut PawScript, expr: &str) -> &'static str {
+    paw.evaluate(expr, true)
+        .err()
+        .unwrap_or_else(|| panic!("evaluation should fail: {}", expr))
+        .code()
+}
+
+#[test]
+fn expressions_see_the_current_variables() {
+    let mut paw = session();
+    assert_eq!(paw.evaluate("count * 10 + names.length()", true).unwrap(), Value::Int(22));
+    assert_eq!(paw.evaluate("origin.x + origin.y", true).unwrap(), Value::Int(7));
+    assert_eq!(paw.evaluate("\"${names[1]}!\"  # 注释", true).unwrap().to_string(), "bo!");
+    // 没有类型检查：类型错误在运行时报告
+    assert!(paw.evaluate("count + names", true).is_err());
+    assert_eq!(paw.evaluate("missing", true).unwrap_err().code(), "E4001");
+}
+
+#[test]
+fn only_a_single_expression_is_accepted() {
+    let mut paw = session();
+    assert_eq!(paw.evaluate("count count", false).unwrap_err().code(), "E1001");
+    assert_eq!(paw.evaluate("let x: Int = 1", false).unwrap_err().code(), "E1001");
+    assert_eq!(paw.evaluate("", false).unwrap_err().code(), "E1001");
+}
+
+#[test]
+fn read_only_evaluation_leaves_state_alone() {
+    let mut paw = session();
+    assert_eq!(read_only_code(&mut paw, "bump()"), "E6023");
+    assert_eq!(read_only_code(&mut paw, "buf.append(\"x\")"), "E6023");
+    assert_eq!(read_only_code(&mut paw, "await async { 1 }"), "E6023");
+    assert_eq!(paw.get("count"), Some(Value::Int(2)));
+    assert_eq!(paw.evaluate("buf.length()", true).unwrap(), Value::Int(0));
+    // 内置函数与方法可以使用
+    assert_eq!(paw.evaluate("names[0].to_uppercase()", true).unwrap().to_string(), "ANN");
+}
+
+#[test]
+fn read_only_evaluation_rejects_natives_with_side_effects() {
+    let mut paw = session();
+    let path = std::env::temp_dir().join(format!("pawc-evaluate-{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let write = format!("fs.write_text({:?}, \"x\")", path.to_string_lossy());
+    assert_eq!(read_only_code(&mut paw, &write), "E6023");
+    assert!(!path.exists());
+    assert_eq!(read_only_code(&mut paw, "seed_random(7)"), "E6023");
+    assert_eq!(read_only_code(&mut paw, "mock_ask([\"yes\"])"), "E6023");
+    // 宿主注册的原生函数不知道有没有副作用，同样拒绝；纯模块中的原生函数可以调用
+    paw.register_native("ping", "fun(): Int", |_| Ok(Value::Int(1))).unwrap();
+    assert_eq!(read_only_code(&mut paw, "ping()"), "E6023");
+    assert_eq!(paw.evaluate("ping()", false).unwrap(), Value::Int(1));
+    assert_eq!(paw.evaluate("math.sqrt(16.0)", true).unwrap(), Value::Double(4.0));
+    assert_eq!(paw.evaluate("Shape.Circle(2.0)", true).unwrap().to_string(), "Shape.Circle(2.0)");
+}
+
+#[test]
+fn writable_evaluation_can_call_functions() {
+    let mut paw = session();
+    assert_eq!(paw.evaluate("bump() + bump()", false).unwrap(), Value::Int(7));
+    assert_eq!(paw.get("count"), Some(Value::Int(4)));
+    assert_eq!(paw.eval_str("count").unwrap(), Value::Int(4));
+}
@@ -0,0 +1,101 @@
+// tests/exceptions.rs
+//
+// bark 出的 record 错误与多个 snatch 子句：按顺序匹配错误类型，不带类型的子句捕获其余错误
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval};
+
+const ERRORS: &str = r#"
+record NotFound { message: String, path: String }
+record Timeout { seconds: Int }
+
+fun open(path: String): String {
+  if path == "" { bark Timeout { seconds: 3 } }
+  if path == "missing" { bark NotFound { message: "no such file", path: path } }
+  if path == "bad" { bark "plain failure" }
+  return "opened " + path
+}
+
+fun attempt(path: String): String {
+  sniff {
+    return open(path)
+  } snatch (e: NotFound) {
+    return "not found: " + e.path + " (" + e.message + ")"
+  } snatch (e: Timeout) {
+    return "timeout after ${e.seconds}s"
+  } snatch (e) {
+    return "other: " + e
+  }
+}
+"#;
+
+#[test]
+fn clauses_match_by_error_type() {
+    let v = eval(&format!(
+        "{}\nattempt(\"a\") + \"|\" + attempt(\"missing\") + \"|\" + attempt(\"\") + \"|\" + attempt(\"bad\")",
+        ERRORS
+    ));
+    assert_eq!(
+        v.to_string(),
+        "opened a|not found: missing (no such file)|timeout after 3s|other: plain failure"
+    );
+}
+
+#[test]
+fn untyped_clause_binds_message() {
+    let v = eval(&format!(
+        "{}\nlet got: String = \"\"\nsniff {{ open(\"missing\") }} snatch (e) {{ got = e }}\ngot",
+        ERRORS
+    ));
+    assert_eq!(v, Value::String("no such file"));
+}
+
+#[test]
+fn unmatched_error_propagates_after_lastly() {
+    let mut paw = PawScript::new();
+    paw.eval_str(ERRORS).unwrap();
+    let err = paw
+        .eval_str(
+            r#"
+let cleaned: Bool = false
+sniff {
+  open("")
+} snatch (e: NotFound) {
+  say "unreachable"
+} lastly {
+  cleaned = true
+}
+"#,
+        )
+        .unwrap_err();
+    assert_eq!(err.code(), "E6001");
+    assert!(err.to_string().contains("Timeout Error"), "{}", err);
+    assert_eq!(paw.get("cleaned"), Some(Value::Bool(true)));
+}
+
+#[test]
+fn typed_clause_must_name_a_record() {
+    let code = error_code("sniff { bark \"x\" } snatch (e: String) { }");
+    assert_eq!(code, "E3035");
+    let code = error_code("sniff { bark \"x\" } snatch (e: Missing) { }");
+    assert_eq!(code, "E3028");
+}
+
+#[test]
+fn clauses_after_catch_all_are_unreachable() {
+    let code = error_code(
+        "record Oops { code: Int }\nsniff { } snatch (e) { } snatch (e: Oops) { }",
+    );
+    assert_eq!(code, "E3036");
+    let code = error_code(
+        "record Oops { code: Int }\nsniff { } snatch (e: Oops) { } snatch (e: Oops) { }",
+    );
+    assert_eq!(code, "E3036");
+}
+
+#[test]
+fn only_strings_and_records_can_be_barked() {
+    assert_eq!(error_code("bark 42"), "E3001");
+}
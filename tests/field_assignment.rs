@@ -0,0 +1,49 @@
+// tests/field_assignment.rs
+//
+// 字段赋值 `p.x = v`：按 record 声明检查字段与类型，可以经过数组下标或嵌套字段；
+// record 按值复制，修改副本不影响原值
+
+mod common;
+
+use common::{both_ways, error_code, eval_string};
+
+#[test]
+fn fields_are_updated() {
+    assert_eq!(both_ways("record P { x: Int }\nlet p = P { x: 1 }\np.x = p.x + 4\np.x"), "5");
+    let v = eval_string(
+        r#"
+record P { x: Int  y: Int? }
+record L { p: P }
+let p = P { x: 1, y: nopaw }
+p.y = 3
+let l = L { p: p }
+l.p.x = 11
+"${p.y} ${l.p.x}"
+"#,
+    );
+    assert_eq!(v, "3 11");
+}
+
+#[test]
+fn records_are_copied() {
+    let v = eval_string(
+        r#"
+record P { x: Int }
+let p = P { x: 1 }
+let q = p
+q.x = 9
+let ps: Array<P> = [p]
+ps[0].x = 7
+"${p.x} ${q.x} ${ps[0].x}"
+"#,
+    );
+    assert_eq!(v, "1 9 7");
+}
+
+#[test]
+fn bad_assignments_are_reported() {
+    let record = "record P { x: Int }\nlet p = P { x: 1 }\n";
+    assert_eq!(error_code(&format!("{}p.z = 1", record)), "E3015");
+    assert_eq!(error_code(&format!("{}p.x = \"s\"", record)), "E3003");
+    assert_eq!(error_code("let n: Int = 1\nn.x = 2"), "E3016");
+}
@@ -0,0 +1,70 @@
+// tests/format_specs.rs
+//
+// `${value:spec}` 与 `format("{:spec}", value)` 共用的格式说明：
+// `[[fill]align][+][0][width][.precision]`
+
+mod common;
+
+use common::{error_code, eval_string};
+
+#[test]
+fn numbers_are_padded_signed_and_rounded() {
+    assert_eq!(eval_string(r#""${3.14159:08.3}""#), "0003.142");
+    assert_eq!(eval_string(r#""${-2.5:08.2}""#), "-0002.50");
+    assert_eq!(eval_string(r#""${1.0 / 3.0:.4}""#), "0.3333");
+    assert_eq!(eval_string(r#""${42L:+}|${-7:+05}""#), "+42|-0007");
+    assert_eq!(eval_string("let n: Int = 42\n\"[${n:5}] [${n:<5}] [${n:^6}]\""), "[   42] [42   ] [  42  ]");
+    assert_eq!(eval_string(r#""${9000000000L:015}""#), "000009000000000");
+}
+
+#[test]
+fn strings_are_aligned_and_truncated() {
+    assert_eq!(eval_string(r#""[${"paw":5}] [${"paw":*^7}] [${"paw":->6}]""#), "[paw  ] [**paw**] [---paw]");
+    assert_eq!(eval_string(r#""${"abcdef":.3}|${"abcdef":>5.2}|""#), "abc|   ab|");
+    assert_eq!(eval_string(r#""${true:>6}""#), "  true");
+}
+
+#[test]
+fn colons_inside_the_expression_are_not_specs() {
+    let v = eval_string(
+        r#"
+record P { x: Int }
+let m: Map<String, Int> = {"a": 1}
+let c: Char = ':'
+"${P { x: 3 }.x:03} ${m["a"]} ${c} ${"a:b"} ${[1, 2].length():>2}"
+"#,
+    );
+    assert_eq!(v, "003 1 : a:b  2");
+}
+
+#[test]
+fn format_shares_the_specs() {
+    assert_eq!(eval_string(r#"format("{:>6}|{0:<4}|{1:.2}", 12, 2.0 / 3.0)"#), "    12|12  |0.67");
+    assert_eq!(eval_string(r#"format("{:+08.1}", -1.25)"#), "-00001.2");
+}
+
+#[test]
+fn specs_in_loops_match_the_tree_walker() {
+    let v = eval_string(
+        r#"
+let out: String = ""
+loop i in 0..3 {
+  out = out + "${i:03}."
+}
+out
+"#,
+    );
+    assert_eq!(v, "000.001.002.");
+}
+
+#[test]
+fn mismatched_specs_are_rejected() {
+    assert_eq!(error_code(r#""${"x":+}""#), "E3047");
+    assert_eq!(error_code(r#""${1:.2}""#), "E3047");
+    assert_eq!(error_code(r#"format("{:08}", "x")"#), "E3047");
+    assert_eq!(error_code(r#""${1:08q}""#), "E1001");
+    assert_eq!(error_code(r#""${1:99999}""#), "E1001");
+    assert_eq!(error_code(r#"format("{:5.}", 1.0)"#), "E3033");
+    assert_eq!(error_code("let a: Any = \"x\"\n\"${a:+}\""), "E6013");
+    assert_eq!(error_code("let t: String = \"{:.1}\"\nformat(t, 3)"), "E6013");
+}
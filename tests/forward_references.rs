@@ -0,0 +1,46 @@
+// tests/forward_references.rs
+//
+// 同一文件或代码块中，record 与函数可以在声明之前使用：签名与字段中引用之后的 record，
+// 函数互相递归，调用写在声明之前的函数
+
+mod common;
+
+use common::{both_ways, error_code, eval_string};
+
+#[test]
+fn signatures_use_later_records() {
+    let v = eval_string(
+        r#"
+fun make(): Point { return Point { x: 1, owner: nopaw } }
+fun x_of(p: Point): Int { return p.x }
+record Point { x: Int  owner: Owner? }
+record Owner { p: Point }
+x_of(make())
+"#,
+    );
+    assert_eq!(v, "1");
+}
+
+#[test]
+fn functions_are_called_before_their_declaration() {
+    assert_eq!(both_ways("let n: Int = seven()\nfun seven(): Int { return 7 }\nn"), "7");
+    let v = eval_string(
+        r#"
+fun is_even(n: Int): Bool { if n == 0 { return true } return is_odd(n - 1) }
+fun is_odd(n: Int): Bool { if n == 0 { return false } return is_even(n - 1) }
+fun outer(): Int {
+  let r: Int = inner() + 1
+  fun inner(): Int { return 41 }
+  return r
+}
+"${is_even(10)} ${is_odd(7)} ${outer()}"
+"#,
+    );
+    assert_eq!(v, "true true 42");
+}
+
+#[test]
+fn unknown_names_are_still_reported() {
+    assert_eq!(error_code("fun f(): Nope { return 1 }"), "E3028");
+    assert_eq!(error_code("say missing()"), "E4001");
+}
@@ -0,0 +1,77 @@
+// tests/fs.rs
+//
+// 标准库模块 `fs`：读写文本文件、列出目录；IO 错误可以被 snatch 捕获，沙箱模式下禁止导入
+
+mod common;
+
+use pawc::{PawScript, Session};
+use std::path::PathBuf;
+use common::eval;
+
+/// 为每个测试准备一个空的临时目录
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pawc-fs-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn write_then_read_and_list() {
+    let dir = temp_dir("roundtrip");
+    let src = format!(
+        r#"
+import fs
+let dir: String = "{}"
+fs.write_text(dir + "/b.txt", "bee")
+fs.write_text(dir + "/a.txt", "ant")
+let names: Array<String> = fs.list_dir(dir)
+"${{fs.read_text(dir + "/a.txt")}} ${{names}} ${{fs.exists(dir + "/b.txt")}} ${{fs.exists(dir + "/c.txt")}}"
+"#,
+        dir.display()
+    );
+    assert_eq!(eval(&src).to_string(), "ant [a.txt, b.txt] true false");
+    assert_eq!(std::fs::read_to_string(dir.join("b.txt")).unwrap(), "bee");
+}
+
+#[test]
+fn io_errors_can_be_caught() {
+    let dir = temp_dir("errors");
+    let src = format!(
+        r#"
+import fs
+let out: String = "none"
+sniff {{
+  out = fs.read_text("{}/missing.txt")
+}} snatch (e) {{
+  out = "caught"
+}}
+out
+"#,
+        dir.display()
+    );
+    assert_eq!(eval(&src).to_string(), "caught");
+    let err = PawScript::new()
+        .eval_str(&format!("import fs\nfs.list_dir(\"{}/missing\")", dir.display()))
+        .unwrap_err();
+    assert_eq!(err.code(), "E6012");
+}
+
+#[test]
+fn signatures_are_type_checked() {
+    let err = PawScript::new()
+        .eval_str("import fs\nlet n: Int = fs.exists(\"x\")")
+        .unwrap_err();
+    assert_eq!(err.code(), "E3003");
+    let err = PawScript::new().eval_str("import fs\nfs.read_text(1)").unwrap_err();
+    assert_eq!(err.code(), "E3033");
+}
+
+#[test]
+fn sandbox_rejects_fs() {
+    let mut session = Session::new("main.paw");
+    session.set_sandbox(true);
+    assert_eq!(session.eval("import fs").unwrap_err().code(), "E6015");
+    // 不访问宿主的标准库模块仍然可用
+    assert!(session.eval("import math").is_ok());
+}
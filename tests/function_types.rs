@@ -0,0 +1,50 @@
+// tests/function_types.rs
+//
+// 函数类型 `fun(Int): Int`：函数可以作为参数与返回值按签名检查；
+// 调用函数类型的值时检查实参个数与类型，返回值带着声明的类型；Any 的值只在运行时检查
+
+mod common;
+
+use common::{error_code, eval_string, failure};
+
+#[test]
+fn functions_are_passed_and_returned() {
+    let v = eval_string(
+        r#"
+fun twice(f: fun(Int): Int): fun(Int): Int { return fun(x: Int): Int { return f(f(x)) } }
+fun inc(x: Int): Int { return x + 1 }
+fun compose(f: fun(Int): Int, g: fun(Int): String): fun(Int): String {
+  return fun(x: Int): String { return g(f(x)) }
+}
+let g: fun(Int): Int = twice(inc)
+let c = compose(inc, fun(n: Int): String { return "n=${n}" })
+let h: Any = inc
+"${g(1)} ${c(2)} ${h(4)}"
+"#,
+    );
+    assert_eq!(v, "3 n=3 5");
+    assert_eq!(eval_string("let out: String = \"\"\nlet log: fun(String) = fun(s: String) { out = s }\nlog(\"hi\")\nout"), "hi");
+}
+
+#[test]
+fn calls_through_function_types_are_checked() {
+    let (code, message, _, hint) = failure("fun run(f: fun(Int): Int): Int { return f(1, 2) }");
+    assert_eq!(code, "E3032");
+    assert_eq!(message, "Function 'f' expects 1 argument(s), found 2");
+    assert_eq!(hint, "Signature: fun(Int): Int");
+    assert_eq!(error_code("fun run(f: fun(Int): Int): Int { return f(\"a\") }"), "E3033");
+    assert_eq!(error_code("let f: fun(Int): Int = fun(x: Int): Int { return x }\nlet s: String = f(1)"), "E3003");
+    assert_eq!(error_code("let log: fun(String) = fun(s: String) { }\nlet x: Int = log(\"a\")"), "E3043");
+}
+
+#[test]
+fn function_arguments_must_match_the_signature() {
+    let apply = "fun apply(f: fun(Int): Int): Int { return f(1) }\n";
+    let (code, message, position, _) = failure(&format!("{}fun s(x: String): Int {{ return 1 }}\napply(s)", apply));
+    assert_eq!((code, position), ("E3033", (3, 7)));
+    assert_eq!(message, "Argument 'f' of 'apply' has type fun(String): Int, expected fun(Int): Int");
+    let (_, message, _, _) = failure(&format!("{}apply(fun(x: Int): String {{ return \"s\" }})", apply));
+    assert_eq!(message, "Argument 'f' of 'apply' has type fun(Int): String, expected fun(Int): Int");
+    // Any 的值绕过静态检查，调用不可调用的值在运行时报错
+    assert_eq!(error_code("let h: Any = 1\nh(4)"), "E4002");
+}
@@ -0,0 +1,74 @@
+// tests/fuzzing.rs
+//
+// 模糊测试入口 `fuzz_lex` / `fuzz_parse`：任意字节都不会让词法或语法分析器 panic；
+// 未闭合的字符串与字符字面量报 E1003，嵌套过深报 E1004
+
+use pawc::fuzz::{fuzz_lex, fuzz_parse};
+use pawc::PawError;
+
+/// 解析失败时的错误码、说明与位置；失败必须是语法错误
+fn syntax_error(src: &str) -> (&'static str, String, (usize, usize)) {
+    match fuzz_parse(src.as_bytes()) {
+        Err(err @ PawError::Syntax { .. }) => {
+            let (_, line, col) = err.location();
+            (err.code(), err.message(), (line, col))
+        }
+        other => panic!("expected a syntax error:\n{}\n{:?}", src, other),
+    }
+}
+
+#[test]
+fn unterminated_literals_are_syntax_errors() {
+    let (code, message, at) = syntax_error("let a: Int = 1\nsay \"paw");
+    assert_eq!((code, message.as_str(), at), ("E1003", "Unterminated string literal", (2, 5)));
+    // 转义的引号不结束字符串
+    assert_eq!(syntax_error("say \"paw\\\"").0, "E1003");
+    for src in ["let c: Char = 'a", "let c: Char = '", "let c: Char = 'ab'"] {
+        let (code, message, _) = syntax_error(src);
+        assert_eq!((code, message.as_str()), ("E1003", "Unterminated char literal"), "{}", src);
+    }
+}
+
+#[test]
+fn deep_nesting_is_limited() {
+    // 未优化构建中每层嵌套占用的栈远多于 release，在较大的栈上解析
+    let checks = std::thread::Builder::new()
+        .stack_size(64 << 20)
+        .spawn(|| {
+            let parens = |n: usize| format!("let x: Int = {}1{}", "(".repeat(n), ")".repeat(n));
+            assert!(fuzz_parse(parens(100).as_bytes()).is_ok());
+            let (code, message, _) = syntax_error(&parens(10_000));
+            assert_eq!((code, message.as_str()), ("E1004", "Nesting deeper than 128 levels"));
+            let blocks = format!("{}{}", "if true {\n".repeat(200), "}\n".repeat(200));
+            assert_eq!(syntax_error(&blocks).0, "E1004");
+            assert_eq!(syntax_error(&"-".repeat(10_000)).0, "E1004");
+        })
+        .unwrap();
+    checks.join().unwrap();
+}
+
+#[test]
+fn arbitrary_bytes_never_panic() {
+    let program = r#"record Cat { name: String  lives: Int }
+fun greet(c: Cat): String { return "hi ${c.name}" }
+let cats: Array<Cat> = [Cat { name: "Mochi", lives: 9 }]
+loop c in cats { say greet(c) }
+let m: Map<String, Int> = {"a": 1}
+match m["a"] { 1 => { say 'x' } _ => { } }
+"#;
+    // 程序的每个前缀都必须得到结果或语法错误
+    for end in 0..=program.len() {
+        let prefix = &program.as_bytes()[..end];
+        fuzz_lex(prefix);
+        if let Err(err) = fuzz_parse(prefix) {
+            assert!(matches!(err, PawError::Syntax { .. }), "{:?}\n{}", err, String::from_utf8_lossy(prefix));
+        }
+    }
+    // 非 UTF-8 字节与控制字符
+    for bytes in [&[0xff, 0xfe, 0x00][..], b"\x01\x02say", b"let \xc3\x28 = 1", b"}}}])", b"${", b"\"${"] {
+        fuzz_lex(bytes);
+        if let Err(err) = fuzz_parse(bytes) {
+            assert!(err.code().starts_with("E1"), "{:?}", err);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// tests/generics.rs
+//
+// 泛型函数与泛型 record：类型参数在调用处按实参确定、在 record 字面量中按字段值确定，
+// 检查器按代入后的类型检查，运行时不区分类型实参
+
+mod common;
+
+use common::{error_code, eval_string};
+
+const FUNCTIONS: &str = r#"
+fun first<T>(xs: Array<T>): T { return xs[0] }
+fun map<T, U>(xs: Array<T>, f: fun(T): U): Array<U> {
+  let out: Array<U> = []
+  loop x in xs { out.push(f(x)) }
+  return out
+}
+fun same<T>(a: T, b: T): Bool { return a == b }
+"#;
+
+#[test]
+fn generic_functions_are_instantiated_per_call() {
+    let v = eval_string(&format!(
+        "{}let n: Int = first([4, 5])\nlet s: String = first([\"a\", \"b\"])\n\
+         let lens: Array<Int> = map([\"ab\", \"c\"], fun(x: String): Int {{ return x.length() }})\n\
+         \"${{n + 1}} ${{s}} ${{lens}} ${{same(1, 1)}} ${{same(\"a\", \"b\")}}\"",
+        FUNCTIONS
+    ));
+    assert_eq!(v, "5 a [2, 1] true false");
+    // 结果的类型来自实参
+    assert_eq!(error_code(&format!("{}let s: String = first([1])", FUNCTIONS)), "E3003");
+    assert_eq!(
+        error_code(&format!("{}let xs: Array<String> = map([1], fun(x: Int): Int {{ return x }})", FUNCTIONS)),
+        "E3003"
+    );
+    assert_eq!(error_code(&format!("{}same(1, \"x\")", FUNCTIONS)), "E3033");
+}
+
+#[test]
+fn type_parameters_are_opaque_in_bodies() {
+    assert_eq!(error_code("fun inc<T>(x: T): T { return x + 1 }"), "E3014");
+    assert_eq!(error_code("fun f<T>(x: T): Int { return x }"), "E3004");
+    assert_eq!(error_code("fun f<T>(x: T): Bool { return x is T }"), "E3044");
+    assert_eq!(error_code("fun f<T, T>(x: T): T { return x }"), "E1001");
+    // 递归调用与局部变量可以使用类型参数
+    let v = eval_string(
+        r#"
+fun last<T>(xs: Array<T>): T {
+  if xs.length() == 1 { return xs[0] }
+  let rest: Array<T> = xs.slice(1, xs.length())
+  return last(rest)
+}
+last([1, 2, 3])
+"#,
+    );
+    assert_eq!(v, "3");
+}
+
+const BOX: &str = r#"
+record Box<T> {
+  value: T
+  fun get(): T { return self.value }
+  fun map<U>(f: fun(T): U): Box<U> { return Box { value: f(self.value) } }
+}
+record Pair<A, B> { a: A  b: B }
+fun unwrap<T>(b: Box<T>): T { return b.value }
+"#;
+
+#[test]
+fn generic_records_carry_their_type_arguments() {
+    let v = eval_string(&format!(
+        "{}let b: Box<Int> = Box {{ value: 41 }}\n\
+         let c: Box<String> = b.map(fun(v: Int): String {{ return \"v${{v + 1}}\" }})\n\
+         let p: Pair<Int, String> = Pair {{ a: 1, b: \"x\" }}\n\
+         let n: Int = unwrap(b) + b.get()\n\
+         \"${{c.get()}} ${{p.b}} ${{n}}\"",
+        BOX
+    ));
+    assert_eq!(v, "v42 x 82");
+    // 字段与方法按类型实参检查
+    assert_eq!(
+        error_code(&format!("{}let b: Box<Int> = Box {{ value: 1 }}\nlet s: String = b.value", BOX)),
+        "E3003"
+    );
+    assert_eq!(error_code(&format!("{}let s: String = unwrap(Box {{ value: 5 }})", BOX)), "E3003");
+    assert_eq!(error_code(&format!("{}let b: Box<Int> = Box {{ value: \"x\" }}", BOX)), "E3003");
+    assert_eq!(error_code("record P<A> { a: A  b: A }\nP { a: 1, b: \"s\" }"), "E3017");
+    // 泛型 record 必须写出个数正确的类型实参
+    assert_eq!(error_code(&format!("{}let b: Box = Box {{ value: 1 }}", BOX)), "E3028");
+    assert_eq!(error_code(&format!("{}let b: Box<Int, Int> = Box {{ value: 1 }}", BOX)), "E3028");
+    assert_eq!(error_code("let xs: Array<Int, Int> = []"), "E3028");
+}
+
+#[test]
+fn generic_records_can_be_matched() {
+    let v = eval_string(&format!(
+        "{}let b: Box<String> = Box {{ value: \"cat\" }}\n\
+         let out: String = \"\"\n\
+         match b {{\n  Box {{ value: v }} => {{ out = v + \"!\" }}\n}}\n\
+         out",
+        BOX
+    ));
+    assert_eq!(v, "cat!");
+}
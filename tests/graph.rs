@@ -0,0 +1,106 @@
+// tests/graph.rs
+//
+// `pawc graph`：输出入口脚本的模块依赖图（DOT 或 JSON），模块按检查器的规则查找，
+// 导入循环中的模块与边被标出，找不到的模块仍在图中并使命令失败
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use common::write_files;
+
+fn graph(dir: &Path, flags: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .current_dir(dir)
+        .arg("graph")
+        .args(flags)
+        .arg("app.paw")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+const PROJECT: &[(&str, &str)] = &[
+    ("app.paw", "import shapes\nimport util.text\nimport math\nsay 1\n"),
+    ("shapes/mod.paw", "import circle\n"),
+    ("shapes/circle.paw", "import mod as shapes\nimport math\n"),
+    ("util/text.paw", "let x: Int = 1\n"),
+];
+
+#[test]
+fn dot_output_highlights_cycles() {
+    let dir = write_files("graph", "dot", PROJECT);
+    let out = graph(&dir, &[]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(stdout.starts_with("digraph imports {\n"), "{}", stdout);
+    assert!(stdout.contains("  n0 [label=\"app.paw\"];\n"), "{}", stdout);
+    // 目录模块按入口文件出现，与检查器找到的文件相同
+    assert!(stdout.contains("  n1 [label=\"shapes/mod.paw\", color=red];\n"), "{}", stdout);
+    assert!(stdout.contains("  n3 [label=\"math\", style=dashed];\n"), "{}", stdout);
+    assert!(stdout.contains("  n4 [label=\"shapes/circle.paw\", color=red];\n"), "{}", stdout);
+    assert!(stdout.contains("  n0 -> n1 [label=\"shapes\"];\n"), "{}", stdout);
+    assert!(stdout.contains("  n1 -> n4 [label=\"circle\", color=red];\n"), "{}", stdout);
+    assert!(stdout.contains("  n4 -> n1 [label=\"mod\", color=red];\n"), "{}", stdout);
+    assert!(stdout.contains("  n4 -> n3 [label=\"math\"];\n"), "{}", stdout);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn json_output_lists_nodes_edges_and_cycles() {
+    let dir = write_files("graph", "json", PROJECT);
+    let out = graph(&dir, &["--format", "json"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(stdout.contains("{\"id\":2,\"name\":\"util/text.paw\",\"kind\":\"file\",\"cycle\":false}"), "{}", stdout);
+    assert!(stdout.contains("{\"id\":3,\"name\":\"math\",\"kind\":\"std\",\"cycle\":false}"), "{}", stdout);
+    assert!(stdout.contains("{\"from\":0,\"to\":2,\"import\":\"util.text\",\"line\":2,\"cycle\":false}"), "{}", stdout);
+    assert!(stdout.contains("{\"from\":1,\"to\":4,\"import\":\"circle\",\"line\":1,\"cycle\":true}"), "{}", stdout);
+    assert!(stdout.trim_end().ends_with("\"cycles\":[[1,4]]}"), "{}", stdout);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn self_imports_are_cycles() {
+    let dir = write_files("graph", "self", &[("app.paw", "import app\n")]);
+    let out = graph(&dir, &["--format", "json"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("{\"from\":0,\"to\":0,\"import\":\"app\",\"line\":1,\"cycle\":true}"), "{}", stdout);
+    assert!(stdout.trim_end().ends_with("\"cycles\":[[0]]}"), "{}", stdout);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_and_broken_modules_are_reported() {
+    let dir = write_files(
+        "graph",
+        "missing",
+        &[("app.paw", "import gone\nimport broken\n"), ("broken.paw", "import app\nlet = 1\n")],
+    );
+    let out = graph(&dir, &[]);
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    // 图照常输出：找不到的模块为点线框，解析失败的模块没有出边
+    assert!(stdout.contains("  n1 [label=\"gone.paw\", style=dotted];\n"), "{}", stdout);
+    assert!(stdout.contains("  n2 [label=\"broken.paw\"];\n"), "{}", stdout);
+    assert!(!stdout.contains("n2 ->"), "{}", stdout);
+    assert!(stderr.contains("[E1002]") && stderr.contains("Module file not found"), "{}", stderr);
+    assert!(stderr.contains("broken.paw"), "{}", stderr);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cfg_flags_select_imports() {
+    let dir = write_files(
+        "graph",
+        "cfg",
+        &[("app.paw", "@cfg(\"debug\") import tools\nsay 1\n"), ("tools.paw", "let x: Int = 1\n")],
+    );
+    let plain = String::from_utf8_lossy(&graph(&dir, &[]).stdout).to_string();
+    assert!(!plain.contains("tools.paw"), "{}", plain);
+    let debug = String::from_utf8_lossy(&graph(&dir, &["--cfg", "debug"]).stdout).to_string();
+    assert!(debug.contains("  n0 -> n1 [label=\"tools\"];\n"), "{}", debug);
+    let _ = fs::remove_dir_all(&dir);
+}
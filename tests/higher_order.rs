@@ -0,0 +1,53 @@
+// tests/higher_order.rs
+//
+// 数组的 map / filter / reduce：回调可以是匿名函数或具名函数，结果是新数组，原数组不变；
+// 回调签名按元素类型检查（E3022），回调中的 bark 向外传播并带上调用栈
+
+mod common;
+
+use common::{both_ways, eval_string, failure};
+
+#[test]
+fn callbacks_build_new_values() {
+    let v = eval_string(
+        r#"
+let a: Array<Int> = [1, 2, 3, 4]
+fun sq(x: Int): Int { return x * x }
+let tags = a.map(fun(x: Int): String { return "<${x}>" })
+let evens = a.filter(fun(x: Int): Bool { return x % 2 == 0 })
+let sum = a.reduce(fun(acc: Int, x: Int): Int { return acc + x }, 0)
+let joined = a.reduce(fun(acc: String, x: Int): String { return acc + x }, "")
+"${tags} ${evens} ${sum} ${joined} ${a.map(sq)} ${a}"
+"#,
+    );
+    assert_eq!(v, "[<1>, <2>, <3>, <4>] [2, 4] 10 1234 [1, 4, 9, 16] [1, 2, 3, 4]");
+    let empty = "let e: Array<Int> = []\n";
+    assert_eq!(both_ways(&format!("{}e.map(fun(x: Int): Int {{ return x }})", empty)), "[]");
+    assert_eq!(both_ways(&format!("{}e.reduce(fun(acc: Int, x: Int): Int {{ return acc + x }}, 7)", empty)), "7");
+}
+
+#[test]
+fn callback_signatures_are_checked() {
+    let a = "let a: Array<Int> = [1]\n";
+    for (call, expected) in [
+        ("a.map(fun(x: String): Int { return 1 })", "Callback of 'map' on Array<Int> must accept (Int), found fun(String): Int"),
+        ("a.map(fun(x: Int, y: Int): Int { return 1 })", "Callback of 'map' on Array<Int> must accept (Int), found fun(Int, Int): Int"),
+        ("a.map(5)", "Callback of 'map' on Array<Int> must accept (Int), found Int"),
+        ("a.filter(fun(x: Int): Int { return 1 })", "Callback of 'filter' must return Bool, found Int"),
+        ("a.reduce(fun(acc: Int, x: Int): String { return \"\" }, 0)", "Callback of 'reduce' must return Int, found String"),
+        (
+            "a.reduce(fun(acc: Int, x: Int): Int { return 1 }, \"s\")",
+            "Callback of 'reduce' on Array<Int> must accept (String, Int), found fun(Int, Int): Int",
+        ),
+    ] {
+        let (code, message, _, _) = failure(&format!("{}{}", a, call));
+        assert_eq!((code, message.as_str()), ("E3022", expected), "{}", call);
+    }
+}
+
+#[test]
+fn barks_leave_the_callback() {
+    let (code, message, position, _) =
+        failure("let a: Array<Int> = [1, 2]\nsay a.map(fun(x: Int): Int { if x == 2 { bark \"two\" } return x })");
+    assert_eq!((code, message.as_str(), position), ("E6001", "two", (2, 42)));
+}
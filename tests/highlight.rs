@@ -0,0 +1,86 @@
+// tests/highlight.rs
+//
+// 语法高亮：去掉颜色后必须与原始源码逐字一致
+
+use pawc::highlight::{highlight, Format};
+
+const SRC: &str = r#"# 计算总和
+record Point { x: Int, y: Int }
+fun sum(xs: Array<Int>): Int {
+  let total: Int = 0   # 累加
+  loop x in xs { total = total + x }
+  return total
+}
+say "sum: ${sum([1, 2])} # not a comment" + 'c'
+let ok: Bool = true && 1 <= 2
+"#;
+
+/// 去掉 ANSI 转义序列
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 去掉 HTML 标签并还原实体
+fn strip_html(s: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+#[test]
+fn ansi_output_preserves_source() {
+    assert_eq!(strip_ansi(&highlight(SRC, Format::Ansi)), SRC);
+}
+
+#[test]
+fn html_output_preserves_source() {
+    let html = highlight(SRC, Format::Html);
+    assert!(html.starts_with("<pre class=\"paw\"><code>"));
+    assert!(html.ends_with("</code></pre>\n"));
+    assert_eq!(strip_html(html.trim_end_matches('\n')), SRC);
+}
+
+#[test]
+fn tokens_and_comments_get_classes() {
+    let html = highlight(SRC, Format::Html);
+    for expected in [
+        "<span class=\"paw-comment\"># 计算总和</span>",
+        "<span class=\"paw-comment\"># 累加</span>",
+        "<span class=\"paw-keyword\">record</span>",
+        "<span class=\"paw-type\">Int</span>",
+        "<span class=\"paw-string\">&quot;sum: ${sum([1, 2])} # not a comment&quot;</span>",
+        "<span class=\"paw-string\">'c'</span>",
+        "<span class=\"paw-literal\">true</span>",
+        "<span class=\"paw-operator\">&amp;&amp;</span>",
+        "<span class=\"paw-operator\">&lt;=</span>",
+        "<span class=\"paw-number\">0</span>",
+    ] {
+        assert!(html.contains(expected), "missing {}\n{}", expected, html);
+    }
+}
+
+#[test]
+fn invalid_input_is_kept_verbatim() {
+    let src = "let s: String = \"unterminated\n@ 1";
+    assert_eq!(strip_ansi(&highlight(src, Format::Ansi)), src);
+}
@@ -0,0 +1,76 @@
+// tests/import_collisions.rs
+//
+// import 的别名与已有名字冲突：检查器报 E2005，提示中给出另一个定义的位置
+
+use pawc::{PawScript, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// 在临时目录中写入两个模块 a.paw / b.paw，返回入口文件路径
+fn modules(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pawc-collisions-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.paw"), "let x: Int = 1\n").unwrap();
+    fs::write(dir.join("b.paw"), "let y: Int = 2\n").unwrap();
+    dir.join("main.paw")
+}
+
+/// 运行脚本，返回错误码与错误文本
+fn collision(name: &str, src: &str) -> (&'static str, String) {
+    let main = modules(name);
+    let err = PawScript::with_file(main.to_str().unwrap())
+        .eval_str(src)
+        .err()
+        .unwrap_or_else(|| panic!("script should fail:\n{}", src));
+    (err.code(), err.to_string())
+}
+
+#[test]
+fn two_imports_under_one_alias() {
+    let (code, text) = collision("alias", "import a as m\nimport b as m\nm.y");
+    assert_eq!(code, "E2005");
+    // 第二个 import 的位置与第一个 import 的位置
+    assert!(text.contains("main.paw:2:1"), "{}", text);
+    assert!(text.contains("main.paw:1:1"), "{}", text);
+    assert!(text.contains("import b as"), "{}", text);
+}
+
+#[test]
+fn aliases_cannot_shadow_variables_or_functions() {
+    let (code, text) = collision("let", "let total: Int = 0\n\nimport a as total");
+    assert_eq!(code, "E2005");
+    assert!(text.contains("main.paw:3:1") && text.contains("main.paw:1:1"), "{}", text);
+
+    // 函数先于其它语句登记，写在 import 之后也算冲突
+    let (code, text) = collision("fun", "import a\nfun a(): Int { return 1 }");
+    assert_eq!(code, "E2005");
+    assert!(text.contains("main.paw:2:1"), "{}", text);
+
+    // 外层作用域中的变量同样不能被别名遮蔽
+    let (code, text) = collision("nested", "let m: Int = 1\nif true {\n  import b as m\n}");
+    assert_eq!(code, "E2005");
+    assert!(text.contains("main.paw:3:3") && text.contains("main.paw:1:1"), "{}", text);
+}
+
+#[test]
+fn later_definitions_point_back_at_the_import() {
+    let (code, text) = collision("later", "import a\nlet a: Int = 3");
+    assert_eq!(code, "E2005");
+    assert!(text.contains("main.paw:2:1") && text.contains("main.paw:1:1"), "{}", text);
+}
+
+#[test]
+fn host_globals_and_distinct_aliases() {
+    let main = modules("host");
+    let mut paw = PawScript::with_file(main.to_str().unwrap());
+    paw.define("config", Value::Int(1));
+    let err = paw.eval_str("import a as config").unwrap_err();
+    assert_eq!(err.code(), "E2005");
+    assert!(err.to_string().contains("host"), "{}", err);
+
+    let v = PawScript::with_file(main.to_str().unwrap())
+        .eval_str("import a as first\nimport b as second\n(first.x as Int) + (second.y as Int)")
+        .unwrap();
+    assert_eq!(v, Value::Int(3));
+}
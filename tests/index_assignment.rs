@@ -0,0 +1,58 @@
+// tests/index_assignment.rs
+//
+// 下标赋值 `a[i] = v`：原地修改数组元素，可以嵌套或经过 record 字段；数组按值传递，
+// 修改副本不影响原数组；越界报 E6010，元素类型不符报 E3003，下标不是 Int 报 E3011
+
+mod common;
+
+use common::{both_ways, error_code, eval_string};
+use pawc::PawScript;
+
+#[test]
+fn elements_are_replaced_in_place() {
+    assert_eq!(both_ways("let a: Array<Int> = [1, 2, 3]\na[1] = 20\na[0] = a[1] + a[2]\na"), "[23, 20, 3]");
+    assert_eq!(
+        both_ways("let a: Array<Int> = [0, 0, 0]\nloop i in 0..3 { a[i] = i * i }\na"),
+        "[0, 1, 4]"
+    );
+    let v = eval_string(
+        r#"
+record P { xs: Array<Int> }
+let p = P { xs: [1, 2] }
+p.xs[1] = 8
+let g: Array<Array<Int>> = [[1, 2], [3]]
+g[0][1] = 9
+"${p.xs} ${g}"
+"#,
+    );
+    assert_eq!(v, "[1, 8] [[1, 9], [3]]");
+}
+
+#[test]
+fn copies_are_independent() {
+    let v = eval_string(
+        r#"
+let g: Array<Array<Int>> = [[1], [3]]
+let b = g
+b[1][0] = 7
+fun f(xs: Array<Int>): Void { xs[0] = 5 }
+let a: Array<Int> = [1]
+f(a)
+"${g} ${b} ${a}"
+"#,
+    );
+    assert_eq!(v, "[[1], [3]] [[1], [7]] [1]");
+}
+
+#[test]
+fn bad_assignments_are_reported() {
+    for (src, index) in [("let a: Array<Int> = [1]\na[5] = 2", 5), ("let a: Array<Int> = [1]\na[-1] = 2", -1)] {
+        let err = PawScript::new().eval_str(src).unwrap_err();
+        assert_eq!(err.code(), "E6010");
+        assert_eq!(err.message(), format!("Index {} out of bounds for Array of length 1", index));
+        assert_eq!(err.location().1, 2);
+    }
+    assert_eq!(error_code("let a: Array<Int> = [1]\na[0] = \"x\""), "E3003");
+    assert_eq!(error_code("let a: Array<Int> = [1]\na[\"k\"] = 2"), "E3011");
+    assert_eq!(error_code("let s: String = \"ab\"\ns[0] = 1"), "E3012");
+}
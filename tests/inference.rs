@@ -0,0 +1,94 @@
+// tests/inference.rs
+//
+// 省略类型的 `let x = value`：变量取初始值的类型，之后按这个类型检查；
+// 初始值确定不了类型（nopaw、空容器、ok/err）时报 E3050
+
+mod common;
+
+use pawc::PawScript;
+use common::{error_code, eval_string};
+
+#[test]
+fn variables_take_the_type_of_their_value() {
+    let v = eval_string(
+        r#"
+record Cat { name: String  lives: Int }
+fun maybe(n: Int): Int? { if n > 0 { return n } }
+let n = 2
+let big = 3000000000L
+let s = "paw"
+let xs = [1, 2, 3]
+let ages = {"mochi": 3}
+let pair = (n, s)
+let cat = Cat { name: "Tama", lives: 9 }
+let double = fun(x: Int): Int { return x * 2 }
+let m = maybe(n)
+let total = 0
+loop x in xs { total = total + double(x) }
+"${total} ${big} ${s.length()} ${ages["mochi"]} ${pair} ${cat.lives} ${m}"
+"#,
+    );
+    assert_eq!(v, "12 3000000000 3 3 (2, paw) 9 2");
+    // 推断出的类型与写出注解时相同
+    assert_eq!(error_code("let n = 1\nn = \"one\""), "E3003");
+    assert_eq!(error_code("let n = 1\nlet s: String = n"), "E3003");
+    assert_eq!(error_code("let xs = [1, 2]\nxs.push(\"three\")"), "E3022");
+    assert_eq!(error_code("let s = \"a\"\ns - 1"), "E3014");
+    assert_eq!(eval_string("let n = 1\nlet m: Int? = n\nm"), "1");
+}
+
+#[test]
+fn inferred_lets_work_in_every_scope() {
+    let v = eval_string(
+        r#"
+fun shout(word: String): String {
+  let loud = word.to_uppercase()
+  let marks = "!!"
+  return loud + marks
+}
+let out = ""
+loop i in 0..3 {
+  let piece = "${i}"
+  out = out + piece
+}
+if out == "012" {
+  let greeting = shout("hi")
+  out = out + greeting
+}
+out
+"#,
+    );
+    assert_eq!(v, "012HI!!");
+    // 内层的推断变量不泄漏到外层
+    assert_eq!(error_code("if true { let inner = 1 }\ninner"), "E4001");
+    assert_eq!(error_code("let n = 1\nlet n = 2"), "E2005");
+}
+
+#[test]
+fn undetermined_values_need_an_annotation() {
+    for src in [
+        "let x = nopaw",
+        "let xs = []",
+        "let m = {}",
+        "let r = ok(1)",
+        "let r = err(\"bad\")",
+        "let xs = [nopaw]",
+        "let xss = [[]]",
+        "let t = (1, nopaw)",
+    ] {
+        assert_eq!(error_code(src), "E3050", "{}", src);
+    }
+    let err = PawScript::new().eval_str("let xs = []").unwrap_err();
+    assert_eq!(err.hint(), Some("Declare the type, e.g. `let xs: Array<T> = []`"));
+    // 值为 Void 时与写出注解时一样报错
+    assert_eq!(error_code("fun f(): Void {}\nlet x = f()"), "E3043");
+    // 有注解时照常接受这些值
+    assert_eq!(eval_string("let xs: Array<Int> = []\nlet x: Int? = nopaw\nxs.length()"), "0");
+}
+
+#[test]
+fn ask_still_needs_a_type() {
+    assert_eq!(error_code("let name <- ask \"Name? \""), "E1001");
+    assert_eq!(error_code("let = 1"), "E1001");
+    assert_eq!(error_code("let x: = 1"), "E1001");
+}
@@ -0,0 +1,76 @@
+// tests/inspect.rs
+//
+// `inspect(value[, depth[, width]])` 与 `say_debug`：带类型、缩进、按内容排序的调试输出
+
+mod common;
+
+use std::process::Command;
+use common::{error_code, eval_string};
+
+#[test]
+fn nested_values_are_indented_with_types() {
+    let v = eval_string(
+        r#"
+record Pet { name: String  age: Int  tags: Array<String> }
+let p: Pet = Pet { name: "Rex", age: 3, tags: ["good", "dog"] }
+inspect(p)
+"#,
+    );
+    assert_eq!(
+        v,
+        r#"Pet {
+  age: Int 3,
+  name: String "Rex",
+  tags: Array(2) [
+    String "good",
+    String "dog",
+  ],
+}"#
+    );
+    assert_eq!(eval_string(r#"inspect((1L, 'x', nopaw))"#), "Tuple(3) (\n  Long 1,\n  Char 'x',\n  nopaw,\n)");
+    assert_eq!(eval_string(r#"inspect(err("no\tbone"))"#), r#"err String "no\tbone""#);
+    assert_eq!(eval_string("inspect([])"), "Array(0) []");
+}
+
+#[test]
+fn maps_and_sets_are_sorted() {
+    let v = eval_string(
+        r#"
+let m: Map<String, Int> = {"b": 2, "c": 3, "a": 1}
+inspect(m) + "\n" + inspect(Set {3, 1, 2})
+"#,
+    );
+    assert_eq!(
+        v,
+        "Map(3) {\n  String \"a\" => Int 1,\n  String \"b\" => Int 2,\n  String \"c\" => Int 3,\n}\n\
+         Set(3) {\n  Int 1,\n  Int 2,\n  Int 3,\n}"
+    );
+}
+
+#[test]
+fn depth_and_width_truncate_output() {
+    assert_eq!(
+        eval_string("inspect([[1, 2], [3]], 1)"),
+        "Array(2) [\n  Array(2) [...],\n  Array(1) [...],\n]"
+    );
+    assert_eq!(eval_string("inspect([[1]], 0)"), "Array(1) [...]");
+    assert_eq!(
+        eval_string("inspect(range(10), 8, 2)"),
+        "Array(10) [\n  Int 0,\n  Int 1,\n  ... 8 more\n]"
+    );
+    assert_eq!(error_code("inspect(1, -1)"), "E6013");
+    assert_eq!(error_code(r#"inspect(1, "deep")"#), "E3033");
+    assert_eq!(error_code("inspect(1, 2, 3, 4)"), "E3032");
+}
+
+#[test]
+fn say_debug_prints_the_inspected_value() {
+    let path = std::env::temp_dir().join(format!("pawc-inspect-{}.paw", std::process::id()));
+    std::fs::write(&path, "say_debug [1, 2]\nsay [1, 2]\n").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_pawc")).arg(&path).output().unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "Array(2) [\n  Int 1,\n  Int 2,\n]\n[1, 2]\n"
+    );
+}
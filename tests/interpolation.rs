@@ -0,0 +1,42 @@
+// tests/interpolation.rs
+//
+// 字符串插值 `"... ${expr} ..."`：任意表达式（包括嵌套的插值字符串）按显示形式拼接，
+// `\${` 是字面量；插值中的错误指向源码中的原位置
+
+mod common;
+
+use common::{both_ways, eval_string, failure};
+
+#[test]
+fn expressions_are_formatted() {
+    let v = eval_string(
+        r#"
+let name: String = "Rex"
+let age: Int = 3
+"Hello ${name}, you are ${age + 1}"
+"#,
+    );
+    assert_eq!(v, "Hello Rex, you are 4");
+    assert_eq!(
+        eval_string(r#""${[1, 2]} ${nopaw} ${1.5} ${'c'} ${true} ${{"a": 1}["a"]}""#),
+        "[1, 2] Nopaw 1.5 c true 1"
+    );
+    assert_eq!(both_ways("let n: Int = 3\n\"${n}${n}\" + \"x\""), "33x");
+}
+
+#[test]
+fn nesting_and_escapes() {
+    assert_eq!(eval_string(r#"let name: String = "Rex"
+"\${name} ${"inner ${name}"}""#), "${name} inner Rex");
+}
+
+#[test]
+fn errors_point_into_the_string() {
+    let (code, _, position, _) = failure("say \"a ${nope} b\"");
+    assert_eq!((code, position), ("E4001", (1, 10)));
+    let (code, _, position, _) = failure("say \"a ${1 +} b\"");
+    assert_eq!((code, position), ("E1001", (1, 13)));
+    let (code, message, _, _) = failure("say \"a ${1\n");
+    assert_eq!((code, message.as_str()), ("E1003", "Unterminated string interpolation"));
+    assert_eq!(failure("say \"${}\"").0, "E1001");
+}
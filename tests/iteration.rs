@@ -0,0 +1,92 @@
+// tests/iteration.rs
+//
+// `loop x in v` 的遍历协议：Array / Set / Range 取元素，Map 取 (键, 值)，String 取字符；
+// `loop (k, v) in m` 按位置解构元素
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{both_ways, error_code, eval_string};
+
+#[test]
+fn strings_yield_chars() {
+    let v = both_ways(
+        r#"
+let out: String = ""
+let vowels: Int = 0
+loop c in "héllo" {
+  out = "${c}" + out
+  if c == 'e' || c == 'o' { vowels = vowels + 1 }
+}
+"${out} ${vowels}"
+"#,
+    );
+    assert_eq!(v, "olléh 1");
+    assert_eq!(eval_string("let n: Int = 0\nloop c in \"\" { n = n + 1 }\nn"), "0");
+}
+
+#[test]
+fn maps_yield_key_value_pairs() {
+    let v = both_ways(
+        r#"
+let ages: Map<String, Int> = {"amy": 5, "bob": 3}
+let total: Int = 0
+let names: Array<String> = []
+loop entry in ages {
+  total = total + entry[1]
+  names.push(entry[0])
+}
+"${total} ${names.sort()}"
+"#,
+    );
+    assert_eq!(v, "8 [amy, bob]");
+    let v = eval_string(
+        r#"
+let ages: Map<String, Int> = {"amy": 5, "bob": 3}
+let lines: Array<String> = []
+loop (name, age) in ages { lines.push("${name}=${age + 1}") }
+loop (_, age) in ages { lines.push("${age}") }
+lines.sort().join(" ")
+"#,
+    );
+    assert_eq!(v, "3 5 amy=6 bob=4");
+}
+
+#[test]
+fn tuples_destructure_from_any_iterable() {
+    let v = eval_string(
+        r#"
+let pairs: Array<(Int, String)> = [(1, "a"), (2, "b")]
+let out: String = ""
+loop (n, s) in pairs { out = out + s + "${n * 10}" }
+loop (flag) in [true, false] { out = out + "${flag}" }
+out
+"#,
+    );
+    assert_eq!(v, "a10b20truefalse");
+    // 仍然是条件循环
+    assert_eq!(eval_string("let i: Int = 0\nloop (i < 3) { i = i + 1 }\ni"), "3");
+}
+
+#[test]
+fn loop_variables_are_typed_per_iterable() {
+    assert_eq!(error_code("loop c in \"ab\" { let s: String = c }"), "E3003");
+    assert_eq!(error_code("let m: Map<String, Int> = {}\nloop e in m { let k: String = e }"), "E3003");
+    assert_eq!(error_code("let m: Map<String, Int> = {}\nloop (k, v) in m { let n: Int = k }"), "E3003");
+    assert_eq!(error_code("let m: Map<String, Int> = {}\nloop (a, b, c) in m { }"), "E3041");
+    assert_eq!(error_code("loop (a, b) in [1, 2] { }"), "E3041");
+    assert_eq!(error_code("loop x in 42 { }"), "E3018");
+    assert_eq!(error_code("loop x in 'c' { }"), "E3018");
+}
+
+#[test]
+fn host_values_are_checked_at_runtime() {
+    let mut paw = PawScript::new();
+    // 元素类型不一致的数组在检查器中是 Array<Any>
+    paw.define("mixed", Value::Array(vec![Value::Int(1), Value::String("x".to_string())]));
+    paw.define("word", Value::String("paw".to_string()));
+    let v = paw.eval_str("let out: String = \"\"\nloop c in word { out = out + \"${c}.\" }\nout").unwrap();
+    assert_eq!(v.to_string(), "p.a.w.");
+    let err = paw.eval_str("loop (a, b) in mixed { }").unwrap_err();
+    assert_eq!(err.code(), "E6017");
+}
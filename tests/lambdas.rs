@@ -0,0 +1,54 @@
+// tests/lambdas.rs
+//
+// 匿名函数 `fun(x: Int): Int { ... }`：可以作为值保存、传参与返回，捕获外层变量（读取时取当前值，
+// 每次调用外层函数得到独立的状态）；`async fun` 返回 Future；签名参与类型检查
+
+mod common;
+
+use common::{error_code, eval_string};
+
+#[test]
+fn lambdas_are_values() {
+    let v = eval_string(
+        r#"
+let double: fun(Int): Int = fun(x: Int): Int { return x * 2 }
+fun apply(f: fun(Int): Int, x: Int): Int { return f(x) }
+let squares = [1, 2, 3].map(fun(x: Int): Int { return x * x })
+"${apply(double, 4)} ${apply(fun(n: Int): Int { return n + 1 }, 4)} ${squares}"
+"#,
+    );
+    assert_eq!(v, "8 5 [1, 4, 9]");
+    assert_eq!(eval_string("let af = async fun(x: Int): Int { return x * 3 }\nawait af(2)"), "6");
+}
+
+#[test]
+fn lambdas_capture_their_surroundings() {
+    let v = eval_string(
+        r#"
+fun counter(): fun(): Int {
+  let n: Int = 0
+  return fun(): Int {
+    n = n + 1
+    return n
+  }
+}
+let c = counter()
+c()
+c()
+let d = counter()
+let base: Int = 10
+let add = fun(x: Int): Int { return x + base }
+base = 20
+"${c()} ${d()} ${add(1)}"
+"#,
+    );
+    assert_eq!(v, "3 1 21");
+}
+
+#[test]
+fn signatures_are_checked() {
+    assert_eq!(error_code("let f: fun(Int): Int = fun(x: String): Int { return 1 }"), "E3003");
+    assert_eq!(error_code("let f = fun(x: Int): Int { return x }\nf(\"s\")"), "E3033");
+    assert_eq!(error_code("let f = fun(x: Int): Int { return x }\nf(1, 2)"), "E3032");
+    assert_eq!(error_code("let f = fun(x: Int): Int { return \"s\" }"), "E3004");
+}
@@ -0,0 +1,29 @@
+// tests/lexer.rs
+//
+// 词法与语法分析的边界情况：数字与标识符读到源码末尾时正常结束，
+// 不完整的 import 报带位置的 E1001 语法错误而不是 panic
+
+mod common;
+
+use common::{eval_string, failure};
+
+#[test]
+fn tokens_at_the_end_of_the_source() {
+    assert_eq!(eval_string("1."), "1.0");
+    assert_eq!(eval_string("2.5"), "2.5");
+    assert_eq!(eval_string("1..3"), "1..3");
+    assert_eq!(eval_string("let n: Int = 42\nn"), "42");
+    assert_eq!(eval_string("let 名字: Int = 2\n名字"), "2");
+    assert_eq!(eval_string("let snake_case_2: Int = 7\nsnake_case_2"), "7");
+}
+
+#[test]
+fn incomplete_imports_are_syntax_errors() {
+    let (code, message, at, _) = failure("say 1\nimport");
+    assert_eq!((code, message.as_str(), at), ("E1001", "Expected identifier, got Eof", (2, 7)));
+    let (code, message, at, _) = failure("import as m");
+    assert_eq!((code, message.as_str(), at), ("E1001", "Expected identifier, got Keyword(\"as\")", (1, 8)));
+    assert_eq!(failure("import 3").0, "E1001");
+    assert_eq!(failure("import a.").0, "E1001");
+    assert_eq!(failure("say 12.5.3").0, "E1001");
+}
@@ -0,0 +1,164 @@
+// tests/lint.rs
+//
+// `pawc lint`：不执行脚本，报告未使用的变量、不可达代码、恒定条件、遮蔽与被丢弃的结果，
+// 规则可以按名字或规则集选择，输出文本或 JSON
+
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+use common::{pawc, script};
+
+fn lint(path: &Path, flags: &[&str]) -> Output {
+    pawc(&[&["lint"], flags].concat(), path)
+}
+
+/// 以 JSON 输出运行，按顺序返回各条诊断的 `code@line:column`
+fn findings(path: &Path, flags: &[&str]) -> Vec<String> {
+    let out = lint(path, &[flags, &["--format", "json"]].concat());
+    let json = String::from_utf8_lossy(&out.stdout).to_string();
+    assert_eq!(out.status.success(), json.trim() == "[]", "{}", json);
+    let field = |item: &str, key: &str| {
+        let rest = &item[item.find(&format!("\"{}\":", key)).unwrap() + key.len() + 3..];
+        rest[..rest.find(',').unwrap()].trim_matches('"').to_string()
+    };
+    json.split("{\"file\"")
+        .skip(1)
+        .map(|item| format!("{}@{}:{}", field(item, "code"), field(item, "line"), field(item, "column")))
+        .collect()
+}
+
+const SAMPLE: &str = r#"fun area(w: Int, h: Int): Int {
+  let unused: Int = 3
+  let _scratch: Int = 4
+  return w * h
+  say "never"
+}
+fun log(msg: String): Void { say msg }
+let total: Int = 0
+if true { total = 1 }
+loop i in 0..3 {
+  let total: Int = i
+  say total
+}
+area(1, 2)
+log("x")
+total + 1
+loop { break } while 1 > 2
+total
+"#;
+
+#[test]
+fn default_rules_find_each_problem() {
+    let path = script("lint", "default", SAMPLE);
+    assert_eq!(
+        findings(&path, &[]),
+        ["W3005@2:3", "W3006@5:3", "W3007@9:4", "W3009@14:1", "W3009@16:1", "W3007@17:22"]
+    );
+    // 文本输出同样列出诊断并给出汇总，有诊断时以非零状态退出
+    let out = lint(&path, &[]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("Variable 'unused' is never used"), "{}", stderr);
+    assert!(stderr.contains("never gets past the `return`"), "{}", stderr);
+    assert!(stderr.contains("'area' returns Int"), "{}", stderr);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Linted 1 file(s): 0 error(s), 6 warning(s)"), "{}", stdout);
+}
+
+#[test]
+fn rules_and_sets_are_selectable() {
+    let path = script("lint", "select", SAMPLE);
+    assert_eq!(findings(&path, &["--rules", "shadowing"]), ["W3008@11:3"]);
+    assert_eq!(findings(&path, &["--rules", "flow"]), ["W3006@5:3", "W3007@9:4", "W3007@17:22"]);
+    assert_eq!(
+        findings(&path, &["--rules", "all", "--allow", "flow", "--allow", "discarded-result"]),
+        ["W3005@2:3", "W3008@11:3"]
+    );
+    let out = lint(&path, &["--rules", "typo"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("unknown rule 'typo'"));
+}
+
+#[test]
+fn checker_warnings_belong_to_rules() {
+    let path = script(
+        "lint",
+        "checker",
+        "@deprecated(\"use b\")\nfun a(): Void { }\na()\nlet n: Int? = 1\nmatch n {\n  1 => { say 1 }\n}\n",
+    );
+    assert_eq!(findings(&path, &[]), ["W3004@3:1", "W3002@5:1"]);
+    assert_eq!(findings(&path, &["--allow", "checker"]), Vec::<String>::new());
+    let json = String::from_utf8_lossy(&lint(&path, &["--format", "json"]).stdout).to_string();
+    assert!(json.contains("\"rule\":\"deprecated\""), "{}", json);
+}
+
+#[test]
+fn discarded_method_results_are_reported() {
+    let path = script(
+        "lint",
+        "methods",
+        r#"fun names(): Array<String> { return ["b", "a"] }
+fun touch(m: Map<String, Int>): Void {
+  m.keys()
+  m.insert("z", 1)
+}
+let s: String = " hi "
+s.trim()
+let xs = [3, 1, 2]
+xs.sort()
+xs.sort(fun(a: Int, b: Int): Int { return a - b })
+xs.push(4)
+names().reverse()
+let seen: Set<Int> = Set {1}
+seen.add(2)
+seen.remove(1)
+seen.union(Set {3})
+"abc".to_uppercase().length()
+say s
+"#,
+    );
+    // 不修改接收者的方法的结果被丢弃时报告；原地修改的 insert / add / remove / push 与带比较函数的 sort 不报告
+    assert_eq!(
+        findings(&path, &[]),
+        ["W3009@3:3", "W3009@7:1", "W3009@9:1", "W3009@12:1", "W3009@16:1", "W3009@17:1"]
+    );
+    let stderr = String::from_utf8_lossy(&lint(&path, &[]).stderr).to_string();
+    assert!(stderr.contains("`trim` only returns a value and leaves the String unchanged"), "{}", stderr);
+}
+
+#[test]
+fn clean_code_has_no_findings() {
+    let path = script(
+        "lint",
+        "clean",
+        r#"
+fun first_even(xs: Array<Int>): Int {
+  loop x in xs {
+    if x % 2 == 0 { return x }
+  }
+  return -1
+}
+let found: Int = first_even([1, 3, 4])
+let (_, b): (Int, Int) = (1, 2)
+let twice: fun(Int): Int = fun(n: Int): Int { return n * b }
+match found {
+  -1 => { say "none" }
+  n => { say twice(n) }
+}
+found
+"#,
+    );
+    assert_eq!(findings(&path, &["--rules", "all"]), Vec::<String>::new());
+    assert!(lint(&path, &["--rules", "all"]).status.success());
+}
+
+#[test]
+fn errors_are_reported_without_running() {
+    let path = script("lint", "errors", "say \"side effect\"\nlet n: Int = \"oops\"\n");
+    let out = lint(&path, &["--format", "json"]);
+    assert!(!out.status.success());
+    let json = String::from_utf8_lossy(&out.stdout);
+    assert!(json.contains("\"severity\":\"error\"") && json.contains("\"rule\":null"), "{}", json);
+    assert!(!json.contains("side effect"), "{}", json);
+}
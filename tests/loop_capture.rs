@@ -0,0 +1,106 @@
+// tests/loop_capture.rs
+//
+// 每次迭代一层新作用域：循环中创建的闭包与 async 块捕获的是本次迭代的循环变量和体内变量，
+// 树遍历解释器与字节码虚拟机的结果一致
+
+mod common;
+
+use common::{both_ways, eval_string};
+
+#[test]
+fn closures_capture_the_range_variable_of_their_iteration() {
+    let src = r#"
+let fs: Array<fun(): Int> = []
+loop i in 0..3 { fs.push(fun(): Int { return i }) }
+let out: String = ""
+loop f in fs { out = out + "${f()} " }
+out
+"#;
+    assert_eq!(both_ways(src), "0 1 2 ");
+}
+
+#[test]
+fn closures_capture_body_variables_of_their_iteration() {
+    let src = r#"
+let fs: Array<fun(): Int> = []
+loop i in 0..3 {
+  let j: Int = i * 10
+  fs.push(fun(): Int { return j })
+}
+let out: String = ""
+loop f in fs { out = out + "${f()} " }
+out
+"#;
+    assert_eq!(both_ways(src), "0 10 20 ");
+}
+
+#[test]
+fn closures_capture_the_array_element_of_their_iteration() {
+    let src = r#"
+let fs: Array<fun(): String> = []
+loop (name, n) in [("a", 1), ("b", 2)] { fs.push(fun(): String { return "${name}${n}" }) }
+loop s in ["c", "d"] { fs.push(fun(): String { return s }) }
+let out: String = ""
+loop f in fs { out = out + f() + " " }
+out
+"#;
+    assert_eq!(both_ways(src), "a1 b2 c d ");
+}
+
+#[test]
+fn closures_capture_body_variables_in_while_loops() {
+    let src = r#"
+let fs: Array<fun(): Int> = []
+let n: Int = 0
+loop n < 3 {
+  let k: Int = n
+  fs.push(fun(): Int { return k })
+  n = n + 1
+}
+loop {
+  let k: Int = n
+  fs.push(fun(): Int { return k })
+  n = n + 1
+} while n < 5
+let out: String = ""
+loop f in fs { out = out + "${f()} " }
+out
+"#;
+    assert_eq!(both_ways(src), "0 1 2 3 4 ");
+}
+
+#[test]
+fn async_blocks_capture_the_variables_of_their_iteration() {
+    let src = r#"
+let futs: Array<Future<Int>> = []
+loop i in 0..3 { futs.push(async { return i }) }
+loop c in [10, 20] {
+  let d: Int = c + 1
+  futs.push(async { return d })
+}
+let n: Int = 0
+loop n < 2 {
+  let k: Int = n * 100
+  futs.push(async { return k })
+  n = n + 1
+}
+let out: String = ""
+loop f in futs { out = out + "${await f} " }
+out
+"#;
+    assert_eq!(both_ways(src), "0 1 2 11 21 0 100 ");
+}
+
+#[test]
+fn loop_variables_do_not_overwrite_outer_variables() {
+    // 只含简单语句的循环由虚拟机执行，同样不把循环变量和体内变量写回外层
+    let src = r#"
+let i: Int = 7
+let x: Int = 1
+loop i in 0..3 { let x: Int = i }
+loop n in [4, 5] { let x: Int = n }
+"${i} ${x}"
+"#;
+    assert_eq!(both_ways(src), "7 1");
+    assert_eq!(eval_string("let t: Int = 0\nloop i in 0..4 { t = t + i }\nt"), "6");
+}
@@ -0,0 +1,48 @@
+// tests/loop_inputs.rs
+//
+// 循环条件必须是 Bool（E3007），遍历的值必须可迭代（E3018）；
+// 类型为 Any 的值在运行时检查，解释器与字节码 VM 都报错而不是跳过循环
+
+mod common;
+
+use common::{both_ways, error_code};
+use pawc::PawScript;
+
+/// 分别用两种执行方式运行，返回相同的错误码、说明与位置
+fn runtime_failure(src: &str) -> (&'static str, String, (usize, usize)) {
+    let mut results = [true, false].into_iter().map(|vm| {
+        let mut paw = PawScript::new();
+        paw.set_vm(vm);
+        let err = paw.eval_str(src).unwrap_err();
+        let (_, line, col) = err.location();
+        (err.code(), err.message(), (line, col))
+    });
+    let first = results.next().unwrap();
+    assert_eq!(Some(&first), results.next().as_ref(), "{}", src);
+    first
+}
+
+#[test]
+fn bad_inputs_are_rejected_by_the_checker() {
+    assert_eq!(error_code("loop 1 { }"), "E3007");
+    assert_eq!(error_code("loop x in 5 { }"), "E3018");
+}
+
+#[test]
+fn any_values_are_checked_at_runtime() {
+    assert_eq!(
+        runtime_failure("let c: Any = 1\nloop c { say \"x\" }"),
+        ("E3007", "Loop condition must be Bool, found Int".to_string(), (2, 6))
+    );
+    assert_eq!(
+        runtime_failure("let c: Any = 5\nloop x in c { say x }"),
+        ("E3018", "Cannot loop over Int".to_string(), (2, 11))
+    );
+    assert_eq!(runtime_failure("let c: Any = nopaw\nloop x in c { say x }").1, "Cannot loop over Nopaw");
+}
+
+#[test]
+fn iterable_any_values_still_loop() {
+    assert_eq!(both_ways("let c: Any = \"ab\"\nlet n: Int = 0\nloop x in c { n = n + 1 }\nn"), "2");
+    assert_eq!(both_ways("let c: Any = [1, 2, 3]\nlet n: Int = 0\nloop x in c { n = n + 1 }\nn"), "3");
+}
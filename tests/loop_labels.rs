@@ -0,0 +1,118 @@
+// tests/loop_labels.rs
+//
+// 循环标签：`loop outer: ...` 与 `break outer` / `continue outer`，
+// 检查器确认标签属于外层循环，解释器与字节码都跳到正确的循环
+
+mod common;
+
+use pawc::PawScript;
+use common::{both_ways, error_code, eval_string};
+
+#[test]
+fn break_and_continue_reach_the_labeled_loop() {
+    let v = both_ways(
+        r#"
+let out: String = ""
+loop outer: i in 0..4 {
+  loop j in 0..4 {
+    if j > i { continue outer }
+    if i == 3 { break outer }
+    out = out + "${i}${j} "
+  }
+}
+out
+"#,
+    );
+    assert_eq!(v, "00 10 11 20 21 22 ");
+    let v = both_ways(
+        r#"
+let grid: Array<Array<Int>> = [[1, 2], [3, 42], [5, 6]]
+let found: String = "none"
+let row: Int = 0
+loop rows: row < grid.length() {
+  loop cell in grid[row] {
+    if cell == 42 {
+      found = "${row}"
+      break rows
+    }
+  }
+  row = row + 1
+}
+found
+"#,
+    );
+    assert_eq!(v, "1");
+}
+
+#[test]
+fn labels_pass_through_several_levels() {
+    let v = both_ways(
+        r#"
+let steps: Int = 0
+loop a: forever {
+  loop b: x in [1, 2, 3] {
+    loop (steps < 100) {
+      steps = steps + 1
+      if steps == 5 { break a }
+      if steps % 2 == 0 { continue b }
+    }
+  }
+}
+steps
+"#,
+    );
+    assert_eq!(v, "5");
+    // 不带标签时仍作用于最近一层
+    let v = both_ways(
+        "let n: Int = 0\nloop outer: i in 0..3 {\n  loop j in 0..3 {\n    if j == 1 { break }\n    n = n + 1\n  }\n}\nn",
+    );
+    assert_eq!(v, "3");
+}
+
+#[test]
+fn labeled_forever_loops_can_finish_a_function() {
+    let v = eval_string(
+        r#"
+fun first_pair(xs: Array<Int>, target: Int): String {
+  let answer: String = "none"
+  loop search: forever {
+    loop i in 0..xs.length() {
+      loop j in i + 1..xs.length() {
+        if xs[i] + xs[j] == target {
+          answer = "${i},${j}"
+          break search
+        }
+      }
+    }
+    break
+  }
+  return answer
+}
+"${first_pair([1, 4, 6, 9], 10)} ${first_pair([1, 2], 10)}"
+"#,
+    );
+    assert_eq!(v, "0,3 none");
+}
+
+#[test]
+fn unknown_and_duplicate_labels_are_rejected() {
+    assert_eq!(error_code("loop outer: i in 0..3 { break inner }"), "E3048");
+    assert_eq!(error_code("loop i in 0..3 { continue outer }"), "E3048");
+    assert_eq!(error_code("loop a: i in 0..3 { loop a: j in 0..3 { } }"), "E3048");
+    // 函数体中看不到外层循环的标签
+    assert_eq!(
+        error_code("loop outer: i in 0..3 {\n  fun f(): Void { loop forever { break outer } }\n}"),
+        "E3048"
+    );
+    assert_eq!(error_code("break outer"), "E3034");
+    let err = PawScript::new().eval_str("loop outer: i in 0..3 { break outre }").unwrap_err();
+    assert!(err.to_string().contains("outer"), "{}", err);
+    // 同级的两个循环可以使用同一个标签
+    assert_eq!(eval_string("let n: Int = 0\nloop a: i in 0..2 { n = n + 1 }\nloop a: i in 0..2 { break a }\nn"), "2");
+}
+
+#[test]
+fn a_name_on_the_next_line_is_not_a_label() {
+    let v = eval_string("let n: Int = 0\nlet outer: Int = 7\nloop forever {\n  break\n  outer\n}\nouter");
+    assert_eq!(v, "7");
+}
@@ -0,0 +1,111 @@
+// tests/maps.rs
+//
+// Map<K, V>：字面量、下标读写、insert / remove 原地修改接收者并返回修改后的 Map，
+// 读取不存在的键报 E6029，按插入顺序遍历
+
+mod common;
+
+use pawc::PawScript;
+use common::{error_code, eval_string, failure};
+
+#[test]
+fn literals_indexing_and_iteration() {
+    let v = eval_string(
+        r#"
+let ages: Map<String, Int> = {"bob": 3, "amy": 5}
+ages["cat"] = 7
+ages["bob"] = ages["bob"] + 1
+let names: String = ""
+loop (name, age) in ages { names = names + name + "=" + age + " " }
+let empty: Map<Int, String> = {}
+"${names}${ages.length()} ${ages.contains_key("amy")} ${ages.keys()} ${ages.values()} ${empty.length()}"
+"#,
+    );
+    assert_eq!(v, "bob=4 amy=5 cat=7 3 true [bob, amy, cat] [4, 5, 7] 0");
+}
+
+#[test]
+fn insert_and_remove_modify_the_receiver() {
+    let v = eval_string(
+        r#"
+let m: Map<String, Int> = {"a": 1}
+let before: Map<String, Int> = m
+m.insert("b", 2)
+m.remove("a")
+let returned: Map<String, Int> = m.insert("c", 3)
+record Config { opts: Map<String, Int> }
+let cfg = Config { opts: {"seed": 1} }
+cfg.opts.insert("depth", 2)
+let all: Array<Map<String, Int>> = [{"x": 1}]
+all[0].insert("y", 2)
+"${m} ${returned} ${before} ${cfg.opts} ${all}"
+"#,
+    );
+    assert_eq!(v, "{b: 2, c: 3} {b: 2, c: 3} {a: 1} {seed: 1, depth: 2} [{x: 1, y: 2}]");
+    // 赋回原变量的旧写法仍然可用
+    assert_eq!(eval_string("let m: Map<Int, Int> = {}\nm = m.insert(1, 1).insert(2, 4)\nm"), "{1: 1, 2: 4}");
+}
+
+#[test]
+fn missing_keys_are_runtime_errors() {
+    let err = PawScript::new()
+        .eval_str("let m: Map<String, Int> = {\"a\": 1}\nlet v: Int = m[\"zz\"]\nsay v + 1")
+        .unwrap_err();
+    assert_eq!(err.code(), "E6029");
+    assert_eq!(err.message(), "Key \"zz\" is not in the map");
+    assert_eq!(err.location().1, 2);
+    let v = eval_string(
+        r#"
+let m: Map<Int, String> = {1: "one"}
+let got: String = "?"
+if m.contains_key(2) { got = m[2] }
+sniff { got = got + m[3] } snatch (e) { got = got + " " + e }
+got
+"#,
+    );
+    assert_eq!(v, "? Key 3 is not in the map");
+}
+
+#[test]
+fn keys_and_values_are_type_checked() {
+    assert_eq!(error_code("let m: Map<String, Int> = {\"a\": \"b\"}"), "E3003");
+    assert_eq!(error_code("let m: Map<String, Int> = {}\nm.insert(1, 2)"), "E3022");
+    assert_eq!(error_code("let m: Map<String, Int> = {}\nsay m[1]"), "E3011");
+    assert_eq!(error_code("say {1.5: 1}"), "E3026");
+    // 注解中的键类型同样受限
+    assert_eq!(error_code("let m: Map<Double, Int> = {}"), "E3026");
+    assert_eq!(error_code("fun f(xs: Array<Map<Float, Int>>): Int { return 1 }"), "E3026");
+}
+
+#[test]
+fn key_type_hint_lists_every_hashable_type() {
+    let expected = "Int, Long, Bool, Char, String, an enum, a Range, Any, or a tuple of these";
+    let (_, _, _, hint) = failure("say {1.5: 1}");
+    assert_eq!(hint, format!("Map keys must be {}", expected));
+    let (_, _, _, hint) = failure("let s: Set<Double> = Set {}");
+    assert_eq!(hint, format!("Set elements must be {}", expected));
+    // 提示中列出的类型确实可以作为键
+    let v = eval_string(
+        r#"
+enum Dir { Up, Down }
+let m: Map<(Dir, Range<Int>), Int> = {(Dir.Up, 0..2): 1}
+"${m[(Dir.Up, 0..2)]}"
+"#,
+    );
+    assert_eq!(v, "1");
+}
+
+#[test]
+fn every_hashable_type_can_be_a_key() {
+    let v = eval_string(
+        r#"
+let a: Map<Long, Int> = {1L: 1}
+let b: Map<Bool, Int> = {true: 1, false: 0}
+let c: Map<Char, Int> = {'x': 3}
+let d: Map<(Int, String), Int> = {(1, "a"): 4}
+fun first_key<K, V>(m: Map<K, V>): K { return m.keys()[0] }
+"${a[1L]} ${b[false]} ${c['x']} ${d[(1, "a")]} ${first_key(c)}"
+"#,
+    );
+    assert_eq!(v, "1 0 3 4 x");
+}
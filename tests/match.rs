@@ -0,0 +1,174 @@
+// tests/match.rs
+//
+// `match value { pattern => { ... } }`：字面量、类型、record 解构与元组模式，
+// 以及检查器给出的穷尽性警告
+
+mod common;
+
+use common::{error_code, eval_string, warning_codes};
+
+#[test]
+fn literal_arms_pick_the_first_match() {
+    let v = eval_string(
+        r#"
+fun name(n: Int): String {
+  match n {
+    0 => { return "zero" }
+    1, 2 => { return "small" }
+    -1 => { return "minus one" }
+    2 => { return "never" }
+    _ => { return "many" }
+  }
+}
+fun greet(word: String): String {
+  let out: String = "?"
+  match word {
+    "woof" => { out = "dog" }
+    "meow" => { out = "cat" }
+    _ => {}
+  }
+  return out
+}
+let big: Long = 1L
+let size: String = ""
+match big {
+  1 => { size = "one" }
+  _ => { size = "other" }
+}
+"${name(0)} ${name(2)} ${name(-1)} ${name(7)} ${greet("meow")} ${greet("moo")} ${size}"
+"#,
+    );
+    assert_eq!(v, "zero small minus one many cat ? one");
+}
+
+#[test]
+fn type_patterns_bind_the_tested_type() {
+    let v = eval_string(
+        r#"
+fun describe(item: Any): String {
+  match item {
+    n: Int => { return "int ${n * 2}" }
+    s: String => { return "string of ${s.length()}" }
+    _: Array<Int> => { return "ints" }
+    _ => { return "other" }
+  }
+}
+"${describe(21)} ${describe("paws")} ${describe([1, 2])} ${describe('x')}"
+"#,
+    );
+    assert_eq!(v, "int 42 string of 4 ints other");
+}
+
+#[test]
+fn record_patterns_destructure_fields() {
+    let v = eval_string(
+        r#"
+protocol Shape { name: String }
+record Circle: Shape { name: String  r: Int }
+record Square: Shape { name: String  side: Int }
+fun area(shape: Shape): Int {
+  match shape {
+    Circle { r: 0 } => { return 0 }
+    Circle { r } => { return 3 * r * r }
+    Square { side: s } => { return s * s }
+  }
+  return -1
+}
+let c: Shape = Circle { name: "c", r: 2 }
+let dot: Shape = Circle { name: "dot", r: 0 }
+let sq: Shape = Square { name: "sq", side: 3 }
+"${area(c)} ${area(dot)} ${area(sq)}"
+"#,
+    );
+    assert_eq!(v, "12 0 9");
+}
+
+#[test]
+fn tuple_and_optional_patterns() {
+    let v = eval_string(
+        r#"
+fun quadrant(p: (Int, Int)): String {
+  match p {
+    (0, 0) => { return "origin" }
+    (0, _) => { return "y axis" }
+    (x, 0) => { return "x axis at ${x}" }
+    _ => { return "plane" }
+  }
+}
+fun pick(x: Int?): String {
+  match x {
+    nopaw => { return "none" }
+    0 => { return "zero" }
+    n: Int => { return "got ${n}" }
+  }
+}
+"${quadrant((0, 0))} ${quadrant((0, 5))} ${quadrant((4, 0))} ${quadrant((1, 1))} ${pick(nopaw)} ${pick(0)} ${pick(9)}"
+"#,
+    );
+    assert_eq!(v, "origin y axis x axis at 4 plane none zero got 9");
+}
+
+#[test]
+fn match_in_loops_and_without_a_matching_arm() {
+    let v = eval_string(
+        r#"
+let total: Int = 0
+loop i in 0..10 {
+  match i % 3 {
+    0 => { total = total + 1 }
+    1 => { continue }
+  }
+  if i == 8 { break }
+  total = total + 10
+}
+total
+"#,
+    );
+    assert_eq!(v, "53");
+}
+
+#[test]
+fn warns_about_missing_and_unreachable_arms() {
+    let src = r#"
+let n: Int = 3
+match n {
+  1 => { say "one" }
+}
+"#;
+    assert_eq!(warning_codes(src), vec!["W3002"]);
+    let src = r#"
+let n: Int = 3
+match n {
+  x => { say x }
+  1 => { say "one" }
+}
+"#;
+    assert_eq!(warning_codes(src), vec!["W3003"]);
+    let src = r#"
+let b: Bool = true
+let o: Int? = nopaw
+match b {
+  true => { say "yes" }
+  false => { say "no" }
+}
+match o {
+  nopaw => { say "none" }
+  v: Int => { say v }
+}
+"#;
+    assert!(warning_codes(src).is_empty());
+}
+
+#[test]
+fn rejects_patterns_that_cannot_match() {
+    assert_eq!(error_code("let n: Int = 1\nmatch n { \"one\" => { } }"), "E3045");
+    assert_eq!(error_code("let n: Int = 1\nmatch n { nopaw => { } }"), "E3045");
+    assert_eq!(error_code("let n: Int = 1\nmatch n { x, 2 => { } }"), "E3045");
+    assert_eq!(error_code("let t: (Int, Int) = (1, 2)\nmatch t { (a, b, c) => { } }"), "E3045");
+    assert_eq!(error_code("let n: Int = 1\nmatch n { s: String => { } }"), "E3044");
+    assert_eq!(
+        error_code("record P { x: Int }\nlet p: P = P { x: 1 }\nmatch p { P { y } => { } }"),
+        "E3015"
+    );
+    assert_eq!(error_code("let n: Int = 1\nmatch n { Q { x } => { } }"), "E3028");
+}
@@ -0,0 +1,44 @@
+// tests/math.rs
+//
+// 标准库模块 `math`：import 时不读取文件，成员按各自的类型检查
+
+mod common;
+
+use pawc::Value;
+use common::{error_code, eval};
+
+#[test]
+fn numeric_functions_keep_the_argument_type() {
+    assert_eq!(eval("import math\nmath.abs(-3)"), Value::Int(3));
+    assert_eq!(eval("import math\nmath.abs(-2.5)"), Value::Double(2.5));
+    assert_eq!(eval("import math\nmath.floor(7)"), Value::Int(7));
+    assert_eq!(eval("import math\nmath.ceil(2.1)"), Value::Double(3.0));
+    assert_eq!(eval("import math\nmath.round(-2.5)"), Value::Double(-3.0));
+    // 结果可以直接参与同类型运算
+    assert_eq!(eval("import math\nlet n: Int = math.max(10, 20) + 1\nn"), Value::Int(21));
+}
+
+#[test]
+fn min_and_max_widen_mixed_arguments() {
+    assert_eq!(eval("import math\nmath.max(3, 7L)"), Value::Long(7));
+    assert_eq!(eval("import math\nmath.min(2, 1.5)"), Value::Double(1.5));
+    assert_eq!(eval("import math\nmath.min(2, 3)"), Value::Int(2));
+}
+
+#[test]
+fn double_functions_and_constants() {
+    assert_eq!(eval("import math\nmath.sqrt(16)"), Value::Double(4.0));
+    assert_eq!(eval("import math\nmath.pow(2, 10)"), Value::Double(1024.0));
+    assert_eq!(eval("import math as m\nm.pi"), Value::Double(std::f64::consts::PI));
+    assert_eq!(eval("import math\nmath.e * 1.0"), Value::Double(std::f64::consts::E));
+}
+
+#[test]
+fn members_are_type_checked() {
+    assert_eq!(error_code("import math\nmath.sqrt(\"4\")"), "E3033");
+    assert_eq!(error_code("import math\nmath.abs(true)"), "E3033");
+    assert_eq!(error_code("import math\nmath.max(1)"), "E3032");
+    assert_eq!(error_code("import math\nmath.tau"), "E3021");
+    assert_eq!(error_code("import math\nmath.pi()"), "E3021");
+    assert_eq!(error_code("import math\nlet s: String = math.pi"), "E3003");
+}
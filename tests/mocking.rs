@@ -0,0 +1,131 @@
+// tests/mocking.rs
+//
+// 测试中的确定输入：`mock_ask` 预先写好 `ask` 的回答，`mock_time` 固定 `time.now_millis()`，
+// `seed_random` 与 `pawc test --seed` 固定 `random` 与 `forall` 的随机数；宿主通过 `Host` 做同样的事
+
+mod common;
+
+use pawc::{Host, PawScript, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use common::{error_code, eval_string};
+
+#[test]
+fn scripted_answers_replace_console_input() {
+    let v = eval_string(
+        r#"
+mock_ask(["Mochi", "3", ""])
+let name: String <- ask "Name? "
+let lives: Int <- ask "Lives? "
+let nick: String? <- ask "Nickname? "
+"${name} ${lives + 1} ${nick}"
+"#,
+    );
+    assert_eq!(v, "Mochi 4 Nopaw");
+    // 回答用完后报错，而不是等待控制台输入
+    assert_eq!(error_code("mock_ask([\"a\"])\nlet a: String <- ask \"\"\nlet b: String <- ask \"\""), "E6027");
+    // 沙箱不允许读取控制台，但允许预先写好的回答
+    let mut paw = PawScript::new();
+    paw.set_sandbox(true);
+    assert_eq!(paw.eval_str("let a: String <- ask \"\"").unwrap_err().code(), "E6015");
+    let v = paw.eval_str("mock_ask([\"paw\"])\nlet b: String <- ask \"\"\nb").unwrap();
+    assert_eq!(v, Value::String("paw".to_string()));
+}
+
+#[test]
+fn the_clock_can_be_frozen() {
+    let v = eval_string(
+        "import time\nmock_time(1700000000000L)\nlet a: Long = time.now_millis()\n\
+         let b: Long = time.now_millis()\n\"${a} ${a == b}\"",
+    );
+    assert_eq!(v, "1700000000000 true");
+    // 被导入的模块看到同一个时钟
+    assert_eq!(eval_string("import time\nmock_time(5)\ntime.now_millis()"), "5");
+}
+
+const RANDOM: &str = r#"
+import random
+let out: Array<Int> = []
+loop i in 0..8 { out.push(random.int(0, 1000)) }
+let seen: Array<Int> = []
+forall(gen_int(0, 1000000), fun(n: Int): Bool {
+  seen.push(n)
+  return true
+}, 5)
+"${out} ${seen} ${random.choice(["a", "b", "c", "d"])}"
+"#;
+
+#[test]
+fn seeded_random_numbers_repeat() {
+    let seeded = |seed: i32| eval_string(&format!("seed_random({})\n{}", seed, RANDOM));
+    assert_eq!(seeded(7), seeded(7));
+    assert_ne!(seeded(7), seeded(8));
+    // 重新设置种子时序列从头开始
+    let v = eval_string(
+        "import random\nseed_random(1)\nlet a: Int = random.int(0, 1000000)\n\
+         seed_random(1)\nlet b: Int = random.int(0, 1000000)\na == b",
+    );
+    assert_eq!(v, "true");
+}
+
+#[test]
+fn mocks_are_type_checked() {
+    assert_eq!(error_code("mock_ask([1, 2])"), "E3033");
+    assert_eq!(error_code("mock_ask(\"yes\")"), "E3033");
+    assert_eq!(error_code("mock_time(1.5)"), "E3033");
+    assert_eq!(error_code("seed_random(\"42\")"), "E3033");
+    assert_eq!(error_code("seed_random()"), "E3032");
+}
+
+#[test]
+fn hosts_provide_the_same_hooks() {
+    let host = Arc::new(Host::new());
+    host.script_answers(["Mochi", "Tama"]);
+    host.set_clock(42);
+    host.seed_random(3);
+    let mut paw = PawScript::new();
+    paw.set_host(host.clone());
+    let src = "import time\nlet a: String <- ask \"\"\nlet b: String <- ask \"\"\n\"${a}+${b}@${time.now_millis()}\"";
+    assert_eq!(paw.eval_str(src).unwrap(), Value::String("Mochi+Tama@42".to_string()));
+
+    // 同一个种子在不同的实例中产生同样的随机数
+    let roll = |seed: u64| {
+        let host = Arc::new(Host::new());
+        host.seed_random(seed);
+        let mut paw = PawScript::new();
+        paw.set_host(host);
+        paw.eval_str("import random\n[random.int(0, 1000000), random.int(0, 1000000)]").unwrap()
+    };
+    assert_eq!(roll(11), roll(11));
+}
+
+#[test]
+fn pawc_test_seed_makes_runs_reproducible() {
+    let dir = std::env::temp_dir().join(format!("pawc-mocking-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let script: PathBuf = dir.join("dice_test.paw");
+    fs::write(
+        &script,
+        "import random\nfun test_roll(): Void {\n  let n: Int = random.int(0, 1000000)\n  \
+         assert(false, \"rolled ${n}\")\n}\n",
+    )
+    .unwrap();
+    let run = |seed: &str| {
+        let out = Command::new(env!("CARGO_BIN_EXE_pawc"))
+            .args(["test", "--seed", seed])
+            .arg(&script)
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap();
+        assert!(!out.status.success());
+        let text = String::from_utf8_lossy(&out.stdout).to_string() + &String::from_utf8_lossy(&out.stderr);
+        let start = text.find("rolled ").unwrap_or_else(|| panic!("{}", text));
+        text[start..].lines().next().unwrap().to_string()
+    };
+    assert_eq!(run("5"), run("5"));
+    assert_ne!(run("5"), run("6"));
+    let _ = fs::remove_dir_all(&dir);
+}
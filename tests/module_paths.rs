@@ -0,0 +1,100 @@
+// tests/module_paths.rs
+//
+// 模块路径：按字面规范化、不同写法的同一文件只加载一次、大小写必须与磁盘上的文件名一致；
+// 末尾是 Windows 专有的路径写法
+
+mod common;
+
+use pawc::{PawScript, ValueInner};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use common::write_files;
+
+fn script_at(path: &Path) -> PawScript {
+    PawScript::with_file(path.to_str().unwrap())
+}
+
+#[test]
+fn dotted_and_parent_segments_in_the_importer_are_normalized() {
+    let dir = write_files(
+        "paths",
+        "normalize",
+        &[
+            ("v1.2/lib/util.paw", "let x: Int = 7\n"),
+            ("v1.2/lib/helper.paw", "import util\nlet y: Int = (util.x as Int) + 1\n"),
+            ("v1.2/main.paw", ""),
+        ],
+    );
+    // 入口文件写成 `v1.2/./sub/../main.paw`，目录名中的点也不影响扩展名
+    let entry = dir.join("v1.2").join(".").join("sub").join("..").join("main.paw");
+    let mut paw = script_at(&entry);
+    paw.eval_str("import lib.util\nimport lib.helper").unwrap();
+    let util = paw.get("util").unwrap();
+    let helper = paw.get("helper").unwrap();
+    let ValueInner::Module(members) = &*helper.0 else { panic!("not a module") };
+    // helper 以 `import util` 导入的是同一个已缓存的模块
+    assert!(Arc::ptr_eq(&members["util"].0, &util.0));
+    assert_eq!(paw.eval_str("helper.y").unwrap(), pawc::Value::Int(8));
+}
+
+#[test]
+fn module_names_must_match_the_case_on_disk() {
+    let dir = write_files(
+        "paths",
+        "case",
+        &[("Util.paw", "let x: Int = 1\n"), ("Lib/tool.paw", "let y: Int = 2\n"), ("main.paw", "")],
+    );
+    let err = script_at(&dir.join("main.paw")).eval_str("import util").unwrap_err();
+    assert_eq!(err.code(), "E1002");
+    let message = err.to_string();
+    assert!(message.contains("Util.paw"), "{}", message);
+    assert!(message.contains("case-sensitive"), "{}", message);
+
+    let err = script_at(&dir.join("main.paw")).eval_str("import lib.tool").unwrap_err();
+    assert_eq!(err.code(), "E1002");
+    assert!(err.to_string().contains("Lib"), "{}", err);
+
+    let v = script_at(&dir.join("main.paw"))
+        .eval_str("import Util\nimport Lib.tool\n(Util.x as Int) + (tool.y as Int)")
+        .unwrap();
+    assert_eq!(v, pawc::Value::Int(3));
+}
+
+#[test]
+fn the_import_check_reports_case_mismatches_before_running() {
+    let dir = write_files("paths", "check", &[("Util.paw", "let x: Int = 1\n"), ("main.paw", "say 1\nimport util\n")]);
+    let out = Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .arg(dir.join("main.paw"))
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(out.stdout.is_empty(), "{}", String::from_utf8_lossy(&out.stdout));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("E1002") && stderr.contains("Util.paw"), "{}", stderr);
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_separators_and_verbatim_paths_resolve_to_one_module() {
+    let dir = write_files("paths", "windows", &[("lib\\util.paw", "let x: Int = 5\n"), ("main.paw", "")]);
+    // 正斜杠与反斜杠混用
+    let mixed = format!("{}/main.paw", dir.display());
+    let mut paw = PawScript::with_file(&mixed);
+    paw.eval_str("import lib.util").unwrap();
+
+    // canonicalize 得到 `\\?\C:\...`，这种路径中的 `..` 不会被系统处理
+    let verbatim = fs::canonicalize(&dir).unwrap().join("lib").join("..").join("main.paw");
+    let mut other = script_at(&verbatim);
+    assert_eq!(other.eval_str("import lib.util\nutil.x").unwrap(), pawc::Value::Int(5));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_rejects_imports_that_only_differ_in_case() {
+    // NTFS 大小写不敏感，不检查的话这里会直接加载 Util.paw
+    let dir = write_files("paths", "windows-case", &[("Util.paw", "let x: Int = 1\n"), ("main.paw", "")]);
+    let err = script_at(&dir.join("main.paw")).eval_str("import util").unwrap_err();
+    assert_eq!(err.code(), "E1002");
+}
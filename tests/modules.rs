@@ -0,0 +1,138 @@
+// tests/modules.rs
+//
+// 模块导入：同一次运行中每个模块只加载一次，循环导入报 E1005；
+// 会话中修改过的模块可以重新加载
+
+mod common;
+
+use pawc::{Value, ValueInner};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use common::{script_in, write_files};
+
+#[test]
+fn module_is_loaded_once() {
+    let dir = write_files(
+        "modules",
+        "cache",
+        &[
+            ("util.paw", "let x: Int = 5\n"),
+            ("other.paw", "import util\nlet y: Int = 1\n"),
+            ("main.paw", ""),
+        ],
+    );
+    let mut paw = script_in(&dir, "main.paw");
+    paw.eval_str("import util\nimport other\nimport util as again")
+        .unwrap();
+    let first = paw.get("util").unwrap();
+    let second = paw.get("again").unwrap();
+    assert!(matches!(&*first.0, ValueInner::Module(m) if m.contains_key("x")));
+    assert!(Arc::ptr_eq(&first.0, &second.0));
+}
+
+#[test]
+fn circular_import_is_reported() {
+    let dir = write_files(
+        "modules",
+        "cycle",
+        &[
+            ("a.paw", "import b\n"),
+            ("b.paw", "import a\n"),
+            ("main.paw", ""),
+        ],
+    );
+    let err = script_in(&dir, "main.paw").eval_str("import a").unwrap_err();
+    assert_eq!(err.code(), "E1005");
+    let message = err.to_string();
+    assert!(message.contains("a.paw -> "), "{}", message);
+}
+
+#[test]
+fn importing_the_entry_script_is_circular() {
+    let dir = write_files("modules", "self", &[("main.paw", "import main\n")]);
+    let err = script_in(&dir, "main.paw").eval_str("import main").unwrap_err();
+    assert_eq!(err.code(), "E1005");
+}
+
+#[test]
+fn module_functions_can_be_called() {
+    let dir = write_files(
+        "modules",
+        "call",
+        &[("util.paw", "fun twice(n: Int): Int { return n * 2 }\n"), ("main.paw", "")],
+    );
+    let v = script_in(&dir, "main.paw")
+        .eval_str("import util\nutil.twice(21)")
+        .unwrap();
+    assert_eq!(v, pawc::Value::Int(42));
+}
+
+#[test]
+fn module_constants_can_be_read() {
+    let dir = write_files("modules", "field", &[("util.paw", "let answer: Int = 42\n"), ("main.paw", "")]);
+    let v = script_in(&dir, "main.paw")
+        .eval_str("import util\nutil.answer")
+        .unwrap();
+    assert_eq!(v, pawc::Value::Int(42));
+}
+
+/// 改写模块文件，并把修改时间推后，避免文件系统时间精度不足时看不出改动
+fn rewrite(path: &Path, src: &str, seconds: u64) {
+    fs::write(path, src).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(seconds))
+        .unwrap();
+}
+
+const COUNTER: &str = r#"
+let count: Int = 0
+fun bump(): Int {
+  count = count + 1
+  return count
+}
+fun version(): String { return "v1" }
+"#;
+
+#[test]
+fn changed_modules_are_reloaded_into_the_session() {
+    let dir = write_files(
+        "modules",
+        "reload",
+        &[
+            ("counter.paw", COUNTER),
+            ("wrapper.paw", "import counter\nfun describe(): Any { return counter.version() }\n"),
+            ("main.paw", ""),
+        ],
+    );
+    let mut paw = script_in(&dir, "main.paw");
+    paw.eval_str("import counter\nimport wrapper\nlet kept: Int = counter.bump()")
+        .unwrap();
+    assert_eq!(paw.eval_str("counter.bump()").unwrap(), Value::Int(2));
+    assert!(paw.reload_modules().unwrap().is_empty());
+
+    rewrite(&dir.join("counter.paw"), &COUNTER.replace("v1", "v2"), 10);
+    let reloaded = paw.reload_modules().unwrap();
+    assert_eq!(reloaded.len(), 1);
+    assert!(reloaded[0].ends_with("counter.paw"));
+    // 模块的状态重新开始，导入它的会话与模块看到新版本，会话自己的变量保持不变
+    let v = paw
+        .eval_str(r#""${counter.version()} ${wrapper.describe()} ${counter.bump()} ${kept}""#)
+        .unwrap();
+    assert_eq!(v, Value::String("v2 v2 1 1"));
+    assert!(paw.reload_modules().unwrap().is_empty());
+}
+
+#[test]
+fn a_broken_change_keeps_the_old_module() {
+    let dir = write_files("modules", "reload-broken", &[("counter.paw", COUNTER), ("main.paw", "")]);
+    let mut paw = script_in(&dir, "main.paw");
+    paw.eval_str("import counter").unwrap();
+    rewrite(&dir.join("counter.paw"), "let count: Int = \"zero\"\n", 10);
+    assert_eq!(paw.reload_modules().unwrap_err().code(), "E3003");
+    assert_eq!(paw.eval_str("counter.version()").unwrap(), Value::String("v1"));
+}
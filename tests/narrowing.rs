@@ -0,0 +1,134 @@
+// tests/narrowing.rs
+//
+// `e is T`：运行时类型测试；`if x is T { ... }` 与 `x is T && ...` 中把 x 收窄为 T，
+// 收窄只影响读取，赋入其它类型的值后失效
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+#[test]
+fn tests_runtime_types() {
+    let v = eval_string(
+        r#"
+fun kind(item: Any): String {
+  if item is Int { return "int" }
+  if item is String { return "string" }
+  if item is Array<Int> { return "ints" }
+  if item is (Int, Char) { return "pair" }
+  if item is Result<Int, String> { return "result" }
+  if item is Int? { return "nopaw" }
+  return "other"
+}
+"${kind(1)} ${kind("two")} ${kind(3L)} ${kind([4, 5])} ${kind(["6"])} ${kind(nopaw)} ${kind((6, 'x'))} ${kind(ok(7))} ${kind(err(8))}"
+"#,
+    );
+    assert_eq!(v, "int string other ints other nopaw pair result other");
+}
+
+#[test]
+fn if_body_sees_the_tested_type() {
+    let v = eval_string(
+        r#"
+fun describe(x: Any): String {
+  if x is String && x.length() > 3 {
+    return "long " + x
+  }
+  if x is Int {
+    let doubled: Int = x * 2
+    return "${doubled}"
+  }
+  return "?"
+}
+let maybe: Int? = 4
+let total: Int = 0
+if maybe is Int { total = maybe + 1 }
+"${describe("whiskers")} ${describe("cat")} ${describe(21)} ${total}"
+"#,
+    );
+    assert_eq!(v, "long whiskers ? 42 5");
+}
+
+#[test]
+fn records_are_tested_by_name() {
+    let v = eval_string(
+        r#"
+protocol Shape { area: fun(): Int }
+record Square: Shape { side: Int  area: fun(): Int }
+record Dot: Shape { area: fun(): Int }
+let sq: Square = Square { side: 3, area: fun(): Int { return 9 } }
+let shapes: Array<Shape> = [sq, Dot { area: fun(): Int { return 0 } }]
+let sides: Int = 0
+loop s in shapes {
+  if s is Square { sides = sides + s.side }
+}
+sides
+"#,
+    );
+    assert_eq!(v, "3");
+}
+
+#[test]
+fn narrowing_stays_inside_the_if_body() {
+    assert_eq!(
+        error_code("let x: Any = 1\nif x is Int { say x + 1 } else { say x + 1 }"),
+        "E3014"
+    );
+    assert_eq!(error_code("let x: Any = 1\nif x is Int { say 1 }\nsay x + 1"), "E3014");
+    assert_eq!(error_code("let x: Any = 1\nif x is Int || true { say x + 1 }"), "E3014");
+}
+
+#[test]
+fn assignments_use_the_declared_type() {
+    // 收窄不限制赋值；赋入别的类型后，之后的读取回到声明的类型
+    let v = eval_string(
+        r#"
+let v: Any = 5
+if v is Int {
+  say v + 1
+  v = "s"
+}
+let o: Int? = 3
+if o is Int { o = nopaw }
+let n: Any = 1
+if n is Int {
+  n = 7
+  n = n + 1
+}
+"${v} ${o} ${n}"
+"#,
+    );
+    assert_eq!(v, "s Nopaw 8");
+    assert_eq!(error_code("let v: Any = 5\nif v is Int {\n  v = \"s\"\n  say v + 1\n}"), "E3014");
+    assert_eq!(error_code("let v: Any = 5\nif v is Int {\n  if true { v = \"s\" }\n  say v + 1\n}"), "E3014");
+    // 循环体后面的赋值在下一轮之前生效
+    assert_eq!(
+        error_code("let v: Any = 5\nif v is Int {\n  loop i in 0..3 {\n    say v + 1\n    v = \"s\"\n  }\n}"),
+        "E3014"
+    );
+    assert_eq!(eval_string("let v: Any = 5\nif v is Int {\n  loop i in 0..3 { v = v + i }\n}\nv"), "8");
+    // 赋值仍然按声明的类型检查
+    assert_eq!(error_code("let o: Int? = 3\nif o is Int { o = \"s\" }"), "E3003");
+}
+
+#[test]
+fn impossible_and_untestable_types_are_rejected() {
+    assert_eq!(error_code("let n: Int = 1\nsay n is String"), "E3044");
+    assert_eq!(error_code("let f: Any = 1\nsay f is fun(): Int"), "E3044");
+    assert_eq!(
+        error_code("protocol P { n: Int }\nlet x: Any = 1\nsay x is P"),
+        "E3044"
+    );
+    assert_eq!(error_code("let x: Any = 1\nsay x is Missing"), "E3028");
+}
+
+#[test]
+fn host_values_can_be_tested() {
+    let mut paw = PawScript::new();
+    paw.define("port", Value::Int(8080));
+    assert_eq!(
+        paw.eval_str("port is Int && !(port is Long)").unwrap(),
+        Value::Bool(true)
+    );
+}
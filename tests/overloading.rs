@@ -0,0 +1,112 @@
+// tests/overloading.rs
+//
+// 函数重载：同一作用域中同名的 fun 按参数个数或参数类型区分，
+// 检查器与运行时按相同的规则选择重载
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+const DESCRIBE: &str = r#"
+fun describe(n: Int): String { return "int ${n}" }
+fun describe(s: String): String { return "string ${s}" }
+fun describe(n: Int, unit: String): String { return "${n} ${unit}" }
+fun describe(x: Double): String { return "double ${x}" }
+"#;
+
+#[test]
+fn calls_pick_by_arity_and_types() {
+    let v = eval_string(&format!(
+        "{}\"${{describe(1)}}|${{describe(\"a\")}}|${{describe(2, \"kg\")}}|${{describe(1.5)}}\"",
+        DESCRIBE
+    ));
+    assert_eq!(v, "int 1|string a|2 kg|double 1.5");
+    // 没有完全符合的重载时接受数值转换
+    let v = eval_string("fun half(x: Double): Double { return x / 2.0 }\nfun half(s: String): String { return s }\nhalf(3)");
+    assert_eq!(v, "1.5");
+}
+
+#[test]
+fn overloads_work_in_nested_scopes_and_recursion() {
+    let v = eval_string(
+        r#"
+fun total(xs: Array<Int>): Int {
+  return total(xs, 0)
+}
+fun total(xs: Array<Int>, start: Int): Int {
+  if start >= xs.length() { return 0 }
+  return xs[start] + total(xs, start + 1)
+}
+fun outer(): String {
+  fun pad(s: String): String { return "[${s}]" }
+  fun pad(n: Int): String { return pad("${n}") }
+  return pad(7)
+}
+"${total([1, 2, 3])} ${outer()}"
+"#,
+    );
+    assert_eq!(v, "6 [7]");
+    // 循环中重复执行的声明替换自身，不会累积
+    let v = eval_string(
+        r#"
+let out: String = ""
+loop i in 0..3 {
+  fun f(n: Int): Int { return n + i }
+  fun f(s: String): String { return s }
+  out = out + "${f(10)}${f("-")}"
+}
+out
+"#,
+    );
+    assert_eq!(v, "10-11-12-");
+}
+
+#[test]
+fn ambiguous_or_missing_overloads_are_errors() {
+    // 参数类型完全相同仍是重复定义
+    assert_eq!(error_code("fun f(a: Int): Int { return a }\nfun f(b: Int): Int { return b }"), "E2005");
+    // 与变量同名不能构成重载
+    assert_eq!(error_code("let f: Int = 1\nfun f(a: String): Int { return 1 }"), "E2005");
+    assert_eq!(error_code(&format!("{}describe(true)", DESCRIBE)), "E3033");
+    assert_eq!(error_code(&format!("{}describe(1, 2, 3)", DESCRIBE)), "E3032");
+    // 同分的调用：Long 既可以转换为 Int 也可以转换为 Double
+    assert_eq!(error_code(&format!("{}let big: Long = 5L\ndescribe(big)", DESCRIBE)), "E3049");
+    let src = "fun g(a: Int, b: Long): Int { return 1 }\nfun g(a: Long, b: Int): Int { return 2 }\ng(1, 2)";
+    assert_eq!(error_code(src), "E3049");
+    // 重载不能作为值使用
+    assert_eq!(error_code(&format!("{}let d: Any = describe", DESCRIBE)), "E3049");
+}
+
+#[test]
+fn overloads_must_be_distinguishable_at_run_time() {
+    let src = r#"
+record A { x: Int }
+record B { x: Int }
+fun f(a: A): Int { return 1 }
+fun f(b: B): Int { return 2 }
+"#;
+    assert_eq!(error_code(src), "E3049");
+    assert_eq!(error_code("fun f(a: Any): Int { return 1 }\nfun f(a: Int): Int { return 2 }"), "E3049");
+    assert_eq!(error_code("fun f(a: Int = 1): Int { return a }\nfun f(s: String): Int { return 2 }"), "E3049");
+    // record 参数可以出现在其它位置
+    let src = r#"
+record P { x: Int }
+fun show(p: P, n: Int): String { return "n" }
+fun show(p: P, s: String): String { return "s" }
+let p: P = P { x: 1 }
+"${show(p, 1)}${show(p, "x")}"
+"#;
+    assert_eq!(eval_string(src), "ns");
+}
+
+#[test]
+fn host_calls_and_any_arguments_dispatch_at_run_time() {
+    let mut paw = PawScript::new();
+    paw.eval_str(DESCRIBE).unwrap();
+    assert_eq!(paw.call_function("describe", vec!["x".into()]).unwrap().to_string(), "string x");
+    let v = paw.eval_str("let a: Any = 3\ndescribe(a)").unwrap();
+    assert_eq!(v.to_string(), "int 3");
+    let err = paw.call_function("describe", vec![Value::Bool(true)]).unwrap_err();
+    assert_eq!(err.code(), "E3033");
+}
@@ -0,0 +1,79 @@
+// tests/package_imports.rs
+//
+// 目录模块：`import utils` 在 utils.paw 不存在时读取 utils/mod.paw 或 utils/index.paw，
+// 检查器与解释器按同样的规则解析
+
+mod common;
+
+use std::process::Command;
+use common::{script_in, write_files};
+
+#[test]
+fn a_directory_with_mod_paw_is_a_module() {
+    let dir = write_files(
+        "packages",
+        "mod",
+        &[
+            ("utils/mod.paw", "import strings\nlet base: Int = 40\nlet name: String = strings.name\n"),
+            // 包内的 import 相对包目录解析
+            ("utils/strings.paw", "let name: String = \"utils\"\n"),
+            ("main.paw", ""),
+        ],
+    );
+    let v = script_in(&dir, "main.paw").eval_str("import utils\n\"${utils.name} ${(utils.base as Int) + 2}\"").unwrap();
+    assert_eq!(v.to_string(), "utils 42");
+    // 包内的文件也可以直接导入
+    let v = script_in(&dir, "main.paw").eval_str("import utils.strings\nstrings.name").unwrap();
+    assert_eq!(v.to_string(), "utils");
+}
+
+#[test]
+fn index_paw_and_resolution_order() {
+    let dir = write_files(
+        "packages",
+        "order",
+        &[
+            ("web/index.paw", "let from: String = \"index\"\n"),
+            ("both/mod.paw", "let from: String = \"mod\"\n"),
+            ("both/index.paw", "let from: String = \"index\"\n"),
+            ("lib.paw", "let from: String = \"file\"\n"),
+            ("lib/mod.paw", "let from: String = \"mod\"\n"),
+            ("main.paw", ""),
+        ],
+    );
+    let v = script_in(&dir, "main.paw")
+        .eval_str("import web\nimport both\nimport lib\n\"${web.from} ${both.from} ${lib.from}\"")
+        .unwrap();
+    assert_eq!(v.to_string(), "index mod file");
+}
+
+#[test]
+fn missing_packages_name_every_candidate() {
+    let dir = write_files("packages", "missing", &[("empty/readme.txt", "not a module"), ("main.paw", "")]);
+    let err = script_in(&dir, "main.paw").eval_str("import empty").unwrap_err();
+    assert_eq!(err.code(), "E1002");
+    let text = err.to_string();
+    assert!(text.contains("empty.paw"), "{}", text);
+    assert!(text.contains("mod.paw") && text.contains("index.paw"), "{}", text);
+
+    let dir = write_files("packages", "case", &[("utils/mod.paw", "let x: Int = 1\n"), ("main.paw", "")]);
+    assert_eq!(script_in(&dir, "main.paw").eval_str("import Utils").unwrap_err().code(), "E1002");
+}
+
+#[test]
+fn the_import_check_reads_package_entries() {
+    let dir = write_files(
+        "packages",
+        "check",
+        &[("pkg/mod.paw", "let x: Int = \"oops\"\n"), ("main.paw", "say 1\nimport pkg\n")],
+    );
+    let out = Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .arg(dir.join("main.paw"))
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(out.stdout.is_empty(), "{}", String::from_utf8_lossy(&out.stdout));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("E3003") && stderr.contains("mod.paw"), "{}", stderr);
+}
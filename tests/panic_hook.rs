@@ -0,0 +1,30 @@
+// tests/panic_hook.rs
+//
+// 内部 panic 以 E9000 报告：在子进程中重新运行本测试，安装 panic hook 后主动 panic，
+// 检查标准错误中是内部错误报告而不是 Rust 的原始输出
+
+use pawc::error::panic::install_hook;
+use std::env;
+use std::process::Command;
+
+const CHILD: &str = "PAWC_PANIC_HOOK_CHILD";
+
+#[test]
+fn internal_panic_is_reported_as_e9000() {
+    if env::var_os(CHILD).is_some() {
+        install_hook();
+        panic!("panic requested by the test");
+    }
+    let out = Command::new(env::current_exe().unwrap())
+        .args(["internal_panic_is_reported_as_e9000", "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD, "1")
+        .env_remove("RUST_BACKTRACE")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("[E9000] Internal Error"), "{}", stderr);
+    assert!(stderr.contains("pawc crashed unexpectedly: panic requested by the test"), "{}", stderr);
+    assert!(stderr.contains("Please report it"), "{}", stderr);
+    assert!(!stderr.contains("panicked at"), "{}", stderr);
+}
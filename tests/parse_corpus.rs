@@ -0,0 +1,62 @@
+// tests/parse_corpus.rs
+//
+// 语法一致性语料：tests/corpus/valid 下的文件必须能解析，invalid 下的必须以语法错误拒绝，
+// 任何文件都不允许让语法分析器 panic
+
+use pawc::fuzz::{parse_corpus, CorpusOutcome};
+use pawc::PawError;
+use std::path::Path;
+
+fn corpus(kind: &str) -> Vec<(std::path::PathBuf, CorpusOutcome)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus").join(kind);
+    let results = parse_corpus(&dir).unwrap();
+    assert!(!results.is_empty(), "empty corpus {}", dir.display());
+    results
+}
+
+#[test]
+fn valid_programs_parse() {
+    for (path, outcome) in corpus("valid") {
+        assert!(
+            matches!(outcome, CorpusOutcome::Parsed),
+            "{}: {:?}",
+            path.display(),
+            outcome
+        );
+    }
+}
+
+#[test]
+fn invalid_programs_are_rejected() {
+    for (path, outcome) in corpus("invalid") {
+        match outcome {
+            CorpusOutcome::Rejected(err) => assert!(err.code().starts_with("E1"), "{}: {}", path.display(), err),
+            other => panic!("{}: {:?}", path.display(), other),
+        }
+    }
+}
+
+#[test]
+fn rejections_point_into_the_source() {
+    for (path, outcome) in corpus("invalid") {
+        match outcome {
+            CorpusOutcome::Rejected(PawError::Syntax { line, column, .. }) => {
+                assert!(line >= 1 && column >= 1, "{}: {}:{}", path.display(), line, column);
+            }
+            other => panic!("{}: {:?}", path.display(), other),
+        }
+    }
+}
+
+#[test]
+fn cli_summarises_the_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .arg("parse-corpus")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("0 panicked"), "{}", stdout);
+}
@@ -0,0 +1,139 @@
+// tests/playground.rs
+//
+// `pawc serve-playground`：POST /run 在沙箱子进程中执行源码，以 JSON 返回输出与状态
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+
+/// 测试结束时关闭服务进程
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn start(args: &[&str]) -> Server {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .args(["serve-playground", "--port", "0"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let addr = line.trim().rsplit("http://").next().unwrap().to_string();
+    Server { child, addr }
+}
+
+/// 发送一个请求，返回状态行与响应体
+fn request(server: &Server, method: &str, path: &str, body: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(&server.addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[test]
+fn runs_source_and_captures_output() {
+    let server = start(&[]);
+    let (status, body) = request(&server, "POST", "/run", "say \"hi\"\nsay 1 + 2");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(body.starts_with(r#"{"status":"ok","stdout":"hi\n3\n","stderr":"","exit_code":0,"#), "{}", body);
+}
+
+#[test]
+fn reports_errors_in_stderr() {
+    let server = start(&[]);
+    let (_, body) = request(&server, "POST", "/run", "let x: Int = \"a\"");
+    assert!(body.starts_with(r#"{"status":"error","#), "{}", body);
+    assert!(body.contains("[E3003]"), "{}", body);
+}
+
+#[test]
+fn imports_are_disabled() {
+    let server = start(&[]);
+    let (_, body) = request(&server, "POST", "/run", "import secrets");
+    assert!(body.contains("[E6015]"), "{}", body);
+}
+
+#[test]
+fn stops_scripts_at_the_time_limit() {
+    let server = start(&["--timeout-ms", "300"]);
+    let (_, body) = request(&server, "POST", "/run", "say \"start\"\nloop forever { }");
+    assert!(body.starts_with(r#"{"status":"timeout","stdout":"start\n","#), "{}", body);
+    assert!(body.contains(r#""exit_code":null"#), "{}", body);
+}
+
+#[test]
+fn stops_scripts_at_the_memory_limit() {
+    let server = start(&["--max-memory", "32"]);
+    let src = "let s: String = \"xxxxxxxx\"\nloop i in 0..40 { s = s + s }";
+    let (_, body) = request(&server, "POST", "/run", src);
+    assert!(body.starts_with(r#"{"status":"memory_limit","#), "{}", body);
+}
+
+#[test]
+fn truncates_long_output() {
+    let server = start(&["--max-output", "1"]);
+    let (_, body) = request(&server, "POST", "/run", "loop i in 0..1000 { say i }");
+    assert!(body.starts_with(r#"{"status":"ok","#), "{}", body);
+    assert!(body.ends_with(r#""truncated":true}"#), "{}", body);
+}
+
+#[test]
+fn rejects_other_routes() {
+    let server = start(&[]);
+    assert_eq!(request(&server, "GET", "/run", "").0, "HTTP/1.1 405 Method Not Allowed");
+    assert_eq!(request(&server, "GET", "/", "").0, "HTTP/1.1 404 Not Found");
+    let big = "say 1\n".repeat(20000);
+    assert_eq!(request(&server, "POST", "/run", &big).0, "HTTP/1.1 413 Payload Too Large");
+}
+
+#[test]
+fn slow_clients_time_out_without_blocking_the_workers() {
+    let server = start(&["--workers", "1", "--request-timeout-ms", "300"]);
+    // 只发一半请求头的客户端占着唯一的工作线程，超时后收到 408
+    let mut stalled = TcpStream::connect(&server.addr).unwrap();
+    write!(stalled, "POST /run HTTP/1.1\r\nHost: loc").unwrap();
+    let (status, body) = request(&server, "POST", "/run", "say 1");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(body.starts_with(r#"{"status":"ok","stdout":"1\n","#), "{}", body);
+    let mut response = String::new();
+    stalled.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 408 Request Timeout"), "{}", response);
+}
+
+#[test]
+fn requests_beyond_the_workers_wait_their_turn() {
+    let server = start(&["--workers", "2"]);
+    let server = &server;
+    std::thread::scope(|scope| {
+        let runs: Vec<_> = (0..6)
+            .map(|i| scope.spawn(move || request(server, "POST", "/run", &format!("say {}", i))))
+            .collect();
+        for (i, run) in runs.into_iter().enumerate() {
+            let (status, body) = run.join().unwrap();
+            assert_eq!(status, "HTTP/1.1 200 OK");
+            assert!(body.contains(&format!(r#""stdout":"{}\n""#, i)), "{}", body);
+        }
+    });
+}
@@ -0,0 +1,78 @@
+// tests/power.rs
+//
+// 乘方运算符 `**`：右结合、优先级高于乘除与负号，整数结果检查溢出
+
+mod common;
+
+use pawc::PawScript;
+use common::{error_code, eval_string};
+
+#[test]
+fn precedence_and_associativity() {
+    assert_eq!(eval_string("2 ** 3 ** 2"), "512");
+    assert_eq!(eval_string("2 * 3 ** 2"), "18");
+    assert_eq!(eval_string("-2 ** 2"), "-4");
+    assert_eq!(eval_string("(-2) ** 3"), "-8");
+    assert_eq!(eval_string("2 ** -1.0"), "0.5");
+    assert_eq!(eval_string("1 + 2 ** 2 == 5"), "true");
+}
+
+#[test]
+fn result_types_follow_the_operands() {
+    let v = eval_string(
+        r#"
+let a: Int = 3 ** 4
+let b: Long = 2L ** 40
+let c: Long = 10 ** 2L
+let d: Double = 2.0 ** 0.5
+let e: Double = 4 ** 0.5
+let f: Double = 1.5f ** 2
+"${a} ${b} ${c} ${d > 1.414 && d < 1.415} ${e} ${f}"
+"#,
+    );
+    assert_eq!(v, "81 1099511627776 100 true 2.0 2.25");
+    assert_eq!(eval_string("\"${inspect(2 ** 3)} ${inspect(2 ** 3L)} ${inspect(2.0f ** 1.0f)}\""), "Int 8 Long 8 Double 2.0");
+    assert_eq!(error_code("let s: String = 2 ** 3"), "E3003");
+    assert_eq!(error_code("\"a\" ** 2"), "E3014");
+}
+
+#[test]
+fn integer_edge_cases() {
+    assert_eq!(eval_string("0 ** 0"), "1");
+    assert_eq!(eval_string("(-1) ** 1000001"), "-1");
+    assert_eq!(eval_string("1 ** 99999999"), "1");
+    assert_eq!(eval_string("2 ** 30"), "1073741824");
+    assert_eq!(eval_string("2L ** 62"), "4611686018427387904");
+}
+
+#[test]
+fn overflow_and_negative_exponents_fail() {
+    assert_eq!(error_code("2 ** 31"), "E6021");
+    assert_eq!(error_code("2L ** 63"), "E6021");
+    assert_eq!(error_code("let k: Int = 10\n3 ** (k * 10)"), "E6021");
+    assert_eq!(error_code("2 ** -1"), "E6021");
+    let v = eval_string(
+        r#"
+let out: String = ""
+loop i in 29..33 {
+  sniff {
+    out = out + "${2 ** i} "
+  } snatch (e) {
+    out = out + "overflow "
+  }
+}
+out
+"#,
+    );
+    assert_eq!(v, "536870912 1073741824 overflow overflow ");
+}
+
+#[test]
+fn loops_in_the_vm_match_the_tree_walker() {
+    let src = "let total: Long = 0L\nloop i in 0..20 { total = total + 3L ** i }\ntotal";
+    let mut paw = PawScript::new();
+    paw.set_vm(false);
+    let walked = paw.eval_str(src).unwrap();
+    assert_eq!(walked.to_string(), eval_string(src));
+    assert_eq!(walked.to_string(), "1743392200");
+}
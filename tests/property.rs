@@ -0,0 +1,111 @@
+// tests/property.rs
+//
+// 属性测试：`gen_*` 创建生成器，`forall` 用生成的输入调用属性，
+// 失败时把输入缩小到仍然失败的最简单输入并报 E6026
+
+mod common;
+
+use pawc::{PawError, PawScript};
+use common::{error_code, eval_string};
+
+/// 运行应当失败的属性测试，返回错误信息与提示
+fn failure(src: &str) -> (String, String) {
+    match PawScript::new().eval_str(src) {
+        Err(err @ PawError::Runtime { .. }) => {
+            assert_eq!(err.code(), "E6026", "{}", err);
+            let hint = err.hint().unwrap_or_default().to_string();
+            (err.message(), hint)
+        }
+        other => panic!("expected a failing property:\n{}\n{:?}", src, other),
+    }
+}
+
+#[test]
+fn passing_properties_run_every_case() {
+    let v = eval_string(
+        r#"
+let calls: Int = 0
+forall(gen_int(-5, 5), fun(n: Int): Bool {
+  calls = calls + 1
+  return n >= -5 && n <= 5
+})
+forall(gen_array(gen_string(8), 10), fun(xs: Array<String>): Void {
+  calls = calls + 1
+  assert(xs.length() <= 10)
+  loop s in xs { assert(s.length() <= 8) }
+}, 25)
+forall(gen_pair(gen_double(0.5, 1.5), gen_one_of(["a", "b"])), fun(p: (Double, String)): Bool {
+  calls = calls + 1
+  let (d, s): (Double, String) = p
+  return d >= 0.5 && d <= 1.5 && (s == "a" || s == "b")
+}, 0)
+forall(gen_bool(), fun(b: Bool): Bool { return b || !b })
+calls
+"#,
+    );
+    assert_eq!(v, "125");
+}
+
+#[test]
+fn failing_inputs_are_shrunk() {
+    // 整数缩小到恰好失败的边界
+    let (message, hint) = failure("forall(gen_int(0, 100000), fun(n: Int): Bool { return n < 37 }, 1000)");
+    assert!(message.contains("for input 37"), "{}", message);
+    assert_eq!(hint, "The property returned false");
+
+    // 范围不含 0 时向最接近 0 的边界缩小
+    let (message, _) = failure("forall(gen_int(-900, -10), fun(n: Int): Bool { return n > -500 }, 1000)");
+    assert!(message.contains("for input -500"), "{}", message);
+
+    // 数组先变短再简化元素
+    let (message, _) = failure(
+        r#"
+forall(gen_array(gen_int(0, 100), 30), fun(xs: Array<Int>): Void {
+  loop x in xs {
+    if x >= 50 { bark "found ${x}" }
+  }
+}, 1000)
+"#,
+    );
+    assert!(message.contains("for input [50]"), "{}", message);
+
+    let (message, hint) = failure(
+        r#"forall(gen_string(20), fun(s: String): Bool { return s.length() < 3 }, 1000)"#,
+    );
+    assert!(message.contains("for input \"aaa\""), "{}", message);
+    assert_eq!(hint, "The property returned false");
+}
+
+#[test]
+fn failures_can_be_caught_and_report_the_reason() {
+    let v = eval_string(
+        r#"
+let caught: String = ""
+sniff {
+  forall(gen_one_of([1, 2, 3]), fun(n: Int): Void { assert(n != 3, "three") }, 1000)
+} snatch (e) { caught = e }
+caught
+"#,
+    );
+    assert!(v.starts_with("Property failed after") && v.ends_with("for input 3"), "{}", v);
+}
+
+#[test]
+fn generators_and_properties_are_checked() {
+    assert_eq!(
+        eval_string("let g: Gen<Array<Int>> = gen_array(gen_int(0, 9), 3)\ninspect(g)"),
+        "Gen gen_array(gen_int(0, 9), 3)"
+    );
+    assert_eq!(error_code("gen_int(0, \"9\")"), "E3033");
+    assert_eq!(error_code("gen_array(5, 3)"), "E3033");
+    assert_eq!(error_code("let g: Gen<String> = gen_int(0, 9)"), "E3003");
+    assert_eq!(error_code("forall(gen_int(0, 9), fun(s: String): Bool { return true })"), "E3033");
+    assert_eq!(error_code("forall(gen_int(0, 9), fun(n: Int): Int { return n })"), "E3033");
+    assert_eq!(error_code("forall([1, 2], fun(n: Int): Bool { return true })"), "E3033");
+    assert_eq!(error_code("forall(gen_bool())"), "E3032");
+    // 空范围、负长度与负次数在运行时报错
+    assert_eq!(error_code("gen_int(5, 1)"), "E6013");
+    assert_eq!(error_code("gen_string(-1)"), "E6013");
+    assert_eq!(error_code("let xs: Array<Int> = []\ngen_one_of(xs)"), "E6013");
+    assert_eq!(error_code("forall(gen_bool(), fun(b: Bool): Bool { return true }, -1)"), "E6013");
+}
@@ -0,0 +1,109 @@
+// tests/protocols.rs
+//
+// protocol 声明：record 用 `record R: P` 声明遵循，检查器要求成员齐全且类型一致；
+// protocol 名可以作为类型使用，函数类型的字段可以像方法一样调用
+
+mod common;
+
+use common::{error_code, eval};
+
+const SHAPE: &str = r#"
+protocol Shape {
+  name: String
+  fun area(): Double
+}
+"#;
+
+#[test]
+fn conforming_record_runs() {
+    let src = format!(
+        r#"{}
+record Square: Shape {{
+  name: String
+  side: Double
+  area: fun(): Double
+}}
+fun square(side: Double): Square {{
+  return Square {{ name: "square", side: side, area: fun(): Double {{ return side * side }} }}
+}}
+let s: Square = square(3.0)
+"${{s.name}} ${{s.area()}}"
+"#,
+        SHAPE
+    );
+    assert_eq!(eval(&src).to_string(), "square 9.0");
+}
+
+#[test]
+fn protocols_may_be_declared_after_use() {
+    let src = "record Tag: Named { name: String }\nprotocol Named { name: String }\nTag { name: \"a\" }.name";
+    assert_eq!(eval(src).to_string(), "a");
+}
+
+#[test]
+fn missing_or_mistyped_members_are_rejected() {
+    let missing = format!("{}record Bad: Shape {{ name: String }}", SHAPE);
+    assert_eq!(error_code(&missing), "E3038");
+    let mistyped = format!(
+        "{}record Bad: Shape {{ name: String\n area: fun(): Int }}",
+        SHAPE
+    );
+    assert_eq!(error_code(&mistyped), "E3038");
+}
+
+#[test]
+fn unknown_and_duplicate_protocols_are_rejected() {
+    assert_eq!(error_code("record R: Nope { x: Int }"), "E3028");
+    assert_eq!(
+        error_code("protocol P { x: Int }\nprotocol P { y: Int }"),
+        "E2005"
+    );
+    assert_eq!(error_code("protocol P { x: Nope }"), "E3028");
+}
+
+#[test]
+fn only_function_fields_can_be_called() {
+    let src = "record R { x: Int }\nlet r: R = R { x: 1 }\nr.x()";
+    assert_eq!(error_code(src), "E3021");
+    let src = "record R { f: fun(Int): Int }\nlet r: R = R { f: fun(n: Int): Int { return n + 1 } }\nr.f(\"a\")";
+    assert_eq!(error_code(src), "E3033");
+}
+
+#[test]
+fn protocols_are_usable_as_types() {
+    let src = r#"
+protocol Drawable {
+  name: String
+  fun draw(): String
+}
+record Circle: Drawable { name: String, r: Double, draw: fun(): String }
+record Dot: Drawable { name: String, draw: fun(): String }
+fun circle(r: Double): Circle {
+  return Circle { name: "circle", r: r, draw: fun(): String { return "(${r})" } }
+}
+fun render(d: Drawable): String { return d.name + d.draw() }
+let shapes: Array<Drawable> = [circle(1.5), Dot { name: "dot", draw: fun(): String { return "." } }]
+let out: String = ""
+loop s in shapes { out = out + render(s) + " " }
+let first: Drawable? = nopaw
+first = shapes[0]
+out + render(circle(2.5))
+"#;
+    assert_eq!(eval(src).to_string(), "circle(1.5) dot. circle(2.5)");
+}
+
+#[test]
+fn protocol_types_only_accept_conforming_records() {
+    let base = "protocol P { x: Int }\nrecord R { x: Int }\nrecord S: P { x: Int, y: Int }\n";
+    assert_eq!(error_code(&format!("{}let p: P = R {{ x: 1 }}", base)), "E3003");
+    assert_eq!(
+        error_code(&format!("{}let ps: Array<P> = [S {{ x: 1, y: 2 }}, R {{ x: 1 }}]", base)),
+        "E3010"
+    );
+    // 通过 protocol 类型只能访问协议要求的成员
+    assert_eq!(
+        error_code(&format!("{}let p: P = S {{ x: 1, y: 2 }}\np.y", base)),
+        "E3015"
+    );
+    assert_eq!(error_code(&format!("{}P {{ x: 1 }}", base)), "E3016");
+}
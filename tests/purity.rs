@@ -0,0 +1,125 @@
+// tests/purity.rs
+//
+// `@pure` 函数：检查器验证函数没有副作用（E3051，指向出问题的语句或表达式），
+// 没有标注的函数与方法会被推断，解释器缓存纯函数的结果
+
+mod common;
+
+use pawc::{PawScript, Session, Value};
+use common::eval_string;
+
+/// 检查失败时的错误码、说明与位置
+fn violation(src: &str) -> (&'static str, String, usize, usize) {
+    let err = PawScript::new()
+        .eval_str(src)
+        .err()
+        .unwrap_or_else(|| panic!("script should fail:\n{}", src));
+    let (_, line, col) = err.location();
+    (err.code(), err.message(), line, col)
+}
+
+#[test]
+fn pure_functions_run_and_cache_results() {
+    let v = eval_string(
+        r#"
+import math
+import string
+record Point {
+  x: Double
+  y: Double
+  fun norm(): Double { return math.sqrt(self.x * self.x + self.y * self.y) }
+}
+fun square(n: Int): Int { return n * n }
+@pure
+fun fib(n: Int): Long {
+  if n < 2 { return n as Long }
+  return fib(n - 1) + fib(n - 2)
+}
+@pure
+fun summary(p: Point, n: Int): String {
+  let parts: Array<String> = []
+  loop i in 0..n { parts.push("${square(i)}") }
+  let b = string.builder()
+  b.append(parts.join(","))
+  return "${p.norm()} ${b.to_string()}"
+}
+"${fib(90)} ${summary(Point { x: 3.0, y: 4.0 }, 4)}"
+"#,
+    );
+    // 不缓存时 fib(90) 要递归调用上万亿次
+    assert_eq!(v, "2880067194370816120 5.0 0,1,4,9");
+}
+
+#[test]
+fn effects_are_reported_where_they_happen() {
+    let (code, message, line, col) = violation("@pure\nfun f(n: Int): Int {\n  say n\n  return n\n}");
+    assert_eq!((code, line, col), ("E3051", 3, 3));
+    assert_eq!(message, "`@pure` function 'f' prints with `say`");
+
+    let (code, message, line, col) = violation("let rate: Int = 2\n@pure\nfun f(n: Int): Int { return n * rate }");
+    assert_eq!((code, line, col), ("E3051", 3, 33));
+    assert_eq!(message, "`@pure` function 'f' reads 'rate', which is defined outside the function");
+
+    let (_, message, line, _) =
+        violation("let count: Int = 0\n@pure\nfun f(n: Int): Int {\n  count = count + 1\n  return n\n}");
+    assert_eq!(line, 4);
+    assert_eq!(message, "`@pure` function 'f' assigns to 'count', which is defined outside the function");
+
+    let (_, message, _, _) = violation("import fs\n@pure\nfun f(p: String): Bool { return fs.exists(p) }");
+    assert_eq!(message, "`@pure` function 'f' uses the module 'fs'");
+    let (_, message, _, _) = violation("@pure\nfun f(): Int {\n  let n: Int <- ask \"n? \"\n  return n\n}");
+    assert_eq!(message, "`@pure` function 'f' reads input with `ask`");
+    let (_, message, _, _) = violation("@pure\nfun f(n: Int): Int {\n  sleep(1)\n  return n\n}");
+    assert_eq!(message, "`@pure` function 'f' calls 'sleep', which is not pure");
+}
+
+#[test]
+fn effects_of_callees_are_inferred() {
+    let (code, message, line, col) = violation(
+        "fun log(n: Int): Void { say n }\nfun twice(n: Int): Int {\n  log(n)\n  return n * 2\n}\n\
+         @pure\nfun f(n: Int): Int { return twice(n) }",
+    );
+    assert_eq!((code, line, col), ("E3051", 7, 29));
+    assert_eq!(message, "`@pure` function 'f' calls 'twice', which is not pure");
+    // 方法按名字推断
+    let (_, message, _, _) = violation(
+        "record Cat {\n  name: String\n  fun meow(): Void { say self.name }\n}\n\
+         @pure\nfun f(c: Cat): Int {\n  c.meow()\n  return 1\n}",
+    );
+    assert_eq!(message, "`@pure` function 'f' calls the method 'meow', which is not pure");
+    // 函数类型的参数效果未知
+    let (_, message, _, _) = violation("@pure\nfun apply(g: fun(Int): Int, n: Int): Int { return g(n) }");
+    assert_eq!(message, "`@pure` function 'apply' calls the function parameter 'g', whose effects are unknown");
+    // 互相递归的函数一起推断，局部的 lambda 与函数照常可用
+    let v = eval_string(
+        "fun even(n: Int): Bool { if n == 0 { return true }\n return odd(n - 1) }\n\
+         fun odd(n: Int): Bool { if n == 0 { return false }\n return even(n - 1) }\n\
+         @pure\nfun f(n: Int): Int {\n  let inc = fun(x: Int): Int { return x + 1 }\n  \
+         fun half(x: Int): Int { return x / 2 }\n  if even(n) { return half(n) }\n  return inc(n)\n}\n\
+         \"${f(10)} ${f(7)}\"",
+    );
+    assert_eq!(v, "5 8");
+}
+
+#[test]
+fn pure_marks_only_synchronous_functions() {
+    let err = PawScript::new().eval_str("@pure\nlet x: Int = 1").unwrap_err();
+    assert_eq!(err.code(), "E1001");
+    assert_eq!(err.message(), "`@pure` can only mark a function");
+    let (code, message, _, _) = violation("@pure\nasync fun f(): Int { return 1 }");
+    assert_eq!(code, "E3051");
+    assert_eq!(message, "`@pure` function 'f' is async");
+    let (_, message, _, _) = violation("@pure\nfun f(): Int {\n  spawn async { return 1 }\n  return 1\n}");
+    assert_eq!(message, "`@pure` function 'f' starts a task with `spawn`");
+}
+
+#[test]
+fn sessions_remember_which_functions_are_pure() {
+    let mut session = Session::new("<test>");
+    session.eval("fun square(n: Int): Int { return n * n }").unwrap();
+    session.eval("fun shout(n: Int): Int {\n  say n\n  return n\n}").unwrap();
+    session.eval("@pure\nfun f(n: Int): Int { return square(n) + 1 }").unwrap();
+    assert_eq!(session.eval("f(3)").unwrap(), Value::Int(10));
+    let err = session.eval("@pure\nfun g(n: Int): Int { return shout(n) }").unwrap_err();
+    assert_eq!(err.code(), "E3051");
+}
@@ -0,0 +1,52 @@
+// tests/random.rs
+//
+// 标准库模块 `random`：结果随机，测试只检查取值范围与类型
+
+mod common;
+
+use pawc::Value;
+use common::{error_code, eval};
+
+#[test]
+fn int_and_float_stay_in_range() {
+    let src = r#"
+import random
+let ok: Bool = true
+loop i in 0..200 {
+  let n: Int = random.int(-2, 3)
+  let f: Double = random.float()
+  ok = ok && n >= -2 && n < 3 && f >= 0.0 && f < 1.0
+}
+ok
+"#;
+    assert_eq!(eval(src), Value::Bool(true));
+}
+
+#[test]
+fn choice_and_shuffle_keep_the_elements() {
+    let src = r#"
+import random
+let xs: Array<Int> = [1, 2, 3, 4]
+let ys: Array<Int> = random.shuffle(xs)
+let sum: Int = 0
+loop y in ys { sum = sum + y }
+let c: Int = random.choice(xs)
+sum * 10 + ys.length() + xs[0] * 0 + (c - c)
+"#;
+    assert_eq!(eval(src), Value::Int(104));
+    assert_eq!(eval("import random\nrandom.choice([\"only\"])").to_string(), "only");
+}
+
+#[test]
+fn signatures_are_type_checked() {
+    assert_eq!(error_code("import random\nlet s: String = random.choice([1])"), "E3003");
+    assert_eq!(error_code("import random\nrandom.shuffle(\"abc\")"), "E3033");
+    assert_eq!(error_code("import random\nrandom.int(1)"), "E3032");
+    assert_eq!(error_code("import random\nrandom.coin()"), "E3021");
+}
+
+#[test]
+fn invalid_arguments_fail_at_runtime() {
+    assert_eq!(error_code("import random\nrandom.int(5, 5)"), "E6012");
+    assert_eq!(error_code("import random\nrandom.choice([])"), "E6012");
+}
@@ -0,0 +1,115 @@
+// tests/ranges.rs
+//
+// 范围值：`a..b`、`a..=b`、`step`，Long 端点，存入变量、作为参数传递与遍历
+
+mod common;
+
+use pawc::PawScript;
+use common::{both_ways, error_code, eval_string, failure};
+
+#[test]
+fn loops_with_steps_and_inclusive_ends() {
+    let collect = |range: &str| {
+        both_ways(&format!(
+            "let out: String = \"\"\nloop i in {} {{ out = out + \"${{i}} \" }}\nout",
+            range
+        ))
+    };
+    assert_eq!(collect("0..5"), "0 1 2 3 4 ");
+    assert_eq!(collect("0..=5"), "0 1 2 3 4 5 ");
+    assert_eq!(collect("0..10 step 3"), "0 3 6 9 ");
+    assert_eq!(collect("0..=9 step 3"), "0 3 6 9 ");
+    assert_eq!(collect("10..0 step -2"), "10 8 6 4 2 ");
+    assert_eq!(collect("10..=0 step -5"), "10 5 0 ");
+    assert_eq!(collect("5..0"), "");
+    assert_eq!(collect("1 + 1..2 * 3"), "2 3 4 5 ");
+}
+
+#[test]
+fn ranges_are_values() {
+    let v = both_ways(
+        r#"
+fun total(r: Range<Int>): Int {
+  let sum: Int = 0
+  loop i in r { sum = sum + i }
+  return sum
+}
+let evens: Range<Int> = 0..=10 step 2
+let down: Range<Int> = 3..0 step -1
+"${evens} ${down} ${0..3} ${total(evens)} ${total(1..4)} ${evens.length()} ${evens.to_array()}"
+"#,
+    );
+    assert_eq!(v, "0..=10 step 2 3..0 step -1 0..3 30 6 6 [0, 2, 4, 6, 8, 10]");
+    let v = eval_string(
+        r#"
+let r: Range<Int> = 0..100 step 7
+let tens: Range<Int> = 0..=100 step 10
+let same: Range<Int> = 0..100 step 7
+"${r.contains(14)} ${r.contains(15)} ${r.contains(100)} ${tens.contains(100)} ${r == same} ${r == tens} ${r is Range<Int>}"
+"#,
+    );
+    assert_eq!(v, "true false false true true false true");
+    assert_eq!(eval_string("inspect(1L..3)"), "Range<Long> 1..3");
+}
+
+#[test]
+fn long_bounds_yield_long_values() {
+    let v = both_ways(
+        r#"
+let big: Long = 3000000000L
+let out: String = ""
+loop n in big..=big + 2L { out = out + "${inspect(n)} " }
+let r: Range<Long> = 0..big step 1000000000
+loop n in r { out = out + "${n} " }
+out
+"#,
+    );
+    assert_eq!(v, "Long 3000000000 Long 3000000001 Long 3000000002 0 1000000000 2000000000 ");
+    // 最后一步越过 Long 的上限时也能正常结束
+    let v = both_ways(
+        "let n: Int = 0\nloop i in 9223372036854775800L..=9223372036854775807L step 3L { n = n + 1 }\nn",
+    );
+    assert_eq!(v, "3");
+}
+
+#[test]
+fn step_is_only_a_keyword_after_a_range() {
+    let v = eval_string("let step: Int = 2\nlet r: Range<Int> = 0..6 step step\nlet xs: Array<Int> = r.to_array()\nstep + xs.length()");
+    assert_eq!(v, "5");
+    let v = eval_string("let step: Int = 4\nlet r: Range<Int> = 0..step\nstep\nr.length()");
+    assert_eq!(v, "4");
+}
+
+#[test]
+fn bad_ranges_are_rejected() {
+    assert_eq!(error_code("loop i in 0..2.5 { }"), "E3008");
+    assert_eq!(error_code("let r: Range<Int> = \"a\"..\"z\""), "E3008");
+    assert_eq!(error_code("let r: Range<Int> = 0..10 step 0.5"), "E3008");
+    assert_eq!(error_code("let r: Range<Int> = 0L..10"), "E3003");
+    assert_eq!(error_code("let r: Range<String> = 0..10"), "E3028");
+    assert_eq!(error_code("let r: Range<Int> = 0..3\nr.push(1)"), "E3021");
+    assert_eq!(error_code("let r: Range<Int> = 0..3\nr.contains(\"a\")"), "E3025");
+    assert_eq!(error_code("let s: Int = 0\nloop i in 0..10 step s { }"), "E6022");
+    let mut paw = PawScript::new();
+    paw.set_vm(false);
+    assert_eq!(paw.eval_str("let s: Int = 0\nloop i in 0..10 step s { }").unwrap_err().code(), "E6022");
+    assert_eq!(error_code("let s: Int = 0\nlet r: Range<Int> = 1..2 step s"), "E6022");
+}
+
+#[test]
+fn non_int_bounds_are_errors_instead_of_empty_loops() {
+    let (code, message, position, hint) = failure("loop i in 1.5..9.5 { say i }");
+    assert_eq!((code, position), ("E3008", (1, 11)));
+    assert_eq!(message, "Range start must be Int or Long, found Double");
+    assert_eq!(hint, "Convert it with `as Int` or `as Long`");
+    // 经过 Any 的边界在运行时检查，两种执行方式都报错而不是跳过循环
+    let src = "let a: Any = 1.5\nloop i in a..3 { say i }";
+    for vm in [true, false] {
+        let mut paw = PawScript::new();
+        paw.set_vm(vm);
+        let err = paw.eval_str(src).unwrap_err();
+        assert_eq!(err.code(), "E3008");
+        assert_eq!(err.message(), "Range bounds and step must be Int or Long, found Double, Int");
+    }
+    assert_eq!(both_ways("let n: Int = 0\nloop i in 3..1 { n = n + 1 }\nn"), "0");
+}
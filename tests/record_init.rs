@@ -0,0 +1,45 @@
+// tests/record_init.rs
+//
+// record 初始化的字段诊断：重复字段报 E3029，缺少字段报 E3030（列出全部缺少的字段），
+// 不存在的字段报 E3015，并在尚未使用的字段中给出拼写建议
+
+mod common;
+
+use common::{eval_string, failure};
+
+const P: &str = "record P { x: Int  y: Int  z: Int }\n";
+
+#[test]
+fn fields_may_come_in_any_order() {
+    assert_eq!(eval_string(&format!("{}let p = P {{ z: 3, x: 1, y: 2 }}\n\"${{p.x}}${{p.y}}${{p.z}}\"", P)), "123");
+}
+
+#[test]
+fn duplicated_fields_are_reported() {
+    let (code, message, (line, _), hint) = failure(&format!("{}let p = P {{ x: 1, y: 2, x: 3, z: 4 }}", P));
+    assert_eq!((code, line), ("E3029", 2));
+    assert_eq!(message, "Field `x` specified twice");
+    assert_eq!(hint, "Remove one of the duplicated fields");
+}
+
+#[test]
+fn missing_fields_are_listed() {
+    let (code, message, position, _) = failure(&format!("{}let p = P {{ x: 1 }}", P));
+    assert_eq!((code, position), ("E3030", (2, 9)));
+    assert_eq!(message, "Missing fields `y`, `z` in record `P`");
+    let (_, message, _, _) = failure(&format!("{}let p = P {{ x: 1, z: 2 }}", P));
+    assert_eq!(message, "Missing field `y` in record `P`");
+}
+
+#[test]
+fn unknown_fields_suggest_unused_ones() {
+    let (code, message, (line, _), hint) = failure(&format!("{}let p = P {{ x: 1, yy: 2, z: 3 }}", P));
+    assert_eq!((code, line), ("E3015", 2));
+    assert_eq!(message, "Record `P` has no field `yy`");
+    assert_eq!(hint, "Did you mean `y`?");
+    // 相近的字段已经用过时不再建议它，列出还没用的字段
+    let (_, _, _, hint) = failure(&format!("{}let p = P {{ x: 1, y: 2, xxx: 3 }}", P));
+    assert_eq!(hint, "Unused fields: z");
+    let (_, _, _, hint) = failure(&format!("{}let p = P {{ x: 1, y: 2, z: 3, w: 4 }}", P));
+    assert_eq!(hint, "");
+}
@@ -0,0 +1,151 @@
+// tests/record_methods.rs
+//
+// record 中声明的方法：方法体中用 `self` 读取字段，`r.method(...)` 调用，
+// 方法可以满足 protocol 的要求，也可以不带括号取出为已绑定 `self` 的函数
+
+mod common;
+
+use pawc::{PawScript, Session};
+use std::fs;
+use common::{error_code, eval_string, failure};
+
+const POINT: &str = r#"
+record Point {
+  x: Double
+  y: Double
+  fun distance(other: Point): Double {
+    let dx: Double = self.x - other.x
+    let dy: Double = self.y - other.y
+    return (dx * dx + dy * dy) ** 0.5
+  }
+  fun moved(dx: Double, dy: Double): Point {
+    return Point { x: self.x + dx, y: self.y + dy }
+  }
+  fun describe(): String { return "(${self.x}, ${self.y})" }
+}
+"#;
+
+#[test]
+fn methods_see_self() {
+    let v = eval_string(&format!(
+        "{}let p: Point = Point {{ x: 0.0, y: 0.0 }}\nlet q: Point = Point {{ x: 3.0, y: 4.0 }}\np.distance(q)",
+        POINT
+    ));
+    assert_eq!(v, "5.0");
+    // 方法返回的 record 可以继续调用方法
+    let v = eval_string(&format!(
+        "{}Point {{ x: 1.0, y: 1.0 }}.moved(0.5, -1.0).describe()",
+        POINT
+    ));
+    assert_eq!(v, "(1.5, 0.0)");
+}
+
+#[test]
+fn methods_call_each_other_and_recurse() {
+    let v = eval_string(
+        r#"
+record Counter {
+  start: Int
+  fun sum_to(n: Int): Int {
+    if n <= self.start { return self.start }
+    return n + self.sum_to(n - 1)
+  }
+  fun twice(n: Int): Int { return self.sum_to(n) * 2 }
+}
+let c: Counter = Counter { start: 1 }
+c.twice(4)
+"#,
+    );
+    assert_eq!(v, "20");
+}
+
+#[test]
+fn methods_satisfy_protocols_and_bind_self() {
+    let v = eval_string(
+        r#"
+protocol Shape { fun area(): Double }
+record Square: Shape {
+  side: Double
+  fun area(): Double { return self.side * self.side }
+}
+record Circle: Shape {
+  r: Double
+  fun area(): Double { return 3.0 * self.r * self.r }
+}
+let shapes: Array<Shape> = [Square { side: 2.0 }, Circle { r: 1.0 }]
+let total: Double = 0.0
+loop s in shapes { total = total + s.area() }
+let later: fun(): Double = shapes[0].area
+"${total} ${later()}"
+"#,
+    );
+    assert_eq!(v, "7.0 4.0");
+}
+
+#[test]
+fn fields_of_self_cannot_be_assigned() {
+    // self 是接收者的副本，给它的字段或元素赋值会在方法返回后丢失，检查器直接拒绝
+    let src = "record Box {\n  n: Int\n  fun bump(): Void {\n    self.n = self.n + 1\n  }\n}";
+    let (code, message, position, _) = failure(src);
+    assert_eq!((code, position), ("E3053", (4, 5)));
+    assert!(message.contains("`self`"), "{}", message);
+    let nested = "record Bag {\n  items: Array<Int>\n  fun f(): Void {\n    let g: fun(): Void = fun() { self.items[0] = 1 }\n  }\n}";
+    assert_eq!(error_code(nested), "E3053");
+    // 修改 record 的方法返回新的 record；方法之外名为 self 的变量不受限制
+    let v = eval_string(
+        r#"
+record Box {
+  n: Int
+  fun bumped(): Box { return Box { n: self.n + 1 } }
+}
+let b: Box = Box { n: 1 }
+let c: Box = b.bumped()
+let self: Box = Box { n: 5 }
+self.n = 6
+"${b.n} ${c.n} ${self.n}"
+"#,
+    );
+    assert_eq!(v, "1 2 6");
+}
+
+#[test]
+fn bodies_and_calls_are_checked() {
+    assert_eq!(error_code(&format!("{}Point {{ x: 1.0, y: 2.0 }}.size()", POINT)), "E3021");
+    assert_eq!(error_code(&format!("{}Point {{ x: 1.0, y: 2.0 }}.moved(1.0)", POINT)), "E3032");
+    assert_eq!(error_code(&format!("{}Point {{ x: 1.0, y: 2.0 }}.distance(1)", POINT)), "E3033");
+    assert_eq!(error_code("record R {\n  n: Int\n  fun bad(): Int { return self.missing }\n}"), "E3015");
+    assert_eq!(error_code("record R {\n  n: Int\n  fun get(): String { return self.n }\n}"), "E3004");
+    // 方法不能与字段或另一个方法同名，也不能有默认值
+    assert_eq!(error_code("record R {\n  n: Int\n  fun n(): Int { return 1 }\n}"), "E2005");
+    assert_eq!(
+        error_code("record R {\n  n: Int\n  fun f(): Int { return 1 }\n  fun f(): Int { return 2 }\n}"),
+        "E2005"
+    );
+    assert_eq!(error_code("record R {\n  n: Int\n  fun f(k: Int = 1): Int { return k }\n}"), "E1001");
+    // self 只在方法中可见
+    assert_eq!(error_code(&format!("{}self.x", POINT)), "E4001");
+}
+
+#[test]
+fn methods_travel_with_values() {
+    let dir = std::env::temp_dir().join(format!("pawc-record-methods-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("geo.paw"),
+        "record Vec2 {\n  x: Int\n  y: Int\n  fun sum(): Int { return self.x + self.y }\n}\n\
+         let origin: Vec2 = Vec2 { x: 2, y: 5 }\nfun total(v: Vec2): Int { return v.sum() }\n",
+    )
+    .unwrap();
+    let main = dir.join("main.paw");
+    let v = PawScript::with_file(main.to_str().unwrap())
+        .eval_str("import geo\ngeo.total(geo.origin)")
+        .unwrap();
+    assert_eq!(v.to_string(), "7");
+
+    // 会话中后一次执行可以调用前一次声明的方法
+    let mut session = Session::new("<test>");
+    session.eval("record Pair {\n  a: Int\n  b: Int\n  fun max(): Int {\n    if self.a > self.b { return self.a }\n    return self.b\n  }\n}").unwrap();
+    session.eval("let p: Pair = Pair { a: 3, b: 9 }").unwrap();
+    assert_eq!(session.eval("p.max()").unwrap().to_string(), "9");
+}
@@ -0,0 +1,50 @@
+// tests/recursive_records.rs
+//
+// record 是按名字区分的类型：字段可以引用 record 自身或之后声明的 record，链表、树等结构能通过检查；
+// 字段相同的两个 record 互不兼容，字段里引用未声明的类型报 E3028
+
+mod common;
+
+use common::{error_code, eval_string};
+
+#[test]
+fn records_can_refer_to_themselves() {
+    let v = eval_string(
+        r#"
+record Node {
+  value: Int
+  next: Node?
+}
+let last = Node { value: 2, next: nopaw }
+let head = Node { value: 1, next: last }
+fun sum(n: Node?): Int {
+  if n is Node { return n.value + sum(n.next) }
+  return 0
+}
+record Tree { kids: Array<Tree> }
+let t = Tree { kids: [Tree { kids: [] }, Tree { kids: [] }] }
+"${sum(head)} ${t.kids.length()} ${head.next is Node}"
+"#,
+    );
+    assert_eq!(v, "3 2 true");
+}
+
+#[test]
+fn records_can_refer_to_each_other() {
+    let v = eval_string(
+        r#"
+record Even { odd: Odd? }
+record Odd { even: Even? }
+let e = Even { odd: Odd { even: Even { odd: nopaw } } }
+"${e.odd is Odd}"
+"#,
+    );
+    assert_eq!(v, "true");
+}
+
+#[test]
+fn records_are_distinct_by_name() {
+    assert_eq!(error_code("record A { x: Int }\nrecord B { x: Int }\nlet b: B = A { x: 1 }"), "E3003");
+    assert_eq!(error_code("record Node { value: Int  next: Node? }\nlet n = Node { value: 1, next: 5 }"), "E3017");
+    assert_eq!(error_code("record A { next: Missing? }"), "E3028");
+}
@@ -0,0 +1,118 @@
+// tests/results.rs
+//
+// Result<T, E>：ok / err 构造、方法，以及 `expr?` 从当前函数提前返回 err
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+const PARSE: &str = r#"
+fun parse_age(s: String): Result<Int, String> {
+  if s == "" { return err("empty") }
+  return ok(s.to_int())
+}
+fun total(a: String, b: String): Result<Int, String> {
+  let x: Int = parse_age(a)?
+  let y: Int = parse_age(b)?
+  return ok(x + y)
+}
+"#;
+
+#[test]
+fn question_mark_returns_the_first_error() {
+    let v = eval_string(&format!(
+        "{}\n\"${{total(\"3\", \"4\")}} ${{total(\"\", \"4\")}} ${{total(\"1\", \"\")}}\"",
+        PARSE
+    ));
+    assert_eq!(v, "ok(7) err(empty) err(empty)");
+}
+
+#[test]
+fn result_methods() {
+    let v = eval_string(&format!(
+        r#"{}
+let good: Result<Int, String> = total("1", "2")
+let bad: Result<Int, String> = total("", "2")
+"${{good.is_ok()}} ${{bad.is_err()}} ${{good.unwrap()}} ${{bad.unwrap_or(-1)}} ${{bad.error()}} ${{good.error()}}"
+"#,
+        PARSE
+    ));
+    assert_eq!(v, "true true 3 -1 empty Nopaw");
+    assert_eq!(
+        error_code(&format!("{}\nsay total(\"\", \"\").unwrap()", PARSE)),
+        "E6018"
+    );
+}
+
+#[test]
+fn propagation_is_not_caught_by_snatch_and_runs_lastly() {
+    let v = eval_string(&format!(
+        r#"{}
+let log: Array<String> = []
+fun guarded(s: String): Result<Int, String> {{
+  sniff {{
+    let n: Int = parse_age(s)?
+    return ok(n)
+  }} snatch (e) {{
+    log.push("caught")
+  }} lastly {{
+    log.push("lastly")
+  }}
+  return ok(0)
+}}
+"${{guarded("")}} ${{guarded("5")}} ${{log}}"
+"#,
+        PARSE
+    ));
+    assert_eq!(v, "err(empty) ok(5) [lastly, lastly]");
+}
+
+#[test]
+fn works_in_lambdas_and_async_functions() {
+    let v = eval_string(&format!(
+        r#"{}
+let twice: fun(String): Result<Int, String> = fun(s: String): Result<Int, String> {{
+  return ok(parse_age(s)? * 2)
+}}
+async fun load(s: String): Result<Int, String> {{
+  return parse_age(s)
+}}
+async fun sum(a: String, b: String): Result<Int, String> {{
+  return ok((await load(a))? + (await load(b))?)
+}}
+"${{twice("4")}} ${{twice("")}} ${{await sum("1", "2")}} ${{await sum("1", "")}}"
+"#,
+        PARSE
+    ));
+    assert_eq!(v, "ok(8) err(empty) ok(3) err(empty)");
+}
+
+#[test]
+fn question_mark_is_type_checked() {
+    assert_eq!(error_code("let x: Int = ok(1)?"), "E3042");
+    assert_eq!(error_code("fun f(): Int {\n  return ok(1)?\n}"), "E3042");
+    assert_eq!(
+        error_code("fun f(): Result<Int, Int> {\n  let x: Int = err(\"s\")?\n  return ok(x)\n}"),
+        "E3042"
+    );
+    assert_eq!(
+        error_code("fun f(): Result<Int, String> {\n  let x: Int = 1?\n  return ok(x)\n}"),
+        "E3042"
+    );
+    assert_eq!(
+        error_code("fun f(): Result<Int, String> {\n  let x: String = ok(1)?\n  return ok(1)\n}"),
+        "E3003"
+    );
+    assert_eq!(error_code(r#"let r: Result<Int, String> = ok("s")"#), "E3003");
+    assert_eq!(error_code("let r: Result<Int, String> = ok(1)\nsay r.unwrap_or(\"x\")"), "E3025");
+}
+
+#[test]
+fn hosts_can_pass_results_in() {
+    let mut paw = PawScript::new();
+    paw.define("answer", Value::Ok(Value::Int(42)));
+    paw.define("failure", Value::Err(Value::String("nope")));
+    assert_eq!(paw.eval_str("answer.unwrap()").unwrap(), Value::Int(42));
+    assert_eq!(paw.eval_str("failure.is_err()").unwrap(), Value::Bool(true));
+}
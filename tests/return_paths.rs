@@ -0,0 +1,61 @@
+// tests/return_paths.rs
+//
+// 声明了返回类型的函数必须在所有路径上 return 或 bark，否则报 E3031；
+// 函数以缺少 `else` 的 if/else-if 链结尾时，列出未覆盖的条件
+
+mod common;
+
+use common::{eval_string, failure};
+
+#[test]
+fn every_path_returns_or_barks() {
+    let v = eval_string(
+        r#"
+fun a(n: Int): Int {
+  if n > 0 { return 1 } else { bark "no" }
+}
+fun b(): Int {
+  loop forever { return 2 }
+}
+fun c(n: Int): Int? {
+  if n > 0 { return 3 }
+}
+enum Color { Red  Green }
+fun d(c: Color): Int {
+  match c { Color.Red => { return 4 } Color.Green => { return 5 } }
+}
+"${a(1)} ${b()} ${c(0)} ${d(Color.Green)}"
+"#,
+    );
+    assert_eq!(v, "1 2 Nopaw 5");
+}
+
+#[test]
+fn missing_returns_are_reported() {
+    let (code, message, position, hint) = failure("fun f(n: Int): Int {\n  say n\n}");
+    assert_eq!((code, position), ("E3031", (1, 1)));
+    assert_eq!(message, "Function 'f' may finish without returning a value of type Int");
+    assert_eq!(hint, "Make sure every path ends with `return` or `bark`");
+    let (code, message, ..) =
+        failure("fun f(n: Int): Int {\n  if n > 0 { return 1 } else if n < 0 { say 1 } else { return 0 }\n}");
+    assert_eq!(code, "E3031");
+    assert!(message.starts_with("Function 'f' may finish"), "{}", message);
+}
+
+#[test]
+fn if_chains_without_else_list_the_conditions() {
+    let (code, message, position, hint) =
+        failure("fun sign(n: Int): String {\n  if n > 0 { return \"pos\" } else if n < 0 { return \"neg\" }\n}");
+    assert_eq!((code, position), ("E3031", (2, 3)));
+    assert_eq!(
+        message,
+        "if/else-if chain in function 'sign' has no final `else`: nothing is returned when none of `n > 0`, `n < 0` hold"
+    );
+    assert_eq!(hint, "Add an `else` branch that returns a value");
+    let (_, message, position, _) = failure("let f = fun(n: Int): Int { if n > 0 { return 1 } }");
+    assert_eq!(position, (1, 28));
+    assert_eq!(
+        message,
+        "if/else-if chain in function '<lambda>' has no final `else`: nothing is returned when `n > 0` does not hold"
+    );
+}
@@ -0,0 +1,63 @@
+// tests/runtime.rs
+//
+// 异步运行时适配层：开启或关闭 `tokio-runtime` feature 时行为相同——
+// block_on 在指定栈大小的线程上驱动 Future，spawn 立即开始执行，sleep 与 spawn_blocking 不阻塞调用方；
+// 不在运行时中（嵌入方用其它执行器）时 spawn 与 sleep 也能工作
+
+use pawc::runtime::{block_on, sleep, spawn, spawn_blocking};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 每层占用约 1 KiB 栈的递归
+fn depth(n: usize) -> usize {
+    let frame = black_box([0u8; 1024]);
+    if n == 0 {
+        frame[0] as usize
+    } else {
+        1 + depth(black_box(n - 1))
+    }
+}
+
+#[test]
+fn block_on_uses_the_requested_stack() {
+    let result = block_on(|| async { depth(16_000) }, 64 << 20).unwrap();
+    assert_eq!(result, 16_000);
+}
+
+#[test]
+fn spawned_tasks_start_right_away() {
+    let result = block_on(
+        || async {
+            let started = Arc::new(AtomicBool::new(false));
+            let flag = started.clone();
+            let task = spawn(async move {
+                flag.store(true, Ordering::SeqCst);
+                21
+            });
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !started.load(Ordering::SeqCst) && Instant::now() < deadline {
+                sleep(Duration::from_millis(5)).await;
+            }
+            // 还没有 await 任务，它已经开始执行
+            assert!(started.load(Ordering::SeqCst));
+            let doubled = spawn_blocking(|| 2).await.unwrap();
+            task.await.unwrap() * doubled
+        },
+        1 << 20,
+    )
+    .unwrap();
+    assert_eq!(result, 42);
+}
+
+#[test]
+fn sleep_and_spawn_work_outside_the_runtime() {
+    let start = Instant::now();
+    let value = futures::executor::block_on(async {
+        sleep(Duration::from_millis(20)).await;
+        spawn(async { 7 }).await.unwrap()
+    });
+    assert_eq!(value, 7);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
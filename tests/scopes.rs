@@ -0,0 +1,86 @@
+// tests/scopes.rs
+//
+// 词法作用域：内层作用域可以读写外层变量，赋值写回定义它的那一层，
+// 内层 let 只遮蔽外层同名变量，离开作用域后外层的值不变
+
+mod common;
+
+use pawc::Value;
+use common::{eval, failure};
+
+#[test]
+fn function_assigns_outer_variable() {
+    let v = eval(
+        r#"
+let count: Int = 0
+fun bump(): Void {
+  count = count + 1
+}
+bump()
+bump()
+count
+"#,
+    );
+    assert_eq!(v, Value::Int(2));
+}
+
+#[test]
+fn closure_keeps_its_own_counter() {
+    let v = eval(
+        r#"
+fun counter(): fun(): Int {
+  let n: Int = 0
+  return fun(): Int {
+    n = n + 1
+    return n
+  }
+}
+let a: fun(): Int = counter()
+let b: fun(): Int = counter()
+a()
+a()
+b()
+a() * 10 + b()
+"#,
+    );
+    assert_eq!(v, Value::Int(32));
+}
+
+#[test]
+fn block_let_shadows_without_leaking() {
+    let v = eval(
+        r#"
+let x: Int = 1
+let seen: Int = 0
+if x == 2 { } else {
+  let x: Int = 10
+  seen = x
+}
+x * 100 + seen
+"#,
+    );
+    assert_eq!(v, Value::Int(110));
+}
+
+#[test]
+fn async_block_assigns_outer_variable() {
+    let v = eval(
+        r#"
+let total: Int = 1
+let f: Future<Int> = async {
+  total = total + 41
+  return total
+}
+await f
+total
+"#,
+    );
+    assert_eq!(v, Value::Int(42));
+}
+
+#[test]
+fn assigning_an_undeclared_variable_is_rejected_by_the_checker() {
+    let (code, message, position, _) = failure("let a: Int = 1\nif a > 0 {\n  total = a\n}");
+    assert_eq!((code, position), ("E4001", (3, 3)));
+    assert!(message.contains("total"), "{}", message);
+}
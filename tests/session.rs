@@ -0,0 +1,58 @@
+// tests/session.rs
+//
+// Session 的增量检查与执行：检查器作用域与运行时环境在多次 eval 之间保持一致
+
+use pawc::{Session, Value};
+
+#[test]
+fn definitions_persist_between_evals() {
+    let mut session = Session::new("<test>");
+    session.eval("let base: Int = 10").unwrap();
+    session.eval("fun add(x: Int): Int { return x + base }").unwrap();
+    session.eval("record P { x: Int }").unwrap();
+    let v = session.eval("add(P { x: 5 }.x)").unwrap();
+    assert_eq!(v, Value::Int(15));
+}
+
+#[test]
+fn rejected_input_leaves_no_trace() {
+    let mut session = Session::new("<test>");
+    let err = session.eval("let y: Int = \"s\"").unwrap_err();
+    assert_eq!(err.code(), "E3003");
+    // 检查失败的定义既不在检查器作用域，也不在运行时环境中
+    assert_eq!(session.eval("y").unwrap_err().code(), "E4001");
+    assert_eq!(session.get("y"), None);
+    // 之后可以用正确的类型重新定义
+    session.eval("let y: String = \"s\"").unwrap();
+    assert_eq!(session.eval("y").unwrap(), Value::String("s"));
+}
+
+#[test]
+fn runtime_error_keeps_earlier_effects() {
+    let mut session = Session::new("<test>");
+    let err = session
+        .eval("let n: Int = 1\nbark \"boom\"")
+        .unwrap_err();
+    assert!(err.to_string().contains("boom"));
+    assert_eq!(session.eval("n + 1").unwrap(), Value::Int(2));
+}
+
+#[test]
+fn eval_async_inside_an_existing_runtime() {
+    let mut session = Session::new("<test>");
+    session.eval("fun square(x: Int): Int { return x * x }").unwrap();
+    let v = futures::executor::block_on(async {
+        session.eval_async("square(7)").await.unwrap();
+        session.call_function_async("square", vec![Value::Int(3)]).await
+    })
+    .unwrap();
+    assert_eq!(v, Value::Int(9));
+}
+
+#[test]
+fn sandbox_rejects_imports() {
+    let mut session = Session::new("main.paw");
+    session.set_sandbox(true);
+    let err = session.eval("import utils").unwrap_err();
+    assert_eq!(err.code(), "E6015");
+}
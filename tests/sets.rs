@@ -0,0 +1,93 @@
+// tests/sets.rs
+//
+// Set<T>：字面量 `Set {…}`、Array.to_set，add / remove 原地修改接收者并返回修改后的集合，集合运算与遍历
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+#[test]
+fn literal_and_to_set_drop_duplicates() {
+    let v = eval_string(
+        r#"
+let a: Set<Int> = Set {3, 1, 3}
+let b: Set<String> = ["x", "y", "x"].to_set()
+"${a.length()} ${b.length()} ${a.contains(3)} ${a.contains(2)}"
+"#,
+    );
+    assert_eq!(v, "2 2 true false");
+}
+
+#[test]
+fn add_and_remove_return_updated_sets() {
+    let v = eval_string(
+        r#"
+let seen: Set<String> = Set {}
+let before: Set<String> = seen
+loop w in ["b", "a", "b", "c", "a"] { seen = seen.add(w) }
+seen = seen.remove("c").remove("zzz")
+"${seen.to_array().sort()} ${before.length()}"
+"#,
+    );
+    assert_eq!(v, "[a, b] 0");
+}
+
+#[test]
+fn add_and_remove_modify_the_receiver() {
+    let v = eval_string(
+        r#"
+let s: Set<Int> = Set {1, 2}
+let before: Set<Int> = s
+s.add(4)
+s.remove(1)
+record Cat { tags: Set<String> }
+let c = Cat { tags: Set {"calm"} }
+c.tags.add("fluffy")
+let groups: Array<Set<Int>> = [Set {1}]
+groups[0].add(2)
+"${s.to_array()} ${before.to_array()} ${c.tags.to_array()} ${groups[0].to_array()} ${Set {7}.add(8).to_array()}"
+"#,
+    );
+    assert_eq!(v, "[2, 4] [1, 2] [calm, fluffy] [1, 2] [7, 8]");
+}
+
+#[test]
+fn union_intersection_and_difference() {
+    let v = eval_string(
+        r#"
+let a: Set<Int> = Set {1, 2, 3}
+let b: Set<Int> = Set {2, 3, 4}
+"${a.union(b).to_array().sort()} ${a.intersection(b).to_array().sort()} ${a.difference(b).to_array()}"
+"#,
+    );
+    assert_eq!(v, "[1, 2, 3, 4] [2, 3] [1]");
+    assert_eq!(
+        PawScript::new().eval_str("Set {1, 2} == Set {2, 1}").unwrap(),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+fn loops_visit_every_element() {
+    let v = eval_string(
+        r#"
+let total: Int = 0
+loop x in Set {5, 7, 5} { total = total + x }
+total
+"#,
+    );
+    assert_eq!(v, "12");
+}
+
+#[test]
+fn element_types_are_checked() {
+    assert_eq!(error_code("say Set {1, \"a\"}"), "E3010");
+    assert_eq!(error_code("say Set {1.5}"), "E3026");
+    assert_eq!(error_code("let s: Set<Array<Int>> = Set {}"), "E3026");
+    assert_eq!(error_code("say [[1]].to_set()"), "E3021");
+    assert_eq!(error_code("let s: Set<Int> = Set {1}\nsay s.add(\"x\")"), "E3025");
+    assert_eq!(error_code("let s: Set<Int> = Set {1}\nsay s.union(Set {\"x\"})"), "E3025");
+    assert_eq!(error_code("let s: Set<Int> = Set {1}\nsay s.push(2)"), "E3021");
+    assert_eq!(error_code("let s: Set<Int> = Set {\"a\"}"), "E3003");
+}
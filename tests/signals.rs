@@ -0,0 +1,126 @@
+// tests/signals.rs
+//
+// 标准库模块 `signal`：在子进程中运行脚本，等它输出 "ready" 后向其发送信号
+
+use pawc::{PawScript, Session};
+
+#[cfg(unix)]
+mod process {
+    use std::io::{BufRead, BufReader, Read};
+    use std::process::{Child, Command, Stdio};
+
+    /// 启动脚本并等到它输出第一行 "ready"
+    pub fn start(name: &str, src: &str) -> (Child, BufReader<std::process::ChildStdout>) {
+        let path = std::env::temp_dir().join(format!("pawc-signal-{}-{}.paw", name, std::process::id()));
+        std::fs::write(&path, src).unwrap();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_pawc"))
+            .arg(&path)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut out = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        out.read_line(&mut line).unwrap();
+        assert_eq!(line, "ready\n");
+        (child, out)
+    }
+
+    pub fn send(child: &Child, signal: &str) {
+        let status = Command::new("kill")
+            .args([signal, &child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    pub fn rest(out: &mut BufReader<std::process::ChildStdout>) -> String {
+        let mut s = String::new();
+        out.read_to_string(&mut s).unwrap();
+        s
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn interrupt_handler_lets_a_hot_loop_finish() {
+    let (mut child, mut out) = process::start(
+        "int",
+        r#"
+import signal
+let running: Bool = true
+let n: Int = 0
+signal.on_interrupt(fun(): Void { running = false })
+say "ready"
+loop running { n = n + 1 }
+say "cleaned up"
+"#,
+    );
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    process::send(&child, "-INT");
+    assert!(child.wait().unwrap().success());
+    assert_eq!(process::rest(&mut out), "cleaned up\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn terminate_handler_runs_inside_async_tasks() {
+    let (mut child, mut out) = process::start(
+        "term",
+        r#"
+import signal
+let stop: Bool = false
+signal.on_terminate(fun(): Void { stop = true })
+let worker: Future<Int> = async {
+  let rounds: Int = 0
+  loop !stop {
+    sleep(5)
+    rounds = rounds + 1
+  }
+  return rounds
+}
+say "ready"
+let rounds: Int = await worker
+say "stopped"
+"#,
+    );
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    process::send(&child, "-TERM");
+    assert!(child.wait().unwrap().success());
+    assert_eq!(process::rest(&mut out), "stopped\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn a_second_signal_ends_a_stuck_script() {
+    let (mut child, _out) = process::start(
+        "stuck",
+        r#"
+import signal
+signal.on_interrupt(fun(): Void { say "never reached" })
+say "ready"
+sleep(60000)
+"#,
+    );
+    // 等脚本进入 sleep，此后处理函数没有机会运行
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    process::send(&child, "-INT");
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    process::send(&child, "-INT");
+    assert_eq!(child.wait().unwrap().code(), Some(130));
+}
+
+#[test]
+fn handlers_must_be_functions() {
+    let err = PawScript::new()
+        .eval_str("import signal\nsignal.on_interrupt(1)")
+        .unwrap_err();
+    assert_eq!(err.code(), "E3033");
+}
+
+#[test]
+fn sandbox_rejects_signal() {
+    let mut session = Session::new("main.paw");
+    session.set_sandbox(true);
+    assert_eq!(session.eval("import signal").unwrap_err().code(), "E6015");
+}
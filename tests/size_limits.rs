@@ -0,0 +1,88 @@
+// tests/size_limits.rs
+//
+// 沙箱模式下字符串与容器的大小上限：超出时报 E6020，非沙箱模式不检查
+
+use pawc::{PawScript, SizeLimits};
+
+const LIMITS: SizeLimits = SizeLimits {
+    max_string_bytes: 64,
+    max_collection_len: 8,
+};
+
+fn sandboxed() -> PawScript {
+    let mut paw = PawScript::new();
+    paw.set_sandbox(true);
+    paw.set_size_limits(LIMITS);
+    paw
+}
+
+fn eval_string(src: &str) -> String {
+    sandboxed()
+        .eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed:\n{}\n{}", src, e))
+        .to_string()
+}
+
+fn error_code(src: &str) -> &'static str {
+    sandboxed()
+        .eval_str(src)
+        .err()
+        .unwrap_or_else(|| panic!("script should fail:\n{}", src))
+        .code()
+}
+
+#[test]
+fn values_within_the_limits_are_fine() {
+    let v = eval_string(
+        r#"
+let xs: Array<Int> = [1, 2, 3, 4, 5, 6, 7]
+xs.push(8)
+let s: String = "paw"
+loop i in 0..4 { s = s + s }
+"${xs.length()} ${s.length()} ${range(8).length()}"
+"#,
+    );
+    assert_eq!(v, "8 48 8");
+}
+
+#[test]
+fn growing_collections_past_the_cap_fails() {
+    assert_eq!(error_code("[1, 2, 3, 4, 5, 6, 7, 8, 9]"), "E6020");
+    assert_eq!(error_code("let xs: Array<Int> = range(8)\nxs.push(9)"), "E6020");
+    assert_eq!(error_code("let r: Array<Array<Int>> = [range(8)]\nr[0].push(9)"), "E6020");
+    assert_eq!(error_code("range(1000000000)"), "E6020");
+    assert_eq!(error_code("repeat(0, 100)"), "E6020");
+    assert_eq!(
+        error_code("let s: Set<Int> = range(8).to_set()\nlet t: Set<Int> = s.add(9)"),
+        "E6020"
+    );
+    // 已有的键不增加元素数
+    let v = eval_string("let s: Set<Int> = range(8).to_set()\ns.add(3).length()");
+    assert_eq!(v, "8");
+}
+
+#[test]
+fn concatenation_past_the_cap_fails() {
+    let doubling = "let s: String = \"paw\"\nloop i in 0..10 { s = s + s }";
+    assert_eq!(error_code(doubling), "E6020");
+    let mut paw = sandboxed();
+    paw.set_vm(false);
+    assert_eq!(paw.eval_str(doubling).unwrap_err().code(), "E6020");
+
+    assert_eq!(error_code("let s: String = \"a\"\nloop i in 0..7 { s = \"${s}${s}\" }"), "E6020");
+    assert_eq!(error_code("repeat(\"paw-paw-paw\", 8).join(\", \")"), "E6020");
+    assert_eq!(
+        error_code("import string\nlet sb: StringBuilder = string.builder()\nloop i in 0..100 { sb.append(i) }"),
+        "E6020"
+    );
+}
+
+#[test]
+fn limits_only_apply_in_sandbox_mode() {
+    let mut paw = PawScript::new();
+    paw.set_size_limits(LIMITS);
+    let v = paw
+        .eval_str("let s: String = \"paw\"\nloop i in 0..10 { s = s + s }\n\"${s.length()} ${range(100).length()}\"")
+        .unwrap();
+    assert_eq!(v.to_string(), "3072 100");
+}
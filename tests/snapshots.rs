@@ -0,0 +1,126 @@
+// tests/snapshots.rs
+//
+// `pawc test` 与快照断言：`test_*` 函数逐个在新会话中运行，`assert_snapshot` 第一次写入
+// `__snapshots__` 中的快照，之后与之比较，`--update-snapshots` 覆盖不符的快照
+
+mod common;
+
+use pawc::PawScript;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use common::{error_code, write_files};
+
+fn pawc_test(dir: &Path, flags: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .current_dir(dir)
+        .arg("test")
+        .args(flags)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+const REPORT: &str = r#"
+let cats: Int = 3
+fun report(n: Int): String {
+  let out: String = ""
+  loop i in 0..n { out = out + "row ${i}\n" }
+  return out
+}
+fun test_text(): Void { assert_snapshot("text", report(cats)) }
+fun test_value(): Void { assert_snapshot("value", {"cats": [cats], "ids": [1, 2]}) }
+"#;
+
+#[test]
+fn snapshots_are_written_then_compared() {
+    let dir = write_files("snapshots", "compare", &[("tests/report_test.paw", REPORT)]);
+    let out = pawc_test(&dir, &[]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("ok    ./tests/report_test.paw::test_text"), "{}", stdout);
+    assert!(stdout.contains("Tested 1 file(s): 2 passed, 0 failed"), "{}", stdout);
+
+    // 字符串按原样保存，其它值保存为 inspect 文本
+    let snapshots = dir.join("tests").join("__snapshots__");
+    let text = fs::read_to_string(snapshots.join("report_test.text.snap")).unwrap();
+    assert_eq!(text, "row 0\nrow 1\nrow 2\n");
+    let value = fs::read_to_string(snapshots.join("report_test.value.snap")).unwrap();
+    assert!(value.starts_with("Map(2) {\n  String \"cats\" => Array(1) [\n    Int 3,"), "{}", value);
+
+    // 值改变后比较失败并指出第一处不同的行，快照保持不变
+    fs::write(dir.join("tests/report_test.paw"), REPORT.replace("cats: Int = 3", "cats: Int = 4")).unwrap();
+    let out = pawc_test(&dir, &["tests"]);
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("FAIL  tests/report_test.paw::test_text"), "{}", stdout);
+    assert!(stdout.contains("0 passed, 2 failed"), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("[E6025]") && stderr.contains("Snapshot 'text' does not match"), "{}", stderr);
+    assert!(stderr.contains("Line 4: expected <end of value>, found `row 3`"), "{}", stderr);
+    assert_eq!(fs::read_to_string(snapshots.join("report_test.text.snap")).unwrap(), text);
+
+    // --update-snapshots 接受新值
+    assert!(pawc_test(&dir, &["--update-snapshots"]).status.success());
+    assert!(pawc_test(&dir, &[]).status.success());
+    let text = fs::read_to_string(snapshots.join("report_test.text.snap")).unwrap();
+    assert_eq!(text, "row 0\nrow 1\nrow 2\nrow 3\n");
+}
+
+#[test]
+fn tests_run_in_order_and_in_isolation() {
+    let dir = write_files(
+        "snapshots",
+        "isolation",
+        &[
+            (
+                "tests/a_test.paw",
+                "let n: Int = 1\nfun test_bump(): Void { n = n + 1\n assert(n == 2) }\n\
+                 fun test_fresh(): Void { assert(n == 1, \"n leaked\") }\n\
+                 fun test_bark(): Void { bark \"boom\" }\nfun test_args(k: Int): Void { bark \"not a test\" }\n",
+            ),
+            ("helper.paw", "fun test_ignored(): Void { bark \"not a test file\" }\n"),
+            ("tests/nested/b_test.paw", "fun test_one(): Void { assert(false, \"expected failure\") }\n"),
+        ],
+    );
+    let out = pawc_test(&dir, &["tests"]);
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "ok    tests/a_test.paw::test_bump",
+            "ok    tests/a_test.paw::test_fresh",
+            "FAIL  tests/a_test.paw::test_bark",
+            "FAIL  tests/nested/b_test.paw::test_one",
+            "🐾 Tested 2 file(s): 2 passed, 2 failed",
+        ]
+    );
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("boom") && stderr.contains("Assertion failed: expected failure"), "{}", stderr);
+}
+
+#[test]
+fn files_that_do_not_load_fail() {
+    let dir = write_files("snapshots", "broken", &[("tests/broken_test.paw", "let n: Int = \"oops\"\nfun test_x(): Void { }\n")]);
+    let out = pawc_test(&dir, &["tests/broken_test.paw"]);
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("FAIL  tests/broken_test.paw\n"), "{}", stdout);
+    assert!(stdout.contains("0 passed, 1 failed"), "{}", stdout);
+}
+
+#[test]
+fn assertions_are_builtins() {
+    assert_eq!(PawScript::new().eval_str("assert(1 < 2)\n1").unwrap().to_string(), "1");
+    assert_eq!(error_code("assert(1 > 2, \"nope\")"), "E6024");
+    assert_eq!(error_code("assert(1)"), "E3033");
+    assert_eq!(error_code("assert(true, 1)"), "E3033");
+    assert_eq!(error_code("assert_snapshot(1, 2)"), "E3033");
+    assert_eq!(error_code("assert_snapshot(\"x\")"), "E3032");
+    assert_eq!(error_code("assert_snapshot(\"../escape\", 1)"), "E6013");
+    let mut sandboxed = PawScript::new();
+    sandboxed.set_sandbox(true);
+    assert_eq!(sandboxed.eval_str("assert_snapshot(\"x\", 1)").unwrap_err().code(), "E6015");
+}
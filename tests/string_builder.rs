@@ -0,0 +1,63 @@
+// tests/string_builder.rs
+//
+// `import string` 与 `string.builder()`：原地追加的可变字符串缓冲区
+
+mod common;
+
+use common::{error_code, eval_string};
+
+#[test]
+fn appends_in_place() {
+    let v = eval_string(
+        r#"
+import string
+let sb: StringBuilder = string.builder()
+loop i in 0..5 {
+  sb.append(i).append(",")
+}
+sb.append('!').append(1.5).append([1, 2])
+"${sb.to_string()}|${sb.length()}"
+"#,
+    );
+    assert_eq!(v, "0,1,2,3,4,!1.5[1, 2]|20");
+}
+
+#[test]
+fn builders_are_shared_not_copied() {
+    let v = eval_string(
+        r#"
+import string
+fun fill(out: StringBuilder, n: Int) {
+  loop i in 0..n { out.append("paw") }
+}
+let sb: StringBuilder = string.builder()
+let alias: StringBuilder = sb
+fill(alias, 2)
+let before: String = sb.to_string()
+sb.clear().append(sb.length())
+"${before} ${sb} ${sb == alias} ${sb == string.builder()} ${sb is StringBuilder}"
+"#,
+    );
+    assert_eq!(v, "pawpaw 0 true false true");
+}
+
+#[test]
+fn large_concatenation_stays_fast() {
+    let v = eval_string(
+        r#"
+import string
+let sb: StringBuilder = string.builder()
+loop i in 0..200000 { sb.append("x") }
+sb.length()
+"#,
+    );
+    assert_eq!(v, "200000");
+}
+
+#[test]
+fn methods_are_checked() {
+    assert_eq!(error_code("import string\nstring.builder().push(1)"), "E3021");
+    assert_eq!(error_code("import string\nstring.builder().append()"), "E3024");
+    assert_eq!(error_code("import string\nlet s: String = string.builder()"), "E3003");
+    assert_eq!(error_code("import string\nstring.builder(1)"), "E3032");
+}
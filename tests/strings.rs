@@ -0,0 +1,63 @@
+// tests/strings.rs
+//
+// String 的 split / replace / substring、to_int / to_double 与 Array<String>.join
+
+mod common;
+
+use pawc::Value;
+use common::{error_code, eval};
+
+#[test]
+fn split_then_join_round_trips() {
+    let v = eval(
+        r#"
+let parts: Array<String> = "a,b,,c".split(",")
+"${parts.length()}|" + parts.join("-")
+"#,
+    );
+    assert_eq!(v.to_string(), "4|a-b--c");
+}
+
+#[test]
+fn replace_and_substring() {
+    assert_eq!(eval(r#""cat hat".replace("at", "og")"#).to_string(), "cog hog");
+    // 下标按字符计算，多字节字符也只算一个
+    assert_eq!(eval(r#""héllo wörld".substring(1, 5)"#).to_string(), "éllo");
+    assert_eq!(eval(r#""abc".substring(3, 3)"#).to_string(), "");
+}
+
+#[test]
+fn substring_out_of_range_is_a_runtime_error() {
+    assert_eq!(error_code(r#"say "abc".substring(1, 4)"#), "E6010");
+    assert_eq!(error_code(r#"say "abc".substring(2, 1)"#), "E6010");
+}
+
+#[test]
+fn signatures_are_checked() {
+    assert_eq!(error_code(r#"say "abc".split(1)"#), "E3025");
+    assert_eq!(error_code(r#"say "abc".replace("a")"#), "E3024");
+    assert_eq!(error_code(r#"say "abc".substring("a", 2)"#), "E3025");
+    assert_eq!(error_code(r#"let n: Int = "a b".split(" ")"#), "E3003");
+    assert_eq!(error_code("say [1, 2].join(\",\")"), "E3021");
+}
+
+#[test]
+fn number_parsing_ignores_locale() {
+    assert_eq!(eval(r#"" -42 ".to_int()"#), Value::Int(-42));
+    assert_eq!(eval(r#""2.5e3".to_double()"#), Value::Double(2500.0));
+    assert_eq!(error_code(r#"say "1,5".to_double()"#), "E6016");
+    assert_eq!(error_code(r#"say "inf".to_double()"#), "E6016");
+    assert_eq!(error_code(r#"say "12abc".to_int()"#), "E6016");
+    assert_eq!(error_code(r#"say "99999999999".to_int()"#), "E6016");
+}
+
+#[test]
+fn separators_are_only_skipped_when_asked_for() {
+    assert_eq!(error_code(r#"say "1,000".to_int()"#), "E6016");
+    assert_eq!(eval(r#""1,000,000".to_int(",")"#), Value::Int(1_000_000));
+    assert_eq!(eval(r#""1 234.5".to_double(" ")"#), Value::Double(1234.5));
+    // 分隔符必须夹在两个数字之间
+    assert_eq!(error_code(r#"say ",100".to_int(",")"#), "E6016");
+    assert_eq!(error_code(r#"say "1,,0".to_int(",")"#), "E6016");
+    assert_eq!(error_code(r#"say "1".to_int(1)"#), "E3025");
+}
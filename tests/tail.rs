@@ -0,0 +1,69 @@
+// tests/tail.rs
+//
+// `tail`：立即在后台开始执行 Future，之后可以 await 它的结果
+
+mod common;
+
+use pawc::Value;
+use common::{error_code, eval};
+
+#[test]
+fn tail_starts_before_await() {
+    // 循环中调用函数，使其由树遍历解释器执行，每轮都重新读取 flag
+    let v = eval(
+        r#"
+fun next(i: Int): Int { return i + 1 }
+let flag: Bool = false
+let f: Future<Void> = tail async { flag = true }
+let i: Int = 0
+loop !flag && i < 5000000 { i = next(i) }
+let seen: Bool = flag
+await f
+seen
+"#,
+    );
+    assert_eq!(v, Value::Bool(true));
+}
+
+#[test]
+fn tailed_calls_can_be_awaited_in_any_order() {
+    let v = eval(
+        r#"
+async fun work(name: String, n: Int): String {
+  let i: Int = 0
+  loop i < n { i = i + 1 }
+  return name
+}
+let a: Future<String> = tail work("a", 20000)
+let b: Future<String> = tail work("b", 10)
+let first: String = await b
+first + (await a) + (await a)
+"#,
+    );
+    assert_eq!(v.to_string(), "baa");
+}
+
+#[test]
+fn errors_surface_on_await() {
+    let v = eval(
+        r#"
+let f: Future<Void> = tail async { bark "boom" }
+let got: String = ""
+sniff { await f } snatch (e) { got = e }
+got
+"#,
+    );
+    assert_eq!(v, Value::String("boom"));
+}
+
+#[test]
+fn unawaited_errors_surface_at_the_end() {
+    let code = error_code("let f: Future<Void> = tail async { bark \"lost\" }");
+    assert_eq!(code, "E6001");
+}
+
+#[test]
+fn tail_requires_a_future() {
+    let code = error_code("fun f(): Int { return 1 }\nlet x: Future<Int> = tail f()");
+    assert_eq!(code, "E3037");
+}
@@ -0,0 +1,82 @@
+// tests/task_groups.rs
+//
+// `task_group { spawn ... }`：代码块在所有子任务结束后才结束；`spawn` 只能出现在 task_group 中；
+// 一个任务失败时取消其余任务，单个错误原样抛出，多个错误合并为 E6009
+
+mod common;
+
+use common::{error_code, eval_string, pawc, script};
+
+#[test]
+fn the_group_waits_for_every_task() {
+    let path = script(
+        "task-groups",
+        "wait",
+        r#"async fun job(n: Int): Int {
+  sleep(n * 10)
+  say "job ${n}"
+  return n
+}
+task_group {
+  spawn job(2)
+  spawn job(1)
+}
+say "both finished"
+"#,
+    );
+    let out = pawc(&[], &path);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.pop(), Some("both finished"));
+    lines.sort();
+    assert_eq!(lines, ["job 1", "job 2"]);
+}
+
+#[test]
+fn spawn_needs_a_group() {
+    assert_eq!(error_code("spawn 1"), "E3027");
+    assert_eq!(error_code("async fun f(): Int { return 1 }\nfun g(): Void { spawn f() }"), "E3027");
+}
+
+#[test]
+fn failures_cancel_siblings_and_are_rethrown() {
+    let path = script(
+        "task-groups",
+        "cancel",
+        r#"async fun bad(): Int { bark "only" }
+async fun slow(): Int {
+  sleep(200)
+  say "never"
+  return 1
+}
+task_group {
+  spawn bad()
+  spawn slow()
+}
+"#,
+    );
+    let out = pawc(&[], &path);
+    assert!(!out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("[E6001]") && stderr.contains("only"), "{}", stderr);
+    // 可以被 snatch 捕获；两个任务都来得及失败时合并为一个 E6009，否则后失败的任务已被取消
+    let v = eval_string(
+        r#"async fun bad(n: Int): Int { bark "fail ${n}" }
+let out: String = ""
+sniff {
+  task_group {
+    spawn bad(1)
+    spawn bad(2)
+  }
+} snatch (e) { out = e }
+out
+"#,
+    );
+    if v.starts_with("2 tasks in task_group failed") {
+        assert!(v.contains("fail 1") && v.contains("fail 2"), "{}", v);
+    } else {
+        assert!(v == "fail 1" || v == "fail 2", "{}", v);
+    }
+}
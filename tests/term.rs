@@ -0,0 +1,75 @@
+// tests/term.rs
+//
+// 标准库模块 `term`：通过命令行运行脚本，标准输入输出都是管道，
+// 因此 `is_tty` 为 false，`read_key` 逐个字符读取输入
+
+use pawc::{PawScript, Session};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(name: &str, src: &str, input: &str) -> Output {
+    let path = std::env::temp_dir().join(format!("pawc-term-{}-{}.paw", name, std::process::id()));
+    std::fs::write(&path, src).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pawc"))
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn styles_and_cursor_control_emit_ansi_sequences() {
+    let out = run(
+        "ansi",
+        r#"
+import term
+say term.is_tty()
+say term.color("hi", "red") + term.bold("!")
+term.clear()
+term.move_to(2, 4)
+"#,
+        "",
+    );
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "false\n\x1b[31mhi\x1b[0m\x1b[1m!\x1b[0m\n\x1b[2J\x1b[H\x1b[3;5H"
+    );
+}
+
+#[test]
+fn read_key_falls_back_to_piped_input() {
+    let src = r#"
+import term
+let keys: Array<String> = []
+loop i in 0..3 { keys.push(term.read_key()) }
+say keys
+sniff { term.read_key() } snatch (e) { say "done" }
+"#;
+    let out = run("keys", src, "aé\n");
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "[a, é, enter]\ndone\n");
+}
+
+#[test]
+fn arguments_are_checked() {
+    let err = PawScript::new()
+        .eval_str("import term\nterm.color(\"x\", \"mauve\")")
+        .unwrap_err();
+    assert_eq!(err.code(), "E6012");
+    let err = PawScript::new()
+        .eval_str("import term\nterm.move_to(\"1\", 2)")
+        .unwrap_err();
+    assert_eq!(err.code(), "E3033");
+}
+
+#[test]
+fn sandbox_rejects_term() {
+    let mut session = Session::new("main.paw");
+    session.set_sandbox(true);
+    assert_eq!(session.eval("import term").unwrap_err().code(), "E6015");
+}
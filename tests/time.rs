@@ -0,0 +1,61 @@
+// tests/time.rs
+//
+// `sleep(ms)` 暂停当前任务而不阻塞其它任务；标准库模块 `time` 无需 .paw 文件即可导入
+
+mod common;
+
+use pawc::{Value, ValueInner};
+use common::{error_code, eval};
+
+#[test]
+fn sleep_pauses_sync_functions() {
+    let v = eval(
+        r#"
+import time
+fun pause(ms: Int): Void { sleep(ms) }
+let start: Long = time.now_millis()
+pause(60)
+time.now_millis() - start
+"#,
+    );
+    let ValueInner::Long(elapsed) = *v.0 else {
+        panic!("expected Long, found {:?}", v);
+    };
+    assert!(elapsed >= 60, "slept only {} ms", elapsed);
+}
+
+#[test]
+fn sleeping_tasks_overlap() {
+    let v = eval(
+        r#"
+import time
+async fun nap(ms: Int): Int {
+  sleep(ms)
+  return ms
+}
+let start: Long = time.now_millis()
+let a: Future<Int> = tail nap(200)
+let b: Future<Int> = tail nap(200)
+let total: Int = (await a) + (await b)
+let elapsed: Long = time.now_millis() - start
+total == 400 && elapsed < 390L
+"#,
+    );
+    assert_eq!(v, Value::Bool(true));
+}
+
+#[test]
+fn now_millis_is_typed_as_long() {
+    let code = error_code("import time\nlet t: String = time.now_millis()");
+    assert_eq!(code, "E3003");
+    let code = error_code("import time\nlet t: Long = time.now_millis(1)");
+    assert_eq!(code, "E3032");
+    let code = error_code("import time\ntime.later()");
+    assert_eq!(code, "E3021");
+}
+
+#[test]
+fn sleep_checks_its_argument() {
+    assert_eq!(error_code("sleep(\"1\")"), "E3033");
+    assert_eq!(error_code("sleep(-1)"), "E6013");
+}
@@ -0,0 +1,71 @@
+// tests/top_level_await.rs
+//
+// 顶层的 `await` 可以出现在任何语句位置；调用 async 函数立即返回 Future，
+// 顶层结束时按创建顺序等待没有被 await 的 Future，被导入模块的顶层任务在 import 返回前完成
+
+mod common;
+
+use common::{eval_string, pawc, write_files};
+
+const TWICE: &str = "async fun twice(n: Int): Int {\n  say \"run ${n}\"\n  return n * 2\n}\n";
+
+#[test]
+fn await_in_any_statement_position() {
+    let dir = write_files(
+        "await",
+        "positions",
+        &[(
+            "main.paw",
+            &format!(
+                "{}let a: Int = await twice(1)\nsay await twice(2)\nif await twice(3) == 6 {{ say \"cond\" }}\n\
+                 fun plus(x: Int, y: Int): Int {{ return x + y }}\nsay plus(await twice(4), a)\nawait twice(5)\n",
+                TWICE
+            ),
+        )],
+    );
+    let out = pawc(&[], &dir.join("main.paw"));
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "run 1\nrun 2\n4\nrun 3\ncond\nrun 4\n10\nrun 5\n"
+    );
+    assert_eq!(eval_string(&format!("{}let f = twice(21)\nawait f", TWICE)), "42");
+}
+
+#[test]
+fn unawaited_futures_finish_with_the_top_level() {
+    let dir = write_files(
+        "await",
+        "unawaited",
+        &[
+            (
+                "main.paw",
+                &format!("{}let f = twice(6)\nsay \"made\"\ntwice(7)\nimport worker\nsay \"after import\"\n", TWICE),
+            ),
+            ("worker.paw", "async fun job(): Int {\n  say \"worker job\"\n  return 1\n}\njob()\nsay \"worker top\"\n"),
+        ],
+    );
+    let out = pawc(&[], &dir.join("main.paw"));
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "made\nworker top\nworker job\nafter import\nrun 6\nrun 7\n"
+    );
+}
+
+#[test]
+fn module_task_errors_fail_the_import() {
+    let dir = write_files(
+        "await",
+        "module-error",
+        &[
+            ("main.paw", "say \"a\"\nimport bad\nsay \"b\"\n"),
+            ("bad.paw", "async fun boom(): Int { bark \"bad\" }\nboom()\nsay \"top\"\n"),
+        ],
+    );
+    let out = pawc(&[], &dir.join("main.paw"));
+    assert!(!out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "a\ntop\n");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("[E6001]") && stderr.contains("bad"), "{}", stderr);
+}
@@ -0,0 +1,81 @@
+// tests/tuples.rs
+//
+// 元组：类型 `(A, B)`、字面量、`let (a, b)` 解构，以及按位置的类型检查
+
+mod common;
+
+use pawc::{PawScript, Value};
+use common::{error_code, eval_string};
+
+#[test]
+fn functions_return_several_values() {
+    let v = eval_string(
+        r#"
+fun div_mod(a: Int, b: Int): (Int, Int) {
+  return (a / b, a % b)
+}
+let (q, r) = div_mod(17, 5)
+let (_, rest) = div_mod(9, 4)
+"${q} ${r} ${rest} ${div_mod(7, 7)}"
+"#,
+    );
+    assert_eq!(v, "3 2 1 (1, 0)");
+}
+
+#[test]
+fn elements_keep_their_own_types() {
+    let v = eval_string(
+        r#"
+let pair: (String, Int?) = ("cat", nopaw)
+let name: String = pair[0]
+let (_, age): (String, Int?) = pair
+let ps: Array<(Int, Bool)> = [(1, true), (2, false)]
+let total: Int = 0
+loop p in ps { if p[1] { total = total + p[0] } }
+"${name} ${age} ${total} ${(1 + 2) * 3}"
+"#,
+    );
+    assert_eq!(v, "cat Nopaw 1 9");
+}
+
+#[test]
+fn tuples_compare_and_hash_by_value() {
+    let v = eval_string(
+        r#"
+let grid: Map<(Int, Int), Char> = {(0, 0): 'x'}
+grid = grid.insert((1, 2), 'o')
+let seen: Set<(Int, Int)> = Set {(1, 2), (1, 2)}
+"${grid[(1, 2)]} ${seen.length()} ${(1, "a") == (1, "a")}"
+"#,
+    );
+    assert_eq!(v, "o 1 true");
+}
+
+#[test]
+fn positions_are_type_checked() {
+    assert_eq!(error_code(r#"let t: (Int, String) = (1, 2)"#), "E3003");
+    assert_eq!(error_code("let (a, b) = (1, 2, 3)"), "E3041");
+    assert_eq!(error_code("let (a, b) = 1"), "E3041");
+    assert_eq!(error_code("let t: (Int, Int) = (1, 2)\nsay t[2]"), "E3041");
+    assert_eq!(error_code("let t: (Int, Int) = (1, 2)\nlet i: Int = 0\nsay t[i]"), "E3041");
+    assert_eq!(error_code("let t: (Int, Int) = (1, 2)\nt[0] = 3"), "E3041");
+    assert_eq!(error_code("let (a, b) = (1, \"x\")\nlet c: Int = b"), "E3003");
+    assert_eq!(error_code("let t: (Int) = 1"), "E1001");
+}
+
+#[test]
+fn untyped_values_are_checked_at_runtime() {
+    let mut paw = PawScript::new();
+    let mut m = ahash::AHashMap::new();
+    m.insert(Value::String("pair"), Value::Tuple(vec![Value::Int(1), Value::Int(2)]));
+    m.insert(Value::String("list"), Value::Array(vec![Value::Int(1)]));
+    paw.define("data", Value::Map(m));
+    assert_eq!(
+        paw.eval_str("let (a, b) = data[\"pair\"]\nb").unwrap(),
+        Value::Int(2)
+    );
+    assert_eq!(
+        paw.eval_str("let (c, d) = data[\"list\"]").unwrap_err().code(),
+        "E6017"
+    );
+}
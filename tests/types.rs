@@ -0,0 +1,62 @@
+// tests/types.rs
+//
+// 类型注解在 AST 中保留结构与位置：错误指向出错的那一层类型；Void 结果不能当作值使用
+
+use pawc::{PawError, PawScript};
+
+/// 检查失败时返回 (错误码, 行, 列)
+fn type_error(src: &str) -> (&'static str, usize, usize) {
+    match PawScript::new().eval_str(src) {
+        Err(PawError::Type {
+            code, line, column, ..
+        }) => (code, line, column),
+        other => panic!("expected a type error for:\n{}\ngot {:?}", src, other.map(|_| ())),
+    }
+}
+
+#[test]
+fn unknown_type_points_at_the_inner_name() {
+    let src = "fun f(a: Int,\n      b: Array<Nope>): Int {\n  return a\n}";
+    assert_eq!(type_error(src), ("E3028", 2, 16));
+    assert_eq!(type_error("let m: Map<String, Nope?> = {}"), ("E3028", 1, 20));
+}
+
+#[test]
+fn malformed_generics_are_rejected() {
+    assert_eq!(type_error("let m: Map<Int> = {}"), ("E3028", 1, 8));
+    assert_eq!(type_error("let a: Array<Int, Int> = []"), ("E3028", 1, 8));
+    assert_eq!(type_error("let n: Int<String> = 1"), ("E3028", 1, 8));
+}
+
+#[test]
+fn nested_annotations_still_resolve() {
+    let src = r#"
+record Point { x: Int, y: Int }
+let f: fun(Int): Point? = fun(n: Int): Point? { return Point { x: n, y: n } }
+let grid: Map<String, Array<Point>> = {"row": [Point { x: 1, y: 2 }]}
+grid["row"][0].y
+"#;
+    assert_eq!(PawScript::new().eval_str(src).unwrap().to_string(), "2");
+}
+
+#[test]
+fn ask_only_reads_basic_types() {
+    assert_eq!(type_error("let xs: Array<Int> <- ask \"?\""), ("E3039", 1, 9));
+}
+
+#[test]
+fn void_results_are_not_values() {
+    let prelude = "fun nothing() { }\nfun id(x: Any): Any { return x }\n";
+    let check = |src: &str| type_error(&format!("{}{}", prelude, src));
+    assert_eq!(check("let x: Any = nothing()"), ("E3043", 3, 14));
+    assert_eq!(check("let x: Any = 1\nx = nothing()"), ("E3043", 4, 5));
+    assert_eq!(check("say nothing() == nothing()"), ("E3043", 3, 5));
+    assert_eq!(check("say id(nothing())"), ("E3043", 3, 8));
+    assert_eq!(check("say [1].length() + [nothing()].length()"), ("E3043", 3, 21));
+    assert_eq!(check("let v: Void = 1"), ("E3043", 3, 8));
+    assert_eq!(check("let x: Any = sleep(1)"), ("E3043", 3, 14));
+    assert_eq!(check("say \"got ${nothing()}\""), ("E3043", 3, 12));
+    assert_eq!(check("say \"[${nothing():>4}]\""), ("E3043", 3, 9));
+    // 单独调用仍然可以
+    assert!(PawScript::new().eval_str(&format!("{}nothing()", prelude)).is_ok());
+}
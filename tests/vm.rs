@@ -0,0 +1,195 @@
+// tests/vm.rs
+//
+// 字节码 VM 与树遍历解释器必须给出相同的结果：
+// 每段脚本分别在开启、关闭 VM 时执行并比较返回值与错误码
+
+use pawc::{PawScript, Value};
+
+fn run(vm: bool, host: &[(&str, Value)]) -> PawScript {
+    let mut paw = PawScript::new();
+    paw.set_vm(vm);
+    for (name, value) in host {
+        paw.define(name, value.clone());
+    }
+    paw
+}
+
+/// 静态类型为 Any 的宿主值，类型检查放行，运行时才报错
+fn opaque() -> Value {
+    Value::Native("opaque", |_| Ok(Value::Null()))
+}
+
+/// 两种模式下都成功且结果相同，返回该结果
+fn both(src: &str) -> Value {
+    let on = run(true, &[])
+        .eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed with vm:\n{}\n{}", src, e));
+    let off = run(false, &[])
+        .eval_str(src)
+        .unwrap_or_else(|e| panic!("script failed without vm:\n{}\n{}", src, e));
+    assert_eq!(on, off, "vm and tree-walker disagree:\n{}", src);
+    on
+}
+
+/// 两种模式下都失败且错误码相同，返回错误码
+fn both_fail(src: &str, host: &[(&str, Value)]) -> &'static str {
+    let on = run(true, host).eval_str(src).expect_err("vm should fail");
+    let off = run(false, host).eval_str(src).expect_err("tree-walker should fail");
+    assert_eq!(on.code(), off.code());
+    on.code()
+}
+
+#[test]
+fn range_loop_with_break_and_continue() {
+    let v = both(
+        r#"
+let total: Int = 0
+loop i in 0..100 {
+  if i % 2 == 0 { continue }
+  if i > 50 { break }
+  total = total + i
+}
+total
+"#,
+    );
+    assert_eq!(v, Value::Int(625));
+}
+
+#[test]
+fn while_loop_with_let_and_interpolation() {
+    let v = both(
+        r#"
+let n: Int = 0
+let out: String = ""
+loop n < 4 {
+  n = n + 1
+  let sq: Int = n * n
+  out = out + "${n}:${sq} "
+}
+out
+"#,
+    );
+    assert_eq!(v.to_string(), "1:1 2:4 3:9 4:16 ");
+}
+
+#[test]
+fn array_and_map_iteration() {
+    let v = both(
+        r#"
+let xs: Array<Int> = [3, 4, 5]
+let m: Map<String, Int> = {"a": 1, "b": 2}
+let acc: Int = 0
+loop x in xs { acc = acc + x * xs[0] }
+loop entry in m { acc = acc + entry[1] }
+acc
+"#,
+    );
+    assert_eq!(v, Value::Int(39));
+}
+
+#[test]
+fn nested_loops_and_else_if() {
+    let v = both(
+        r#"
+let s: String = ""
+loop i in 0..3 {
+  let j: Int = 0
+  loop forever {
+    j = j + 1
+    if j > 3 { break } else if j == 2 { continue } else if i == j { s = s + "=" }
+    s = s + "${j}"
+  }
+  s = s + "|"
+}
+s
+"#,
+    );
+    assert_eq!(v.to_string(), "13|=13|13|");
+}
+
+#[test]
+fn return_from_compiled_loop() {
+    let v = both(
+        r#"
+fun first_square_over(limit: Int): Int {
+  loop i in 0..100 {
+    if i * i > limit { return i }
+  }
+  return -1
+}
+first_square_over(50) * 10 + first_square_over(100000)
+"#,
+    );
+    assert_eq!(v, Value::Int(79));
+}
+
+#[test]
+fn non_bool_condition_reports_e3007() {
+    let code = both_fail("loop flag { }", &[("flag", opaque())]);
+    assert_eq!(code, "E3007");
+}
+
+#[test]
+fn non_iterable_reports_e3018() {
+    let code = both_fail("loop x in xs { }", &[("xs", opaque())]);
+    assert_eq!(code, "E3018");
+}
+
+#[test]
+fn division_by_zero_reports_e6014() {
+    let code = both_fail("let d: Int = 3\nloop i in 0..5 { d = d - 1\n say 6 / d }", &[]);
+    assert_eq!(code, "E6014");
+}
+
+#[test]
+fn assignments_survive_runtime_errors() {
+    for vm in [true, false] {
+        let src = r#"
+let n: Int = 0
+loop i in 0..5 {
+  n = n + 1
+  if i == 2 { loop flag { } }
+}
+"#;
+        let mut paw = run(vm, &[("flag", opaque())]);
+        assert_eq!(paw.eval_str(src).unwrap_err().code(), "E3007");
+        assert_eq!(paw.get("n"), Some(Value::Int(3)), "vm = {}", vm);
+    }
+}
+
+#[test]
+fn else_block_scoping() {
+    let v = both(
+        r#"
+let x: Int = 1
+let b: Int = 0
+loop i in 0..4 {
+  if i % 2 == 0 { } else {
+    b = b + x
+    let x: Int = i * 100
+    b = b + x
+    loop j in 0..2 { let x: Int = j }
+    b = b + x
+  }
+}
+b * 10 + x
+"#,
+    );
+    assert_eq!(v, Value::Int(8021));
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    let v = both(
+        r#"
+let xs: Array<Int> = [1, 2]
+let hits: Int = 0
+loop i in 0..4 {
+  if i < 2 && xs[i] > 1 { hits = hits + 10 }
+  if i >= 2 || xs[i] == 1 { hits = hits + 1 }
+}
+hits
+"#,
+    );
+    assert_eq!(v, Value::Int(13));
+}